@@ -1,3 +1,45 @@
+use std::process::Command;
+
+mod build_info;
+
 fn main() {
-  tauri_build::build()
+  tauri_build::build();
+
+  let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+  println!(
+    "cargo:rustc-env=CARGO_BUILD_DATE={}",
+    build_info::build_date(source_date_epoch.as_deref(), chrono::Utc::now())
+  );
+  println!("cargo:rustc-env=CARGO_GIT_SHA={}", git_sha());
+  println!(
+    "cargo:rustc-env=CARGO_TARGET_TRIPLE={}",
+    std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string())
+  );
+
+  println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+  println!("cargo:rerun-if-env-changed=GITHUB_SHA");
+  if std::path::Path::new("../.git/HEAD").exists() {
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+  }
+}
+
+/// Short commit SHA. Shallow CI checkouts without usable history fall back to
+/// `GITHUB_SHA`, then to `"unknown"` when git isn't available at all.
+fn git_sha() -> String {
+  let from_git = Command::new("git")
+    .args(["rev-parse", "--short", "HEAD"])
+    .output()
+    .ok()
+    .filter(|output| output.status.success())
+    .and_then(|output| String::from_utf8(output.stdout).ok())
+    .map(|sha| sha.trim().to_string())
+    .filter(|sha| !sha.is_empty());
+
+  from_git
+    .or_else(|| {
+      std::env::var("GITHUB_SHA")
+        .ok()
+        .map(|sha| sha.chars().take(7).collect())
+    })
+    .unwrap_or_else(|| "unknown".to_string())
 }
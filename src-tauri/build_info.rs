@@ -0,0 +1,48 @@
+//! Values `build.rs` bakes into the binary. Kept out of `build.rs` so the
+//! crate's unit tests can include it (see `lib.rs`).
+
+use chrono::{DateTime, Utc};
+
+/// UTC build date (`YYYY-MM-DD`). `source_date_epoch` is the value of
+/// `SOURCE_DATE_EPOCH`, so reproducible builds get a stable value; `now`
+/// applies when it is unset or not a Unix timestamp.
+pub fn build_date(source_date_epoch: Option<&str>, now: DateTime<Utc>) -> String {
+  let date = source_date_epoch
+    .and_then(|secs| secs.trim().parse::<i64>().ok())
+    .and_then(|secs| DateTime::from_timestamp(secs, 0))
+    .unwrap_or(now);
+  date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_767_225_600, 0).unwrap() // 2026-01-01
+  }
+
+  #[test]
+  fn uses_now_without_source_date_epoch() {
+    assert_eq!(build_date(None, now()), "2026-01-01");
+  }
+
+  #[test]
+  fn honors_source_date_epoch() {
+    assert_eq!(build_date(Some("1700000000"), now()), "2023-11-14");
+    assert_eq!(build_date(Some("0"), now()), "1970-01-01");
+  }
+
+  #[test]
+  fn formats_in_utc() {
+    // 2024-02-29 23:59:59 UTC is already March 1st east of UTC.
+    assert_eq!(build_date(Some("1709251199"), now()), "2024-02-29");
+  }
+
+  #[test]
+  fn ignores_an_invalid_source_date_epoch() {
+    assert_eq!(build_date(Some(""), now()), "2026-01-01");
+    assert_eq!(build_date(Some("yesterday"), now()), "2026-01-01");
+    assert_eq!(build_date(Some("99999999999999999"), now()), "2026-01-01");
+  }
+}
@@ -0,0 +1,333 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::kv;
+use crate::telemetry::TelemetryState;
+use crate::timer;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key of the bcrypt hash of the PIN set by the last `app_lock`.
+pub(crate) const PIN_HASH_KV: &str = "app_lock_pin_hash";
+
+/// `kv_store` key set while the app is locked, so a restart stays locked.
+const LOCKED_KV: &str = "app_locked";
+
+const LOCKED_EVENT: &str = "app:locked";
+const UNLOCKED_EVENT: &str = "app:unlocked";
+
+const MIN_PIN_CHARS: usize = 4;
+const MAX_PIN_CHARS: usize = 64;
+
+/// Consecutive wrong PINs after which `app_unlock` refuses for
+/// `LOCKOUT_DURATION`.
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+const LOCKOUT_DURATION: Duration = Duration::from_secs(30);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Tauri managed state of the lock screen.
+pub struct AppLockState {
+    locked: AtomicBool,
+    failed_attempts: AtomicU32,
+    retry_after: Mutex<Option<Instant>>,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+impl AppLockState {
+    fn new(locked: bool) -> Self {
+        AppLockState {
+            locked: AtomicBool::new(locked),
+            failed_attempts: AtomicU32::new(0),
+            retry_after: Mutex::new(None),
+        }
+    }
+}
+
+/// Register `AppLockState`, locked if the app was locked when it last
+/// exited (the frontend asks `is_app_locked` on startup). Called once from
+/// `lib.rs` during app setup, after `init_telemetry_db`.
+pub async fn init_app_lock(app: &AppHandle) {
+    let pool = &app.state::<TelemetryState>().pool();
+    let locked = match kv::get(pool, LOCKED_KV).await {
+        Ok(value) => value.is_some(),
+        Err(e) => {
+            log::warn!("init_app_lock: cannot read lock state: {}", e);
+            false
+        }
+    };
+    app.manage(AppLockState::new(locked));
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Bcrypt hash of `pin`, to pass to `app_lock` as `password_hash`.
+#[tauri::command]
+pub async fn hash_lock_pin(pin: String) -> Result<String, AppError> {
+    validate_pin(&pin)?;
+    with_timeout(
+        async {
+            tauri::async_runtime::spawn_blocking(move || bcrypt::hash(pin, bcrypt::DEFAULT_COST))
+                .await
+                .map_err(|e| AppError::Io(e.to_string()))?
+                .map_err(|e| AppError::Io(format!("cannot hash PIN: {}", e)))
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Lock the app: every window receives `app:locked` and shows the lock
+/// overlay, and a running ticket timer pauses until unlock. With
+/// `password_hash` (from `hash_lock_pin`), `app_unlock` requires the PIN;
+/// without, any `app_unlock` call succeeds. Fails with `Unauthorized` while
+/// the app is already locked, so the PIN cannot be replaced or removed.
+#[tauri::command]
+pub async fn app_lock(
+    password_hash: Option<String>,
+    app: AppHandle,
+    lock: tauri::State<'_, AppLockState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            lock_with(&state.pool(), &lock, password_hash.as_deref()).await?;
+            app.emit(LOCKED_EVENT, ()).ok();
+            timer::pause_running(&app).await;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Unlock the app if `pin` matches the one given to `app_lock`, and emit
+/// `app:unlocked`. Returns `false` for a wrong or missing PIN. After five
+/// wrong PINs in a row, fails with `Unauthorized` for 30 seconds.
+#[tauri::command]
+pub async fn app_unlock(
+    pin: Option<String>,
+    app: AppHandle,
+    lock: tauri::State<'_, AppLockState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<bool, AppError> {
+    with_timeout(
+        async {
+            let was_locked = lock.locked.load(Ordering::SeqCst);
+            let unlocked = unlock_with(&state.pool(), &lock, pin, Instant::now()).await?;
+            if was_locked && unlocked {
+                app.emit(UNLOCKED_EVENT, ()).ok();
+                timer::resume_running(&app).await;
+            }
+            Ok(unlocked)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Whether the app is locked.
+#[tauri::command]
+pub fn is_app_locked(lock: tauri::State<'_, AppLockState>) -> Result<bool, AppError> {
+    Ok(lock.locked.load(Ordering::SeqCst))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Store the lock state (and the PIN hash, if any) of `app_lock`.
+async fn lock_with(
+    pool: &SqlitePool,
+    lock: &AppLockState,
+    password_hash: Option<&str>,
+) -> Result<(), AppError> {
+    if lock.locked.load(Ordering::SeqCst) {
+        return Err(AppError::Unauthorized("the app is already locked".into()));
+    }
+    match password_hash {
+        Some(hash) => {
+            bcrypt::HashParts::from_str(hash)
+                .map_err(|_| AppError::Validation("password_hash is not a bcrypt hash".into()))?;
+            kv::set(pool, PIN_HASH_KV, hash).await?;
+        }
+        None => kv::delete(pool, PIN_HASH_KV).await?,
+    }
+    kv::set(pool, LOCKED_KV, "1").await?;
+
+    lock.locked.store(true, Ordering::SeqCst);
+    lock.failed_attempts.store(0, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Check `pin` for `app_unlock` at `now`, and clear the lock state if it
+/// matches.
+async fn unlock_with(
+    pool: &SqlitePool,
+    lock: &AppLockState,
+    pin: Option<String>,
+    now: Instant,
+) -> Result<bool, AppError> {
+    if !lock.locked.load(Ordering::SeqCst) {
+        return Ok(true);
+    }
+    if let Some(until) = *lock.retry_after.lock().unwrap() {
+        if now < until {
+            return Err(AppError::Unauthorized(
+                "too many wrong PINs; try again later".into(),
+            ));
+        }
+    }
+
+    let matches = match kv::get(pool, PIN_HASH_KV).await? {
+        None => true,
+        Some(hash) => match pin {
+            None => false,
+            Some(pin) => tauri::async_runtime::spawn_blocking(move || {
+                bcrypt::verify(pin, &hash).unwrap_or(false)
+            })
+            .await
+            .map_err(|e| AppError::Io(e.to_string()))?,
+        },
+    };
+
+    if !matches {
+        let failed = lock.failed_attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if failed >= MAX_FAILED_ATTEMPTS {
+            log::warn!("app_unlock: {} wrong PINs, locking out", failed);
+            *lock.retry_after.lock().unwrap() = Some(now + LOCKOUT_DURATION);
+            lock.failed_attempts.store(0, Ordering::SeqCst);
+        }
+        return Ok(false);
+    }
+
+    kv::delete(pool, LOCKED_KV).await?;
+    lock.locked.store(false, Ordering::SeqCst);
+    lock.failed_attempts.store(0, Ordering::SeqCst);
+    *lock.retry_after.lock().unwrap() = None;
+    Ok(true)
+}
+
+fn validate_pin(pin: &str) -> Result<(), AppError> {
+    let chars = pin.chars().count();
+    if !(MIN_PIN_CHARS..=MAX_PIN_CHARS).contains(&chars) {
+        return Err(AppError::Validation(format!(
+            "PIN must be {} to {} characters",
+            MIN_PIN_CHARS, MAX_PIN_CHARS
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIN: &str = "2468";
+
+    async fn setup(pool: &SqlitePool) -> AppLockState {
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(pool).await.unwrap();
+        AppLockState::new(false)
+    }
+
+    fn pin_hash() -> String {
+        bcrypt::hash(PIN, 4).unwrap()
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn lock_persists_and_refuses_while_locked(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        lock_with(&pool, &lock, Some(&pin_hash())).await.unwrap();
+        assert!(lock.locked.load(Ordering::SeqCst));
+        assert!(kv::get(&pool, LOCKED_KV).await.unwrap().is_some());
+
+        // Locking again must not replace or drop the PIN.
+        let err = lock_with(&pool, &lock, None).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+        assert!(kv::get(&pool, PIN_HASH_KV).await.unwrap().is_some());
+        assert!(!unlock_with(&pool, &lock, None, Instant::now())
+            .await
+            .unwrap());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn lock_refuses_a_plaintext_pin(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        let err = lock_with(&pool, &lock, Some(PIN)).await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert!(!lock.locked.load(Ordering::SeqCst));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn unlock_with_the_right_pin(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        lock_with(&pool, &lock, Some(&pin_hash())).await.unwrap();
+        let unlocked = unlock_with(&pool, &lock, Some(PIN.into()), Instant::now())
+            .await
+            .unwrap();
+        assert!(unlocked);
+        assert!(!lock.locked.load(Ordering::SeqCst));
+        assert!(kv::get(&pool, LOCKED_KV).await.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn unlock_without_pin_hash_always_succeeds(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        lock_with(&pool, &lock, None).await.unwrap();
+        assert!(unlock_with(&pool, &lock, None, Instant::now())
+            .await
+            .unwrap());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn wrong_pin_keeps_the_app_locked(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        lock_with(&pool, &lock, Some(&pin_hash())).await.unwrap();
+        let unlocked = unlock_with(&pool, &lock, Some("1357".into()), Instant::now())
+            .await
+            .unwrap();
+        assert!(!unlocked);
+        assert!(lock.locked.load(Ordering::SeqCst));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn wrong_pins_lock_out_for_thirty_seconds(pool: SqlitePool) {
+        let lock = setup(&pool).await;
+        lock_with(&pool, &lock, Some(&pin_hash())).await.unwrap();
+        let start = Instant::now();
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            let unlocked = unlock_with(&pool, &lock, Some("0000".into()), start)
+                .await
+                .unwrap();
+            assert!(!unlocked);
+        }
+
+        // Even the right PIN is refused during the lockout.
+        let later = start + LOCKOUT_DURATION - Duration::from_secs(1);
+        let err = unlock_with(&pool, &lock, Some(PIN.into()), later)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+
+        let after = start + LOCKOUT_DURATION;
+        assert!(unlock_with(&pool, &lock, Some(PIN.into()), after)
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,838 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnection;
+use sqlx::{Connection, Row};
+use tauri::AppHandle;
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::compaction;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+use crate::vacuum::{self, database_size, vacuum_database};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Replaces the `.db` extension of the project database to name its archive
+/// (`backlog.archive.db`).
+const ARCHIVE_SUFFIX: &str = ".archive.db";
+
+/// First schema version with `archived_items`.
+const MIN_SCHEMA_VERSION: i64 = 6;
+
+/// Tables copied to the archive, parents first so foreign keys hold.
+const ARCHIVE_TABLES: [&str; 5] = [
+    "projects",
+    "sections",
+    "backlog_items",
+    "archived_items",
+    "item_relations",
+];
+
+/// Ticket tables, with the column that changes whenever a row does.
+const TICKET_TABLES: [(&str, &str); 2] = [
+    ("backlog_items", "updated_at"),
+    ("archived_items", "archived_at"),
+];
+
+/// Upper bound on the page size of `search_archive`.
+const MAX_RESULTS: i64 = 200;
+
+/// Target section of a restored ticket: the live section with the same
+/// title (preferring the same id), else the first one.
+const RESTORED_SECTION: &str = "COALESCE(
+    (SELECT ms.id FROM main.sections ms
+     JOIN archive.sections s ON s.title = ms.title
+     WHERE s.id = src.section_id
+     ORDER BY ms.id = s.id DESC, ms.position
+     LIMIT 1),
+    (SELECT id FROM main.sections ORDER BY position LIMIT 1))";
+
+const RESTORED_PROJECT: &str = "COALESCE(
+    (SELECT id FROM main.projects WHERE id = src.project_id),
+    (SELECT id FROM main.projects ORDER BY id LIMIT 1))";
+
+/// Ids of the tickets being moved; `source` is their table.
+const MOVING_SCHEMA: &str = "
+    CREATE TEMP TABLE IF NOT EXISTS moving (
+        id TEXT NOT NULL,
+        source TEXT NOT NULL,
+        PRIMARY KEY (id, source)
+    );
+    DELETE FROM temp.moving;
+";
+
+/// Ids of the live tickets, board and in-app archive alike.
+const LIVE_IDS: &str =
+    "SELECT id FROM main.backlog_items UNION ALL SELECT id FROM main.archived_items";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `archive_tickets`.
+#[derive(Debug, Default, Serialize)]
+pub struct ArchiveReport {
+    /// Board tickets moved to the archive database.
+    pub tickets_moved: u64,
+    /// Tickets of the in-app archive (`archived_items`) moved.
+    pub archived_moved: u64,
+    pub relations_moved: u64,
+    /// Matching tickets left in place because they changed while being
+    /// archived.
+    pub skipped: u64,
+    pub vacuumed: bool,
+    /// Shrinkage of the database file (and WAL). Deleted rows only free
+    /// space inside the file, so this is about zero without `vacuum`.
+    pub reclaimed_bytes: u64,
+}
+
+/// Return value of `restore_from_archive`.
+#[derive(Debug, Serialize)]
+pub struct ArchiveRestoreReport {
+    pub restored: Vec<String>,
+    /// Requested ids absent from the archive, or already in the project.
+    pub not_found: Vec<String>,
+}
+
+/// One ticket of the archive database.
+#[derive(Debug, Serialize)]
+pub struct ArchivedTicket {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub title: String,
+    /// Section the ticket was in; `None` for in-app archive tickets.
+    pub section: Option<String>,
+    /// Whether it came from the in-app archive rather than the board.
+    pub in_app_archive: bool,
+    /// Last update, or archiving date for in-app archive tickets.
+    pub date: Option<String>,
+}
+
+/// Return value of `search_archive`.
+#[derive(Debug, Serialize)]
+pub struct ArchiveSearchResults {
+    /// Total number of matches, for paging.
+    pub total: i64,
+    pub hits: Vec<ArchivedTicket>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Move old tickets out of a project into its archive database
+/// (`backlog.archive.db` next to it, created with the project's table
+/// definitions and encrypted like it). Moves the board tickets in one of
+/// `statuses` (section titles, case-insensitive) last updated before
+/// `before_date`, the in-app archive tickets archived before that date, and
+/// their relations.
+///
+/// Rows are copied in one transaction on the archive, then deleted in a
+/// second one on the project, only where the archived copy matches the live
+/// row: an interruption leaves a ticket in both databases, never in
+/// neither. With `vacuum`, the project is vacuumed afterwards to give the
+/// space back. The frontend's file watcher is paused meanwhile; reload the
+/// project after the call.
+#[tauri::command]
+pub async fn archive_tickets(
+    db_path: String,
+    before_date: String,
+    statuses: Vec<String>,
+    vacuum: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<ArchiveReport, AppError> {
+    let result = async {
+        if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+            return Err(AppError::Validation(
+                "a schema migration is in progress".into(),
+            ));
+        }
+        let before = NaiveDate::parse_from_str(&before_date, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("invalid date: {}", before_date)))?
+            .format("%Y-%m-%d")
+            .to_string();
+        let _guard = maintenance.lock.try_lock().map_err(|_| {
+            AppError::Validation("a backup or restore is already in progress".into())
+        })?;
+        let _paused = fs_watch::pause_project_watch(&app);
+        let db = Path::new(&db_path);
+        files::validate_path(&app, db)?;
+
+        let before_bytes = database_size(db);
+        let mut report =
+            with_timeout(move_to_archive(db, &before, &statuses), DB_TIMEOUT_MS).await?;
+        if vacuum.unwrap_or(false) && report.tickets_moved + report.archived_moved > 0 {
+            vacuum_database(db, vacuum::progress_emitter(&app, None)).await?;
+            report.vacuumed = true;
+        } else if report.tickets_moved + report.archived_moved > 0 {
+            compaction::schedule_compaction(&app, db);
+        }
+        report.reclaimed_bytes = before_bytes.saturating_sub(database_size(db));
+        Ok(report)
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "archive_tickets",
+        &format!("{} (before {})", db_path, before_date),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Search the archive database of a project by id, title, description,
+/// component or module (case-insensitive substring), most recent first. An
+/// empty query lists everything; a project without archive has no results.
+#[tauri::command]
+pub async fn search_archive(
+    db_path: String,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    app: AppHandle,
+) -> Result<ArchiveSearchResults, AppError> {
+    let db = Path::new(&db_path);
+    files::validate_path(&app, db)?;
+    let archive = archive_path(db);
+    if !archive.is_file() {
+        return Ok(ArchiveSearchResults {
+            total: 0,
+            hits: Vec::new(),
+        });
+    }
+    let limit = limit.unwrap_or(50).clamp(1, MAX_RESULTS);
+    let offset = offset.unwrap_or(0).max(0);
+    with_timeout(
+        search_rows(&archive, query.trim(), limit, offset),
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Move tickets back from the archive database into the project, in the
+/// table they came from. Board tickets return to the section with the same
+/// title, or to the first section when it no longer exists. Copies are
+/// inserted in the project first, then removed from the archive. Reload the
+/// project after the call.
+#[tauri::command]
+pub async fn restore_from_archive(
+    db_path: String,
+    ticket_ids: Vec<String>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<ArchiveRestoreReport, AppError> {
+    let result = async {
+        if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+            return Err(AppError::Validation(
+                "a schema migration is in progress".into(),
+            ));
+        }
+        let _guard = maintenance.lock.try_lock().map_err(|_| {
+            AppError::Validation("a backup or restore is already in progress".into())
+        })?;
+        let _paused = fs_watch::pause_project_watch(&app);
+        let db = Path::new(&db_path);
+        files::validate_path(&app, db)?;
+        with_timeout(restore_rows(db, &ticket_ids), DB_TIMEOUT_MS).await
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "restore_from_archive",
+        &format!("{} ({} tickets)", db_path, ticket_ids.len()),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Archive database of the project database `db`.
+pub fn archive_path(db: &Path) -> PathBuf {
+    let stem = db.file_stem().unwrap_or_default().to_string_lossy();
+    db.with_file_name(format!("{}{}", stem, ARCHIVE_SUFFIX))
+}
+
+async fn move_to_archive(
+    db: &Path,
+    before: &str,
+    statuses: &[String],
+) -> Result<ArchiveReport, AppError> {
+    let mut conn = open_project(db).await?;
+    let statuses = serde_json::to_string(statuses)
+        .map_err(|e| AppError::Validation(format!("invalid statuses: {}", e)))?;
+
+    sqlx::raw_sql(MOVING_SCHEMA).execute(&mut conn).await?;
+    sqlx::query(
+        "INSERT INTO temp.moving (id, source)
+         SELECT bi.id, 'backlog_items' FROM main.backlog_items bi
+         JOIN main.sections s ON s.id = bi.section_id
+         WHERE LOWER(TRIM(s.title)) IN (SELECT LOWER(TRIM(value)) FROM json_each(?1))
+           AND date(COALESCE(bi.updated_at, bi.created_at)) < ?2",
+    )
+    .bind(&statuses)
+    .bind(before)
+    .execute(&mut conn)
+    .await?;
+    sqlx::query(
+        "INSERT INTO temp.moving (id, source)
+         SELECT id, 'archived_items' FROM main.archived_items
+         WHERE date(archived_at) < ?",
+    )
+    .bind(before)
+    .execute(&mut conn)
+    .await?;
+    let expected: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM temp.moving")
+        .fetch_one(&mut conn)
+        .await?;
+    let mut report = ArchiveReport::default();
+    if expected == 0 {
+        return Ok(report);
+    }
+    attach_archive(&mut conn, db).await?;
+
+    // First transaction: copy into the archive.
+    let mut tx = conn.begin().await?;
+    for table in ARCHIVE_TABLES {
+        ensure_archive_table(&mut tx, table).await?;
+    }
+    copy_rows(&mut tx, "projects", "main", "archive", "1", &[]).await?;
+    copy_rows(
+        &mut tx,
+        "sections",
+        "main",
+        "archive",
+        "src.id IN (SELECT section_id FROM main.backlog_items
+                    WHERE id IN (SELECT id FROM temp.moving WHERE source = 'backlog_items'))",
+        &[],
+    )
+    .await?;
+    for (table, _) in TICKET_TABLES {
+        sqlx::query(&format!(
+            "DELETE FROM archive.\"{0}\"
+             WHERE id IN (SELECT id FROM temp.moving WHERE source = '{0}')",
+            table
+        ))
+        .execute(&mut *tx)
+        .await?;
+        copy_rows(
+            &mut tx,
+            table,
+            "main",
+            "archive",
+            &format!(
+                "src.id IN (SELECT id FROM temp.moving WHERE source = '{}')",
+                table
+            ),
+            &[],
+        )
+        .await?;
+    }
+    report.relations_moved = copy_rows(
+        &mut tx,
+        "item_relations",
+        "main",
+        "archive",
+        "src.source_id IN (SELECT id FROM temp.moving)
+         OR src.target_id IN (SELECT id FROM temp.moving)",
+        &[("id", None)],
+    )
+    .await?;
+    tx.commit().await?;
+
+    // Second transaction: delete the live rows whose archived copy matches.
+    let mut tx = conn.begin().await?;
+    for (table, stamp) in TICKET_TABLES {
+        let deleted = sqlx::query(&format!(
+            "DELETE FROM main.\"{0}\" WHERE id IN (
+                 SELECT m.id FROM main.\"{0}\" m
+                 JOIN archive.\"{0}\" a ON a.id = m.id
+                   AND a.raw_markdown IS m.raw_markdown AND a.{1} IS m.{1}
+                 WHERE m.id IN (SELECT id FROM temp.moving WHERE source = '{0}')
+             )",
+            table, stamp
+        ))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        match table {
+            "backlog_items" => report.tickets_moved = deleted,
+            _ => report.archived_moved = deleted,
+        }
+    }
+    let gone = format!("SELECT id FROM temp.moving WHERE id NOT IN ({})", LIVE_IDS);
+    sqlx::query(&format!(
+        "DELETE FROM main.item_relations
+         WHERE source_id IN ({0}) OR target_id IN ({0})",
+        gone
+    ))
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    report.skipped = (expected as u64).saturating_sub(report.tickets_moved + report.archived_moved);
+    if report.skipped > 0 {
+        log::warn!(
+            "archive_tickets: {} tickets changed meanwhile, left in place",
+            report.skipped
+        );
+        // Their archived copies are outdated.
+        for (table, _) in TICKET_TABLES {
+            sqlx::query(&format!(
+                "DELETE FROM archive.\"{0}\"
+                 WHERE id IN (SELECT id FROM temp.moving WHERE source = '{0}')
+                   AND id IN (SELECT id FROM main.\"{0}\")",
+                table
+            ))
+            .execute(&mut conn)
+            .await?;
+        }
+    }
+    Ok(report)
+}
+
+/// One page of the archive database's tickets matching `query`.
+async fn search_rows(
+    archive: &Path,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<ArchiveSearchResults, AppError> {
+    let pattern = format!("%{}%", escape_like(query));
+    let mut conn = project_db::open_read_only(archive).await?;
+    let matches = "FROM (
+             SELECT bi.id, bi.type, bi.title, bi.description, bi.component,
+               bi.module, s.title AS section, 0 AS in_app_archive,
+               COALESCE(bi.updated_at, bi.created_at) AS date
+             FROM backlog_items bi
+             LEFT JOIN sections s ON s.id = bi.section_id
+             UNION ALL
+             SELECT id, type, title, description, component, module, NULL, 1,
+               archived_at
+             FROM archived_items
+         )
+         WHERE ?1 = '' OR id LIKE ?2 ESCAPE '\\' OR title LIKE ?2 ESCAPE '\\'
+           OR description LIKE ?2 ESCAPE '\\' OR component LIKE ?2 ESCAPE '\\'
+           OR module LIKE ?2 ESCAPE '\\'";
+
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {}", matches))
+        .bind(query)
+        .bind(&pattern)
+        .fetch_one(&mut conn)
+        .await?;
+    let hits = sqlx::query(&format!(
+        "SELECT id, type, title, section, in_app_archive, date {}
+         ORDER BY date DESC, id
+         LIMIT ?3 OFFSET ?4",
+        matches
+    ))
+    .bind(query)
+    .bind(&pattern)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&mut conn)
+    .await?
+    .iter()
+    .map(|row| {
+        Ok(ArchivedTicket {
+            id: row.try_get(0)?,
+            item_type: row.try_get(1)?,
+            title: row.try_get(2)?,
+            section: row.try_get(3)?,
+            in_app_archive: row.try_get(4)?,
+            date: row.try_get(5)?,
+        })
+    })
+    .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    Ok(ArchiveSearchResults { total, hits })
+}
+
+async fn restore_rows(db: &Path, ids: &[String]) -> Result<ArchiveRestoreReport, AppError> {
+    if !archive_path(db).is_file() {
+        return Err(AppError::Validation("project has no archive".into()));
+    }
+    let mut conn = open_with_archive(db).await?;
+    let ids_json = serde_json::to_string(ids)
+        .map_err(|e| AppError::Validation(format!("invalid ticket ids: {}", e)))?;
+
+    sqlx::raw_sql(MOVING_SCHEMA).execute(&mut conn).await?;
+    for (table, _) in TICKET_TABLES {
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO temp.moving (id, source)
+             SELECT value, '{0}' FROM json_each(?)
+             WHERE value IN (SELECT id FROM archive.\"{0}\")
+               AND value NOT IN ({1})",
+            table, LIVE_IDS
+        ))
+        .bind(&ids_json)
+        .execute(&mut conn)
+        .await?;
+    }
+    let to_board: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM temp.moving WHERE source = 'backlog_items'")
+            .fetch_one(&mut conn)
+            .await?;
+    let has_section: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM main.sections)")
+        .fetch_one(&mut conn)
+        .await?;
+    if to_board > 0 && !has_section {
+        return Err(AppError::Validation(
+            "project has no section to restore tickets into".into(),
+        ));
+    }
+
+    // First transaction: copy into the project.
+    let mut tx = conn.begin().await?;
+    for (table, _) in TICKET_TABLES {
+        let overrides: &[(&str, Option<&str>)] = match table {
+            "backlog_items" => &[
+                ("project_id", Some(RESTORED_PROJECT)),
+                ("section_id", Some(RESTORED_SECTION)),
+            ],
+            _ => &[("project_id", Some(RESTORED_PROJECT))],
+        };
+        copy_rows(
+            &mut tx,
+            table,
+            "archive",
+            "main",
+            &format!(
+                "src.id IN (SELECT id FROM temp.moving WHERE source = '{}')",
+                table
+            ),
+            overrides,
+        )
+        .await?;
+    }
+    copy_rows(
+        &mut tx,
+        "item_relations",
+        "archive",
+        "main",
+        &format!(
+            "(src.source_id IN (SELECT id FROM temp.moving)
+              OR src.target_id IN (SELECT id FROM temp.moving))
+             AND src.source_id IN ({0}) AND src.target_id IN ({0})",
+            LIVE_IDS
+        ),
+        &[("id", None), ("project_id", Some(RESTORED_PROJECT))],
+    )
+    .await?;
+    tx.commit().await?;
+
+    // Second transaction: delete the archived rows now in the project.
+    let mut tx = conn.begin().await?;
+    for (table, _) in TICKET_TABLES {
+        sqlx::query(&format!(
+            "DELETE FROM archive.\"{0}\" WHERE id IN (
+                 SELECT a.id FROM archive.\"{0}\" a
+                 JOIN main.\"{0}\" m ON m.id = a.id AND m.raw_markdown IS a.raw_markdown
+                 WHERE a.id IN (SELECT id FROM temp.moving WHERE source = '{0}')
+             )",
+            table
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query(&format!(
+        "DELETE FROM archive.item_relations
+         WHERE source_id IN ({0}) AND target_id IN ({0})",
+        LIVE_IDS
+    ))
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let restored: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT id FROM temp.moving WHERE id IN ({}) ORDER BY id",
+        LIVE_IDS
+    ))
+    .fetch_all(&mut conn)
+    .await?;
+    let not_found = ids
+        .iter()
+        .filter(|id| !restored.contains(id))
+        .cloned()
+        .collect();
+    Ok(ArchiveRestoreReport {
+        restored,
+        not_found,
+    })
+}
+
+/// Open the project database with its archive attached as `archive`.
+async fn open_with_archive(db: &Path) -> Result<SqliteConnection, AppError> {
+    let mut conn = open_project(db).await?;
+    attach_archive(&mut conn, db).await?;
+    Ok(conn)
+}
+
+/// Open the project database, refusing schemas without `archived_items`.
+async fn open_project(db: &Path) -> Result<SqliteConnection, AppError> {
+    let mut conn = project_db::open_connection(db).await?;
+    let version = project_db::schema_version(&mut conn).await?;
+    if version < MIN_SCHEMA_VERSION {
+        return Err(AppError::Validation(format!(
+            "project database schema v{} is too old to archive (need v{})",
+            version, MIN_SCHEMA_VERSION
+        )));
+    }
+    Ok(conn)
+}
+
+/// Attach the archive of `db` to `conn` as `archive`, creating it if
+/// missing. A new archive gets the project's SQLCipher key; a plaintext one
+/// from before the project was encrypted stays plaintext.
+async fn attach_archive(conn: &mut SqliteConnection, db: &Path) -> Result<(), AppError> {
+    let archive = archive_path(db);
+    let key = if archive.is_file() && !project_db::is_encrypted(&archive) {
+        String::new()
+    } else {
+        project_db::project_key(db).unwrap_or_default()
+    };
+    // ATTACH opens with the flags of the project connection, which does not
+    // create files; an empty file is an empty database.
+    if !archive.exists() {
+        std::fs::File::create(&archive)?;
+    }
+    sqlx::query("ATTACH DATABASE ? AS archive KEY ?")
+        .bind(archive.to_string_lossy().into_owned())
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Create `table` in the archive from the project's definition, and add the
+/// columns the project gained since the archive was created.
+async fn ensure_archive_table(conn: &mut SqliteConnection, table: &str) -> Result<(), AppError> {
+    let ddl: Option<String> =
+        sqlx::query_scalar("SELECT sql FROM main.sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_optional(&mut *conn)
+            .await?;
+    // sqlite_master keeps `CREATE TABLE <name> (...)`; reuse from `(` on.
+    let definition = ddl
+        .as_deref()
+        .and_then(|ddl| ddl.find('(').map(|start| &ddl[start..]))
+        .ok_or_else(|| AppError::Database(format!("project has no {} table", table)))?;
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS archive.\"{}\" {}",
+        table, definition
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    let archived = columns(conn, "archive", table).await?;
+    for (name, kind) in columns(conn, "main", table).await? {
+        if !archived.iter().any(|(existing, _)| existing == &name) {
+            sqlx::query(&format!(
+                "ALTER TABLE archive.\"{}\" ADD COLUMN \"{}\" {}",
+                table, name, kind
+            ))
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// `INSERT OR IGNORE` the rows of `from.table` matching `filter` (on alias
+/// `src`) into `to.table`, over the columns both have. `overrides` replaces
+/// a column's value with an SQL expression, or leaves it out (`None`).
+/// Returns the number of rows inserted.
+async fn copy_rows(
+    conn: &mut SqliteConnection,
+    table: &str,
+    from: &str,
+    to: &str,
+    filter: &str,
+    overrides: &[(&str, Option<&str>)],
+) -> Result<u64, AppError> {
+    let target = columns(conn, to, table).await?;
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for (name, _) in columns(conn, from, table).await? {
+        if !target.iter().any(|(column, _)| column == &name) {
+            continue;
+        }
+        match overrides.iter().find(|(column, _)| *column == name) {
+            Some((_, None)) => continue,
+            Some((_, Some(expression))) => values.push(expression.to_string()),
+            None => values.push(format!("src.\"{}\"", name)),
+        }
+        names.push(format!("\"{}\"", name));
+    }
+    let inserted = sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {}.\"{}\" ({}) SELECT {} FROM {}.\"{}\" AS src WHERE {}",
+        to,
+        table,
+        names.join(", "),
+        values.join(", "),
+        from,
+        table,
+        filter
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    Ok(inserted)
+}
+
+/// Name and declared type of the columns of `schema.table`.
+async fn columns(
+    conn: &mut SqliteConnection,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<(String, String)>, AppError> {
+    let columns = sqlx::query_as("SELECT name, type FROM pragma_table_info(?, ?)")
+        .bind(table)
+        .bind(schema)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(columns)
+}
+
+/// Escape `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+
+    /// Two old tickets and a recent one, an already archived ticket and a
+    /// relation between two of the old tickets.
+    const TICKETS: &str = "
+        INSERT INTO backlog_items (id, project_id, section_id, type, title, description,
+            component, module, raw_markdown, created_at, updated_at) VALUES
+            ('BUG-1', 1, 2, 'BUG', 'Crash on save', '', 'editor', '', '# BUG-1',
+             '2023-01-02', '2023-01-05'),
+            ('BUG-2', 1, 2, 'BUG', 'Recent fix', '', 'editor', '', '# BUG-2',
+             '2024-05-01', '2024-05-02'),
+            ('FEAT-1', 1, 1, 'FEAT', 'Old idea', '', '', '', '# FEAT-1',
+             '2023-01-01', '2023-01-01');
+        INSERT INTO archived_items (id, project_id, type, title, raw_markdown, archived_at)
+            VALUES ('BUG-0', 1, 'BUG', 'Ancient 50% bug', '# BUG-0', '2022-12-01');
+        INSERT INTO item_relations (id, project_id, source_id, target_id, relation_type)
+            VALUES (1, 1, 'BUG-1', 'FEAT-1', 'blocks');
+    ";
+
+    async fn project(dir: &Path) -> PathBuf {
+        let seed = [BOARD, TICKETS].concat();
+        create_db(dir, project_db::SUPPORTED_SCHEMA_VERSION, &seed).await
+    }
+
+    async fn live_ids(db: &Path) -> Vec<String> {
+        let mut conn = project_db::open_read_only(db).await.unwrap();
+        sqlx::query_scalar(&format!("SELECT id FROM ({}) ORDER BY id", LIVE_IDS))
+            .fetch_all(&mut conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn old_closed_tickets_move_to_a_new_archive_and_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+
+        let report = move_to_archive(&db, "2024-01-01", &["done".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(report.tickets_moved, 1);
+        assert_eq!(report.archived_moved, 1);
+        assert_eq!(report.relations_moved, 1);
+        assert_eq!(report.skipped, 0);
+        assert!(archive_path(&db).is_file());
+        assert_eq!(live_ids(&db).await, ["BUG-2", "FEAT-1"]);
+
+        let found = search_rows(&archive_path(&db), "crash", 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(found.total, 1);
+        assert_eq!(found.hits[0].id, "BUG-1");
+        assert_eq!(found.hits[0].section.as_deref(), Some("Done"));
+        assert!(!found.hits[0].in_app_archive);
+
+        let restored = restore_rows(&db, &["BUG-1".to_string(), "BUG-9".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(restored.restored, ["BUG-1"]);
+        assert_eq!(restored.not_found, ["BUG-9"]);
+        assert_eq!(live_ids(&db).await, ["BUG-1", "BUG-2", "FEAT-1"]);
+
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+        let (section, relations): (i64, i64) = sqlx::query_as(
+            "SELECT (SELECT section_id FROM backlog_items WHERE id = 'BUG-1'),
+                    (SELECT COUNT(*) FROM item_relations)",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!((section, relations), (2, 1));
+        let left = search_rows(&archive_path(&db), "", 50, 0).await.unwrap();
+        assert_eq!(left.total, 1);
+        assert_eq!(left.hits[0].id, "BUG-0");
+    }
+
+    #[tokio::test]
+    async fn nothing_matching_leaves_the_project_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        let report = move_to_archive(&db, "2020-01-01", &["Done".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(report.tickets_moved + report.archived_moved, 0);
+        assert_eq!(live_ids(&db).await.len(), 4);
+        assert!(!archive_path(&db).exists());
+        assert!(restore_rows(&db, &["BUG-1".to_string()]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn search_wildcards_are_literal() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        move_to_archive(&db, "2024-01-01", &["Done".to_string()])
+            .await
+            .unwrap();
+        let archive = archive_path(&db);
+        let percent = search_rows(&archive, "50%", 50, 0).await.unwrap();
+        assert_eq!(percent.total, 1);
+        assert!(percent.hits[0].in_app_archive);
+        assert_eq!(search_rows(&archive, "_", 50, 0).await.unwrap().total, 0);
+    }
+
+    #[test]
+    fn archive_sits_next_to_the_project() {
+        assert_eq!(
+            archive_path(Path::new("/p/backlog.db")),
+            Path::new("/p/backlog.archive.db")
+        );
+    }
+}
@@ -0,0 +1,1271 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqliteConnection;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::archive_db;
+use crate::backup::{self, MaintenanceState};
+use crate::clipboard;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::import::open_project;
+use crate::kv;
+use crate::project_db;
+use crate::search;
+use crate::storage::StorageState;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Attachments live in `<data_dir>/attachments/<project>/<ab>/<sha256>`.
+const ATTACHMENTS_DIR: &str = "attachments";
+
+/// First schema version with `attachments`.
+const MIN_SCHEMA_VERSION: i64 = 11;
+
+const MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Budget of the save commands. Copying and hashing 100 MB from a slow
+/// drive can take longer than `DB_TIMEOUT_MS`; a staged copy left by an
+/// expired save is removed by `cleanup_orphaned_attachments`.
+const SAVE_TIMEOUT_MS: u64 = 5 * 60 * 1000;
+
+/// Total size of the distinct files attached in one project.
+const PROJECT_QUOTA_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Prefix of the file a new attachment is copied to before it is hashed
+/// and moved to its blob path.
+const STAGING_PREFIX: &str = ".incoming-";
+
+/// `kv_store` key of the extensions refused when files are dropped on the
+/// window, as a JSON array.
+pub(crate) const DROP_DENYLIST_KV: &str = "attachment_drop_denylist";
+
+/// Extensions refused by default: executables, installers and scripts.
+const DEFAULT_DROP_DENYLIST: [&str; 14] = [
+    "exe", "msi", "bat", "cmd", "com", "scr", "pif", "ps1", "vbs", "vbe", "wsf", "jar", "app",
+    "dmg",
+];
+
+/// Emitted with an `AttachmentsAdded` once dropped files are processed.
+const ATTACHMENTS_ADDED_EVENT: &str = "attachments:added";
+
+const MAX_FILENAME_CHARS: usize = 255;
+const COPY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// MIME types by lowercase extension; anything else is
+/// `application/octet-stream`.
+const MIME_TYPES: [(&str, &str); 22] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("bmp", "image/bmp"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("html", "text/html"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A row of `attachments`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: i64,
+    pub item_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub created_at: Option<String>,
+}
+
+/// Attachments of one ticket in `AttachmentStorageStats`.
+#[derive(Debug, Serialize)]
+pub struct TicketAttachmentUsage {
+    pub item_id: String,
+    pub attachments: i64,
+    pub bytes: i64,
+    /// `false` once the ticket was deleted: its attachments still hold
+    /// their files until removed with `delete_attachment`.
+    pub ticket_exists: bool,
+}
+
+/// Return value of `attachment_storage_stats`.
+#[derive(Debug, Serialize)]
+pub struct AttachmentStorageStats {
+    /// Size of every file in the project's attachment folder.
+    pub total_bytes: u64,
+    /// Largest first.
+    pub tickets: Vec<TicketAttachmentUsage>,
+    /// Files no attachment of the project or its archive refers to.
+    pub orphaned_blobs: u64,
+    pub orphaned_bytes: u64,
+}
+
+/// Tauri managed state: the ticket files dropped on the window are
+/// attached to, set by `set_drop_target`.
+#[derive(Default)]
+pub struct DropTargetState {
+    target: Mutex<Option<DropTarget>>,
+}
+
+#[derive(Debug, Clone)]
+struct DropTarget {
+    db_path: String,
+    ticket_id: String,
+}
+
+/// Payload of `attachments:added`.
+#[derive(Debug, Clone, Serialize)]
+struct AttachmentsAdded {
+    db_path: String,
+    ticket_id: String,
+    attachments: Vec<Attachment>,
+    rejected: Vec<RejectedFile>,
+}
+
+/// A dropped file that was not attached.
+#[derive(Debug, Clone, Serialize)]
+struct RejectedFile {
+    path: String,
+    error: String,
+}
+
+/// Return value of `save_clipboard_image`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipboardAttachment {
+    /// The clipboard image, saved as `clipboard-<timestamp>.png`.
+    Image { attachment: Attachment },
+    /// Files copied in a file manager, in order.
+    Files { attachments: Vec<Attachment> },
+    /// Nothing to attach on the clipboard.
+    NoImage,
+}
+
+/// Content given to `store_attachment`.
+enum AttachmentSource {
+    /// A file already checked against the fs scope.
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// A file of the project's attachment folder.
+struct Blob {
+    path: PathBuf,
+    /// File name: the sha256 of a blob, or a leftover staging file.
+    name: String,
+    size: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Attach a file to `ticket_id`, from `source_path` or from `bytes` (with
+/// `filename`). The content is stored once per project by sha256: attaching
+/// the same file again adds a row but no copy. Fails with `QuotaExceeded`
+/// when a new file would take the project past 1 GB of attachments; files
+/// are limited to 100 MB.
+#[tauri::command]
+pub async fn save_attachment(
+    db_path: String,
+    ticket_id: String,
+    source_path: Option<String>,
+    bytes: Option<Vec<u8>>,
+    filename: Option<String>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<Attachment, AppError> {
+    let source = match (source_path, bytes) {
+        (Some(path), None) => AttachmentSource::File(files::validate_path(&app, Path::new(&path))?),
+        (None, Some(bytes)) => AttachmentSource::Bytes(bytes),
+        _ => {
+            return Err(AppError::Validation(
+                "give either source_path or bytes".into(),
+            ))
+        }
+    };
+
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+
+    let _guard = maintenance_guard(&maintenance)?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    let data_dir = &app.state::<StorageState>().data_dir;
+    with_timeout(
+        store_attachment(data_dir, &db, &ticket_id, source, filename),
+        SAVE_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Attach what is on the clipboard to `ticket_id`: the files when files
+/// were copied in a file manager, else the image encoded as PNG, through
+/// the same storage as `save_attachment`. Like `source_path`, copied files
+/// must lie inside the app data directory or the fs scope. Returns
+/// `no_image` when there is nothing to attach.
+#[tauri::command]
+pub async fn save_clipboard_image(
+    db_path: String,
+    ticket_id: String,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<ClipboardAttachment, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let db_path = db.as_path();
+    with_timeout(
+        async {
+            let copied = clipboard::read_file_list(&app)
+                .await?
+                .iter()
+                .map(|path| files::validate_path(&app, path))
+                .collect::<Result<Vec<_>, _>>()?;
+            if !copied.is_empty() {
+                let _guard = maintenance_guard(&maintenance)?;
+                let _paused = fs_watch::pause_project_watch(&app);
+                let data_dir = &app.state::<StorageState>().data_dir;
+                let mut attachments = Vec::with_capacity(copied.len());
+                for path in copied {
+                    let source = AttachmentSource::File(path);
+                    attachments
+                        .push(store_attachment(data_dir, db_path, &ticket_id, source, None).await?);
+                }
+                return Ok(ClipboardAttachment::Files { attachments });
+            }
+
+            let Some(png) = clipboard::read_image_png(&app)? else {
+                return Ok(ClipboardAttachment::NoImage);
+            };
+            let filename = chrono::Local::now()
+                .format("clipboard-%Y%m%d-%H%M%S.png")
+                .to_string();
+            let _guard = maintenance_guard(&maintenance)?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            let source = AttachmentSource::Bytes(png);
+            let data_dir = &app.state::<StorageState>().data_dir;
+            let attachment =
+                store_attachment(data_dir, db_path, &ticket_id, source, Some(filename)).await?;
+            Ok(ClipboardAttachment::Image { attachment })
+        },
+        SAVE_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Attach files dropped on the window to `ticket_id` from now on, until
+/// `clear_drop_target`. Without a target, drops are left to the frontend.
+#[tauri::command]
+pub fn set_drop_target(
+    db_path: String,
+    ticket_id: String,
+    app: AppHandle,
+    state: tauri::State<'_, DropTargetState>,
+) -> Result<(), AppError> {
+    files::validate_path(&app, Path::new(&db_path))?;
+    *state.target.lock().unwrap() = Some(DropTarget { db_path, ticket_id });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_drop_target(state: tauri::State<'_, DropTargetState>) {
+    *state.target.lock().unwrap() = None;
+}
+
+/// Extensions refused when files are dropped on the window.
+#[tauri::command]
+pub async fn get_drop_denylist(
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<String>, AppError> {
+    with_timeout(async { Ok(drop_denylist(&state).await) }, DB_TIMEOUT_MS).await
+}
+
+/// Replace the extensions refused when files are dropped on the window
+/// (case-insensitive, with or without the dot). Returns the saved list.
+#[tauri::command]
+pub async fn set_drop_denylist(
+    extensions: Vec<String>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<String>, AppError> {
+    let denylist = normalize_extensions(extensions)?;
+    let json = serde_json::to_string(&denylist).map_err(|e| AppError::Io(e.to_string()))?;
+    with_timeout(
+        async {
+            kv::set(&state.pool(), DROP_DENYLIST_KV, &json).await?;
+            Ok(denylist)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Attachments of `ticket_id`, oldest first.
+#[tauri::command]
+pub async fn list_attachments(
+    db_path: String,
+    ticket_id: String,
+    app: AppHandle,
+) -> Result<Vec<Attachment>, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(&db, true, MIN_SCHEMA_VERSION).await?;
+            let attachments = sqlx::query_as(
+                "SELECT id, item_id, filename, mime_type, size_bytes, sha256, created_at
+                 FROM attachments
+                 WHERE project_id = ? AND item_id = ?
+                 ORDER BY id",
+            )
+            .bind(context.project_id)
+            .bind(&ticket_id)
+            .fetch_all(&mut conn)
+            .await?;
+            Ok(attachments)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Content of an attachment, as an `ArrayBuffer` on the frontend.
+#[tauri::command]
+pub async fn read_attachment(
+    db_path: String,
+    attachment_id: i64,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+) -> Result<tauri::ipc::Response, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let db_path = db.as_path();
+    let attachment = with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, true, MIN_SCHEMA_VERSION).await?;
+            fetch_attachment(&mut conn, context.project_id, attachment_id).await
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+    let blob = blob_path(&project_dir(&storage.data_dir, db_path), &attachment.sha256)?;
+    let bytes = tokio::fs::read(&blob).await.map_err(|e| {
+        AppError::Io(format!(
+            "content of attachment {} is missing: {}",
+            attachment_id, e
+        ))
+    })?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+/// Delete an attachment. Its file is removed once no other attachment of
+/// the project has the same content.
+#[tauri::command]
+pub async fn delete_attachment(
+    db_path: String,
+    attachment_id: i64,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<(), AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let _guard = maintenance_guard(&maintenance)?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    with_timeout(
+        remove_attachment(&storage.data_dir, &db, attachment_id),
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Disk usage of a project's attachments: the total, the share of each
+/// ticket and the files no longer referenced (see
+/// `cleanup_orphaned_attachments`).
+#[tauri::command]
+pub async fn attachment_storage_stats(
+    db_path: String,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+) -> Result<AttachmentStorageStats, AppError> {
+    let db_path = Path::new(&db_path);
+    files::validate_path(&app, db_path)?;
+    let tickets = with_timeout(ticket_usage(db_path), DB_TIMEOUT_MS).await?;
+    let referenced = with_timeout(referenced_hashes(db_path), DB_TIMEOUT_MS).await?;
+    let blobs = scan_blobs(project_dir(&storage.data_dir, db_path)).await?;
+
+    let orphans: Vec<&Blob> = blobs
+        .iter()
+        .filter(|blob| !referenced.contains(&blob.name))
+        .collect();
+    Ok(AttachmentStorageStats {
+        total_bytes: blobs.iter().map(|blob| blob.size).sum(),
+        tickets,
+        orphaned_blobs: orphans.len() as u64,
+        orphaned_bytes: orphans.iter().map(|blob| blob.size).sum(),
+    })
+}
+
+/// Delete the files of a project's attachment folder that no row of
+/// `attachments` refers to, in the project or in its archive database, and
+/// the hash folders left empty. With `dry_run`, nothing is deleted. Returns
+/// the bytes reclaimed (or that would be).
+#[tauri::command]
+pub async fn cleanup_orphaned_attachments(
+    db_path: String,
+    dry_run: bool,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<u64, AppError> {
+    let db_path = Path::new(&db_path);
+    files::validate_path(&app, db_path)?;
+    // Also keeps `save_attachment` from adding a blob meanwhile.
+    let _guard = maintenance_guard(&maintenance)?;
+
+    let referenced = with_timeout(referenced_hashes(db_path), DB_TIMEOUT_MS).await?;
+    let dir = project_dir(&storage.data_dir, db_path);
+    let orphans: Vec<Blob> = scan_blobs(dir.clone())
+        .await?
+        .into_iter()
+        .filter(|blob| !referenced.contains(&blob.name))
+        .collect();
+    let orphaned_bytes = orphans.iter().map(|blob| blob.size).sum();
+    if dry_run || orphans.is_empty() {
+        return Ok(orphaned_bytes);
+    }
+
+    let reclaimed = tauri::async_runtime::spawn_blocking(move || {
+        let mut reclaimed = 0;
+        for blob in &orphans {
+            match std::fs::remove_file(&blob.path) {
+                Ok(()) => reclaimed += blob.size,
+                Err(e) => log::warn!(
+                    "cleanup_orphaned_attachments: {}: {}",
+                    blob.path.display(),
+                    e
+                ),
+            }
+        }
+        remove_empty_dirs(&dir);
+        reclaimed
+    })
+    .await
+    .map_err(|e| AppError::Io(e.to_string()))?;
+    log::info!(
+        "cleanup_orphaned_attachments: reclaimed {} bytes for {}",
+        reclaimed,
+        db_path.display()
+    );
+    Ok(reclaimed)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Called on the window's drag-drop event: attach `paths` to the drop
+/// target, if any, and emit `attachments:added`. Each file must exist, be
+/// a regular file under the size limit and not have a denylisted
+/// extension; rejected files are listed with their error. Dropped files
+/// are read wherever they are, since the user picked them.
+pub fn handle_file_drop(app: &AppHandle, paths: Vec<PathBuf>) {
+    let Some(target) = app
+        .state::<DropTargetState>()
+        .target
+        .lock()
+        .unwrap()
+        .clone()
+    else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let added = attach_dropped(&app, target, paths).await;
+        log::info!(
+            "handle_file_drop: {} attached, {} rejected",
+            added.attachments.len(),
+            added.rejected.len()
+        );
+        app.emit(ATTACHMENTS_ADDED_EVENT, added).ok();
+    });
+}
+
+async fn attach_dropped(
+    app: &AppHandle,
+    target: DropTarget,
+    paths: Vec<PathBuf>,
+) -> AttachmentsAdded {
+    let denylist = drop_denylist(&app.state::<TelemetryState>()).await;
+    let mut rejected = Vec::new();
+    let reject = |path: &Path, error: AppError| RejectedFile {
+        path: path.to_string_lossy().into_owned(),
+        error: error.to_string(),
+    };
+    let mut accepted = Vec::new();
+    for path in paths {
+        match check_dropped_file(&path, &denylist) {
+            Ok(()) => accepted.push(path),
+            Err(e) => rejected.push(reject(&path, e)),
+        }
+    }
+
+    let mut attachments = Vec::new();
+    if !accepted.is_empty() {
+        let maintenance = app.state::<MaintenanceState>();
+        match maintenance_guard(&maintenance) {
+            Ok(_guard) => {
+                let _paused = fs_watch::pause_project_watch(app);
+                let data_dir = &app.state::<StorageState>().data_dir;
+                let db_path = Path::new(&target.db_path);
+                for path in accepted {
+                    let source = AttachmentSource::File(path.clone());
+                    match store_attachment(data_dir, db_path, &target.ticket_id, source, None).await
+                    {
+                        Ok(attachment) => attachments.push(attachment),
+                        Err(e) => rejected.push(reject(&path, e)),
+                    }
+                }
+            }
+            Err(e) => {
+                let error = e.to_string();
+                rejected.extend(accepted.iter().map(|path| RejectedFile {
+                    path: path.to_string_lossy().into_owned(),
+                    error: error.clone(),
+                }));
+            }
+        };
+    }
+    AttachmentsAdded {
+        db_path: target.db_path,
+        ticket_id: target.ticket_id,
+        attachments,
+        rejected,
+    }
+}
+
+/// Check a dropped path before it is copied.
+fn check_dropped_file(path: &Path, denylist: &[String]) -> Result<(), AppError> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|_| AppError::Validation(format!("file not found: {}", path.display())))?;
+    if !metadata.is_file() {
+        return Err(AppError::Validation(format!(
+            "not a regular file: {}",
+            path.display()
+        )));
+    }
+    if metadata.len() > MAX_ATTACHMENT_BYTES {
+        return Err(too_large());
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    if let Some(extension) = extension.filter(|ext| denylist.contains(ext)) {
+        return Err(AppError::Validation(format!(
+            ".{} files cannot be attached",
+            extension
+        )));
+    }
+    Ok(())
+}
+
+/// The saved drop denylist, or `DEFAULT_DROP_DENYLIST`.
+pub(crate) async fn drop_denylist(state: &TelemetryState) -> Vec<String> {
+    kv::get(&state.pool(), DROP_DENYLIST_KV)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("drop_denylist: {}", e);
+            None
+        })
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| {
+            DEFAULT_DROP_DENYLIST
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect()
+        })
+}
+
+/// Lowercase extensions without their dot, deduplicated.
+pub(crate) fn normalize_extensions(extensions: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut denylist = Vec::new();
+    for extension in extensions {
+        let extension = extension
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(AppError::Validation(format!(
+                "invalid extension: {}",
+                extension
+            )));
+        }
+        if !denylist.contains(&extension) {
+            denylist.push(extension);
+        }
+    }
+    Ok(denylist)
+}
+
+/// Hash `source` into the project's attachment folder, reusing an
+/// existing blob, and add its row. Callers hold the maintenance lock and
+/// pause the project watch.
+async fn store_attachment(
+    data_dir: &Path,
+    db_path: &Path,
+    ticket_id: &str,
+    source: AttachmentSource,
+    filename: Option<String>,
+) -> Result<Attachment, AppError> {
+    let filename = filename
+        .or_else(|| {
+            match &source {
+                AttachmentSource::File(path) => path.file_name(),
+                AttachmentSource::Bytes(_) => None,
+            }
+            .map(|name| name.to_string_lossy().into_owned())
+        })
+        .map(|name| sanitize_filename(&name))
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| AppError::Validation("attachment has no file name".into()))?;
+
+    let (mut conn, project_id) = open_for_ticket(db_path, ticket_id).await?;
+
+    let dir = project_dir(data_dir, db_path);
+    std::fs::create_dir_all(&dir)?;
+    let staged = dir.join(format!("{}{}", STAGING_PREFIX, now_ms()));
+    let staged_result = match source {
+        AttachmentSource::File(source) => {
+            let staged = staged.clone();
+            tauri::async_runtime::spawn_blocking(move || copy_hashed(&source, &staged))
+                .await
+                .map_err(|e| AppError::Io(e.to_string()))?
+        }
+        AttachmentSource::Bytes(bytes) => write_hashed(&bytes, &staged),
+    };
+    let (sha256, size) = staged_result.inspect_err(|_| {
+        std::fs::remove_file(&staged).ok();
+    })?;
+
+    let stored = async {
+        let blob = blob_path(&dir, &sha256)?;
+        if !blob.is_file() {
+            let used = project_usage(&mut conn, project_id).await?;
+            if used + size > PROJECT_QUOTA_BYTES {
+                return Err(AppError::QuotaExceeded(format!(
+                    "attachments would use {} of the {} bytes allowed per project",
+                    used + size,
+                    PROJECT_QUOTA_BYTES
+                )));
+            }
+            std::fs::create_dir_all(blob.parent().unwrap_or(&dir))?;
+            std::fs::rename(&staged, &blob)?;
+        }
+
+        let id = sqlx::query(
+            "INSERT INTO attachments (project_id, item_id, filename, mime_type, size_bytes, sha256)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(project_id)
+        .bind(ticket_id)
+        .bind(&filename)
+        .bind(mime_type(&filename))
+        .bind(size as i64)
+        .bind(&sha256)
+        .execute(&mut conn)
+        .await?
+        .last_insert_rowid();
+        // The attachment is saved either way; it just won't be searchable.
+        if let Some(text) = search::attachment_text(&filename, &blob, size) {
+            if let Err(e) = search::index_attachment_text(&mut conn, id, &text).await {
+                log::warn!("cannot index the text of {}: {}", filename, e);
+            }
+        }
+        fetch_attachment(&mut conn, project_id, id).await
+    };
+    let result = with_timeout(stored, DB_TIMEOUT_MS).await;
+    // Already moved into place, or a duplicate of an existing blob.
+    std::fs::remove_file(&staged).ok();
+    result
+}
+
+/// Delete attachment `attachment_id` and, when no other attachment of the
+/// project shares its content, its file. Callers hold the maintenance lock
+/// and pause the project watch.
+async fn remove_attachment(
+    data_dir: &Path,
+    db_path: &Path,
+    attachment_id: i64,
+) -> Result<(), AppError> {
+    let orphan = with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, false, MIN_SCHEMA_VERSION).await?;
+            let attachment = fetch_attachment(&mut conn, context.project_id, attachment_id).await?;
+            let blob = blob_path(&project_dir(data_dir, db_path), &attachment.sha256)?;
+            sqlx::query("DELETE FROM attachments WHERE id = ?")
+                .bind(attachment_id)
+                .execute(&mut conn)
+                .await?;
+            search::unindex_attachment(&mut conn, attachment_id).await?;
+            let shared: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM attachments WHERE project_id = ? AND sha256 = ?",
+            )
+            .bind(context.project_id)
+            .bind(&attachment.sha256)
+            .fetch_one(&mut conn)
+            .await?;
+            Ok((shared == 0).then_some(blob))
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    if let Some(blob) = orphan {
+        if let Err(e) = std::fs::remove_file(&blob) {
+            log::warn!("delete_attachment: {}: {}", blob.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Open a project for writing and check that `ticket_id` belongs to it.
+async fn open_for_ticket(
+    db_path: &Path,
+    ticket_id: &str,
+) -> Result<(SqliteConnection, i64), AppError> {
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, false, MIN_SCHEMA_VERSION).await?;
+            let exists: Option<i64> =
+                sqlx::query_scalar("SELECT 1 FROM backlog_items WHERE id = ? AND project_id = ?")
+                    .bind(ticket_id)
+                    .bind(context.project_id)
+                    .fetch_optional(&mut conn)
+                    .await?;
+            if exists.is_none() {
+                return Err(AppError::Validation(format!(
+                    "no ticket with id {}",
+                    ticket_id
+                )));
+            }
+            Ok((conn, context.project_id))
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+async fn fetch_attachment(
+    conn: &mut SqliteConnection,
+    project_id: i64,
+    id: i64,
+) -> Result<Attachment, AppError> {
+    sqlx::query_as(
+        "SELECT id, item_id, filename, mime_type, size_bytes, sha256, created_at
+         FROM attachments WHERE id = ? AND project_id = ?",
+    )
+    .bind(id)
+    .bind(project_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::Validation(format!("no attachment with id {}", id)))
+}
+
+/// Bytes used by the distinct files attached in the project.
+async fn project_usage(conn: &mut SqliteConnection, project_id: i64) -> Result<u64, AppError> {
+    let used: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(size_bytes), 0) FROM (
+           SELECT MAX(size_bytes) AS size_bytes FROM attachments
+           WHERE project_id = ? GROUP BY sha256
+         )",
+    )
+    .bind(project_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(used.max(0) as u64)
+}
+
+/// Attachment count and size of each ticket, largest first.
+async fn ticket_usage(db_path: &Path) -> Result<Vec<TicketAttachmentUsage>, AppError> {
+    let mut conn = open_read_only(db_path).await?;
+    let rows: Vec<(String, i64, i64, bool)> = sqlx::query_as(
+        "SELECT a.item_id, COUNT(*), SUM(a.size_bytes),
+           EXISTS (SELECT 1 FROM backlog_items WHERE id = a.item_id)
+             OR EXISTS (SELECT 1 FROM archived_items WHERE id = a.item_id)
+         FROM attachments a
+         GROUP BY a.item_id
+         ORDER BY SUM(a.size_bytes) DESC, a.item_id",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(item_id, attachments, bytes, ticket_exists)| TicketAttachmentUsage {
+                item_id,
+                attachments,
+                bytes,
+                ticket_exists,
+            },
+        )
+        .collect())
+}
+
+/// Hashes referenced by `attachments` in the project and, when it has one,
+/// in its archive database.
+async fn referenced_hashes(db_path: &Path) -> Result<HashSet<String>, AppError> {
+    let mut conn = open_read_only(db_path).await?;
+    let mut hashes: HashSet<String> =
+        sqlx::query_scalar::<_, String>("SELECT DISTINCT sha256 FROM attachments")
+            .fetch_all(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+
+    let archive = archive_db::archive_path(db_path);
+    if archive.is_file() {
+        let mut conn = project_db::open_read_only(&archive).await?;
+        let has_table: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'attachments')",
+        )
+        .fetch_one(&mut conn)
+        .await?;
+        if has_table {
+            hashes.extend(
+                sqlx::query_scalar::<_, String>("SELECT DISTINCT sha256 FROM attachments")
+                    .fetch_all(&mut conn)
+                    .await?,
+            );
+        }
+    }
+    Ok(hashes)
+}
+
+/// Attachments of `ticket_id` with the path of their content, oldest
+/// first. None when the project's schema predates attachments.
+pub(crate) async fn ticket_files(
+    data_dir: &Path,
+    db_path: &Path,
+    ticket_id: &str,
+) -> Result<Vec<(Attachment, PathBuf)>, AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    if project_db::schema_version(&mut conn).await? < MIN_SCHEMA_VERSION {
+        return Ok(Vec::new());
+    }
+    let attachments: Vec<Attachment> = sqlx::query_as(
+        "SELECT id, item_id, filename, mime_type, size_bytes, sha256, created_at
+         FROM attachments WHERE item_id = ? ORDER BY id",
+    )
+    .bind(ticket_id)
+    .fetch_all(&mut conn)
+    .await?;
+    let dir = project_dir(data_dir, db_path);
+    attachments
+        .into_iter()
+        .map(|attachment| {
+            let path = blob_path(&dir, &attachment.sha256)?;
+            Ok((attachment, path))
+        })
+        .collect()
+}
+
+async fn open_read_only(db_path: &Path) -> Result<SqliteConnection, AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let version = project_db::schema_version(&mut conn).await?;
+    if version < MIN_SCHEMA_VERSION {
+        return Err(AppError::Validation(format!(
+            "project database schema v{} has no attachments (need v{})",
+            version, MIN_SCHEMA_VERSION
+        )));
+    }
+    Ok(conn)
+}
+
+/// Files of the hash folders of `dir`, including staging files left by an
+/// interrupted `save_attachment`.
+async fn scan_blobs(dir: PathBuf) -> Result<Vec<Blob>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut blobs = Vec::new();
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(blobs),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if path.is_dir() {
+                for file in std::fs::read_dir(&path)?.flatten() {
+                    let metadata = file.metadata()?;
+                    if metadata.is_file() {
+                        blobs.push(Blob {
+                            path: file.path(),
+                            name: file.file_name().to_string_lossy().into_owned(),
+                            size: metadata.len(),
+                        });
+                    }
+                }
+            } else if name.starts_with(STAGING_PREFIX) {
+                blobs.push(Blob {
+                    size: entry.metadata()?.len(),
+                    path,
+                    name,
+                });
+            }
+        }
+        Ok(blobs)
+    })
+    .await
+    .map_err(|e| AppError::Io(e.to_string()))?
+}
+
+/// Remove the hash folders of `dir` that are empty.
+fn remove_empty_dirs(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_empty = std::fs::read_dir(&path).is_ok_and(|mut files| files.next().is_none());
+        if path.is_dir() && is_empty {
+            std::fs::remove_dir(&path).ok();
+        }
+    }
+}
+
+/// Copy `source` to `dest` while hashing it, refusing files over
+/// `MAX_ATTACHMENT_BYTES`. Returns the hex sha256 and the size.
+fn copy_hashed(source: &Path, dest: &Path) -> Result<(String, u64), AppError> {
+    let mut input = std::fs::File::open(source)?;
+    let mut output = std::fs::File::create(dest)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    let mut size = 0u64;
+    loop {
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        size += read as u64;
+        if size > MAX_ATTACHMENT_BYTES {
+            return Err(too_large());
+        }
+        hasher.update(&buffer[..read]);
+        output.write_all(&buffer[..read])?;
+    }
+    output.sync_all()?;
+    Ok((hex(&hasher.finalize()), size))
+}
+
+fn write_hashed(bytes: &[u8], dest: &Path) -> Result<(String, u64), AppError> {
+    if bytes.len() as u64 > MAX_ATTACHMENT_BYTES {
+        return Err(too_large());
+    }
+    std::fs::write(dest, bytes)?;
+    Ok((hex(&Sha256::digest(bytes)), bytes.len() as u64))
+}
+
+fn too_large() -> AppError {
+    AppError::Validation(format!(
+        "attachment is larger than {} bytes",
+        MAX_ATTACHMENT_BYTES
+    ))
+}
+
+/// Directory of the attachments of the project owning `db_path`.
+fn project_dir(data_dir: &Path, db_path: &Path) -> PathBuf {
+    data_dir
+        .join(ATTACHMENTS_DIR)
+        .join(backup::project_name(db_path))
+}
+
+/// Path of the content with hash `sha256`. The hash comes from the project
+/// database, so anything but 64 hex digits is refused rather than joined.
+fn blob_path(project_dir: &Path, sha256: &str) -> Result<PathBuf, AppError> {
+    if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::Validation(format!(
+            "invalid attachment hash: {}",
+            sha256
+        )));
+    }
+    Ok(project_dir.join(&sha256[..2]).join(sha256))
+}
+
+/// Last path component of `name`, without control characters, at most
+/// `MAX_FILENAME_CHARS` characters.
+fn sanitize_filename(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+pub(crate) fn mime_type(filename: &str) -> &'static str {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    MIME_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map_or("application/octet-stream", |(_, mime)| mime)
+}
+
+fn maintenance_guard(
+    maintenance: &MaintenanceState,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation("a migration is in progress".into()));
+    }
+    maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+
+    /// The two tickets attachments are added to.
+    const TICKETS: &str = "
+        INSERT INTO backlog_items (id, project_id, section_id, type, title, raw_markdown) VALUES
+            ('BUG-1', 1, 1, 'BUG', 'Crash on save', '### BUG-1 | Crash on save'),
+            ('BUG-2', 1, 1, 'BUG', 'Slow', '### BUG-2 | Slow');
+    ";
+
+    /// A project database in `dir/<name>/` and the data dir next to it.
+    async fn project(dir: &Path) -> (PathBuf, PathBuf) {
+        let project = dir.join("client-a");
+        std::fs::create_dir_all(&project).unwrap();
+        let seed = [BOARD, TICKETS].concat();
+        let path = create_db(&project, project_db::SUPPORTED_SCHEMA_VERSION, &seed).await;
+        (path, dir.join("data"))
+    }
+
+    fn bytes(content: &[u8]) -> AttachmentSource {
+        AttachmentSource::Bytes(content.to_vec())
+    }
+
+    #[tokio::test]
+    async fn same_content_is_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, data_dir) = project(dir.path()).await;
+
+        let first = store_attachment(&data_dir, &db, "BUG-1", bytes(b"log"), Some("a.log".into()))
+            .await
+            .unwrap();
+        let source = dir.path().join("copy.log");
+        std::fs::write(&source, b"log").unwrap();
+        let second = store_attachment(
+            &data_dir,
+            &db,
+            "BUG-2",
+            AttachmentSource::File(source),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(first.sha256, second.sha256);
+        assert_eq!(first.sha256, hex(&Sha256::digest(b"log")));
+        assert_eq!(second.filename, "copy.log");
+        let blobs = scan_blobs(project_dir(&data_dir, &db)).await.unwrap();
+        assert_eq!(blobs.len(), 1, "one file for both rows, no staging left");
+        assert_eq!(blobs[0].name, first.sha256);
+        assert_eq!(std::fs::read(&blobs[0].path).unwrap(), b"log");
+    }
+
+    #[tokio::test]
+    async fn file_is_removed_with_its_last_attachment() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, data_dir) = project(dir.path()).await;
+        let first = store_attachment(&data_dir, &db, "BUG-1", bytes(b"png"), Some("a.png".into()))
+            .await
+            .unwrap();
+        let second = store_attachment(&data_dir, &db, "BUG-2", bytes(b"png"), Some("b.png".into()))
+            .await
+            .unwrap();
+        let blob = blob_path(&project_dir(&data_dir, &db), &first.sha256).unwrap();
+
+        remove_attachment(&data_dir, &db, first.id).await.unwrap();
+        assert!(blob.is_file(), "still used by {}", second.id);
+        remove_attachment(&data_dir, &db, second.id).await.unwrap();
+        assert!(!blob.exists());
+
+        let err = remove_attachment(&data_dir, &db, second.id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn tampered_hashes_never_reach_the_file_system() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, data_dir) = project(dir.path()).await;
+        let victim = dir.path().join("victim.txt");
+        std::fs::write(&victim, "keep me").unwrap();
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        let id = sqlx::query(
+            "INSERT INTO attachments (project_id, item_id, filename, mime_type, size_bytes, sha256)
+             VALUES (1, 'BUG-1', 'v.txt', 'text/plain', 7, '../../../../victim.txt')",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+        let err = remove_attachment(&data_dir, &db, id).await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+        assert!(victim.is_file());
+        let err = ticket_files(&data_dir, &db, "BUG-1").await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+        assert!(blob_path(&data_dir, "ab").is_err());
+    }
+
+    #[tokio::test]
+    async fn new_content_past_the_quota_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, data_dir) = project(dir.path()).await;
+        let kept = store_attachment(
+            &data_dir,
+            &db,
+            "BUG-1",
+            bytes(b"kept"),
+            Some("k.txt".into()),
+        )
+        .await
+        .unwrap();
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        sqlx::query(
+            "INSERT INTO attachments (project_id, item_id, filename, mime_type, size_bytes, sha256)
+             VALUES (1, 'BUG-1', 'big.bin', 'application/octet-stream', ?, 'ff00')",
+        )
+        .bind((PROJECT_QUOTA_BYTES - 4) as i64)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        let err = store_attachment(
+            &data_dir,
+            &db,
+            "BUG-2",
+            bytes(b"new!"),
+            Some("n.txt".into()),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::QuotaExceeded(_)), "{:?}", err);
+        // A copy of stored content takes no new space.
+        store_attachment(
+            &data_dir,
+            &db,
+            "BUG-2",
+            bytes(b"kept"),
+            Some("k2.txt".into()),
+        )
+        .await
+        .unwrap();
+        let blobs = scan_blobs(project_dir(&data_dir, &db)).await.unwrap();
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].name, kept.sha256);
+    }
+
+    #[tokio::test]
+    async fn unknown_ticket_and_missing_name_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, data_dir) = project(dir.path()).await;
+
+        let err = store_attachment(&data_dir, &db, "BUG-9", bytes(b"x"), Some("x.txt".into()))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(m) if m.contains("BUG-9")),
+            "{:?}",
+            err
+        );
+        let err = store_attachment(&data_dir, &db, "BUG-1", bytes(b"x"), Some("/\n".into()))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(m) if m.contains("name")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn oversized_content_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("staged");
+        let big = vec![0u8; MAX_ATTACHMENT_BYTES as usize + 1];
+        assert!(matches!(
+            write_hashed(&big, &dest),
+            Err(AppError::Validation(_))
+        ));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn filenames_are_sanitized() {
+        assert_eq!(sanitize_filename("C:\\Users\\me\\shot.png"), "shot.png");
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename(" notes\u{7}.txt "), "notes.txt");
+        let long = "a".repeat(MAX_FILENAME_CHARS + 10);
+        assert_eq!(sanitize_filename(&long).chars().count(), MAX_FILENAME_CHARS);
+    }
+
+    #[test]
+    fn mime_type_follows_the_extension() {
+        assert_eq!(mime_type("Shot.PNG"), "image/png");
+        assert_eq!(mime_type("archive"), "application/octet-stream");
+        assert_eq!(mime_type("payload.exe"), "application/octet-stream");
+    }
+}
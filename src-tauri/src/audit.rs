@@ -0,0 +1,196 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const MAX_AUDIT_ROWS: i64 = 10_000;
+const MAX_SUMMARY_CHARS: usize = 256;
+const MAX_AUDIT_QUERY_LIMIT: i64 = 1_000;
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// audit trail for security-sensitive commands.
+pub const AUDIT_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        command_name TEXT NOT NULL,
+        args_summary TEXT NOT NULL,
+        called_at INTEGER NOT NULL,
+        outcome TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_audit_called ON audit_log(called_at DESC);
+";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A single row of `audit_log`, as returned by `get_audit_log`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub command_name: String,
+    pub args_summary: String,
+    pub called_at: i64,
+    pub outcome: String,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Return the most recent audit entries, newest first.
+#[tauri::command]
+pub async fn get_audit_log(
+    limit: i64,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<AuditEntry>, AppError> {
+    let limit = limit.clamp(1, MAX_AUDIT_QUERY_LIMIT);
+    with_timeout(
+        async {
+            let entries = sqlx::query_as::<_, AuditEntry>(
+                "SELECT id, command_name, args_summary, called_at, outcome
+                 FROM audit_log
+                 ORDER BY called_at DESC, id DESC
+                 LIMIT ?",
+            )
+            .bind(limit)
+            .fetch_all(&state.pool())
+            .await?;
+            Ok(entries)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Record a call to a security-sensitive command. Call at the end of the
+/// command with a short, human-readable `args_summary` (file names, counts —
+/// never raw SQL or secrets). Failures are logged, never propagated.
+pub async fn audit_log_command(pool: &SqlitePool, name: &str, args_summary: &str, outcome: &str) {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    let insert = sqlx::query(
+        "INSERT INTO audit_log (command_name, args_summary, called_at, outcome)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(name)
+    .bind(sanitize_summary(args_summary))
+    .bind(now_ms)
+    .bind(outcome)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = insert {
+        log::error!("audit_log_command: insert failed: {}", e);
+        return;
+    }
+
+    // Prune oldest entries beyond MAX_AUDIT_ROWS.
+    let prune = sqlx::query(
+        "DELETE FROM audit_log WHERE id IN (
+             SELECT id FROM audit_log ORDER BY called_at ASC
+             LIMIT MAX(0, (SELECT COUNT(*) FROM audit_log) - ?)
+         )",
+    )
+    .bind(MAX_AUDIT_ROWS)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = prune {
+        log::error!("audit_log_command: prune failed: {}", e);
+    }
+}
+
+/// Outcome string for a command result: `"ok"` or `"error:<Kind>"`.
+/// Only the error kind is stored — messages may contain user data.
+pub fn outcome_of<T>(result: &Result<T, AppError>) -> String {
+    match result {
+        Ok(_) => "ok".to_string(),
+        Err(e) => format!("error:{}", e.kind()),
+    }
+}
+
+/// Strip control characters and cap length so a summary can't smuggle
+/// multi-line payloads into the log.
+fn sanitize_summary(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_SUMMARY_CHARS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(AUDIT_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn summaries_are_sanitized() {
+        let pool = pool().await;
+        let summary = format!("backup.db\n{}\u{1b}[31m", "x".repeat(MAX_SUMMARY_CHARS));
+        audit_log_command(&pool, "restore_backup", &summary, "ok").await;
+
+        let stored: String = sqlx::query_scalar("SELECT args_summary FROM audit_log")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored.chars().count(), MAX_SUMMARY_CHARS);
+        assert!(stored.starts_with("backup.dbxxx"), "{}", stored);
+        assert!(!stored.contains(char::is_control));
+    }
+
+    #[tokio::test]
+    async fn oldest_rows_are_pruned() {
+        let pool = pool().await;
+        sqlx::query(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?)
+             INSERT INTO audit_log (command_name, args_summary, called_at, outcome)
+             SELECT 'old', '', i, 'ok' FROM n",
+        )
+        .bind(MAX_AUDIT_ROWS)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        audit_log_command(&pool, "new", "", "ok").await;
+
+        let (count, oldest): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), MIN(called_at) FROM audit_log")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, MAX_AUDIT_ROWS);
+        assert_eq!(oldest, 2);
+    }
+
+    #[test]
+    fn outcomes_keep_only_the_error_kind() {
+        assert_eq!(outcome_of(&Ok::<_, AppError>(())), "ok");
+        let failed: Result<(), _> = Err(AppError::Validation("secret path".into()));
+        assert_eq!(outcome_of(&failed), "error:Validation");
+    }
+}
@@ -0,0 +1,478 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnection;
+use tauri::AppHandle;
+
+use crate::audit;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::disk;
+use crate::error::AppError;
+use crate::files::{self, PathScope};
+use crate::fs_watch;
+use crate::pre_migration;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+use crate::vacuum;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Default backup location inside the project, shared with `src/db/backup.ts`.
+pub const BACKUP_DIR: &str = ".backlog-backups";
+
+/// Suffix appended to the files a restore replaces.
+const PRE_RESTORE_SUFFIX: &str = ".pre-restore";
+
+/// SQLite sidecar files that belong with a database in WAL mode.
+const SIDECAR_SUFFIXES: [&str; 2] = ["-wal", "-shm"];
+
+/// Table every project database has since schema version 1.
+const REQUIRED_TABLE: &str = "backlog_items";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `backup_project_db`.
+#[derive(Debug, Serialize)]
+pub struct BackupResult {
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// Tauri managed state serializing the operations that snapshot or replace
+/// project databases: restores, scheduled backups and schema migrations.
+#[derive(Default)]
+pub struct MaintenanceState {
+    /// Held for the duration of a restore or scheduled backup.
+    pub lock: tokio::sync::Mutex<()>,
+    /// Set by the frontend migration runner (`set_migration_in_progress`).
+    pub migration_in_progress: AtomicBool,
+}
+
+/// Return value of `restore_project_db`.
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub restored_path: String,
+    /// Where the previous database was moved (`None` if there was none).
+    pub safety_copy_path: Option<String>,
+    pub schema_version: i64,
+    pub size_bytes: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Write a consistent copy of a project database to
+/// `<dest_dir>/<project>-YYYYMMDD-HHMMSS.db` (default `dest_dir`: the
+/// project's `.backlog-backups`). Safe to run while the frontend has the
+/// database open: the WAL is checkpointed, then `VACUUM INTO` snapshots the
+/// database from a second connection. Both paths must lie inside the app
+/// data directory or the fs scope.
+#[tauri::command]
+pub async fn backup_project_db(
+    db_path: String,
+    dest_dir: Option<String>,
+    app: AppHandle,
+) -> Result<BackupResult, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let dest_dir = dest_dir
+        .map(|dir| files::validate_path(&app, Path::new(&dir)))
+        .transpose()?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    with_timeout(backup_database(&db, dest_dir.as_deref()), DB_TIMEOUT_MS).await
+}
+
+/// Replace `target_path` with a validated backup. The current database (and
+/// its `-wal`/`-shm` files) is kept as `*.pre-restore`. Refuses to touch a
+/// database the frontend still has open unless `connection_closed` confirms
+/// it has called `closeDatabase()` first.
+#[tauri::command]
+pub async fn restore_project_db(
+    backup_path: String,
+    target_path: String,
+    connection_closed: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<RestoreReport, AppError> {
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            let target = Path::new(&target_path);
+            if !connection_closed.unwrap_or(false)
+                && project_db::is_open_in_frontend(&app, target).await
+            {
+                return Err(AppError::Validation(
+                    "target database is open; close it before restoring".into(),
+                ));
+            }
+            restore_database(&PathScope::of(&app), Path::new(&backup_path), target).await
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "restore_project_db",
+        &format!("{} -> {}", file_name(&backup_path), target_path),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Called by the frontend migration runner around schema migrations so
+/// scheduled backups never snapshot a half-migrated database. When the run
+/// ends, pre-migration snapshots are settled (see `pre_migration`).
+#[tauri::command]
+pub fn set_migration_in_progress(
+    active: bool,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) {
+    maintenance
+        .migration_in_progress
+        .store(active, Ordering::SeqCst);
+    if !active {
+        tauri::async_runtime::spawn(async move { pre_migration::settle_pending(&app).await });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Back up `db_path` into `dest_dir` (or the project's `.backlog-backups`).
+pub async fn backup_database(
+    db_path: &Path,
+    dest_dir: Option<&Path>,
+) -> Result<BackupResult, AppError> {
+    let dest_dir = match dest_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => db_path
+            .parent()
+            .map(|parent| parent.join(BACKUP_DIR))
+            .ok_or_else(|| AppError::Validation("database path has no parent".into()))?,
+    };
+
+    let key = if project_db::is_encrypted(db_path) {
+        let key = project_db::project_key(db_path).ok_or_else(|| {
+            AppError::Unauthorized("unlock the project before backing it up".into())
+        })?;
+        Some(key)
+    } else {
+        None
+    };
+    let mut conn = project_db::open_connection(db_path).await?;
+    project_db::checkpoint_truncate(&mut conn).await?;
+
+    std::fs::create_dir_all(&dest_dir)?;
+    disk::ensure_free_space(&dest_dir, vacuum::database_size(db_path))?;
+    let output = backup_path(&dest_dir, &project_name(db_path));
+
+    let result = match key {
+        Some(key) => export_keyed(&mut conn, &output, &key).await,
+        None => sqlx::query("VACUUM INTO ?")
+            .bind(output.to_string_lossy().into_owned())
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(AppError::from),
+    };
+    if let Err(e) = result {
+        // Don't leave a truncated file behind (e.g. disk full mid-write).
+        let _ = std::fs::remove_file(&output);
+        return Err(e);
+    }
+
+    let size_bytes = std::fs::metadata(&output)?.len();
+    Ok(BackupResult {
+        path: output.to_string_lossy().into_owned(),
+        size_bytes,
+    })
+}
+
+/// Copy an encrypted project into `output` under the same passphrase.
+/// `VACUUM INTO` would not carry the key over, so SQLCipher's own export is
+/// used. The backup's key is registered so it can be validated and restored
+/// this session.
+async fn export_keyed(
+    conn: &mut SqliteConnection,
+    output: &Path,
+    key: &str,
+) -> Result<(), AppError> {
+    let version = project_db::schema_version(conn).await?;
+    // ATTACH cannot create the file with the flags `conn` was opened with.
+    std::fs::File::create(output)?;
+    sqlx::query("ATTACH DATABASE ? AS backup KEY ?")
+        .bind(output.to_string_lossy().into_owned())
+        .bind(key)
+        .execute(&mut *conn)
+        .await?;
+    let exported = async {
+        sqlx::query("SELECT sqlcipher_export('backup')")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(&format!("PRAGMA backup.user_version = {};", version))
+            .execute(&mut *conn)
+            .await
+    }
+    .await;
+    sqlx::query("DETACH DATABASE backup")
+        .execute(&mut *conn)
+        .await?;
+    exported?;
+    project_db::set_project_key(output, Some(key));
+    Ok(())
+}
+
+/// Name used in backup file names: the project directory, since every
+/// project database is called `backlog.db`.
+pub fn project_name(db_path: &Path) -> String {
+    db_path
+        .parent()
+        .and_then(Path::file_name)
+        .or_else(|| db_path.file_stem())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "project".to_string())
+}
+
+/// Timestamped, non-existing output path (`VACUUM INTO` refuses to
+/// overwrite). Two backups within the same second get a numeric suffix.
+fn backup_path(dest_dir: &Path, project: &str) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut path = dest_dir.join(format!("{}-{}.db", project, stamp));
+    let mut suffix = 1;
+    while path.exists() {
+        path = dest_dir.join(format!("{}-{}-{}.db", project, stamp, suffix));
+        suffix += 1;
+    }
+    path
+}
+
+/// Validate `backup` and copy it over `target`, keeping the previous files
+/// as `*.pre-restore`. On a failed copy the previous files are put back.
+/// Both paths must lie inside `scope`.
+pub async fn restore_database(
+    scope: &PathScope,
+    backup: &Path,
+    target: &Path,
+) -> Result<RestoreReport, AppError> {
+    let backup = &scope.validate(backup)?;
+    let target = &scope.validate(target)?;
+    let schema_version = validate_backup(backup).await?;
+
+    let target_parent = target
+        .parent()
+        .ok_or_else(|| AppError::Validation("target path has no parent".into()))?;
+    std::fs::create_dir_all(target_parent)?;
+
+    // Move the current database and its sidecars out of the way. A stale
+    // WAL left next to the restored file would be replayed into it.
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for path in with_sidecars(target) {
+        if path.exists() {
+            let safety = append_suffix(&path, PRE_RESTORE_SUFFIX);
+            if let Err(e) = std::fs::rename(&path, &safety) {
+                undo_moves(&moved);
+                return Err(e.into());
+            }
+            moved.push((path, safety));
+        }
+    }
+    let safety_copy_path = moved
+        .first()
+        .filter(|(path, _)| path == target)
+        .map(|(_, safety)| safety.to_string_lossy().into_owned());
+
+    // Copy via a temporary name so `target` never holds a partial file.
+    // Backups made by `src/db/backup.ts` may carry a `-wal` with the latest
+    // pages; it travels with the database (the `-shm` is rebuilt by SQLite).
+    if let Err(e) = copy_into_place(backup, target) {
+        for path in with_sidecars(target) {
+            let _ = std::fs::remove_file(path);
+        }
+        undo_moves(&moved);
+        return Err(e.into());
+    }
+
+    let size_bytes = std::fs::metadata(target)?.len();
+    Ok(RestoreReport {
+        restored_path: target.to_string_lossy().into_owned(),
+        safety_copy_path,
+        schema_version,
+        size_bytes,
+    })
+}
+
+/// Check that `backup` is a healthy project database this build can open.
+/// Returns its schema version.
+async fn validate_backup(backup: &Path) -> Result<i64, AppError> {
+    let mut conn = project_db::open_read_only(backup).await?;
+
+    let problems = project_db::integrity_problems(&mut conn).await?;
+    if !problems.is_empty() {
+        return Err(AppError::Validation(format!(
+            "backup failed integrity check: {}",
+            problems.join("; ")
+        )));
+    }
+
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+    )
+    .bind(REQUIRED_TABLE)
+    .fetch_one(&mut conn)
+    .await?;
+    if !has_table {
+        return Err(AppError::Validation(
+            "backup is not a Ticketflow project database".into(),
+        ));
+    }
+
+    let version = project_db::schema_version(&mut conn).await?;
+    if version > project_db::SUPPORTED_SCHEMA_VERSION {
+        return Err(AppError::Validation(format!(
+            "backup schema version {} is newer than supported version {}",
+            version,
+            project_db::SUPPORTED_SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// Copy `backup` (and its `-wal`, if any) to `target` through staging files.
+fn copy_into_place(backup: &Path, target: &Path) -> std::io::Result<()> {
+    let backup_wal = append_suffix(backup, SIDECAR_SUFFIXES[0]);
+    let mut pairs = vec![(backup.to_path_buf(), target.to_path_buf())];
+    if backup_wal.exists() {
+        pairs.push((backup_wal, append_suffix(target, SIDECAR_SUFFIXES[0])));
+    }
+
+    for (source, dest) in &pairs {
+        let staging = append_suffix(dest, ".restoring");
+        let copied = std::fs::copy(source, &staging).and_then(|_| std::fs::rename(&staging, dest));
+        if copied.is_err() {
+            let _ = std::fs::remove_file(&staging);
+        }
+        copied?;
+    }
+    Ok(())
+}
+
+/// `db_path` followed by its `-wal` and `-shm` sidecar paths.
+fn with_sidecars(db_path: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![db_path.to_path_buf()];
+    paths.extend(
+        SIDECAR_SUFFIXES
+            .iter()
+            .map(|suffix| append_suffix(db_path, suffix)),
+    );
+    paths
+}
+
+pub fn append_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Best-effort rollback of the renames done before a failed restore.
+fn undo_moves(moved: &[(PathBuf, PathBuf)]) {
+    for (original, safety) in moved {
+        if let Err(e) = std::fs::rename(safety, original) {
+            log::error!(
+                "restore_database: cannot move {} back: {}",
+                safety.display(),
+                e
+            );
+        }
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, items, project, BOARD, INVOICE};
+
+    #[tokio::test]
+    async fn encrypted_project_is_backed_up_under_its_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain");
+        std::fs::create_dir(&plain).unwrap();
+        let seed = [BOARD, INVOICE].concat();
+        let plain = create_db(&plain, project_db::SUPPORTED_SCHEMA_VERSION, &seed).await;
+        let db = dir.path().join(project_db::PROJECT_DB_FILE);
+        crate::encryption::export_encrypted(&plain, &db, "correct horse")
+            .await
+            .unwrap();
+        assert!(project_db::is_encrypted(&db));
+
+        let dest = dir.path().join("backups");
+        let locked = backup_database(&db, Some(&dest)).await.unwrap_err();
+        assert!(matches!(locked, AppError::Unauthorized(_)));
+
+        project_db::set_project_key(&db, Some("correct horse"));
+        let backup = backup_database(&db, Some(&dest)).await.unwrap();
+        let output = Path::new(&backup.path);
+        assert!(output.starts_with(&dest));
+        assert!(project_db::is_encrypted(output));
+
+        let mut conn = project_db::open_read_only(output).await.unwrap();
+        let title: String = sqlx::query_scalar("SELECT title FROM backlog_items")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(title, "Invoice client");
+        assert_eq!(
+            project_db::schema_version(&mut conn).await.unwrap(),
+            project_db::SUPPORTED_SCHEMA_VERSION
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_paths_outside_the_scope() {
+        let data = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let backup = project(data.path()).await;
+        let victim = outside.path().join("notes.txt");
+        std::fs::write(&victim, "keep me").unwrap();
+        let scope = PathScope::data_dir(data.path());
+
+        let refused = restore_database(&scope, &backup, &victim).await;
+        assert!(matches!(refused, Err(AppError::Unauthorized(_))));
+        assert_eq!(std::fs::read_to_string(&victim).unwrap(), "keep me");
+        assert!(!append_suffix(&victim, PRE_RESTORE_SUFFIX).exists());
+
+        let outside_backup = project(outside.path()).await;
+        let target = data
+            .path()
+            .join("restored")
+            .join(project_db::PROJECT_DB_FILE);
+        std::fs::create_dir(target.parent().unwrap()).unwrap();
+        let refused = restore_database(&scope, &outside_backup, &target).await;
+        assert!(matches!(refused, Err(AppError::Unauthorized(_))));
+        assert!(!target.exists());
+
+        let report = restore_database(&scope, &backup, &target).await.unwrap();
+        assert_eq!(report.schema_version, project_db::SUPPORTED_SCHEMA_VERSION);
+        assert_eq!(report.safety_copy_path, None);
+        assert_eq!(items(&target).await.len(), 1);
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup::{self, MaintenanceState};
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::fs_watch;
+use crate::kv;
+use crate::project_db;
+use crate::storage::StorageState;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key holding the schedule as JSON.
+pub(crate) const SCHEDULE_KV: &str = "backup_schedule";
+
+/// Scheduled backups live in `<data_dir>/backups/<project>/`.
+const BACKUPS_DIR: &str = "backups";
+
+/// How often the scheduler wakes up to look for due backups.
+const CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
+const BACKUP_COMPLETED_EVENT: &str = "backup:completed";
+const BACKUP_FAILED_EVENT: &str = "backup:failed";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// User-configurable backup cadence and retention.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    /// Minimum time between two backups of the same project.
+    pub interval_hours: u32,
+    /// Number of most recent days for which the newest backup is kept.
+    pub keep_daily: u32,
+    /// Number of most recent ISO weeks for which the newest backup is kept.
+    pub keep_weekly: u32,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// A backup file, as returned by `list_backups`.
+#[derive(Debug, Serialize)]
+pub struct BackupEntry {
+    pub path: String,
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// Modification time in Unix milliseconds.
+    pub created_at: i64,
+}
+
+/// Payload of `backup:completed`.
+#[derive(Debug, Clone, Serialize)]
+struct BackupCompleted {
+    project: String,
+    path: String,
+    size_bytes: u64,
+}
+
+/// Payload of `backup:failed`.
+#[derive(Debug, Clone, Serialize)]
+struct BackupFailed {
+    project: String,
+    error: AppError,
+}
+
+/// Tauri managed state for the backup scheduler.
+pub struct BackupScheduleState {
+    pub schedule: Mutex<BackupSchedule>,
+    /// Wakes the scheduler early when the schedule changes.
+    wake: tokio::sync::Notify,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Load the schedule from `kv_store`, register `BackupScheduleState` and
+/// spawn the scheduler task. Called once from `lib.rs` during app setup,
+/// after `TelemetryState` and `MaintenanceState` are managed.
+pub fn init_backup_scheduler(app: &AppHandle) {
+    let pool = app.state::<TelemetryState>().pool();
+    let schedule = tauri::async_runtime::block_on(kv::get(&pool, SCHEDULE_KV))
+        .unwrap_or_else(|e| {
+            log::warn!("init_backup_scheduler: cannot read schedule: {}", e);
+            None
+        })
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    app.manage(BackupScheduleState {
+        schedule: Mutex::new(schedule),
+        wake: tokio::sync::Notify::new(),
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            run_due_backups(&app).await;
+            let state = app.state::<BackupScheduleState>();
+            let _ = tokio::time::timeout(
+                Duration::from_secs(CHECK_INTERVAL_SECS),
+                state.wake.notified(),
+            )
+            .await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Return the current backup schedule.
+#[tauri::command]
+pub fn get_backup_schedule(
+    state: tauri::State<'_, BackupScheduleState>,
+) -> Result<BackupSchedule, AppError> {
+    Ok(state.schedule.lock().unwrap().clone())
+}
+
+/// Persist a new backup schedule and apply it immediately.
+#[tauri::command]
+pub async fn set_backup_schedule(
+    schedule: BackupSchedule,
+    state: tauri::State<'_, BackupScheduleState>,
+    telemetry: tauri::State<'_, TelemetryState>,
+) -> Result<BackupSchedule, AppError> {
+    validate_schedule(&schedule)?;
+
+    let json = serde_json::to_string(&schedule)
+        .map_err(|e| AppError::Validation(format!("invalid schedule: {}", e)))?;
+    with_timeout(
+        async {
+            kv::set(&telemetry.pool(), SCHEDULE_KV, &json).await?;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    state.replace(schedule.clone());
+    Ok(schedule)
+}
+
+/// List the scheduled backups of `project` (a project directory name),
+/// newest first.
+#[tauri::command]
+pub fn list_backups(
+    project: String,
+    storage: tauri::State<'_, StorageState>,
+) -> Result<Vec<BackupEntry>, AppError> {
+    if project.is_empty() || project.contains(['/', '\\']) || project == ".." {
+        return Err(AppError::Validation(format!(
+            "invalid project name: {}",
+            project
+        )));
+    }
+    let dir = backups_dir(&storage.data_dir, &project);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let entries = scan_backups(&dir)?
+        .into_iter()
+        .map(|(path, modified, size_bytes)| BackupEntry {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().into_owned(),
+            size_bytes,
+            created_at: unix_ms(modified),
+        })
+        .collect();
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+impl BackupScheduleState {
+    /// Apply a schedule already saved to `kv_store`.
+    pub(crate) fn replace(&self, schedule: BackupSchedule) {
+        *self.schedule.lock().unwrap() = schedule;
+        self.wake.notify_one();
+    }
+}
+
+/// Reject schedules that never run or keep nothing.
+pub(crate) fn validate_schedule(schedule: &BackupSchedule) -> Result<(), AppError> {
+    if schedule.interval_hours == 0 {
+        return Err(AppError::Validation(
+            "interval_hours must be at least 1".into(),
+        ));
+    }
+    if schedule.keep_daily == 0 && schedule.keep_weekly == 0 {
+        return Err(AppError::Validation(
+            "retention must keep at least one backup".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Back up every open project database that is due, then prune old copies.
+/// Skipped entirely while a restore or migration is running.
+async fn run_due_backups(app: &AppHandle) {
+    let schedule = app
+        .state::<BackupScheduleState>()
+        .schedule
+        .lock()
+        .unwrap()
+        .clone();
+    if !schedule.enabled {
+        return;
+    }
+
+    let maintenance = app.state::<MaintenanceState>();
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        log::info!("run_due_backups: migration in progress, skipping");
+        return;
+    }
+    let Ok(_guard) = maintenance.lock.try_lock() else {
+        log::info!("run_due_backups: restore in progress, skipping");
+        return;
+    };
+    let _paused = fs_watch::pause_project_watch(app);
+
+    let data_dir = app.state::<StorageState>().data_dir.clone();
+    let interval = Duration::from_secs(u64::from(schedule.interval_hours) * 3600);
+
+    for db_path in project_db::open_database_paths(app).await {
+        let project = backup::project_name(&db_path);
+        let dir = backups_dir(&data_dir, &project);
+        if !is_due(&db_path, &dir, interval) {
+            continue;
+        }
+
+        match backup::backup_database(&db_path, Some(&dir)).await {
+            Ok(result) => {
+                if let Err(e) = prune_backups(&dir, &schedule) {
+                    log::warn!("run_due_backups: prune failed for {}: {}", project, e);
+                }
+                app.emit(
+                    BACKUP_COMPLETED_EVENT,
+                    BackupCompleted {
+                        project,
+                        path: result.path,
+                        size_bytes: result.size_bytes,
+                    },
+                )
+                .ok();
+            }
+            Err(error) => {
+                log::error!("run_due_backups: backup of {} failed: {}", project, error);
+                app.emit(BACKUP_FAILED_EVENT, BackupFailed { project, error })
+                    .ok();
+            }
+        }
+    }
+}
+
+/// A project is due when its newest backup is older than `interval` and the
+/// database (or its WAL) was modified after that backup.
+fn is_due(db_path: &Path, dir: &Path, interval: Duration) -> bool {
+    let newest = scan_backups(dir)
+        .ok()
+        .and_then(|backups| backups.first().map(|(_, modified, _)| *modified));
+    let Some(newest) = newest else {
+        return true;
+    };
+
+    if newest.elapsed().unwrap_or_default() < interval {
+        return false;
+    }
+
+    let mut wal = db_path.as_os_str().to_os_string();
+    wal.push("-wal");
+    [db_path.to_path_buf(), PathBuf::from(wal)]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .any(|modified| modified > newest)
+}
+
+/// Delete backups not covered by the retention policy: the newest backup of
+/// each of the last `keep_daily` days and of the last `keep_weekly` weeks
+/// are kept.
+fn prune_backups(dir: &Path, schedule: &BackupSchedule) -> std::io::Result<()> {
+    let backups = scan_backups(dir)?;
+
+    let mut keep: HashSet<PathBuf> = HashSet::new();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for (path, modified, _) in &backups {
+        let local: DateTime<Local> = (*modified).into();
+        let day = local.date_naive();
+        if days.len() < schedule.keep_daily as usize && days.insert(day) {
+            keep.insert(path.clone());
+        }
+        let week = local.iso_week();
+        if weeks.len() < schedule.keep_weekly as usize && weeks.insert(week) {
+            keep.insert(path.clone());
+        }
+    }
+
+    for (path, _, _) in backups {
+        if !keep.contains(&path) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// `.db` files in `dir` with their modification time and size, newest first.
+fn scan_backups(dir: &Path) -> std::io::Result<Vec<(PathBuf, SystemTime, u64)>> {
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "db") {
+            let metadata = entry.metadata()?;
+            backups.push((path, metadata.modified()?, metadata.len()));
+        }
+    }
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.1));
+    Ok(backups)
+}
+
+/// Directory of the scheduled backups of `project` (see `project_name`).
+pub fn backups_dir(data_dir: &Path, project: &str) -> PathBuf {
+    data_dir.join(BACKUPS_DIR).join(project)
+}
+
+fn unix_ms(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Create `dir/name` last modified at `time`.
+    fn touch(dir: &Path, name: &str, time: SystemTime) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, name).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+        path
+    }
+
+    fn local(month: u32, day: u32, hour: u32) -> SystemTime {
+        Local
+            .with_ymd_and_hms(2024, month, day, hour, 0, 0)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_of_each_day_and_week() {
+        let dir = tempfile::tempdir().unwrap();
+        // 2024-05-15 is a Wednesday of ISO week 20.
+        let kept = [
+            touch(dir.path(), "wed-noon.db", local(5, 15, 12)),
+            touch(dir.path(), "tue.db", local(5, 14, 12)),
+            touch(dir.path(), "previous-sunday.db", local(5, 12, 12)),
+        ];
+        let pruned = [
+            touch(dir.path(), "wed-morning.db", local(5, 15, 11)),
+            touch(dir.path(), "two-weeks-ago.db", local(5, 5, 12)),
+            touch(dir.path(), "april.db", local(4, 20, 12)),
+        ];
+        let other = touch(dir.path(), "notes.txt", local(1, 1, 12));
+        let schedule = BackupSchedule {
+            keep_daily: 2,
+            keep_weekly: 2,
+            ..Default::default()
+        };
+
+        prune_backups(dir.path(), &schedule).unwrap();
+
+        for path in kept.iter().chain([&other]) {
+            assert!(path.exists(), "{} was pruned", path.display());
+        }
+        for path in &pruned {
+            assert!(!path.exists(), "{} was kept", path.display());
+        }
+        let names: Vec<_> = scan_backups(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .collect();
+        assert_eq!(names, kept, "newest first, .db files only");
+    }
+
+    #[test]
+    fn backup_is_due_after_the_interval_once_the_project_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("backups");
+        std::fs::create_dir(&backups).unwrap();
+        let day = Duration::from_secs(24 * 3600);
+        let now = SystemTime::now();
+        let db = touch(dir.path(), "backlog.db", now - 3 * day);
+
+        assert!(is_due(&db, &backups, day), "no backup yet");
+        touch(&backups, "recent.db", now - day / 2);
+        assert!(!is_due(&db, &backups, day), "interval not elapsed");
+
+        std::fs::remove_file(backups.join("recent.db")).unwrap();
+        touch(&backups, "old.db", now - 2 * day);
+        assert!(!is_due(&db, &backups, day), "unchanged since the backup");
+        touch(dir.path(), "backlog.db-wal", now);
+        assert!(is_due(&db, &backups, day), "changed in the WAL");
+    }
+
+    #[test]
+    fn schedules_must_run_and_keep_something() {
+        assert!(validate_schedule(&BackupSchedule::default()).is_ok());
+        let never = BackupSchedule {
+            interval_hours: 0,
+            ..Default::default()
+        };
+        assert!(validate_schedule(&never).is_err());
+        let keeps_nothing = BackupSchedule {
+            keep_daily: 0,
+            keep_weekly: 0,
+            ..Default::default()
+        };
+        assert!(validate_schedule(&keeps_nothing).is_err());
+    }
+}
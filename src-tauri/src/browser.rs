@@ -0,0 +1,527 @@
+use std::net::IpAddr;
+
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Identify the user's default web browser: the desktop file id on Linux
+/// (`firefox.desktop`, from the XDG `mimeapps.list` files), the bundle id on
+/// macOS (`com.apple.Safari`, from Launch Services) and the ProgId on
+/// Windows (`ChromeHTML`, from the https `UserChoice` registry key). The
+/// value can be passed back to `open_url_in_browser`.
+#[tauri::command]
+pub fn get_default_browser() -> Result<String, AppError> {
+    platform::default_browser()
+        .ok_or_else(|| AppError::Io("no default web browser is configured".into()))
+}
+
+/// Open an http(s) URL in `browser` (an id as returned by
+/// `get_default_browser`) or in the default browser. Unlike the generic
+/// "open" of the shell plugin, the browser is launched directly, so the URL
+/// never ends up in whatever other app claims the scheme. Localhost URLs are
+/// only accepted in debug builds.
+#[tauri::command]
+pub fn open_url_in_browser(
+    url: String,
+    browser: Option<String>,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    let url = validate_url(&url)?;
+    let browser = match browser {
+        Some(browser) => Some(browser.trim().to_string()),
+        None => platform::default_browser(),
+    };
+
+    let Some(browser) = browser else {
+        log::warn!("open_url_in_browser: no default browser found, using the system opener");
+        return spawn(&app, platform::OPENER, &[url.as_str().to_string()]);
+    };
+    if !platform::is_valid_browser_id(&browser) {
+        return Err(AppError::Validation(format!(
+            "invalid browser id: {}",
+            browser
+        )));
+    }
+    let (program, args) = platform::browser_command(&browser, url.as_str())?;
+    spawn(&app, &program, &args)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Accept only absolute `http://` / `https://` URLs, which rules out
+/// `javascript:`, `file:` and custom schemes. Loopback hosts are refused in
+/// release builds so a crafted link cannot reach a local service.
+fn validate_url(url: &str) -> Result<reqwest::Url, AppError> {
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    if !lower.starts_with("https://") && !lower.starts_with("http://") {
+        return Err(AppError::Validation(
+            "only http:// and https:// URLs can be opened".into(),
+        ));
+    }
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| AppError::Validation(format!("invalid URL: {}", e)))?;
+    if !cfg!(debug_assertions) && is_local_host(&parsed) {
+        return Err(AppError::Validation(
+            "localhost URLs cannot be opened".into(),
+        ));
+    }
+    Ok(parsed)
+}
+
+fn is_local_host(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return true;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || ip.is_unspecified(),
+        Err(_) => {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            host == "localhost" || host.ends_with(".localhost")
+        }
+    }
+}
+
+fn spawn(app: &AppHandle, program: &str, args: &[String]) -> Result<(), AppError> {
+    app.shell()
+        .command(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot start {}: {}", program, e)))?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use std::path::PathBuf;
+
+    use crate::error::AppError;
+
+    pub const OPENER: &str = "xdg-open";
+
+    /// MIME types whose default handler is the web browser.
+    const SCHEME_HANDLERS: [&str; 2] = ["x-scheme-handler/https", "x-scheme-handler/http"];
+
+    pub fn default_browser() -> Option<String> {
+        let data_dirs = data_dirs();
+        for list in mimeapps_lists() {
+            let Ok(content) = std::fs::read_to_string(&list) else {
+                continue;
+            };
+            for mime in SCHEME_HANDLERS {
+                let handlers = desktop_entry_value(&content, "Default Applications", mime);
+                let found = handlers
+                    .iter()
+                    .flat_map(|value| value.split(';'))
+                    .map(str::trim)
+                    .find(|id| !id.is_empty() && find_desktop_file(&data_dirs, id).is_some());
+                if let Some(id) = found {
+                    return Some(id.to_string());
+                }
+            }
+        }
+        None
+    }
+
+    pub fn is_valid_browser_id(id: &str) -> bool {
+        id.ends_with(".desktop") && !id.contains(['/', '\\']) && !id.starts_with('.')
+    }
+
+    /// Program and arguments from the `Exec` line of the browser's desktop
+    /// file, with the URL substituted for its `%u`/`%U` field code.
+    pub fn browser_command(id: &str, url: &str) -> Result<(String, Vec<String>), AppError> {
+        let file = find_desktop_file(&data_dirs(), id)
+            .ok_or_else(|| AppError::Validation(format!("browser not installed: {}", id)))?;
+        let content = std::fs::read_to_string(&file)?;
+        let exec = desktop_entry_value(&content, "Desktop Entry", "Exec")
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Io(format!("{} has no Exec line", file.display())))?;
+
+        let words = exec_words(&exec);
+        if words.is_empty() {
+            return Err(AppError::Io(format!(
+                "{} has an empty Exec line",
+                file.display()
+            )));
+        }
+
+        let mut args = Vec::new();
+        let mut has_url = false;
+        for word in words {
+            match word.as_str() {
+                "%u" | "%U" | "%f" | "%F" => {
+                    args.push(url.to_string());
+                    has_url = true;
+                }
+                // Icon, name and location field codes.
+                "%i" | "%c" | "%k" => {}
+                _ => args.push(word.replace("%%", "%")),
+            }
+        }
+        if !has_url {
+            args.push(url.to_string());
+        }
+        let program = args.remove(0);
+        Ok((program, args))
+    }
+
+    /// `mimeapps.list` files in XDG precedence order, desktop-specific
+    /// (`gnome-mimeapps.list`) before generic in each directory.
+    fn mimeapps_lists() -> Vec<PathBuf> {
+        let desktops: Vec<String> = std::env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .split(':')
+            .filter(|desktop| !desktop.is_empty())
+            .map(|desktop| format!("{}-mimeapps.list", desktop.to_ascii_lowercase()))
+            .collect();
+
+        let mut dirs: Vec<PathBuf> = dirs::config_dir().into_iter().collect();
+        dirs.extend(env_paths("XDG_CONFIG_DIRS", "/etc/xdg"));
+        dirs.extend(data_dirs().into_iter().map(|dir| dir.join("applications")));
+
+        dirs.iter()
+            .flat_map(|dir| {
+                desktops
+                    .iter()
+                    .map(String::as_str)
+                    .chain(std::iter::once("mimeapps.list"))
+                    .map(move |name| dir.join(name))
+            })
+            .collect()
+    }
+
+    /// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`.
+    fn data_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+        dirs.extend(env_paths("XDG_DATA_DIRS", "/usr/local/share:/usr/share"));
+        dirs
+    }
+
+    fn env_paths(var: &str, default: &str) -> Vec<PathBuf> {
+        let value = std::env::var(var)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| default.to_string());
+        value
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn find_desktop_file(data_dirs: &[PathBuf], id: &str) -> Option<PathBuf> {
+        data_dirs
+            .iter()
+            .map(|dir| dir.join("applications").join(id))
+            .find(|path| path.is_file())
+    }
+
+    /// Values of `key` in the `[group]` section of a desktop-entry style
+    /// file (`mimeapps.list`, `*.desktop`).
+    pub(super) fn desktop_entry_value(content: &str, group: &str, key: &str) -> Vec<String> {
+        let header = format!("[{}]", group);
+        let mut in_group = false;
+        let mut values = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_group = line == header;
+            } else if in_group {
+                if let Some((name, value)) = line.split_once('=') {
+                    if name.trim() == key {
+                        values.push(value.trim().to_string());
+                    }
+                }
+            }
+        }
+        values
+    }
+
+    /// Split an `Exec` value into words: whitespace separates, double quotes
+    /// group, and a backslash escapes the next character inside quotes.
+    pub(super) fn exec_words(exec: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        let mut in_word = false;
+        let mut quoted = false;
+        let mut chars = exec.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    in_word = true;
+                }
+                '\\' if quoted => current.extend(chars.next()),
+                c if c.is_whitespace() && !quoted => {
+                    if in_word {
+                        words.push(std::mem::take(&mut current));
+                        in_word = false;
+                    }
+                }
+                c => {
+                    current.push(c);
+                    in_word = true;
+                }
+            }
+        }
+        if in_word {
+            words.push(current);
+        }
+        words
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2_app_kit::NSWorkspace;
+    use objc2_foundation::{NSBundle, NSString, NSURL};
+
+    use crate::error::AppError;
+
+    pub const OPENER: &str = "open";
+
+    /// Bundle id of the app Launch Services opens https URLs with.
+    pub fn default_browser() -> Option<String> {
+        // `unused_unsafe`: these bindings are safe in recent objc2 releases
+        // and unsafe in older ones.
+        #[allow(unused_unsafe)]
+        unsafe {
+            let probe = NSURL::URLWithString(&NSString::from_str("https://example.com"))?;
+            let app = NSWorkspace::sharedWorkspace().URLForApplicationToOpenURL(&probe)?;
+            let bundle = NSBundle::bundleWithURL(&app)?;
+            Some(bundle.bundleIdentifier()?.to_string())
+        }
+    }
+
+    pub fn is_valid_browser_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    }
+
+    pub fn browser_command(id: &str, url: &str) -> Result<(String, Vec<String>), AppError> {
+        Ok((
+            OPENER.to_string(),
+            vec!["-b".to_string(), id.to_string(), url.to_string()],
+        ))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{
+        RegGetValueW, HKEY, HKEY_CLASSES_ROOT, HKEY_CURRENT_USER, RRF_RT_REG_SZ,
+    };
+
+    use crate::error::AppError;
+
+    pub const OPENER: &str = "explorer";
+
+    const USER_CHOICE_KEY: &str =
+        r"Software\Microsoft\Windows\Shell\Associations\UrlAssociations\https\UserChoice";
+
+    /// ProgId the user chose for https links.
+    pub fn default_browser() -> Option<String> {
+        read_string(HKEY_CURRENT_USER, USER_CHOICE_KEY, Some("ProgId"))
+    }
+
+    pub fn is_valid_browser_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    }
+
+    /// Program and arguments from the ProgId's `shell\open\command`, with
+    /// the URL substituted for `%1`.
+    pub fn browser_command(id: &str, url: &str) -> Result<(String, Vec<String>), AppError> {
+        let command = read_string(
+            HKEY_CLASSES_ROOT,
+            &format!(r"{}\shell\open\command", id),
+            None,
+        )
+        .ok_or_else(|| AppError::Validation(format!("browser not installed: {}", id)))?;
+
+        let mut words = command_words(&command).into_iter();
+        let program = words
+            .next()
+            .ok_or_else(|| AppError::Io(format!("empty open command for {}", id)))?;
+        let mut has_url = false;
+        let mut args: Vec<String> = words
+            .map(|word| {
+                if word.contains("%1") {
+                    has_url = true;
+                    word.replace("%1", url)
+                } else {
+                    word
+                }
+            })
+            .collect();
+        if !has_url {
+            args.push(url.to_string());
+        }
+        Ok((program, args))
+    }
+
+    fn read_string(root: HKEY, key: &str, value: Option<&str>) -> Option<String> {
+        let key = HSTRING::from(key);
+        let value = value.map(HSTRING::from);
+        let value_ptr = value
+            .as_ref()
+            .map_or(PCWSTR::null(), |value| PCWSTR(value.as_ptr()));
+
+        let mut buffer = [0u16; 1024];
+        let mut size = (buffer.len() * 2) as u32;
+        // SAFETY: `buffer` is writable for `size` bytes and both strings
+        // outlive the call.
+        let status = unsafe {
+            RegGetValueW(
+                root,
+                &key,
+                value_ptr,
+                RRF_RT_REG_SZ,
+                None,
+                Some(buffer.as_mut_ptr().cast()),
+                Some(&mut size),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return None;
+        }
+        let len = (size as usize / 2).saturating_sub(1).min(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..len]))
+    }
+
+    /// Split a registry command line: whitespace separates and double
+    /// quotes group (backslashes are path separators, not escapes).
+    fn command_words(command: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut current = String::new();
+        let mut in_word = false;
+        let mut quoted = false;
+        for c in command.chars() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    in_word = true;
+                }
+                c if c.is_whitespace() && !quoted => {
+                    if in_word {
+                        words.push(std::mem::take(&mut current));
+                        in_word = false;
+                    }
+                }
+                c => {
+                    current.push(c);
+                    in_word = true;
+                }
+            }
+        }
+        if in_word {
+            words.push(current);
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_file_and_custom_schemes_are_refused() {
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(document.cookie)",
+            "  javascript:alert(1)",
+            "file:///etc/passwd",
+            "FILE://C:/Windows/win.ini",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox",
+            "ticketflow://open",
+            "//example.com",
+            "example.com",
+            "",
+        ] {
+            assert!(
+                matches!(validate_url(url), Err(AppError::Validation(_))),
+                "{} was accepted",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn http_and_https_urls_are_accepted() {
+        let url = validate_url(" https://example.com/tickets?id=BUG-1 ").unwrap();
+        assert_eq!(url.as_str(), "https://example.com/tickets?id=BUG-1");
+        assert!(validate_url("HTTP://example.com").is_ok());
+        assert!(validate_url("http://").is_err());
+    }
+
+    #[test]
+    fn loopback_hosts_are_local() {
+        for url in [
+            "http://localhost:1420",
+            "http://LOCALHOST./",
+            "http://app.localhost",
+            "http://127.0.0.1",
+            "http://127.8.9.10",
+            "http://[::1]:8080",
+            "http://0.0.0.0",
+        ] {
+            assert!(is_local_host(&reqwest::Url::parse(url).unwrap()), "{}", url);
+        }
+        for url in [
+            "https://example.com",
+            "http://192.168.1.10",
+            "http://localhost.example.com",
+        ] {
+            assert!(
+                !is_local_host(&reqwest::Url::parse(url).unwrap()),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn desktop_exec_lines_are_split_like_the_spec() {
+        assert_eq!(
+            platform::exec_words(r#"/usr/bin/firefox --name "Fire Fox" "a\"b" %u"#),
+            ["/usr/bin/firefox", "--name", "Fire Fox", "a\"b", "%u"]
+        );
+        assert_eq!(platform::exec_words(r#"app """#), ["app", ""]);
+        let desktop = "[Desktop Entry]\nName=Firefox\nExec=firefox %u\n[Desktop Action new]\nExec=firefox --new-window\n";
+        assert_eq!(
+            platform::desktop_entry_value(desktop, "Desktop Entry", "Exec"),
+            ["firefox %u"]
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn browser_ids_cannot_name_paths() {
+        assert!(platform::is_valid_browser_id("firefox.desktop"));
+        for id in [
+            "../evil.desktop",
+            "/usr/share/applications/x.desktop",
+            ".desktop",
+            "firefox",
+        ] {
+            assert!(!platform::is_valid_browser_id(id), "{}", id);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::vacuum;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Attempts after the first when a reader or writer blocks the checkpoint.
+const BUSY_RETRIES: u32 = 4;
+
+/// First retry delay; doubled after every attempt.
+const BUSY_BACKOFF_MS: u64 = 250;
+
+/// Largest accepted `PRAGMA wal_autocheckpoint` threshold, in pages.
+const MAX_AUTOCHECKPOINT_PAGES: u32 = 100_000;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `checkpoint_project_db`.
+#[derive(Debug, Serialize)]
+pub struct CheckpointResult {
+    /// Frames in the WAL when the last attempt started.
+    pub wal_frames: i64,
+    /// Frames copied into the database file.
+    pub checkpointed_frames: i64,
+    /// Whether the WAL could not be fully checkpointed and truncated
+    /// because the database stayed busy.
+    pub busy: bool,
+    pub attempts: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Copy the WAL of a project database into the database file and truncate
+/// it, so sync clients see one up-to-date file. A busy database is retried
+/// with backoff; `busy` in the result reports a checkpoint that still could
+/// not complete.
+#[tauri::command]
+pub async fn checkpoint_project_db(
+    db_path: String,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<CheckpointResult, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation(
+            "a schema migration is in progress".into(),
+        ));
+    }
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is already in progress".into()))?;
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    with_timeout(checkpoint_with_retry(&db), DB_TIMEOUT_MS).await
+}
+
+/// Set how many WAL pages trigger an automatic checkpoint on the backend's
+/// connections to a project, for the rest of the session. SQLite's default
+/// of 1000 makes for rarer but longer checkpoints; a smaller threshold
+/// spreads the work out. The pragma is per connection, so it does not reach
+/// the frontend's tauri-plugin-sql pool.
+#[tauri::command]
+pub async fn db_set_wal_autocheckpoint(
+    db_path: String,
+    pages: u32,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(set_autocheckpoint(&db, pages), DB_TIMEOUT_MS).await
+}
+
+/// WAL autocheckpoint threshold, in pages, of the backend's connections to
+/// a project.
+#[tauri::command]
+pub async fn db_get_wal_autocheckpoint(db_path: String, app: AppHandle) -> Result<u32, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(autocheckpoint_pages(&db), DB_TIMEOUT_MS).await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Checkpoint every project database the frontend has open, e.g. when the
+/// window is hidden to the tray. Skipped while maintenance is running.
+pub async fn checkpoint_open_projects(app: &AppHandle) {
+    let maintenance = app.state::<MaintenanceState>();
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return;
+    }
+    let Ok(_guard) = maintenance.lock.try_lock() else {
+        return;
+    };
+    let _paused = fs_watch::pause_project_watch(app);
+
+    for db_path in project_db::open_database_paths(app).await {
+        if project_db::is_read_only(&db_path) {
+            continue;
+        }
+        match checkpoint_with_retry(&db_path).await {
+            Ok(result) if result.busy => log::warn!(
+                "checkpoint_open_projects: {} still busy after {} attempts",
+                db_path.display(),
+                result.attempts
+            ),
+            Ok(result) => log::info!(
+                "checkpoint_open_projects: {}: {} frame(s) checkpointed",
+                db_path.display(),
+                result.checkpointed_frames
+            ),
+            Err(e) => log::warn!("checkpoint_open_projects: {}: {}", db_path.display(), e),
+        }
+    }
+}
+
+/// `PRAGMA wal_checkpoint(TRUNCATE)`, retried with backoff while the
+/// database is busy, whether SQLite fails with SQLITE_BUSY or reports an
+/// incomplete checkpoint.
+async fn checkpoint_with_retry(db_path: &Path) -> Result<CheckpointResult, AppError> {
+    let mut conn = with_timeout(project_db::open_connection(db_path), DB_TIMEOUT_MS).await?;
+    let mut backoff = Duration::from_millis(BUSY_BACKOFF_MS);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = sqlx::query_as::<_, (i64, i64, i64)>("PRAGMA wal_checkpoint(TRUNCATE);")
+            .fetch_one(&mut conn)
+            .await;
+        match result {
+            Ok((busy, wal_frames, checkpointed_frames)) if busy == 0 || attempts > BUSY_RETRIES => {
+                return Ok(CheckpointResult {
+                    wal_frames,
+                    checkpointed_frames,
+                    busy: busy != 0,
+                    attempts,
+                });
+            }
+            Ok(_) => {}
+            Err(e) if vacuum::is_busy(&e) && attempts <= BUSY_RETRIES => {}
+            Err(e) => return Err(e.into()),
+        }
+        log::info!(
+            "checkpoint: {} busy, retry {}/{}",
+            db_path.display(),
+            attempts,
+            BUSY_RETRIES
+        );
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+/// Record the threshold of `db_path`, then check that a new connection
+/// applies it.
+async fn set_autocheckpoint(db_path: &Path, pages: u32) -> Result<(), AppError> {
+    if pages == 0 || pages > MAX_AUTOCHECKPOINT_PAGES {
+        return Err(AppError::Validation(format!(
+            "pages must be between 1 and {}",
+            MAX_AUTOCHECKPOINT_PAGES
+        )));
+    }
+    project_db::set_wal_autocheckpoint(db_path, pages);
+
+    let applied = autocheckpoint_pages(db_path).await?;
+    if applied != pages {
+        return Err(AppError::Database(format!(
+            "wal_autocheckpoint is {} instead of {}",
+            applied, pages
+        )));
+    }
+    Ok(())
+}
+
+/// `PRAGMA wal_autocheckpoint` of a new connection to `db_path`.
+async fn autocheckpoint_pages(db_path: &Path) -> Result<u32, AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let pages: i64 = sqlx::query_scalar("PRAGMA wal_autocheckpoint;")
+        .fetch_one(&mut conn)
+        .await?;
+    Ok(u32::try_from(pages).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    async fn create_db(dir: &Path) -> std::path::PathBuf {
+        test_support::create_db(dir, project_db::SUPPORTED_SCHEMA_VERSION, "").await
+    }
+
+    #[tokio::test]
+    async fn threshold_is_set_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(dir.path()).await;
+        // SQLite's default.
+        assert_eq!(autocheckpoint_pages(&db).await.unwrap(), 1000);
+
+        set_autocheckpoint(&db, 250).await.unwrap();
+        assert_eq!(autocheckpoint_pages(&db).await.unwrap(), 250);
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        let pages: i64 = sqlx::query_scalar("PRAGMA wal_autocheckpoint;")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(pages, 250);
+    }
+
+    #[tokio::test]
+    async fn out_of_range_thresholds_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(dir.path()).await;
+        set_autocheckpoint(&db, 500).await.unwrap();
+
+        for pages in [0, MAX_AUTOCHECKPOINT_PAGES + 1] {
+            assert!(matches!(
+                set_autocheckpoint(&db, pages).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert_eq!(autocheckpoint_pages(&db).await.unwrap(), 500);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{storage, telemetry};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const VERSION_FLAG: &str = "--version";
+const HELP_FLAG: &str = "--help";
+const FLUSH_TELEMETRY_FLAG: &str = "--flush-telemetry";
+
+const USAGE: &str = "\
+Usage: ticketflow [OPTIONS]
+
+Options:
+  --portable         Store data in <exe_dir>/data instead of the app data directory
+  --flush-telemetry  Send the offline telemetry queue, print a summary and exit
+  --version          Print version information and exit
+  --help             Print this help and exit";
+
+// ---------------------------------------------------------------------------
+// Entry point (called at the top of `run()`)
+// ---------------------------------------------------------------------------
+
+/// Handle flags that must run without a window or tray. Returns the process
+/// exit code when a flag was handled, `None` to continue with the normal GUI
+/// startup.
+pub fn handle_headless_flags() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match headless_flag(&args)? {
+        VERSION_FLAG => println!("{}", version_string()),
+        HELP_FLAG => println!("{}\n\n{}", version_string(), USAGE),
+        _ => return Some(flush_telemetry()),
+    }
+    Some(0)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// The headless flag among `args` to handle, `--version` first, then
+/// `--help`, then `--flush-telemetry`.
+fn headless_flag(args: &[String]) -> Option<&'static str> {
+    [VERSION_FLAG, HELP_FLAG, FLUSH_TELEMETRY_FLAG]
+        .into_iter()
+        .find(|flag| args.iter().any(|arg| arg == flag))
+}
+
+fn version_string() -> String {
+    format!(
+        "Ticketflow {} ({} {}, {})",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_GIT_SHA"),
+        env!("CARGO_BUILD_DATE"),
+        env!("CARGO_TARGET_TRIPLE"),
+    )
+}
+
+fn flush_telemetry() -> i32 {
+    let mode = storage::detect_storage_mode();
+    let data_dir = match storage::resolve_headless_data_dir(mode) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("flush-telemetry: {}", e);
+            return 1;
+        }
+    };
+
+    match tauri::async_runtime::block_on(telemetry::headless_flush(&data_dir)) {
+        Ok(summary) => {
+            println!(
+                "flush-telemetry: sent {} event(s), {} still queued ({})",
+                summary.sent,
+                summary.remaining,
+                data_dir.join("telemetry.db").display()
+            );
+            if summary.remaining > 0 {
+                2
+            } else {
+                0
+            }
+        }
+        Err(e) => {
+            eprintln!("flush-telemetry: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn version_wins_over_other_flags() {
+        let flag = headless_flag(&args(&["--flush-telemetry", "--help", "--version"]));
+        assert_eq!(flag, Some(VERSION_FLAG));
+        assert_eq!(
+            headless_flag(&args(&["--portable", "--help"])),
+            Some(HELP_FLAG)
+        );
+        assert_eq!(
+            headless_flag(&args(&["--flush-telemetry"])),
+            Some(FLUSH_TELEMETRY_FLAG)
+        );
+    }
+
+    #[test]
+    fn other_arguments_start_the_gui() {
+        assert_eq!(headless_flag(&args(&[])), None);
+        assert_eq!(
+            headless_flag(&args(&["--portable", "--versions", "-h"])),
+            None
+        );
+    }
+
+    #[test]
+    fn usage_lists_every_flag() {
+        for flag in [
+            VERSION_FLAG,
+            HELP_FLAG,
+            FLUSH_TELEMETRY_FLAG,
+            storage::PORTABLE_FLAG,
+        ] {
+            assert!(USAGE.contains(flag), "{} missing from the usage", flag);
+        }
+        assert!(
+            version_string().starts_with(&format!("Ticketflow {} (", env!("CARGO_PKG_VERSION")))
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const POLL_INTERVAL_MS: u64 = 500;
+
+/// Event emitted with the new clipboard text whenever it changes.
+const CLIPBOARD_CHANGED_EVENT: &str = "clipboard:changed";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+struct ActiveWatcher {
+    id: String,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Tauri managed state tracking the (single) clipboard watcher.
+#[derive(Default)]
+pub struct ClipboardState {
+    active: Mutex<Option<ActiveWatcher>>,
+    next_id: AtomicU64,
+}
+
+impl ClipboardState {
+    /// Register the watcher task made by `spawn` and return its id. Fails,
+    /// without spawning, when a watcher is already running.
+    fn start(
+        &self,
+        spawn: impl FnOnce() -> tauri::async_runtime::JoinHandle<()>,
+    ) -> Result<String, AppError> {
+        let mut active = self.active.lock().unwrap();
+        if let Some(watcher) = active.as_ref() {
+            return Err(AppError::Validation(format!(
+                "clipboard watcher {} is already running",
+                watcher.id
+            )));
+        }
+
+        let id = format!(
+            "clipboard-{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        *active = Some(ActiveWatcher {
+            id: id.clone(),
+            task: spawn(),
+        });
+        Ok(id)
+    }
+
+    /// Abort the watcher `watcher_id`.
+    fn stop(&self, watcher_id: &str) -> Result<(), AppError> {
+        let mut active = self.active.lock().unwrap();
+        match active.as_ref() {
+            Some(watcher) if watcher.id == watcher_id => {
+                watcher.task.abort();
+                *active = None;
+                Ok(())
+            }
+            _ => Err(AppError::Validation(format!(
+                "no clipboard watcher with id {}",
+                watcher_id
+            ))),
+        }
+    }
+}
+
+/// What the watcher last saw of the clipboard.
+struct ClipboardChange {
+    last_token: Option<i64>,
+    last_text: Option<String>,
+}
+
+impl ClipboardChange {
+    /// The new clipboard text, if it changed since the last poll. With an
+    /// OS change counter (`token`), the text is only read when the counter
+    /// moved. `read_text` gives `None` for non-text content, which is ignored.
+    fn poll(
+        &mut self,
+        token: Option<i64>,
+        read_text: impl FnOnce() -> Option<String>,
+    ) -> Option<String> {
+        if token.is_some() && token == self.last_token {
+            return None;
+        }
+        self.last_token = token;
+
+        let text = read_text()?;
+        if self.last_text.as_deref() == Some(text.as_str()) {
+            return None;
+        }
+        self.last_text = Some(text.clone());
+        Some(text)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Start polling the clipboard and emit `clipboard:changed` with the new
+/// text on every change. Only one watcher may run at a time. Returns the
+/// watcher id to pass to `unwatch_clipboard`.
+#[tauri::command]
+pub async fn watch_clipboard(
+    app: AppHandle,
+    state: tauri::State<'_, ClipboardState>,
+) -> Result<String, AppError> {
+    state.start(move || tauri::async_runtime::spawn(poll_clipboard(app)))
+}
+
+/// Stop the watcher started by `watch_clipboard`.
+#[tauri::command]
+pub fn unwatch_clipboard(
+    watcher_id: String,
+    state: tauri::State<'_, ClipboardState>,
+) -> Result<(), AppError> {
+    state.stop(&watcher_id)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// The clipboard image encoded as PNG, or `None` when it holds no image.
+pub(crate) fn read_image_png(app: &AppHandle) -> Result<Option<Vec<u8>>, AppError> {
+    let Ok(image) = app.clipboard().read_image() else {
+        return Ok(None);
+    };
+    if image.width() == 0 || image.height() == 0 {
+        return Ok(None);
+    }
+    let encode_error = |e: png::EncodingError| AppError::Io(format!("cannot encode PNG: {}", e));
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    writer
+        .write_image_data(image.rgba())
+        .map_err(encode_error)?;
+    writer.finish().map_err(encode_error)?;
+    Ok(Some(png))
+}
+
+/// Files copied to the clipboard (in a file manager), in order. Empty when
+/// the clipboard holds no file list.
+pub(crate) async fn read_file_list(app: &AppHandle) -> Result<Vec<PathBuf>, AppError> {
+    let paths = platform::copied_files(app).await?;
+    Ok(paths.into_iter().filter(|path| path.is_file()).collect())
+}
+
+/// Path of a `file://` URI, percent-decoded.
+#[cfg(target_os = "linux")]
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.trim().strip_prefix("file://")?;
+    // Skip the host part (`file://host/path`), usually empty.
+    let path = &rest[rest.find('/')?..];
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// Poll loop run by the watcher task.
+async fn poll_clipboard(app: AppHandle) {
+    let mut interval = tokio::time::interval(Duration::from_millis(POLL_INTERVAL_MS));
+    let mut change = ClipboardChange {
+        last_token: change_token(),
+        last_text: app.clipboard().read_text().ok(),
+    };
+
+    loop {
+        interval.tick().await;
+        if let Some(text) = change.poll(change_token(), || app.clipboard().read_text().ok()) {
+            app.emit(CLIPBOARD_CHANGED_EVENT, &text).ok();
+        }
+    }
+}
+
+/// OS clipboard change counter, or `None` where there is none (Linux), in
+/// which case the text itself is compared on every tick.
+#[cfg(target_os = "macos")]
+fn change_token() -> Option<i64> {
+    use objc2_app_kit::NSPasteboard;
+
+    // `unused_unsafe`: these bindings are safe in recent objc2-app-kit
+    // releases and unsafe in older ones.
+    #[allow(unused_unsafe)]
+    let count = unsafe { NSPasteboard::generalPasteboard().changeCount() };
+    Some(count as i64)
+}
+
+#[cfg(target_os = "windows")]
+fn change_token() -> Option<i64> {
+    // SAFETY: takes no arguments and only reads a global counter.
+    let sequence = unsafe { windows::Win32::System::DataExchange::GetClipboardSequenceNumber() };
+    // 0 means the caller lacks access to the window station.
+    (sequence != 0).then_some(i64::from(sequence))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn change_token() -> Option<i64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::PathBuf;
+
+    use gtk::gdk;
+    use tauri::AppHandle;
+
+    use crate::error::AppError;
+
+    /// GTK's clipboard may only be used on the main thread.
+    pub async fn copied_files(app: &AppHandle) -> Result<Vec<PathBuf>, AppError> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        app.run_on_main_thread(move || {
+            let clipboard = gtk::Clipboard::get(&gdk::Atom::intern("CLIPBOARD"));
+            let uris: Vec<String> = clipboard
+                .wait_for_uris()
+                .iter()
+                .map(|uri| uri.as_str().to_string())
+                .collect();
+            sender.send(uris).ok();
+        })
+        .map_err(|e| AppError::Io(format!("cannot read the clipboard: {}", e)))?;
+        let uris = receiver
+            .await
+            .map_err(|_| AppError::Io("cannot read the clipboard".into()))?;
+        Ok(uris
+            .iter()
+            .filter_map(|uri| super::file_uri_path(uri))
+            .collect())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::PathBuf;
+
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeFileURL};
+    use objc2_foundation::NSURL;
+    use tauri::AppHandle;
+
+    use crate::error::AppError;
+
+    pub async fn copied_files(_app: &AppHandle) -> Result<Vec<PathBuf>, AppError> {
+        // `unused_unsafe`: these bindings are safe in recent objc2-app-kit
+        // releases and unsafe in older ones.
+        #[allow(unused_unsafe)]
+        let paths = unsafe {
+            let Some(items) = NSPasteboard::generalPasteboard().pasteboardItems() else {
+                return Ok(Vec::new());
+            };
+            items
+                .iter()
+                .filter_map(|item| item.stringForType(NSPasteboardTypeFileURL))
+                // Finder copies file reference URLs (`file:///.file/id=...`).
+                .filter_map(|url| NSURL::URLWithString(&url))
+                .filter_map(|url| url.filePathURL())
+                .filter_map(|url| url.path())
+                .map(|path| PathBuf::from(path.to_string()))
+                .collect()
+        };
+        Ok(paths)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::PathBuf;
+
+    use tauri::AppHandle;
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    };
+    use windows::Win32::System::Ole::CF_HDROP;
+    use windows::Win32::UI::Shell::{DragQueryFileW, HDROP};
+
+    use crate::error::AppError;
+
+    pub async fn copied_files(_app: &AppHandle) -> Result<Vec<PathBuf>, AppError> {
+        let format = u32::from(CF_HDROP.0);
+        // SAFETY: the clipboard is opened and closed on this thread, and the
+        // HDROP is only read while it is open.
+        unsafe {
+            if IsClipboardFormatAvailable(format).is_err() {
+                return Ok(Vec::new());
+            }
+            OpenClipboard(None)
+                .map_err(|e| AppError::Io(format!("cannot open the clipboard: {}", e)))?;
+            let paths = match GetClipboardData(format) {
+                Ok(handle) => {
+                    let drop = HDROP(handle.0);
+                    let count = DragQueryFileW(drop, u32::MAX, None);
+                    (0..count)
+                        .map(|index| {
+                            let len = DragQueryFileW(drop, index, None) as usize;
+                            let mut buffer = vec![0u16; len + 1];
+                            DragQueryFileW(drop, index, Some(&mut buffer));
+                            PathBuf::from(OsString::from_wide(&buffer[..len]))
+                        })
+                        .collect()
+                }
+                Err(_) => Vec::new(),
+            };
+            CloseClipboard().ok();
+            Ok(paths)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::path::PathBuf;
+
+    use tauri::AppHandle;
+
+    use crate::error::AppError;
+
+    pub async fn copied_files(_app: &AppHandle) -> Result<Vec<PathBuf>, AppError> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn one_watcher_starts_and_stops_by_id() {
+        let state = ClipboardState::default();
+        let (alive, closed) = tokio::sync::oneshot::channel::<()>();
+        let id = state
+            .start(|| {
+                tauri::async_runtime::spawn(async move {
+                    let _alive = alive;
+                    std::future::pending::<()>().await
+                })
+            })
+            .unwrap();
+        assert_eq!(id, "clipboard-1");
+
+        let second = state.start(|| panic!("a second watcher must not be spawned"));
+        assert!(matches!(second, Err(AppError::Validation(_))));
+        assert!(matches!(
+            state.stop("clipboard-2"),
+            Err(AppError::Validation(_))
+        ));
+
+        state.stop(&id).unwrap();
+        // The aborted task drops its end of the channel.
+        assert!(closed.await.is_err());
+        assert!(state.stop(&id).is_err());
+
+        let next = state
+            .start(|| tauri::async_runtime::spawn(std::future::pending()))
+            .unwrap();
+        assert_eq!(next, "clipboard-2");
+        state.stop(&next).unwrap();
+    }
+
+    #[test]
+    fn text_changes_are_reported_once() {
+        let mut change = ClipboardChange {
+            last_token: None,
+            last_text: Some("TF-1".to_string()),
+        };
+        assert_eq!(change.poll(None, || Some("TF-1".to_string())), None);
+        assert_eq!(
+            change.poll(None, || Some("TF-2".to_string())),
+            Some("TF-2".to_string())
+        );
+        assert_eq!(change.poll(None, || Some("TF-2".to_string())), None);
+        // An image on the clipboard reads as no text.
+        assert_eq!(change.poll(None, || None), None);
+        assert_eq!(
+            change.poll(None, || Some("TF-1".to_string())),
+            Some("TF-1".to_string())
+        );
+    }
+
+    #[test]
+    fn text_is_read_only_when_the_change_counter_moves() {
+        let mut change = ClipboardChange {
+            last_token: Some(7),
+            last_text: None,
+        };
+        assert_eq!(
+            change.poll(Some(7), || panic!("read with an unchanged counter")),
+            None
+        );
+        assert_eq!(
+            change.poll(Some(8), || Some("TF-3".to_string())),
+            Some("TF-3".to_string())
+        );
+        // The counter also moves when the same text is copied again.
+        assert_eq!(change.poll(Some(9), || Some("TF-3".to_string())), None);
+    }
+}
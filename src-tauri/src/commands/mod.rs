@@ -0,0 +1,66 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Default budget for commands that mostly wait on SQLite.
+pub const DB_TIMEOUT_MS: u64 = 30_000;
+
+/// Default budget for commands that mostly wait on the network.
+pub const NETWORK_TIMEOUT_MS: u64 = 15_000;
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Run a command body with an upper bound on its duration so a slow query or
+/// request can never leave the frontend awaiting forever. The body is
+/// dropped (cancelled) on expiry and `AppError::Timeout` is returned.
+///
+/// Every async command that waits on SQLite, the network or a possibly
+/// remote file goes through here. The deliberate exceptions are the file
+/// jobs that emit progress (exports, imports, compression, hashing, PDF
+/// reports), which can rightly run for many minutes on a large project,
+/// and commands that only start background work (watchers, shell streams,
+/// windows, screenshots).
+pub async fn with_timeout<F, T>(future: F, timeout_ms: u64) -> Result<T, AppError>
+where
+    F: Future<Output = Result<T, AppError>>,
+{
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), future).await {
+        Ok(result) => result,
+        Err(_) => Err(AppError::Timeout(format!(
+            "operation timed out after {} ms",
+            timeout_ms
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn slow_operation_times_out() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        };
+        let err = with_timeout(slow, 20).await.unwrap_err();
+        assert!(matches!(err, AppError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn fast_operation_keeps_its_result() {
+        let fast = async { Ok::<_, AppError>(42) };
+        assert_eq!(with_timeout(fast, 1_000).await.unwrap(), 42);
+
+        let failed = async { Err::<(), _>(AppError::Validation("bad input".into())) };
+        let err = with_timeout(failed, 1_000).await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::kv;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+use crate::vacuum::{self, VacuumResult};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key holding the compaction settings as JSON.
+pub(crate) const COMPACTION_KV: &str = "auto_compaction";
+
+/// Delay between the check that finds a database worth compacting and the
+/// compaction itself, so it does not compete with loading the project.
+const COMPACTION_DELAY_SECS: u64 = 120;
+
+const COMPACTION_STARTED_EVENT: &str = "compaction:started";
+const COMPACTION_FINISHED_EVENT: &str = "compaction:finished";
+
+/// `PRAGMA auto_vacuum` value of a database created in incremental mode.
+const AUTO_VACUUM_INCREMENTAL: i64 = 2;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// When a project database is compacted automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionSettings {
+    pub enabled: bool,
+    /// Share of free pages, in percent, above which the database is compacted.
+    pub free_percent: u32,
+    /// Databases smaller than this are left alone.
+    pub min_size_mb: u32,
+}
+
+impl Default for CompactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            free_percent: 30,
+            min_size_mb: 4,
+        }
+    }
+}
+
+/// Return value of `check_project_compaction`.
+#[derive(Debug, Serialize)]
+pub struct CompactionCheck {
+    pub size_bytes: u64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub free_percent: f64,
+    /// Whether a compaction is scheduled (or already was) for this database.
+    pub scheduled: bool,
+}
+
+/// Payload of `compaction:started`.
+#[derive(Debug, Clone, Serialize)]
+struct CompactionStarted {
+    db_path: String,
+    free_percent: f64,
+    size_bytes: u64,
+    /// `incremental_vacuum` or `vacuum`.
+    method: &'static str,
+}
+
+/// Payload of `compaction:finished`.
+#[derive(Debug, Clone, Serialize)]
+struct CompactionFinished {
+    db_path: String,
+    before_bytes: u64,
+    after_bytes: u64,
+    error: Option<String>,
+}
+
+/// Tauri managed state for automatic compaction.
+pub struct CompactionState {
+    pub settings: Mutex<CompactionSettings>,
+    /// Databases with a compaction scheduled, by canonical path.
+    pending: Mutex<HashSet<PathBuf>>,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Load the settings from `kv_store` and register `CompactionState`. Called
+/// once from `lib.rs` during app setup, after `TelemetryState` is managed.
+pub fn init_compaction(app: &AppHandle) {
+    let pool = app.state::<TelemetryState>().pool();
+    let settings = tauri::async_runtime::block_on(kv::get(&pool, COMPACTION_KV))
+        .unwrap_or_else(|e| {
+            log::warn!("init_compaction: cannot read settings: {}", e);
+            None
+        })
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    app.manage(CompactionState {
+        settings: Mutex::new(settings),
+        pending: Mutex::new(HashSet::new()),
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Return the automatic compaction settings.
+#[tauri::command]
+pub fn get_compaction_settings(
+    state: tauri::State<'_, CompactionState>,
+) -> Result<CompactionSettings, AppError> {
+    Ok(state.settings.lock().unwrap().clone())
+}
+
+/// Persist new automatic compaction settings and apply them immediately.
+#[tauri::command]
+pub async fn set_compaction_settings(
+    settings: CompactionSettings,
+    state: tauri::State<'_, CompactionState>,
+    telemetry: tauri::State<'_, TelemetryState>,
+) -> Result<CompactionSettings, AppError> {
+    validate_settings(&settings)?;
+
+    let json = serde_json::to_string(&settings)
+        .map_err(|e| AppError::Validation(format!("invalid settings: {}", e)))?;
+    with_timeout(
+        async {
+            kv::set(&telemetry.pool(), COMPACTION_KV, &json).await?;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    state.replace(settings.clone());
+    Ok(settings)
+}
+
+/// Measure the free pages of a project database and, when automatic
+/// compaction is enabled and they exceed the threshold, schedule a
+/// compaction a couple of minutes later. Call when a project is opened and
+/// after deleting many tickets; `archive_tickets` schedules its own.
+#[tauri::command]
+pub async fn check_project_compaction(
+    db_path: String,
+    app: AppHandle,
+) -> Result<CompactionCheck, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let (size_bytes, page_count, freelist_count) =
+        with_timeout(free_pages(&db), DB_TIMEOUT_MS).await?;
+    let free_percent = percent(freelist_count, page_count);
+
+    let settings = app
+        .state::<CompactionState>()
+        .settings
+        .lock()
+        .unwrap()
+        .clone();
+    let scheduled =
+        exceeds_threshold(&settings, size_bytes, free_percent) && schedule_compaction(&app, &db);
+    Ok(CompactionCheck {
+        size_bytes,
+        page_count,
+        freelist_count,
+        free_percent,
+        scheduled,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+impl CompactionState {
+    /// Apply settings already saved to `kv_store`.
+    pub(crate) fn replace(&self, settings: CompactionSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+}
+
+/// Reject thresholds that would compact on every open or never.
+pub(crate) fn validate_settings(settings: &CompactionSettings) -> Result<(), AppError> {
+    if !(5..=95).contains(&settings.free_percent) {
+        return Err(AppError::Validation(
+            "free_percent must be between 5 and 95".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Schedule a compaction of `db_path` after `COMPACTION_DELAY_SECS`, unless
+/// one is already pending. The threshold is checked again when it runs.
+/// Returns whether a compaction is pending.
+pub fn schedule_compaction(app: &AppHandle, db_path: &Path) -> bool {
+    let Some(state) = app.try_state::<CompactionState>() else {
+        return false;
+    };
+    if !state.settings.lock().unwrap().enabled || project_db::is_read_only(db_path) {
+        return false;
+    }
+    let key = project_db::canonical(db_path);
+    if !state.pending.lock().unwrap().insert(key.clone()) {
+        return true;
+    }
+
+    let app = app.clone();
+    let db_path = db_path.to_path_buf();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(COMPACTION_DELAY_SECS)).await;
+        run_compaction(&app, &db_path).await;
+        app.state::<CompactionState>()
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&key);
+    });
+    true
+}
+
+/// Compact `db_path` if it still needs it. Skipped while a migration,
+/// import, backup or restore is running; the next check schedules it again.
+async fn run_compaction(app: &AppHandle, db_path: &Path) {
+    let settings = app
+        .state::<CompactionState>()
+        .settings
+        .lock()
+        .unwrap()
+        .clone();
+    if !settings.enabled {
+        return;
+    }
+
+    let maintenance = app.state::<MaintenanceState>();
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        log::info!("run_compaction: migration in progress, skipping");
+        return;
+    }
+    let Ok(_guard) = maintenance.lock.try_lock() else {
+        log::info!("run_compaction: maintenance in progress, skipping");
+        return;
+    };
+
+    let (size_bytes, page_count, freelist_count) =
+        match with_timeout(free_pages(db_path), DB_TIMEOUT_MS).await {
+            Ok(counts) => counts,
+            Err(e) => {
+                log::warn!("run_compaction: {}: {}", db_path.display(), e);
+                return;
+            }
+        };
+    let free_percent = percent(freelist_count, page_count);
+    if !exceeds_threshold(&settings, size_bytes, free_percent) {
+        return;
+    }
+    let incremental = matches!(
+        with_timeout(auto_vacuum_mode(db_path), DB_TIMEOUT_MS).await,
+        Ok(AUTO_VACUUM_INCREMENTAL)
+    );
+
+    let _paused = fs_watch::pause_project_watch(app);
+    let db = db_path.to_string_lossy().into_owned();
+    app.emit(
+        COMPACTION_STARTED_EVENT,
+        CompactionStarted {
+            db_path: db.clone(),
+            free_percent,
+            size_bytes,
+            method: if incremental {
+                "incremental_vacuum"
+            } else {
+                "vacuum"
+            },
+        },
+    )
+    .ok();
+
+    let result = if incremental {
+        incremental_vacuum(db_path).await
+    } else {
+        vacuum::vacuum_database(db_path, vacuum::progress_emitter(app, None)).await
+    };
+
+    let state = app.state::<TelemetryState>();
+    audit::audit_log_command(
+        &state.pool(),
+        "auto_compaction",
+        &db,
+        &audit::outcome_of(&result),
+    )
+    .await;
+
+    let finished = match result {
+        Ok(result) => CompactionFinished {
+            db_path: db,
+            before_bytes: result.before_bytes,
+            after_bytes: result.after_bytes,
+            error: None,
+        },
+        Err(e) => {
+            log::warn!("run_compaction: {}: {}", db_path.display(), e);
+            CompactionFinished {
+                db_path: db,
+                before_bytes: size_bytes,
+                after_bytes: vacuum::database_size(db_path),
+                error: Some(e.to_string()),
+            }
+        }
+    };
+    app.emit(COMPACTION_FINISHED_EVENT, finished).ok();
+}
+
+/// Size in bytes, page count and free page count of a database.
+async fn free_pages(db_path: &Path) -> Result<(u64, i64, i64), AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count;")
+        .fetch_one(&mut conn)
+        .await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size;")
+        .fetch_one(&mut conn)
+        .await?;
+    let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count;")
+        .fetch_one(&mut conn)
+        .await?;
+    let size_bytes = u64::try_from(page_count.saturating_mul(page_size)).unwrap_or(0);
+    Ok((size_bytes, page_count, freelist_count))
+}
+
+async fn auto_vacuum_mode(db_path: &Path) -> Result<i64, AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    Ok(sqlx::query_scalar("PRAGMA auto_vacuum;")
+        .fetch_one(&mut conn)
+        .await?)
+}
+
+/// Release every free page of a database in `auto_vacuum = INCREMENTAL`
+/// mode, which is much cheaper than rebuilding it.
+async fn incremental_vacuum(db_path: &Path) -> Result<VacuumResult, AppError> {
+    let started = std::time::Instant::now();
+    let before_bytes = vacuum::database_size(db_path);
+    let mut conn = project_db::open_connection(db_path).await?;
+    // The pragma frees pages one step at a time as its rows are read.
+    sqlx::query("PRAGMA incremental_vacuum;")
+        .fetch_all(&mut conn)
+        .await?;
+    project_db::checkpoint_truncate(&mut conn).await?;
+    drop(conn);
+    Ok(VacuumResult {
+        before_bytes,
+        after_bytes: vacuum::database_size(db_path),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn exceeds_threshold(settings: &CompactionSettings, size_bytes: u64, free_percent: f64) -> bool {
+    settings.enabled
+        && size_bytes >= u64::from(settings.min_size_mb) * 1024 * 1024
+        && free_percent > f64::from(settings.free_percent)
+}
+
+fn percent(part: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    part as f64 * 100.0 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions};
+    use sqlx::{ConnectOptions, Connection};
+
+    /// A database in `auto_vacuum` `mode` of about 2 MB, half of it freed.
+    async fn fragmented_db(dir: &Path, mode: SqliteAutoVacuum) -> PathBuf {
+        let path = dir.join(project_db::PROJECT_DB_FILE);
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .auto_vacuum(mode)
+            .connect()
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE blobs (id INTEGER PRIMARY KEY, data BLOB);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 256)
+             INSERT INTO blobs SELECT i, zeroblob(8000) FROM n;
+             DELETE FROM blobs WHERE id % 2 = 0;",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        path
+    }
+
+    #[test]
+    fn thresholds_outside_5_to_95_percent_are_refused() {
+        let mut settings = CompactionSettings::default();
+        assert!(validate_settings(&settings).is_ok());
+        for free_percent in [4, 96] {
+            settings.free_percent = free_percent;
+            assert!(validate_settings(&settings).is_err());
+        }
+    }
+
+    #[test]
+    fn compaction_needs_size_and_free_pages_above_the_threshold() {
+        let mut settings = CompactionSettings::default();
+        let size = 4 * 1024 * 1024;
+        assert!(exceeds_threshold(&settings, size, 30.5));
+        assert!(!exceeds_threshold(&settings, size, 30.0));
+        assert!(!exceeds_threshold(&settings, size - 1, 90.0));
+        settings.enabled = false;
+        assert!(!exceeds_threshold(&settings, size, 90.0));
+
+        assert_eq!(percent(1, 4), 25.0);
+        assert_eq!(percent(3, 0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn incremental_vacuum_releases_free_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = fragmented_db(dir.path(), SqliteAutoVacuum::Incremental).await;
+        assert_eq!(
+            auto_vacuum_mode(&db).await.unwrap(),
+            AUTO_VACUUM_INCREMENTAL
+        );
+
+        let (size_bytes, page_count, freelist_count) = free_pages(&db).await.unwrap();
+        assert!(size_bytes > 1024 * 1024);
+        assert!(percent(freelist_count, page_count) > 40.0);
+
+        let result = incremental_vacuum(&db).await.unwrap();
+        assert!(result.after_bytes < result.before_bytes);
+        let (_, _, freelist_count) = free_pages(&db).await.unwrap();
+        assert_eq!(freelist_count, 0);
+
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM blobs")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rows, 128);
+    }
+
+    #[tokio::test]
+    async fn databases_without_auto_vacuum_keep_their_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = fragmented_db(dir.path(), SqliteAutoVacuum::None).await;
+        assert_eq!(auto_vacuum_mode(&db).await.unwrap(), 0);
+
+        incremental_vacuum(&db).await.unwrap();
+        let (_, _, freelist_count) = free_pages(&db).await.unwrap();
+        assert!(freelist_count > 0);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::disk;
+use crate::error::AppError;
+use crate::files;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// zstd level used by `compress_file`: a good size/speed trade-off for
+/// SQLite files.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Emit `compress:progress` every this many bytes read from the source.
+const PROGRESS_EVERY_BYTES: u64 = 1024 * 1024;
+
+const COMPRESS_PROGRESS_EVENT: &str = "compress:progress";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `compress_file`.
+#[derive(Debug, Serialize)]
+pub struct CompressResult {
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+    /// `original_bytes / compressed_bytes`.
+    pub ratio: f64,
+}
+
+/// Payload of `compress:progress`.
+#[derive(Debug, Clone, Serialize)]
+struct CompressProgress {
+    src: String,
+    processed_bytes: u64,
+}
+
+/// Reader that reports the number of bytes read so far.
+struct ProgressReader<R, F> {
+    inner: R,
+    read: u64,
+    next_report: u64,
+    report: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.read >= self.next_report {
+            (self.report)(self.read);
+            self.next_report = self.read + PROGRESS_EVERY_BYTES;
+        }
+        Ok(n)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Compress `src` to `dest` with zstd. Emits `compress:progress` every MB
+/// read.
+#[tauri::command]
+pub async fn compress_file(
+    src: String,
+    dest: String,
+    app: AppHandle,
+) -> Result<CompressResult, AppError> {
+    let src = files::validate_path(&app, Path::new(&src))?;
+    let dest = files::validate_path(&app, Path::new(&dest))?;
+
+    let original_bytes = std::fs::metadata(&src)?.len();
+    if let Some(dir) = dest.parent() {
+        disk::ensure_free_space(dir, original_bytes)?;
+    }
+    let compressed_bytes = run_blocking("compress_file", move || {
+        stream_file(&src, &dest, progress_emitter(&app, &src), encode)
+    })
+    .await?;
+
+    Ok(CompressResult {
+        original_bytes,
+        compressed_bytes,
+        ratio: if compressed_bytes == 0 {
+            0.0
+        } else {
+            original_bytes as f64 / compressed_bytes as f64
+        },
+    })
+}
+
+/// Decompress a zstd file `src` to `dest`. Returns the uncompressed size.
+/// Emits `compress:progress` every MB read.
+#[tauri::command]
+pub async fn decompress_file(src: String, dest: String, app: AppHandle) -> Result<u64, AppError> {
+    let src = files::validate_path(&app, Path::new(&src))?;
+    let dest = files::validate_path(&app, Path::new(&dest))?;
+
+    run_blocking("decompress_file", move || {
+        stream_file(&src, &dest, progress_emitter(&app, &src), decode)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+async fn run_blocking<T: Send + 'static>(
+    name: &str,
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| AppError::Io(format!("{} task failed: {}", name, e)))?
+}
+
+fn encode(reader: &mut dyn Read, writer: &mut dyn Write) -> std::io::Result<()> {
+    zstd::stream::copy_encode(reader, writer, COMPRESSION_LEVEL)
+}
+
+fn decode(reader: &mut dyn Read, writer: &mut dyn Write) -> std::io::Result<()> {
+    zstd::stream::copy_decode(reader, writer)
+}
+
+/// Emit `compress:progress` for `src` with the bytes read so far.
+fn progress_emitter<'a>(app: &'a AppHandle, src: &Path) -> impl FnMut(u64) + 'a {
+    let src_label = src.to_string_lossy().into_owned();
+    move |processed_bytes| {
+        app.emit(
+            COMPRESS_PROGRESS_EVENT,
+            CompressProgress {
+                src: src_label.clone(),
+                processed_bytes,
+            },
+        )
+        .ok();
+    }
+}
+
+/// Pipe `src` through `transform` into `<dest>.tmp`, then rename it over
+/// `dest` so a failure never leaves a truncated output behind. Returns the
+/// size of `dest`.
+fn stream_file(
+    src: &Path,
+    dest: &Path,
+    report: impl FnMut(u64),
+    transform: impl FnOnce(&mut dyn Read, &mut dyn Write) -> std::io::Result<()>,
+) -> Result<u64, AppError> {
+    if src == dest {
+        return Err(AppError::Validation(
+            "source and destination must differ".into(),
+        ));
+    }
+
+    let mut reader = ProgressReader {
+        inner: BufReader::new(File::open(src)?),
+        read: 0,
+        next_report: PROGRESS_EVERY_BYTES,
+        report,
+    };
+
+    let tmp = tmp_path(dest);
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        transform(&mut reader, &mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&tmp, dest)
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    Ok(std::fs::metadata(dest)?.len())
+}
+
+fn tmp_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// About 4.5 MB of repetitive CSV-like text.
+    fn write_sample(path: &Path) {
+        let mut file = BufWriter::new(File::create(path).unwrap());
+        for i in 0..100_000 {
+            writeln!(file, "BUG-{},open,Crash when saving a ticket,2024-01-01", i).unwrap();
+        }
+        file.flush().unwrap();
+    }
+
+    #[test]
+    fn compressible_file_round_trips_with_ratio_above_1_5() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("tickets.csv");
+        let packed = dir.path().join("tickets.csv.zst");
+        let unpacked = dir.path().join("tickets.out.csv");
+        write_sample(&src);
+        let original_bytes = std::fs::metadata(&src).unwrap().len();
+
+        let compressed_bytes = stream_file(&src, &packed, |_| {}, encode).unwrap();
+        let ratio = original_bytes as f64 / compressed_bytes as f64;
+        assert!(ratio > 1.5, "ratio {}", ratio);
+
+        let restored_bytes = stream_file(&packed, &unpacked, |_| {}, decode).unwrap();
+        assert_eq!(restored_bytes, original_bytes);
+        assert_eq!(
+            std::fs::read(&unpacked).unwrap(),
+            std::fs::read(&src).unwrap()
+        );
+    }
+
+    #[test]
+    fn progress_is_reported_every_megabyte() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("tickets.csv");
+        write_sample(&src);
+        let size = std::fs::metadata(&src).unwrap().len();
+
+        let mut reports = Vec::new();
+        stream_file(
+            &src,
+            &dir.path().join("copy.csv"),
+            |read| reports.push(read),
+            |reader, writer| std::io::copy(reader, writer).map(|_| ()),
+        )
+        .unwrap();
+
+        assert_eq!(reports.len() as u64, size / PROGRESS_EVERY_BYTES);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] >= PROGRESS_EVERY_BYTES));
+    }
+
+    #[test]
+    fn failed_transform_leaves_no_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("tickets.csv");
+        let dest = dir.path().join("tickets.csv.zst");
+        std::fs::write(&src, "not zstd").unwrap();
+
+        assert!(stream_file(&src, &dest, |_| {}, decode).is_err());
+        assert!(!dest.exists());
+        assert!(!tmp_path(&dest).exists());
+        assert!(matches!(
+            stream_file(&src, &src, |_| {}, encode),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,378 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::storage::StorageState;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// table of recorded panics.
+pub const CRASH_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS crash_reports (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        message TEXT NOT NULL,
+        location TEXT NOT NULL,
+        backtrace TEXT NOT NULL,
+        occurred_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_crash_occurred ON crash_reports(occurred_at DESC);
+";
+
+/// File in the data directory the panic hook appends to (one JSON report
+/// per line). Its content is moved into `crash_reports` by `ingest_spool`.
+const SPOOL_FILE: &str = "crash-reports.jsonl";
+
+/// Event emitted once the main window has loaded when the previous run
+/// panicked.
+const PREVIOUS_SESSION_EVENT: &str = "crash:previous-session";
+
+const MAX_CRASH_ROWS: i64 = 100;
+const MAX_CRASH_QUERY_LIMIT: i64 = 100;
+const MAX_MESSAGE_CHARS: usize = 2_000;
+const MAX_BACKTRACE_CHARS: usize = 20_000;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A row of `crash_reports`.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CrashReport {
+    pub id: i64,
+    pub message: String,
+    /// `file:line:column` of the panic.
+    pub location: String,
+    pub backtrace: String,
+    /// Unix milliseconds.
+    pub occurred_at: i64,
+}
+
+/// Tauri managed state: number of panics found from the previous run, until
+/// `crash:previous-session` has been emitted.
+pub struct CrashState {
+    previous_session: AtomicUsize,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Record every panic in the spool file of `data_dir`, then run the
+/// previously installed hook (which prints the message). The hook writes
+/// synchronously to a plain file rather than to `telemetry.db` because it
+/// may run inside the async runtime or just before the process aborts.
+pub fn install_panic_hook(data_dir: &Path) {
+    let spool = data_dir.join(SPOOL_FILE);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic with a non-string payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let report = CrashReport {
+            id: 0,
+            message: truncate_chars(&message, MAX_MESSAGE_CHARS),
+            location,
+            backtrace: truncate_chars(
+                &std::backtrace::Backtrace::force_capture().to_string(),
+                MAX_BACKTRACE_CHARS,
+            ),
+            occurred_at: now_ms(),
+        };
+        append_to_spool(&spool, &report);
+        previous(info);
+    }));
+}
+
+/// Move the spooled reports into `crash_reports` and remember how many there
+/// were for `notify_previous_session`. Called from `lib.rs` once
+/// `telemetry.db` is open.
+pub async fn init_crash_reports(app: &AppHandle, data_dir: &Path) {
+    let state = app.state::<TelemetryState>();
+    let found = ingest_spool(&state.pool(), data_dir).await;
+    app.manage(CrashState {
+        previous_session: AtomicUsize::new(found),
+    });
+}
+
+/// Emit `crash:previous-session` (with the number of reports) if the
+/// previous run panicked. Called when the main window has finished loading,
+/// so the frontend is there to hear it; emits at most once.
+pub fn notify_previous_session(app: &AppHandle) {
+    let Some(state) = app.try_state::<CrashState>() else {
+        return;
+    };
+    let count = state.previous_session.swap(0, Ordering::AcqRel);
+    if count > 0 {
+        app.emit(PREVIOUS_SESSION_EVENT, count).ok();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Return the most recent crash reports, newest first. Panics of the current
+/// run (in a command, so the app survived) are included.
+#[tauri::command]
+pub async fn get_crash_reports(
+    limit: i64,
+    storage: tauri::State<'_, StorageState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<CrashReport>, AppError> {
+    let limit = limit.clamp(1, MAX_CRASH_QUERY_LIMIT);
+    with_timeout(
+        async {
+            ingest_spool(&state.pool(), &storage.data_dir).await;
+            let reports = sqlx::query_as::<_, CrashReport>(
+                "SELECT id, message, location, backtrace, occurred_at
+                 FROM crash_reports
+                 ORDER BY occurred_at DESC, id DESC
+                 LIMIT ?",
+            )
+            .bind(limit)
+            .fetch_all(&state.pool())
+            .await?;
+            Ok(reports)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Delete all crash reports.
+#[tauri::command]
+pub async fn clear_crash_reports(
+    storage: tauri::State<'_, StorageState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            ingest_spool(&state.pool(), &storage.data_dir).await;
+            sqlx::query("DELETE FROM crash_reports")
+                .execute(&state.pool())
+                .await?;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Append one report to the spool and flush it to disk. Never panics:
+/// failures are ignored, there is nothing left to report them to.
+fn append_to_spool(spool: &Path, report: &CrashReport) {
+    let Ok(mut line) = serde_json::to_string(report) else {
+        return;
+    };
+    line.push('\n');
+    let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(spool)
+    else {
+        return;
+    };
+    if file.write_all(line.as_bytes()).is_ok() {
+        file.sync_all().ok();
+    }
+}
+
+/// Insert the spooled reports into `crash_reports`, prune old rows and
+/// delete the spool. The spool is renamed first so a panic on another
+/// thread meanwhile starts a fresh file instead of being lost. Returns the
+/// number of reports moved.
+async fn ingest_spool(pool: &SqlitePool, data_dir: &Path) -> usize {
+    let spool = data_dir.join(SPOOL_FILE);
+    let mut ingesting = spool.as_os_str().to_owned();
+    ingesting.push(".ingesting");
+    let ingesting = PathBuf::from(ingesting);
+    // A leftover from an interrupted ingest is read before newer reports.
+    if !ingesting.exists() && std::fs::rename(&spool, &ingesting).is_err() {
+        return 0;
+    }
+    let content = match std::fs::read_to_string(&ingesting) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("ingest_spool: {}", e);
+            return 0;
+        }
+    };
+
+    let reports: Vec<CrashReport> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    // One transaction, so a failure leaves the spool to retry without
+    // inserting any report twice.
+    if let Err(e) = insert_reports(pool, &reports).await {
+        log::error!("ingest_spool: insert failed: {}", e);
+        return 0;
+    }
+
+    let prune = sqlx::query(
+        "DELETE FROM crash_reports WHERE id NOT IN (
+             SELECT id FROM crash_reports ORDER BY occurred_at DESC, id DESC LIMIT ?
+         )",
+    )
+    .bind(MAX_CRASH_ROWS)
+    .execute(pool)
+    .await;
+    if let Err(e) = prune {
+        log::error!("ingest_spool: prune failed: {}", e);
+    }
+    if let Err(e) = std::fs::remove_file(&ingesting) {
+        log::warn!("ingest_spool: {}", e);
+    }
+    reports.len()
+}
+
+async fn insert_reports(pool: &SqlitePool, reports: &[CrashReport]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for report in reports {
+        sqlx::query(
+            "INSERT INTO crash_reports (message, location, backtrace, occurred_at)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(&report.message)
+        .bind(&report.location)
+        .bind(&report.backtrace)
+        .bind(report.occurred_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Truncate `value` to at most `max_chars` characters (not bytes).
+fn truncate_chars(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// An in-memory `telemetry.db` with the crash table. One connection, as
+    /// each connection to `:memory:` is a database of its own.
+    async fn crash_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(CRASH_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn report(message: &str, occurred_at: i64) -> CrashReport {
+        CrashReport {
+            id: 0,
+            message: message.to_string(),
+            location: "src/lib.rs:1:1".to_string(),
+            backtrace: String::new(),
+            occurred_at,
+        }
+    }
+
+    async fn stored_messages(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar("SELECT message FROM crash_reports ORDER BY occurred_at")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn spooled_reports_are_moved_into_the_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = dir.path().join(SPOOL_FILE);
+        append_to_spool(&spool, &report("first", 1));
+        append_to_spool(&spool, &report("second", 2));
+        // A line cut short by the process dying is skipped.
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&spool)
+            .unwrap()
+            .write_all(b"{\"id\":0,\"mess")
+            .unwrap();
+
+        let pool = crash_pool().await;
+        assert_eq!(ingest_spool(&pool, dir.path()).await, 2);
+        assert_eq!(stored_messages(&pool).await, ["first", "second"]);
+        assert!(!spool.exists());
+
+        assert_eq!(ingest_spool(&pool, dir.path()).await, 0);
+        assert_eq!(stored_messages(&pool).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn only_the_newest_reports_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = dir.path().join(SPOOL_FILE);
+        for i in 0..MAX_CRASH_ROWS + 5 {
+            append_to_spool(&spool, &report(&format!("panic {}", i), i));
+        }
+
+        let pool = crash_pool().await;
+        ingest_spool(&pool, dir.path()).await;
+        let messages = stored_messages(&pool).await;
+        assert_eq!(messages.len() as i64, MAX_CRASH_ROWS);
+        assert_eq!(messages[0], "panic 5");
+    }
+
+    #[test]
+    fn panic_hook_spools_message_and_location() {
+        let dir = tempfile::tempdir().unwrap();
+        install_panic_hook(dir.path());
+        let line = line!() + 1;
+        let result = std::panic::catch_unwind(|| panic!("hook test {}", 42));
+        // Back to the default hook for the other tests.
+        drop(std::panic::take_hook());
+        assert!(result.is_err());
+
+        let spool = std::fs::read_to_string(dir.path().join(SPOOL_FILE)).unwrap();
+        let report = spool
+            .lines()
+            .filter_map(|line| serde_json::from_str::<CrashReport>(line).ok())
+            .find(|report| report.message == "hook test 42")
+            .expect("the panic was not spooled");
+        assert!(report.location.contains(&format!("crash.rs:{}:", line)));
+        assert!(report.occurred_at > 0);
+    }
+
+    #[test]
+    fn long_values_are_cut_on_characters() {
+        assert_eq!(truncate_chars("ééé", 2), "éé");
+        assert_eq!(truncate_chars("abc", 10), "abc");
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use tauri::AppHandle;
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Tables every project database at `SUPPORTED_SCHEMA_VERSION` has.
+const EXPECTED_TABLES: [&str; 21] = [
+    "projects",
+    "sections",
+    "type_configs",
+    "backlog_items",
+    "history",
+    "ai_telemetry",
+    "ai_feedback",
+    "item_relations",
+    "item_templates",
+    "user_preferences",
+    "chat_messages",
+    "saved_views",
+    "archived_items",
+    "type_counters",
+    "item_external_refs",
+    "recurrences",
+    "time_entries",
+    "attachments",
+    "project_settings",
+    "backlog_items_fts",
+    "attachments_fts",
+];
+
+/// Cap on the problems reported per check, so a badly damaged file does not
+/// produce a multi-megabyte report.
+const MAX_VIOLATIONS: usize = 100;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A problem found by `check_project_db`.
+#[derive(Debug, Serialize)]
+pub struct DbViolation {
+    /// `open`, `integrity`, `foreign_key`, `missing_table` or `schema`.
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// Return value of `check_project_db`.
+#[derive(Debug, Serialize)]
+pub struct DbCheckReport {
+    pub path: String,
+    pub ok: bool,
+    pub violations: Vec<DbViolation>,
+    /// Size of the main database file (the WAL is not included).
+    pub size_bytes: u64,
+    pub page_count: i64,
+    pub freelist_pages: i64,
+    pub journal_mode: String,
+    pub schema_version: i64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Check a project database: `PRAGMA integrity_check`,
+/// `PRAGMA foreign_key_check`, expected tables and schema version. The file
+/// is opened read-only, so this is safe to run against the live project.
+/// Problems are reported in the result rather than as an error; only a
+/// path outside the data directory or fs scope fails the command.
+#[tauri::command]
+pub async fn check_project_db(app: AppHandle, db_path: String) -> Result<DbCheckReport, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(async { Ok(check_database(&db).await) }, DB_TIMEOUT_MS).await
+}
+
+/// Check every known project database: the ones the frontend has open and
+/// the recently opened projects.
+#[tauri::command]
+pub async fn check_all_projects(
+    app: AppHandle,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<DbCheckReport>, AppError> {
+    let open = project_db::open_database_paths(&app).await;
+    let recent: Vec<String> = sqlx::query_scalar("SELECT path FROM recent_files")
+        .fetch_all(&state.pool())
+        .await?;
+
+    let paths = database_paths(open, recent);
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in paths {
+        reports.push(with_timeout(async { Ok(check_database(&path).await) }, DB_TIMEOUT_MS).await?);
+    }
+    Ok(reports)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Existing project database files among the open databases and the
+/// recent files (project folders or database files), canonicalized and
+/// without duplicates.
+fn database_paths(open: Vec<PathBuf>, recent: Vec<String>) -> BTreeSet<PathBuf> {
+    let mut paths: BTreeSet<PathBuf> = open.into_iter().collect();
+    for path in recent {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            paths.insert(path.join(project_db::PROJECT_DB_FILE));
+        } else if path.is_file() {
+            paths.insert(path);
+        }
+    }
+    paths
+        .into_iter()
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+async fn check_database(db_path: &Path) -> DbCheckReport {
+    let mut report = DbCheckReport {
+        path: db_path.to_string_lossy().into_owned(),
+        ok: false,
+        violations: Vec::new(),
+        size_bytes: std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0),
+        page_count: 0,
+        freelist_pages: 0,
+        journal_mode: String::new(),
+        schema_version: 0,
+    };
+
+    match project_db::open_read_only(db_path).await {
+        Ok(mut conn) => {
+            if let Err(e) = inspect(&mut conn, &mut report).await {
+                report.violations.push(DbViolation {
+                    kind: "integrity",
+                    message: e.to_string(),
+                });
+            }
+        }
+        Err(e) => report.violations.push(DbViolation {
+            kind: "open",
+            message: e.to_string(),
+        }),
+    }
+
+    report.violations.truncate(MAX_VIOLATIONS);
+    report.ok = report.violations.is_empty();
+    report
+}
+
+/// Fill in the statistics of `report` and append the problems found.
+async fn inspect(conn: &mut SqliteConnection, report: &mut DbCheckReport) -> Result<(), AppError> {
+    report.page_count = sqlx::query_scalar("PRAGMA page_count;")
+        .fetch_one(&mut *conn)
+        .await?;
+    report.freelist_pages = sqlx::query_scalar("PRAGMA freelist_count;")
+        .fetch_one(&mut *conn)
+        .await?;
+    report.journal_mode = sqlx::query_scalar("PRAGMA journal_mode;")
+        .fetch_one(&mut *conn)
+        .await?;
+    report.schema_version = project_db::schema_version(conn).await?;
+
+    for problem in project_db::integrity_problems(conn).await? {
+        report.violations.push(DbViolation {
+            kind: "integrity",
+            message: problem,
+        });
+    }
+
+    let fk_rows: Vec<(String, Option<i64>, String, i64)> =
+        sqlx::query_as("PRAGMA foreign_key_check;")
+            .fetch_all(&mut *conn)
+            .await?;
+    for (table, rowid, parent, _) in fk_rows {
+        report.violations.push(DbViolation {
+            kind: "foreign_key",
+            message: match rowid {
+                Some(rowid) => format!("{} row {} references a missing {}", table, rowid, parent),
+                None => format!("{} references a missing {}", table, parent),
+            },
+        });
+    }
+
+    let tables: BTreeSet<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+    if report.schema_version >= project_db::SUPPORTED_SCHEMA_VERSION {
+        for table in EXPECTED_TABLES {
+            if !tables.contains(table) {
+                report.violations.push(DbViolation {
+                    kind: "missing_table",
+                    message: format!("table {} is missing", table),
+                });
+            }
+        }
+    }
+
+    if report.schema_version > project_db::SUPPORTED_SCHEMA_VERSION {
+        report.violations.push(DbViolation {
+            kind: "schema",
+            message: format!(
+                "schema version {} is newer than supported version {}",
+                report.schema_version,
+                project_db::SUPPORTED_SCHEMA_VERSION
+            ),
+        });
+    } else if report.schema_version < project_db::SUPPORTED_SCHEMA_VERSION {
+        report.violations.push(DbViolation {
+            kind: "schema",
+            message: format!(
+                "schema version {} is behind {}; migrations run when the project is opened",
+                report.schema_version,
+                project_db::SUPPORTED_SCHEMA_VERSION
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_db;
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::{ConnectOptions, Connection};
+
+    fn kinds(report: &DbCheckReport) -> Vec<&str> {
+        report.violations.iter().map(|v| v.kind).collect()
+    }
+
+    #[tokio::test]
+    async fn healthy_database_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path(), project_db::SUPPORTED_SCHEMA_VERSION, "").await;
+
+        let report = check_database(&path).await;
+        assert!(report.ok, "{:?}", report.violations);
+        assert_eq!(report.schema_version, project_db::SUPPORTED_SCHEMA_VERSION);
+        assert!(report.page_count > 0);
+        assert!(report.size_bytes > 0);
+        assert_eq!(report.journal_mode, "delete");
+    }
+
+    #[tokio::test]
+    async fn problems_are_reported_not_returned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(
+            dir.path(),
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            "DROP TABLE attachments;",
+        )
+        .await;
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&path)
+            .foreign_keys(false)
+            .connect()
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO sections (id, project_id, title, raw_header) VALUES (7, 42, 'x', 'x')",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+
+        let report = check_database(&path).await;
+        assert!(!report.ok);
+        assert_eq!(kinds(&report), ["foreign_key", "missing_table"]);
+        assert_eq!(
+            report.violations[0].message,
+            "sections row 7 references a missing projects"
+        );
+        assert_eq!(report.violations[1].message, "table attachments is missing");
+    }
+
+    #[tokio::test]
+    async fn schema_versions_are_compared() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path(), 3, "").await;
+        let report = check_database(&path).await;
+        // Tables are only required at the supported version.
+        assert_eq!(kinds(&report), ["schema"]);
+        assert!(report.violations[0].message.contains("behind"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let newer = format!(
+            "PRAGMA user_version = {};",
+            project_db::SUPPORTED_SCHEMA_VERSION + 1
+        );
+        let path = create_db(dir.path(), project_db::SUPPORTED_SCHEMA_VERSION, &newer).await;
+        let report = check_database(&path).await;
+        assert_eq!(kinds(&report), ["schema"]);
+        assert!(report.violations[0]
+            .message
+            .contains("newer than supported"));
+    }
+
+    #[tokio::test]
+    async fn unreadable_files_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = check_database(&dir.path().join("missing.db")).await;
+        assert_eq!(kinds(&report), ["open"]);
+
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, vec![b'x'; 4096]).unwrap();
+        let report = check_database(&garbage).await;
+        assert!(!report.ok);
+        assert_eq!(report.size_bytes, 4096);
+    }
+
+    #[test]
+    fn recent_folders_resolve_to_their_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let db = project.join(project_db::PROJECT_DB_FILE);
+        std::fs::write(&db, b"").unwrap();
+        let other = dir.path().join("other.db");
+        std::fs::write(&other, b"").unwrap();
+        let empty = dir.path().join("empty");
+        std::fs::create_dir(&empty).unwrap();
+
+        let paths = database_paths(
+            vec![project.join(".").join(project_db::PROJECT_DB_FILE)],
+            vec![
+                project.to_string_lossy().into_owned(),
+                other.to_string_lossy().into_owned(),
+                empty.to_string_lossy().into_owned(),
+                dir.path().join("gone").to_string_lossy().into_owned(),
+            ],
+        );
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            [other.canonicalize().unwrap(), db.canonicalize().unwrap()]
+        );
+    }
+}
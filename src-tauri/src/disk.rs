@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Free space required by `ensure_free_space`, as a multiple of the size of
+/// the file about to be written: room for the output and its temp copy.
+const REQUIRED_SPACE_FACTOR: u64 = 2;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `get_free_disk_space`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DiskSpaceInfo {
+    /// Bytes available to the current user.
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Space of the file system holding `path`, or the app data folder when
+/// `path` does not exist.
+#[tauri::command]
+pub fn get_free_disk_space(path: String, app: AppHandle) -> Result<DiskSpaceInfo, AppError> {
+    let path = Path::new(&path);
+    if path.exists() {
+        return disk_space(path);
+    }
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(format!("cannot resolve app data directory: {}", e)))?;
+    disk_space(&data_dir)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Fail with "Insufficient disk space" unless the file system holding `dir`
+/// has twice `size` bytes free.
+pub fn ensure_free_space(dir: &Path, size: u64) -> Result<(), AppError> {
+    check_free_space(dir, size, disk_space)
+}
+
+/// `ensure_free_space` with the file system query passed in.
+fn check_free_space(
+    dir: &Path,
+    size: u64,
+    disk_space: impl FnOnce(&Path) -> Result<DiskSpaceInfo, AppError>,
+) -> Result<(), AppError> {
+    let free = disk_space(dir)?.free_bytes;
+    let needed = size.saturating_mul(REQUIRED_SPACE_FACTOR);
+    if free < needed {
+        log::warn!("{}: {} bytes free, {} needed", dir.display(), free, needed);
+        return Err(AppError::Io("Insufficient disk space".into()));
+    }
+    Ok(())
+}
+
+/// Free, total and used bytes of the file system holding `path`.
+#[cfg(unix)]
+pub fn disk_space(path: &Path) -> Result<DiskSpaceInfo, AppError> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| AppError::Validation("path contains a NUL byte".into()))?;
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid out pointer.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // The field widths differ between Linux and macOS.
+    #[allow(clippy::unnecessary_cast)]
+    let (fragment, blocks, free, available) = (
+        stat.f_frsize as u64,
+        stat.f_blocks as u64,
+        stat.f_bfree as u64,
+        stat.f_bavail as u64,
+    );
+    Ok(statvfs_space(fragment, blocks, free, available))
+}
+
+/// Byte counts from `statvfs` block counts. `free` includes the blocks
+/// reserved for root, `available` does not.
+#[cfg(unix)]
+fn statvfs_space(fragment: u64, blocks: u64, free: u64, available: u64) -> DiskSpaceInfo {
+    let total_bytes = blocks * fragment;
+    DiskSpaceInfo {
+        free_bytes: available * fragment,
+        total_bytes,
+        used_bytes: total_bytes.saturating_sub(free * fragment),
+    }
+}
+
+#[cfg(windows)]
+pub fn disk_space(path: &Path) -> Result<DiskSpaceInfo, AppError> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let (mut available, mut total, mut free) = (0u64, 0u64, 0u64);
+    // SAFETY: the path is a valid wide string and the counters outlive the
+    // call.
+    unsafe {
+        GetDiskFreeSpaceExW(
+            &HSTRING::from(path.as_os_str()),
+            Some(&mut available),
+            Some(&mut total),
+            Some(&mut free),
+        )
+    }
+    .map_err(|e| AppError::Io(format!("cannot read free disk space: {}", e)))?;
+    Ok(DiskSpaceInfo {
+        free_bytes: available,
+        total_bytes: total,
+        used_bytes: total.saturating_sub(free),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn space(free_bytes: u64) -> DiskSpaceInfo {
+        DiskSpaceInfo {
+            free_bytes,
+            total_bytes: u64::MAX,
+            used_bytes: u64::MAX - free_bytes,
+        }
+    }
+
+    #[test]
+    fn twice_the_size_must_be_free() {
+        let dir = Path::new("/backups");
+        check_free_space(dir, 500, |_| Ok(space(1000))).unwrap();
+        assert!(matches!(
+            check_free_space(dir, 501, |_| Ok(space(1000))),
+            Err(AppError::Io(message)) if message == "Insufficient disk space"
+        ));
+        assert!(check_free_space(dir, u64::MAX, |_| Ok(space(u64::MAX - 1))).is_err());
+    }
+
+    #[test]
+    fn query_errors_are_passed_on() {
+        let result = check_free_space(Path::new("/gone"), 1, |path| {
+            assert_eq!(path, Path::new("/gone"));
+            Err(AppError::Io("no such device".into()))
+        });
+        assert!(matches!(result, Err(AppError::Io(message)) if message == "no such device"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reserved_blocks_are_used_but_not_free() {
+        let info = statvfs_space(4096, 1000, 300, 250);
+        assert_eq!(info.total_bytes, 4_096_000);
+        assert_eq!(info.free_bytes, 1_024_000);
+        assert_eq!(info.used_bytes, 2_867_200);
+    }
+
+    #[test]
+    fn space_of_a_real_directory_is_consistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = disk_space(dir.path()).unwrap();
+        assert!(info.total_bytes > 0);
+        assert!(info.free_bytes <= info.total_bytes);
+        assert!(info.used_bytes <= info.total_bytes);
+        assert!(disk_space(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,481 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{Emitter, Manager, WebviewWindow};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Event emitted when the data was dropped on a target.
+const DRAG_COMPLETE_EVENT: &str = "drag:complete";
+
+/// Event emitted when the drag was cancelled (Escape, or dropped nowhere).
+const DRAG_CANCELLED_EVENT: &str = "drag:cancelled";
+
+/// Upper bound on the dragged payload.
+const MAX_DRAG_BYTES: usize = 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Tauri managed state: whether a native drag is in progress. The OS runs
+/// one drag loop at a time.
+#[derive(Default)]
+pub struct DragState {
+    active: AtomicBool,
+}
+
+impl DragState {
+    /// Claim the drag loop, unless a drag is already running.
+    fn begin(&self) -> Result<(), AppError> {
+        self.active
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| ())
+            .map_err(|_| AppError::Validation("a native drag is already in progress".into()))
+    }
+
+    fn end(&self) {
+        self.active.store(false, Ordering::Release);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Start a native OS drag of `data` from `window`, e.g. a ticket id dragged
+/// to a terminal. Must be called while the primary mouse button is held
+/// (from a `mousedown`/`dragstart` handler). The data is always offered as
+/// plain text and, when `mime_type` is something else, also under that
+/// type. `drag:complete` or `drag:cancelled` is emitted when the drag ends.
+#[tauri::command]
+pub fn start_native_drag(
+    data: String,
+    mime_type: String,
+    window: WebviewWindow,
+    state: tauri::State<'_, DragState>,
+) -> Result<(), AppError> {
+    let mime_type = validate_drag(&data, &mime_type)?;
+    state.begin()?;
+
+    let drag_window = window.clone();
+    let scheduled = window.run_on_main_thread(move || {
+        if let Err(e) = platform::start(&drag_window, &data, &mime_type) {
+            log::warn!("start_native_drag: {}", e);
+            finish(&drag_window, false);
+        }
+    });
+    if let Err(e) = scheduled {
+        state.end();
+        return Err(AppError::Io(format!("cannot start drag: {}", e)));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Check the payload size and MIME type of a drag; returns the MIME type
+/// trimmed and lowercased.
+fn validate_drag(data: &str, mime_type: &str) -> Result<String, AppError> {
+    if data.is_empty() || data.len() > MAX_DRAG_BYTES {
+        return Err(AppError::Validation(format!(
+            "drag data must be between 1 and {} bytes",
+            MAX_DRAG_BYTES
+        )));
+    }
+    let mime_type = mime_type.trim().to_ascii_lowercase();
+    if !is_valid_mime(&mime_type) {
+        return Err(AppError::Validation(format!(
+            "invalid MIME type: {}",
+            mime_type
+        )));
+    }
+    Ok(mime_type)
+}
+
+/// `type/subtype` made of token characters.
+fn is_valid_mime(mime_type: &str) -> bool {
+    let token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    matches!(mime_type.split_once('/'), Some((kind, sub)) if token(kind) && token(sub))
+}
+
+/// Mark the drag as over and tell the frontend how it ended.
+fn finish(window: &WebviewWindow, completed: bool) {
+    window.state::<DragState>().end();
+    let event = if completed {
+        DRAG_COMPLETE_EVENT
+    } else {
+        DRAG_CANCELLED_EVENT
+    };
+    window.emit(event, ()).ok();
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use gtk::gdk;
+    use gtk::prelude::*;
+    use tauri::WebviewWindow;
+
+    /// `info` of the extra `mime_type` target; text targets use 0.
+    const CUSTOM_TARGET_INFO: u32 = 1;
+
+    /// GTK drag from the window widget. GTK picks the XDnD or Wayland
+    /// protocol itself, which raw X11 calls could not do on Wayland.
+    pub fn start(window: &WebviewWindow, data: &str, mime_type: &str) -> Result<(), String> {
+        let widget = window.gtk_window().map_err(|e| e.to_string())?;
+
+        let targets = gtk::TargetList::new(&[]);
+        targets.add_text_targets(0);
+        if mime_type != "text/plain" {
+            targets.add(&gdk::Atom::intern(mime_type), 0, CUSTOM_TARGET_INFO);
+        }
+
+        let handlers = Rc::new(RefCell::new(Vec::new()));
+        let cancelled = Rc::new(Cell::new(false));
+
+        let payload = data.to_string();
+        let custom_type = gdk::Atom::intern(mime_type);
+        handlers.borrow_mut().push(widget.connect_drag_data_get(
+            move |_, _, selection, info, _| {
+                if info == CUSTOM_TARGET_INFO {
+                    selection.set(&custom_type, 8, payload.as_bytes());
+                } else {
+                    selection.set_text(&payload);
+                }
+            },
+        ));
+
+        let failed = cancelled.clone();
+        handlers
+            .borrow_mut()
+            .push(widget.connect_drag_failed(move |_, _, _| {
+                failed.set(true);
+                gtk::glib::Propagation::Proceed
+            }));
+
+        let end_handlers = handlers.clone();
+        let end_window = window.clone();
+        handlers
+            .borrow_mut()
+            .push(widget.connect_drag_end(move |widget, _| {
+                for handler in end_handlers.borrow_mut().drain(..) {
+                    widget.disconnect(handler);
+                }
+                super::finish(&end_window, !cancelled.get());
+            }));
+
+        let context =
+            widget.drag_begin_with_coordinates(&targets, gdk::DragAction::COPY, 1, None, -1, -1);
+        if context.is_none() {
+            for handler in handlers.borrow_mut().drain(..) {
+                widget.disconnect(handler);
+            }
+            return Err("GTK refused to start the drag".into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::mem::ManuallyDrop;
+
+    use tauri::WebviewWindow;
+    use windows::core::{implement, BOOL, HRESULT, HSTRING};
+    use windows::Win32::Foundation::{
+        DRAGDROP_S_CANCEL, DRAGDROP_S_DROP, DRAGDROP_S_USEDEFAULTCURSORS, S_OK,
+    };
+    use windows::Win32::System::Com::{
+        IDataObject, DVASPECT_CONTENT, FORMATETC, STGMEDIUM, STGMEDIUM_0, TYMED_HGLOBAL,
+    };
+    use windows::Win32::System::DataExchange::RegisterClipboardFormatW;
+    use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use windows::Win32::System::Ole::{
+        DoDragDrop, IDropSource, IDropSource_Impl, CF_UNICODETEXT, DROPEFFECT, DROPEFFECT_COPY,
+    };
+    use windows::Win32::System::SystemServices::{MK_LBUTTON, MODIFIERKEYS_FLAGS};
+    use windows::Win32::UI::Shell::SHCreateDataObject;
+
+    /// Ends the drag on Escape, drops when the left button is released.
+    #[implement(IDropSource)]
+    struct DropSource;
+
+    impl IDropSource_Impl for DropSource_Impl {
+        fn QueryContinueDrag(
+            &self,
+            escape_pressed: BOOL,
+            key_state: MODIFIERKEYS_FLAGS,
+        ) -> HRESULT {
+            continue_drag(escape_pressed.as_bool(), key_state)
+        }
+
+        fn GiveFeedback(&self, _effect: DROPEFFECT) -> HRESULT {
+            DRAGDROP_S_USEDEFAULTCURSORS
+        }
+    }
+
+    /// Answer of `QueryContinueDrag`.
+    pub(super) fn continue_drag(escape_pressed: bool, key_state: MODIFIERKEYS_FLAGS) -> HRESULT {
+        if escape_pressed {
+            DRAGDROP_S_CANCEL
+        } else if (key_state & MK_LBUTTON).0 == 0 {
+            DRAGDROP_S_DROP
+        } else {
+            S_OK
+        }
+    }
+
+    /// OLE drag through `DoDragDrop`, which runs a modal loop on the UI
+    /// thread until the drop. The `IDataObject` is the shell's generic
+    /// implementation, filled with `SetData`.
+    pub fn start(window: &WebviewWindow, data: &str, mime_type: &str) -> Result<(), String> {
+        // SAFETY: called on the main (STA, OLE-initialized) thread; every
+        // HGLOBAL handed to SetData is owned by the data object afterwards.
+        unsafe {
+            let data_object: IDataObject =
+                SHCreateDataObject(None, None, None::<&IDataObject>).map_err(|e| e.to_string())?;
+
+            let mut text: Vec<u16> = data.encode_utf16().collect();
+            text.push(0);
+            set_data(&data_object, CF_UNICODETEXT.0, as_bytes(&text))?;
+
+            if mime_type != "text/plain" {
+                let format = RegisterClipboardFormatW(&HSTRING::from(mime_type));
+                if format == 0 {
+                    return Err(format!("cannot register clipboard format {}", mime_type));
+                }
+                set_data(&data_object, format as u16, data.as_bytes())?;
+            }
+
+            let source: IDropSource = DropSource.into();
+            let mut effect = DROPEFFECT::default();
+            let result = DoDragDrop(&data_object, &source, DROPEFFECT_COPY, &mut effect);
+            super::finish(
+                window,
+                result == DRAGDROP_S_DROP && effect != DROPEFFECT::default(),
+            );
+        }
+        Ok(())
+    }
+
+    unsafe fn set_data(data_object: &IDataObject, format: u16, bytes: &[u8]) -> Result<(), String> {
+        let global = GlobalAlloc(GMEM_MOVEABLE, bytes.len()).map_err(|e| e.to_string())?;
+        let ptr = GlobalLock(global) as *mut u8;
+        if ptr.is_null() {
+            return Err("GlobalLock failed".into());
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+        // Returns an "error" once the lock count reaches zero.
+        GlobalUnlock(global).ok();
+
+        let format = FORMATETC {
+            cfFormat: format,
+            ptd: std::ptr::null_mut(),
+            dwAspect: DVASPECT_CONTENT.0,
+            lindex: -1,
+            tymed: TYMED_HGLOBAL.0 as u32,
+        };
+        let medium = STGMEDIUM {
+            tymed: TYMED_HGLOBAL.0 as u32,
+            u: STGMEDIUM_0 { hGlobal: global },
+            pUnkForRelease: ManuallyDrop::new(None),
+        };
+        data_object
+            .SetData(&format, &medium, true)
+            .map_err(|e| e.to_string())
+    }
+
+    pub(super) fn as_bytes(text: &[u16]) -> &[u8] {
+        // SAFETY: u16 has no padding and u8 has no alignment requirement.
+        unsafe { std::slice::from_raw_parts(text.as_ptr() as *const u8, text.len() * 2) }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::cell::RefCell;
+
+    use objc2::rc::Retained;
+    use objc2::runtime::ProtocolObject;
+    use objc2::{define_class, msg_send, DefinedClass, MainThreadMarker, MainThreadOnly};
+    use objc2_app_kit::{
+        NSApplication, NSDragOperation, NSDraggingContext, NSDraggingItem, NSDraggingSession,
+        NSDraggingSource, NSPasteboardItem, NSPasteboardTypeString, NSView,
+    };
+    use objc2_foundation::{
+        NSArray, NSObject, NSObjectProtocol, NSPoint, NSRect, NSSize, NSString,
+    };
+    use tauri::WebviewWindow;
+
+    pub struct SourceIvars {
+        window: WebviewWindow,
+    }
+
+    define_class!(
+        /// `NSDraggingSource` reporting the end of the session back to Tauri.
+        #[unsafe(super(NSObject))]
+        #[thread_kind = MainThreadOnly]
+        #[name = "TicketflowDragSource"]
+        #[ivars = SourceIvars]
+        struct DragSource;
+
+        unsafe impl NSObjectProtocol for DragSource {}
+
+        unsafe impl NSDraggingSource for DragSource {
+            #[unsafe(method(draggingSession:sourceOperationMaskForDraggingContext:))]
+            fn operation_mask(
+                &self,
+                _session: &NSDraggingSession,
+                _context: NSDraggingContext,
+            ) -> NSDragOperation {
+                NSDragOperation::Copy
+            }
+
+            #[unsafe(method(draggingSession:endedAtPoint:operation:))]
+            fn ended(
+                &self,
+                _session: &NSDraggingSession,
+                _point: NSPoint,
+                operation: NSDragOperation,
+            ) {
+                super::finish(&self.ivars().window, operation != NSDragOperation::None);
+                ACTIVE_SOURCE.with(|source| source.borrow_mut().take());
+            }
+        }
+    );
+
+    thread_local! {
+        /// The source of the running session, kept alive until it ends.
+        static ACTIVE_SOURCE: RefCell<Option<Retained<DragSource>>> = const { RefCell::new(None) };
+    }
+
+    /// AppKit dragging session started from the webview's `NSView` with the
+    /// mouse event currently being handled.
+    pub fn start(window: &WebviewWindow, data: &str, mime_type: &str) -> Result<(), String> {
+        let mtm = MainThreadMarker::new().ok_or("not on the main thread")?;
+        let view_ptr = window.ns_view().map_err(|e| e.to_string())?;
+        // SAFETY: Tauri hands out the live content view of this window.
+        let view: Retained<NSView> =
+            unsafe { Retained::retain(view_ptr as *mut NSView) }.ok_or("window has no view")?;
+        let event = NSApplication::sharedApplication(mtm)
+            .currentEvent()
+            .ok_or("no mouse event to start the drag from")?;
+
+        // `unused_unsafe`: these bindings are safe in recent objc2-app-kit
+        // releases and unsafe in older ones.
+        #[allow(unused_unsafe)]
+        unsafe {
+            let item = NSPasteboardItem::new();
+            item.setString_forType(&NSString::from_str(data), NSPasteboardTypeString);
+            if mime_type != "text/plain" {
+                item.setString_forType(&NSString::from_str(data), &NSString::from_str(mime_type));
+            }
+
+            let dragging_item = NSDraggingItem::initWithPasteboardWriter(
+                NSDraggingItem::alloc(),
+                ProtocolObject::from_ref(&*item),
+            );
+            let location = view.convertPoint_fromView(event.locationInWindow(), None);
+            dragging_item
+                .setDraggingFrame_contents(NSRect::new(location, NSSize::new(1.0, 1.0)), None);
+
+            let source = DragSource::alloc(mtm).set_ivars(SourceIvars {
+                window: window.clone(),
+            });
+            let source: Retained<DragSource> = msg_send![super(source), init];
+            view.beginDraggingSessionWithItems_event_source(
+                &NSArray::from_retained_slice(&[dragging_item]),
+                &event,
+                ProtocolObject::from_ref(&*source),
+            );
+            ACTIVE_SOURCE.with(|active| *active.borrow_mut() = Some(source));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use tauri::WebviewWindow;
+
+    pub fn start(_window: &WebviewWindow, _data: &str, _mime_type: &str) -> Result<(), String> {
+        Err("native drag is not supported on this platform".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_size_is_bounded() {
+        assert!(validate_drag("", "text/plain").is_err());
+        assert!(validate_drag(&"a".repeat(MAX_DRAG_BYTES + 1), "text/plain").is_err());
+        assert!(validate_drag(&"a".repeat(MAX_DRAG_BYTES), "text/plain").is_ok());
+    }
+
+    #[test]
+    fn mime_types_are_normalized_and_checked() {
+        assert_eq!(
+            validate_drag("BUG-1", " Application/X-Ticket+JSON ").unwrap(),
+            "application/x-ticket+json"
+        );
+        for mime_type in [
+            "text",
+            "text/",
+            "/plain",
+            "text/plain; charset=utf-8",
+            "a/b/c",
+            "té/xt",
+        ] {
+            assert!(
+                matches!(
+                    validate_drag("BUG-1", mime_type),
+                    Err(AppError::Validation(_))
+                ),
+                "{} was accepted",
+                mime_type
+            );
+        }
+    }
+
+    #[test]
+    fn one_drag_at_a_time() {
+        let state = DragState::default();
+        state.begin().unwrap();
+        assert!(matches!(state.begin(), Err(AppError::Validation(_))));
+        state.end();
+        state.begin().unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn ole_drag_loop_drops_on_release_and_cancels_on_escape() {
+        use windows::Win32::Foundation::{DRAGDROP_S_CANCEL, DRAGDROP_S_DROP, S_OK};
+        use windows::Win32::System::SystemServices::{MK_LBUTTON, MODIFIERKEYS_FLAGS};
+
+        let released = MODIFIERKEYS_FLAGS(0);
+        assert_eq!(platform::continue_drag(false, MK_LBUTTON), S_OK);
+        assert_eq!(platform::continue_drag(false, released), DRAGDROP_S_DROP);
+        assert_eq!(platform::continue_drag(true, MK_LBUTTON), DRAGDROP_S_CANCEL);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn utf16_text_is_passed_as_little_endian_bytes() {
+        let text: Vec<u16> = "Aé\0".encode_utf16().collect();
+        assert_eq!(platform::as_bytes(&text), [0x41, 0, 0xe9, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,675 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use pulldown_cmark::{Event, Options, Parser};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::AppHandle;
+
+use crate::attachments;
+use crate::audit;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::export::{self, TicketDocument};
+use crate::files;
+use crate::kv;
+use crate::secrets;
+use crate::storage::StorageState;
+use crate::telemetry::{self, TelemetryState};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key holding the `SmtpSettings` as JSON.
+pub(crate) const SMTP_KV: &str = "smtp_settings";
+
+/// Secret holding the SMTP password.
+pub(crate) const SMTP_PASSWORD_SECRET: &str = "smtp_password";
+
+/// Timeout of the connection and of each SMTP command.
+const SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// Budget of `email_test`: connecting, login and NOOP together get the
+/// time of one SMTP command.
+const TEST_TIMEOUT_MS: u64 = SMTP_TIMEOUT_SECS * 1000;
+
+const MAX_RECIPIENTS: usize = 20;
+const MAX_NOTE_CHARS: usize = 5_000;
+
+/// Total size of the files attached to one email. Base64 adds a third, and
+/// many servers refuse messages over 25 MB.
+const MAX_ATTACHMENTS_BYTES: u64 = 15 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTlsMode {
+    /// Plain connection upgraded with STARTTLS (usually port 587). Fails
+    /// when the server does not offer it.
+    Starttls,
+    /// TLS from the start (usually port 465).
+    Implicit,
+    /// No encryption, for a relay on the local network. Refused with a
+    /// user name.
+    None,
+}
+
+/// SMTP server settings, stored in `kv_store`. The password is kept in the
+/// OS keyring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTlsMode,
+    /// Sender, `address` or `Name <address>`.
+    pub from: String,
+    pub username: Option<String>,
+}
+
+/// Argument of `email_configure`.
+#[derive(Debug, Deserialize)]
+pub struct SmtpConfigInput {
+    #[serde(flatten)]
+    pub settings: SmtpSettings,
+    /// Kept as is when `None`, removed when empty.
+    pub password: Option<String>,
+}
+
+/// Return value of `email_configure` and `email_get_config`.
+#[derive(Debug, Serialize)]
+pub struct SmtpConfig {
+    /// `None` until `email_configure` is called.
+    pub settings: Option<SmtpSettings>,
+    /// Whether a password is stored. The password itself is not returned.
+    pub password_set: bool,
+}
+
+/// Options accepted by `email_send_ticket`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailTicketOptions {
+    /// Markdown written above the ticket.
+    pub note: Option<String>,
+    /// Attach the ticket's attachments and screenshots, up to 15 MB in
+    /// total.
+    pub include_attachments: bool,
+}
+
+/// Return value of `email_send_ticket`.
+#[derive(Debug, Serialize)]
+pub struct EmailSendResult {
+    /// `Message-ID` header of the email sent.
+    pub message_id: String,
+    /// Files attached.
+    pub attachments: usize,
+    /// Files left out: missing, or past the size cap.
+    pub omitted: Vec<String>,
+}
+
+/// A file to attach.
+struct OutgoingFile {
+    filename: String,
+    mime_type: String,
+    path: PathBuf,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Save the SMTP server settings. The password goes to the OS keyring.
+#[tauri::command]
+pub async fn email_configure(
+    smtp: SmtpConfigInput,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<SmtpConfig, AppError> {
+    let SmtpConfigInput { settings, password } = smtp;
+    let settings = SmtpSettings {
+        host: settings.host.trim().to_string(),
+        from: settings.from.trim().to_string(),
+        username: settings
+            .username
+            .map(|username| username.trim().to_string())
+            .filter(|username| !username.is_empty()),
+        ..settings
+    };
+    let result = async {
+        validate_settings(&settings)?;
+        let json = serde_json::to_string(&settings)
+            .map_err(|e| AppError::Validation(format!("invalid SMTP settings: {}", e)))?;
+        with_timeout(
+            async {
+                kv::set(&state.pool(), SMTP_KV, &json).await?;
+                Ok(())
+            },
+            DB_TIMEOUT_MS,
+        )
+        .await?;
+        if let Some(password) = password.as_deref() {
+            secrets::set_secret(
+                SMTP_PASSWORD_SECRET,
+                Some(password).filter(|password| !password.is_empty()),
+            )?;
+        }
+        Ok(SmtpConfig {
+            settings: Some(settings.clone()),
+            password_set: secrets::get_secret(SMTP_PASSWORD_SECRET)?.is_some(),
+        })
+    }
+    .await;
+
+    let password_change = match password.as_deref() {
+        None => "password kept",
+        Some("") => "password removed",
+        Some(_) => "password set",
+    };
+    audit::audit_log_command(
+        &state.pool(),
+        "email_configure",
+        &format!(
+            "{}:{} {:?}, {}",
+            settings.host, settings.port, settings.tls, password_change
+        ),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// The SMTP server settings, and whether a password is stored.
+#[tauri::command]
+pub async fn email_get_config(
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<SmtpConfig, AppError> {
+    let settings = with_timeout(load_settings(&state.pool()), DB_TIMEOUT_MS).await?;
+    Ok(SmtpConfig {
+        settings,
+        password_set: secrets::get_secret(SMTP_PASSWORD_SECRET)?.is_some(),
+    })
+}
+
+/// Email a ticket to `to`: the title as subject, the ticket as HTML with a
+/// plain-text alternative, and optionally its files. Fails with
+/// `Unauthorized` when the server refuses the credentials, `Validation`
+/// when it rejects a recipient, and `Network`, `Timeout` or `Certificate`
+/// when it cannot be reached.
+#[tauri::command]
+pub async fn email_send_ticket(
+    db_path: String,
+    ticket_id: String,
+    to: Vec<String>,
+    options: EmailTicketOptions,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<EmailSendResult, AppError> {
+    let result = async {
+        if to.is_empty() || to.len() > MAX_RECIPIENTS {
+            return Err(AppError::Validation(format!(
+                "an email needs 1 to {} recipients",
+                MAX_RECIPIENTS
+            )));
+        }
+        let recipients = to
+            .iter()
+            .map(|address| parse_mailbox(address))
+            .collect::<Result<Vec<_>, _>>()?;
+        let note = options
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty());
+        if note.is_some_and(|note| note.chars().count() > MAX_NOTE_CHARS) {
+            return Err(AppError::Validation(format!(
+                "the note is longer than {} characters",
+                MAX_NOTE_CHARS
+            )));
+        }
+        let settings = configured_settings(&state.pool()).await?;
+        let from = parse_mailbox(&settings.from)?;
+
+        let db = files::validate_path(&app, Path::new(&db_path))?;
+        let document =
+            with_timeout(export::ticket_document(&db, &ticket_id), DB_TIMEOUT_MS).await?;
+        let mut outgoing = Vec::new();
+        if options.include_attachments {
+            let attached = with_timeout(
+                attachments::ticket_files(&storage.data_dir, &db, &ticket_id),
+                DB_TIMEOUT_MS,
+            )
+            .await?;
+            for (attachment, path) in attached {
+                outgoing.push(OutgoingFile {
+                    filename: attachment.filename,
+                    mime_type: attachment.mime_type,
+                    path,
+                });
+            }
+            for path in &document.screenshots {
+                let filename = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                outgoing.push(OutgoingFile {
+                    mime_type: attachments::mime_type(&filename).to_string(),
+                    filename,
+                    path: path.clone(),
+                });
+            }
+        }
+        let (parts, omitted) = read_files(outgoing).await;
+
+        let attached = parts.len();
+        let message_id = message_id(&from);
+        let message = build_message(from, recipients, &message_id, &document, note, parts)?;
+        transport(&settings)?
+            .send(message)
+            .await
+            .map_err(smtp_error)?;
+        Ok(EmailSendResult {
+            message_id,
+            attachments: attached,
+            omitted,
+        })
+    }
+    .await;
+
+    // Addresses are left out of the audit log.
+    audit::audit_log_command(
+        &state.pool(),
+        "email_send_ticket",
+        &format!("{} {} to {} recipients", db_path, ticket_id, to.len()),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Connect and log in to the SMTP server with the saved settings, so the
+/// user can check them. Nothing is sent.
+#[tauri::command]
+pub async fn email_test(state: tauri::State<'_, TelemetryState>) -> Result<(), AppError> {
+    let settings = configured_settings(&state.pool()).await?;
+    parse_mailbox(&settings.from)?;
+    let transport = transport(&settings)?;
+    let connected = with_timeout(
+        async { transport.test_connection().await.map_err(smtp_error) },
+        TEST_TIMEOUT_MS,
+    )
+    .await?;
+    if !connected {
+        return Err(AppError::Network(format!(
+            "{} did not answer NOOP",
+            settings.host
+        )));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Settings stored in `kv_store`, `None` when unset or unreadable.
+pub(crate) async fn load_settings(pool: &SqlitePool) -> Result<Option<SmtpSettings>, AppError> {
+    Ok(kv::get(pool, SMTP_KV)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// A host name, a port, a valid sender, and TLS whenever a user name is
+/// sent.
+pub(crate) fn validate_settings(settings: &SmtpSettings) -> Result<(), AppError> {
+    if settings.host.is_empty() || settings.host.contains(|c: char| c.is_whitespace()) {
+        return Err(AppError::Validation("invalid SMTP host".into()));
+    }
+    if settings.port == 0 {
+        return Err(AppError::Validation("invalid SMTP port".into()));
+    }
+    parse_mailbox(&settings.from)?;
+    if settings.tls == SmtpTlsMode::None && settings.username.is_some() {
+        return Err(AppError::Validation(
+            "credentials are only sent over STARTTLS or TLS".into(),
+        ));
+    }
+    Ok(())
+}
+
+async fn configured_settings(pool: &SqlitePool) -> Result<SmtpSettings, AppError> {
+    with_timeout(load_settings(pool), DB_TIMEOUT_MS)
+        .await?
+        .ok_or_else(|| AppError::Validation("no SMTP server is configured".into()))
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, AppError> {
+    address
+        .trim()
+        .parse()
+        .map_err(|e| AppError::Validation(format!("invalid email address {:?}: {}", address, e)))
+}
+
+/// SMTP client for `settings`, logging in with the stored password when a
+/// user name is set.
+fn transport(settings: &SmtpSettings) -> Result<AsyncSmtpTransport<Tokio1Executor>, AppError> {
+    let tls = match settings.tls {
+        SmtpTlsMode::Starttls => Tls::Required(tls_parameters(&settings.host)?),
+        SmtpTlsMode::Implicit => Tls::Wrapper(tls_parameters(&settings.host)?),
+        SmtpTlsMode::None => Tls::None,
+    };
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        .port(settings.port)
+        .tls(tls)
+        .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECS)));
+    if let Some(username) = &settings.username {
+        let password = secrets::get_secret(SMTP_PASSWORD_SECRET)?.unwrap_or_default();
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+fn tls_parameters(host: &str) -> Result<TlsParameters, AppError> {
+    TlsParameters::new(host.to_string()).map_err(smtp_error)
+}
+
+/// Map an SMTP failure to the error shown to the user: refused
+/// credentials, a rejected address, an untrusted certificate, a timeout,
+/// or a server that cannot be reached.
+fn smtp_error(err: lettre::transport::smtp::Error) -> AppError {
+    let code = err.status().map(|code| code.to_string());
+    match code.as_deref() {
+        Some("530" | "534" | "535" | "538") => {
+            AppError::Unauthorized(format!("the SMTP server refused the credentials: {}", err))
+        }
+        Some("550" | "551" | "553") => AppError::Validation(format!("recipient rejected: {}", err)),
+        Some(_) => AppError::Network(format!("the SMTP server refused the email: {}", err)),
+        None if err.is_timeout() => {
+            AppError::Timeout(format!("the SMTP server did not answer: {}", err))
+        }
+        None if err.is_tls() && telemetry::is_certificate_error(&err) => {
+            AppError::Certificate(err.to_string())
+        }
+        None => AppError::Network(format!("cannot connect to the SMTP server: {}", err)),
+    }
+}
+
+/// Attachment parts of `files` in order, and the names of those left out
+/// because they are missing or would take the email past the size cap.
+async fn read_files(files: Vec<OutgoingFile>) -> (Vec<SinglePart>, Vec<String>) {
+    let mut parts = Vec::new();
+    let mut omitted = Vec::new();
+    let mut total = 0;
+    for file in files {
+        let size = match tokio::fs::metadata(&file.path).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                log::warn!("email_send_ticket: {}: {}", file.filename, e);
+                omitted.push(file.filename);
+                continue;
+            }
+        };
+        if total + size > MAX_ATTACHMENTS_BYTES {
+            omitted.push(file.filename);
+            continue;
+        }
+        let bytes = match tokio::fs::read(&file.path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::warn!("email_send_ticket: {}: {}", file.filename, e);
+                omitted.push(file.filename);
+                continue;
+            }
+        };
+        total += size;
+        let content_type = ContentType::parse(&file.mime_type)
+            .unwrap_or_else(|_| ContentType::parse("application/octet-stream").unwrap());
+        parts.push(Attachment::new(file.filename).body(bytes, content_type));
+    }
+    (parts, omitted)
+}
+
+/// `<timestamp.random@sender domain>`, so the id does not give away the
+/// name of the machine.
+fn message_id(from: &Mailbox) -> String {
+    let random = RandomState::new().build_hasher().finish();
+    format!(
+        "<{}.{:016x}@{}>",
+        chrono::Utc::now().timestamp_millis(),
+        random,
+        from.email.domain()
+    )
+}
+
+fn build_message(
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    message_id: &str,
+    document: &TicketDocument,
+    note: Option<&str>,
+    files: Vec<SinglePart>,
+) -> Result<Message, AppError> {
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(format!("[{}] {}", document.id, document.title))
+        .message_id(Some(message_id.to_string()));
+    for mailbox in to {
+        builder = builder.to(mailbox);
+    }
+
+    let markdown = match note {
+        Some(note) => format!("{}\n\n---\n\n{}", note, document.markdown),
+        None => document.markdown.clone(),
+    };
+    let html = markdown_to_html(&markdown);
+    let body = MultiPart::alternative_plain_html(markdown, html);
+    let message = if files.is_empty() {
+        builder.multipart(body)
+    } else {
+        let mut mixed = MultiPart::mixed().multipart(body);
+        for file in files {
+            mixed = mixed.singlepart(file);
+        }
+        builder.multipart(mixed)
+    };
+    message.map_err(|e| AppError::Validation(format!("cannot build the email: {}", e)))
+}
+
+/// HTML document of `markdown`. Raw HTML in the ticket is shown as text,
+/// not rendered.
+fn markdown_to_html(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    });
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, events);
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"></head>\n<body style=\"font-family: sans-serif; line-height: 1.5\">\n{}</body></html>\n",
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    fn settings(port: u16, username: Option<&str>) -> SmtpSettings {
+        SmtpSettings {
+            host: "127.0.0.1".into(),
+            port,
+            tls: SmtpTlsMode::None,
+            from: "Ana <ana@consulting.example>".into(),
+            username: username.map(String::from),
+        }
+    }
+
+    fn document() -> TicketDocument {
+        TicketDocument {
+            id: "BUG-7".into(),
+            title: "Crash on save".into(),
+            markdown: "# Crash on save\n\n<script>alert(1)</script>\n\n- **Type:** BUG\n".into(),
+            screenshots: Vec::new(),
+        }
+    }
+
+    /// An SMTP server on a local port that greets, then answers each
+    /// command line with the next of `replies` and hangs up.
+    fn smtp_server(replies: &'static [&'static str]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            for reply in replies {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    return;
+                }
+                if writer.write_all(reply.as_bytes()).is_err() {
+                    return;
+                }
+            }
+        });
+        port
+    }
+
+    async fn send(settings: &SmtpSettings) -> Result<(), AppError> {
+        let from = parse_mailbox(&settings.from)?;
+        let to = vec![parse_mailbox("client@example.com")?];
+        let message = build_message(from, to, "<1@example>", &document(), None, Vec::new())?;
+        transport(settings)?
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(smtp_error)
+    }
+
+    #[tokio::test]
+    async fn rejected_recipient_is_a_validation_error() {
+        let port = smtp_server(&[
+            "250 localhost\r\n",
+            "250 OK\r\n",
+            "550 5.1.1 No such user\r\n",
+        ]);
+        let err = send(&settings(port, None)).await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(m) if m.contains("No such user")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn refused_credentials_are_unauthorized() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let port = smtp_server(&[
+            "250-localhost\r\n250 AUTH PLAIN LOGIN\r\n",
+            "535 5.7.8 Authentication failed\r\n",
+        ]);
+        let err = send(&settings(port, Some("ana"))).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_a_network_error() {
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let err = send(&settings(port, None)).await.unwrap_err();
+        assert!(matches!(err, AppError::Network(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn credentials_need_tls() {
+        assert!(validate_settings(&settings(25, None)).is_ok());
+        let mut with_user = settings(587, Some("ana"));
+        assert!(validate_settings(&with_user).is_err());
+        with_user.tls = SmtpTlsMode::Starttls;
+        assert!(validate_settings(&with_user).is_ok());
+
+        for broken in [
+            SmtpSettings {
+                host: "smtp example.com".into(),
+                ..settings(25, None)
+            },
+            SmtpSettings {
+                port: 0,
+                ..settings(25, None)
+            },
+            SmtpSettings {
+                from: "not an address".into(),
+                ..settings(25, None)
+            },
+        ] {
+            let err = validate_settings(&broken).unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn message_has_markdown_and_escaped_html() {
+        let from = parse_mailbox("Ana <ana@consulting.example>").unwrap();
+        let id = message_id(&from);
+        assert!(
+            id.starts_with('<') && id.ends_with("@consulting.example>"),
+            "{}",
+            id
+        );
+        let to = vec![parse_mailbox("client@example.com").unwrap()];
+        let message =
+            build_message(from, to, &id, &document(), Some("See below."), Vec::new()).unwrap();
+        let raw = String::from_utf8(message.formatted()).unwrap();
+        assert!(raw.contains("Subject: [BUG-7] Crash on save"), "{}", raw);
+        assert!(raw.contains(&format!("Message-ID: {}", id)), "{}", raw);
+        assert!(raw.contains("multipart/alternative"), "{}", raw);
+
+        let html = markdown_to_html("See below.\n\n<script>alert(1)</script>\n\n**bold**");
+        assert!(html.contains("&lt;script&gt;"), "{}", html);
+        assert!(!html.contains("<script>"), "{}", html);
+        assert!(html.contains("<strong>bold</strong>"), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn attachments_past_the_cap_or_missing_are_omitted() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = dir.path().join("big.bin");
+        std::fs::File::create(&big)
+            .unwrap()
+            .set_len(MAX_ATTACHMENTS_BYTES - 10)
+            .unwrap();
+        let small = dir.path().join("small.txt");
+        std::fs::write(&small, b"0123456789ab").unwrap();
+        let file = |name: &str, path: &Path| OutgoingFile {
+            filename: name.into(),
+            mime_type: attachments::mime_type(name).into(),
+            path: path.to_path_buf(),
+        };
+
+        let (parts, omitted) = read_files(vec![
+            file("big.bin", &big),
+            file("gone.png", &dir.path().join("gone.png")),
+            file("small.txt", &small),
+        ])
+        .await;
+        assert_eq!(parts.len(), 1);
+        assert_eq!(omitted, ["gone.png", "small.txt"]);
+    }
+}
@@ -0,0 +1,509 @@
+use std::path::Path;
+use std::time::Duration;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::ConnectOptions;
+use tauri::AppHandle;
+use tauri_plugin_sql::{DbInstances, DbPool};
+
+use crate::archive_db;
+use crate::audit;
+use crate::backup::{self, MaintenanceState};
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Service name of the OS keyring entries; the account is the canonical
+/// database path.
+const KEYRING_SERVICE: &str = "ticketflow";
+
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// SQLCipher 4 default page size. An intact encrypted file is a whole
+/// number of pages.
+const CIPHER_PAGE_SIZE: u64 = 4096;
+
+/// Primary SQLite result codes (the extended code masked with 0xff).
+const SQLITE_CORRUPT: i64 = 11;
+const SQLITE_NOTADB: i64 = 26;
+
+const BUSY_TIMEOUT_SECS: u64 = 5;
+
+/// Suffix of the encrypted copy while `encrypt_project` writes it.
+const ENCRYPTING_SUFFIX: &str = ".encrypting";
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Whether the project database at `db_path` is encrypted.
+#[tauri::command]
+pub fn is_project_encrypted(db_path: String, app: AppHandle) -> Result<bool, AppError> {
+    let db_path = Path::new(&db_path);
+    files::validate_path(&app, db_path)?;
+    Ok(project_db::is_encrypted(db_path))
+}
+
+/// Encrypt a plaintext project database in place with SQLCipher. The data is
+/// copied into a new keyed file with `sqlcipher_export`, verified, then moved
+/// over the original. Existing backups are left as they are. Fails while the
+/// project is open; call closeDatabase() first.
+#[tauri::command]
+pub async fn encrypt_project(
+    db_path: String,
+    passphrase: String,
+    remember: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            let db = Path::new(&db_path);
+            files::validate_path(&app, db)?;
+            validate_passphrase(&passphrase)?;
+            if project_db::is_encrypted(db) {
+                return Err(AppError::Validation("project is already encrypted".into()));
+            }
+            if project_db::is_open_in_frontend(&app, db).await {
+                return Err(AppError::Validation(
+                    "project is open; close it before encrypting".into(),
+                ));
+            }
+
+            let encrypted = backup::append_suffix(db, ENCRYPTING_SUFFIX);
+            std::fs::remove_file(&encrypted).ok();
+            if let Err(e) = export_encrypted(db, &encrypted, &passphrase).await {
+                std::fs::remove_file(&encrypted).ok();
+                return Err(e);
+            }
+            if let Err(e) = verify_passphrase(&encrypted, &passphrase).await {
+                std::fs::remove_file(&encrypted).ok();
+                return Err(e);
+            }
+
+            // The WAL was checkpointed into the plaintext file; its sidecars
+            // must not be replayed into the encrypted one.
+            for sidecar in ["-wal", "-shm"] {
+                let path = backup::append_suffix(db, sidecar);
+                if path.exists() {
+                    std::fs::remove_file(&path)?;
+                }
+            }
+            std::fs::rename(&encrypted, db)?;
+
+            project_db::set_project_key(db, Some(&passphrase));
+            if remember.unwrap_or(false) {
+                store_passphrase(db, Some(&passphrase))?;
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "encrypt_project",
+        &db_path,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Unlock an encrypted project and register a keyed pool for it in
+/// tauri-plugin-sql, so the frontend reaches it with `Database.get` instead
+/// of `Database.load` (which cannot pass a key). Without `passphrase`, the
+/// one unlocked earlier this session or stored in the OS keyring is used.
+/// A wrong passphrase fails with `Unauthorized`, a damaged file with
+/// `Database`.
+#[tauri::command]
+pub async fn open_encrypted_project(
+    db_path: String,
+    passphrase: Option<String>,
+    remember: Option<bool>,
+    app: AppHandle,
+    instances: tauri::State<'_, DbInstances>,
+) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            let db = Path::new(&db_path);
+            files::validate_path(&app, db)?;
+            if !project_db::is_encrypted(db) {
+                return Err(AppError::Validation("project is not encrypted".into()));
+            }
+            let passphrase = passphrase
+                .or_else(|| project_db::project_key(db))
+                .or_else(|| stored_passphrase(db))
+                .ok_or_else(|| AppError::Unauthorized("passphrase required".into()))?;
+            verify_passphrase(db, &passphrase).await?;
+            project_db::set_project_key(db, Some(&passphrase));
+            match remember {
+                Some(true) => store_passphrase(db, Some(&passphrase))?,
+                Some(false) => store_passphrase(db, None)?,
+                None => {}
+            }
+
+            let url = format!("sqlite:{}", db_path);
+            let mut pools = instances.0.write().await;
+            if let Some(DbPool::Sqlite(pool)) = pools.get(&url) {
+                if !pool.is_closed() {
+                    return Ok(());
+                }
+            }
+            // Same pragmas the frontend sets on the connections it loads.
+            let options = project_db::configure_options(keyed_options(db, &passphrase), false);
+            let pool = SqlitePoolOptions::new().connect_with(options).await?;
+            pools.insert(url, DbPool::Sqlite(pool));
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Re-encrypt a project under a new passphrase (`PRAGMA rekey`). Fails
+/// while the project is open; call closeDatabase() first. With `remember`
+/// unset, a passphrase stored in the keyring is replaced by the new one.
+#[tauri::command]
+pub async fn change_project_passphrase(
+    db_path: String,
+    current_passphrase: String,
+    new_passphrase: String,
+    remember: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            let db = Path::new(&db_path);
+            files::validate_path(&app, db)?;
+            validate_passphrase(&new_passphrase)?;
+            if !project_db::is_encrypted(db) {
+                return Err(AppError::Validation("project is not encrypted".into()));
+            }
+            if project_db::is_open_in_frontend(&app, db).await {
+                return Err(AppError::Validation(
+                    "project is open; close it before changing its passphrase".into(),
+                ));
+            }
+            verify_passphrase(db, &current_passphrase).await?;
+
+            let mut conn = keyed_options(db, &current_passphrase).connect().await?;
+            project_db::checkpoint_truncate(&mut conn).await?;
+            // SQLCipher cannot rekey a database in WAL mode.
+            sqlx::query_scalar::<_, String>("PRAGMA journal_mode = DELETE;")
+                .fetch_one(&mut conn)
+                .await?;
+            sqlx::query(&format!(
+                "PRAGMA rekey = {};",
+                project_db::key_pragma(&new_passphrase)
+            ))
+            .execute(&mut conn)
+            .await?;
+            sqlx::query_scalar::<_, String>("PRAGMA journal_mode = WAL;")
+                .fetch_one(&mut conn)
+                .await?;
+            drop(conn);
+            verify_passphrase(db, &new_passphrase).await?;
+
+            // The archive database is keyed like its project.
+            let archive = archive_db::archive_path(db);
+            if project_db::is_encrypted(&archive) {
+                let mut conn = keyed_options(&archive, &current_passphrase)
+                    .journal_mode(SqliteJournalMode::Delete)
+                    .connect()
+                    .await?;
+                sqlx::query(&format!(
+                    "PRAGMA rekey = {};",
+                    project_db::key_pragma(&new_passphrase)
+                ))
+                .execute(&mut conn)
+                .await?;
+            }
+
+            project_db::set_project_key(db, Some(&new_passphrase));
+            let stored = stored_passphrase(db).is_some();
+            match remember {
+                Some(false) => store_passphrase(db, None)?,
+                Some(true) => store_passphrase(db, Some(&new_passphrase))?,
+                None if stored => store_passphrase(db, Some(&new_passphrase))?,
+                None => {}
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "change_project_passphrase",
+        &db_path,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Move the keyring entry of a project database whose path changed (e.g.
+/// after `rename_project`). Best effort: failures are only logged.
+pub fn move_stored_passphrase(old_db: &Path, new_db: &Path) {
+    let Some(passphrase) = stored_passphrase(old_db) else {
+        return;
+    };
+    if let Err(e) = store_passphrase(new_db, Some(&passphrase)) {
+        log::warn!("move_stored_passphrase: {}", e);
+        return;
+    }
+    if let Err(e) = store_passphrase(old_db, None) {
+        log::warn!("move_stored_passphrase: {}", e);
+    }
+}
+
+/// Forget the keyring entry of a deleted project database. Best effort.
+pub fn forget_stored_passphrase(db_path: &Path) {
+    if let Err(e) = store_passphrase(db_path, None) {
+        log::warn!("forget_stored_passphrase: {}", e);
+    }
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(AppError::Validation(format!(
+            "passphrase must be at least {} characters",
+            MIN_PASSPHRASE_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// Connection options for `db_path` keyed with `passphrase`.
+fn keyed_options(db_path: &Path, passphrase: &str) -> SqliteConnectOptions {
+    SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(false)
+        .pragma("key", project_db::key_pragma(passphrase))
+        .busy_timeout(Duration::from_secs(BUSY_TIMEOUT_SECS))
+}
+
+/// Copy the plaintext database `db_path` into a new file `output` encrypted
+/// with `passphrase`.
+pub(crate) async fn export_encrypted(
+    db_path: &Path,
+    output: &Path,
+    passphrase: &str,
+) -> Result<(), AppError> {
+    let mut conn = project_db::open_connection(db_path).await?;
+    let cipher: Option<String> = sqlx::query_scalar("PRAGMA cipher_version;")
+        .fetch_optional(&mut conn)
+        .await?;
+    if cipher.is_none() {
+        return Err(AppError::Database(
+            "this build was linked without SQLCipher".into(),
+        ));
+    }
+    project_db::checkpoint_truncate(&mut conn).await?;
+    let version = project_db::schema_version(&mut conn).await?;
+
+    // ATTACH opens with the flags of the main connection, which may not
+    // create files; an empty file is an empty database.
+    std::fs::File::create(output)?;
+    sqlx::query("ATTACH DATABASE ? AS encrypted KEY ?")
+        .bind(output.to_string_lossy().into_owned())
+        .bind(passphrase)
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("SELECT sqlcipher_export('encrypted')")
+        .execute(&mut conn)
+        .await?;
+    // sqlcipher_export copies the schema and rows, not the header fields.
+    sqlx::query(&format!("PRAGMA encrypted.user_version = {};", version))
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("DETACH DATABASE encrypted")
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Open `db_path` with `passphrase` and run a quick check. SQLCipher reports
+/// a key that does not decrypt the first page as SQLITE_NOTADB, the same
+/// code as a damaged header, so a file that is still a whole number of
+/// pages is taken as a wrong passphrase and anything else as corruption.
+async fn verify_passphrase(db_path: &Path, passphrase: &str) -> Result<(), AppError> {
+    let result = async {
+        let mut conn = keyed_options(db_path, passphrase)
+            .read_only(true)
+            .connect()
+            .await?;
+        sqlx::query_scalar::<_, String>("PRAGMA quick_check;")
+            .fetch_all(&mut conn)
+            .await
+    }
+    .await;
+
+    let rows = match result {
+        Ok(rows) => rows,
+        Err(e) => return Err(classify_open_error(e, db_path)),
+    };
+    let problems: Vec<String> = rows.into_iter().filter(|row| row != "ok").collect();
+    if !problems.is_empty() {
+        return Err(AppError::Database(format!(
+            "database is corrupt: {}",
+            problems.join("; ")
+        )));
+    }
+    Ok(())
+}
+
+fn classify_open_error(e: sqlx::Error, db_path: &Path) -> AppError {
+    let code = match &e {
+        sqlx::Error::Database(db) => db
+            .code()
+            .and_then(|code| code.parse::<i64>().ok())
+            .map(|code| code & 0xff),
+        _ => None,
+    };
+    let whole_pages = std::fs::metadata(db_path)
+        .map(|meta| meta.len() > 0 && meta.len() % CIPHER_PAGE_SIZE == 0)
+        .unwrap_or(false);
+    match code {
+        Some(SQLITE_NOTADB) if whole_pages => AppError::Unauthorized("wrong passphrase".into()),
+        Some(SQLITE_NOTADB) | Some(SQLITE_CORRUPT) => {
+            AppError::Database(format!("database is corrupt: {}", e))
+        }
+        _ => e.into(),
+    }
+}
+
+fn keyring_entry(db_path: &Path) -> Result<keyring::Entry, AppError> {
+    let account = project_db::canonical(db_path)
+        .to_string_lossy()
+        .into_owned();
+    keyring::Entry::new(KEYRING_SERVICE, &account)
+        .map_err(|e| AppError::Io(format!("keyring unavailable: {}", e)))
+}
+
+fn stored_passphrase(db_path: &Path) -> Option<String> {
+    match keyring_entry(db_path).map(|entry| entry.get_password()) {
+        Ok(Ok(passphrase)) => Some(passphrase),
+        Ok(Err(keyring::Error::NoEntry)) => None,
+        Ok(Err(e)) => {
+            log::warn!("stored_passphrase: {}", e);
+            None
+        }
+        Err(e) => {
+            log::warn!("stored_passphrase: {}", e);
+            None
+        }
+    }
+}
+
+/// Save `passphrase` in the OS keyring, or delete the entry with `None`.
+fn store_passphrase(db_path: &Path, passphrase: Option<&str>) -> Result<(), AppError> {
+    let entry = keyring_entry(db_path)?;
+    let result = match passphrase {
+        Some(passphrase) => entry.set_password(passphrase),
+        None => match entry.delete_credential() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            other => other,
+        },
+    };
+    result.map_err(|e| AppError::Io(format!("keyring: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    /// A plaintext project database in `dir` with one ticket.
+    async fn plaintext_db(dir: &Path) -> std::path::PathBuf {
+        let seed = [test_support::BOARD, test_support::INVOICE].concat();
+        test_support::create_db(dir, project_db::SUPPORTED_SCHEMA_VERSION, &seed).await
+    }
+
+    #[test]
+    fn short_passphrases_are_refused() {
+        assert!(matches!(
+            validate_passphrase("1234567"),
+            Err(AppError::Validation(_))
+        ));
+        // Counted in characters, not bytes.
+        assert!(validate_passphrase("ééééééé").is_err());
+        validate_passphrase("éééééééé").unwrap();
+    }
+
+    #[tokio::test]
+    async fn undecryptable_whole_pages_are_a_wrong_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locked.db");
+        std::fs::write(&path, vec![0x5a; 2 * CIPHER_PAGE_SIZE as usize]).unwrap();
+        let err = verify_passphrase(&path, "correct horse").await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn partial_pages_are_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("damaged.db");
+        std::fs::write(&path, vec![0x5a; CIPHER_PAGE_SIZE as usize + 100]).unwrap();
+        let err = verify_passphrase(&path, "correct horse").await.unwrap_err();
+        assert!(matches!(err, AppError::Database(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn exported_copy_opens_only_with_its_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = plaintext_db(dir.path()).await;
+        let encrypted = backup::append_suffix(&db, ENCRYPTING_SUFFIX);
+        export_encrypted(&db, &encrypted, "correct horse")
+            .await
+            .unwrap();
+
+        assert!(project_db::is_encrypted(&encrypted));
+        verify_passphrase(&encrypted, "correct horse")
+            .await
+            .unwrap();
+        let err = verify_passphrase(&encrypted, "battery staple")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+
+        let mut conn = keyed_options(&encrypted, "correct horse")
+            .connect()
+            .await
+            .unwrap();
+        let title: String = sqlx::query_scalar("SELECT title FROM backlog_items")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(title, "Invoice client");
+        assert_eq!(
+            project_db::schema_version(&mut conn).await.unwrap(),
+            project_db::SUPPORTED_SCHEMA_VERSION
+        );
+    }
+}
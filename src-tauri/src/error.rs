@@ -0,0 +1,130 @@
+use serde::Serialize;
+
+/// Error type returned by every Tauri command.
+///
+/// Serialized as `{ "kind": "<Variant>", "message": "<text>" }` so the
+/// frontend can branch on `kind` (see `src/types/appError.ts`).
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize, PartialEq))]
+#[serde(tag = "kind", content = "message")]
+pub enum AppError {
+    Database(String),
+    Network(String),
+    Validation(String),
+    Io(String),
+    Unauthorized(String),
+    Timeout(String),
+    /// The project is locked by another machine (see `project_lock.rs`).
+    InUse(String),
+    /// A storage quota would be exceeded (see `attachments.rs`).
+    QuotaExceeded(String),
+    /// The project database cannot be written (see `open_project_probe`).
+    ReadOnly(String),
+    /// A server's TLS certificate is not trusted, e.g. self-signed (see
+    /// `import/gitlab.rs`).
+    Certificate(String),
+}
+
+impl AppError {
+    /// Variant name, matching the serialized `kind` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "Database",
+            AppError::Network(_) => "Network",
+            AppError::Validation(_) => "Validation",
+            AppError::Io(_) => "Io",
+            AppError::Unauthorized(_) => "Unauthorized",
+            AppError::Timeout(_) => "Timeout",
+            AppError::InUse(_) => "InUse",
+            AppError::QuotaExceeded(_) => "QuotaExceeded",
+            AppError::ReadOnly(_) => "ReadOnly",
+            AppError::Certificate(_) => "Certificate",
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Database(msg) => write!(f, "database error: {}", msg),
+            AppError::Network(msg) => write!(f, "network error: {}", msg),
+            AppError::Validation(msg) => write!(f, "validation error: {}", msg),
+            AppError::Io(msg) => write!(f, "I/O error: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "unauthorized: {}", msg),
+            AppError::Timeout(msg) => write!(f, "timeout: {}", msg),
+            AppError::InUse(msg) => write!(f, "project in use: {}", msg),
+            AppError::QuotaExceeded(msg) => write!(f, "quota exceeded: {}", msg),
+            AppError::ReadOnly(msg) => write!(f, "project is read-only: {}", msg),
+            AppError::Certificate(msg) => write!(f, "untrusted certificate: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        AppError::Network(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::Io(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_variants() -> Vec<AppError> {
+        vec![
+            AppError::Database("disk I/O error".into()),
+            AppError::Network("connection refused".into()),
+            AppError::Validation("window title is empty".into()),
+            AppError::Io("permission denied".into()),
+            AppError::Unauthorized("wrong PIN".into()),
+            AppError::Timeout("operation timed out after 30000 ms".into()),
+            AppError::InUse("locked by laptop-42".into()),
+            AppError::QuotaExceeded("attachments exceed 100 MB".into()),
+            AppError::ReadOnly("backlog.db is read-only".into()),
+            AppError::Certificate("self-signed certificate".into()),
+        ]
+    }
+
+    #[test]
+    fn every_variant_serializes_as_kind_and_message() {
+        for err in all_variants() {
+            let json = serde_json::to_value(&err).unwrap();
+            let object = json.as_object().unwrap();
+            assert_eq!(object.len(), 2, "{}", json);
+            assert_eq!(object["kind"], err.kind());
+            let message = object["message"].as_str().unwrap();
+            assert!(!message.is_empty());
+            assert!(err.to_string().ends_with(message), "{}", json);
+        }
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        for err in all_variants() {
+            let json = serde_json::to_string(&err).unwrap();
+            let back: AppError = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, err);
+        }
+    }
+
+    #[test]
+    fn frontend_shape_deserializes() {
+        let err: AppError =
+            serde_json::from_str(r#"{"kind":"InUse","message":"locked by laptop-42"}"#).unwrap();
+        assert_eq!(err, AppError::InUse("locked by laptop-42".into()));
+    }
+}
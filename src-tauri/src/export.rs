@@ -0,0 +1,1402 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::search;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Rows fetched per query while exporting.
+const PAGE_SIZE: i64 = 1_000;
+
+/// A progress event is emitted every this many rows.
+const PROGRESS_EVERY: usize = 2_000;
+
+const EXPORT_PROGRESS_EVENT: &str = "export:progress";
+
+/// Version of the JSON export document layout. Bump on breaking changes so
+/// an importer can refuse documents newer than it understands.
+pub const JSON_EXPORT_FORMAT_VERSION: i64 = 1;
+
+/// `format` marker at the top of a JSON export.
+pub const JSON_EXPORT_FORMAT: &str = "ticketflow-project";
+
+/// JSON exports larger than this are gzip-compressed (`.json.gz`).
+const GZIP_THRESHOLD_BYTES: u64 = 20 * 1024 * 1024;
+
+/// A progress event is emitted every this many tickets of a Markdown export.
+const MARKDOWN_PROGRESS_EVERY: usize = 50;
+
+/// Screenshot folder of a project, relative to `backlog.db` (see
+/// `src/lib/screenshots.ts`), and its replacement in a Markdown export.
+const SCREENSHOTS_DIR: &str = ".backlog-assets/screenshots";
+const MARKDOWN_ASSETS_DIR: &str = "assets";
+
+/// Tickets as read by `MarkdownItem`, without `WHERE` or `ORDER BY`.
+const MARKDOWN_ITEM_QUERY: &str = "
+    SELECT i.id, i.type, i.title, i.emoji, s.title AS section, i.component,
+      i.module, i.severity, i.priority, i.effort, i.description,
+      i.user_story, i.specs, i.reproduction, i.criteria, i.dependencies,
+      i.constraints, i.screens, i.screenshots, i.created_at, i.updated_at
+    FROM backlog_items i
+    LEFT JOIN sections s ON s.id = i.section_id";
+
+/// Longest Markdown file name, extension excluded.
+const MAX_SLUG_CHARS: usize = 80;
+
+/// UTF-8 byte order mark, so Excel detects the encoding.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Exportable columns: CSV header name and the SQL expression producing it
+/// (`i` = backlog_items, `s` = sections).
+const CSV_COLUMNS: [(&str, &str); 19] = [
+    ("id", "i.id"),
+    ("type", "i.type"),
+    ("title", "i.title"),
+    ("section", "s.title"),
+    ("emoji", "i.emoji"),
+    ("component", "i.component"),
+    ("module", "i.module"),
+    ("severity", "i.severity"),
+    ("priority", "i.priority"),
+    ("effort", "i.effort"),
+    ("description", "i.description"),
+    ("user_story", "i.user_story"),
+    ("specs", "i.specs"),
+    ("reproduction", "i.reproduction"),
+    ("criteria", "i.criteria"),
+    ("dependencies", "i.dependencies"),
+    ("constraints", "i.constraints"),
+    ("created_at", "i.created_at"),
+    ("updated_at", "i.updated_at"),
+];
+
+/// Columns exported when `options.columns` is not given.
+const DEFAULT_COLUMNS: [&str; 10] = [
+    "id",
+    "type",
+    "title",
+    "section",
+    "severity",
+    "priority",
+    "effort",
+    "description",
+    "created_at",
+    "updated_at",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options accepted by `export_tickets_csv`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CsvExportOptions {
+    /// Columns to export, in order (see `CSV_COLUMNS`).
+    pub columns: Option<Vec<String>>,
+    /// Only export items in these sections (the board's status columns).
+    pub sections: Option<Vec<String>>,
+    /// Only export items updated at or after this date (`YYYY-MM-DD...`).
+    pub updated_from: Option<String>,
+    /// Only export items updated before this date.
+    pub updated_to: Option<String>,
+    /// Prefix the file with a UTF-8 BOM for Excel.
+    pub bom: bool,
+    /// Caller-chosen id used to cancel the export with `cancel_export`.
+    pub job_id: Option<String>,
+}
+
+/// Return value of `export_tickets_csv`.
+#[derive(Debug, Serialize)]
+pub struct CsvExportResult {
+    pub path: String,
+    pub rows: usize,
+}
+
+/// Return value of `export_project_json`.
+#[derive(Debug, Serialize)]
+pub struct JsonExportResult {
+    /// Final path; `.gz` is appended when the output was compressed.
+    pub path: String,
+    pub size_bytes: u64,
+    pub compressed: bool,
+    /// Row count per exported table.
+    pub tables: Vec<(String, usize)>,
+}
+
+/// Options accepted by `export_markdown`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MarkdownExportOptions {
+    /// Replace files already in the destination folder; otherwise they are
+    /// left alone and listed in `skipped`.
+    pub overwrite: bool,
+    /// Caller-chosen id used to cancel the export with `cancel_export`.
+    pub job_id: Option<String>,
+}
+
+/// Return value of `export_markdown`.
+#[derive(Debug, Serialize)]
+pub struct MarkdownExportResult {
+    pub path: String,
+    /// Markdown files written, `index.md` included.
+    pub files: usize,
+    /// Screenshots copied into `assets/`.
+    pub assets: usize,
+    /// Existing files left untouched (relative to `path`).
+    pub skipped: Vec<String>,
+}
+
+/// Payload of `export:progress`.
+#[derive(Debug, Clone, Serialize)]
+struct ExportProgress {
+    job_id: Option<String>,
+    rows: usize,
+}
+
+/// A ticket as exported to Markdown.
+#[derive(Debug, sqlx::FromRow)]
+struct MarkdownItem {
+    id: String,
+    #[sqlx(rename = "type")]
+    item_type: String,
+    title: String,
+    emoji: Option<String>,
+    section: Option<String>,
+    component: Option<String>,
+    module: Option<String>,
+    severity: Option<String>,
+    priority: Option<String>,
+    effort: Option<String>,
+    description: Option<String>,
+    user_story: Option<String>,
+    specs: Option<String>,
+    reproduction: Option<String>,
+    criteria: Option<String>,
+    dependencies: Option<String>,
+    constraints: Option<String>,
+    screens: Option<String>,
+    screenshots: Option<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+}
+
+/// An entry of a ticket's `screenshots` JSON column.
+#[derive(Debug, Deserialize)]
+struct ScreenshotRef {
+    filename: String,
+    alt: Option<String>,
+}
+
+/// A ticket rendered for use outside the app, as by `email_send_ticket`.
+pub(crate) struct TicketDocument {
+    pub id: String,
+    pub title: String,
+    /// Markdown of the ticket without front matter, its fields listed last.
+    pub markdown: String,
+    /// Screenshot files of the ticket, which the Markdown does not embed.
+    pub screenshots: Vec<PathBuf>,
+}
+
+/// Tauri managed state holding the cancellation flags of running exports.
+#[derive(Default)]
+pub struct ExportState {
+    jobs: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Stream the project's items to an RFC 4180 CSV file, page by page, so
+/// large projects never go through the webview. Emits `export:progress`
+/// every few thousand rows. The file is written to `<dest_path>.tmp` and
+/// only renamed into place once complete.
+#[tauri::command]
+pub async fn export_tickets_csv(
+    db_path: String,
+    dest_path: String,
+    options: CsvExportOptions,
+    app: AppHandle,
+    state: tauri::State<'_, ExportState>,
+) -> Result<CsvExportResult, AppError> {
+    let dest = files::validate_path(&app, Path::new(&dest_path))?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(job_id) = &options.job_id {
+        let mut jobs = state.jobs.lock().unwrap();
+        if jobs.contains_key(job_id) {
+            return Err(AppError::Validation(format!(
+                "export job {} is already running",
+                job_id
+            )));
+        }
+        jobs.insert(job_id.clone(), cancelled.clone());
+    }
+
+    let result = write_csv(
+        Path::new(&db_path),
+        &dest,
+        &options,
+        &cancelled,
+        progress_emitter(&app, &options.job_id),
+    )
+    .await;
+
+    if let Some(job_id) = &options.job_id {
+        state.jobs.lock().unwrap().remove(job_id);
+    }
+    result
+}
+
+/// Request cancellation of a running export. The export stops before its
+/// next page and removes its partial output.
+#[tauri::command]
+pub fn cancel_export(job_id: String, state: tauri::State<'_, ExportState>) -> Result<(), AppError> {
+    match state.jobs.lock().unwrap().get(&job_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err(AppError::Validation(format!(
+            "no running export with id {}",
+            job_id
+        ))),
+    }
+}
+
+/// Export every table of a project database to a single JSON document:
+///
+/// ```text
+/// { "format": "ticketflow-project", "schema_version": 1,
+///   "db_schema_version": <PRAGMA user_version>, "app_version": "...",
+///   "exported_at": "...", "tables": { "<table>": [ {row}, ... ], ... } }
+/// ```
+///
+/// Rows are serialized by SQLite (`json_object`) and streamed to disk page by
+/// page. Outputs over 20 MB are gzip-compressed to `<dest_path>.gz`.
+#[tauri::command]
+pub async fn export_project_json(
+    db_path: String,
+    dest_path: String,
+    app: AppHandle,
+) -> Result<JsonExportResult, AppError> {
+    let dest = files::validate_path(&app, Path::new(&dest_path))?;
+    let tmp = tmp_path(&dest);
+
+    let result = write_json(Path::new(&db_path), &tmp).await;
+    let tables = match result {
+        Ok(tables) => tables,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+
+    let size = std::fs::metadata(&tmp)?.len();
+    let (path, compressed) = if size > GZIP_THRESHOLD_BYTES {
+        let mut gz_name = dest.as_os_str().to_os_string();
+        gz_name.push(".gz");
+        let gz_path = PathBuf::from(gz_name);
+        let gzipped = gzip_file(&tmp, &gz_path);
+        let _ = std::fs::remove_file(&tmp);
+        gzipped?;
+        (gz_path, true)
+    } else {
+        std::fs::rename(&tmp, &dest)?;
+        (dest, false)
+    };
+
+    Ok(JsonExportResult {
+        size_bytes: std::fs::metadata(&path)?.len(),
+        path: path.to_string_lossy().into_owned(),
+        compressed,
+        tables,
+    })
+}
+
+/// Export a project as a folder of Markdown files for Obsidian and similar
+/// tools: one file per ticket, named after its id and title, with YAML
+/// front matter (id, type, status, severity, ...) and the ticket's fields
+/// as sections, plus an `index.md` linking every ticket grouped by status
+/// (board section). Screenshots are copied into `assets/` and links to
+/// `.backlog-assets/screenshots/` rewritten to point there.
+///
+/// Existing files are only replaced with `options.overwrite`. Emits
+/// `export:progress` every 50 files; a cancelled export keeps the files
+/// already written.
+#[tauri::command]
+pub async fn export_markdown(
+    db_path: String,
+    dest_dir: String,
+    options: MarkdownExportOptions,
+    app: AppHandle,
+    state: tauri::State<'_, ExportState>,
+) -> Result<MarkdownExportResult, AppError> {
+    let dest = files::validate_path(&app, Path::new(&dest_dir))?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(job_id) = &options.job_id {
+        let mut jobs = state.jobs.lock().unwrap();
+        if jobs.contains_key(job_id) {
+            return Err(AppError::Validation(format!(
+                "export job {} is already running",
+                job_id
+            )));
+        }
+        jobs.insert(job_id.clone(), cancelled.clone());
+    }
+
+    let result = write_markdown(
+        Path::new(&db_path),
+        &dest,
+        &options,
+        &cancelled,
+        progress_emitter(&app, &options.job_id),
+    )
+    .await;
+
+    if let Some(job_id) = &options.job_id {
+        state.jobs.lock().unwrap().remove(job_id);
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Render `ticket_id` of the project at `db_path` as a `TicketDocument`.
+pub(crate) async fn ticket_document(
+    db_path: &Path,
+    ticket_id: &str,
+) -> Result<TicketDocument, AppError> {
+    let item: MarkdownItem = {
+        let mut conn = project_db::open_read_only(db_path).await?;
+        sqlx::query_as(&format!("{} WHERE i.id = ?", MARKDOWN_ITEM_QUERY))
+            .bind(ticket_id)
+            .fetch_optional(&mut conn)
+            .await?
+            .ok_or_else(|| AppError::Validation(format!("no ticket with id {}", ticket_id)))?
+    };
+
+    let mut markdown = render_body(&item, &[]);
+    markdown.push_str("\n---\n\n");
+    for (key, value) in item_fields(&item) {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            let _ = writeln!(markdown, "- **{}:** {}", key, value.trim());
+        }
+    }
+    let screenshots_dir = db_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(SCREENSHOTS_DIR);
+    let screenshots = parse_screenshots(item.screenshots.as_deref())
+        .into_iter()
+        .map(|shot| screenshots_dir.join(shot.filename))
+        .collect();
+    Ok(TicketDocument {
+        id: item.id,
+        title: item.title,
+        markdown,
+        screenshots,
+    })
+}
+
+/// Stream the JSON export document of `db_path` to `out_path`. Returns the
+/// row count of each table. Full-text indexes are left out: they are
+/// rebuilt from the tables they index, and their internal tables have no
+/// rowid to page by.
+async fn write_json(db_path: &Path, out_path: &Path) -> Result<Vec<(String, usize)>, AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let db_schema_version = project_db::schema_version(&mut conn).await?;
+
+    let table_names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM pragma_table_list
+         WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+
+    let mut out = BufWriter::new(std::fs::File::create(out_path)?);
+    let header = serde_json::json!({
+        "format": JSON_EXPORT_FORMAT,
+        "schema_version": JSON_EXPORT_FORMAT_VERSION,
+        "db_schema_version": db_schema_version,
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "exported_at": chrono::Local::now().to_rfc3339(),
+    });
+    // Write the header object without its closing brace, then the tables.
+    let header = header.to_string();
+    let header = header.strip_suffix('}').unwrap_or(&header);
+    out.write_all(header.as_bytes())?;
+    out.write_all(b",\"tables\":{")?;
+
+    let mut counts = Vec::new();
+    for (table_index, table) in table_names.iter().enumerate() {
+        if table_index > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(serde_json::to_string(table).unwrap_or_default().as_bytes())?;
+        out.write_all(b":[")?;
+        let rows = write_table_rows(&mut conn, table, &mut out).await?;
+        out.write_all(b"]")?;
+        counts.push((table.clone(), rows));
+    }
+    out.write_all(b"}}")?;
+
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(counts)
+}
+
+/// Write the rows of `table` as comma-separated JSON objects, paging by
+/// rowid. BLOB values are hex-encoded (JSON cannot hold them).
+async fn write_table_rows<W: Write>(
+    conn: &mut sqlx::SqliteConnection,
+    table: &str,
+    out: &mut W,
+) -> Result<usize, AppError> {
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    if columns.is_empty() {
+        return Ok(0);
+    }
+
+    let fields = columns
+        .iter()
+        .map(|column| {
+            let ident = quote_ident(column);
+            format!(
+                "{}, CASE WHEN typeof({ident}) = 'blob' THEN hex({ident}) ELSE {ident} END",
+                quote_literal(column),
+                ident = ident
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "SELECT rowid, json_object({}) FROM {} WHERE rowid > ? ORDER BY rowid LIMIT ?",
+        fields,
+        quote_ident(table)
+    );
+
+    let mut rows = 0usize;
+    let mut last_rowid = i64::MIN;
+    loop {
+        let page: Vec<(i64, String)> = sqlx::query_as(&sql)
+            .bind(last_rowid)
+            .bind(PAGE_SIZE)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        for (rowid, json) in &page {
+            if rows > 0 {
+                out.write_all(b",")?;
+            }
+            out.write_all(json.as_bytes())?;
+            rows += 1;
+            last_rowid = *rowid;
+        }
+
+        if (page.len() as i64) < PAGE_SIZE {
+            return Ok(rows);
+        }
+    }
+}
+
+/// Compress `source` into `dest` with gzip.
+fn gzip_file(source: &Path, dest: &Path) -> std::io::Result<()> {
+    let mut input = std::fs::File::open(source)?;
+    let output = std::fs::File::create(dest)?;
+    let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+    let copied = std::io::copy(&mut input, &mut encoder).and_then(|_| encoder.finish());
+    match copied {
+        Ok(file) => file.sync_all(),
+        Err(e) => {
+            let _ = std::fs::remove_file(dest);
+            Err(e)
+        }
+    }
+}
+
+/// Emit `export:progress` for `job_id` with the rows written so far.
+pub(crate) fn progress_emitter<'a>(
+    app: &'a AppHandle,
+    job_id: &Option<String>,
+) -> impl FnMut(usize) + Send + 'a {
+    let job_id = job_id.clone();
+    move |rows| {
+        app.emit(
+            EXPORT_PROGRESS_EVENT,
+            ExportProgress {
+                job_id: job_id.clone(),
+                rows,
+            },
+        )
+        .ok();
+    }
+}
+
+/// Quote an SQLite identifier (table or column name from sqlite_master).
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quote an SQLite string literal.
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Write the CSV export of `db_path` to `dest` through `<dest>.tmp`,
+/// calling `report` with the row count every `PROGRESS_EVERY` rows.
+pub(crate) async fn write_csv(
+    db_path: &Path,
+    dest: &Path,
+    options: &CsvExportOptions,
+    cancelled: &AtomicBool,
+    mut report: impl FnMut(usize) + Send,
+) -> Result<CsvExportResult, AppError> {
+    let columns = selected_columns(options.columns.as_deref())?;
+    let (sql, params) = build_query(&columns, options);
+
+    let mut conn = project_db::open_read_only(db_path).await?;
+
+    let tmp = tmp_path(dest);
+    let outcome = async {
+        let mut out = BufWriter::new(std::fs::File::create(&tmp)?);
+        if options.bom {
+            out.write_all(UTF8_BOM)?;
+        }
+        write_record(&mut out, columns.iter().map(|(name, _)| Some(*name)))?;
+
+        let mut rows = 0usize;
+        loop {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(AppError::Validation("export cancelled".into()));
+            }
+
+            let mut query = sqlx::query(&sql);
+            for param in &params {
+                query = query.bind(param);
+            }
+            let page = query
+                .bind(PAGE_SIZE)
+                .bind(rows as i64)
+                .fetch_all(&mut conn)
+                .await?;
+
+            for row in &page {
+                let values = (0..columns.len())
+                    .map(|index| row.try_get::<Option<String>, _>(index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                write_record(&mut out, values.iter().map(Option::as_deref))?;
+                rows += 1;
+                if rows % PROGRESS_EVERY == 0 {
+                    report(rows);
+                }
+            }
+
+            if (page.len() as i64) < PAGE_SIZE {
+                break;
+            }
+        }
+
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&tmp, dest)?;
+        Ok(rows)
+    }
+    .await;
+
+    match outcome {
+        Ok(rows) => Ok(CsvExportResult {
+            path: dest.to_string_lossy().into_owned(),
+            rows,
+        }),
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Resolve the requested column names against `CSV_COLUMNS`.
+fn selected_columns(
+    requested: Option<&[String]>,
+) -> Result<Vec<(&'static str, &'static str)>, AppError> {
+    let lookup = |name: &str| {
+        CSV_COLUMNS
+            .iter()
+            .find(|(column, _)| *column == name)
+            .copied()
+            .ok_or_else(|| AppError::Validation(format!("unknown export column: {}", name)))
+    };
+
+    match requested {
+        Some([]) => Err(AppError::Validation("no columns selected".into())),
+        Some(names) => names.iter().map(|name| lookup(name)).collect(),
+        None => DEFAULT_COLUMNS.iter().map(|name| lookup(name)).collect(),
+    }
+}
+
+/// Build the paged SELECT and its filter parameters. Column expressions
+/// come from `CSV_COLUMNS` only; user input is always bound. The last two
+/// placeholders are LIMIT and OFFSET.
+fn build_query(
+    columns: &[(&'static str, &'static str)],
+    options: &CsvExportOptions,
+) -> (String, Vec<String>) {
+    let select = columns
+        .iter()
+        .map(|(_, expr)| format!("CAST({} AS TEXT)", expr))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut filters = Vec::new();
+    let mut params = Vec::new();
+    if let Some(sections) = options.sections.as_ref().filter(|s| !s.is_empty()) {
+        filters.push(format!(
+            "s.title IN ({})",
+            vec!["?"; sections.len()].join(", ")
+        ));
+        params.extend(sections.iter().cloned());
+    }
+    if let Some(from) = &options.updated_from {
+        filters.push("i.updated_at >= ?".to_string());
+        params.push(from.clone());
+    }
+    if let Some(to) = &options.updated_to {
+        filters.push("i.updated_at < ?".to_string());
+        params.push(to.clone());
+    }
+    let where_clause = if filters.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", filters.join(" AND "))
+    };
+
+    let sql = format!(
+        "SELECT {} FROM backlog_items i
+         LEFT JOIN sections s ON s.id = i.section_id
+         {}
+         ORDER BY s.position, i.position, i.id
+         LIMIT ? OFFSET ?",
+        select, where_clause
+    );
+    (sql, params)
+}
+
+/// Write one CSV record (RFC 4180: CRLF line ending, fields containing a
+/// comma, quote, CR or LF are quoted with embedded quotes doubled). `None`
+/// is written as an empty field.
+fn write_record<'a, W: Write>(
+    out: &mut W,
+    fields: impl Iterator<Item = Option<&'a str>>,
+) -> std::io::Result<()> {
+    for (index, field) in fields.enumerate() {
+        if index > 0 {
+            out.write_all(b",")?;
+        }
+        let field = field.unwrap_or("");
+        if field.contains([',', '"', '\r', '\n']) {
+            out.write_all(b"\"")?;
+            out.write_all(field.replace('"', "\"\"").as_bytes())?;
+            out.write_all(b"\"")?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
+
+fn tmp_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// Write the Markdown export of `db_path` into `dest`, calling `report`
+/// with the ticket count every `MARKDOWN_PROGRESS_EVERY` tickets.
+async fn write_markdown(
+    db_path: &Path,
+    dest: &Path,
+    options: &MarkdownExportOptions,
+    cancelled: &AtomicBool,
+    mut report: impl FnMut(usize) + Send,
+) -> Result<MarkdownExportResult, AppError> {
+    let items: Vec<MarkdownItem> = {
+        let mut conn = project_db::open_read_only(db_path).await?;
+        sqlx::query_as(&format!(
+            "{} ORDER BY s.position, i.position, i.id",
+            MARKDOWN_ITEM_QUERY
+        ))
+        .fetch_all(&mut conn)
+        .await?
+    };
+
+    let project_dir = db_path.parent().unwrap_or(Path::new(""));
+    let screenshots_dir = project_dir.join(SCREENSHOTS_DIR);
+    let assets_dir = dest.join(MARKDOWN_ASSETS_DIR);
+    std::fs::create_dir_all(dest)?;
+
+    let mut result = MarkdownExportResult {
+        path: dest.to_string_lossy().into_owned(),
+        files: 0,
+        assets: 0,
+        skipped: Vec::new(),
+    };
+    let mut used = HashSet::from(["index".to_string()]);
+    // (section, link text, file name) for index.md, in board order.
+    let mut index: Vec<(String, String, String)> = Vec::new();
+
+    for item in &items {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(AppError::Validation("export cancelled".into()));
+        }
+
+        let mut embeds = Vec::new();
+        for shot in parse_screenshots(item.screenshots.as_deref()) {
+            let target = assets_dir.join(&shot.filename);
+            if target.exists() && !options.overwrite {
+                result
+                    .skipped
+                    .push(format!("{}/{}", MARKDOWN_ASSETS_DIR, shot.filename));
+            } else {
+                std::fs::create_dir_all(&assets_dir)?;
+                match std::fs::copy(screenshots_dir.join(&shot.filename), &target) {
+                    Ok(_) => result.assets += 1,
+                    Err(e) => {
+                        log::warn!("export_markdown: {}: {}", shot.filename, e);
+                        continue;
+                    }
+                }
+            }
+            embeds.push(shot);
+        }
+
+        let file_name = format!(
+            "{}.md",
+            unique_slug(&format!("{} {}", item.id, item.title), &mut used)
+        );
+        if write_new_file(
+            &dest.join(&file_name),
+            &render_item(item, &embeds),
+            options.overwrite,
+        )? {
+            result.files += 1;
+        } else {
+            result.skipped.push(file_name.clone());
+        }
+        index.push((
+            item.section.clone().unwrap_or_else(|| "No section".into()),
+            format!("{} — {}", item.id, item.title),
+            file_name,
+        ));
+
+        if index.len() % MARKDOWN_PROGRESS_EVERY == 0 {
+            report(index.len());
+        }
+    }
+
+    let mut out = String::new();
+    let title = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Ticketflow".into());
+    let _ = writeln!(out, "# {}", title);
+    let mut current = None;
+    for (section, text, file_name) in &index {
+        if current != Some(section) {
+            let _ = write!(out, "\n## {}\n\n", section);
+            current = Some(section);
+        }
+        let _ = writeln!(out, "- [{}]({})", text.replace(['[', ']'], ""), file_name);
+    }
+    if write_new_file(&dest.join("index.md"), &out, options.overwrite)? {
+        result.files += 1;
+    } else {
+        result.skipped.push("index.md".into());
+    }
+
+    Ok(result)
+}
+
+/// Markdown document of one ticket, front matter first. `embeds` are the
+/// screenshots copied into `assets/`.
+fn render_item(item: &MarkdownItem, embeds: &[ScreenshotRef]) -> String {
+    let mut out = String::from("---\n");
+    for (key, value) in item_fields(item) {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            // A JSON string is a valid double-quoted YAML scalar.
+            let _ = writeln!(out, "{}: {}", key, serde_json::Value::from(value.as_str()));
+        }
+    }
+    out.push_str("---\n\n");
+    out.push_str(&render_body(item, embeds));
+    out
+}
+
+/// Front matter keys of a ticket and their values.
+fn item_fields(item: &MarkdownItem) -> [(&'static str, Option<&String>); 10] {
+    [
+        ("id", Some(&item.id)),
+        ("type", Some(&item.item_type)),
+        ("status", item.section.as_ref()),
+        ("severity", item.severity.as_ref()),
+        ("priority", item.priority.as_ref()),
+        ("effort", item.effort.as_ref()),
+        ("component", item.component.as_ref()),
+        ("module", item.module.as_ref()),
+        ("created", item.created_at.as_ref()),
+        ("updated", item.updated_at.as_ref()),
+    ]
+}
+
+/// Markdown of a ticket from its `# title` heading on.
+fn render_body(item: &MarkdownItem, embeds: &[ScreenshotRef]) -> String {
+    let mut out = String::new();
+    match item.emoji.as_deref().filter(|e| !e.is_empty()) {
+        Some(emoji) => {
+            let _ = writeln!(out, "# {} {}", emoji, item.title);
+        }
+        None => {
+            let _ = writeln!(out, "# {}", item.title);
+        }
+    }
+    if let Some(description) = item.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        let _ = write!(out, "\n{}\n", rewrite_asset_links(description.trim_end()));
+    }
+    if let Some(story) = item.user_story.as_deref().filter(|s| !s.trim().is_empty()) {
+        out.push_str("\n## User Story\n\n");
+        for line in story.trim_end().lines() {
+            let _ = writeln!(out, "> {}", rewrite_asset_links(line));
+        }
+    }
+    for (heading, json, numbered) in [
+        ("Reproduction", &item.reproduction, true),
+        ("Spécifications", &item.specs, false),
+        ("Écrans", &item.screens, true),
+        ("Critères d'acceptation", &item.criteria, false),
+        ("Dépendances", &item.dependencies, false),
+        ("Contraintes", &item.constraints, false),
+    ] {
+        write_list_section(&mut out, heading, json.as_deref(), numbered);
+    }
+    if !embeds.is_empty() {
+        out.push_str("\n## Screenshots\n\n");
+        for shot in embeds {
+            let alt = shot
+                .alt
+                .clone()
+                .unwrap_or_else(|| shot.filename.trim_end_matches(".png").to_string());
+            let _ = writeln!(out, "![{}]({}/{})", alt, MARKDOWN_ASSETS_DIR, shot.filename);
+        }
+    }
+    out
+}
+
+/// Append a `## heading` section for a JSON array column: strings become
+/// list entries, `{text, checked}` objects (criteria) task-list entries.
+/// Text that is not a JSON array is written as is.
+fn write_list_section(out: &mut String, heading: &str, json: Option<&str>, numbered: bool) {
+    let Some(json) = json.filter(|j| !j.trim().is_empty()) else {
+        return;
+    };
+    let entries = match serde_json::from_str::<Vec<serde_json::Value>>(json) {
+        Ok(entries) if entries.is_empty() => return,
+        Ok(entries) => entries,
+        Err(_) => {
+            let _ = write!(
+                out,
+                "\n## {}\n\n{}\n",
+                heading,
+                rewrite_asset_links(json.trim_end())
+            );
+            return;
+        }
+    };
+    let _ = write!(out, "\n## {}\n\n", heading);
+    for (index, entry) in entries.iter().enumerate() {
+        let text = match entry {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Object(fields) => {
+                let text = fields
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default();
+                let checked = fields.get("checked").and_then(|c| c.as_bool()) == Some(true);
+                format!("[{}] {}", if checked { 'x' } else { ' ' }, text)
+            }
+            other => other.to_string(),
+        };
+        let text = rewrite_asset_links(&text);
+        if numbered {
+            let _ = writeln!(out, "{}. {}", index + 1, text);
+        } else {
+            let _ = writeln!(out, "- {}", text);
+        }
+    }
+}
+
+/// Point links to the project's screenshot folder at the export's `assets/`.
+fn rewrite_asset_links(text: &str) -> String {
+    text.replace(SCREENSHOTS_DIR, MARKDOWN_ASSETS_DIR)
+}
+
+/// Screenshots of a ticket whose file name is a plain name (no path).
+fn parse_screenshots(json: Option<&str>) -> Vec<ScreenshotRef> {
+    let Some(json) = json else {
+        return Vec::new();
+    };
+    serde_json::from_str::<Vec<ScreenshotRef>>(json)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|shot| {
+            Path::new(&shot.filename).file_name() == Some(std::ffi::OsStr::new(&shot.filename))
+        })
+        .collect()
+}
+
+/// File-name-safe, lowercase, accent-free form of `text`, made unique
+/// against `used` with a `-2`, `-3`, ... suffix.
+fn unique_slug(text: &str, used: &mut HashSet<String>) -> String {
+    let folded = search::fold(text);
+    let mut slug: String = folded.chars().take(MAX_SLUG_CHARS).collect();
+    slug = slug.trim_end().replace(' ', "-");
+    if slug.is_empty() {
+        slug = "ticket".into();
+    }
+    let mut candidate = slug.clone();
+    let mut counter = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{}-{}", slug, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+/// Write `contents` to `path` unless it exists and `overwrite` is false.
+/// Returns whether the file was written.
+fn write_new_file(path: &Path, contents: &str, overwrite: bool) -> Result<bool, AppError> {
+    if path.exists() && !overwrite {
+        return Ok(false);
+    }
+    std::fs::write(path, contents)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+    use sqlx::Connection;
+
+    /// The Demo board with three tickets, the last one in a section that no
+    /// longer exists.
+    const TICKETS: &str = "
+        PRAGMA foreign_keys = OFF;
+        INSERT INTO backlog_items (id, project_id, section_id, position, type, title, severity,
+            description, raw_markdown, updated_at) VALUES
+            ('BUG-2', 1, 2, 0, 'BUG', 'Slow export', 'P2', NULL, '### BUG-2 | Slow export',
+             '2024-03-01'),
+            ('BUG-1', 1, 1, 0, 'BUG', 'Crash, \"again\"', 'P0', 'Line one
+line two', '### BUG-1 | Crash', '2024-01-15'),
+            ('CT-1', 1, 99, 0, 'CT', 'Orphan', NULL, 'See .backlog-assets/screenshots/a.png',
+             '### CT-1 | Orphan', '2024-02-01');
+    ";
+
+    async fn project(dir: &Path) -> PathBuf {
+        create_db(
+            dir,
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            &[BOARD, TICKETS].concat(),
+        )
+        .await
+    }
+
+    fn csv_options(columns: &[&str]) -> CsvExportOptions {
+        CsvExportOptions {
+            columns: Some(columns.iter().map(|c| c.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    async fn export_csv(db_path: &Path, dest: &Path, options: &CsvExportOptions) -> String {
+        let result = write_csv(db_path, dest, options, &AtomicBool::new(false), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(result.path, dest.to_string_lossy());
+        std::fs::read_to_string(dest).unwrap()
+    }
+
+    #[test]
+    fn columns_default_and_unknown() {
+        let defaults = selected_columns(None).unwrap();
+        assert_eq!(defaults.len(), DEFAULT_COLUMNS.len());
+        assert_eq!(defaults[3], ("section", "s.title"));
+
+        let err = selected_columns(Some(&["title".into(), "secret".into()])).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown export column: secret"),
+            "{}",
+            err
+        );
+        assert!(selected_columns(Some(&[])).is_err());
+    }
+
+    #[test]
+    fn filters_are_bound_not_inlined() {
+        let columns = selected_columns(Some(&["id".into()])).unwrap();
+        let options = CsvExportOptions {
+            sections: Some(vec!["Todo".into(), "x' OR 1=1".into()]),
+            updated_from: Some("2024-01-01".into()),
+            ..Default::default()
+        };
+        let (sql, params) = build_query(&columns, &options);
+        assert!(sql.contains("s.title IN (?, ?)"), "{}", sql);
+        assert!(sql.contains("i.updated_at >= ?"), "{}", sql);
+        assert!(!sql.contains("updated_at < ?"), "{}", sql);
+        assert!(!sql.contains("OR 1=1"), "{}", sql);
+        assert_eq!(params, ["Todo", "x' OR 1=1", "2024-01-01"]);
+
+        let (sql, params) = build_query(&columns, &CsvExportOptions::default());
+        assert!(!sql.contains("WHERE"), "{}", sql);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn records_follow_rfc_4180() {
+        let mut out = Vec::new();
+        write_record(
+            &mut out,
+            [
+                Some("plain"),
+                None,
+                Some("a,b"),
+                Some("say \"hi\""),
+                Some("x\ny"),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "plain,,\"a,b\",\"say \"\"hi\"\"\",\"x\ny\"\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_lists_tickets_in_board_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let dest = dir.path().join("out.csv");
+
+        let csv = export_csv(
+            &db_path,
+            &dest,
+            &csv_options(&["id", "section", "title", "description"]),
+        )
+        .await;
+        assert_eq!(
+            csv,
+            "id,section,title,description\r\n\
+             CT-1,,Orphan,See .backlog-assets/screenshots/a.png\r\n\
+             BUG-1,Todo,\"Crash, \"\"again\"\"\",\"Line one\nline two\"\r\n\
+             BUG-2,Done,Slow export,\r\n"
+        );
+        assert!(!tmp_path(&dest).exists());
+    }
+
+    #[tokio::test]
+    async fn csv_filters_and_bom() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let dest = dir.path().join("out.csv");
+
+        let options = CsvExportOptions {
+            sections: Some(vec!["Todo".into(), "Done".into()]),
+            updated_to: Some("2024-02-01".into()),
+            bom: true,
+            ..csv_options(&["id"])
+        };
+        let csv = export_csv(&db_path, &dest, &options).await;
+        assert_eq!(csv, "\u{feff}id\r\nBUG-1\r\n");
+    }
+
+    #[tokio::test]
+    async fn cancelled_csv_export_leaves_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let dest = dir.path().join("out.csv");
+
+        let err = write_csv(
+            &db_path,
+            &dest,
+            &CsvExportOptions::default(),
+            &AtomicBool::new(true),
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("export cancelled"), "{}", err);
+        assert!(!dest.exists());
+        assert!(!tmp_path(&dest).exists());
+    }
+
+    #[tokio::test]
+    async fn json_export_holds_every_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mut conn = project_db::open_connection(&db_path).await.unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE \"odd \"\"name\" (data BLOB, note TEXT);
+             INSERT INTO \"odd \"\"name\" VALUES (x'00ff10', 'it''s');",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        let out = dir.path().join("out.json");
+
+        let tables = write_json(&db_path, &out).await.unwrap();
+        for table in [("backlog_items", 3), ("odd \"name", 1), ("sections", 2)] {
+            assert!(
+                tables.contains(&(table.0.to_string(), table.1)),
+                "{:?}",
+                tables
+            );
+        }
+        assert!(
+            !tables.iter().any(|(name, _)| name.contains("_fts")),
+            "{:?}",
+            tables
+        );
+
+        let document: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(document["format"], JSON_EXPORT_FORMAT);
+        assert_eq!(document["schema_version"], JSON_EXPORT_FORMAT_VERSION);
+        assert_eq!(
+            document["db_schema_version"],
+            project_db::SUPPORTED_SCHEMA_VERSION
+        );
+        assert_eq!(
+            document["tables"]["odd \"name"],
+            serde_json::json!([{ "data": "00FF10", "note": "it's" }])
+        );
+        assert_eq!(document["tables"]["sections"][1]["title"], "Done");
+        assert_eq!(document["tables"]["backlog_items"][1]["id"], "BUG-1");
+    }
+
+    #[tokio::test]
+    async fn json_export_pages_large_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mut conn = project_db::open_connection(&db_path).await.unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE numbers (n INTEGER);
+             WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 2500)
+             INSERT INTO numbers SELECT n FROM seq;",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        let out = dir.path().join("out.json");
+
+        let tables = write_json(&db_path, &out).await.unwrap();
+        assert!(tables.contains(&("numbers".to_string(), 2500)));
+        let document: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        let numbers = document["tables"]["numbers"].as_array().unwrap();
+        assert_eq!(numbers.len(), 2500);
+        assert_eq!(numbers[1000]["n"], 1001);
+    }
+
+    #[test]
+    fn gzip_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("out.json");
+        let dest = dir.path().join("out.json.gz");
+        std::fs::write(&source, "{\"tables\":{}}".repeat(100)).unwrap();
+
+        gzip_file(&source, &dest).unwrap();
+        let mut decoded = String::new();
+        std::io::Read::read_to_string(
+            &mut flate2::read::GzDecoder::new(std::fs::File::open(&dest).unwrap()),
+            &mut decoded,
+        )
+        .unwrap();
+        assert_eq!(decoded, std::fs::read_to_string(&source).unwrap());
+    }
+
+    #[test]
+    fn sql_quoting() {
+        assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+    }
+
+    /// Give BUG-1 list fields and two screenshots, one of them a path
+    /// outside the screenshot folder, and create its image file.
+    async fn add_markdown_fields(db_path: &Path) {
+        let mut conn = project_db::open_connection(db_path).await.unwrap();
+        sqlx::query(
+            "UPDATE backlog_items SET emoji = '🐛', user_story = ?, reproduction = ?,
+               specs = 'not a list', criteria = ?, screenshots = ?
+             WHERE id = 'BUG-1'",
+        )
+        .bind("As a user\nI want it to start")
+        .bind(r#"["Open", "Click"]"#)
+        .bind(r#"[{"text": "Starts", "checked": true}, {"text": "No log"}]"#)
+        .bind(r#"[{"filename": "a.png", "alt": "Trace"}, {"filename": "../evil.png"}]"#)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        let screenshots = db_path.parent().unwrap().join(SCREENSHOTS_DIR);
+        std::fs::create_dir_all(&screenshots).unwrap();
+        std::fs::write(screenshots.join("a.png"), b"png").unwrap();
+    }
+
+    async fn export_markdown_to(
+        db_path: &Path,
+        dest: &Path,
+        overwrite: bool,
+    ) -> MarkdownExportResult {
+        let options = MarkdownExportOptions {
+            overwrite,
+            job_id: None,
+        };
+        write_markdown(db_path, dest, &options, &AtomicBool::new(false), |_| {})
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn markdown_export_writes_tickets_index_and_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        add_markdown_fields(&db_path).await;
+        let dest = dir.path().join("md");
+
+        let result = export_markdown_to(&db_path, &dest, false).await;
+        assert_eq!((result.files, result.assets), (4, 1));
+        assert!(result.skipped.is_empty());
+        assert_eq!(std::fs::read(dest.join("assets/a.png")).unwrap(), b"png");
+
+        let bug = std::fs::read_to_string(dest.join("bug-1-crash-again.md")).unwrap();
+        assert!(
+            bug.starts_with(
+                "---\nid: \"BUG-1\"\ntype: \"BUG\"\nstatus: \"Todo\"\nseverity: \"P0\"\n"
+            ),
+            "{}",
+            bug
+        );
+        assert!(bug.contains("---\n\n# 🐛 Crash, \"again\"\n"), "{}", bug);
+        assert!(bug.contains("## User Story\n\n> As a user\n> I want it to start\n"));
+        assert!(bug.contains("## Reproduction\n\n1. Open\n2. Click\n"));
+        assert!(bug.contains("## Spécifications\n\nnot a list\n"));
+        assert!(bug.contains("## Critères d'acceptation\n\n- [x] Starts\n- [ ] No log\n"));
+        assert!(
+            bug.ends_with("## Screenshots\n\n![Trace](assets/a.png)\n"),
+            "{}",
+            bug
+        );
+        assert!(!bug.contains("evil"));
+
+        let orphan = std::fs::read_to_string(dest.join("ct-1-orphan.md")).unwrap();
+        assert!(orphan.contains("\nSee assets/a.png\n"), "{}", orphan);
+
+        let index = std::fs::read_to_string(dest.join("index.md")).unwrap();
+        assert_eq!(
+            index,
+            "# tmp\n\n## No section\n\n- [CT-1 — Orphan](ct-1-orphan.md)\n\n\
+             ## Todo\n\n- [BUG-1 — Crash, \"again\"](bug-1-crash-again.md)\n\n\
+             ## Done\n\n- [BUG-2 — Slow export](bug-2-slow-export.md)\n"
+                .replace("tmp", &dir.path().file_name().unwrap().to_string_lossy())
+        );
+    }
+
+    #[tokio::test]
+    async fn markdown_export_keeps_existing_files_unless_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        add_markdown_fields(&db_path).await;
+        let dest = dir.path().join("md");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("index.md"), "mine").unwrap();
+
+        let result = export_markdown_to(&db_path, &dest, false).await;
+        assert_eq!((result.files, result.assets), (3, 1));
+        assert_eq!(result.skipped, ["index.md"]);
+        assert_eq!(
+            std::fs::read_to_string(dest.join("index.md")).unwrap(),
+            "mine"
+        );
+
+        let result = export_markdown_to(&db_path, &dest, false).await;
+        assert_eq!((result.files, result.assets), (0, 0));
+        assert_eq!(result.skipped.len(), 5);
+
+        let result = export_markdown_to(&db_path, &dest, true).await;
+        assert_eq!((result.files, result.assets), (4, 1));
+        assert!(result.skipped.is_empty());
+        assert_ne!(
+            std::fs::read_to_string(dest.join("index.md")).unwrap(),
+            "mine"
+        );
+    }
+
+    #[tokio::test]
+    async fn ticket_document_lists_fields_last() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        add_markdown_fields(&db_path).await;
+
+        let document = ticket_document(&db_path, "BUG-1").await.unwrap();
+        assert_eq!(document.title, "Crash, \"again\"");
+        assert!(document.markdown.starts_with("# 🐛 Crash"));
+        assert!(document.markdown.contains("\n---\n\n- **id:** BUG-1\n"));
+        assert!(document.markdown.ends_with("- **updated:** 2024-01-15\n"));
+        assert!(!document.markdown.contains("## Screenshots"));
+        assert_eq!(
+            document.screenshots,
+            [dir.path().join(SCREENSHOTS_DIR).join("a.png")]
+        );
+
+        let err = ticket_document(&db_path, "BUG-9").await.err().unwrap();
+        assert!(
+            err.to_string().contains("no ticket with id BUG-9"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn slugs_are_folded_and_unique() {
+        let mut used = HashSet::from(["index".to_string()]);
+        assert_eq!(unique_slug("Été déjà vu", &mut used), "ete-deja-vu");
+        assert_eq!(unique_slug("ÉTÉ, déjà vu!", &mut used), "ete-deja-vu-2");
+        assert_eq!(unique_slug("Index", &mut used), "index-2");
+        assert_eq!(unique_slug("???", &mut used), "ticket");
+        assert_eq!(
+            unique_slug(&"a".repeat(200), &mut used).len(),
+            MAX_SLUG_CHARS
+        );
+    }
+}
@@ -0,0 +1,808 @@
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+use tauri_plugin_shell::ShellExt;
+use tokio::io::AsyncReadExt;
+
+use crate::error::AppError;
+use crate::storage::StorageState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Suffix of the temporary file used by atomic writes.
+const ATOMIC_TMP_SUFFIX: &str = ".tmp";
+
+/// Upper bound on the page size of `read_file_lines`.
+const MAX_LINES_PER_PAGE: usize = 10_000;
+
+/// Files larger than this are refused by `read_file_lines`.
+const MAX_LINES_FILE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Files larger than this are refused by `file_hash`.
+const MAX_HASH_FILE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Read size used while hashing.
+const HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Extensions of the files `open_path` hands to their default application:
+/// documents, images and media. Anything else could be a program or script
+/// written through `write_file_text` and would run.
+const OPENABLE_EXTENSIONS: [&str; 37] = [
+    "pdf", "txt", "md", "markdown", "log", "csv", "tsv", "json", "rtf", "odt", "ods", "odp", "doc",
+    "docx", "xls", "xlsx", "ppt", "pptx", "png", "jpg", "jpeg", "gif", "webp", "bmp", "tif",
+    "tiff", "heic", "ico", "mp3", "wav", "ogg", "m4a", "mp4", "mov", "webm", "mkv", "zip",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Directories commands may read and write: the app data directory and the
+/// tauri-plugin-fs scope. Helpers that run without an `AppHandle` take one
+/// of these instead.
+#[derive(Clone, Default)]
+pub struct PathScope {
+    data_dir: Option<PathBuf>,
+    fs_scope: Option<tauri::fs::Scope>,
+}
+
+/// Text encodings supported by `read_file_text` / `write_file_text`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Parse an encoding label (case-insensitive). `None` means UTF-8.
+    pub fn from_label(label: Option<&str>) -> Result<Self, AppError> {
+        let Some(label) = label else {
+            return Ok(TextEncoding::Utf8);
+        };
+        match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "utf-16le" | "utf16le" => Ok(TextEncoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(TextEncoding::Utf16Be),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(TextEncoding::Latin1),
+            other => Err(AppError::Validation(format!(
+                "unsupported encoding: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Decode `bytes`, dropping a leading byte order mark that matches the
+    /// encoding. Malformed input is an error rather than silently replaced.
+    pub fn decode(self, bytes: &[u8]) -> Result<String, AppError> {
+        let encoding = match self {
+            // ISO-8859-1 maps every byte to the code point of the same value.
+            // (encoding_rs treats the "latin1" label as windows-1252.)
+            TextEncoding::Latin1 => return Ok(bytes.iter().map(|&b| char::from(b)).collect()),
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+        };
+
+        let bytes = match encoding_rs::Encoding::for_bom(bytes) {
+            Some((bom_encoding, bom_len)) if bom_encoding == encoding => &bytes[bom_len..],
+            _ => bytes,
+        };
+        encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned())
+            .ok_or_else(|| AppError::Validation(format!("file is not valid {}", encoding.name())))
+    }
+
+    /// Encode `text` without a byte order mark. Characters Latin-1 cannot
+    /// represent are an error.
+    pub fn encode(self, text: &str) -> Result<Vec<u8>, AppError> {
+        // encoding_rs never encodes to UTF-16 (it emits UTF-8 instead), so
+        // the UTF-16 variants are encoded by hand.
+        match self {
+            TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            TextEncoding::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            TextEncoding::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            TextEncoding::Latin1 => text
+                .chars()
+                .map(|c| {
+                    u8::try_from(u32::from(c)).map_err(|_| {
+                        AppError::Validation(format!(
+                            "character {:?} is not representable in latin-1",
+                            c
+                        ))
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Digest algorithms supported by `file_hash`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha256,
+    Md5,
+    Sha1,
+}
+
+/// Return value of `read_file_lines`.
+#[derive(Debug, Serialize)]
+pub struct ReadLinesResult {
+    pub lines: Vec<String>,
+    /// Number of lines matching the filter in the whole file.
+    pub total_matched: usize,
+    /// Whether matching lines remain after this page.
+    pub has_more: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Read a text file and decode it (`utf-8` by default, or `utf-16le`,
+/// `utf-16be`, `latin-1`). The path must be inside the app data directory
+/// or a directory the user granted through a dialog.
+#[tauri::command]
+pub fn read_file_text(
+    path: String,
+    encoding: Option<String>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    let encoding = TextEncoding::from_label(encoding.as_deref())?;
+    let path = validate_path(&app, Path::new(&path))?;
+    let bytes = std::fs::read(&path)?;
+    encoding.decode(&bytes)
+}
+
+/// Encode `content` and write it to `path`. With `atomic`, the content goes
+/// to `<path>.tmp` first and is renamed over `path`, so readers never see a
+/// partially written file.
+#[tauri::command]
+pub fn write_file_text(
+    path: String,
+    content: String,
+    encoding: Option<String>,
+    atomic: bool,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    let encoding = TextEncoding::from_label(encoding.as_deref())?;
+    let path = validate_path(&app, Path::new(&path))?;
+    let bytes = encoding.encode(&content)?;
+
+    if atomic {
+        write_atomic(&path, &bytes)?;
+    } else {
+        std::fs::write(&path, bytes)?;
+    }
+    Ok(())
+}
+
+/// Page through the lines of a large UTF-8 text file (logs, CSV) without
+/// loading it whole. Lines can be filtered by substring (`filter`) or by
+/// regular expression (`filter_regex`), not both. `offset` and `max_lines`
+/// (capped at 10,000) apply to the matching lines.
+#[tauri::command]
+pub async fn read_file_lines(
+    path: String,
+    filter: Option<String>,
+    filter_regex: Option<String>,
+    max_lines: usize,
+    offset: usize,
+    app: AppHandle,
+) -> Result<ReadLinesResult, AppError> {
+    let matcher = LineMatcher::new(filter, filter_regex)?;
+    let path = validate_path(&app, Path::new(&path))?;
+
+    // Scanning up to 500 MB is blocking work; keep it off the async workers.
+    tauri::async_runtime::spawn_blocking(move || {
+        scan_lines(&path, &matcher, max_lines.min(MAX_LINES_PER_PAGE), offset)
+    })
+    .await
+    .map_err(|e| AppError::Io(format!("read_file_lines task failed: {}", e)))?
+}
+
+/// Compute the hex digest of a file (up to 2 GB), reading it in 64 KB
+/// chunks.
+#[tauri::command]
+pub async fn file_hash(
+    path: String,
+    algorithm: HashAlgorithm,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    let path = validate_path(&app, Path::new(&path))?;
+    digest_file(&path, algorithm).await
+}
+
+/// Compare a file's digest with `expected` (hex, case-insensitive).
+#[tauri::command]
+pub async fn verify_file_hash(
+    path: String,
+    algorithm: HashAlgorithm,
+    expected: String,
+    app: AppHandle,
+) -> Result<bool, AppError> {
+    let path = validate_path(&app, Path::new(&path))?;
+    Ok(digest_matches(
+        &digest_file(&path, algorithm).await?,
+        &expected,
+    ))
+}
+
+/// Reveal a file in Finder / Explorer with the file selected. Linux file
+/// managers have no common "select" flag, so the parent folder is opened.
+#[tauri::command]
+pub fn show_item_in_folder(path: String, app: AppHandle) -> Result<(), AppError> {
+    let path = shell_path(&validate_existing(&app, &path)?);
+    let (program, args) = reveal_command(&path);
+    app.shell()
+        .command(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot show {}: {}", path.display(), e)))?;
+    Ok(())
+}
+
+/// Open a file or folder with its default application. Only folders and
+/// files in `OPENABLE_EXTENSIONS` are opened; programs, scripts and
+/// application bundles fail with `Unauthorized`.
+#[tauri::command]
+pub fn open_path(path: String, app: AppHandle) -> Result<(), AppError> {
+    let path = validate_existing(&app, &path)?;
+    check_openable(&path)?;
+    let path = shell_path(&path);
+    let (program, args) = open_command(&path);
+    app.shell()
+        .command(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot open {}: {}", path.display(), e)))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+async fn digest_file(path: &Path, algorithm: HashAlgorithm) -> Result<String, AppError> {
+    match algorithm {
+        HashAlgorithm::Sha256 => hash_file::<sha2::Sha256>(path).await,
+        HashAlgorithm::Md5 => hash_file::<md5::Md5>(path).await,
+        HashAlgorithm::Sha1 => hash_file::<sha1::Sha1>(path).await,
+    }
+}
+
+/// Compare hex digests, ignoring case and whitespace around `expected`.
+fn digest_matches(actual: &str, expected: &str) -> bool {
+    actual.eq_ignore_ascii_case(expected.trim())
+}
+
+async fn hash_file<D: Digest>(path: &Path) -> Result<String, AppError> {
+    let size = tokio::fs::metadata(path).await?.len();
+    if size > MAX_HASH_FILE_BYTES {
+        return Err(AppError::Validation(format!(
+            "file is too large to hash ({} bytes, limit {})",
+            size, MAX_HASH_FILE_BYTES
+        )));
+    }
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; HASH_CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn scan_lines(
+    path: &Path,
+    matcher: &LineMatcher,
+    max_lines: usize,
+    offset: usize,
+) -> Result<ReadLinesResult, AppError> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_LINES_FILE_BYTES {
+        return Err(AppError::Validation(format!(
+            "file is too large ({} bytes, limit {})",
+            size, MAX_LINES_FILE_BYTES
+        )));
+    }
+
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut lines = Vec::new();
+    let mut total_matched = 0usize;
+    for line in reader.lines() {
+        let line = line.map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => {
+                AppError::Validation("file is not valid UTF-8".into())
+            }
+            _ => e.into(),
+        })?;
+        if !matcher.is_match(&line) {
+            continue;
+        }
+        if total_matched >= offset && lines.len() < max_lines {
+            lines.push(line);
+        }
+        total_matched += 1;
+    }
+
+    let has_more = total_matched > offset + lines.len();
+    Ok(ReadLinesResult {
+        lines,
+        total_matched,
+        has_more,
+    })
+}
+
+/// Line filter of `read_file_lines`. An empty substring matches every line.
+enum LineMatcher {
+    All,
+    Substring(String),
+    Regex(Regex),
+}
+
+impl LineMatcher {
+    fn new(filter: Option<String>, filter_regex: Option<String>) -> Result<Self, AppError> {
+        match (filter, filter_regex) {
+            (Some(_), Some(_)) => Err(AppError::Validation(
+                "filter and filter_regex are mutually exclusive".into(),
+            )),
+            (Some(filter), None) if !filter.is_empty() => Ok(LineMatcher::Substring(filter)),
+            (None, Some(pattern)) => Regex::new(&pattern)
+                .map(LineMatcher::Regex)
+                .map_err(|e| AppError::Validation(format!("invalid regex: {}", e))),
+            _ => Ok(LineMatcher::All),
+        }
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            LineMatcher::All => true,
+            LineMatcher::Substring(needle) => line.contains(needle.as_str()),
+            LineMatcher::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// Resolve `path` (following symlinks and `..`) and check it lies inside the
+/// app data directory or the tauri-plugin-fs scope, which holds the folders
+/// the user picked in a dialog. The file itself may not exist yet, but its
+/// parent directory must.
+pub fn validate_path(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    PathScope::of(app).validate(path)
+}
+
+impl PathScope {
+    /// The directories `app` lets commands touch.
+    pub fn of(app: &AppHandle) -> Self {
+        PathScope {
+            data_dir: app
+                .try_state::<StorageState>()
+                .map(|storage| storage.data_dir.clone()),
+            fs_scope: app.try_fs_scope(),
+        }
+    }
+
+    /// A scope holding only `data_dir`, for tests.
+    #[cfg(test)]
+    pub fn data_dir(data_dir: &Path) -> Self {
+        PathScope {
+            data_dir: Some(data_dir.to_path_buf()),
+            fs_scope: None,
+        }
+    }
+
+    /// See `validate_path`.
+    pub fn validate(&self, path: &Path) -> Result<PathBuf, AppError> {
+        let resolved = match path.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(_) => {
+                let parent = path
+                    .parent()
+                    .filter(|parent| !parent.as_os_str().is_empty())
+                    .ok_or_else(|| {
+                        AppError::Validation(format!("invalid path: {}", path.display()))
+                    })?;
+                let file_name = path.file_name().ok_or_else(|| {
+                    AppError::Validation(format!("invalid path: {}", path.display()))
+                })?;
+                parent.canonicalize()?.join(file_name)
+            }
+        };
+
+        let in_data_dir = self
+            .data_dir
+            .as_ref()
+            .and_then(|data_dir| data_dir.canonicalize().ok())
+            .is_some_and(|data_dir| resolved.starts_with(data_dir));
+        let in_fs_scope = self
+            .fs_scope
+            .as_ref()
+            .is_some_and(|scope| scope.is_allowed(&resolved));
+
+        if in_data_dir || in_fs_scope {
+            Ok(resolved)
+        } else {
+            Err(AppError::Unauthorized(format!(
+                "path is outside the allowed directories: {}",
+                path.display()
+            )))
+        }
+    }
+}
+
+/// `validate_path` for commands that need the target to exist.
+fn validate_existing(app: &AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    let resolved = validate_path(app, Path::new(path))?;
+    if !resolved.exists() {
+        return Err(AppError::Validation(format!("path not found: {}", path)));
+    }
+    Ok(resolved)
+}
+
+/// Refuse to open anything but a folder or a file with one of the
+/// `OPENABLE_EXTENSIONS`. A `.app` folder is a macOS application.
+fn check_openable(path: &Path) -> Result<(), AppError> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    let openable = if path.is_dir() {
+        extension.as_deref() != Some("app")
+    } else {
+        extension.is_some_and(|extension| OPENABLE_EXTENSIONS.contains(&extension.as_str()))
+    };
+    if openable {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized(format!(
+            "refusing to open {}: not a document, image or folder",
+            path.display()
+        )))
+    }
+}
+
+/// Program and arguments that reveal `path` in the file manager.
+fn reveal_command(path: &Path) -> (&'static str, Vec<OsString>) {
+    #[cfg(target_os = "macos")]
+    let command = ("open", vec!["-R".into(), path.into()]);
+    #[cfg(windows)]
+    let command = ("explorer", vec!["/select,".into(), path.into()]);
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let command = ("xdg-open", vec![path.parent().unwrap_or(path).into()]);
+    command
+}
+
+/// Program and arguments that open `path` with its default application.
+fn open_command(path: &Path) -> (&'static str, Vec<OsString>) {
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let program = "xdg-open";
+    (program, vec![path.into()])
+}
+
+/// Strip the `\\?\` prefix of canonical Windows paths, which Explorer does
+/// not understand.
+fn shell_path(path: &Path) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if let Some(rest) = raw.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", rest))
+    } else if let Some(rest) = raw.strip_prefix(r"\\?\") {
+        PathBuf::from(rest)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Write `bytes` to `<path>.tmp`, flush it to disk, then rename it over
+/// `path`.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_os_string();
+    tmp_name.push(ATOMIC_TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp_name);
+
+    let result = std::fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_documents_and_folders_are_opened() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["report.PDF", "shot.png", "notes.md"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+            check_openable(&dir.path().join(name)).unwrap();
+        }
+        std::fs::create_dir(dir.path().join("v1.2")).unwrap();
+        check_openable(&dir.path().join("v1.2")).unwrap();
+        check_openable(dir.path()).unwrap();
+
+        for name in [
+            "run.sh",
+            "setup.exe",
+            "notes.txt.bat",
+            "Makefile",
+            "page.html",
+        ] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+            let err = check_openable(&dir.path().join(name)).unwrap_err();
+            assert!(
+                matches!(err, AppError::Unauthorized(_)),
+                "{}: {:?}",
+                name,
+                err
+            );
+        }
+        std::fs::create_dir(dir.path().join("Tool.app")).unwrap();
+        assert!(check_openable(&dir.path().join("Tool.app")).is_err());
+    }
+
+    const SAMPLE: &str = "Café ünïcode 日本 😀";
+
+    #[test]
+    fn latin1_maps_each_byte_to_its_code_point() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let text = TextEncoding::Latin1.decode(&bytes).unwrap();
+        assert_eq!(text.chars().count(), 256);
+        assert!(text.chars().zip(0u32..).all(|(c, b)| u32::from(c) == b));
+        // Not windows-1252, where 0x80 is the euro sign.
+        assert_eq!(TextEncoding::Latin1.decode(&[0x80]).unwrap(), "\u{80}");
+        assert_eq!(TextEncoding::Latin1.encode(&text).unwrap(), bytes);
+    }
+
+    #[test]
+    fn latin1_refuses_characters_above_0xff() {
+        assert_eq!(TextEncoding::Latin1.encode("Café").unwrap(), b"Caf\xe9");
+        assert!(matches!(
+            TextEncoding::Latin1.encode("€"),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn utf16_round_trips_without_a_bom() {
+        let le = TextEncoding::Utf16Le.encode(SAMPLE).unwrap();
+        let be = TextEncoding::Utf16Be.encode(SAMPLE).unwrap();
+        assert_eq!(&le[..4], b"C\0a\0");
+        assert_eq!(&be[..4], b"\0C\0a");
+        assert_eq!(TextEncoding::Utf16Le.decode(&le).unwrap(), SAMPLE);
+        assert_eq!(TextEncoding::Utf16Be.decode(&be).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn utf16_drops_a_matching_bom_only() {
+        let mut le = vec![0xff, 0xfe];
+        le.extend(TextEncoding::Utf16Le.encode("hi").unwrap());
+        assert_eq!(TextEncoding::Utf16Le.decode(&le).unwrap(), "hi");
+
+        let mut be = vec![0xfe, 0xff];
+        be.extend(TextEncoding::Utf16Be.encode("hi").unwrap());
+        assert_eq!(TextEncoding::Utf16Be.decode(&be).unwrap(), "hi");
+        // Read as little-endian, the big-endian BOM is a U+FFFE character.
+        assert_eq!(TextEncoding::Utf16Le.decode(&be).unwrap(), "\u{fffe}栀椀");
+    }
+
+    #[test]
+    fn utf16_refuses_malformed_input() {
+        // Odd length, then an unpaired high surrogate.
+        for bytes in [&[0x41, 0x00, 0x42][..], &[0x00, 0xd8, 0x41, 0x00][..]] {
+            assert!(matches!(
+                TextEncoding::Utf16Le.decode(bytes),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn encoding_labels_are_case_insensitive() {
+        assert_eq!(TextEncoding::from_label(None).unwrap(), TextEncoding::Utf8);
+        assert_eq!(
+            TextEncoding::from_label(Some(" UTF-16LE ")).unwrap(),
+            TextEncoding::Utf16Le
+        );
+        assert_eq!(
+            TextEncoding::from_label(Some("ISO-8859-1")).unwrap(),
+            TextEncoding::Latin1
+        );
+        assert!(TextEncoding::from_label(Some("windows-1252")).is_err());
+    }
+
+    /// `log.txt` in a new temp dir, holding `line 0` to `line 99`.
+    fn numbered_log() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let text: String = (0..100).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(&path, text).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn lines_are_paged_over_the_matches() {
+        let (_dir, path) = numbered_log();
+        let all = LineMatcher::new(None, None).unwrap();
+
+        let page = scan_lines(&path, &all, 10, 85).unwrap();
+        assert_eq!(page.lines.first().unwrap(), "line 85");
+        assert_eq!(page.lines.len(), 10);
+        assert_eq!(page.total_matched, 100);
+        assert!(page.has_more);
+
+        let last = scan_lines(&path, &all, 10, 95).unwrap();
+        assert_eq!(last.lines.len(), 5);
+        assert!(!last.has_more);
+
+        let past_end = scan_lines(&path, &all, 10, 200).unwrap();
+        assert!(past_end.lines.is_empty());
+        assert!(!past_end.has_more);
+    }
+
+    #[test]
+    fn empty_filter_matches_every_line() {
+        let (_dir, path) = numbered_log();
+        let matcher = LineMatcher::new(Some(String::new()), None).unwrap();
+        assert!(matches!(matcher, LineMatcher::All));
+        assert_eq!(
+            scan_lines(&path, &matcher, 1, 0).unwrap().total_matched,
+            100
+        );
+    }
+
+    #[test]
+    fn substring_and_regex_filters_apply_before_paging() {
+        let (_dir, path) = numbered_log();
+
+        let substring = LineMatcher::new(Some("line 9".into()), None).unwrap();
+        let page = scan_lines(&path, &substring, 3, 1).unwrap();
+        assert_eq!(page.lines, ["line 90", "line 91", "line 92"]);
+        assert_eq!(page.total_matched, 11);
+        assert!(page.has_more);
+
+        let regex = LineMatcher::new(None, Some(r"^line \d5$".into())).unwrap();
+        let page = scan_lines(&path, &regex, 100, 0).unwrap();
+        assert_eq!(page.total_matched, 9);
+        assert_eq!(page.lines.last().unwrap(), "line 95");
+    }
+
+    #[test]
+    fn invalid_or_conflicting_filters_are_refused() {
+        for (filter, regex) in [(None, Some("(")), (Some("a"), Some("a"))] {
+            assert!(matches!(
+                LineMatcher::new(filter.map(String::from), regex.map(String::from)),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn lines_of_a_non_utf8_file_are_refused() {
+        let (dir, _) = numbered_log();
+        let path = dir.path().join("latin1.txt");
+        std::fs::write(&path, b"caf\xe9\n").unwrap();
+        let all = LineMatcher::new(None, None).unwrap();
+        assert!(matches!(
+            scan_lines(&path, &all, 10, 0),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn known_digests_are_verified() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, "abc").unwrap();
+
+        let cases = [
+            (
+                HashAlgorithm::Sha256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (HashAlgorithm::Md5, "900150983cd24fb0d6963f7d28e17f72"),
+            (
+                HashAlgorithm::Sha1,
+                "a9993e364706816aba3e25717850c26c9cd0d89d",
+            ),
+        ];
+        for (algorithm, expected) in cases {
+            let actual = digest_file(&path, algorithm).await.unwrap();
+            assert_eq!(actual, expected);
+            assert!(digest_matches(&actual, expected));
+            assert!(digest_matches(
+                &actual,
+                &format!(" {}\n", expected.to_uppercase())
+            ));
+            assert!(!digest_matches(&actual, &expected[1..]));
+        }
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file_and_leaves_no_tmp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!dir.path().join("notes.txt.tmp").exists());
+    }
+
+    #[test]
+    fn extended_length_prefixes_are_stripped() {
+        assert_eq!(
+            shell_path(Path::new(r"\\?\C:\Projects\a.db")),
+            PathBuf::from(r"C:\Projects\a.db")
+        );
+        assert_eq!(
+            shell_path(Path::new(r"\\?\UNC\server\share\a.db")),
+            PathBuf::from(r"\\server\share\a.db")
+        );
+        assert_eq!(
+            shell_path(Path::new("/home/me/a.db")),
+            PathBuf::from("/home/me/a.db")
+        );
+    }
+
+    #[test]
+    fn reveal_and_open_use_the_platform_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("ticket.md");
+        std::fs::write(&file, "# BUG-1").unwrap();
+
+        let (reveal, reveal_args) = reveal_command(&file);
+        let (open, open_args) = open_command(&file);
+        #[cfg(target_os = "macos")]
+        {
+            assert_eq!((reveal, open), ("open", "open"));
+            assert_eq!(reveal_args, [OsString::from("-R"), file.clone().into()]);
+        }
+        #[cfg(windows)]
+        {
+            assert_eq!((reveal, open), ("explorer", "explorer"));
+            assert_eq!(
+                reveal_args,
+                [OsString::from("/select,"), file.clone().into()]
+            );
+        }
+        #[cfg(not(any(target_os = "macos", windows)))]
+        {
+            assert_eq!((reveal, open), ("xdg-open", "xdg-open"));
+            assert_eq!(reveal_args, [OsString::from(dir.path())]);
+        }
+        assert_eq!(open_args, [OsString::from(&file)]);
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::error::AppError;
+use crate::files;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Upper bound on concurrently watched directories.
+const MAX_WATCHERS: usize = 10;
+
+/// Event emitted for every file created, modified or removed in a watched
+/// directory.
+const FS_CHANGE_EVENT: &str = "fs:change";
+
+/// Event emitted when the active project database was changed by someone
+/// else (typically a sync client replacing the file).
+const PROJECT_CHANGED_EVENT: &str = "project:changed-externally";
+
+/// Quiet time after the last file event before the database is compared
+/// with its previous state, so one sync or checkpoint is reported once.
+const PROJECT_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Changes within this long after a pause ends still belong to our own
+/// maintenance (file events are delivered asynchronously).
+const PAUSE_SETTLE: Duration = Duration::from_secs(3);
+
+/// Our own writes always go through the WAL first; a main-file change with
+/// no WAL write this recent did not come from us.
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Payload of `fs:change`.
+#[derive(Debug, Clone, Serialize)]
+struct FsChange {
+    watcher_id: String,
+    /// `create`, `modify` or `remove`.
+    kind: &'static str,
+    path: String,
+}
+
+/// Payload of `project:changed-externally`.
+#[derive(Debug, Clone, Serialize)]
+struct ProjectChange {
+    db_path: String,
+}
+
+/// Attributes of the project database compared between file events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DbFingerprint {
+    /// Changes when the file is replaced rather than written in place.
+    identity: u64,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Watcher of the active project's database.
+struct ProjectWatch {
+    _watcher: RecommendedWatcher,
+}
+
+/// Nesting depth of `ProjectWatchPause` guards and when the last one ended.
+#[derive(Default)]
+struct ProjectPause {
+    depth: AtomicUsize,
+    resumed_at: Mutex<Option<Instant>>,
+}
+
+impl ProjectPause {
+    fn begin(self: &Arc<Self>) -> ProjectWatchPause {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        ProjectWatchPause {
+            pause: Some(self.clone()),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.depth.load(Ordering::SeqCst) > 0
+            || self
+                .resumed_at
+                .lock()
+                .unwrap()
+                .is_some_and(|at| at.elapsed() < PAUSE_SETTLE)
+    }
+}
+
+/// Keeps the project watcher from reporting changes while our own backup,
+/// restore, vacuum or import rewrites the database. Released on drop.
+pub struct ProjectWatchPause {
+    pause: Option<Arc<ProjectPause>>,
+}
+
+impl Drop for ProjectWatchPause {
+    fn drop(&mut self) {
+        if let Some(pause) = &self.pause {
+            *pause.resumed_at.lock().unwrap() = Some(Instant::now());
+            pause.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Tauri managed state holding the active directory watchers and the
+/// project watcher. Dropping a watcher stops it.
+#[derive(Default)]
+pub struct FsWatchState {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    next_id: AtomicU64,
+    project: Mutex<Option<ProjectWatch>>,
+    project_pause: Arc<ProjectPause>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Watch a directory (non-recursively) and emit `fs:change` for external
+/// changes to its files, e.g. a project database on a network drive being
+/// modified by another machine. Returns the watcher id to pass to
+/// `unwatch_directory`.
+#[tauri::command]
+pub fn watch_directory(
+    path: String,
+    app: AppHandle,
+    state: tauri::State<'_, FsWatchState>,
+) -> Result<String, AppError> {
+    let dir = files::validate_path(&app, Path::new(&path))?;
+    if !dir.is_dir() {
+        return Err(AppError::Validation(format!(
+            "not a directory: {}",
+            dir.display()
+        )));
+    }
+
+    let mut watchers = state.watchers.lock().unwrap();
+    if watchers.len() >= MAX_WATCHERS {
+        return Err(AppError::Validation(format!(
+            "too many directory watchers (max {})",
+            MAX_WATCHERS
+        )));
+    }
+
+    let id = format!("fs-{}", state.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    let watcher_id = id.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(e) => {
+                log::warn!("watch_directory: {}: {}", watcher_id, e);
+                return;
+            }
+        };
+        let Some(kind) = change_kind(&event.kind) else {
+            return;
+        };
+        for path in event.paths {
+            app.emit(
+                FS_CHANGE_EVENT,
+                FsChange {
+                    watcher_id: watcher_id.clone(),
+                    kind,
+                    path: path.to_string_lossy().into_owned(),
+                },
+            )
+            .ok();
+        }
+    })
+    .map_err(|e| AppError::Io(format!("cannot create watcher: {}", e)))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::Io(format!("cannot watch {}: {}", dir.display(), e)))?;
+
+    watchers.insert(id.clone(), watcher);
+    Ok(id)
+}
+
+/// Stop a watcher started by `watch_directory`.
+#[tauri::command]
+pub fn unwatch_directory(
+    watcher_id: String,
+    state: tauri::State<'_, FsWatchState>,
+) -> Result<(), AppError> {
+    match state.watchers.lock().unwrap().remove(&watcher_id) {
+        Some(_) => Ok(()),
+        None => Err(AppError::Validation(format!(
+            "no directory watcher with id {}",
+            watcher_id
+        ))),
+    }
+}
+
+/// Watch the active project database and emit `project:changed-externally`
+/// when it is changed by something other than this app, e.g. Dropbox or
+/// Syncthing replacing it. Replaces the watcher of the previous project.
+#[tauri::command]
+pub fn watch_project(
+    db_path: String,
+    app: AppHandle,
+    state: tauri::State<'_, FsWatchState>,
+) -> Result<(), AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    if !db.is_file() {
+        return Err(AppError::Validation(format!(
+            "project database not found: {}",
+            db.display()
+        )));
+    }
+    let dir = db
+        .parent()
+        .ok_or_else(|| AppError::Validation("database path has no parent".into()))?
+        .to_path_buf();
+    let file_name = db.file_name().map(|name| name.to_os_string());
+
+    // The directory is watched rather than the file: sync clients replace
+    // the file, and a watch on the old one would go silent.
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let event = match result {
+            Ok(event) => event,
+            Err(e) => {
+                log::warn!("watch_project: {}", e);
+                return;
+            }
+        };
+        if is_project_event(&event, file_name.as_deref()) {
+            tx.send(()).ok();
+        }
+    })
+    .map_err(|e| AppError::Io(format!("cannot create watcher: {}", e)))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::Io(format!("cannot watch {}: {}", dir.display(), e)))?;
+
+    tauri::async_runtime::spawn(debounce_project_changes(
+        app.clone(),
+        db,
+        rx,
+        state.project_pause.clone(),
+    ));
+    // Dropping the previous watcher closes its channel, which ends its task.
+    *state.project.lock().unwrap() = Some(ProjectWatch { _watcher: watcher });
+    Ok(())
+}
+
+/// Stop watching the active project (when it is closed).
+#[tauri::command]
+pub fn unwatch_project(state: tauri::State<'_, FsWatchState>) -> Result<(), AppError> {
+    state.project.lock().unwrap().take();
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Pause the project watcher until the returned guard is dropped. Call
+/// around our own operations that rewrite a project database.
+pub fn pause_project_watch(app: &AppHandle) -> ProjectWatchPause {
+    match app.try_state::<FsWatchState>() {
+        Some(state) => state.project_pause.begin(),
+        None => ProjectWatchPause { pause: None },
+    }
+}
+
+/// `fs:change` kind of a file event, `None` for events not reported.
+fn change_kind(kind: &EventKind) -> Option<&'static str> {
+    match kind {
+        EventKind::Create(_) => Some("create"),
+        EventKind::Modify(_) => Some("modify"),
+        EventKind::Remove(_) => Some("remove"),
+        _ => None,
+    }
+}
+
+/// Whether a file event in the project directory concerns the database
+/// file itself.
+fn is_project_event(event: &notify::Event, file_name: Option<&OsStr>) -> bool {
+    change_kind(&event.kind).is_some()
+        && event.paths.iter().any(|path| path.file_name() == file_name)
+}
+
+/// Compare the database with its previous state after each burst of file
+/// events. A change counts as external when the file was replaced, or was
+/// rewritten in place without any recent write to our WAL.
+async fn debounce_project_changes(
+    app: AppHandle,
+    db: PathBuf,
+    mut rx: UnboundedReceiver<()>,
+    pause: Arc<ProjectPause>,
+) {
+    let mut baseline = fingerprint(&db);
+    while rx.recv().await.is_some() {
+        loop {
+            match tokio::time::timeout(PROJECT_DEBOUNCE, rx.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        let current = fingerprint(&db);
+        if current == baseline {
+            continue;
+        }
+        let previous = std::mem::replace(&mut baseline, current);
+        // Removed: a replacement in progress, reported when the new file
+        // shows up.
+        let Some(current) = current else {
+            continue;
+        };
+        if pause.is_paused() {
+            continue;
+        }
+        let replaced = previous.map_or(true, |previous| previous.identity != current.identity);
+        if replaced || !wal_recently_written(&db) {
+            log::info!(
+                "debounce_project_changes: {} changed externally",
+                db.display()
+            );
+            app.emit(
+                PROJECT_CHANGED_EVENT,
+                ProjectChange {
+                    db_path: db.to_string_lossy().into_owned(),
+                },
+            )
+            .ok();
+        }
+    }
+}
+
+fn fingerprint(db: &Path) -> Option<DbFingerprint> {
+    let meta = std::fs::metadata(db).ok()?;
+    Some(DbFingerprint {
+        identity: file_identity(&meta),
+        size: meta.len(),
+        modified: meta.modified().ok(),
+    })
+}
+
+fn wal_recently_written(db: &Path) -> bool {
+    let mut wal = db.as_os_str().to_owned();
+    wal.push("-wal");
+    std::fs::metadata(PathBuf::from(wal))
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < OWN_WRITE_WINDOW)
+}
+
+#[cfg(unix)]
+fn file_identity(meta: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+/// Creation time: a replaced file is a new file. (The file index is not
+/// available on stable Rust.)
+#[cfg(not(unix))]
+fn file_identity(meta: &std::fs::Metadata) -> u64 {
+    meta.created()
+        .ok()
+        .and_then(|created| created.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind, RemoveKind};
+
+    fn event(kind: EventKind, path: &Path) -> notify::Event {
+        notify::Event::new(kind).add_path(path.to_path_buf())
+    }
+
+    #[test]
+    fn only_create_modify_and_remove_are_reported() {
+        assert_eq!(
+            change_kind(&EventKind::Create(CreateKind::Any)),
+            Some("create")
+        );
+        assert_eq!(
+            change_kind(&EventKind::Modify(ModifyKind::Any)),
+            Some("modify")
+        );
+        assert_eq!(
+            change_kind(&EventKind::Remove(RemoveKind::Any)),
+            Some("remove")
+        );
+        assert_eq!(change_kind(&EventKind::Access(AccessKind::Any)), None);
+        assert_eq!(change_kind(&EventKind::Other), None);
+    }
+
+    #[test]
+    fn project_events_are_filtered_by_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("project.db");
+        let name = db.file_name();
+        let modify = EventKind::Modify(ModifyKind::Any);
+
+        assert!(is_project_event(&event(modify, &db), name));
+        assert!(!is_project_event(
+            &event(modify, &dir.path().join("project.db-wal")),
+            name
+        ));
+        assert!(!is_project_event(
+            &event(EventKind::Access(AccessKind::Any), &db),
+            name
+        ));
+    }
+
+    #[test]
+    fn fingerprint_tells_in_place_writes_from_replacements() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("project.db");
+        assert_eq!(fingerprint(&db), None);
+
+        std::fs::write(&db, "v1").unwrap();
+        let original = fingerprint(&db).unwrap();
+        std::fs::write(&db, "v2 longer").unwrap();
+        let rewritten = fingerprint(&db).unwrap();
+        assert_eq!(rewritten.identity, original.identity);
+        assert_ne!(rewritten.size, original.size);
+
+        // What a sync client does: write a new file, rename it over.
+        let incoming = dir.path().join("project.db.sync");
+        std::fs::write(&incoming, "v3").unwrap();
+        std::fs::rename(&incoming, &db).unwrap();
+        #[cfg(unix)]
+        assert_ne!(fingerprint(&db).unwrap().identity, original.identity);
+    }
+
+    #[test]
+    fn wal_write_marks_the_change_as_ours() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("project.db");
+        std::fs::write(&db, "").unwrap();
+        assert!(!wal_recently_written(&db));
+
+        std::fs::write(dir.path().join("project.db-wal"), "frame").unwrap();
+        assert!(wal_recently_written(&db));
+    }
+
+    #[test]
+    fn pause_lasts_until_the_last_guard_settles() {
+        let pause = Arc::new(ProjectPause::default());
+        assert!(!pause.is_paused());
+
+        let outer = pause.begin();
+        let inner = pause.begin();
+        drop(inner);
+        assert_eq!(pause.depth.load(Ordering::SeqCst), 1);
+        drop(outer);
+        assert_eq!(pause.depth.load(Ordering::SeqCst), 0);
+        // Events of the operation are still arriving.
+        assert!(pause.is_paused());
+
+        *pause.resumed_at.lock().unwrap() = Instant::now().checked_sub(PAUSE_SETTLE);
+        assert!(!pause.is_paused());
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use tauri::AppHandle;
+
+use super::{
+    emit_progress, find_section, lookup_code, open_project, resolve_type, ImportRow, ItemWriter,
+    ProjectContext, EFFORTS, MIN_SCHEMA_VERSION, PRIORITIES, SEVERITIES,
+};
+use crate::backup::MaintenanceState;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Item fields a CSV column can be mapped to. `status` maps to the section
+/// (board column) the item is placed in.
+const IMPORT_FIELDS: [&str; 11] = [
+    "title",
+    "description",
+    "status",
+    "type",
+    "emoji",
+    "component",
+    "module",
+    "severity",
+    "priority",
+    "effort",
+    "user_story",
+];
+
+/// Fields users commonly have in spreadsheets that the project schema has no
+/// column for. Rejected with an explicit message rather than "unknown field".
+const UNSUPPORTED_FIELDS: [&str; 2] = ["due_date", "tags"];
+
+/// Refuse CSV files larger than this; they are parsed in memory.
+const MAX_CSV_BYTES: u64 = 50 * 1024 * 1024;
+
+const UTF8_BOM: &str = "\u{FEFF}";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How CSV columns map onto item fields, as accepted by `import_tickets_csv`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CsvImportMapping {
+    /// CSV header → item field (see `IMPORT_FIELDS`). Unmapped columns are
+    /// ignored.
+    pub columns: HashMap<String, String>,
+    /// Item type for rows without a mapped, non-empty `type` cell.
+    pub default_type: Option<String>,
+    /// Section for rows without a mapped, non-empty `status` cell.
+    pub default_status: Option<String>,
+}
+
+/// A CSV row that cannot be imported.
+#[derive(Debug, Serialize)]
+pub struct CsvRowError {
+    /// 1-based line number where the record starts in the file.
+    pub line: usize,
+    pub message: String,
+}
+
+/// A parsed CSV file: its records after the header, and the column index
+/// of each mapped field.
+struct CsvTable {
+    delimiter: char,
+    columns: HashMap<String, usize>,
+    records: Vec<(usize, Vec<String>)>,
+}
+
+/// Return value of `import_tickets_csv`.
+#[derive(Debug, Serialize)]
+pub struct CsvImportReport {
+    pub dry_run: bool,
+    /// Detected field delimiter.
+    pub delimiter: char,
+    pub rows_ok: usize,
+    pub errors: Vec<CsvRowError>,
+    /// Statuses with no matching section; a section is created for each.
+    pub new_statuses: Vec<String>,
+    /// Ids of the created items, in CSV order. Empty for a dry run and when
+    /// any row has errors (nothing is written in that case).
+    pub created_ids: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Create items from a CSV file. The header row names the columns; `mapping`
+/// assigns them to item fields. BOMs and both `,` and `;` delimiters are
+/// handled.
+///
+/// Every row is validated first. With `dry_run`, or when any row is invalid,
+/// the report is returned without writing anything. Otherwise all rows are
+/// inserted in a single transaction, creating a section for each unknown
+/// status, and `import:progress` is emitted along the way. The frontend must
+/// reload the project afterwards.
+#[tauri::command]
+pub async fn import_tickets_csv(
+    db_path: String,
+    csv_path: String,
+    mapping: CsvImportMapping,
+    dry_run: bool,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<CsvImportReport, AppError> {
+    let field_of = resolve_mapping(&mapping)?;
+    let csv_path = files::validate_path(&app, Path::new(&csv_path))?;
+
+    let size = std::fs::metadata(&csv_path)?.len();
+    if size > MAX_CSV_BYTES {
+        return Err(AppError::Validation(format!(
+            "CSV file is too large ({} bytes, max {})",
+            size, MAX_CSV_BYTES
+        )));
+    }
+    let bytes = std::fs::read(&csv_path)?;
+    let text = String::from_utf8(bytes)
+        .map_err(|_| AppError::Validation("CSV file is not valid UTF-8".into()))?;
+    let text = text.strip_prefix(UTF8_BOM).unwrap_or(&text);
+
+    let table = read_table(text, &field_of)?;
+
+    let _guard = if dry_run {
+        None
+    } else {
+        Some(
+            maintenance
+                .lock
+                .try_lock()
+                .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?,
+        )
+    };
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    import_table(
+        Path::new(&db_path),
+        table,
+        &mapping,
+        dry_run,
+        |done, total| emit_progress(&app, done, total),
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Parse `text` and locate the column of each mapped field in its header.
+fn read_table(text: &str, field_of: &[(String, String)]) -> Result<CsvTable, AppError> {
+    let delimiter = detect_delimiter(text);
+    let mut records = parse_csv(text, delimiter)?;
+    if records.is_empty() {
+        return Err(AppError::Validation("CSV file is empty".into()));
+    }
+    let (_, header) = records.remove(0);
+
+    // Column index for each mapped field.
+    let mut columns = HashMap::new();
+    for (csv_column, field) in field_of {
+        let index = header
+            .iter()
+            .position(|name| name.trim() == csv_column.as_str())
+            .ok_or_else(|| {
+                AppError::Validation(format!("CSV has no column named {}", csv_column))
+            })?;
+        columns.insert(field.clone(), index);
+    }
+    if !columns.contains_key("title") {
+        return Err(AppError::Validation(
+            "a CSV column must be mapped to title".into(),
+        ));
+    }
+    Ok(CsvTable {
+        delimiter,
+        columns,
+        records,
+    })
+}
+
+/// Validate every record of `table` against the project at `db_path` and,
+/// unless `dry_run` or a row is invalid, insert them all in one
+/// transaction, calling `progress` with the rows written and the total.
+async fn import_table(
+    db_path: &Path,
+    table: CsvTable,
+    mapping: &CsvImportMapping,
+    dry_run: bool,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<CsvImportReport, AppError> {
+    let (mut conn, context) = open_project(db_path, dry_run, MIN_SCHEMA_VERSION).await?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (line, record) in table.records {
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        match validate_row(&record, &table.columns, mapping, &context) {
+            Ok(row) => rows.push(row),
+            Err(message) => errors.push(CsvRowError { line, message }),
+        }
+    }
+
+    let new_statuses: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.status.as_deref())
+        .filter(|status| find_section(&context.sections, status).is_none())
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut report = CsvImportReport {
+        dry_run,
+        delimiter: table.delimiter,
+        rows_ok: rows.len(),
+        errors,
+        new_statuses,
+        created_ids: Vec::new(),
+    };
+    if dry_run || !report.errors.is_empty() || rows.is_empty() {
+        return Ok(report);
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+    for (index, row) in rows.iter().enumerate() {
+        report
+            .created_ids
+            .push(writer.insert_item(&mut tx, row).await?);
+        progress(index + 1, rows.len());
+    }
+    tx.commit().await?;
+    Ok(report)
+}
+
+/// Check `mapping.columns` against `IMPORT_FIELDS`; each field may be mapped
+/// at most once.
+fn resolve_mapping(mapping: &CsvImportMapping) -> Result<Vec<(String, String)>, AppError> {
+    let mut seen = BTreeSet::new();
+    let mut resolved = Vec::new();
+    for (csv_column, field) in &mapping.columns {
+        let field = field.trim().to_lowercase().replace(' ', "_");
+        if UNSUPPORTED_FIELDS.contains(&field.as_str()) {
+            return Err(AppError::Validation(format!(
+                "{} cannot be imported: projects have no {} field",
+                field, field
+            )));
+        }
+        if !IMPORT_FIELDS.contains(&field.as_str()) {
+            return Err(AppError::Validation(format!(
+                "unknown import field: {}",
+                field
+            )));
+        }
+        if !seen.insert(field.clone()) {
+            return Err(AppError::Validation(format!(
+                "{} is mapped to more than one column",
+                field
+            )));
+        }
+        resolved.push((csv_column.trim().to_string(), field));
+    }
+    Ok(resolved)
+}
+
+/// Pick `;` when the header line has more semicolons than commas (outside
+/// quotes), as spreadsheets do in locales with a decimal comma.
+fn detect_delimiter(text: &str) -> char {
+    let mut in_quotes = false;
+    let (mut commas, mut semicolons) = (0, 0);
+    for c in text.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => commas += 1,
+            ';' if !in_quotes => semicolons += 1,
+            '\n' if !in_quotes => break,
+            _ => {}
+        }
+    }
+    if semicolons > commas {
+        ';'
+    } else {
+        ','
+    }
+}
+
+/// Parse RFC 4180 CSV (quoted fields may contain delimiters, doubled quotes
+/// and line breaks; CRLF or LF line endings). Returns each record with the
+/// 1-based line it starts on.
+fn parse_csv(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, AppError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(AppError::Validation(format!(
+            "unterminated quoted field starting on line {}",
+            record_line
+        )));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/// Validate one record. Errors are user-facing and name the offending field.
+fn validate_row(
+    record: &[String],
+    columns: &HashMap<String, usize>,
+    mapping: &CsvImportMapping,
+    context: &ProjectContext,
+) -> Result<ImportRow, String> {
+    let cell = |field: &str| -> Option<String> {
+        columns
+            .get(field)
+            .and_then(|&index| record.get(index))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    let title = cell("title").ok_or("title is empty")?;
+    if title.contains(['\r', '\n']) {
+        return Err("title must be a single line".into());
+    }
+
+    let item_type = cell("type")
+        .or_else(|| mapping.default_type.clone())
+        .ok_or("type is empty and no default type was given")?;
+    let item_type = resolve_type(context, &item_type)?;
+
+    let severity = match cell("severity") {
+        Some(value) => Some(
+            lookup_code(&SEVERITIES, &value)
+                .ok_or_else(|| format!("invalid severity: {} (expected P0-P4)", value))?,
+        ),
+        None => None,
+    };
+    let priority = match cell("priority") {
+        Some(value) => Some(
+            PRIORITIES
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(&value))
+                .copied()
+                .ok_or_else(|| {
+                    format!(
+                        "invalid priority: {} (expected {})",
+                        value,
+                        PRIORITIES.join(", ")
+                    )
+                })?,
+        ),
+        None => None,
+    };
+    let effort = match cell("effort") {
+        Some(value) => Some(
+            lookup_code(&EFFORTS, &value)
+                .ok_or_else(|| format!("invalid effort: {} (expected XS-XL)", value))?,
+        ),
+        None => None,
+    };
+
+    Ok(ImportRow {
+        item_type,
+        title,
+        status: cell("status").or_else(|| mapping.default_status.clone()),
+        emoji: cell("emoji"),
+        component: cell("component"),
+        module: cell("module"),
+        severity,
+        priority,
+        effort,
+        description: cell("description"),
+        user_story: cell("user_story"),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{items, project};
+
+    fn mapping(columns: &[(&str, &str)]) -> CsvImportMapping {
+        CsvImportMapping {
+            columns: columns
+                .iter()
+                .map(|(column, field)| (column.to_string(), field.to_string()))
+                .collect(),
+            default_type: Some("bug".into()),
+            default_status: None,
+        }
+    }
+
+    fn table(text: &str, mapping: &CsvImportMapping) -> CsvTable {
+        read_table(text, &resolve_mapping(mapping).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn records_follow_rfc_4180() {
+        let text = "a;b\r\n\"x;\"\"y\"\"\";\"multi\nline\"\n\nlast;";
+        assert_eq!(detect_delimiter(text), ';');
+        assert_eq!(detect_delimiter("\"a;b;c\",d\n"), ',');
+        assert_eq!(
+            parse_csv(text, ';').unwrap(),
+            [
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x;\"y\"".to_string(), "multi\nline".to_string()]),
+                (4, vec![String::new()]),
+                (5, vec!["last".to_string(), String::new()]),
+            ]
+        );
+
+        let err = parse_csv("a\n\"open\nstill", ',').unwrap_err();
+        assert!(err.to_string().contains("starting on line 2"), "{}", err);
+    }
+
+    #[test]
+    fn mappings_are_checked() {
+        let resolved =
+            resolve_mapping(&mapping(&[("Name", " Title"), ("Story", "User Story")])).unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert!(resolved.contains(&("Story".into(), "user_story".into())));
+
+        for (columns, message) in [
+            (vec![("Due", "due_date")], "projects have no due_date field"),
+            (vec![("X", "owner")], "unknown import field: owner"),
+            (
+                vec![("A", "title"), ("B", "TITLE")],
+                "title is mapped to more than one column",
+            ),
+        ] {
+            let err = resolve_mapping(&mapping(&columns)).unwrap_err();
+            assert!(err.to_string().contains(message), "{}", err);
+        }
+
+        let err = read_table(
+            "Name\nx",
+            &resolve_mapping(&mapping(&[("Nom", "title")])).unwrap(),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("no column named Nom"), "{}", err);
+        let err = read_table(
+            "Name\nx",
+            &resolve_mapping(&mapping(&[("Name", "module")])).unwrap(),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("mapped to title"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn invalid_rows_block_the_whole_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mapping = mapping(&[
+            ("Title", "title"),
+            ("Type", "type"),
+            ("Sev", "severity"),
+            ("Prio", "priority"),
+            ("Effort", "effort"),
+        ]);
+        let text = "Title,Type,Sev,Prio,Effort\n\
+                    Good,,P1 - Critique,haute,xs\n\
+                    ,,,,\n\
+                    ,,P1,,\n\
+                    Bad type,TASK,,,\n\
+                    Bad sev,,P9,,\n\
+                    Bad prio,,,Urgent,\n\
+                    Bad effort,,,,XXL\n\
+                    \"Two\nlines\",,,,\n";
+
+        let report = import_table(&db_path, table(text, &mapping), &mapping, false, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(report.rows_ok, 1);
+        let errors: Vec<(usize, &str)> = report
+            .errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (4, "title is empty"),
+                (5, "unknown type: TASK"),
+                (6, "invalid severity: P9 (expected P0-P4)"),
+                (
+                    7,
+                    "invalid priority: Urgent (expected Haute, Moyenne, Faible)"
+                ),
+                (8, "invalid effort: XXL (expected XS-XL)"),
+                (9, "title must be a single line"),
+            ]
+        );
+        assert!(report.created_ids.is_empty());
+        assert_eq!(items(&db_path).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn valid_rows_are_written_unless_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mapping = mapping(&[
+            ("Title", "title"),
+            ("Status", "status"),
+            ("Sev", "severity"),
+        ]);
+        let text = "Title;Status;Sev\nFirst;Review;P2\nSecond;;\nThird;done;P0 - Bloquant\n";
+
+        let report = import_table(&db_path, table(text, &mapping), &mapping, true, |_, _| {})
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.delimiter, ';');
+        assert_eq!(report.rows_ok, 3);
+        assert_eq!(report.new_statuses, ["Review"]);
+        assert!(report.created_ids.is_empty());
+        assert_eq!(items(&db_path).await.len(), 1);
+
+        let mut progress = Vec::new();
+        let report = import_table(
+            &db_path,
+            table(text, &mapping),
+            &mapping,
+            false,
+            |done, total| progress.push((done, total)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.created_ids, ["BUG-002", "BUG-003", "BUG-004"]);
+        assert_eq!(progress, [(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(
+            items(&db_path).await,
+            [
+                ("BUG-002".to_string(), "Review".to_string(), 0),
+                ("BUG-003".to_string(), "Todo".to_string(), 1),
+                ("BUG-004".to_string(), "Done".to_string(), 0),
+                ("BUG-1".to_string(), "Todo".to_string(), 0),
+            ]
+        );
+    }
+}
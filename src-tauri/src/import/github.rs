@@ -0,0 +1,692 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, ACCEPT, RETRY_AFTER, USER_AGENT};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use tauri::{AppHandle, Manager};
+
+use super::{
+    emit_progress, open_project, resolve_type, ImportRow, ItemWriter, ProjectContext,
+    EXTERNAL_REFS_SCHEMA_VERSION,
+};
+use crate::backup::MaintenanceState;
+use crate::commands::NETWORK_TIMEOUT_MS;
+use crate::error::AppError;
+use crate::fs_watch;
+use crate::secrets;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// REST API version requested with every call.
+const GITHUB_API_VERSION: &str = "2022-11-28";
+
+/// `item_external_refs.source` of GitHub issues; the key is `owner/repo#N`.
+const GITHUB_SOURCE: &str = "github";
+
+/// Name of the secret holding the personal access token.
+const GITHUB_TOKEN_SECRET: &str = "github_token";
+
+/// Issues per page, the API maximum.
+const PER_PAGE: usize = 100;
+
+/// Refuse repositories with more issues than this.
+const MAX_PAGES: usize = 100;
+
+/// Attempts after the first for a page that failed with a server error, a
+/// connection error or a rate limit.
+const RETRIES: u32 = 4;
+
+/// First retry delay after a server or connection error; doubled after
+/// every attempt.
+const RETRY_BACKOFF_MS: u64 = 1_000;
+
+/// Longest wait for a rate limit to reset before giving up.
+const MAX_RATE_LIMIT_WAIT_SECS: u64 = 120;
+
+const MAX_TOKEN_CHARS: usize = 255;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options accepted by `github_import`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GithubImportOptions {
+    /// `open`, `closed` or `all` (the default).
+    pub state: Option<String>,
+    /// Only issues updated at or after this RFC 3339 timestamp, to make
+    /// re-imports of large repositories quick.
+    pub since: Option<String>,
+    /// Issue state (`open`, `closed`) or close reason (`completed`,
+    /// `not_planned`) → section title. Close reasons take precedence;
+    /// unmapped states go to sections `Open` and `Closed`.
+    pub statuses: HashMap<String, String>,
+    /// Label name → item type. The first label of an issue with a mapping
+    /// decides its type.
+    pub labels: HashMap<String, String>,
+    /// Item type of issues without a mapped label. Defaults to the
+    /// project's first type.
+    pub default_type: Option<String>,
+    /// Leave issues imported by an earlier run as they are instead of
+    /// updating them.
+    pub skip_existing: bool,
+}
+
+/// An issue that was not imported.
+#[derive(Debug, Serialize)]
+pub struct GithubProblem {
+    pub number: u64,
+    pub message: String,
+}
+
+/// Return value of `github_import`.
+#[derive(Debug, Serialize)]
+pub struct GithubImportReport {
+    pub created: usize,
+    pub updated: usize,
+    /// Pull requests, issues unchanged since the last import (or already
+    /// imported with `skip_existing`) and issues listed in `problems`.
+    pub skipped: usize,
+    pub created_ids: Vec<String>,
+    pub updated_ids: Vec<String>,
+    pub problems: Vec<GithubProblem>,
+    /// States for which a section was created.
+    pub new_sections: Vec<String>,
+    /// Times the import waited for the rate limit to reset.
+    pub rate_limit_waits: u32,
+}
+
+/// The parts of an issue the import reads.
+#[derive(Debug, Deserialize)]
+struct GithubIssue {
+    number: u64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    state_reason: Option<String>,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    #[serde(default)]
+    milestone: Option<GithubMilestone>,
+    /// Set on pull requests, which the issues API lists too.
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLabel {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubMilestone {
+    #[serde(default)]
+    title: String,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Save the GitHub personal access token used by `github_import` in the OS
+/// keyring, or forget it with `None` or an empty string.
+#[tauri::command]
+pub fn set_github_token(token: Option<String>) -> Result<(), AppError> {
+    let token = token.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if let Some(token) = token {
+        if token.chars().count() > MAX_TOKEN_CHARS || token.chars().any(char::is_whitespace) {
+            return Err(AppError::Validation("invalid GitHub token".into()));
+        }
+    }
+    secrets::set_secret(GITHUB_TOKEN_SECRET, token)
+}
+
+/// Whether a GitHub token is stored.
+#[tauri::command]
+pub fn has_github_token() -> Result<bool, AppError> {
+    Ok(secrets::get_secret(GITHUB_TOKEN_SECRET)?.is_some())
+}
+
+/// Import the issues of a GitHub repository (`owner/name`) with the stored
+/// token. Milestones go to the module field, labels pick the item type and
+/// are listed in the description (items have no tags), and the state picks
+/// the section. Pull requests are skipped.
+///
+/// Each issue keeps `owner/name#N` as an external reference and its URL at
+/// the end of the description, so running the import again updates the
+/// items it created instead of duplicating them. Every page is fetched
+/// before anything is written; rate limits are waited out when they reset
+/// within a couple of minutes. Writes happen in one transaction and emit
+/// `import:progress`.
+#[tauri::command]
+pub async fn github_import(
+    db_path: String,
+    repo: String,
+    options: GithubImportOptions,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<GithubImportReport, AppError> {
+    let repo = validate_repo(&repo)?;
+    let state = options.state.as_deref().unwrap_or("all");
+    if !matches!(state, "open" | "closed" | "all") {
+        return Err(AppError::Validation(format!(
+            "invalid issue state: {}",
+            state
+        )));
+    }
+    if let Some(since) = &options.since {
+        since
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .map_err(|e| AppError::Validation(format!("invalid since: {}", e)))?;
+    }
+    let token = secrets::get_secret(GITHUB_TOKEN_SECRET)?
+        .ok_or_else(|| AppError::Unauthorized("no GitHub token is stored".into()))?;
+
+    let mut report = GithubImportReport {
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        created_ids: Vec::new(),
+        updated_ids: Vec::new(),
+        problems: Vec::new(),
+        new_sections: Vec::new(),
+        rate_limit_waits: 0,
+    };
+
+    let client = app.state::<TelemetryState>().client.read().await.clone();
+    let mut issues = Vec::new();
+    for page in 1..=MAX_PAGES {
+        let mut query = vec![
+            ("state", state.to_string()),
+            ("sort", "created".to_string()),
+            ("direction", "asc".to_string()),
+            ("per_page", PER_PAGE.to_string()),
+            ("page", page.to_string()),
+        ];
+        if let Some(since) = &options.since {
+            query.push(("since", since.clone()));
+        }
+        let batch = fetch_page(&client, &token, &repo, &query, &mut report).await?;
+        let last = batch.len() < PER_PAGE;
+        issues.extend(batch);
+        if last {
+            break;
+        }
+        if page == MAX_PAGES {
+            return Err(AppError::Validation(format!(
+                "{} has more than {} issues",
+                repo,
+                MAX_PAGES * PER_PAGE
+            )));
+        }
+    }
+
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    let (mut conn, context) =
+        open_project(Path::new(&db_path), false, EXTERNAL_REFS_SCHEMA_VERSION).await?;
+
+    let default_type = match &options.default_type {
+        Some(item_type) => resolve_type(&context, item_type).map_err(AppError::Validation)?,
+        None => context
+            .types
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::Validation("project has no item types".into()))?,
+    };
+
+    let mut known: HashMap<String, String> = sqlx::query_as(
+        "SELECT external_key, item_id FROM item_external_refs
+         WHERE project_id = ? AND source = ?",
+    )
+    .bind(context.project_id)
+    .bind(GITHUB_SOURCE)
+    .fetch_all(&mut conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut pending = Vec::new();
+    for issue in &issues {
+        if issue.pull_request.is_some() {
+            report.skipped += 1;
+            continue;
+        }
+        match parse_issue(issue, &options, &default_type, &context) {
+            Ok(row) => pending.push((issue.number, format!("{}#{}", repo, issue.number), row)),
+            Err(message) => {
+                report.skipped += 1;
+                report.problems.push(GithubProblem {
+                    number: issue.number,
+                    message,
+                });
+            }
+        }
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+    for (index, (number, key, row)) in pending.iter().enumerate() {
+        let sections = writer.sections.len();
+        match known.get(key) {
+            Some(_) if options.skip_existing => report.skipped += 1,
+            Some(id) => match writer.update_item(&mut tx, id, row).await? {
+                Some(true) => {
+                    report.updated += 1;
+                    report.updated_ids.push(id.clone());
+                }
+                Some(false) => report.skipped += 1,
+                None => {
+                    report.skipped += 1;
+                    report.problems.push(GithubProblem {
+                        number: *number,
+                        message: format!("{} was archived or deleted; not updated", id),
+                    });
+                }
+            },
+            None => {
+                let id = writer.insert_item(&mut tx, row).await?;
+                sqlx::query(
+                    "INSERT INTO item_external_refs (project_id, item_id, source, external_key)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(context.project_id)
+                .bind(&id)
+                .bind(GITHUB_SOURCE)
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+                known.insert(key.clone(), id.clone());
+                report.created += 1;
+                report.created_ids.push(id);
+            }
+        }
+        if writer.sections.len() > sections {
+            report.new_sections.extend(row.status.clone());
+        }
+        emit_progress(&app, index + 1, pending.len());
+    }
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// `owner/name`, lowercased: GitHub names are case-insensitive and the
+/// external keys must match across runs.
+fn validate_repo(repo: &str) -> Result<String, AppError> {
+    let repo = repo.trim().trim_end_matches(".git");
+    let parts: Vec<&str> = repo.split('/').collect();
+    let valid = parts.len() == 2
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && !part.starts_with('.')
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "invalid repository (expected owner/name): {}",
+            repo
+        )));
+    }
+    Ok(repo.to_ascii_lowercase())
+}
+
+/// One page of `GET /repos/{repo}/issues`, retried with backoff on server
+/// and connection errors and after waiting out a rate limit.
+async fn fetch_page(
+    client: &reqwest::Client,
+    token: &str,
+    repo: &str,
+    query: &[(&str, String)],
+    report: &mut GithubImportReport,
+) -> Result<Vec<GithubIssue>, AppError> {
+    let url = format!("{}/repos/{}/issues", GITHUB_API, repo);
+    let mut backoff = Duration::from_millis(RETRY_BACKOFF_MS);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let retry = attempts <= RETRIES;
+        let result = client
+            .get(&url)
+            .query(query)
+            .bearer_auth(token)
+            .header(ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", GITHUB_API_VERSION)
+            .header(
+                USER_AGENT,
+                concat!("ticketflow/", env!("CARGO_PKG_VERSION")),
+            )
+            .timeout(Duration::from_millis(NETWORK_TIMEOUT_MS))
+            .send()
+            .await;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) if retry && (e.is_timeout() || e.is_connect()) => {
+                log::info!("github_import: {}, retry {}/{}", e, attempts, RETRIES);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let status = response.status();
+        if status.is_success() {
+            return response
+                .json()
+                .await
+                .map_err(|e| AppError::Network(format!("invalid GitHub response: {}", e)));
+        }
+        match status {
+            StatusCode::UNAUTHORIZED => {
+                return Err(AppError::Unauthorized("GitHub rejected the token".into()))
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(AppError::Validation(format!(
+                    "repository {} not found, or not visible with this token",
+                    repo
+                )))
+            }
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+                let Some(wait) = rate_limit_wait(response.headers()) else {
+                    return Err(AppError::Unauthorized(format!(
+                        "the token has no access to the issues of {}",
+                        repo
+                    )));
+                };
+                if !retry || wait.as_secs() > MAX_RATE_LIMIT_WAIT_SECS {
+                    return Err(AppError::QuotaExceeded(format!(
+                        "GitHub rate limit reached; it resets in {} s",
+                        wait.as_secs()
+                    )));
+                }
+                log::info!("github_import: rate limited, waiting {} s", wait.as_secs());
+                report.rate_limit_waits += 1;
+                tokio::time::sleep(wait).await;
+            }
+            _ if status.is_server_error() && retry => {
+                log::info!("github_import: {}, retry {}/{}", status, attempts, RETRIES);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            _ => return Err(AppError::Network(format!("GitHub returned {}", status))),
+        }
+    }
+}
+
+/// How long a rate-limited response asks to wait: `Retry-After` (secondary
+/// limits), else until `X-RateLimit-Reset` when no requests remain. `None`
+/// when the response is not about a rate limit.
+fn rate_limit_wait(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    if let Some(seconds) = header(RETRY_AFTER.as_str()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header("x-ratelimit-remaining") != Some(0) {
+        return None;
+    }
+    let reset = header("x-ratelimit-reset")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // One more second so the request does not land just before the reset.
+    Some(Duration::from_secs(reset.saturating_sub(now) + 1))
+}
+
+/// Map one issue onto an item.
+fn parse_issue(
+    issue: &GithubIssue,
+    options: &GithubImportOptions,
+    default_type: &str,
+    context: &ProjectContext,
+) -> Result<ImportRow, String> {
+    let title = issue.title.trim().replace(['\r', '\n'], " ");
+    if title.is_empty() {
+        return Err("issue has no title".into());
+    }
+
+    let item_type = match issue
+        .labels
+        .iter()
+        .find_map(|label| options.labels.get(&label.name))
+    {
+        Some(item_type) => resolve_type(context, item_type)?,
+        None => default_type.to_string(),
+    };
+
+    let status = issue
+        .state_reason
+        .as_ref()
+        .and_then(|reason| options.statuses.get(reason))
+        .or_else(|| options.statuses.get(&issue.state))
+        .cloned()
+        .unwrap_or_else(|| {
+            if issue.state == "closed" {
+                "Closed".to_string()
+            } else {
+                "Open".to_string()
+            }
+        });
+
+    let mut description = issue
+        .body
+        .as_deref()
+        .unwrap_or("")
+        .replace("\r\n", "\n")
+        .trim()
+        .to_string();
+    let labels: Vec<&str> = issue
+        .labels
+        .iter()
+        .map(|label| label.name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if !labels.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&format!("**Labels:** {}", labels.join(", ")));
+    }
+    if !issue.html_url.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&format!("GitHub: <{}>", issue.html_url));
+    }
+
+    Ok(ImportRow {
+        item_type,
+        title,
+        status: Some(status),
+        module: issue
+            .milestone
+            .as_ref()
+            .map(|milestone| milestone.title.trim().to_string())
+            .filter(|title| !title.is_empty()),
+        description: Some(description).filter(|d| !d.is_empty()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn context() -> ProjectContext {
+        ProjectContext {
+            project_id: 1,
+            types: vec!["BUG".into(), "FEAT".into()],
+            sections: vec![(1, "Open".into())],
+        }
+    }
+
+    fn issue(json: serde_json::Value) -> GithubIssue {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn repositories_are_normalized() {
+        assert_eq!(
+            validate_repo(" Bouaris/TicketFlow.git ").unwrap(),
+            "bouaris/ticketflow"
+        );
+        assert_eq!(validate_repo("a-b/c_d.e").unwrap(), "a-b/c_d.e");
+        for repo in [
+            "ticketflow",
+            "a/b/c",
+            "../ticketflow",
+            "owner/.github",
+            "own er/x",
+            "/x",
+        ] {
+            assert!(validate_repo(repo).is_err(), "{}", repo);
+        }
+    }
+
+    #[test]
+    fn rate_limit_waits_follow_the_headers() {
+        let headers = |pairs: &[(&'static str, String)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, HeaderValue::from_str(value).unwrap());
+            }
+            headers
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let wait = rate_limit_wait(&headers(&[("retry-after", "30".into())]));
+        assert_eq!(wait, Some(Duration::from_secs(30)));
+        let wait = rate_limit_wait(&headers(&[
+            ("x-ratelimit-remaining", "0".into()),
+            ("x-ratelimit-reset", (now + 60).to_string()),
+        ]))
+        .unwrap();
+        assert!((60..=61).contains(&wait.as_secs()), "{:?}", wait);
+        // A reset in the past still waits a second.
+        let wait = rate_limit_wait(&headers(&[
+            ("x-ratelimit-remaining", "0".into()),
+            ("x-ratelimit-reset", (now - 5).to_string()),
+        ]));
+        assert_eq!(wait, Some(Duration::from_secs(1)));
+
+        let wait = rate_limit_wait(&headers(&[
+            ("x-ratelimit-remaining", "12".into()),
+            ("x-ratelimit-reset", now.to_string()),
+        ]));
+        assert_eq!(wait, None, "a 403 with requests left is a permission error");
+        assert_eq!(rate_limit_wait(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn issues_map_onto_items() {
+        let options = GithubImportOptions {
+            statuses: HashMap::from([
+                ("open".into(), "Todo".into()),
+                ("not_planned".into(), "Won't do".into()),
+            ]),
+            labels: HashMap::from([("enhancement".into(), "feat".into())]),
+            ..Default::default()
+        };
+        let row = parse_issue(
+            &issue(serde_json::json!({
+                "number": 7,
+                "title": " Dark\nmode ",
+                "body": "Please.\r\nThanks",
+                "state": "closed",
+                "state_reason": "not_planned",
+                "html_url": "https://github.com/o/r/issues/7",
+                "labels": [{"name": "ui"}, {"name": "enhancement"}],
+                "milestone": {"title": " v2 "}
+            })),
+            &options,
+            "BUG",
+            &context(),
+        )
+        .unwrap();
+        assert_eq!(row.item_type, "FEAT");
+        assert_eq!(row.title, "Dark mode");
+        assert_eq!(row.status.as_deref(), Some("Won't do"));
+        assert_eq!(row.module.as_deref(), Some("v2"));
+        assert_eq!(
+            row.description.as_deref(),
+            Some("Please.\nThanks\n\n**Labels:** ui, enhancement\n\nGitHub: <https://github.com/o/r/issues/7>")
+        );
+
+        let row = parse_issue(
+            &issue(serde_json::json!({"number": 8, "title": "Crash", "state": "open"})),
+            &options,
+            "BUG",
+            &context(),
+        )
+        .unwrap();
+        assert_eq!(row.item_type, "BUG");
+        assert_eq!(row.status.as_deref(), Some("Todo"));
+        assert_eq!(row.module, None);
+        assert_eq!(row.description, None);
+
+        let closed = issue(serde_json::json!({
+            "number": 9, "title": "Old", "state": "closed", "state_reason": "completed"
+        }));
+        let row = parse_issue(&closed, &Default::default(), "BUG", &context()).unwrap();
+        assert_eq!(row.status.as_deref(), Some("Closed"));
+    }
+
+    #[test]
+    fn unusable_issues_are_reported() {
+        let untitled = issue(serde_json::json!({"number": 1, "title": " ", "state": "open"}));
+        assert!(parse_issue(&untitled, &Default::default(), "BUG", &context()).is_err());
+
+        let options = GithubImportOptions {
+            labels: HashMap::from([("question".into(), "QUESTION".into())]),
+            ..Default::default()
+        };
+        let asked = issue(serde_json::json!({
+            "number": 2, "title": "How?", "state": "open", "labels": [{"name": "question"}]
+        }));
+        let err = parse_issue(&asked, &options, "BUG", &context()).unwrap_err();
+        assert!(err.contains("QUESTION"), "{}", err);
+    }
+
+    #[test]
+    fn malformed_tokens_are_refused_before_the_keyring() {
+        for token in ["ghp abc", "ghp_\tabc", &"x".repeat(MAX_TOKEN_CHARS + 1)] {
+            let err = set_github_token(Some(token.into())).unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+        }
+    }
+}
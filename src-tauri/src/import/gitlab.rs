@@ -0,0 +1,902 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER, USER_AGENT};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use tauri::{AppHandle, Manager};
+
+use super::{
+    emit_progress, open_project, resolve_type, ImportRow, ItemWriter, ProjectContext,
+    EXTERNAL_REFS_SCHEMA_VERSION,
+};
+use crate::backup::MaintenanceState;
+use crate::commands::NETWORK_TIMEOUT_MS;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::secrets;
+use crate::telemetry::{self, TelemetryState};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const DEFAULT_BASE_URL: &str = "https://gitlab.com";
+
+/// `item_external_refs.source` of GitLab issues; the key is
+/// `<host>/<project id>#<iid>`.
+const GITLAB_SOURCE: &str = "gitlab";
+
+/// Prefix of the secrets holding personal access tokens, followed by the
+/// host (and port) of the instance.
+const GITLAB_TOKEN_SECRET: &str = "gitlab_token:";
+
+/// Issues per page, the API maximum.
+const PER_PAGE: usize = 100;
+
+/// Refuse projects with more issues than this.
+const MAX_PAGES: usize = 100;
+
+/// Attempts after the first for a page that failed with a server error, a
+/// connection error or a rate limit.
+const RETRIES: u32 = 4;
+
+/// First retry delay after a server or connection error; doubled after
+/// every attempt.
+const RETRY_BACKOFF_MS: u64 = 1_000;
+
+/// Longest wait for a rate limit to reset before giving up.
+const MAX_RATE_LIMIT_WAIT_SECS: u64 = 120;
+
+const MAX_TOKEN_CHARS: usize = 255;
+
+/// Emoji of items imported from confidential issues.
+const CONFIDENTIAL_EMOJI: &str = "🔒";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options accepted by `gitlab_import`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GitlabImportOptions {
+    /// `opened`, `closed` or `all` (the default).
+    pub state: Option<String>,
+    /// Only issues updated at or after this RFC 3339 timestamp, to make
+    /// re-imports of large projects quick.
+    pub since: Option<String>,
+    /// Issue state (`opened`, `closed`) → section title. Unmapped states go
+    /// to sections `Open` and `Closed`.
+    pub statuses: HashMap<String, String>,
+    /// Label name → item type. The first label of an issue with a mapping
+    /// decides its type.
+    pub labels: HashMap<String, String>,
+    /// Item type of issues without a mapped label. Defaults to the
+    /// project's first type.
+    pub default_type: Option<String>,
+    /// Leave confidential issues out instead of importing them marked 🔒.
+    pub skip_confidential: bool,
+    /// Leave issues imported by an earlier run as they are instead of
+    /// updating them.
+    pub skip_existing: bool,
+    /// Do not verify the server's TLS certificate. For self-hosted
+    /// instances behind a self-signed or corporate certificate only.
+    pub accept_invalid_certs: bool,
+}
+
+/// An issue that was not imported.
+#[derive(Debug, Serialize)]
+pub struct GitlabProblem {
+    pub iid: u64,
+    pub message: String,
+}
+
+/// Return value of `gitlab_import`.
+#[derive(Debug, Serialize)]
+pub struct GitlabImportReport {
+    pub created: usize,
+    pub updated: usize,
+    /// Issues unchanged since the last import (or already imported with
+    /// `skip_existing`), confidential issues left out and issues listed in
+    /// `problems`.
+    pub skipped: usize,
+    pub created_ids: Vec<String>,
+    pub updated_ids: Vec<String>,
+    pub problems: Vec<GitlabProblem>,
+    /// States for which a section was created.
+    pub new_sections: Vec<String>,
+    /// Times the import waited for the rate limit to reset.
+    pub rate_limit_waits: u32,
+}
+
+/// The parts of an issue the import reads.
+#[derive(Debug, Deserialize)]
+struct GitlabIssue {
+    iid: u64,
+    project_id: u64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    web_url: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    milestone: Option<GitlabMilestone>,
+    #[serde(default)]
+    due_date: Option<String>,
+    #[serde(default)]
+    confidential: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabMilestone {
+    #[serde(default)]
+    title: String,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Save the personal access token of a GitLab instance (gitlab.com when
+/// `base_url` is `None`) in the OS keyring, or forget it with `None` or an
+/// empty string.
+#[tauri::command]
+pub fn set_gitlab_token(base_url: Option<String>, token: Option<String>) -> Result<(), AppError> {
+    let base_url = parse_base_url(base_url.as_deref())?;
+    let token = token.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if let Some(token) = token {
+        if token.chars().count() > MAX_TOKEN_CHARS || token.chars().any(char::is_whitespace) {
+            return Err(AppError::Validation("invalid GitLab token".into()));
+        }
+    }
+    secrets::set_secret(&token_secret(&base_url), token)
+}
+
+/// Whether a token is stored for a GitLab instance.
+#[tauri::command]
+pub fn has_gitlab_token(base_url: Option<String>) -> Result<bool, AppError> {
+    let base_url = parse_base_url(base_url.as_deref())?;
+    Ok(secrets::get_secret(&token_secret(&base_url))?.is_some())
+}
+
+/// Import the issues of a GitLab project (numeric id or `group/project`
+/// path) from gitlab.com or the self-hosted instance at `base_url`, with
+/// the token stored for that instance. Milestones go to the module field,
+/// labels pick the item type and are listed in the description with the
+/// due date (items have neither tags nor due dates), confidential issues
+/// are marked 🔒 and the state picks the section.
+///
+/// Each issue keeps its project id and IID as an external reference and its
+/// URL at the end of the description, so running the import again updates
+/// the items it created instead of duplicating them. Pages are followed
+/// through `X-Next-Page` and all fetched before anything is written; rate
+/// limits are waited out when they reset within a couple of minutes. An
+/// untrusted server certificate fails with `Certificate` unless
+/// `options.accept_invalid_certs` is set. Writes happen in one transaction
+/// and emit `import:progress`. `db_path` must be inside the data directory
+/// or fs scope.
+#[tauri::command]
+pub async fn gitlab_import(
+    db_path: String,
+    base_url: Option<String>,
+    project_id: String,
+    options: GitlabImportOptions,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<GitlabImportReport, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let base_url = parse_base_url(base_url.as_deref())?;
+    let project_id = project_id.trim().trim_matches('/');
+    if project_id.is_empty() {
+        return Err(AppError::Validation("missing GitLab project".into()));
+    }
+    let state = options.state.as_deref().unwrap_or("all");
+    if !matches!(state, "opened" | "closed" | "all") {
+        return Err(AppError::Validation(format!(
+            "invalid issue state: {}",
+            state
+        )));
+    }
+    if let Some(since) = &options.since {
+        since
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .map_err(|e| AppError::Validation(format!("invalid since: {}", e)))?;
+    }
+    let token = secrets::get_secret(&token_secret(&base_url))?.ok_or_else(|| {
+        AppError::Unauthorized(format!("no GitLab token is stored for {}", host(&base_url)))
+    })?;
+
+    let mut url = base_url.clone();
+    url.path_segments_mut()
+        .map_err(|_| AppError::Validation("invalid GitLab URL".into()))?
+        .pop_if_empty()
+        .extend(["api", "v4", "projects", project_id, "issues"]);
+
+    let mut report = GitlabImportReport {
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        created_ids: Vec::new(),
+        updated_ids: Vec::new(),
+        problems: Vec::new(),
+        new_sections: Vec::new(),
+        rate_limit_waits: 0,
+    };
+
+    let telemetry = app.state::<TelemetryState>();
+    let client = if options.accept_invalid_certs {
+        let proxy = telemetry::load_proxy_settings(&telemetry.pool()).await;
+        log::warn!(
+            "gitlab_import: TLS verification disabled for {}",
+            host(&base_url)
+        );
+        telemetry::http_client_builder(&proxy)
+            .danger_accept_invalid_certs(true)
+            .build()?
+    } else {
+        telemetry.client.read().await.clone()
+    };
+
+    let mut issues = Vec::new();
+    let mut page = "1".to_string();
+    for fetched in 1..=MAX_PAGES {
+        let mut query = vec![
+            ("state", state.to_string()),
+            ("order_by", "created_at".to_string()),
+            ("sort", "asc".to_string()),
+            ("per_page", PER_PAGE.to_string()),
+            ("page", page),
+        ];
+        if let Some(since) = &options.since {
+            query.push(("updated_after", since.clone()));
+        }
+        let (batch, next) =
+            fetch_page(&client, &token, &url, project_id, &query, &mut report).await?;
+        issues.extend(batch);
+        let Some(next) = next else {
+            break;
+        };
+        if fetched == MAX_PAGES {
+            return Err(AppError::Validation(format!(
+                "{} has more than {} issues",
+                project_id,
+                MAX_PAGES * PER_PAGE
+            )));
+        }
+        page = next;
+    }
+
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    import_issues(
+        &db,
+        &host(&base_url),
+        &issues,
+        &options,
+        &mut report,
+        |done, total| emit_progress(&app, done, total),
+    )
+    .await?;
+
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Write fetched issues into the project at `db_path` in one transaction,
+/// counting the outcome in `report` and calling `progress` with the issues
+/// written and the total. `host` prefixes the external keys.
+async fn import_issues(
+    db_path: &Path,
+    host: &str,
+    issues: &[GitlabIssue],
+    options: &GitlabImportOptions,
+    report: &mut GitlabImportReport,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<(), AppError> {
+    let (mut conn, context) = open_project(db_path, false, EXTERNAL_REFS_SCHEMA_VERSION).await?;
+
+    let default_type = match &options.default_type {
+        Some(item_type) => resolve_type(&context, item_type).map_err(AppError::Validation)?,
+        None => context
+            .types
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::Validation("project has no item types".into()))?,
+    };
+
+    let mut known: HashMap<String, String> = sqlx::query_as(
+        "SELECT external_key, item_id FROM item_external_refs
+         WHERE project_id = ? AND source = ?",
+    )
+    .bind(context.project_id)
+    .bind(GITLAB_SOURCE)
+    .fetch_all(&mut conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut pending = Vec::new();
+    for issue in issues {
+        if issue.confidential && options.skip_confidential {
+            report.skipped += 1;
+            continue;
+        }
+        match parse_issue(issue, options, &default_type, &context) {
+            Ok(row) => {
+                let key = format!("{}/{}#{}", host, issue.project_id, issue.iid);
+                pending.push((issue.iid, key, row));
+            }
+            Err(message) => {
+                report.skipped += 1;
+                report.problems.push(GitlabProblem {
+                    iid: issue.iid,
+                    message,
+                });
+            }
+        }
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+    for (index, (iid, key, row)) in pending.iter().enumerate() {
+        let sections = writer.sections.len();
+        match known.get(key) {
+            Some(_) if options.skip_existing => report.skipped += 1,
+            Some(id) => match writer.update_item(&mut tx, id, row).await? {
+                Some(true) => {
+                    report.updated += 1;
+                    report.updated_ids.push(id.clone());
+                }
+                Some(false) => report.skipped += 1,
+                None => {
+                    report.skipped += 1;
+                    report.problems.push(GitlabProblem {
+                        iid: *iid,
+                        message: format!("{} was archived or deleted; not updated", id),
+                    });
+                }
+            },
+            None => {
+                let id = writer.insert_item(&mut tx, row).await?;
+                sqlx::query(
+                    "INSERT INTO item_external_refs (project_id, item_id, source, external_key)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(context.project_id)
+                .bind(&id)
+                .bind(GITLAB_SOURCE)
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+                known.insert(key.clone(), id.clone());
+                report.created += 1;
+                report.created_ids.push(id);
+            }
+        }
+        if writer.sections.len() > sections {
+            report.new_sections.extend(row.status.clone());
+        }
+        progress(index + 1, pending.len());
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// `base_url` (gitlab.com by default) as an `https://` URL without query or
+/// fragment. A path is kept for instances served under one.
+fn parse_base_url(base_url: Option<&str>) -> Result<Url, AppError> {
+    let raw = base_url
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .unwrap_or(DEFAULT_BASE_URL);
+    let url =
+        Url::parse(raw).map_err(|e| AppError::Validation(format!("invalid GitLab URL: {}", e)))?;
+    if url.scheme() != "https" || url.host_str().is_none() {
+        return Err(AppError::Validation(format!(
+            "GitLab URL must be https: {}",
+            raw
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() || !url.username().is_empty() {
+        return Err(AppError::Validation(format!(
+            "GitLab URL must not carry credentials, a query or a fragment: {}",
+            raw
+        )));
+    }
+    Ok(url)
+}
+
+/// Host of an instance, with its port when not the default one.
+fn host(base_url: &Url) -> String {
+    let host = base_url.host_str().unwrap_or_default().to_ascii_lowercase();
+    match base_url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    }
+}
+
+fn token_secret(base_url: &Url) -> String {
+    format!("{}{}", GITLAB_TOKEN_SECRET, host(base_url))
+}
+
+/// One page of issues and the number of the next page (`X-Next-Page`,
+/// empty on the last one), retried with backoff on server and connection
+/// errors and after waiting out a rate limit.
+async fn fetch_page(
+    client: &reqwest::Client,
+    token: &str,
+    url: &Url,
+    project_id: &str,
+    query: &[(&str, String)],
+    report: &mut GitlabImportReport,
+) -> Result<(Vec<GitlabIssue>, Option<String>), AppError> {
+    let mut backoff = Duration::from_millis(RETRY_BACKOFF_MS);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let retry = attempts <= RETRIES;
+        let result = client
+            .get(url.clone())
+            .query(query)
+            .header("PRIVATE-TOKEN", token)
+            .header(
+                USER_AGENT,
+                concat!("ticketflow/", env!("CARGO_PKG_VERSION")),
+            )
+            .timeout(Duration::from_millis(NETWORK_TIMEOUT_MS))
+            .send()
+            .await;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) if telemetry::is_certificate_error(&e) => {
+                return Err(AppError::Certificate(format!(
+                    "{} presented a certificate that is not trusted ({}); import with \
+                     accept_invalid_certs to skip verification",
+                    host(url),
+                    e
+                )))
+            }
+            Err(e) if retry && (e.is_timeout() || e.is_connect()) => {
+                log::info!("gitlab_import: {}, retry {}/{}", e, attempts, RETRIES);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let status = response.status();
+        if status.is_success() {
+            let next = response
+                .headers()
+                .get("x-next-page")
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|page| !page.is_empty())
+                .map(str::to_string);
+            let issues = response
+                .json()
+                .await
+                .map_err(|e| AppError::Network(format!("invalid GitLab response: {}", e)))?;
+            return Ok((issues, next));
+        }
+        match status {
+            StatusCode::UNAUTHORIZED => {
+                return Err(AppError::Unauthorized("GitLab rejected the token".into()))
+            }
+            StatusCode::FORBIDDEN => {
+                return Err(AppError::Unauthorized(format!(
+                    "the token has no access to the issues of {}",
+                    project_id
+                )))
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(AppError::Validation(format!(
+                    "project {} not found, or not visible with this token",
+                    project_id
+                )))
+            }
+            StatusCode::TOO_MANY_REQUESTS => {
+                let wait = rate_limit_wait(response.headers()).unwrap_or(backoff);
+                if !retry || wait.as_secs() > MAX_RATE_LIMIT_WAIT_SECS {
+                    return Err(AppError::QuotaExceeded(format!(
+                        "GitLab rate limit reached; it resets in {} s",
+                        wait.as_secs()
+                    )));
+                }
+                log::info!("gitlab_import: rate limited, waiting {} s", wait.as_secs());
+                report.rate_limit_waits += 1;
+                tokio::time::sleep(wait).await;
+            }
+            _ if status.is_server_error() && retry => {
+                log::info!("gitlab_import: {}, retry {}/{}", status, attempts, RETRIES);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            _ => return Err(AppError::Network(format!("GitLab returned {}", status))),
+        }
+    }
+}
+
+/// How long a rate-limited response asks to wait: `Retry-After`, else until
+/// `RateLimit-Reset` when no requests remain. `None` when it says neither.
+fn rate_limit_wait(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    if let Some(seconds) = header(RETRY_AFTER.as_str()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header("ratelimit-remaining") != Some(0) {
+        return None;
+    }
+    let reset = header("ratelimit-reset")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // One more second so the request does not land just before the reset.
+    Some(Duration::from_secs(reset.saturating_sub(now) + 1))
+}
+
+/// Map one issue onto an item.
+fn parse_issue(
+    issue: &GitlabIssue,
+    options: &GitlabImportOptions,
+    default_type: &str,
+    context: &ProjectContext,
+) -> Result<ImportRow, String> {
+    let title = issue.title.trim().replace(['\r', '\n'], " ");
+    if title.is_empty() {
+        return Err("issue has no title".into());
+    }
+
+    let item_type = match issue
+        .labels
+        .iter()
+        .find_map(|label| options.labels.get(label))
+    {
+        Some(item_type) => resolve_type(context, item_type)?,
+        None => default_type.to_string(),
+    };
+
+    let status = options
+        .statuses
+        .get(&issue.state)
+        .cloned()
+        .unwrap_or_else(|| {
+            if issue.state == "closed" {
+                "Closed".to_string()
+            } else {
+                "Open".to_string()
+            }
+        });
+
+    let mut description = issue
+        .description
+        .as_deref()
+        .unwrap_or("")
+        .replace("\r\n", "\n")
+        .trim()
+        .to_string();
+    let mut details = Vec::new();
+    let labels: Vec<&str> = issue
+        .labels
+        .iter()
+        .map(|label| label.trim())
+        .filter(|label| !label.is_empty())
+        .collect();
+    if !labels.is_empty() {
+        details.push(format!("**Labels:** {}", labels.join(", ")));
+    }
+    if let Some(due_date) = issue.due_date.as_deref().filter(|d| !d.is_empty()) {
+        details.push(format!("**Due:** {}", due_date));
+    }
+    if !issue.web_url.is_empty() {
+        details.push(format!("GitLab: <{}>", issue.web_url));
+    }
+    for detail in details {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&detail);
+    }
+
+    Ok(ImportRow {
+        item_type,
+        title,
+        status: Some(status),
+        emoji: issue.confidential.then(|| CONFIDENTIAL_EMOJI.to_string()),
+        module: issue
+            .milestone
+            .as_ref()
+            .map(|milestone| milestone.title.trim().to_string())
+            .filter(|title| !title.is_empty()),
+        description: Some(description).filter(|d| !d.is_empty()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{json_response, serve};
+    use super::*;
+    use crate::project_db;
+    use crate::test_support::{items, project};
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn issues(value: serde_json::Value) -> Vec<GitlabIssue> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn board() -> Vec<GitlabIssue> {
+        issues(json!([
+            {
+                "iid": 1, "project_id": 42, "title": "Crash on\r\nsave", "state": "opened",
+                "description": "Steps\r\n", "labels": ["bug", "ui"],
+                "milestone": { "title": "v2" }, "due_date": "2024-06-01",
+                "web_url": "https://gitlab.example.com/g/p/-/issues/1"
+            },
+            { "iid": 2, "project_id": 42, "title": "Dark mode", "state": "closed",
+              "labels": ["feature"], "confidential": true },
+            { "iid": 3, "project_id": 42, "title": " ", "state": "opened" }
+        ]))
+    }
+
+    fn options() -> GitlabImportOptions {
+        GitlabImportOptions {
+            statuses: HashMap::from([("opened".to_string(), "Todo".to_string())]),
+            labels: HashMap::from([("feature".to_string(), "feat".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn report() -> GitlabImportReport {
+        GitlabImportReport {
+            created: 0,
+            updated: 0,
+            skipped: 0,
+            created_ids: Vec::new(),
+            updated_ids: Vec::new(),
+            problems: Vec::new(),
+            new_sections: Vec::new(),
+            rate_limit_waits: 0,
+        }
+    }
+
+    async fn import(
+        db_path: &Path,
+        issues: &[GitlabIssue],
+        options: &GitlabImportOptions,
+    ) -> GitlabImportReport {
+        let mut report = report();
+        import_issues(
+            db_path,
+            "gitlab.example.com",
+            issues,
+            options,
+            &mut report,
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+        report
+    }
+
+    #[tokio::test]
+    async fn issues_are_imported_with_their_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+
+        let report = import(&db_path, &board(), &options()).await;
+        assert_eq!(report.created_ids, ["BUG-002", "FEAT-001"]);
+        assert_eq!(report.new_sections, ["Closed"]);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.problems[0].iid, 3);
+        assert_eq!(report.problems[0].message, "issue has no title");
+
+        let items = items(&db_path).await;
+        assert_eq!(items[0].0, "BUG-002");
+        assert_eq!(items[0].1, "Todo");
+        assert_eq!(items[2].0, "FEAT-001");
+        assert_eq!(items[2].1, "Closed");
+
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let (title, module, description): (String, String, String) = sqlx::query_as(
+            "SELECT title, module, description FROM backlog_items WHERE id = 'BUG-002'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(title, "Crash on  save");
+        assert_eq!(module, "v2");
+        assert_eq!(
+            description,
+            "Steps\n\n**Labels:** bug, ui\n\n**Due:** 2024-06-01\n\n\
+             GitLab: <https://gitlab.example.com/g/p/-/issues/1>"
+        );
+        let emoji: Option<String> =
+            sqlx::query_scalar("SELECT emoji FROM backlog_items WHERE id = 'FEAT-001'")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(emoji.as_deref(), Some(CONFIDENTIAL_EMOJI));
+        let keys: Vec<String> =
+            sqlx::query_scalar("SELECT external_key FROM item_external_refs ORDER BY item_id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(keys, ["gitlab.example.com/42#1", "gitlab.example.com/42#2"]);
+    }
+
+    #[tokio::test]
+    async fn reimports_update_changed_issues_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        import(&db_path, &board(), &options()).await;
+
+        let mut changed = board();
+        changed[1].title = "Dark theme".into();
+        let report = import(&db_path, &changed, &options()).await;
+        assert_eq!(report.created, 0);
+        assert_eq!(report.updated_ids, ["FEAT-001"]);
+        // The unchanged issue and the one without a title.
+        assert_eq!(report.skipped, 2);
+
+        changed[1].title = "Dark mode".into();
+        let skip_existing = GitlabImportOptions {
+            skip_existing: true,
+            ..options()
+        };
+        let report = import(&db_path, &changed, &skip_existing).await;
+        assert_eq!((report.updated, report.skipped), (0, 3));
+        assert_eq!(items(&db_path).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn confidential_issues_can_be_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let options = GitlabImportOptions {
+            skip_confidential: true,
+            default_type: Some("feat".into()),
+            ..Default::default()
+        };
+
+        let report = import(&db_path, &board(), &options).await;
+        assert_eq!(report.created_ids, ["FEAT-001"]);
+        assert_eq!(report.new_sections, ["Open"]);
+        assert_eq!(report.skipped, 2);
+    }
+
+    #[test]
+    fn base_urls_must_be_plain_https() {
+        let url = parse_base_url(None).unwrap();
+        assert_eq!(url.as_str(), "https://gitlab.com/");
+        assert_eq!(token_secret(&url), "gitlab_token:gitlab.com");
+        let url = parse_base_url(Some(" https://Git.Example.com:8443/gitlab ")).unwrap();
+        assert_eq!(url.path(), "/gitlab");
+        assert_eq!(host(&url), "git.example.com:8443");
+
+        for raw in [
+            "http://gitlab.example.com",
+            "https://user@gitlab.example.com",
+            "https://gitlab.example.com/?private_token=x",
+            "gitlab.example.com",
+        ] {
+            let err = parse_base_url(Some(raw)).unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn rate_limit_wait_follows_the_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(rate_limit_wait(&headers), None);
+
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("0"));
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 30;
+        headers.insert("ratelimit-reset", HeaderValue::from(reset));
+        let wait = rate_limit_wait(&headers).unwrap().as_secs();
+        assert!((30..=31).contains(&wait), "{}", wait);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(rate_limit_wait(&headers), Some(Duration::from_secs(7)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("5"));
+        headers.insert("ratelimit-reset", HeaderValue::from(reset));
+        assert_eq!(rate_limit_wait(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn pages_are_fetched_with_the_token_and_rate_limits_waited_out() {
+        let page = r#"[{"iid": 1, "project_id": 42, "title": "Crash"}]"#;
+        let (server, requests) = serve(vec![
+            json_response("429 Too Many Requests", "retry-after: 0\r\n", "[]"),
+            json_response("200 OK", "x-next-page: 2\r\n", page),
+        ])
+        .await;
+        let url = Url::parse(&format!("{}/api/v4/projects/42/issues", server)).unwrap();
+        let query = [("page", "1".to_string())];
+        let mut report = report();
+
+        let (issues, next) = fetch_page(
+            &reqwest::Client::new(),
+            "glpat-secret",
+            &url,
+            "42",
+            &query,
+            &mut report,
+        )
+        .await
+        .unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(next.as_deref(), Some("2"));
+        assert_eq!(report.rate_limit_waits, 1);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /api/v4/projects/42/issues?page=1 "));
+        assert!(requests[1]
+            .to_ascii_lowercase()
+            .contains("private-token: glpat-secret"));
+    }
+
+    #[tokio::test]
+    async fn http_errors_are_mapped() {
+        for (status, check) in [
+            (
+                "401 Unauthorized",
+                (|e: &AppError| matches!(e, AppError::Unauthorized(m) if m.contains("token")))
+                    as fn(&AppError) -> bool,
+            ),
+            (
+                "403 Forbidden",
+                |e| matches!(e, AppError::Unauthorized(m) if m.contains("no access")),
+            ),
+            (
+                "404 Not Found",
+                |e| matches!(e, AppError::Validation(m) if m.contains("not found")),
+            ),
+            (
+                "400 Bad Request",
+                |e| matches!(e, AppError::Network(m) if m.contains("400")),
+            ),
+            (
+                "200 OK",
+                |e| matches!(e, AppError::Network(m) if m.contains("invalid GitLab response")),
+            ),
+        ] {
+            let (server, _) = serve(vec![json_response(status, "", "{}")]).await;
+            let url = Url::parse(&server).unwrap();
+            let err = fetch_page(&reqwest::Client::new(), "t", &url, "42", &[], &mut report())
+                .await
+                .unwrap_err();
+            assert!(check(&err), "{}: {:?}", status, err);
+        }
+    }
+}
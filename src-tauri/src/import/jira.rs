@@ -0,0 +1,790 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Connection, SqliteConnection};
+use tauri::AppHandle;
+
+use super::{
+    emit_progress, open_project, resolve_type, ImportRow, ItemWriter, EXTERNAL_REFS_SCHEMA_VERSION,
+};
+use crate::backup::MaintenanceState;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Refuse exports larger than this; they are parsed in memory.
+const MAX_EXPORT_BYTES: u64 = 200 * 1024 * 1024;
+
+/// `item_external_refs.source` of Jira issues.
+pub(super) const JIRA_SOURCE: &str = "jira";
+
+/// Jira priority names → item priority.
+const PRIORITIES: [(&str, &str); 5] = [
+    ("highest", "Haute"),
+    ("high", "Haute"),
+    ("medium", "Moyenne"),
+    ("low", "Faible"),
+    ("lowest", "Faible"),
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How Jira issues map onto items, as accepted by `import_jira`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct JiraImportMapping {
+    /// Jira status name or status category key (`new`, `indeterminate`,
+    /// `done`) → section title. Status names take precedence; unmapped
+    /// statuses become sections of the same name.
+    pub statuses: HashMap<String, String>,
+    /// Jira issue type name → item type.
+    pub types: HashMap<String, String>,
+    /// Item type for issue types missing from `types`. Defaults to the
+    /// project's first type.
+    pub default_type: Option<String>,
+}
+
+/// An issue that was imported.
+#[derive(Debug, Serialize)]
+pub struct JiraImported {
+    pub key: String,
+    pub id: String,
+}
+
+/// An issue that was not imported, or imported with a caveat.
+#[derive(Debug, Serialize)]
+pub struct JiraProblem {
+    /// Issue key, or `#<index>` when the issue has none.
+    pub key: String,
+    pub message: String,
+}
+
+/// Return value of `import_jira`.
+#[derive(Debug, Serialize)]
+pub struct JiraImportReport {
+    pub imported: Vec<JiraImported>,
+    /// Keys already imported by an earlier run.
+    pub already_imported: Vec<String>,
+    pub problems: Vec<JiraProblem>,
+    /// Statuses for which a section was created.
+    pub new_sections: Vec<String>,
+    /// Epic/parent links created as item relations.
+    pub links: usize,
+}
+
+/// Regexes used by `wiki_to_markdown`.
+struct WikiPatterns {
+    heading: Regex,
+    list: Regex,
+    code: Regex,
+    link: Regex,
+    bare_link: Regex,
+    mono: Regex,
+    bold: Regex,
+    italic: Regex,
+}
+
+/// An issue, validated and ready to insert.
+pub(super) struct JiraIssue {
+    pub(super) key: String,
+    pub(super) parent_key: Option<String>,
+    pub(super) row: ImportRow,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Import issues from a Jira JSON export (the REST search response,
+/// `{ "issues": [...] }`, or a bare array of issues).
+///
+/// Each issue keeps its key as an external reference, so running the import
+/// again skips issues already present. Descriptions are converted from Jira
+/// wiki markup (or Atlassian document format) to Markdown and comments are
+/// appended with their author and date. Epic and parent links become
+/// `related-to` relations. Invalid issues are listed in the report; the rest
+/// are written in one transaction. Emits `import:progress`.
+#[tauri::command]
+pub async fn import_jira(
+    db_path: String,
+    file_path: String,
+    mapping: JiraImportMapping,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<JiraImportReport, AppError> {
+    let file_path = files::validate_path(&app, Path::new(&file_path))?;
+    let size = std::fs::metadata(&file_path)?.len();
+    if size > MAX_EXPORT_BYTES {
+        return Err(AppError::Validation(format!(
+            "Jira export is too large ({} bytes, max {})",
+            size, MAX_EXPORT_BYTES
+        )));
+    }
+    let export: Value = serde_json::from_slice(&std::fs::read(&file_path)?)
+        .map_err(|e| AppError::Validation(format!("invalid Jira export: {}", e)))?;
+    let issues = match &export {
+        Value::Array(issues) => issues,
+        Value::Object(object) => match object.get("issues") {
+            Some(Value::Array(issues)) => issues,
+            _ => {
+                return Err(AppError::Validation(
+                    "Jira export has no issues array".into(),
+                ))
+            }
+        },
+        _ => {
+            return Err(AppError::Validation(
+                "Jira export must be an object or an array".into(),
+            ))
+        }
+    };
+
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    import_issues(Path::new(&db_path), issues, &mapping, |done, total| {
+        emit_progress(&app, done, total)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Write the issues of an export into the project at `db_path` in one
+/// transaction, calling `progress` with the issues written and the total.
+async fn import_issues(
+    db_path: &Path,
+    issues: &[Value],
+    mapping: &JiraImportMapping,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<JiraImportReport, AppError> {
+    let (mut conn, context) = open_project(db_path, false, EXTERNAL_REFS_SCHEMA_VERSION).await?;
+
+    let default_type = match &mapping.default_type {
+        Some(item_type) => resolve_type(&context, item_type).map_err(AppError::Validation)?,
+        None => context
+            .types
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::Validation("project has no item types".into()))?,
+    };
+
+    let mut known: HashMap<String, String> = sqlx::query_as(
+        "SELECT external_key, item_id FROM item_external_refs
+         WHERE project_id = ? AND source = ?",
+    )
+    .bind(context.project_id)
+    .bind(JIRA_SOURCE)
+    .fetch_all(&mut conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut report = JiraImportReport {
+        imported: Vec::new(),
+        already_imported: Vec::new(),
+        problems: Vec::new(),
+        new_sections: Vec::new(),
+        links: 0,
+    };
+
+    let mut pending = Vec::new();
+    for (index, issue) in issues.iter().enumerate() {
+        let key = str_at(issue, &["key"]).map(str::to_string);
+        let label = key.clone().unwrap_or_else(|| format!("#{}", index + 1));
+        match parse_issue(issue, mapping, &default_type, &context) {
+            Ok(parsed) if known.contains_key(&parsed.key) => {
+                report.already_imported.push(parsed.key);
+            }
+            Ok(parsed) => pending.push(parsed),
+            Err(message) => report.problems.push(JiraProblem {
+                key: label,
+                message,
+            }),
+        }
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+    for (index, issue) in pending.iter().enumerate() {
+        let sections = writer.sections.len();
+        let id = writer.insert_item(&mut tx, &issue.row).await?;
+        if writer.sections.len() > sections {
+            report.new_sections.extend(issue.row.status.clone());
+        }
+        sqlx::query(
+            "INSERT INTO item_external_refs (project_id, item_id, source, external_key)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(context.project_id)
+        .bind(&id)
+        .bind(JIRA_SOURCE)
+        .bind(&issue.key)
+        .execute(&mut *tx)
+        .await?;
+        known.insert(issue.key.clone(), id.clone());
+        report.imported.push(JiraImported {
+            key: issue.key.clone(),
+            id,
+        });
+        progress(index + 1, pending.len());
+    }
+
+    // Links last, so parents imported in this run resolve too.
+    for issue in &pending {
+        let Some(parent_key) = &issue.parent_key else {
+            continue;
+        };
+        let (Some(child), Some(parent)) = (known.get(&issue.key), known.get(parent_key)) else {
+            report.problems.push(JiraProblem {
+                key: issue.key.clone(),
+                message: format!("parent {} was not imported; link skipped", parent_key),
+            });
+            continue;
+        };
+        if insert_relation(&mut tx, context.project_id, child, parent).await? {
+            report.links += 1;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+/// Validate one issue of the export.
+pub(super) fn parse_issue(
+    issue: &Value,
+    mapping: &JiraImportMapping,
+    default_type: &str,
+    context: &super::ProjectContext,
+) -> Result<JiraIssue, String> {
+    let key = str_at(issue, &["key"])
+        .ok_or("issue has no key")?
+        .trim()
+        .to_string();
+    let fields = issue.get("fields").ok_or("issue has no fields")?;
+    let title = str_at(fields, &["summary"])
+        .map(|summary| summary.trim().replace(['\r', '\n'], " "))
+        .filter(|summary| !summary.is_empty())
+        .ok_or("issue has no summary")?;
+
+    let item_type =
+        match str_at(fields, &["issuetype", "name"]).and_then(|name| mapping.types.get(name)) {
+            Some(item_type) => resolve_type(context, item_type)?,
+            None => default_type.to_string(),
+        };
+
+    let status_name = str_at(fields, &["status", "name"]);
+    let category = str_at(fields, &["status", "statusCategory", "key"]);
+    let status = status_name
+        .and_then(|name| mapping.statuses.get(name))
+        .or_else(|| category.and_then(|key| mapping.statuses.get(key)))
+        .map(String::as_str)
+        .or(status_name)
+        .map(str::to_string);
+
+    let priority = str_at(fields, &["priority", "name"]).and_then(|name| {
+        PRIORITIES
+            .iter()
+            .find(|(jira, _)| jira.eq_ignore_ascii_case(name))
+            .map(|(_, priority)| *priority)
+    });
+
+    // Classic projects link stories to epics through a custom field (a
+    // bare key); next-gen projects and sub-tasks use `parent`.
+    let parent_key = str_at(fields, &["parent", "key"])
+        .or_else(|| str_at(fields, &["customfield_10014"]))
+        .or_else(|| str_at(fields, &["epic", "key"]))
+        .map(str::to_string);
+
+    let mut description = fields.get("description").map(jira_text).unwrap_or_default();
+    let comments = fields
+        .get("comment")
+        .and_then(|comment| comment.get("comments"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if !comments.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str("Comments:");
+        for comment in comments {
+            let author = str_at(comment, &["author", "displayName"]).unwrap_or("Unknown");
+            let created = str_at(comment, &["created"]).unwrap_or("");
+            let body = comment.get("body").map(jira_text).unwrap_or_default();
+            description.push_str(&format!("\n\n**{}** ({}):\n\n{}", author, created, body));
+        }
+    }
+
+    Ok(JiraIssue {
+        key,
+        parent_key,
+        row: ImportRow {
+            item_type,
+            title,
+            status,
+            priority,
+            component: fields
+                .get("components")
+                .and_then(Value::as_array)
+                .and_then(|components| components.first())
+                .and_then(|component| str_at(component, &["name"]))
+                .map(str::to_string),
+            description: Some(description).filter(|d| !d.is_empty()),
+            ..Default::default()
+        },
+    })
+}
+
+/// Record `child` as related to `parent`, unless either direction already
+/// exists. Returns whether a relation was created.
+pub(super) async fn insert_relation(
+    conn: &mut SqliteConnection,
+    project_id: i64,
+    child: &str,
+    parent: &str,
+) -> Result<bool, AppError> {
+    let exists: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM item_relations
+         WHERE relation_type = 'related-to'
+           AND ((source_id = ? AND target_id = ?) OR (source_id = ? AND target_id = ?))",
+    )
+    .bind(child)
+    .bind(parent)
+    .bind(parent)
+    .bind(child)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if exists.is_some() {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "INSERT INTO item_relations (project_id, source_id, target_id, relation_type, reason)
+         VALUES (?, ?, ?, 'related-to', 'Jira parent')",
+    )
+    .bind(project_id)
+    .bind(child)
+    .bind(parent)
+    .execute(&mut *conn)
+    .await?;
+    Ok(true)
+}
+
+/// String at a path of object keys.
+fn str_at<'a>(value: &'a Value, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(Value::as_str)
+}
+
+/// Markdown for a Jira rich-text field: wiki markup (REST v2) or an
+/// Atlassian document (REST v3).
+fn jira_text(value: &Value) -> String {
+    match value {
+        Value::String(markup) => wiki_to_markdown(markup),
+        Value::Object(_) => {
+            let mut out = String::new();
+            adf_to_markdown(value, &mut out);
+            out.trim().to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Best-effort conversion of Jira wiki markup to Markdown: headings, bold,
+/// italics, monospace, code/noformat blocks, quotes, lists and links.
+/// Anything else is kept as is.
+fn wiki_to_markdown(markup: &str) -> String {
+    let patterns = wiki_patterns();
+
+    let mut out = Vec::new();
+    let mut in_code = false;
+    for line in markup.replace("\r\n", "\n").lines() {
+        let trimmed = line.trim_start();
+        if let Some(code) = patterns.code.captures(trimmed) {
+            if in_code {
+                out.push("```".to_string());
+            } else {
+                let language = code.get(2).map_or("", |m| m.as_str().trim());
+                out.push(format!("```{}", language));
+            }
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let mut line = line.to_string();
+        if let Some(heading) = patterns.heading.captures(&line) {
+            let level: usize = heading[1].parse().unwrap_or(1);
+            let end = heading.get(0).map_or(0, |m| m.end());
+            line = format!("{} {}", "#".repeat(level), &line[end..]);
+        } else if let Some(rest) = line.strip_prefix("bq. ") {
+            line = format!("> {}", rest);
+        } else if let Some(list) = patterns.list.captures(&line) {
+            let marker = &list[1];
+            let end = list.get(0).map_or(0, |m| m.end());
+            let indent = "  ".repeat(marker.len() - 1);
+            let bullet = if marker.ends_with('#') { "1." } else { "-" };
+            line = format!("{}{} {}", indent, bullet, &line[end..]);
+        }
+
+        line = patterns.link.replace_all(&line, "[$1]($2)").into_owned();
+        line = patterns.bare_link.replace_all(&line, "<$1>").into_owned();
+        line = patterns.mono.replace_all(&line, "`$1`").into_owned();
+        line = patterns.bold.replace_all(&line, "$1**$2**").into_owned();
+        line = patterns.italic.replace_all(&line, "$1*$2*").into_owned();
+        out.push(line);
+    }
+    if in_code {
+        out.push("```".to_string());
+    }
+    out.join("\n").trim().to_string()
+}
+
+fn wiki_patterns() -> &'static WikiPatterns {
+    static PATTERNS: OnceLock<WikiPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| WikiPatterns {
+        heading: Regex::new(r"^h([1-6])\.\s+").unwrap(),
+        list: Regex::new(r"^([*#-]+)\s+").unwrap(),
+        code: Regex::new(r"^\{(code|noformat)(?::([^}|]*))?[^}]*\}").unwrap(),
+        link: Regex::new(r"\[([^\[\]|]+)\|([^\[\]]+)\]").unwrap(),
+        bare_link: Regex::new(r"\[((?:https?|mailto):[^\[\]|]+)\]").unwrap(),
+        mono: Regex::new(r"\{\{(.+?)\}\}").unwrap(),
+        bold: Regex::new(r"(^|[\s(])\*(\S(?:.*?\S)?)\*").unwrap(),
+        italic: Regex::new(r"(^|[\s(])_(\S(?:.*?\S)?)_").unwrap(),
+    })
+}
+
+/// Best-effort rendering of an Atlassian document node as Markdown.
+fn adf_to_markdown(node: &Value, out: &mut String) {
+    let children = |out: &mut String| {
+        for child in node
+            .get("content")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            adf_to_markdown(child, out);
+        }
+    };
+
+    match str_at(node, &["type"]).unwrap_or("") {
+        "text" => {
+            let text = str_at(node, &["text"]).unwrap_or("");
+            let marks: Vec<&str> = node
+                .get("marks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|mark| str_at(mark, &["type"]))
+                .collect();
+            let link = node
+                .get("marks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .find_map(|mark| str_at(mark, &["attrs", "href"]));
+            let mut text = text.to_string();
+            if marks.contains(&"code") {
+                text = format!("`{}`", text);
+            }
+            if marks.contains(&"strong") {
+                text = format!("**{}**", text);
+            }
+            if marks.contains(&"em") {
+                text = format!("*{}*", text);
+            }
+            if let Some(href) = link {
+                text = format!("[{}]({})", text, href);
+            }
+            out.push_str(&text);
+        }
+        "hardBreak" => out.push('\n'),
+        "heading" => {
+            let level = node
+                .get("attrs")
+                .and_then(|attrs| attrs.get("level"))
+                .and_then(Value::as_u64)
+                .unwrap_or(1)
+                .clamp(1, 6) as usize;
+            out.push_str(&"#".repeat(level));
+            out.push(' ');
+            children(out);
+            out.push_str("\n\n");
+        }
+        "paragraph" => {
+            children(out);
+            out.push_str("\n\n");
+        }
+        "listItem" => {
+            out.push_str("- ");
+            let mut item = String::new();
+            for child in node
+                .get("content")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                adf_to_markdown(child, &mut item);
+            }
+            out.push_str(item.trim());
+            out.push('\n');
+        }
+        "bulletList" | "orderedList" => {
+            children(out);
+            out.push('\n');
+        }
+        "codeBlock" => {
+            let language = str_at(node, &["attrs", "language"]).unwrap_or("");
+            out.push_str(&format!("```{}\n", language));
+            children(out);
+            out.push_str("\n```\n\n");
+        }
+        "blockquote" => {
+            let mut quote = String::new();
+            for child in node
+                .get("content")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                adf_to_markdown(child, &mut quote);
+            }
+            for line in quote.trim().lines() {
+                out.push_str("> ");
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        "rule" => out.push_str("---\n\n"),
+        "mention" => out.push_str(str_at(node, &["attrs", "text"]).unwrap_or("")),
+        _ => children(out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_db;
+    use crate::test_support::{items, project};
+    use serde_json::json;
+
+    fn issues() -> Vec<Value> {
+        serde_json::from_value(json!([
+            {
+                "key": "APP-2",
+                "fields": {
+                    "summary": "Login story",
+                    "issuetype": { "name": "Story" },
+                    "status": { "name": "In QA", "statusCategory": { "key": "indeterminate" } },
+                    "priority": { "name": "Highest" },
+                    "parent": { "key": "APP-1" },
+                    "components": [{ "name": "Auth" }, { "name": "UI" }],
+                    "description": "h2. Steps\n* one\n** *two*",
+                    "comment": { "comments": [
+                        { "author": { "displayName": "Ana" }, "created": "2024-05-01",
+                          "body": "Use {{token}}" }
+                    ] }
+                }
+            },
+            {
+                "key": "APP-1",
+                "fields": {
+                    "summary": "Epic\r\nrewrite",
+                    "status": { "name": "Done", "statusCategory": { "key": "done" } }
+                }
+            },
+            {
+                "key": "APP-3",
+                "fields": { "summary": "Orphan", "customfield_10014": "OLD-9" }
+            },
+            { "key": "APP-4", "fields": { "summary": " " } },
+            { "fields": { "summary": "No key" } }
+        ]))
+        .unwrap()
+    }
+
+    fn mapping() -> JiraImportMapping {
+        JiraImportMapping {
+            statuses: HashMap::from([
+                ("indeterminate".to_string(), "Todo".to_string()),
+                ("Done".to_string(), "Done".to_string()),
+            ]),
+            types: HashMap::from([("Story".to_string(), "feat".to_string())]),
+            default_type: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn issues_are_imported_once_with_parent_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+
+        let report = import_issues(&db_path, &issues(), &mapping(), |_, _| {})
+            .await
+            .unwrap();
+        let imported: Vec<(&str, &str)> = report
+            .imported
+            .iter()
+            .map(|i| (i.key.as_str(), i.id.as_str()))
+            .collect();
+        assert_eq!(
+            imported,
+            [
+                ("APP-2", "FEAT-001"),
+                ("APP-1", "BUG-002"),
+                ("APP-3", "BUG-003")
+            ]
+        );
+        assert_eq!(report.links, 1);
+        assert!(report.new_sections.is_empty());
+        let problems: Vec<(&str, &str)> = report
+            .problems
+            .iter()
+            .map(|p| (p.key.as_str(), p.message.as_str()))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                ("APP-4", "issue has no summary"),
+                ("#5", "issue has no key"),
+                ("APP-3", "parent OLD-9 was not imported; link skipped"),
+            ]
+        );
+
+        let sections: Vec<String> = items(&db_path).await.into_iter().map(|i| i.1).collect();
+        assert_eq!(sections, ["Done", "Todo", "Todo", "Todo"]);
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let relation: (String, String) =
+            sqlx::query_as("SELECT source_id, target_id FROM item_relations")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(relation, ("FEAT-001".to_string(), "BUG-002".to_string()));
+        drop(conn);
+
+        let again = import_issues(&db_path, &issues(), &mapping(), |_, _| {})
+            .await
+            .unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.already_imported, ["APP-2", "APP-1", "APP-3"]);
+        assert_eq!(again.links, 0);
+    }
+
+    #[tokio::test]
+    async fn unknown_statuses_become_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mapping = JiraImportMapping {
+            default_type: Some("FEAT".into()),
+            ..Default::default()
+        };
+
+        let report = import_issues(&db_path, &issues()[..1], &mapping, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(report.new_sections, ["In QA"]);
+        assert_eq!(report.imported[0].id, "FEAT-001");
+    }
+
+    #[test]
+    fn issue_fields_are_mapped() {
+        let context = super::super::ProjectContext {
+            project_id: 1,
+            types: vec!["BUG".into(), "FEAT".into()],
+            sections: Vec::new(),
+        };
+        let issue = parse_issue(&issues()[0], &mapping(), "BUG", &context).unwrap();
+        assert_eq!(issue.key, "APP-2");
+        assert_eq!(issue.parent_key.as_deref(), Some("APP-1"));
+        assert_eq!(issue.row.item_type, "FEAT");
+        assert_eq!(issue.row.status.as_deref(), Some("Todo"));
+        assert_eq!(issue.row.priority, Some("Haute"));
+        assert_eq!(issue.row.component.as_deref(), Some("Auth"));
+        assert_eq!(
+            issue.row.description.as_deref(),
+            Some(
+                "## Steps\n- one\n  - **two**\n\nComments:\n\n**Ana** (2024-05-01):\n\nUse `token`"
+            )
+        );
+
+        let unknown = JiraImportMapping {
+            types: HashMap::from([("Story".to_string(), "TASK".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_issue(&issues()[0], &unknown, "BUG", &context).err(),
+            Some("unknown type: TASK".to_string())
+        );
+    }
+
+    #[test]
+    fn wiki_markup_becomes_markdown() {
+        assert_eq!(
+            wiki_to_markdown(
+                "h1. Title\r\nbq. quoted\n# first\n## nested\n\
+                 see [docs|https://example.com] or [https://example.com/x]\n\
+                 _it_ and *bold* but not a*b*c\n{code:rust}\nlet *x* = 1;\n{code}\n{noformat}\nraw"
+            ),
+            "# Title\n> quoted\n1. first\n  1. nested\n\
+             see [docs](https://example.com) or <https://example.com/x>\n\
+             *it* and **bold** but not a*b*c\n```rust\nlet *x* = 1;\n```\n```\nraw\n```"
+        );
+    }
+
+    #[test]
+    fn atlassian_documents_become_markdown() {
+        let doc = json!({
+            "type": "doc",
+            "content": [
+                { "type": "heading", "attrs": { "level": 9 },
+                  "content": [{ "type": "text", "text": "Steps" }] },
+                { "type": "paragraph", "content": [
+                    { "type": "text", "text": "Run " },
+                    { "type": "text", "text": "npm", "marks": [{ "type": "code" }] },
+                    { "type": "hardBreak" },
+                    { "type": "text", "text": "see", "marks": [
+                        { "type": "strong" }, { "type": "link", "attrs": { "href": "https://x.io" } }
+                    ] },
+                    { "type": "mention", "attrs": { "text": " @ana" } }
+                ] },
+                { "type": "bulletList", "content": [
+                    { "type": "listItem", "content": [
+                        { "type": "paragraph", "content": [{ "type": "text", "text": "a" }] }
+                    ] }
+                ] },
+                { "type": "blockquote", "content": [
+                    { "type": "paragraph", "content": [{ "type": "text", "text": "q" }] }
+                ] },
+                { "type": "codeBlock", "attrs": { "language": "sh" },
+                  "content": [{ "type": "text", "text": "ls" }] },
+                { "type": "rule" }
+            ]
+        });
+        assert_eq!(
+            jira_text(&doc),
+            "###### Steps\n\nRun `npm`\n[**see**](https://x.io) @ana\n\n- a\n\n> q\n\n```sh\nls\n```\n\n---"
+        );
+        assert_eq!(jira_text(&Value::Null), "");
+    }
+}
@@ -0,0 +1,934 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use reqwest::header::{ACCEPT, RETRY_AFTER, USER_AGENT};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::Connection;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::jira::{insert_relation, parse_issue, JiraImportMapping, JiraProblem, JIRA_SOURCE};
+use super::{emit_progress, open_project, resolve_type, ItemWriter, EXTERNAL_REFS_SCHEMA_VERSION};
+use crate::backup::MaintenanceState;
+use crate::commands::NETWORK_TIMEOUT_MS;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::secrets;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Prefix of the secrets holding Jira credentials (`JiraCredentials` as
+/// JSON), followed by the site's host.
+const JIRA_CREDENTIALS_SECRET: &str = "jira_credentials:";
+
+/// Issue fields requested from the search API: the ones `parse_issue`
+/// reads.
+const SEARCH_FIELDS: &str =
+    "summary,description,status,issuetype,priority,components,parent,comment,customfield_10014";
+
+/// Issues per page, the API maximum.
+const PAGE_SIZE: usize = 100;
+
+/// Refuse JQL results with more issues than this.
+const MAX_ISSUES: usize = 20_000;
+
+/// Attempts after the first for a page that failed with a server error, a
+/// connection error or a rate limit.
+const RETRIES: u32 = 5;
+
+/// First retry delay when Jira does not say how long to wait; doubled after
+/// every attempt.
+const RETRY_BACKOFF_MS: u64 = 2_000;
+
+/// Longest `Retry-After` waited out before giving up.
+const MAX_RATE_LIMIT_WAIT_SECS: u64 = 120;
+
+const MAX_JQL_CHARS: usize = 4_000;
+
+/// Emitted with a `JiraFetchProgress` after every page, while issues are
+/// downloaded; `import:progress` follows while they are written.
+const JIRA_FETCH_PROGRESS_EVENT: &str = "jira:fetch-progress";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options accepted by `jira_import`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct JiraCloudImportOptions {
+    /// Status and type mapping, as for file imports.
+    pub mapping: JiraImportMapping,
+    /// Leave issues imported by an earlier run as they are instead of
+    /// updating them.
+    pub skip_existing: bool,
+    /// Count the matching issues and map the first page without writing.
+    pub dry_run: bool,
+}
+
+/// Email and API token of a Jira Cloud account.
+#[derive(Debug, Serialize, Deserialize)]
+struct JiraCredentials {
+    email: String,
+    token: String,
+}
+
+/// How an issue would be imported, listed by a dry run.
+#[derive(Debug, Serialize)]
+pub struct JiraPreview {
+    pub key: String,
+    pub title: String,
+    pub item_type: String,
+    /// Target section.
+    pub status: Option<String>,
+    pub priority: Option<&'static str>,
+    pub component: Option<String>,
+    /// Item an earlier import created for the issue, which would be updated.
+    pub existing_id: Option<String>,
+}
+
+/// Return value of `jira_import`.
+#[derive(Debug, Serialize)]
+pub struct JiraCloudImportReport {
+    pub dry_run: bool,
+    /// Issues matching the JQL (an estimate for dry runs).
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    /// Issues unchanged since the last import (or already imported with
+    /// `skip_existing`) and issues listed in `problems`.
+    pub skipped: usize,
+    pub created_ids: Vec<String>,
+    pub updated_ids: Vec<String>,
+    pub problems: Vec<JiraProblem>,
+    /// Statuses for which a section was created.
+    pub new_sections: Vec<String>,
+    /// Epic/parent links created as item relations.
+    pub links: usize,
+    /// Times the import waited because Jira rate limited it.
+    pub rate_limit_waits: u32,
+    /// Mapping of the first page of issues; dry runs only.
+    pub preview: Vec<JiraPreview>,
+}
+
+/// Payload of `jira:fetch-progress`.
+#[derive(Debug, Clone, Serialize)]
+struct JiraFetchProgress {
+    fetched: usize,
+    /// Approximate number of matching issues, when Jira reported it.
+    total: Option<usize>,
+}
+
+/// A page of `POST /rest/api/3/search/jql`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    #[serde(default)]
+    issues: Vec<Value>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Save the email and API token used with a Jira Cloud site in the OS
+/// keyring, or forget them when `token` is `None` or empty.
+#[tauri::command]
+pub fn set_jira_credentials(
+    site_url: String,
+    email: String,
+    token: Option<String>,
+) -> Result<(), AppError> {
+    let site = parse_site_url(&site_url)?;
+    let secret = credentials_secret(&site);
+    let Some(token) = token.as_deref().map(str::trim).filter(|t| !t.is_empty()) else {
+        return secrets::set_secret(&secret, None);
+    };
+    let email = email.trim();
+    if !email.contains('@') || email.chars().any(char::is_whitespace) {
+        return Err(AppError::Validation(format!("invalid email: {}", email)));
+    }
+    if token.chars().any(char::is_whitespace) {
+        return Err(AppError::Validation("invalid Jira API token".into()));
+    }
+    let json = serde_json::to_string(&JiraCredentials {
+        email: email.to_string(),
+        token: token.to_string(),
+    })
+    .map_err(|e| AppError::Validation(format!("invalid credentials: {}", e)))?;
+    secrets::set_secret(&secret, Some(&json))
+}
+
+/// Whether credentials are stored for a Jira Cloud site.
+#[tauri::command]
+pub fn has_jira_credentials(site_url: String) -> Result<bool, AppError> {
+    let site = parse_site_url(&site_url)?;
+    Ok(secrets::get_secret(&credentials_secret(&site))?.is_some())
+}
+
+/// Import the issues a JQL query matches on a Jira Cloud site, with the
+/// credentials stored for it. Issues map onto items as in `import_jira`:
+/// Atlassian document descriptions become Markdown, statuses (or their
+/// category) pick the section through `options.mapping`, and epic and
+/// parent links become relations.
+///
+/// Each issue keeps its key as an external reference, so running the
+/// import again updates the items it created. Every page is fetched before
+/// anything is written, emitting `jira:fetch-progress`; rate limits
+/// (`Retry-After`) are waited out. A dry run only counts the matching
+/// issues and maps the first page. `db_path` must be inside the data
+/// directory or fs scope.
+#[tauri::command]
+pub async fn jira_import(
+    db_path: String,
+    site_url: String,
+    jql: String,
+    options: JiraCloudImportOptions,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<JiraCloudImportReport, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let site = parse_site_url(&site_url)?;
+    let jql = jql.trim();
+    if jql.is_empty() || jql.chars().count() > MAX_JQL_CHARS {
+        return Err(AppError::Validation(format!(
+            "JQL must be 1 to {} characters",
+            MAX_JQL_CHARS
+        )));
+    }
+    let credentials: JiraCredentials = secrets::get_secret(&credentials_secret(&site))?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or_else(|| {
+            AppError::Unauthorized(format!(
+                "no Jira credentials are stored for {}",
+                site.host_str().unwrap_or_default()
+            ))
+        })?;
+
+    let mut report = JiraCloudImportReport {
+        dry_run: options.dry_run,
+        total: 0,
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        created_ids: Vec::new(),
+        updated_ids: Vec::new(),
+        problems: Vec::new(),
+        new_sections: Vec::new(),
+        links: 0,
+        rate_limit_waits: 0,
+        preview: Vec::new(),
+    };
+
+    let client = app.state::<TelemetryState>().client.read().await.clone();
+    let jira = JiraClient {
+        client: &client,
+        site: &site,
+        credentials: &credentials,
+    };
+    let issues = fetch_issues(
+        &jira,
+        jql,
+        options.dry_run,
+        &mut report,
+        |fetched, total| {
+            app.emit(
+                JIRA_FETCH_PROGRESS_EVENT,
+                JiraFetchProgress { fetched, total },
+            )
+            .ok();
+        },
+    )
+    .await?;
+
+    // Dry runs only read, so they neither wait for nor block maintenance.
+    let _guard = if options.dry_run {
+        None
+    } else {
+        Some(
+            maintenance
+                .lock
+                .try_lock()
+                .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?,
+        )
+    };
+    let _paused = (!options.dry_run).then(|| fs_watch::pause_project_watch(&app));
+    import_issues(&db, &issues, &options, &mut report, |done, total| {
+        emit_progress(&app, done, total)
+    })
+    .await?;
+
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Every issue matching `jql`, page by page, calling `on_page` with the
+/// issues fetched so far and the estimated total. Dry runs stop after the
+/// first page. Sets `report.total`.
+async fn fetch_issues(
+    jira: &JiraClient<'_>,
+    jql: &str,
+    dry_run: bool,
+    report: &mut JiraCloudImportReport,
+    mut on_page: impl FnMut(usize, Option<usize>) + Send,
+) -> Result<Vec<Value>, AppError> {
+    let estimate = jira.approximate_count(jql, report).await?;
+
+    let mut issues = Vec::new();
+    let mut next_page_token = None;
+    loop {
+        let page = jira.search(jql, next_page_token.as_deref(), report).await?;
+        issues.extend(page.issues);
+        on_page(issues.len(), estimate);
+        next_page_token = page.next_page_token.filter(|token| !token.is_empty());
+        if dry_run || next_page_token.is_none() {
+            break;
+        }
+        if issues.len() >= MAX_ISSUES {
+            return Err(AppError::Validation(format!(
+                "the query matches more than {} issues; narrow the JQL",
+                MAX_ISSUES
+            )));
+        }
+    }
+    report.total = if dry_run {
+        estimate.unwrap_or(issues.len()).max(issues.len())
+    } else {
+        issues.len()
+    };
+
+    Ok(issues)
+}
+
+/// Write fetched issues into the project at `db_path` in one transaction,
+/// counting the outcome in `report` and calling `progress` with the issues
+/// written and the total. A dry run only fills `report.preview`.
+async fn import_issues(
+    db_path: &Path,
+    issues: &[Value],
+    options: &JiraCloudImportOptions,
+    report: &mut JiraCloudImportReport,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<(), AppError> {
+    let (mut conn, context) =
+        open_project(db_path, options.dry_run, EXTERNAL_REFS_SCHEMA_VERSION).await?;
+
+    let default_type = match &options.mapping.default_type {
+        Some(item_type) => resolve_type(&context, item_type).map_err(AppError::Validation)?,
+        None => context
+            .types
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::Validation("project has no item types".into()))?,
+    };
+
+    let mut known: HashMap<String, String> = sqlx::query_as(
+        "SELECT external_key, item_id FROM item_external_refs
+         WHERE project_id = ? AND source = ?",
+    )
+    .bind(context.project_id)
+    .bind(JIRA_SOURCE)
+    .fetch_all(&mut conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut pending = Vec::new();
+    for (index, issue) in issues.iter().enumerate() {
+        match parse_issue(issue, &options.mapping, &default_type, &context) {
+            Ok(parsed) => pending.push(parsed),
+            Err(message) => {
+                report.skipped += 1;
+                report.problems.push(JiraProblem {
+                    key: issue
+                        .get("key")
+                        .and_then(Value::as_str)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("#{}", index + 1)),
+                    message,
+                });
+            }
+        }
+    }
+
+    if options.dry_run {
+        report.preview = pending
+            .into_iter()
+            .map(|issue| JiraPreview {
+                existing_id: known.get(&issue.key).cloned(),
+                key: issue.key,
+                title: issue.row.title,
+                item_type: issue.row.item_type,
+                status: issue.row.status,
+                priority: issue.row.priority,
+                component: issue.row.component,
+            })
+            .collect();
+        return Ok(());
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+    for (index, issue) in pending.iter().enumerate() {
+        let sections = writer.sections.len();
+        match known.get(&issue.key) {
+            Some(_) if options.skip_existing => report.skipped += 1,
+            Some(id) => match writer.update_item(&mut tx, id, &issue.row).await? {
+                Some(true) => {
+                    report.updated += 1;
+                    report.updated_ids.push(id.clone());
+                }
+                Some(false) => report.skipped += 1,
+                None => {
+                    report.skipped += 1;
+                    report.problems.push(JiraProblem {
+                        key: issue.key.clone(),
+                        message: format!("{} was archived or deleted; not updated", id),
+                    });
+                }
+            },
+            None => {
+                let id = writer.insert_item(&mut tx, &issue.row).await?;
+                sqlx::query(
+                    "INSERT INTO item_external_refs (project_id, item_id, source, external_key)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(context.project_id)
+                .bind(&id)
+                .bind(JIRA_SOURCE)
+                .bind(&issue.key)
+                .execute(&mut *tx)
+                .await?;
+                known.insert(issue.key.clone(), id.clone());
+                report.created += 1;
+                report.created_ids.push(id);
+            }
+        }
+        if writer.sections.len() > sections {
+            report.new_sections.extend(issue.row.status.clone());
+        }
+        progress(index + 1, pending.len());
+    }
+
+    // Links last, so parents imported in this run resolve too. Parents
+    // outside the JQL results are linked when an earlier run imported them.
+    for issue in &pending {
+        let Some(parent_key) = &issue.parent_key else {
+            continue;
+        };
+        let (Some(child), Some(parent)) = (known.get(&issue.key), known.get(parent_key)) else {
+            continue;
+        };
+        if insert_relation(&mut tx, context.project_id, child, parent).await? {
+            report.links += 1;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The REST API of one site, called with one account.
+struct JiraClient<'a> {
+    client: &'a reqwest::Client,
+    site: &'a Url,
+    credentials: &'a JiraCredentials,
+}
+
+impl JiraClient<'_> {
+    /// One page of issues matching `jql`.
+    async fn search(
+        &self,
+        jql: &str,
+        next_page_token: Option<&str>,
+        report: &mut JiraCloudImportReport,
+    ) -> Result<SearchPage, AppError> {
+        let mut body = json!({
+            "jql": jql,
+            "maxResults": PAGE_SIZE,
+            "fields": SEARCH_FIELDS.split(',').collect::<Vec<_>>(),
+        });
+        if let Some(token) = next_page_token {
+            body["nextPageToken"] = Value::from(token);
+        }
+        let response = self.post("search/jql", &body, report).await?;
+        serde_json::from_value(response)
+            .map_err(|e| AppError::Network(format!("invalid Jira response: {}", e)))
+    }
+
+    /// Approximate number of issues matching `jql`; `None` when the site
+    /// does not offer the estimate.
+    async fn approximate_count(
+        &self,
+        jql: &str,
+        report: &mut JiraCloudImportReport,
+    ) -> Result<Option<usize>, AppError> {
+        match self
+            .post("search/approximate-count", &json!({ "jql": jql }), report)
+            .await
+        {
+            Ok(response) => Ok(response
+                .get("count")
+                .and_then(Value::as_u64)
+                .map(|count| count as usize)),
+            Err(AppError::Network(message)) => {
+                log::info!("jira_import: no issue count: {}", message);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// POST to `/rest/api/3/<path>`, retried with backoff on server and
+    /// connection errors and after the wait a rate limit asks for.
+    async fn post(
+        &self,
+        path: &str,
+        body: &Value,
+        report: &mut JiraCloudImportReport,
+    ) -> Result<Value, AppError> {
+        let url = self
+            .site
+            .join(&format!("rest/api/3/{}", path))
+            .map_err(|e| AppError::Validation(format!("invalid Jira URL: {}", e)))?;
+        let mut backoff = Duration::from_millis(RETRY_BACKOFF_MS);
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let retry = attempts <= RETRIES;
+            let result = self
+                .client
+                .post(url.clone())
+                .basic_auth(&self.credentials.email, Some(&self.credentials.token))
+                .header(ACCEPT, "application/json")
+                .header(
+                    USER_AGENT,
+                    concat!("ticketflow/", env!("CARGO_PKG_VERSION")),
+                )
+                .json(body)
+                .timeout(Duration::from_millis(NETWORK_TIMEOUT_MS))
+                .send()
+                .await;
+            let response = match result {
+                Ok(response) => response,
+                Err(e) if retry && (e.is_timeout() || e.is_connect()) => {
+                    log::info!("jira_import: {}, retry {}/{}", e, attempts, RETRIES);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let status = response.status();
+            if status.is_success() {
+                return response
+                    .json()
+                    .await
+                    .map_err(|e| AppError::Network(format!("invalid Jira response: {}", e)));
+            }
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            match status {
+                StatusCode::UNAUTHORIZED => {
+                    return Err(AppError::Unauthorized(
+                        "Jira rejected the email or API token".into(),
+                    ))
+                }
+                StatusCode::FORBIDDEN => {
+                    return Err(AppError::Unauthorized(
+                        "the account may not search issues on this site".into(),
+                    ))
+                }
+                StatusCode::BAD_REQUEST => {
+                    let body: Value = response.json().await.unwrap_or_default();
+                    let messages: Vec<&str> = body
+                        .get("errorMessages")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect();
+                    return Err(AppError::Validation(format!(
+                        "Jira refused the query: {}",
+                        if messages.is_empty() {
+                            "bad request".to_string()
+                        } else {
+                            messages.join("; ")
+                        }
+                    )));
+                }
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE if retry => {
+                    let wait = retry_after.unwrap_or(backoff);
+                    if wait.as_secs() > MAX_RATE_LIMIT_WAIT_SECS {
+                        return Err(AppError::QuotaExceeded(format!(
+                            "Jira rate limit reached; retry in {} s",
+                            wait.as_secs()
+                        )));
+                    }
+                    log::info!("jira_import: rate limited, waiting {} s", wait.as_secs());
+                    report.rate_limit_waits += 1;
+                    tokio::time::sleep(wait).await;
+                    backoff *= 2;
+                }
+                StatusCode::TOO_MANY_REQUESTS => {
+                    return Err(AppError::QuotaExceeded("Jira rate limit reached".into()))
+                }
+                _ if status.is_server_error() && retry => {
+                    log::info!("jira_import: {}, retry {}/{}", status, attempts, RETRIES);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                _ => return Err(AppError::Network(format!("Jira returned {}", status))),
+            }
+        }
+    }
+}
+
+/// `site_url` as an `https://` origin with a trailing slash, the base the
+/// REST paths are joined to.
+fn parse_site_url(site_url: &str) -> Result<Url, AppError> {
+    let url = Url::parse(site_url.trim())
+        .map_err(|e| AppError::Validation(format!("invalid Jira URL: {}", e)))?;
+    if url.scheme() != "https" || url.host_str().is_none() || !url.username().is_empty() {
+        return Err(AppError::Validation(format!(
+            "Jira URL must be an https site: {}",
+            site_url
+        )));
+    }
+    let mut site = url;
+    site.set_path("/");
+    site.set_query(None);
+    site.set_fragment(None);
+    Ok(site)
+}
+
+fn credentials_secret(site: &Url) -> String {
+    format!(
+        "{}{}",
+        JIRA_CREDENTIALS_SECRET,
+        site.host_str().unwrap_or_default().to_ascii_lowercase()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{json_response, serve};
+    use super::*;
+    use crate::project_db;
+    use crate::test_support::{items, project};
+
+    fn issue(key: &str, summary: &str, status: &str, parent: Option<&str>) -> Value {
+        let mut issue = json!({
+            "key": key,
+            "fields": {
+                "summary": summary,
+                "issuetype": { "name": "Story" },
+                "status": { "name": status, "statusCategory": { "key": "indeterminate" } }
+            }
+        });
+        if let Some(parent) = parent {
+            issue["fields"]["parent"] = json!({ "key": parent });
+        }
+        issue
+    }
+
+    fn options() -> JiraCloudImportOptions {
+        JiraCloudImportOptions {
+            mapping: JiraImportMapping {
+                statuses: HashMap::from([("indeterminate".to_string(), "Todo".to_string())]),
+                types: HashMap::from([("Story".to_string(), "feat".to_string())]),
+                default_type: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn report(dry_run: bool) -> JiraCloudImportReport {
+        JiraCloudImportReport {
+            dry_run,
+            total: 0,
+            created: 0,
+            updated: 0,
+            skipped: 0,
+            created_ids: Vec::new(),
+            updated_ids: Vec::new(),
+            problems: Vec::new(),
+            new_sections: Vec::new(),
+            links: 0,
+            rate_limit_waits: 0,
+            preview: Vec::new(),
+        }
+    }
+
+    fn credentials() -> JiraCredentials {
+        JiraCredentials {
+            email: "ana@example.com".into(),
+            token: "secret".into(),
+        }
+    }
+
+    fn page(issues: &[Value], next: Option<&str>) -> String {
+        let mut body = json!({ "issues": issues });
+        if let Some(next) = next {
+            body["nextPageToken"] = json!(next);
+        }
+        json_response("200 OK", "", &body.to_string())
+    }
+
+    async fn import(
+        db_path: &Path,
+        issues: &[Value],
+        options: &JiraCloudImportOptions,
+    ) -> JiraCloudImportReport {
+        let mut report = report(options.dry_run);
+        import_issues(db_path, issues, options, &mut report, |_, _| {})
+            .await
+            .unwrap();
+        report
+    }
+
+    #[tokio::test]
+    async fn pages_are_followed_with_their_token() {
+        let (server, requests) = serve(vec![
+            json_response("200 OK", "", r#"{"count": 3}"#),
+            json_response("429 Too Many Requests", "retry-after: 0\r\n", "{}"),
+            page(&[issue("APP-1", "One", "To Do", None)], Some("p2")),
+            page(
+                &[
+                    issue("APP-2", "Two", "To Do", None),
+                    issue("APP-3", "Three", "To Do", None),
+                ],
+                None,
+            ),
+        ])
+        .await;
+        let site = Url::parse(&server).unwrap();
+        let credentials = credentials();
+        let client = reqwest::Client::new();
+        let jira = JiraClient {
+            client: &client,
+            site: &site,
+            credentials: &credentials,
+        };
+        let mut report = report(false);
+        let mut pages = Vec::new();
+
+        let issues = fetch_issues(
+            &jira,
+            "project = APP",
+            false,
+            &mut report,
+            |fetched, total| pages.push((fetched, total)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(issues.len(), 3);
+        assert_eq!(report.total, 3);
+        assert_eq!(report.rate_limit_waits, 1);
+        assert_eq!(pages, [(1, Some(3)), (3, Some(3))]);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /rest/api/3/search/approximate-count "));
+        assert!(requests[3].starts_with("POST /rest/api/3/search/jql "));
+        assert!(requests[3]
+            .to_ascii_lowercase()
+            .contains("authorization: basic "));
+        assert!(requests[3].contains(r#""nextPageToken":"p2""#));
+        assert!(requests[3].contains(r#""jql":"project = APP""#));
+    }
+
+    #[tokio::test]
+    async fn dry_runs_fetch_one_page_without_a_count() {
+        let (server, requests) = serve(vec![
+            json_response("404 Not Found", "", "{}"),
+            page(
+                &[
+                    issue("APP-1", "One", "To Do", None),
+                    issue("APP-2", "Two", "To Do", None),
+                ],
+                Some("p2"),
+            ),
+        ])
+        .await;
+        let site = Url::parse(&server).unwrap();
+        let credentials = credentials();
+        let client = reqwest::Client::new();
+        let jira = JiraClient {
+            client: &client,
+            site: &site,
+            credentials: &credentials,
+        };
+        let mut report = report(true);
+
+        let issues = fetch_issues(&jira, "project = APP", true, &mut report, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(report.total, 2);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn http_errors_are_mapped() {
+        for (response, check) in [
+            (
+                json_response("401 Unauthorized", "", "{}"),
+                (|e: &AppError| matches!(e, AppError::Unauthorized(m) if m.contains("API token")))
+                    as fn(&AppError) -> bool,
+            ),
+            (
+                json_response("403 Forbidden", "", "{}"),
+                |e| matches!(e, AppError::Unauthorized(m) if m.contains("may not search")),
+            ),
+            (
+                json_response(
+                    "400 Bad Request",
+                    "",
+                    r#"{"errorMessages": ["Field 'x' does not exist", "Bad JQL"]}"#,
+                ),
+                |e| {
+                    matches!(e, AppError::Validation(m)
+                        if m == "Jira refused the query: Field 'x' does not exist; Bad JQL")
+                },
+            ),
+            (
+                json_response("429 Too Many Requests", "retry-after: 600\r\n", "{}"),
+                |e| matches!(e, AppError::QuotaExceeded(m) if m.contains("600 s")),
+            ),
+            (
+                json_response("200 OK", "", "not json"),
+                |e| matches!(e, AppError::Network(m) if m.contains("invalid Jira response")),
+            ),
+        ] {
+            let (server, _) = serve(vec![response]).await;
+            let site = Url::parse(&server).unwrap();
+            let credentials = credentials();
+            let client = reqwest::Client::new();
+            let jira = JiraClient {
+                client: &client,
+                site: &site,
+                credentials: &credentials,
+            };
+            let err = jira
+                .search("x", None, &mut report(false))
+                .await
+                .unwrap_err();
+            assert!(check(&err), "{:?}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn issues_are_imported_once_with_parent_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let issues = [
+            issue("APP-2", "Login", "To Do", Some("APP-1")),
+            issue("APP-1", "Epic", "To Do", None),
+            issue("APP-3", " ", "To Do", None),
+        ];
+
+        let report = import(&db_path, &issues, &options()).await;
+        assert_eq!(report.created_ids, ["FEAT-001", "FEAT-002"]);
+        assert_eq!(report.links, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.problems[0].key, "APP-3");
+        assert!(items(&db_path)
+            .await
+            .iter()
+            .all(|(_, section, _)| section == "Todo"));
+
+        let mut changed = issues.clone();
+        changed[1]["fields"]["summary"] = json!("Epic v2");
+        let report = import(&db_path, &changed, &options()).await;
+        assert_eq!(report.updated_ids, ["FEAT-002"]);
+        assert_eq!((report.created, report.links), (0, 0));
+
+        let skip_existing = JiraCloudImportOptions {
+            skip_existing: true,
+            ..options()
+        };
+        let report = import(&db_path, &issues, &skip_existing).await;
+        assert_eq!((report.updated, report.skipped), (0, 3));
+    }
+
+    #[tokio::test]
+    async fn dry_runs_preview_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        import(
+            &db_path,
+            &[issue("APP-1", "Epic", "To Do", None)],
+            &options(),
+        )
+        .await;
+
+        let dry_run = JiraCloudImportOptions {
+            dry_run: true,
+            ..options()
+        };
+        let issues = [
+            issue("APP-1", "Epic", "To Do", None),
+            issue("APP-2", "Login", "Blocked", None),
+        ];
+        let report = import(&db_path, &issues, &dry_run).await;
+        assert_eq!(report.created, 0);
+        let preview: Vec<(&str, &str, Option<&str>, Option<&str>)> = report
+            .preview
+            .iter()
+            .map(|p| {
+                (
+                    p.key.as_str(),
+                    p.item_type.as_str(),
+                    p.status.as_deref(),
+                    p.existing_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            preview,
+            [
+                ("APP-1", "FEAT", Some("Todo"), Some("FEAT-001")),
+                ("APP-2", "FEAT", Some("Todo"), None),
+            ]
+        );
+        assert_eq!(items(&db_path).await.len(), 2);
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let refs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM item_external_refs")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(refs, 1);
+    }
+
+    #[test]
+    fn site_urls_are_reduced_to_https_origins() {
+        let site = parse_site_url(" https://Acme.atlassian.net/jira/software?x=1#top ").unwrap();
+        assert_eq!(site.as_str(), "https://acme.atlassian.net/");
+        assert_eq!(
+            credentials_secret(&site),
+            "jira_credentials:acme.atlassian.net"
+        );
+        for raw in [
+            "http://acme.atlassian.net",
+            "https://ana@acme.atlassian.net",
+            "acme.atlassian.net",
+        ] {
+            assert!(parse_site_url(raw).is_err(), "{}", raw);
+        }
+    }
+}
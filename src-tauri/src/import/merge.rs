@@ -0,0 +1,790 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, SqliteConnection};
+use tauri::AppHandle;
+
+use super::{
+    emit_progress, find_section, load_context, open_project, ItemWriter, MIN_SCHEMA_VERSION,
+};
+use crate::backup::MaintenanceState;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Screenshot folder, relative to the project directory (see
+/// `src/lib/screenshots.ts`).
+const SCREENSHOTS_DIR: &str = ".backlog-assets/screenshots";
+
+/// Longest `MergeOptions::number_prefix`.
+const MAX_NUMBER_PREFIX_DIGITS: usize = 4;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options of `merge_projects`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    /// Digits put before the number of every source ticket (`BUG-012`
+    /// becomes `BUG-7012` with `"7"`), so merged tickets stay recognizable.
+    /// The target's counters are raised past the new numbers. Without it,
+    /// tickets get the next free numbers of the target.
+    pub number_prefix: Option<String>,
+    /// Leave out the source's in-app archive (`archived_items`).
+    pub skip_archived: bool,
+    pub dry_run: bool,
+}
+
+/// Return value of `merge_projects`.
+#[derive(Debug, Serialize)]
+pub struct MergeReport {
+    pub dry_run: bool,
+    /// Source ticket id → id in the target.
+    pub id_map: BTreeMap<String, String>,
+    /// Sections (by title) and item types (by id) the target did not have.
+    pub new_sections: Vec<String>,
+    pub new_types: Vec<String>,
+    pub relations: u64,
+    pub screenshots_copied: usize,
+    /// Screenshot files referenced by source tickets but not found.
+    pub missing_screenshots: Vec<String>,
+}
+
+/// A `backlog_items` or `archived_items` row. Columns the table does not
+/// have are selected as `NULL`.
+#[derive(Debug, sqlx::FromRow)]
+struct ItemRow {
+    /// Whether the row comes from `archived_items`.
+    archived: bool,
+    id: String,
+    section: Option<String>,
+    #[sqlx(rename = "type")]
+    item_type: String,
+    title: String,
+    emoji: Option<String>,
+    component: Option<String>,
+    module: Option<String>,
+    severity: Option<String>,
+    priority: Option<String>,
+    effort: Option<String>,
+    description: Option<String>,
+    user_story: Option<String>,
+    specs: Option<String>,
+    reproduction: Option<String>,
+    criteria: Option<String>,
+    dependencies: Option<String>,
+    constraints: Option<String>,
+    screens: Option<String>,
+    screenshots: Option<String>,
+    raw_markdown: String,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    archived_at: Option<String>,
+    original_created_at: Option<String>,
+}
+
+/// A row of `item_relations`.
+#[derive(Debug, sqlx::FromRow)]
+struct RelationRow {
+    source_id: String,
+    target_id: String,
+    relation_type: String,
+    confidence: Option<f64>,
+    reason: Option<String>,
+}
+
+/// A screenshot file to copy once the merge is committed.
+struct ScreenshotCopy {
+    from: PathBuf,
+    to: PathBuf,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Copy the tickets of the project `source_db` into `target_db`: board and
+/// in-app archive tickets, their relations, external references and
+/// screenshots. Tickets get new ids in the target (see
+/// `MergeOptions::number_prefix`), and references to merged ids in their
+/// text and dependencies are rewritten. Sections are matched by title and
+/// item types by id; missing ones are created. Project-level data
+/// (history, saved views, templates, AI telemetry) is not merged.
+///
+/// Both projects must be at the same schema version. The source is only
+/// read. Everything is written to the target in one transaction, which a
+/// dry run rolls back after building the report. Screenshot files are
+/// copied after the commit. Emits `import:progress`; reload the target
+/// afterwards.
+#[tauri::command]
+pub async fn merge_projects(
+    source_db: String,
+    target_db: String,
+    options: MergeOptions,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<MergeReport, AppError> {
+    let source_db = files::validate_path(&app, Path::new(&source_db))?;
+    let target_db = files::validate_path(&app, Path::new(&target_db))?;
+    if project_db::canonical(&source_db) == project_db::canonical(&target_db) {
+        return Err(AppError::Validation(
+            "cannot merge a project into itself".into(),
+        ));
+    }
+    if let Some(prefix) = &options.number_prefix {
+        if prefix.is_empty()
+            || prefix.len() > MAX_NUMBER_PREFIX_DIGITS
+            || !prefix.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(AppError::Validation(format!(
+                "number prefix must be 1 to {} digits",
+                MAX_NUMBER_PREFIX_DIGITS
+            )));
+        }
+    }
+
+    let _guard = if options.dry_run {
+        None
+    } else {
+        Some(
+            maintenance
+                .lock
+                .try_lock()
+                .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?,
+        )
+    };
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    merge(&source_db, &target_db, &options, |done, total| {
+        emit_progress(&app, done, total)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Copy the tickets of `source_db` into `target_db` as `merge_projects`
+/// describes, calling `progress` with the tickets written and the total.
+async fn merge(
+    source_db: &Path,
+    target_db: &Path,
+    options: &MergeOptions,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<MergeReport, AppError> {
+    let mut source = project_db::open_read_only(source_db).await?;
+    let source_version = project_db::schema_version(&mut source).await?;
+    let source_context = load_context(&mut source, source_db).await?;
+
+    let (mut target, context) = open_project(target_db, false, MIN_SCHEMA_VERSION).await?;
+    let target_version = project_db::schema_version(&mut target).await?;
+    if source_version != target_version {
+        return Err(AppError::Validation(format!(
+            "projects are at different schema versions (v{} and v{}); open both to migrate them first",
+            source_version, target_version
+        )));
+    }
+    if target_version > project_db::SUPPORTED_SCHEMA_VERSION {
+        return Err(AppError::Validation(format!(
+            "project database schema v{} is newer than this version supports",
+            target_version
+        )));
+    }
+
+    let mut items = load_items(&mut source, "backlog_items").await?;
+    if !options.skip_archived {
+        items.extend(load_items(&mut source, "archived_items").await?);
+    }
+    let types: Vec<(String, String, String, i64, i64)> = sqlx::query_as(
+        "SELECT id, label, color, position, visible FROM type_configs
+         WHERE project_id = ? ORDER BY position",
+    )
+    .bind(source_context.project_id)
+    .fetch_all(&mut source)
+    .await?;
+    let relations: Vec<RelationRow> = sqlx::query_as(
+        "SELECT source_id, target_id, relation_type, confidence, reason FROM item_relations",
+    )
+    .fetch_all(&mut source)
+    .await?;
+    let external_refs: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT item_id, source, external_key FROM item_external_refs")
+            .fetch_all(&mut source)
+            .await?;
+    drop(source);
+
+    let mut report = MergeReport {
+        dry_run: options.dry_run,
+        id_map: BTreeMap::new(),
+        new_sections: Vec::new(),
+        new_types: Vec::new(),
+        relations: 0,
+        screenshots_copied: 0,
+        missing_screenshots: Vec::new(),
+    };
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = target.begin().await?;
+
+    for (id, label, color, position, visible) in &types {
+        if context.types.iter().any(|known| known == id) {
+            continue;
+        }
+        sqlx::query(
+            "INSERT INTO type_configs (id, project_id, label, color, position, visible)
+             VALUES (?, ?, ?, ?,
+               (SELECT COALESCE(MAX(position) + 1, ?) FROM type_configs WHERE project_id = ?),
+               ?)",
+        )
+        .bind(id)
+        .bind(context.project_id)
+        .bind(label)
+        .bind(color)
+        .bind(position)
+        .bind(context.project_id)
+        .bind(visible)
+        .execute(&mut *tx)
+        .await?;
+        report.new_types.push(id.clone());
+    }
+
+    // Allocate every id first: tickets refer to each other. Prefixed ids
+    // go first, so the raised counters keep the others clear of them.
+    if let Some(prefix) = &options.number_prefix {
+        for item in &items {
+            if let Some(new_id) =
+                prefixed_id(&mut tx, &writer, &item.id, &item.item_type, prefix).await?
+            {
+                report.id_map.insert(item.id.clone(), new_id);
+            }
+        }
+    }
+    for item in &items {
+        if !report.id_map.contains_key(&item.id) {
+            let new_id = writer.next_id(&mut tx, &item.item_type).await?;
+            report.id_map.insert(item.id.clone(), new_id);
+        }
+    }
+
+    let id_pattern = Regex::new(r"\b[A-Z]+-\d+\b").unwrap();
+    let source_screenshots = project_dir(source_db).join(SCREENSHOTS_DIR);
+    let target_screenshots = project_dir(target_db).join(SCREENSHOTS_DIR);
+    let mut copies = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        let new_id = &report.id_map[&item.id];
+        let mut raw_markdown = item.raw_markdown.clone();
+        let screenshots = item.screenshots.as_deref().map(|json| {
+            rename_screenshots(json, new_id, |old, new| {
+                raw_markdown = raw_markdown.replace(old, new);
+                let from = source_screenshots.join(old);
+                if from.is_file() {
+                    copies.push(ScreenshotCopy {
+                        from,
+                        to: target_screenshots.join(new),
+                    });
+                } else {
+                    report.missing_screenshots.push(old.to_string());
+                }
+            })
+        });
+        let remap = |text: &Option<String>| {
+            text.as_deref()
+                .map(|text| remap_ids(&id_pattern, text, &report.id_map))
+        };
+
+        if item.archived {
+            sqlx::query(
+                "INSERT INTO archived_items (
+                   id, project_id, type, title, emoji, component, module, severity,
+                   priority, effort, description, user_story, specs, reproduction,
+                   criteria, dependencies, constraints, screens, screenshots,
+                   raw_markdown, archived_at, original_created_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(new_id)
+            .bind(context.project_id)
+            .bind(&item.item_type)
+            .bind(remap_ids(&id_pattern, &item.title, &report.id_map))
+            .bind(&item.emoji)
+            .bind(&item.component)
+            .bind(&item.module)
+            .bind(&item.severity)
+            .bind(&item.priority)
+            .bind(&item.effort)
+            .bind(remap(&item.description))
+            .bind(remap(&item.user_story))
+            .bind(remap(&item.specs))
+            .bind(remap(&item.reproduction))
+            .bind(remap(&item.criteria))
+            .bind(remap(&item.dependencies))
+            .bind(remap(&item.constraints))
+            .bind(&item.screens)
+            .bind(&screenshots)
+            .bind(remap_ids(&id_pattern, &raw_markdown, &report.id_map))
+            .bind(&item.archived_at)
+            .bind(&item.original_created_at)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            if let Some(section) = &item.section {
+                if find_section(&writer.sections, section).is_none() {
+                    report.new_sections.push(section.clone());
+                }
+            }
+            let section_id = writer.section_id(&mut tx, item.section.as_deref()).await?;
+            let position = writer.next_position(&mut tx, section_id).await?;
+            sqlx::query(
+                "INSERT INTO backlog_items (
+                   id, project_id, section_id, type, title, emoji, component, module,
+                   severity, priority, effort, description, user_story, specs,
+                   reproduction, criteria, dependencies, constraints, screens,
+                   screenshots, position, raw_markdown, created_at, updated_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                   COALESCE(?, datetime('now')), COALESCE(?, datetime('now')))",
+            )
+            .bind(new_id)
+            .bind(context.project_id)
+            .bind(section_id)
+            .bind(&item.item_type)
+            .bind(remap_ids(&id_pattern, &item.title, &report.id_map))
+            .bind(&item.emoji)
+            .bind(&item.component)
+            .bind(&item.module)
+            .bind(&item.severity)
+            .bind(&item.priority)
+            .bind(&item.effort)
+            .bind(remap(&item.description))
+            .bind(remap(&item.user_story))
+            .bind(remap(&item.specs))
+            .bind(remap(&item.reproduction))
+            .bind(remap(&item.criteria))
+            .bind(remap(&item.dependencies))
+            .bind(remap(&item.constraints))
+            .bind(&item.screens)
+            .bind(&screenshots)
+            .bind(position)
+            .bind(remap_ids(&id_pattern, &raw_markdown, &report.id_map))
+            .bind(&item.created_at)
+            .bind(&item.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        progress(index + 1, items.len());
+    }
+
+    for relation in &relations {
+        let (Some(source_id), Some(target_id)) = (
+            report.id_map.get(&relation.source_id),
+            report.id_map.get(&relation.target_id),
+        ) else {
+            continue;
+        };
+        report.relations += sqlx::query(
+            "INSERT OR IGNORE INTO item_relations
+               (project_id, source_id, target_id, relation_type, confidence, reason)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(context.project_id)
+        .bind(source_id)
+        .bind(target_id)
+        .bind(&relation.relation_type)
+        .bind(relation.confidence)
+        .bind(&relation.reason)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+    for (item_id, source, external_key) in &external_refs {
+        let Some(item_id) = report.id_map.get(item_id) else {
+            continue;
+        };
+        // A key already in the target keeps pointing at the target's item.
+        sqlx::query(
+            "INSERT OR IGNORE INTO item_external_refs (project_id, item_id, source, external_key)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(context.project_id)
+        .bind(item_id)
+        .bind(source)
+        .bind(external_key)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if options.dry_run {
+        tx.rollback().await?;
+        return Ok(report);
+    }
+    tx.commit().await?;
+
+    if !copies.is_empty() {
+        if let Err(e) = std::fs::create_dir_all(&target_screenshots) {
+            log::warn!("merge_projects: {}: {}", target_screenshots.display(), e);
+        }
+    }
+    for copy in copies {
+        if copy.to.exists() {
+            log::warn!("merge_projects: {} exists, not copied", copy.to.display());
+            continue;
+        }
+        match std::fs::copy(&copy.from, &copy.to) {
+            Ok(_) => report.screenshots_copied += 1,
+            Err(e) => log::warn!("merge_projects: {}: {}", copy.from.display(), e),
+        }
+    }
+    Ok(report)
+}
+
+/// Read every row of `backlog_items` or `archived_items`, board tickets in
+/// board order.
+async fn load_items(conn: &mut SqliteConnection, table: &str) -> Result<Vec<ItemRow>, AppError> {
+    let sql = match table {
+        "backlog_items" => {
+            "SELECT bi.*, 0 AS archived, s.title AS section, NULL AS archived_at,
+               NULL AS original_created_at
+             FROM backlog_items bi
+             LEFT JOIN sections s ON s.id = bi.section_id
+             ORDER BY s.position, bi.position"
+        }
+        _ => {
+            "SELECT *, 1 AS archived, NULL AS section, NULL AS created_at, NULL AS updated_at
+             FROM archived_items
+             ORDER BY archived_at"
+        }
+    };
+    Ok(sqlx::query_as(sql).fetch_all(&mut *conn).await?)
+}
+
+/// `TYPE-<prefix><number>` for the source id `id`, or `None` when that id
+/// is taken in the target. Raises the type's counter past it so ids the
+/// frontend allocates later don't collide.
+async fn prefixed_id(
+    conn: &mut SqliteConnection,
+    writer: &ItemWriter,
+    id: &str,
+    item_type: &str,
+    prefix: &str,
+) -> Result<Option<String>, AppError> {
+    let Some(digits) = id.rsplit_once('-').map(|(_, digits)| digits) else {
+        return Ok(None);
+    };
+    let new_id = format!("{}-{}{}", item_type, prefix, digits);
+    let Ok(number) = format!("{}{}", prefix, digits).parse::<i64>() else {
+        return Ok(None);
+    };
+    let taken: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM backlog_items WHERE id = ?1)
+             OR EXISTS(SELECT 1 FROM archived_items WHERE id = ?1)",
+    )
+    .bind(&new_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if taken {
+        return Ok(None);
+    }
+    sqlx::query(
+        "INSERT INTO type_counters (project_id, type_prefix, last_number)
+         VALUES (?, ?, ?)
+         ON CONFLICT (project_id, type_prefix)
+         DO UPDATE SET last_number = MAX(last_number, excluded.last_number)",
+    )
+    .bind(writer.project_id)
+    .bind(item_type)
+    .bind(number)
+    .execute(&mut *conn)
+    .await?;
+    Ok(Some(new_id))
+}
+
+/// Rename the files of a `screenshots` JSON array (`{TICKET-ID}_{ts}.png`)
+/// after the ticket's new id, calling `renamed(old, new)` for each file.
+/// Returns the rewritten JSON, or the input when it cannot be parsed.
+fn rename_screenshots(json: &str, new_id: &str, mut renamed: impl FnMut(&str, &str)) -> String {
+    let Ok(mut screenshots) = serde_json::from_str::<Vec<serde_json::Value>>(json) else {
+        return json.to_string();
+    };
+    let mut seen = HashSet::new();
+    for screenshot in &mut screenshots {
+        let Some(old) = screenshot
+            .get("filename")
+            .and_then(|filename| filename.as_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let new = match old.split_once('_') {
+            Some((_, rest)) => format!("{}_{}", new_id, rest),
+            None => old.clone(),
+        };
+        if seen.insert(old.clone()) {
+            renamed(&old, &new);
+        }
+        screenshot["filename"] = serde_json::Value::String(new);
+    }
+    serde_json::to_string(&screenshots).unwrap_or_else(|_| json.to_string())
+}
+
+/// Replace the ticket ids of `text` found in `id_map`.
+fn remap_ids(pattern: &Regex, text: &str, id_map: &BTreeMap<String, String>) -> String {
+    pattern
+        .replace_all(text, |caps: &regex::Captures| {
+            id_map
+                .get(&caps[0])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn project_dir(db: &Path) -> PathBuf {
+    db.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{items, project};
+
+    /// The shared test project plus a DOC type, BUG-2 in a new `Review`
+    /// section pointing at BUG-1 with two screenshots (one missing on
+    /// disk), an archived DOC-1, a relation and an external reference.
+    async fn source(dir: &Path) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let db_path = project(dir).await;
+        let mut conn = project_db::open_connection(&db_path).await.unwrap();
+        sqlx::raw_sql(
+            r#"INSERT INTO type_configs VALUES ('DOC', 1, 'Doc', '#00f', 2, 1);
+               INSERT INTO sections VALUES (3, 1, 'Review', 2, '## 3. Review');
+               INSERT INTO backlog_items (id, project_id, section_id, type, title, description,
+                 dependencies, screenshots, position, raw_markdown, created_at) VALUES
+                 ('BUG-2', 1, 3, 'BUG', 'Follow-up of BUG-1', 'Blocks BUG-1, not BUG-10',
+                  '["BUG-1"]',
+                  '[{"filename": "BUG-2_123.png"}, {"filename": "BUG-2_999.png"}]',
+                  0, '![shot](BUG-2_123.png)', '2024-01-01');
+               INSERT INTO archived_items (id, project_id, type, title, raw_markdown, archived_at)
+                 VALUES ('DOC-1', 1, 'DOC', 'Old doc', '### DOC-1', '2024-02-01');
+               INSERT INTO item_relations (project_id, source_id, target_id, relation_type)
+                 VALUES (1, 'BUG-2', 'BUG-1', 'blocks'), (1, 'BUG-2', 'GONE-1', 'blocks');
+               INSERT INTO item_external_refs (project_id, item_id, source, external_key)
+                 VALUES (1, 'BUG-1', 'jira', 'APP-1');"#,
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        let screenshots = dir.join(SCREENSHOTS_DIR);
+        std::fs::create_dir_all(&screenshots).unwrap();
+        std::fs::write(screenshots.join("BUG-2_123.png"), b"png").unwrap();
+        db_path
+    }
+
+    async fn target(dir: &Path) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        project(dir).await
+    }
+
+    #[tokio::test]
+    async fn tickets_are_renumbered_and_references_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = source(&dir.path().join("source")).await;
+        let target_db = target(&dir.path().join("target")).await;
+        let mut progress = Vec::new();
+
+        let report = merge(
+            &source_db,
+            &target_db,
+            &MergeOptions::default(),
+            |done, total| progress.push((done, total)),
+        )
+        .await
+        .unwrap();
+        let id_map: Vec<(&str, &str)> = report
+            .id_map
+            .iter()
+            .map(|(old, new)| (old.as_str(), new.as_str()))
+            .collect();
+        assert_eq!(
+            id_map,
+            [
+                ("BUG-1", "BUG-002"),
+                ("BUG-2", "BUG-003"),
+                ("DOC-1", "DOC-001")
+            ]
+        );
+        assert_eq!(report.new_types, ["DOC"]);
+        assert_eq!(report.new_sections, ["Review"]);
+        assert_eq!(report.relations, 1);
+        assert_eq!(report.screenshots_copied, 1);
+        assert_eq!(report.missing_screenshots, ["BUG-2_999.png"]);
+        assert_eq!(progress.last(), Some(&(3, 3)));
+
+        let sections: Vec<(String, String)> = items(&target_db)
+            .await
+            .into_iter()
+            .map(|(id, section, _)| (id, section))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("BUG-002".to_string(), "Todo".to_string()),
+                ("BUG-003".to_string(), "Review".to_string()),
+                ("BUG-1".to_string(), "Todo".to_string()),
+            ]
+        );
+        let mut conn = project_db::open_read_only(&target_db).await.unwrap();
+        let merged: (String, String, String, String, String, String) = sqlx::query_as(
+            "SELECT title, description, dependencies, screenshots, raw_markdown, created_at
+             FROM backlog_items WHERE id = 'BUG-003'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(merged.0, "Follow-up of BUG-002");
+        assert_eq!(merged.1, "Blocks BUG-002, not BUG-10");
+        assert_eq!(merged.2, r#"["BUG-002"]"#);
+        assert_eq!(
+            merged.3,
+            r#"[{"filename":"BUG-003_123.png"},{"filename":"BUG-003_999.png"}]"#
+        );
+        assert_eq!(merged.4, "![shot](BUG-003_123.png)");
+        assert_eq!(merged.5, "2024-01-01");
+        let relation: (String, String) =
+            sqlx::query_as("SELECT source_id, target_id FROM item_relations")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(relation, ("BUG-003".to_string(), "BUG-002".to_string()));
+        let external: String = sqlx::query_scalar("SELECT item_id FROM item_external_refs")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(external, "BUG-002");
+        let archived: String = sqlx::query_scalar("SELECT id FROM archived_items")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(archived, "DOC-001");
+        assert_eq!(
+            std::fs::read(
+                dir.path()
+                    .join("target")
+                    .join(SCREENSHOTS_DIR)
+                    .join("BUG-003_123.png")
+            )
+            .unwrap(),
+            b"png"
+        );
+    }
+
+    #[tokio::test]
+    async fn number_prefix_keeps_numbers_and_raises_counters() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = source(&dir.path().join("source")).await;
+        let target_db = target(&dir.path().join("target")).await;
+        let options = MergeOptions {
+            number_prefix: Some("7".into()),
+            skip_archived: true,
+            dry_run: false,
+        };
+
+        let report = merge(&source_db, &target_db, &options, |_, _| {})
+            .await
+            .unwrap();
+        let new_ids: Vec<&str> = report.id_map.values().map(String::as_str).collect();
+        assert_eq!(new_ids, ["BUG-71", "BUG-72"]);
+
+        let mut conn = project_db::open_connection(&target_db).await.unwrap();
+        let counter: i64 =
+            sqlx::query_scalar("SELECT last_number FROM type_counters WHERE type_prefix = 'BUG'")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(counter, 72);
+        let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM archived_items")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(archived, 0);
+    }
+
+    #[tokio::test]
+    async fn dry_run_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = source(&dir.path().join("source")).await;
+        let target_db = target(&dir.path().join("target")).await;
+        let options = MergeOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = merge(&source_db, &target_db, &options, |_, _| {})
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.id_map.len(), 3);
+        assert_eq!(report.screenshots_copied, 0);
+        assert_eq!(items(&target_db).await.len(), 1);
+        assert!(!dir.path().join("target").join(SCREENSHOTS_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn schema_versions_must_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_db = source(&dir.path().join("source")).await;
+        let target_db = target(&dir.path().join("target")).await;
+        let mut conn = project_db::open_connection(&target_db).await.unwrap();
+        sqlx::query("PRAGMA user_version = 9")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let err = merge(&source_db, &target_db, &MergeOptions::default(), |_, _| {})
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains(&format!(
+                "different schema versions (v{} and v9)",
+                project_db::SUPPORTED_SCHEMA_VERSION
+            )),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn screenshots_are_renamed_once_each() {
+        let mut renamed = Vec::new();
+        let json = rename_screenshots(
+            r#"[{"filename": "BUG-1_1.png", "alt": "a"}, {"filename": "BUG-1_1.png"},
+                {"filename": "plain.png"}, {"alt": "no file"}]"#,
+            "BUG-009",
+            |old, new| renamed.push((old.to_string(), new.to_string())),
+        );
+        assert_eq!(
+            json,
+            r#"[{"alt":"a","filename":"BUG-009_1.png"},{"filename":"BUG-009_1.png"},{"filename":"plain.png"},{"alt":"no file"}]"#
+        );
+        assert_eq!(
+            renamed,
+            [
+                ("BUG-1_1.png".to_string(), "BUG-009_1.png".to_string()),
+                ("plain.png".to_string(), "plain.png".to_string()),
+            ]
+        );
+        assert_eq!(
+            rename_screenshots("not json", "BUG-9", |_, _| {}),
+            "not json"
+        );
+    }
+}
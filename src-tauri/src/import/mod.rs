@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::project_db;
+
+pub mod csv;
+pub mod github;
+pub mod gitlab;
+pub mod jira;
+pub mod jira_cloud;
+pub mod merge;
+pub mod trello;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+pub(crate) const SEVERITIES: [(&str, &str); 5] = [
+    ("P0", "P0 - Bloquant"),
+    ("P1", "P1 - Critique"),
+    ("P2", "P2 - Moyenne"),
+    ("P3", "P3 - Faible"),
+    ("P4", "P4 - Mineure"),
+];
+pub(crate) const PRIORITIES: [&str; 3] = ["Haute", "Moyenne", "Faible"];
+pub(crate) const EFFORTS: [(&str, &str); 5] = [
+    ("XS", "XS (Extra Small)"),
+    ("S", "S (Small)"),
+    ("M", "M (Medium)"),
+    ("L", "L (Large)"),
+    ("XL", "XL (Extra Large)"),
+];
+
+/// First schema version with `type_counters`, used to allocate item ids.
+const MIN_SCHEMA_VERSION: i64 = 7;
+
+/// First schema version with `item_external_refs`.
+const EXTERNAL_REFS_SCHEMA_VERSION: i64 = 8;
+
+/// Emit `import:progress` every this many written items.
+const PROGRESS_EVERY: usize = 500;
+
+const IMPORT_PROGRESS_EVENT: &str = "import:progress";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Payload of `import:progress`.
+#[derive(Debug, Clone, Serialize)]
+struct ImportProgress {
+    rows: usize,
+    total: usize,
+}
+
+/// An acceptance criterion, stored as JSON in the `criteria` column.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Criterion {
+    text: String,
+    checked: bool,
+}
+
+/// A validated item, ready to insert.
+#[derive(Debug, Default)]
+pub(crate) struct ImportRow {
+    pub(crate) item_type: String,
+    pub(crate) title: String,
+    /// Title of the target section; the first section when `None`.
+    pub(crate) status: Option<String>,
+    pub(crate) emoji: Option<String>,
+    pub(crate) component: Option<String>,
+    pub(crate) module: Option<String>,
+    pub(crate) severity: Option<&'static str>,
+    pub(crate) priority: Option<&'static str>,
+    pub(crate) effort: Option<&'static str>,
+    pub(crate) description: Option<String>,
+    pub(crate) user_story: Option<String>,
+    pub(crate) criteria: Vec<Criterion>,
+}
+
+/// Project data rows are validated against.
+pub(crate) struct ProjectContext {
+    pub(crate) project_id: i64,
+    types: Vec<String>,
+    /// Existing sections as (id, title), in board order.
+    sections: Vec<(i64, String)>,
+}
+
+/// Writes imported items into an open transaction, creating missing
+/// sections and allocating ids from `type_counters` the way the frontend
+/// does.
+pub(crate) struct ItemWriter {
+    project_id: i64,
+    sections: Vec<(i64, String)>,
+    next_position: HashMap<i64, i64>,
+}
+
+impl ItemWriter {
+    pub(crate) fn new(context: &ProjectContext) -> Self {
+        Self {
+            project_id: context.project_id,
+            sections: context.sections.clone(),
+            next_position: HashMap::new(),
+        }
+    }
+
+    /// Insert `row` into `backlog_items` and return its new id.
+    pub(crate) async fn insert_item(
+        &mut self,
+        conn: &mut SqliteConnection,
+        row: &ImportRow,
+    ) -> Result<String, AppError> {
+        let section_id = self.section_id(conn, row.status.as_deref()).await?;
+        let position = self.next_position(conn, section_id).await?;
+        let id = self.next_id(conn, &row.item_type).await?;
+        sqlx::query(
+            "INSERT INTO backlog_items (
+               id, project_id, section_id, type, title, emoji, component, module,
+               severity, priority, effort, description, user_story, criteria, position,
+               raw_markdown, created_at, updated_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+               datetime('now'), datetime('now'))",
+        )
+        .bind(&id)
+        .bind(self.project_id)
+        .bind(section_id)
+        .bind(&row.item_type)
+        .bind(&row.title)
+        .bind(&row.emoji)
+        .bind(&row.component)
+        .bind(&row.module)
+        .bind(row.severity)
+        .bind(row.priority)
+        .bind(row.effort)
+        .bind(&row.description)
+        .bind(&row.user_story)
+        .bind(criteria_json(&row.criteria))
+        .bind(position)
+        .bind(item_markdown(&id, row))
+        .execute(&mut *conn)
+        .await?;
+        Ok(id)
+    }
+
+    /// Overwrite the title, description and module of an existing item with
+    /// those of `row`, moving it to the section of `row.status` when that
+    /// changed. Other fields are left as the user edited them, and
+    /// `raw_markdown` is cleared so exports rebuild it from the fields.
+    /// Returns `None` when the item is gone (deleted or archived), else
+    /// whether anything changed.
+    pub(crate) async fn update_item(
+        &mut self,
+        conn: &mut SqliteConnection,
+        id: &str,
+        row: &ImportRow,
+    ) -> Result<Option<bool>, AppError> {
+        let current: Option<(i64, String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT section_id, title, description, module FROM backlog_items
+             WHERE id = ? AND project_id = ?",
+        )
+        .bind(id)
+        .bind(self.project_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some((current_section, title, description, module)) = current else {
+            return Ok(None);
+        };
+
+        let section_id = self.section_id(conn, row.status.as_deref()).await?;
+        if section_id == current_section
+            && title == row.title
+            && description == row.description
+            && module == row.module
+        {
+            return Ok(Some(false));
+        }
+        let position = if section_id == current_section {
+            None
+        } else {
+            Some(self.next_position(conn, section_id).await?)
+        };
+        sqlx::query(
+            "UPDATE backlog_items
+             SET section_id = ?, position = COALESCE(?, position), title = ?,
+                 description = ?, module = ?, raw_markdown = '', updated_at = datetime('now')
+             WHERE id = ? AND project_id = ?",
+        )
+        .bind(section_id)
+        .bind(position)
+        .bind(&row.title)
+        .bind(&row.description)
+        .bind(&row.module)
+        .bind(id)
+        .bind(self.project_id)
+        .execute(&mut *conn)
+        .await?;
+        Ok(Some(true))
+    }
+
+    /// Insert `row` directly into `archived_items` and return its new id.
+    async fn insert_archived(
+        &mut self,
+        conn: &mut SqliteConnection,
+        row: &ImportRow,
+    ) -> Result<String, AppError> {
+        let id = self.next_id(conn, &row.item_type).await?;
+        sqlx::query(
+            "INSERT INTO archived_items (
+               id, project_id, type, title, emoji, component, module,
+               severity, priority, effort, description, user_story, criteria,
+               raw_markdown, archived_at, original_created_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+               datetime('now'), datetime('now'))",
+        )
+        .bind(&id)
+        .bind(self.project_id)
+        .bind(&row.item_type)
+        .bind(&row.title)
+        .bind(&row.emoji)
+        .bind(&row.component)
+        .bind(&row.module)
+        .bind(row.severity)
+        .bind(row.priority)
+        .bind(row.effort)
+        .bind(&row.description)
+        .bind(&row.user_story)
+        .bind(criteria_json(&row.criteria))
+        .bind(item_markdown(&id, row))
+        .execute(&mut *conn)
+        .await?;
+        Ok(id)
+    }
+
+    /// Id of the section titled `status`, created at the end of the board
+    /// when missing.
+    async fn section_id(
+        &mut self,
+        conn: &mut SqliteConnection,
+        status: Option<&str>,
+    ) -> Result<i64, AppError> {
+        let status = match status {
+            Some(status) => status.to_string(),
+            None => self
+                .sections
+                .first()
+                .map(|(_, title)| title.clone())
+                .unwrap_or_else(|| "Backlog".to_string()),
+        };
+        if let Some(id) = find_section(&self.sections, &status) {
+            return Ok(id);
+        }
+
+        let position: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM sections WHERE project_id = ?",
+        )
+        .bind(self.project_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        let id = sqlx::query(
+            "INSERT INTO sections (project_id, title, position, raw_header)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(self.project_id)
+        .bind(&status)
+        .bind(position)
+        .bind(format!("## {}. {}", position + 1, status))
+        .execute(&mut *conn)
+        .await?
+        .last_insert_rowid();
+        self.sections.push((id, status));
+        Ok(id)
+    }
+
+    /// Position after the last item of the section.
+    async fn next_position(
+        &mut self,
+        conn: &mut SqliteConnection,
+        section_id: i64,
+    ) -> Result<i64, AppError> {
+        let position =
+            match self.next_position.get(&section_id) {
+                Some(&position) => position,
+                None => sqlx::query_scalar(
+                    "SELECT COALESCE(MAX(position) + 1, 0) FROM backlog_items WHERE section_id = ?",
+                )
+                .bind(section_id)
+                .fetch_one(&mut *conn)
+                .await?,
+            };
+        self.next_position.insert(section_id, position + 1);
+        Ok(position)
+    }
+
+    /// Allocate the next `TYPE-NNN` id.
+    async fn next_id(
+        &self,
+        conn: &mut SqliteConnection,
+        item_type: &str,
+    ) -> Result<String, AppError> {
+        sqlx::query(
+            "INSERT INTO type_counters (project_id, type_prefix, last_number)
+             VALUES (?, ?, 1)
+             ON CONFLICT (project_id, type_prefix)
+             DO UPDATE SET last_number = last_number + 1",
+        )
+        .bind(self.project_id)
+        .bind(item_type)
+        .execute(&mut *conn)
+        .await?;
+        let number: i64 = sqlx::query_scalar(
+            "SELECT last_number FROM type_counters WHERE project_id = ? AND type_prefix = ?",
+        )
+        .bind(self.project_id)
+        .bind(item_type)
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(format!("{}-{:03}", item_type, number))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Open a project database for import (read-only for dry runs) and load the
+/// project it belongs to.
+pub(crate) async fn open_project(
+    db_path: &Path,
+    read_only: bool,
+    min_version: i64,
+) -> Result<(SqliteConnection, ProjectContext), AppError> {
+    let mut conn = if read_only {
+        project_db::open_read_only(db_path).await?
+    } else {
+        project_db::open_connection(db_path).await?
+    };
+    let version = project_db::schema_version(&mut conn).await?;
+    if version < min_version {
+        return Err(AppError::Validation(format!(
+            "project database schema v{} is too old to import into (need v{})",
+            version, min_version
+        )));
+    }
+    let context = load_context(&mut conn, db_path).await?;
+    Ok((conn, context))
+}
+
+async fn load_context(
+    conn: &mut SqliteConnection,
+    db_path: &Path,
+) -> Result<ProjectContext, AppError> {
+    let project_path = db_path
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut project_id: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM projects WHERE path = ? LIMIT 1")
+            .bind(&project_path)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if project_id.is_none() {
+        // Fall back to the only project when the stored path differs
+        // (e.g. the project folder was moved).
+        let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM projects LIMIT 2")
+            .fetch_all(&mut *conn)
+            .await?;
+        if let [id] = ids[..] {
+            project_id = Some(id);
+        }
+    }
+    let project_id = project_id.ok_or_else(|| {
+        AppError::Validation(format!("no project found for {}", db_path.display()))
+    })?;
+
+    let types =
+        sqlx::query_scalar("SELECT id FROM type_configs WHERE project_id = ? ORDER BY position")
+            .bind(project_id)
+            .fetch_all(&mut *conn)
+            .await?;
+    let sections =
+        sqlx::query_as("SELECT id, title FROM sections WHERE project_id = ? ORDER BY position, id")
+            .bind(project_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    Ok(ProjectContext {
+        project_id,
+        types,
+        sections,
+    })
+}
+
+/// Resolve `item_type` case-insensitively against the project's types.
+pub(crate) fn resolve_type(context: &ProjectContext, item_type: &str) -> Result<String, String> {
+    context
+        .types
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(item_type))
+        .cloned()
+        .ok_or_else(|| format!("unknown type: {}", item_type))
+}
+
+/// Match `value` against a code (`P1`) or its full label (`P1 - Critique`).
+pub(crate) fn lookup_code(codes: &[(&'static str, &str)], value: &str) -> Option<&'static str> {
+    codes
+        .iter()
+        .find(|(code, label)| code.eq_ignore_ascii_case(value) || label.eq_ignore_ascii_case(value))
+        .map(|(code, _)| *code)
+}
+
+fn find_section(sections: &[(i64, String)], status: &str) -> Option<i64> {
+    sections
+        .iter()
+        .find(|(_, title)| title.trim().eq_ignore_ascii_case(status))
+        .map(|(id, _)| *id)
+}
+
+fn criteria_json(criteria: &[Criterion]) -> Option<String> {
+    if criteria.is_empty() {
+        None
+    } else {
+        serde_json::to_string(criteria).ok()
+    }
+}
+
+/// Emit `import:progress` every `PROGRESS_EVERY` items and on the last one.
+fn emit_progress(app: &AppHandle, done: usize, total: usize) {
+    if done % PROGRESS_EVERY == 0 || done == total {
+        app.emit(IMPORT_PROGRESS_EVENT, ImportProgress { rows: done, total })
+            .ok();
+    }
+}
+
+/// Markdown for a new item, in the format of the frontend's
+/// `buildItemMarkdown` for the fields an import can set.
+fn item_markdown(id: &str, row: &ImportRow) -> String {
+    let mut lines = Vec::new();
+    let emoji = row
+        .emoji
+        .as_deref()
+        .map(|emoji| format!("{} ", emoji))
+        .unwrap_or_default();
+    lines.push(format!("### {} | {}{}", id, emoji, row.title));
+    if let Some(component) = &row.component {
+        lines.push(format!("**Composant:** {}", component));
+    }
+    if let Some(module) = &row.module {
+        lines.push(format!("**Module:** {}", module));
+    }
+    if let Some(severity) = row.severity {
+        let label = SEVERITIES
+            .iter()
+            .find(|(code, _)| *code == severity)
+            .map_or(severity, |(_, label)| label);
+        lines.push(format!("**Sévérité:** {}", label));
+    }
+    if let Some(priority) = row.priority {
+        lines.push(format!("**Priorité:** {}", priority));
+    }
+    if let Some(effort) = row.effort {
+        let label = EFFORTS
+            .iter()
+            .find(|(code, _)| *code == effort)
+            .map_or(effort, |(_, label)| label);
+        lines.push(format!("**Effort:** {}", label));
+    }
+    if let Some(description) = &row.description {
+        lines.push(format!("**Description:** {}", description));
+    }
+    if let Some(user_story) = &row.user_story {
+        lines.push(String::new());
+        lines.push("**User Story:**".to_string());
+        lines.push(format!("> {}", user_story));
+    }
+    if !row.criteria.is_empty() {
+        lines.push(String::new());
+        lines.push("**Critères d'acceptation:**".to_string());
+        for criterion in &row.criteria {
+            let check = if criterion.checked { 'x' } else { ' ' };
+            lines.push(format!("- [{}] {}", check, criterion.text));
+        }
+    }
+    lines.extend([String::new(), "---".to_string(), String::new()]);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::test_support::{items, project};
+    use sqlx::Connection;
+
+    /// Answer one connection per entry of `responses` on a local port,
+    /// each with that raw HTTP response. Returns the server URL and the
+    /// requests received.
+    pub(crate) async fn serve(
+        responses: Vec<String>,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = received.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    let Some(end) = text.find("\r\n\r\n") else {
+                        if n == 0 {
+                            break;
+                        }
+                        continue;
+                    };
+                    let length: usize = text[..end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse().ok())?
+                        })
+                        .unwrap_or(0);
+                    if n == 0 || request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+                seen.lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request).into_owned());
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        });
+        (url, received)
+    }
+
+    /// A raw HTTP response with a JSON body and extra `headers` lines.
+    pub(crate) fn json_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+             connection: close\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+        )
+    }
+
+    fn row(item_type: &str, title: &str, status: Option<&str>) -> ImportRow {
+        ImportRow {
+            item_type: item_type.into(),
+            title: title.into(),
+            status: status.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn writer_allocates_ids_positions_and_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let (mut conn, context) = open_project(&db_path, false, MIN_SCHEMA_VERSION)
+            .await
+            .unwrap();
+        assert_eq!(context.types, ["BUG", "FEAT"]);
+
+        let mut writer = ItemWriter::new(&context);
+        let mut ids = Vec::new();
+        for row in [
+            row("BUG", "Second", None),
+            row("FEAT", "Idea", Some("done")),
+            row("BUG", "Third", Some("Review")),
+            row("FEAT", "Other idea", Some("Review")),
+        ] {
+            ids.push(writer.insert_item(&mut conn, &row).await.unwrap());
+        }
+        assert_eq!(ids, ["BUG-002", "FEAT-001", "BUG-003", "FEAT-002"]);
+        let archived = writer
+            .insert_archived(&mut conn, &row("BUG", "Old", None))
+            .await
+            .unwrap();
+        assert_eq!(archived, "BUG-004");
+        conn.close().await.unwrap();
+
+        assert_eq!(
+            items(&db_path).await,
+            [
+                ("BUG-002".to_string(), "Todo".to_string(), 1),
+                ("BUG-003".to_string(), "Review".to_string(), 0),
+                ("BUG-1".to_string(), "Todo".to_string(), 0),
+                ("FEAT-001".to_string(), "Done".to_string(), 0),
+                ("FEAT-002".to_string(), "Review".to_string(), 1),
+            ]
+        );
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let review: (i64, String) =
+            sqlx::query_as("SELECT position, raw_header FROM sections WHERE title = 'Review'")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(review, (2, "## 3. Review".to_string()));
+    }
+
+    #[tokio::test]
+    async fn update_moves_changed_items_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let (mut conn, context) = open_project(&db_path, false, MIN_SCHEMA_VERSION)
+            .await
+            .unwrap();
+        let mut writer = ItemWriter::new(&context);
+
+        let same = row("BUG", "Crash", Some("Todo"));
+        assert_eq!(
+            writer.update_item(&mut conn, "BUG-1", &same).await.unwrap(),
+            Some(false)
+        );
+        let moved = row("BUG", "Crash on save", Some("Done"));
+        assert_eq!(
+            writer
+                .update_item(&mut conn, "BUG-1", &moved)
+                .await
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            writer
+                .update_item(&mut conn, "BUG-9", &moved)
+                .await
+                .unwrap(),
+            None
+        );
+
+        let updated: (String, i64, String) = sqlx::query_as(
+            "SELECT title, section_id, raw_markdown FROM backlog_items WHERE id = 'BUG-1'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(updated, ("Crash on save".to_string(), 2, String::new()));
+    }
+
+    #[tokio::test]
+    async fn projects_are_found_or_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+
+        let version = project_db::SUPPORTED_SCHEMA_VERSION;
+        let err = open_project(&db_path, true, version + 1)
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains(&format!("schema v{} is too old", version)),
+            "{}",
+            err
+        );
+
+        // A moved project falls back to the only project of the database.
+        let mut conn = project_db::open_connection(&db_path).await.unwrap();
+        sqlx::query("UPDATE projects SET path = '/elsewhere'")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let (_, context) = open_project(&db_path, true, 8).await.unwrap();
+        assert_eq!(context.project_id, 1);
+
+        sqlx::query("INSERT INTO projects (id, name, path) VALUES (2, 'Other', '/other')")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let err = open_project(&db_path, true, 8).await.err().unwrap();
+        assert!(err.to_string().contains("no project found"), "{}", err);
+    }
+
+    #[test]
+    fn codes_match_code_or_label() {
+        assert_eq!(lookup_code(&SEVERITIES, "p1"), Some("P1"));
+        assert_eq!(lookup_code(&SEVERITIES, "P1 - critique"), Some("P1"));
+        assert_eq!(lookup_code(&EFFORTS, "Extra small"), None);
+        assert_eq!(lookup_code(&EFFORTS, "xl (extra large)"), Some("XL"));
+    }
+
+    #[test]
+    fn markdown_matches_the_frontend_layout() {
+        let row = ImportRow {
+            emoji: Some("🐛".into()),
+            module: Some("Editor".into()),
+            severity: Some("P1"),
+            effort: Some("S"),
+            user_story: Some("As a user".into()),
+            criteria: vec![Criterion {
+                text: "Saves".into(),
+                checked: true,
+            }],
+            ..row("BUG", "Crash", None)
+        };
+        assert_eq!(
+            item_markdown("BUG-002", &row),
+            "### BUG-002 | 🐛 Crash\n**Module:** Editor\n**Sévérité:** P1 - Critique\n\
+             **Effort:** S (Small)\n\n**User Story:**\n> As a user\n\n\
+             **Critères d'acceptation:**\n- [x] Saves\n\n---\n"
+        );
+        assert_eq!(criteria_json(&[]), None);
+        assert_eq!(
+            criteria_json(&row.criteria).unwrap(),
+            r#"[{"text":"Saves","checked":true}]"#
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::Connection;
+use tauri::AppHandle;
+
+use super::{
+    emit_progress, open_project, resolve_type, Criterion, ImportRow, ItemWriter, MIN_SCHEMA_VERSION,
+};
+use crate::backup::MaintenanceState;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Refuse exports larger than this; they are parsed in memory.
+const MAX_EXPORT_BYTES: u64 = 100 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options accepted by `import_trello`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TrelloImportOptions {
+    /// Leave archived cards (and cards of archived lists) out instead of
+    /// importing them as archived items.
+    pub skip_archived: bool,
+    /// Type given to every card. Defaults to the project's first type.
+    pub item_type: Option<String>,
+}
+
+/// A card or list that was not imported.
+#[derive(Debug, Serialize)]
+pub struct TrelloSkipped {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Return value of `import_trello`.
+#[derive(Debug, Serialize)]
+pub struct TrelloImportSummary {
+    /// Open lists, each mapped to a section.
+    pub lists: usize,
+    /// Lists for which a section was created.
+    pub new_sections: Vec<String>,
+    /// Cards imported as items.
+    pub cards: usize,
+    /// Cards imported as archived items.
+    pub archived: usize,
+    pub created_ids: Vec<String>,
+    pub skipped: Vec<TrelloSkipped>,
+    /// Card data that has no equivalent in a project (due dates, labels).
+    pub warnings: Vec<String>,
+}
+
+/// The parts of a Trello board export (`Menu → Print and export → JSON`)
+/// the import reads. Everything else is ignored.
+#[derive(Debug, Deserialize)]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    checklists: Vec<TrelloChecklist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloList {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    attachments: Vec<TrelloAttachment>,
+    #[serde(default)]
+    id_checklists: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloAttachment {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklist {
+    id: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloCheckItem {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Import a Trello board export into a project. Open lists become sections,
+/// cards become items (checklist entries as acceptance criteria, attachments
+/// as links appended to the description) and archived cards become archived
+/// items unless `options.skip_archived` is set. Nothing is downloaded.
+///
+/// The whole import runs in one transaction, so a malformed export never
+/// leaves a half-imported project. Emits `import:progress`; the frontend
+/// must reload the project afterwards.
+#[tauri::command]
+pub async fn import_trello(
+    db_path: String,
+    json_path: String,
+    options: TrelloImportOptions,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<TrelloImportSummary, AppError> {
+    let json_path = files::validate_path(&app, Path::new(&json_path))?;
+    let size = std::fs::metadata(&json_path)?.len();
+    if size > MAX_EXPORT_BYTES {
+        return Err(AppError::Validation(format!(
+            "Trello export is too large ({} bytes, max {})",
+            size, MAX_EXPORT_BYTES
+        )));
+    }
+    let board: TrelloBoard = serde_json::from_slice(&std::fs::read(&json_path)?)
+        .map_err(|e| AppError::Validation(format!("invalid Trello export: {}", e)))?;
+
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    let _paused = fs_watch::pause_project_watch(&app);
+    import_board(Path::new(&db_path), &board, &options, |done, total| {
+        emit_progress(&app, done, total)
+    })
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Write `board` into the project at `db_path` in one transaction, calling
+/// `progress` with the cards written so far and the total.
+async fn import_board(
+    db_path: &Path,
+    board: &TrelloBoard,
+    options: &TrelloImportOptions,
+    mut progress: impl FnMut(usize, usize) + Send,
+) -> Result<TrelloImportSummary, AppError> {
+    let (mut conn, context) = open_project(db_path, false, MIN_SCHEMA_VERSION).await?;
+
+    let item_type = match &options.item_type {
+        Some(item_type) => resolve_type(&context, item_type).map_err(AppError::Validation)?,
+        None => context
+            .types
+            .first()
+            .cloned()
+            .ok_or_else(|| AppError::Validation("project has no item types".into()))?,
+    };
+
+    let mut summary = TrelloImportSummary {
+        lists: 0,
+        new_sections: Vec::new(),
+        cards: 0,
+        archived: 0,
+        created_ids: Vec::new(),
+        skipped: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let mut lists: Vec<&TrelloList> = board.lists.iter().collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let list_by_id: HashMap<&str, (usize, &TrelloList)> = lists
+        .iter()
+        .enumerate()
+        .map(|(index, list)| (list.id.as_str(), (index, *list)))
+        .collect();
+    let checklists: HashMap<&str, &TrelloChecklist> = board
+        .checklists
+        .iter()
+        .map(|checklist| (checklist.id.as_str(), checklist))
+        .collect();
+
+    // Cards in board order: by list, then by position within the list.
+    let mut cards = Vec::new();
+    for card in &board.cards {
+        let Some(&(list_index, list)) = list_by_id.get(card.id_list.as_str()) else {
+            summary.skipped.push(TrelloSkipped {
+                name: card.name.clone(),
+                reason: format!("list {} not found in the export", card.id_list),
+            });
+            continue;
+        };
+        let title = card.name.trim();
+        if title.is_empty() {
+            summary.skipped.push(TrelloSkipped {
+                name: card.name.clone(),
+                reason: "card has no title".into(),
+            });
+            continue;
+        }
+        let archived = card.closed || list.closed;
+        if archived && options.skip_archived {
+            summary.skipped.push(TrelloSkipped {
+                name: card.name.clone(),
+                reason: "archived".into(),
+            });
+            continue;
+        }
+
+        let row = ImportRow {
+            item_type: item_type.clone(),
+            title: title.replace(['\r', '\n'], " "),
+            status: Some(list.name.trim().to_string()).filter(|name| !name.is_empty()),
+            description: card_description(card),
+            criteria: card_criteria(card, &checklists),
+            ..Default::default()
+        };
+        cards.push((list_index, card.pos, archived, row));
+    }
+    cards.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+    let with_due = board.cards.iter().filter(|card| card.due.is_some()).count();
+    if with_due > 0 {
+        summary.warnings.push(format!(
+            "{} due dates not imported: items have no due date",
+            with_due
+        ));
+    }
+    let with_labels = board
+        .cards
+        .iter()
+        .filter(|card| card.labels.iter().any(|label| !label.name.is_empty()))
+        .count();
+    if with_labels > 0 {
+        summary.warnings.push(format!(
+            "labels of {} cards not imported: items have no tags",
+            with_labels
+        ));
+    }
+
+    let mut writer = ItemWriter::new(&context);
+    let mut tx = conn.begin().await?;
+
+    // Create sections for every open list, empty ones included, in board
+    // order.
+    for list in lists.iter().filter(|list| !list.closed) {
+        let name = list.name.trim();
+        if name.is_empty() {
+            summary.skipped.push(TrelloSkipped {
+                name: list.id.clone(),
+                reason: "list has no name".into(),
+            });
+            continue;
+        }
+        let known = writer.sections.len();
+        writer.section_id(&mut tx, Some(name)).await?;
+        if writer.sections.len() > known {
+            summary.new_sections.push(name.to_string());
+        }
+        summary.lists += 1;
+    }
+
+    for (index, (_, _, archived, row)) in cards.iter().enumerate() {
+        let id = if *archived {
+            summary.archived += 1;
+            writer.insert_archived(&mut tx, row).await?
+        } else {
+            summary.cards += 1;
+            writer.insert_item(&mut tx, row).await?
+        };
+        summary.created_ids.push(id);
+        progress(index + 1, cards.len());
+    }
+    tx.commit().await?;
+
+    Ok(summary)
+}
+
+/// Card description with its attachments appended as Markdown links.
+fn card_description(card: &TrelloCard) -> Option<String> {
+    let mut description = card.desc.trim().to_string();
+    let links: Vec<String> = card
+        .attachments
+        .iter()
+        .filter(|attachment| !attachment.url.is_empty())
+        .map(|attachment| {
+            let name = if attachment.name.trim().is_empty() {
+                &attachment.url
+            } else {
+                attachment.name.trim()
+            };
+            format!("- [{}]({})", name, attachment.url)
+        })
+        .collect();
+    if !links.is_empty() {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str("Attachments:\n");
+        description.push_str(&links.join("\n"));
+    }
+    Some(description).filter(|d| !d.is_empty())
+}
+
+/// Checklist entries of a card, checklist by checklist, in Trello order.
+fn card_criteria(
+    card: &TrelloCard,
+    checklists: &HashMap<&str, &TrelloChecklist>,
+) -> Vec<Criterion> {
+    let mut card_checklists: Vec<&TrelloChecklist> = card
+        .id_checklists
+        .iter()
+        .filter_map(|id| checklists.get(id.as_str()).copied())
+        .collect();
+    card_checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut criteria = Vec::new();
+    for checklist in card_checklists {
+        let mut items: Vec<&TrelloCheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        criteria.extend(
+            items
+                .into_iter()
+                .filter(|item| !item.name.trim().is_empty())
+                .map(|item| Criterion {
+                    text: item.name.trim().to_string(),
+                    checked: item.state == "complete",
+                }),
+        );
+    }
+    criteria
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_db;
+    use crate::test_support::{items, project};
+
+    fn board() -> TrelloBoard {
+        serde_json::from_value(serde_json::json!({
+            "name": "Ignored",
+            "lists": [
+                { "id": "l2", "name": "Done", "pos": 2.0 },
+                { "id": "l1", "name": "Review", "pos": 1.0 },
+                { "id": "l3", "name": "Old", "pos": 3.0, "closed": true },
+                { "id": "l4", "name": " ", "pos": 4.0 }
+            ],
+            "cards": [
+                { "name": "Second", "idList": "l1", "pos": 20.0, "due": "2024-05-01" },
+                {
+                    "name": "First\nline", "idList": "l1", "pos": 10.0, "desc": "Details",
+                    "labels": [{ "name": "ui" }],
+                    "attachments": [
+                        { "name": "Spec", "url": "https://example.com/spec" },
+                        { "name": "", "url": "https://example.com/log" },
+                        { "name": "upload", "url": "" }
+                    ],
+                    "idChecklists": ["c2", "c1", "missing"]
+                },
+                { "name": "Shipped", "idList": "l2", "pos": 1.0 },
+                { "name": "Archived card", "idList": "l2", "pos": 2.0, "closed": true },
+                { "name": "In closed list", "idList": "l3", "pos": 1.0 },
+                { "name": "  ", "idList": "l2", "pos": 3.0 },
+                { "name": "Lost", "idList": "gone", "pos": 1.0 }
+            ],
+            "checklists": [
+                {
+                    "id": "c1", "pos": 1.0,
+                    "checkItems": [
+                        { "name": "B", "state": "incomplete", "pos": 2.0 },
+                        { "name": "A", "state": "complete", "pos": 1.0 }
+                    ]
+                },
+                { "id": "c2", "pos": 2.0, "checkItems": [{ "name": "C", "pos": 1.0 }] }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn board_becomes_sections_items_and_archived_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let mut progress = Vec::new();
+
+        let summary = import_board(
+            &db_path,
+            &board(),
+            &TrelloImportOptions::default(),
+            |done, total| progress.push((done, total)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.lists, 2);
+        assert_eq!(summary.new_sections, ["Review"]);
+        assert_eq!((summary.cards, summary.archived), (3, 2));
+        assert_eq!(
+            summary.created_ids,
+            ["BUG-002", "BUG-003", "BUG-004", "BUG-005", "BUG-006"]
+        );
+        let skipped: Vec<&str> = summary.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(
+            skipped,
+            [
+                "card has no title",
+                "list gone not found in the export",
+                "list has no name"
+            ]
+        );
+        assert_eq!(summary.warnings.len(), 2);
+        assert_eq!(progress.last(), Some(&(5, 5)));
+
+        // Cards follow list then card position; the Review section goes
+        // after the existing ones.
+        assert_eq!(
+            items(&db_path).await,
+            [
+                ("BUG-002".to_string(), "Review".to_string(), 0),
+                ("BUG-003".to_string(), "Review".to_string(), 1),
+                ("BUG-004".to_string(), "Done".to_string(), 0),
+                ("BUG-1".to_string(), "Todo".to_string(), 0),
+            ]
+        );
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let first: (String, String, String) = sqlx::query_as(
+            "SELECT title, description, criteria FROM backlog_items WHERE id = 'BUG-002'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(first.0, "First line");
+        assert_eq!(
+            first.1,
+            "Details\n\nAttachments:\n- [Spec](https://example.com/spec)\n\
+             - [https://example.com/log](https://example.com/log)"
+        );
+        assert_eq!(
+            first.2,
+            r#"[{"text":"A","checked":true},{"text":"B","checked":false},{"text":"C","checked":false}]"#
+        );
+        let archived: Vec<String> =
+            sqlx::query_scalar("SELECT title FROM archived_items ORDER BY id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(archived, ["Archived card", "In closed list"]);
+    }
+
+    #[tokio::test]
+    async fn archived_cards_can_be_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project(dir.path()).await;
+        let options = TrelloImportOptions {
+            skip_archived: true,
+            item_type: Some("feat".into()),
+        };
+
+        let summary = import_board(&db_path, &board(), &options, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!((summary.cards, summary.archived), (3, 0));
+        assert_eq!(summary.created_ids, ["FEAT-001", "FEAT-002", "FEAT-003"]);
+        assert_eq!(
+            summary
+                .skipped
+                .iter()
+                .filter(|s| s.reason == "archived")
+                .count(),
+            2
+        );
+
+        let options = TrelloImportOptions {
+            item_type: Some("TASK".into()),
+            ..Default::default()
+        };
+        let err = import_board(&db_path, &board(), &options, |_, _| {})
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("unknown type: TASK"), "{}", err);
+    }
+}
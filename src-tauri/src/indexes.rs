@@ -0,0 +1,508 @@
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use tauri::AppHandle;
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Table and column names accepted by `db_create_index`. Anything else
+/// (quotes, spaces, dots, SQL) is refused before it reaches a statement.
+const IDENTIFIER_PATTERN: &str = r"^[a-zA-Z_][a-zA-Z0-9_]*$";
+
+const MAX_INDEX_COLUMNS: usize = 8;
+
+/// Prefix of the tables and indexes SQLite reserves for itself.
+const SYSTEM_PREFIX: &str = "sqlite_";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// An index of a project database, as listed by `db_list_indexes`.
+#[derive(Debug, Serialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub table: String,
+    /// Indexed columns in order; `<expr>` for an expression.
+    pub columns: Vec<String>,
+    pub unique: bool,
+    /// Whether the index was made by `db_create_index` (its name is the one
+    /// that command gives), so `db_drop_index` may drop it. Indexes of the
+    /// project schema and of constraints are not.
+    pub user_managed: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Create `idx_<table>_<columns>` on a table of a project database, unless
+/// it exists, and return its name. Table and column names must be plain
+/// identifiers of an existing table and its columns; SQLite's own tables,
+/// virtual tables and their shadow tables are refused.
+#[tauri::command]
+pub async fn db_create_index(
+    db_path: String,
+    table: String,
+    columns: Vec<String>,
+    unique: bool,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<String, AppError> {
+    let result = async {
+        validate_index_columns(&table, &columns)?;
+
+        let _guard = maintenance_guard(&maintenance)?;
+        let db = files::validate_path(&app, Path::new(&db_path))?;
+        let _paused = fs_watch::pause_project_watch(&app);
+        let mut conn = with_timeout(project_db::open_connection(&db), DB_TIMEOUT_MS).await?;
+        // No timeout: indexing a large table may take a while, and
+        // abandoning the statement would not stop it.
+        create_index(&mut conn, &table, &columns, unique).await
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "db_create_index",
+        &format!("{} {}({})", db_path, table, columns.join(", ")),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Drop an index made by `db_create_index`. Indexes of the project schema,
+/// of constraints and of SQLite itself are refused.
+#[tauri::command]
+pub async fn db_drop_index(
+    db_path: String,
+    index_name: String,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let result = async {
+        validate_drop_name(&index_name)?;
+
+        let _guard = maintenance_guard(&maintenance)?;
+        let db = files::validate_path(&app, Path::new(&db_path))?;
+        let _paused = fs_watch::pause_project_watch(&app);
+        let mut conn = with_timeout(project_db::open_connection(&db), DB_TIMEOUT_MS).await?;
+
+        check_droppable(&mut conn, &index_name).await?;
+
+        with_timeout(
+            async {
+                sqlx::query(&format!("DROP INDEX IF EXISTS \"{}\"", index_name))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            DB_TIMEOUT_MS,
+        )
+        .await
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "db_drop_index",
+        &format!("{} {}", db_path, index_name),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Every index of the tables of a project database, SQLite's own tables
+/// excepted.
+#[tauri::command]
+pub async fn db_list_indexes(db_path: String, app: AppHandle) -> Result<Vec<IndexInfo>, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(
+        async {
+            let mut conn = project_db::open_read_only(&db).await?;
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT name FROM pragma_table_list
+                 WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+                 ORDER BY name",
+            )
+            .fetch_all(&mut conn)
+            .await?;
+
+            let mut indexes = Vec::new();
+            for table in tables {
+                let names: Vec<String> =
+                    sqlx::query_scalar("SELECT name FROM pragma_index_list(?) ORDER BY name")
+                        .bind(&table)
+                        .fetch_all(&mut conn)
+                        .await?;
+                for name in names {
+                    indexes.extend(index_info(&mut conn, &table, &name).await?);
+                }
+            }
+            Ok(indexes)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn validate_identifier(identifier: &str) -> Result<(), AppError> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(IDENTIFIER_PATTERN).unwrap());
+    if !pattern.is_match(identifier) {
+        return Err(AppError::Validation(format!(
+            "invalid identifier: {:?}",
+            identifier
+        )));
+    }
+    Ok(())
+}
+
+/// Check the table and column names of an index request, before anything
+/// touches the database.
+fn validate_index_columns(table: &str, columns: &[String]) -> Result<(), AppError> {
+    validate_identifier(table)?;
+    if columns.is_empty() || columns.len() > MAX_INDEX_COLUMNS {
+        return Err(AppError::Validation(format!(
+            "an index needs 1 to {} columns",
+            MAX_INDEX_COLUMNS
+        )));
+    }
+    for (position, column) in columns.iter().enumerate() {
+        validate_identifier(column)?;
+        if columns[..position].contains(column) {
+            return Err(AppError::Validation(format!(
+                "duplicate column: {}",
+                column
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Create the index of `table` on `columns`, whose names passed
+/// `validate_index_columns`, and return its name.
+async fn create_index(
+    conn: &mut SqliteConnection,
+    table: &str,
+    columns: &[String],
+    unique: bool,
+) -> Result<String, AppError> {
+    let name = index_name(table, columns);
+    check_user_table(conn, table).await?;
+    let known: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    if let Some(column) = columns.iter().find(|column| !known.contains(column)) {
+        return Err(AppError::Validation(format!(
+            "table {} has no column {}",
+            table, column
+        )));
+    }
+
+    // Every identifier was validated, so quoting cannot be escaped; the
+    // quotes only guard against keywords.
+    let quoted: Vec<String> = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column))
+        .collect();
+    sqlx::query(&format!(
+        "CREATE {}INDEX IF NOT EXISTS \"{}\" ON \"{}\" ({})",
+        if unique { "UNIQUE " } else { "" },
+        name,
+        table,
+        quoted.join(", ")
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // An index of that name made earlier with the other uniqueness (or,
+    // through an ambiguous name, on other columns) was left as is.
+    let existing = index_info(conn, table, &name).await?;
+    if existing
+        .as_ref()
+        .map(|index| (index.columns.as_slice(), index.unique))
+        != Some((columns, unique))
+    {
+        return Err(AppError::Validation(format!(
+            "index {} already exists with another definition",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+fn validate_drop_name(index_name: &str) -> Result<(), AppError> {
+    validate_identifier(index_name)?;
+    if index_name.to_ascii_lowercase().starts_with(SYSTEM_PREFIX) {
+        return Err(AppError::Validation(format!(
+            "{} belongs to SQLite",
+            index_name
+        )));
+    }
+    Ok(())
+}
+
+/// Refuse to drop an index that `db_create_index` did not make.
+async fn check_droppable(conn: &mut SqliteConnection, index_name: &str) -> Result<(), AppError> {
+    let table: Option<String> =
+        sqlx::query_scalar("SELECT tbl_name FROM sqlite_master WHERE type = 'index' AND name = ?")
+            .bind(index_name)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let table = table.ok_or_else(|| AppError::Validation(format!("no index {}", index_name)))?;
+    let index = index_info(conn, &table, index_name).await?;
+    if !index.is_some_and(|index| index.user_managed) {
+        return Err(AppError::Validation(format!(
+            "{} is part of the project schema and cannot be dropped",
+            index_name
+        )));
+    }
+    Ok(())
+}
+
+/// Name `db_create_index` gives an index.
+fn index_name(table: &str, columns: &[String]) -> String {
+    format!("idx_{}_{}", table, columns.join("_"))
+}
+
+/// Refuse SQLite's tables, views, virtual tables and the shadow tables
+/// behind them (e.g. the FTS5 `_data` tables).
+async fn check_user_table(conn: &mut SqliteConnection, table: &str) -> Result<(), AppError> {
+    if table.to_ascii_lowercase().starts_with(SYSTEM_PREFIX) {
+        return Err(AppError::Validation(format!("{} belongs to SQLite", table)));
+    }
+    let kind: Option<String> =
+        sqlx::query_scalar("SELECT type FROM pragma_table_list WHERE schema = 'main' AND name = ?")
+            .bind(table)
+            .fetch_optional(&mut *conn)
+            .await?;
+    match kind.as_deref() {
+        Some("table") => Ok(()),
+        Some(kind) => Err(AppError::Validation(format!(
+            "{} is a {} and cannot be indexed",
+            table, kind
+        ))),
+        None => Err(AppError::Validation(format!("no table {}", table))),
+    }
+}
+
+/// Definition of the index `name` of `table`, if it exists.
+async fn index_info(
+    conn: &mut SqliteConnection,
+    table: &str,
+    name: &str,
+) -> Result<Option<IndexInfo>, AppError> {
+    let unique: Option<bool> =
+        sqlx::query_scalar("SELECT \"unique\" FROM pragma_index_list(?) WHERE name = ?")
+            .bind(table)
+            .bind(name)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some(unique) = unique else {
+        return Ok(None);
+    };
+    let columns: Vec<Option<String>> =
+        sqlx::query_scalar("SELECT name FROM pragma_index_info(?) ORDER BY seqno")
+            .bind(name)
+            .fetch_all(&mut *conn)
+            .await?;
+    let columns: Vec<String> = columns
+        .into_iter()
+        .map(|column| column.unwrap_or_else(|| "<expr>".to_string()))
+        .collect();
+    let user_managed = index_name(table, &columns) == name;
+    Ok(Some(IndexInfo {
+        name: name.to_string(),
+        table: table.to_string(),
+        columns,
+        unique,
+        user_managed,
+    }))
+}
+
+fn maintenance_guard(
+    maintenance: &MaintenanceState,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation(
+            "a schema migration is in progress".into(),
+        ));
+    }
+    maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is already in progress".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Connection;
+
+    const SCHEMA: &str = "
+        CREATE TABLE tickets (id INTEGER PRIMARY KEY, title TEXT, status TEXT, \"order\" INTEGER);
+        CREATE INDEX tickets_by_status ON tickets (status);
+        CREATE TABLE tags (name TEXT UNIQUE);
+        CREATE VIEW open_tickets AS SELECT * FROM tickets WHERE status = 'open';
+        CREATE VIRTUAL TABLE tickets_fts USING fts5 (title);
+    ";
+
+    async fn connect() -> SqliteConnection {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(SCHEMA).execute(&mut conn).await.unwrap();
+        conn
+    }
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    async fn table_count(conn: &mut SqliteConnection) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'")
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn injection_attempts_are_refused() {
+        let mut conn = connect().await;
+        let tables = table_count(&mut conn).await;
+        let attempts = [
+            "tickets; DROP TABLE tickets",
+            "tickets\" (id); DROP TABLE tickets; --",
+            "status' OR '1'='1",
+            "main.tickets",
+            "title status",
+            "1title",
+            "",
+            "title\0",
+            "tïtle",
+        ];
+
+        for attempt in attempts {
+            assert!(matches!(
+                validate_index_columns(attempt, &columns(&["status"])),
+                Err(AppError::Validation(_))
+            ));
+            assert!(matches!(
+                validate_index_columns("tickets", &columns(&["title", attempt])),
+                Err(AppError::Validation(_))
+            ));
+            assert!(matches!(
+                validate_drop_name(attempt),
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert_eq!(table_count(&mut conn).await, tables);
+    }
+
+    #[test]
+    fn column_lists_are_bounded_and_unique() {
+        assert!(validate_index_columns("tickets", &[]).is_err());
+        assert!(validate_index_columns("tickets", &columns(&["title", "title"])).is_err());
+        let many: Vec<String> = (0..=MAX_INDEX_COLUMNS).map(|n| format!("c{}", n)).collect();
+        assert!(validate_index_columns("tickets", &many).is_err());
+        assert!(validate_index_columns("tickets", &many[..MAX_INDEX_COLUMNS]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn index_is_created_once_and_droppable() {
+        let mut conn = connect().await;
+        let wanted = columns(&["status", "order"]);
+
+        let name = create_index(&mut conn, "tickets", &wanted, false)
+            .await
+            .unwrap();
+        assert_eq!(name, "idx_tickets_status_order");
+        // Creating it again is a no-op.
+        create_index(&mut conn, "tickets", &wanted, false)
+            .await
+            .unwrap();
+
+        let index = index_info(&mut conn, "tickets", &name)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(index.columns, wanted);
+        assert!(!index.unique);
+        assert!(index.user_managed);
+
+        // Same name, other uniqueness.
+        assert!(matches!(
+            create_index(&mut conn, "tickets", &wanted, true).await,
+            Err(AppError::Validation(_))
+        ));
+
+        validate_drop_name(&name).unwrap();
+        check_droppable(&mut conn, &name).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_plain_tables_and_known_columns_are_indexed() {
+        let mut conn = connect().await;
+        for table in [
+            "sqlite_master",
+            "open_tickets",
+            "tickets_fts",
+            "tickets_fts_data",
+            "missing",
+        ] {
+            assert!(
+                matches!(
+                    create_index(&mut conn, table, &columns(&["title"]), false).await,
+                    Err(AppError::Validation(_))
+                ),
+                "{} was indexed",
+                table
+            );
+        }
+        assert!(matches!(
+            create_index(&mut conn, "tickets", &columns(&["missing"]), false).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn schema_and_sqlite_indexes_are_not_droppable() {
+        let mut conn = connect().await;
+        assert!(check_droppable(&mut conn, "tickets_by_status")
+            .await
+            .is_err());
+        assert!(check_droppable(&mut conn, "missing").await.is_err());
+
+        let autoindex: String = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'tags'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert!(validate_drop_name(&autoindex).is_err());
+        assert!(validate_drop_name("SQLITE_anything").is_err());
+    }
+}
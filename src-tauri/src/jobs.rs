@@ -0,0 +1,602 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup::MaintenanceState;
+use crate::error::AppError;
+use crate::export::{self, CsvExportOptions};
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::vacuum;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Emitted with a `JobProgress` when a job starts and finishes a step.
+const PROGRESS_EVENT: &str = "job:progress";
+
+/// Emitted with the final `JobStatus` of a job, cancelled ones included.
+const COMPLETE_EVENT: &str = "job:complete";
+
+/// Jobs waiting at once; more fail with `InUse`.
+const MAX_QUEUED: usize = 20;
+
+/// Finished jobs kept for `list_background_jobs`, newest last.
+const MAX_FINISHED: usize = 50;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Work a background job does, from the `kind` and `params` of
+/// `spawn_background_job`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", content = "params", rename_all = "snake_case")]
+pub enum JobKind {
+    /// Rebuild `backlog_items_fts` from `backlog_items`.
+    FtsRebuild { db_path: String },
+    /// Same as `vacuum_project_db`.
+    Vacuum { db_path: String },
+    /// Same as `export_project_json`.
+    ExportJson { db_path: String, dest_path: String },
+    /// Same as `export_tickets_csv`; the only kind cancellable while it
+    /// runs.
+    ExportCsv {
+        db_path: String,
+        dest_path: String,
+        #[serde(default)]
+        options: CsvExportOptions,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+    Cancelled = 4,
+}
+
+/// A `JobState` shared between the worker and the commands.
+pub struct AtomicJobStatus(AtomicU8);
+
+pub struct BackgroundJob {
+    pub id: String,
+    pub kind: JobKind,
+    pub status: AtomicJobStatus,
+    cancelled: AtomicBool,
+    /// Result value or error message, once finished.
+    outcome: Mutex<Option<Result<serde_json::Value, String>>>,
+}
+
+/// Tauri managed state: every queued, running and recently finished job.
+/// A dedicated worker thread runs them one at a time, oldest first.
+pub struct BackgroundJobQueue {
+    jobs: Mutex<VecDeque<Arc<BackgroundJob>>>,
+    wake: Condvar,
+    next_id: AtomicU64,
+}
+
+/// A job as listed by `list_background_jobs` and sent with `job:complete`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub kind: &'static str,
+    pub status: JobState,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Payload of `job:progress`.
+#[derive(Debug, Clone, Serialize)]
+struct JobProgress {
+    job_id: String,
+    /// From 0 to 1.
+    progress: f64,
+    step: &'static str,
+}
+
+impl AtomicJobStatus {
+    fn new(state: JobState) -> Self {
+        Self(AtomicU8::new(state as u8))
+    }
+
+    pub fn load(&self) -> JobState {
+        match self.0.load(Ordering::SeqCst) {
+            0 => JobState::Queued,
+            1 => JobState::Running,
+            2 => JobState::Completed,
+            3 => JobState::Failed,
+            _ => JobState::Cancelled,
+        }
+    }
+
+    fn store(&self, state: JobState) {
+        self.0.store(state as u8, Ordering::SeqCst);
+    }
+
+    /// Move from `from` to `to`, unless another thread changed it first.
+    fn transition(&self, from: JobState, to: JobState) -> bool {
+        self.0
+            .compare_exchange(from as u8, to as u8, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+}
+
+impl JobKind {
+    fn name(&self) -> &'static str {
+        match self {
+            JobKind::FtsRebuild { .. } => "fts_rebuild",
+            JobKind::Vacuum { .. } => "vacuum",
+            JobKind::ExportJson { .. } => "export_json",
+            JobKind::ExportCsv { .. } => "export_csv",
+        }
+    }
+}
+
+impl BackgroundJobQueue {
+    fn new() -> Self {
+        Self {
+            jobs: Mutex::new(VecDeque::new()),
+            wake: Condvar::new(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Queue a job for the worker, making room by forgetting the oldest
+    /// finished jobs. Returns its id.
+    fn enqueue(&self, kind: JobKind) -> Result<String, AppError> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let queued = jobs
+            .iter()
+            .filter(|job| job.status.load() == JobState::Queued)
+            .count();
+        if queued >= MAX_QUEUED {
+            return Err(AppError::InUse(format!(
+                "{} background jobs are already waiting",
+                MAX_QUEUED
+            )));
+        }
+        let finished = jobs.iter().filter(|job| job.is_finished()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED - 1);
+        jobs.retain(|job| {
+            let drop = excess > 0 && job.is_finished();
+            if drop {
+                excess -= 1;
+            }
+            !drop
+        });
+
+        let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        log::info!("spawn_background_job: {} ({})", id, kind.name());
+        jobs.push_back(Arc::new(BackgroundJob {
+            id: id.clone(),
+            kind,
+            status: AtomicJobStatus::new(JobState::Queued),
+            cancelled: AtomicBool::new(false),
+            outcome: Mutex::new(None),
+        }));
+        self.wake.notify_one();
+        Ok(id)
+    }
+
+    /// Cancel a job. Returns its final status when it was still queued, and
+    /// `None` when a running `export_csv` was asked to stop.
+    fn cancel(&self, job_id: &str) -> Result<Option<JobStatus>, AppError> {
+        let job = find_job(self, job_id)?;
+        if job.status.transition(JobState::Queued, JobState::Cancelled) {
+            return Ok(Some(job.to_status()));
+        }
+        match (job.status.load(), &job.kind) {
+            (JobState::Running, JobKind::ExportCsv { .. }) => {
+                job.cancelled.store(true, Ordering::SeqCst);
+                Ok(None)
+            }
+            (JobState::Running, kind) => Err(AppError::Validation(format!(
+                "a running {} job cannot be cancelled",
+                kind.name()
+            ))),
+            _ => Err(AppError::Validation(format!(
+                "job {} has already finished",
+                job_id
+            ))),
+        }
+    }
+}
+
+impl BackgroundJob {
+    fn to_status(&self) -> JobStatus {
+        let outcome = self.outcome.lock().unwrap();
+        JobStatus {
+            id: self.id.clone(),
+            kind: self.kind.name(),
+            status: self.status.load(),
+            result: outcome.as_ref().and_then(|outcome| outcome.clone().ok()),
+            error: outcome.as_ref().and_then(|outcome| outcome.clone().err()),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(
+            self.status.load(),
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Manage `BackgroundJobQueue` and start its worker thread, which runs jobs
+/// on its own thread so heavy work never holds an async runtime worker.
+pub fn init_job_worker(app: &AppHandle) {
+    app.manage(BackgroundJobQueue::new());
+
+    let app = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("background-jobs".into())
+        .spawn(move || loop {
+            let job = next_job(&app.state::<BackgroundJobQueue>());
+            run_job(&app, &job);
+        });
+    if let Err(e) = spawned {
+        log::error!("init_job_worker: cannot start worker thread: {}", e);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Queue a job and return its id. `kind` is `fts_rebuild`, `vacuum`
+/// (`params: { db_path }`), `export_json` (`{ db_path, dest_path }`) or
+/// `export_csv` (`{ db_path, dest_path, options }`). Progress is reported
+/// with `job:progress` and the outcome with `job:complete`.
+#[tauri::command]
+pub fn spawn_background_job(
+    kind: String,
+    params: serde_json::Value,
+    app: AppHandle,
+    queue: tauri::State<'_, BackgroundJobQueue>,
+) -> Result<String, AppError> {
+    let kind: JobKind = serde_json::from_value(serde_json::json!({
+        "kind": kind,
+        "params": params,
+    }))
+    .map_err(|e| AppError::Validation(format!("invalid job {}: {}", kind, e)))?;
+    match &kind {
+        JobKind::FtsRebuild { db_path } | JobKind::Vacuum { db_path } => {
+            files::validate_path(&app, Path::new(db_path))?;
+        }
+        JobKind::ExportJson { db_path, dest_path }
+        | JobKind::ExportCsv {
+            db_path, dest_path, ..
+        } => {
+            files::validate_path(&app, Path::new(db_path))?;
+            files::validate_path(&app, Path::new(dest_path))?;
+        }
+    }
+
+    queue.enqueue(kind)
+}
+
+/// Cancel a job. A queued job is dropped; a running `export_csv` stops
+/// before its next page. Other running jobs cannot be stopped.
+#[tauri::command]
+pub fn cancel_background_job(
+    job_id: String,
+    app: AppHandle,
+    queue: tauri::State<'_, BackgroundJobQueue>,
+) -> Result<(), AppError> {
+    if let Some(status) = queue.cancel(&job_id)? {
+        app.emit(COMPLETE_EVENT, status).ok();
+    }
+    Ok(())
+}
+
+/// Queued, running and recently finished jobs, oldest first.
+#[tauri::command]
+pub fn list_background_jobs(queue: tauri::State<'_, BackgroundJobQueue>) -> Vec<JobStatus> {
+    queue
+        .jobs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|job| job.to_status())
+        .collect()
+}
+
+/// Current status of one job.
+#[tauri::command]
+pub fn get_background_job(
+    job_id: String,
+    queue: tauri::State<'_, BackgroundJobQueue>,
+) -> Result<JobStatus, AppError> {
+    Ok(find_job(&queue, &job_id)?.to_status())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn find_job(queue: &BackgroundJobQueue, job_id: &str) -> Result<Arc<BackgroundJob>, AppError> {
+    queue
+        .jobs
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|job| job.id == job_id)
+        .cloned()
+        .ok_or_else(|| AppError::Validation(format!("no background job with id {}", job_id)))
+}
+
+/// Block until a job is queued and mark it running.
+fn next_job(queue: &BackgroundJobQueue) -> Arc<BackgroundJob> {
+    let mut jobs = queue.jobs.lock().unwrap();
+    loop {
+        let next = jobs
+            .iter()
+            .find(|job| job.status.transition(JobState::Queued, JobState::Running))
+            .cloned();
+        if let Some(job) = next {
+            return job;
+        }
+        jobs = queue.wake.wait(jobs).unwrap();
+    }
+}
+
+fn run_job(app: &AppHandle, job: &BackgroundJob) {
+    let report = |progress: JobProgress| {
+        app.emit(PROGRESS_EVENT, progress).ok();
+    };
+    let status = run_with(job, &report, |progress| {
+        tauri::async_runtime::block_on(execute(app, job, progress))
+    });
+    app.emit(COMPLETE_EVENT, status).ok();
+}
+
+/// Run `work` for `job` between its `started` and `finished` progress
+/// reports, record the outcome and return the final status.
+fn run_with(
+    job: &BackgroundJob,
+    report: &dyn Fn(JobProgress),
+    work: impl FnOnce(&dyn Fn(f64, &'static str)) -> Result<serde_json::Value, AppError>,
+) -> JobStatus {
+    let progress = |progress: f64, step: &'static str| {
+        report(JobProgress {
+            job_id: job.id.clone(),
+            progress,
+            step,
+        });
+    };
+    progress(0.0, "started");
+    let result = work(&progress);
+    progress(1.0, "finished");
+
+    let state = match &result {
+        Ok(_) => JobState::Completed,
+        Err(_) if job.cancelled.load(Ordering::SeqCst) => JobState::Cancelled,
+        Err(_) => JobState::Failed,
+    };
+    if let Err(e) = &result {
+        log::warn!("background job {} ({}): {}", job.id, job.kind.name(), e);
+    }
+    *job.outcome.lock().unwrap() = Some(result.map_err(|e| e.to_string()));
+    job.status.store(state);
+    job.to_status()
+}
+
+async fn execute(
+    app: &AppHandle,
+    job: &BackgroundJob,
+    progress: &dyn Fn(f64, &'static str),
+) -> Result<serde_json::Value, AppError> {
+    let value = match &job.kind {
+        JobKind::FtsRebuild { db_path } => {
+            let maintenance = app.state::<MaintenanceState>();
+            let _guard = maintenance_guard(&maintenance)?;
+            let _paused = fs_watch::pause_project_watch(app);
+            let mut conn = project_db::open_connection(Path::new(db_path)).await?;
+            progress(0.1, "rebuilding");
+            sqlx::query("INSERT INTO backlog_items_fts(backlog_items_fts) VALUES ('rebuild')")
+                .execute(&mut conn)
+                .await?;
+            serde_json::Value::Null
+        }
+        JobKind::Vacuum { db_path } => {
+            let maintenance = app.state::<MaintenanceState>();
+            let _guard = maintenance_guard(&maintenance)?;
+            let _paused = fs_watch::pause_project_watch(app);
+            to_value(
+                vacuum::vacuum_database(
+                    Path::new(db_path),
+                    vacuum::progress_emitter(app, Some(job.id.clone())),
+                )
+                .await?,
+            )?
+        }
+        JobKind::ExportJson { db_path, dest_path } => to_value(
+            export::export_project_json(db_path.clone(), dest_path.clone(), app.clone()).await?,
+        )?,
+        JobKind::ExportCsv {
+            db_path,
+            dest_path,
+            options,
+        } => {
+            let dest = files::validate_path(app, Path::new(dest_path))?;
+            to_value(
+                export::write_csv(
+                    Path::new(db_path),
+                    &dest,
+                    options,
+                    &job.cancelled,
+                    export::progress_emitter(app, &options.job_id),
+                )
+                .await?,
+            )?
+        }
+    };
+    Ok(value)
+}
+
+fn maintenance_guard(
+    maintenance: &MaintenanceState,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation(
+            "a schema migration is in progress".into(),
+        ));
+    }
+    maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is already in progress".into()))
+}
+
+fn to_value(value: impl Serialize) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(value).map_err(|e| AppError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vacuum() -> JobKind {
+        JobKind::Vacuum {
+            db_path: "project.db".into(),
+        }
+    }
+
+    fn export_csv() -> JobKind {
+        JobKind::ExportCsv {
+            db_path: "project.db".into(),
+            dest_path: "tickets.csv".into(),
+            options: CsvExportOptions::default(),
+        }
+    }
+
+    fn status(queue: &BackgroundJobQueue, id: &str) -> JobState {
+        find_job(queue, id).unwrap().status.load()
+    }
+
+    #[test]
+    fn jobs_run_oldest_first() {
+        let queue = BackgroundJobQueue::new();
+        let first = queue.enqueue(vacuum()).unwrap();
+        let second = queue.enqueue(export_csv()).unwrap();
+        assert_eq!((first.as_str(), second.as_str()), ("job-1", "job-2"));
+
+        let job = next_job(&queue);
+        assert_eq!(job.id, first);
+        assert_eq!(status(&queue, &first), JobState::Running);
+        assert_eq!(status(&queue, &second), JobState::Queued);
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let queue = BackgroundJobQueue::new();
+        for _ in 0..MAX_QUEUED {
+            queue.enqueue(vacuum()).unwrap();
+        }
+        assert!(matches!(queue.enqueue(vacuum()), Err(AppError::InUse(_))));
+
+        // A job taken by the worker frees a slot.
+        next_job(&queue);
+        queue.enqueue(vacuum()).unwrap();
+    }
+
+    #[test]
+    fn progress_is_reported_from_start_to_finish() {
+        let queue = BackgroundJobQueue::new();
+        let id = queue.enqueue(vacuum()).unwrap();
+        let job = next_job(&queue);
+
+        let reports = Mutex::new(Vec::new());
+        let report = |progress: JobProgress| {
+            assert_eq!(progress.job_id, id);
+            reports
+                .lock()
+                .unwrap()
+                .push((progress.step, progress.progress));
+        };
+        let status = run_with(&job, &report, |progress| {
+            progress(0.5, "vacuuming");
+            Ok(serde_json::json!({ "freed_bytes": 4096 }))
+        });
+
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            [("started", 0.0), ("vacuuming", 0.5), ("finished", 1.0)]
+        );
+        assert_eq!(status.status, JobState::Completed);
+        assert_eq!(status.result.unwrap()["freed_bytes"], 4096);
+    }
+
+    #[test]
+    fn failed_job_keeps_its_error() {
+        let queue = BackgroundJobQueue::new();
+        queue.enqueue(vacuum()).unwrap();
+        let job = next_job(&queue);
+
+        let status = run_with(&job, &|_| {}, |_| {
+            Err(AppError::Validation(
+                "a schema migration is in progress".into(),
+            ))
+        });
+        assert_eq!(status.status, JobState::Failed);
+        assert!(status.error.unwrap().contains("migration"));
+    }
+
+    #[test]
+    fn queued_job_is_cancelled_at_once() {
+        let queue = BackgroundJobQueue::new();
+        let id = queue.enqueue(vacuum()).unwrap();
+
+        let cancelled = queue.cancel(&id).unwrap().unwrap();
+        assert_eq!(cancelled.status, JobState::Cancelled);
+        // The worker skips it.
+        let next = queue.enqueue(vacuum()).unwrap();
+        assert_eq!(next_job(&queue).id, next);
+        assert!(queue.cancel(&id).is_err());
+    }
+
+    #[test]
+    fn only_a_running_csv_export_can_be_stopped() {
+        let queue = BackgroundJobQueue::new();
+        let vacuum_id = queue.enqueue(vacuum()).unwrap();
+        let export_id = queue.enqueue(export_csv()).unwrap();
+
+        next_job(&queue);
+        assert!(matches!(
+            queue.cancel(&vacuum_id),
+            Err(AppError::Validation(_))
+        ));
+
+        let export = next_job(&queue);
+        assert!(queue.cancel(&export_id).unwrap().is_none());
+        assert!(export.cancelled.load(Ordering::SeqCst));
+        // The export sees the flag and gives up.
+        let status = run_with(&export, &|_| {}, |_| {
+            Err(AppError::Validation("export cancelled".into()))
+        });
+        assert_eq!(status.status, JobState::Cancelled);
+    }
+
+    #[test]
+    fn unknown_job_ids_are_refused() {
+        let queue = BackgroundJobQueue::new();
+        assert!(matches!(
+            queue.cancel("job-42"),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use sqlx::{SqliteExecutor, SqlitePool};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// backend key/value settings table in `telemetry.db`.
+pub const KV_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS kv_store (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+";
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Read a value from `kv_store`. Returns `Ok(None)` when the key is absent.
+pub async fn get(pool: &SqlitePool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>("SELECT value FROM kv_store WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Insert or replace a value in `kv_store`. Takes the pool, or a
+/// transaction to write several keys at once.
+pub async fn set<'e>(
+    executor: impl SqliteExecutor<'e>,
+    key: &str,
+    value: &str,
+) -> Result<(), sqlx::Error> {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    sqlx::query(
+        "INSERT INTO kv_store (key, value, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    )
+    .bind(key)
+    .bind(value)
+    .bind(now_ms)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove a value from `kv_store`. Removing an absent key is not an error.
+pub async fn delete<'e>(executor: impl SqliteExecutor<'e>, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM kv_store WHERE key = ?")
+        .bind(key)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(KV_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn values_are_set_replaced_and_deleted() {
+        let pool = pool().await;
+        assert_eq!(get(&pool, "theme").await.unwrap(), None);
+
+        set(&pool, "theme", "dark").await.unwrap();
+        set(&pool, "theme", "light").await.unwrap();
+        assert_eq!(get(&pool, "theme").await.unwrap().as_deref(), Some("light"));
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM kv_store")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        delete(&pool, "theme").await.unwrap();
+        delete(&pool, "theme").await.unwrap();
+        assert_eq!(get(&pool, "theme").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn writes_in_a_transaction_roll_back_together() {
+        let pool = pool().await;
+        set(&pool, "kept", "1").await.unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        set(&mut *tx, "added", "2").await.unwrap();
+        delete(&mut *tx, "kept").await.unwrap();
+        tx.rollback().await.unwrap();
+
+        assert_eq!(get(&pool, "kept").await.unwrap().as_deref(), Some("1"));
+        assert_eq!(get(&pool, "added").await.unwrap(), None);
+    }
+}
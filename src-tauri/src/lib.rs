@@ -1,19 +1,94 @@
+mod app_lock;
+mod archive_db;
+mod attachments;
+mod audit;
+mod backup;
+mod backup_schedule;
+mod browser;
+mod checkpoint;
+mod cli;
+mod clipboard;
+mod commands;
+mod compaction;
+mod compress;
+mod crash;
+mod db_check;
+mod disk;
+mod drag;
+mod email;
+mod encryption;
+mod error;
+mod export;
+mod files;
+mod fs_watch;
+mod import;
+mod indexes;
+mod jobs;
+mod kv;
+mod locale;
+mod pre_migration;
+mod project_db;
+mod project_lock;
+mod project_probe;
+mod project_settings;
+mod projects;
+mod proxy;
+mod recent;
+mod recurrence;
+mod report;
+mod screenshot;
+mod search;
+mod secrets;
+mod settings;
+mod shell;
+mod shutdown;
+mod slack;
+mod spell;
+mod startup;
+mod stats;
+mod storage;
 mod telemetry;
+mod timer;
+mod tray;
+mod vacuum;
+mod webhooks;
+mod window;
 
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager, WindowEvent,
-};
+#[cfg(test)]
+#[path = "../build_info.rs"]
+mod build_info;
+#[cfg(test)]
+mod test_support;
+
+use tauri::{Manager, WindowEvent};
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 #[tauri::command]
-fn force_quit(app: tauri::AppHandle) {
+async fn force_quit(app: tauri::AppHandle) {
+    shutdown::orderly_cleanup(&app).await;
     app.exit(0);
 }
 
+/// Restart after an orderly shutdown, preserving the current CLI flags.
+/// `extra_args` (e.g. `["--project", "<path>"]`) are appended so the app
+/// reopens where the user was.
+#[tauri::command]
+async fn restart_app(app: tauri::AppHandle, extra_args: Option<Vec<String>>) {
+    shutdown::orderly_cleanup(&app).await;
+    shutdown::restart_with_args(&app, &extra_args.unwrap_or_default());
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Headless flags (--version, --help, --flush-telemetry) exit before any
+    // window, tray or plugin is created.
+    if let Some(code) = cli::handle_headless_flags() {
+        std::process::exit(code);
+    }
+
+    // Phase timings for `get_startup_timing`, marked through `setup`
+    let mut startup_timer = startup::StartupTimer::new();
+
     // SQLite Migrations
     // ==================
     // Migrations run automatically on Database.load() in order of version number.
@@ -30,16 +105,18 @@ pub fn run() {
     //
     // Note: Dynamic paths are handled in database.ts. The placeholder path
     // "sqlite:ticketflow.db" is overwritten per-project at runtime.
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_initial_tables",
-            sql: include_str!("../migrations/001_initial.sql"),
-            kind: MigrationKind::Up,
-        },
-    ];
-
-    tauri::Builder::default()
+    let migrations = vec![Migration {
+        version: 1,
+        description: "create_initial_tables",
+        sql: include_str!("../migrations/001_initial.sql"),
+        kind: MigrationKind::Up,
+    }];
+
+    // Portable mode keeps data next to the executable and updates by file
+    // replacement, so the updater plugin is not registered at all.
+    let storage_mode = storage::detect_storage_mode();
+
+    let mut builder = tauri::Builder::default()
         .plugin(
             tauri_plugin_sql::Builder::default()
                 .add_migrations("sqlite:ticketflow.db", migrations)
@@ -48,26 +125,247 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
             // When a second instance is launched, show the existing window
-            if let Some(window) = app.get_webview_window("main") {
-                window.show().ok();
-                window.unminimize().ok();
-                window.set_focus().ok();
+            // (rebuilding it if it was destroyed) and forward its arguments
+            window::apply_cli_navigation(app, &args);
+        }));
+
+    if storage_mode == storage::StorageMode::Installed {
+        builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
+    }
+
+    builder
+        .invoke_handler(tauri::generate_handler![
+            force_quit,
+            restart_app,
+            audit::get_audit_log,
+            backup::backup_project_db,
+            backup::restore_project_db,
+            backup::set_migration_in_progress,
+            pre_migration::preflight_project_migration,
+            pre_migration::restore_pre_migration_backup,
+            vacuum::vacuum_project_db,
+            checkpoint::checkpoint_project_db,
+            checkpoint::db_set_wal_autocheckpoint,
+            checkpoint::db_get_wal_autocheckpoint,
+            indexes::db_create_index,
+            indexes::db_drop_index,
+            indexes::db_list_indexes,
+            compaction::get_compaction_settings,
+            compaction::set_compaction_settings,
+            compaction::check_project_compaction,
+            browser::get_default_browser,
+            browser::open_url_in_browser,
+            backup_schedule::get_backup_schedule,
+            backup_schedule::set_backup_schedule,
+            settings::export_settings,
+            settings::import_settings,
+            project_settings::project_get_setting,
+            project_settings::project_set_setting,
+            project_settings::project_settings_all,
+            backup_schedule::list_backups,
+            clipboard::watch_clipboard,
+            clipboard::unwatch_clipboard,
+            compress::compress_file,
+            disk::get_free_disk_space,
+            compress::decompress_file,
+            crash::get_crash_reports,
+            crash::clear_crash_reports,
+            export::export_tickets_csv,
+            export::cancel_export,
+            export::export_project_json,
+            export::export_markdown,
+            report::export_report_pdf,
+            report::html::pdf_export,
+            recurrence::create_recurrence,
+            recurrence::list_recurrences,
+            recurrence::delete_recurrence,
+            timer::timer_start,
+            timer::timer_stop,
+            timer::timer_status,
+            timer::list_time_entries,
+            attachments::save_attachment,
+            attachments::save_clipboard_image,
+            attachments::set_drop_target,
+            attachments::clear_drop_target,
+            attachments::get_drop_denylist,
+            attachments::set_drop_denylist,
+            attachments::list_attachments,
+            attachments::read_attachment,
+            attachments::delete_attachment,
+            attachments::attachment_storage_stats,
+            attachments::cleanup_orphaned_attachments,
+            shell::run_shell_command_safe,
+            shell::stream_shell_output,
+            shell::kill_streamed_process,
+            jobs::spawn_background_job,
+            jobs::cancel_background_job,
+            jobs::list_background_jobs,
+            jobs::get_background_job,
+            files::read_file_text,
+            files::write_file_text,
+            files::read_file_lines,
+            files::file_hash,
+            files::verify_file_hash,
+            files::show_item_in_folder,
+            files::open_path,
+            fs_watch::watch_directory,
+            fs_watch::unwatch_directory,
+            fs_watch::watch_project,
+            fs_watch::unwatch_project,
+            drag::start_native_drag,
+            import::csv::import_tickets_csv,
+            import::github::github_import,
+            import::github::set_github_token,
+            import::github::has_github_token,
+            import::gitlab::gitlab_import,
+            import::gitlab::set_gitlab_token,
+            import::gitlab::has_gitlab_token,
+            import::jira::import_jira,
+            import::jira_cloud::jira_import,
+            import::jira_cloud::set_jira_credentials,
+            import::jira_cloud::has_jira_credentials,
+            import::trello::import_trello,
+            import::merge::merge_projects,
+            projects::list_projects,
+            projects::create_project,
+            projects::delete_project,
+            projects::rename_project,
+            encryption::is_project_encrypted,
+            encryption::encrypt_project,
+            encryption::open_encrypted_project,
+            encryption::change_project_passphrase,
+            project_probe::open_project_probe,
+            project_lock::acquire_project_lock,
+            project_lock::release_project_lock,
+            project_lock::force_unlock_project,
+            proxy::proxy_http_request,
+            proxy::add_proxy_allowlist_entry,
+            proxy::remove_proxy_allowlist_entry,
+            webhooks::list_webhooks,
+            webhooks::create_webhook,
+            webhooks::update_webhook,
+            webhooks::delete_webhook,
+            webhooks::fire_webhook,
+            webhooks::list_webhook_deliveries,
+            slack::slack_configure,
+            slack::slack_get_config,
+            slack::slack_notify,
+            slack::slack_test,
+            email::email_configure,
+            email::email_get_config,
+            email::email_send_ticket,
+            email::email_test,
+            recent::add_recent_file,
+            recent::get_recent_files,
+            recent::clear_recent_files,
+            screenshot::screenshot_window,
+            screenshot::screenshot_region,
+            search::search_tickets,
+            search::find_similar,
+            stats::ticket_stats,
+            spell::spell_check_text,
+            spell::get_spell_check_languages,
+            locale::get_system_locale,
+            locale::watch_locale_change,
+            startup::get_startup_timing,
+            db_check::check_project_db,
+            db_check::check_all_projects,
+            storage::get_storage_mode,
+            storage::migrate_app_data,
+            storage::get_app_data_override,
+            telemetry::ph_send_batch,
+            telemetry::ph_capture_exception,
+            telemetry::set_http_proxy,
+            telemetry::get_http_proxy,
+            telemetry::pin_tls_cert,
+            telemetry::set_telemetry_opt_out,
+            telemetry::telemetry_diagnostics,
+            telemetry::get_pool_health,
+            tray::set_tray_update_available,
+            tray::set_minimize_to_tray,
+            tray::get_minimize_to_tray,
+            tray::set_tray_menu_item_label,
+            tray::set_tray_menu_item_enabled,
+            tray::set_tray_icon,
+            tray::start_tray_animation,
+            tray::stop_tray_animation,
+            app_lock::hash_lock_pin,
+            app_lock::app_lock,
+            app_lock::app_unlock,
+            app_lock::is_app_locked,
+            archive_db::archive_tickets,
+            archive_db::search_archive,
+            archive_db::restore_from_archive,
+            window::take_cli_navigation,
+            window::get_screen_info,
+            window::get_monitor_at_window_position,
+            window::window_center,
+            window::window_ensure_on_screen,
+            window::set_window_title,
+            window::get_window_title,
+            window::set_window_vibrancy,
+            window::get_window_vibrancy,
+            window::create_window,
+            window::close_window,
+            window::list_windows,
+            window::emit_to_window,
+            window::broadcast_event,
+            window::set_window_icon,
+            window::reset_window_icon,
+        ])
+        .on_page_load(|webview, payload| {
+            // Tell the frontend about panics of the previous run once it is loaded
+            if webview.label() == window::MAIN_WINDOW
+                && payload.event() == tauri::webview::PageLoadEvent::Finished
+            {
+                crash::notify_previous_session(webview.app_handle());
             }
-        }))
-        .invoke_handler(tauri::generate_handler![force_quit, telemetry::ph_send_batch])
+        })
         .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                window::notify_window_closed(window.app_handle(), window.label());
+            }
+            // Files dropped on a ticket (see set_drop_target)
+            if let WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+                attachments::handle_file_drop(window.app_handle(), paths.clone());
+            }
             if let WindowEvent::CloseRequested { api, .. } = event {
-                // Prevent window close, hide to tray instead
-                api.prevent_close();
-                window.hide().ok();
+                let minimize_to_tray = tray::minimize_to_tray(window.app_handle());
+                match tray::close_action(window.label(), minimize_to_tray) {
+                    // Secondary windows (create_window) close normally
+                    tray::CloseAction::Close => {}
+                    tray::CloseAction::Quit => {
+                        // True close: same cleanup as force_quit, then exit
+                        api.prevent_close();
+                        window::save_window_state(window);
+                        let app = window.app_handle().clone();
+                        tauri::async_runtime::spawn(async move {
+                            shutdown::orderly_cleanup(&app).await;
+                            app.exit(0);
+                        });
+                    }
+                    tray::CloseAction::HideToTray => {
+                        // Hide to tray instead of closing, and fold the WAL into
+                        // the database file so sync clients see it up to date
+                        api.prevent_close();
+                        window::save_window_state(window);
+                        window.hide().ok();
+                        let app = window.app_handle().clone();
+                        tauri::async_runtime::spawn(async move {
+                            checkpoint::checkpoint_open_projects(&app).await;
+                        });
+                    }
+                }
             }
         })
-        .setup(|app| {
+        .setup(move |app| {
+            startup_timer.mark("plugin_init");
+
             // Debug logging (dev only)
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -77,68 +375,80 @@ pub fn run() {
                 )?;
             }
 
+            // Resolve the data directory (app data dir, or <exe_dir>/data when portable)
+            let data_dir = storage::resolve_data_dir(app.handle(), storage_mode)?;
+            crash::install_panic_hook(&data_dir);
+            app.manage(storage::StorageState {
+                mode: storage_mode,
+                data_dir: data_dir.clone(),
+            });
+            startup_timer.mark("storage_init");
+
             // Initialize telemetry DB (separate from the main app DB managed by tauri-plugin-sql)
-            let data_dir = app.path().app_data_dir()
-                .expect("app data dir unavailable");
-            let telemetry_pool = tauri::async_runtime::block_on(
-                telemetry::init_telemetry_db(&data_dir)
-            );
+            let telemetry_config = telemetry::TelemetryConfig::default();
+            let telemetry_pool = tauri::async_runtime::block_on(telemetry::init_telemetry_db(
+                &data_dir,
+                &telemetry_config,
+            ))?;
+            let proxy_settings =
+                tauri::async_runtime::block_on(telemetry::load_proxy_settings(&telemetry_pool));
+            // Never fall back to an unpinned client: refuse to start instead.
+            let tls_pin = telemetry::production_tls_pin()?;
+            let ingest_client = telemetry::build_ingest_client(&proxy_settings, tls_pin.as_ref())?;
             app.manage(telemetry::TelemetryState {
-                pool: telemetry_pool,
-                api_host: "https://eu.i.posthog.com".to_string(),
+                client: tokio::sync::RwLock::new(telemetry::build_http_client(&proxy_settings)),
+                ingest_client: tokio::sync::RwLock::new(ingest_client),
+                tls_pin: tokio::sync::RwLock::new(tls_pin),
+                pool: std::sync::RwLock::new(telemetry_pool),
+                db_path: data_dir.join(telemetry::TELEMETRY_DB_FILE),
+                api_host: telemetry::DEFAULT_API_HOST.to_string(),
+                config: telemetry_config,
+                pending_send: tokio::sync::Mutex::new(None),
+                last_health_check_at: std::sync::atomic::AtomicI64::new(0),
+                last_reconnect_at: std::sync::atomic::AtomicI64::new(0),
+                breaker: std::sync::Arc::default(),
             });
+            telemetry::init_pool_monitor(app.handle());
             // Flush any events that were queued before the last shutdown.
-            tauri::async_runtime::block_on(
-                telemetry::startup_flush(app.state::<telemetry::TelemetryState>())
-            );
-
-            // Tray menu items
-            let open_item = MenuItem::with_id(app, "open", "Ouvrir Ticketflow", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quitter", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&open_item, &quit_item])?;
-
-            // Build tray icon
-            TrayIconBuilder::new()
-                .icon(app.default_window_icon().unwrap().clone())
-                .tooltip("Ticketflow")
-                .menu(&menu)
-                .show_menu_on_left_click(false)
-                .on_menu_event(|app, event| {
-                    if let Some(window) = app.get_webview_window("main") {
-                        match event.id.as_ref() {
-                            "open" => {
-                                window.show().ok();
-                                window.unminimize().ok();
-                                window.set_focus().ok();
-                            }
-                            "quit" => {
-                                // Show window first so user can see the confirmation modal
-                                window.show().ok();
-                                window.unminimize().ok();
-                                window.set_focus().ok();
-                                // Then emit event for frontend to show confirmation
-                                window.emit("tray:quit-requested", ()).ok();
-                            }
-                            _ => {}
-                        }
-                    }
-                })
-                .on_tray_icon_event(|tray, event| {
-                    // Left click on tray icon = restore window
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        if let Some(window) = tray.app_handle().get_webview_window("main") {
-                            window.show().ok();
-                            window.unminimize().ok();
-                            window.set_focus().ok();
-                        }
-                    }
-                })
-                .build(app)?;
+            tauri::async_runtime::block_on(telemetry::startup_flush(
+                app.state::<telemetry::TelemetryState>(),
+            ));
+            // Move panics recorded by the previous run into telemetry.db
+            tauri::async_runtime::block_on(crash::init_crash_reports(app.handle(), &data_dir));
+            tauri::async_runtime::block_on(app_lock::init_app_lock(app.handle()));
+            tauri::async_runtime::block_on(timer::init_timer(app.handle()));
+            startup_timer.mark("telemetry_init");
+
+            // Restore the last window geometry and pick up --project
+            let args: Vec<String> = std::env::args().collect();
+            tauri::async_runtime::block_on(window::init_main_window(app.handle(), &args));
+            startup_timer.mark("window_init");
+
+            app.manage(clipboard::ClipboardState::default());
+            app.manage(fs_watch::FsWatchState::default());
+            app.manage(drag::DragState::default());
+            app.manage(export::ExportState::default());
+            app.manage(shell::ShellState::default());
+            app.manage(attachments::DropTargetState::default());
+            jobs::init_job_worker(app.handle());
+            app.manage(projects::ProjectListState::default());
+            app.manage(project_lock::ProjectLockState::default());
+
+            // Scheduled project backups (needs StorageState and TelemetryState),
+            // automatic compaction, recurring tickets, webhook and Slack deliveries
+            app.manage(backup::MaintenanceState::default());
+            backup_schedule::init_backup_scheduler(app.handle());
+            compaction::init_compaction(app.handle());
+            recurrence::init_recurrence_scheduler(app.handle());
+            webhooks::init_webhooks(app.handle());
+            slack::init_slack(app.handle());
+            startup_timer.mark("state_init");
+
+            tray::init_tray(app.handle())?;
+            startup_timer.mark("tray_init");
+            app.manage(startup::StartupState {
+                timer: std::sync::Mutex::new(startup_timer),
+            });
 
             Ok(())
         })
@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Returned when the OS locale is unset or `C`/`POSIX`.
+const FALLBACK_LOCALE: &str = "en";
+
+/// Event emitted with the new locale when the OS locale changes.
+const LOCALE_CHANGED_EVENT: &str = "locale:changed";
+
+/// How often `watch_locale_change` compares the OS locale.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Set once the watch task runs, so repeated calls don't start another.
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// The OS locale as a BCP 47 tag reduced to language and region (`fr-FR`,
+/// `en`): `NSLocale` on macOS, `GetUserDefaultLocaleName` on Windows,
+/// `LC_ALL`/`LC_MESSAGES`/`LANG` on Linux.
+#[tauri::command]
+pub fn get_system_locale() -> Result<String, AppError> {
+    Ok(system_locale())
+}
+
+/// Emit `locale:changed` with the new tag whenever the OS locale changes.
+/// The locale is compared every 30 seconds: Tauri exposes neither
+/// `WM_SETTINGCHANGE` nor the AppKit notification center to commands. On
+/// Linux the locale comes from the environment, so it never changes for a
+/// running app. Calling it again is a no-op.
+#[tauri::command]
+pub fn watch_locale_change(app: AppHandle) -> Result<(), AppError> {
+    if WATCHING.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut current = system_locale();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let locale = system_locale();
+            if locale != current {
+                log::info!("watch_locale_change: {} -> {}", current, locale);
+                app.emit(LOCALE_CHANGED_EVENT, &locale).ok();
+                current = locale;
+            }
+        }
+    });
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Normalized OS locale, `FALLBACK_LOCALE` when there is none.
+pub fn system_locale() -> String {
+    platform::locale()
+        .and_then(|locale| normalize(&locale))
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+}
+
+/// Reduce a platform locale (`fr_FR.UTF-8`, `zh-Hans_CN`, `de_DE@euro`) to
+/// `language[-REGION]`, dropping encoding, modifiers and script.
+fn normalize(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+    let mut parts = locale.split(['_', '-']);
+    let language = parts.next()?.to_ascii_lowercase();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let region =
+        parts.find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()));
+    Some(match region {
+        Some(region) => format!("{}-{}", language, region.to_ascii_uppercase()),
+        None => language,
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// First set of `LC_ALL`, `LC_MESSAGES` and `LANG` (`fr_FR.UTF-8`).
+    pub fn locale() -> Option<String> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::Win32::Globalization::{GetUserDefaultLocaleName, LOCALE_NAME_MAX_LENGTH};
+
+    /// The user's locale name (`fr-FR`).
+    pub fn locale() -> Option<String> {
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+        // SAFETY: the buffer has the documented maximum length.
+        let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+        (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2_foundation::NSLocale;
+
+    /// Identifier of the auto-updating current locale (`fr_FR`), which
+    /// follows changes made in System Settings while the app runs.
+    pub fn locale() -> Option<String> {
+        // `unused_unsafe`: these bindings are safe in recent
+        // objc2-foundation releases and unsafe in older ones.
+        #[allow(unused_unsafe)]
+        let identifier = unsafe { NSLocale::autoupdatingCurrentLocale().localeIdentifier() };
+        Some(identifier.to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    pub fn locale() -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_locale_is_language_and_region() {
+        let pattern = regex::Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap();
+        let locale = system_locale();
+        assert!(pattern.is_match(&locale), "{}", locale);
+    }
+
+    #[test]
+    fn platform_locales_are_normalized() {
+        for (raw, expected) in [
+            ("fr_FR.UTF-8", "fr-FR"),
+            ("de_DE@euro", "de-DE"),
+            ("zh-Hans_CN", "zh-CN"),
+            ("en-us", "en-US"),
+            ("pt", "pt"),
+            ("fil_PH", "fil-PH"),
+            ("sr_Latn", "sr"),
+        ] {
+            assert_eq!(normalize(raw).as_deref(), Some(expected), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn unset_and_malformed_locales_have_no_tag() {
+        for raw in ["", "C", "POSIX", "C.UTF-8", "x", "english_US", "f1_FR"] {
+            assert_eq!(normalize(raw), None, "{}", raw);
+        }
+    }
+}
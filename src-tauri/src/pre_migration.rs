@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit;
+use crate::backup::{self, MaintenanceState, RestoreReport};
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files::{self, PathScope};
+use crate::fs_watch;
+use crate::kv;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Subdirectory of the project's backup directory holding the snapshots
+/// taken before schema migrations.
+const PRE_MIGRATION_DIR: &str = "pre-migration";
+
+/// `kv_store` key of the pending snapshots (JSON map keyed by database path).
+const PENDING_KV: &str = "pre_migration_backups";
+
+/// Event emitted when a database is found stuck below the schema version
+/// of this build after a migration started.
+const MIGRATION_FAILED_EVENT: &str = "migration:failed";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A snapshot taken before migrating a database, kept until the database
+/// reaches `SUPPORTED_SCHEMA_VERSION`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingMigration {
+    backup_path: String,
+    from_version: i64,
+    /// Unix milliseconds.
+    created_at: i64,
+}
+
+/// Return value of `preflight_project_migration`.
+#[derive(Debug, Serialize)]
+pub struct MigrationPreflight {
+    pub schema_version: i64,
+    pub target_version: i64,
+    /// Migrations will run when the database is loaded.
+    pub pending: bool,
+    /// Snapshot to restore if the migration fails.
+    pub backup_path: Option<String>,
+    /// An earlier migration of this database did not complete.
+    pub failed: bool,
+}
+
+/// Payload of `migration:failed`.
+#[derive(Debug, Clone, Serialize)]
+struct MigrationFailed {
+    db_path: String,
+    backup_path: String,
+    from_version: i64,
+    schema_version: i64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Pre-flight check run by the frontend before `Database.load()`. When the
+/// database is behind the schema version of this build, it is snapshotted
+/// into `.backlog-backups/pre-migration/` first. If a snapshot is already
+/// pending (an earlier migration never completed), no new one is taken, so
+/// the half-migrated file never replaces the good copy, and
+/// `migration:failed` is emitted. `db_path` must be inside the data
+/// directory or fs scope.
+#[tauri::command]
+pub async fn preflight_project_migration(
+    db_path: String,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<MigrationPreflight, AppError> {
+    let target_version = project_db::SUPPORTED_SCHEMA_VERSION;
+    let path = &files::validate_path(&app, Path::new(&db_path))?;
+    if !path.is_file() {
+        // New project: the schema is created from scratch.
+        return Ok(MigrationPreflight {
+            schema_version: 0,
+            target_version,
+            pending: false,
+            backup_path: None,
+            failed: false,
+        });
+    }
+
+    with_timeout(
+        async {
+            let _guard = maintenance.lock.lock().await;
+            preflight(path, &state.pool(), |key, previous, schema_version| {
+                emit_failed(&app, key, previous, schema_version)
+            })
+            .await
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Put back the snapshot `preflight_project_migration` took of `db_path`.
+/// Like `restore_project_db`, refuses while the frontend has the database
+/// open unless `connection_closed` confirms `closeDatabase()` was called.
+/// Both `db_path` and the snapshot must be inside the data directory or fs
+/// scope.
+#[tauri::command]
+pub async fn restore_pre_migration_backup(
+    db_path: String,
+    connection_closed: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<RestoreReport, AppError> {
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let scope = PathScope::of(&app);
+            let target = &scope.validate(Path::new(&db_path))?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            let key = pending_key(target);
+            let mut pending = load_pending(&state.pool()).await?;
+            let snapshot = pending.get(&key).cloned().ok_or_else(|| {
+                AppError::Validation(format!("no pre-migration backup for {}", db_path))
+            })?;
+            if !connection_closed.unwrap_or(false)
+                && project_db::is_open_in_frontend(&app, target).await
+            {
+                return Err(AppError::Validation(
+                    "target database is open; close it before restoring".into(),
+                ));
+            }
+
+            let report =
+                backup::restore_database(&scope, Path::new(&snapshot.backup_path), target).await?;
+            pending.remove(&key);
+            save_pending(&state.pool(), &pending).await?;
+            Ok(report)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "restore_pre_migration_backup",
+        &db_path,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Called when the frontend migration runner finishes, successfully or not:
+/// forget the snapshots of databases that reached the current schema and
+/// emit `migration:failed` for the others.
+pub async fn settle_pending(app: &AppHandle) {
+    let state = app.state::<TelemetryState>();
+    settle(&state.pool(), |key, snapshot, version| {
+        emit_failed(app, key, snapshot, version)
+    })
+    .await;
+}
+
+/// `settle_pending` against the pending map of `pool`, calling `on_failed`
+/// for each database still behind.
+async fn settle(pool: &SqlitePool, mut on_failed: impl FnMut(&str, &PendingMigration, i64)) {
+    let mut pending = match load_pending(pool).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            log::warn!("settle_pending: {}", e);
+            return;
+        }
+    };
+
+    let mut changed = false;
+    let keys: Vec<String> = pending.keys().cloned().collect();
+    for key in keys {
+        let path = Path::new(&key);
+        if !path.is_file() {
+            pending.remove(&key);
+            changed = true;
+            continue;
+        }
+        match inspect(path).await {
+            Ok((version, _)) if version >= project_db::SUPPORTED_SCHEMA_VERSION => {
+                pending.remove(&key);
+                changed = true;
+            }
+            Ok((version, _)) => on_failed(&key, &pending[&key], version),
+            Err(e) => log::warn!("settle_pending: {}: {}", key, e),
+        }
+    }
+    if changed {
+        if let Err(e) = save_pending(pool, &pending).await {
+            log::warn!("settle_pending: {}", e);
+        }
+    }
+}
+
+/// Snapshot the existing database at `path` when it is behind
+/// `SUPPORTED_SCHEMA_VERSION`, recording it in the pending map of `pool`.
+/// Calls `on_failed` instead when a snapshot is already pending. The caller
+/// holds the maintenance lock.
+async fn preflight(
+    path: &Path,
+    pool: &SqlitePool,
+    on_failed: impl FnOnce(&str, &PendingMigration, i64),
+) -> Result<MigrationPreflight, AppError> {
+    let target_version = project_db::SUPPORTED_SCHEMA_VERSION;
+    let key = pending_key(path);
+    let mut pending = load_pending(pool).await?;
+    let (schema_version, has_data) = inspect(path).await?;
+
+    if schema_version >= target_version || !has_data {
+        if pending.remove(&key).is_some() {
+            save_pending(pool, &pending).await?;
+        }
+        return Ok(MigrationPreflight {
+            schema_version,
+            target_version,
+            pending: schema_version < target_version,
+            backup_path: None,
+            failed: false,
+        });
+    }
+
+    if let Some(previous) = pending.get(&key) {
+        on_failed(&key, previous, schema_version);
+        return Ok(MigrationPreflight {
+            schema_version,
+            target_version,
+            pending: true,
+            backup_path: Some(previous.backup_path.clone()),
+            failed: true,
+        });
+    }
+
+    let dest_dir = path
+        .parent()
+        .ok_or_else(|| AppError::Validation("database path has no parent".into()))?
+        .join(backup::BACKUP_DIR)
+        .join(PRE_MIGRATION_DIR);
+    let snapshot = backup::backup_database(path, Some(&dest_dir)).await?;
+    log::info!(
+        "preflight_project_migration: v{} -> v{}, snapshot {}",
+        schema_version,
+        target_version,
+        snapshot.path
+    );
+    pending.insert(
+        key,
+        PendingMigration {
+            backup_path: snapshot.path.clone(),
+            from_version: schema_version,
+            created_at: now_ms(),
+        },
+    );
+    save_pending(pool, &pending).await?;
+
+    Ok(MigrationPreflight {
+        schema_version,
+        target_version,
+        pending: true,
+        backup_path: Some(snapshot.path),
+        failed: false,
+    })
+}
+
+/// Schema version of a project database and whether it holds a project at
+/// all (an empty file gets its schema created, not migrated).
+async fn inspect(db_path: &Path) -> Result<(i64, bool), AppError> {
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let version = project_db::schema_version(&mut conn).await?;
+    let has_data: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'backlog_items')",
+    )
+    .fetch_one(&mut conn)
+    .await?;
+    Ok((version, has_data))
+}
+
+fn emit_failed(app: &AppHandle, db_path: &str, snapshot: &PendingMigration, schema_version: i64) {
+    log::warn!(
+        "migration of {} from v{} stopped at v{}; backup at {}",
+        db_path,
+        snapshot.from_version,
+        schema_version,
+        snapshot.backup_path
+    );
+    app.emit(
+        MIGRATION_FAILED_EVENT,
+        MigrationFailed {
+            db_path: db_path.to_string(),
+            backup_path: snapshot.backup_path.clone(),
+            from_version: snapshot.from_version,
+            schema_version,
+        },
+    )
+    .ok();
+}
+
+async fn load_pending(pool: &SqlitePool) -> Result<HashMap<String, PendingMigration>, AppError> {
+    let pending = kv::get(pool, PENDING_KV)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    Ok(pending)
+}
+
+async fn save_pending(
+    pool: &SqlitePool,
+    pending: &HashMap<String, PendingMigration>,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(pending)
+        .map_err(|e| AppError::Database(format!("cannot encode pending backups: {}", e)))?;
+    kv::set(pool, PENDING_KV, &json).await?;
+    Ok(())
+}
+
+/// Key of a database in the pending map: its canonical path, so the
+/// frontend's `/`-joined paths and OS paths agree.
+fn pending_key(db_path: &Path) -> String {
+    db_path
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(db_path))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{migrate, project_at};
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::{ConnectOptions, Connection};
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    async fn preflight_of(path: &Path, pool: &SqlitePool) -> (MigrationPreflight, usize) {
+        let mut failures = 0;
+        let preflight = preflight(path, pool, |_, _, _| failures += 1)
+            .await
+            .unwrap();
+        (preflight, failures)
+    }
+
+    #[tokio::test]
+    async fn outdated_databases_are_snapshotted_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool().await;
+        let path = project_at(dir.path(), 5).await;
+
+        let (first, failures) = preflight_of(&path, &pool).await;
+        assert_eq!(failures, 0);
+        assert!(first.pending && !first.failed);
+        assert_eq!(first.schema_version, 5);
+        let backup_path = first.backup_path.unwrap();
+        let snapshots = Path::new(backup::BACKUP_DIR).join(PRE_MIGRATION_DIR);
+        assert!(
+            Path::new(&backup_path)
+                .parent()
+                .unwrap()
+                .ends_with(&snapshots),
+            "{}",
+            backup_path
+        );
+        assert!(Path::new(&backup_path).is_file());
+
+        // The migration never completed: the first snapshot is kept.
+        let (second, failures) = preflight_of(&path, &pool).await;
+        assert_eq!(failures, 1);
+        assert!(second.pending && second.failed);
+        assert_eq!(second.backup_path.as_deref(), Some(backup_path.as_str()));
+        assert_eq!(load_pending(&pool).await.unwrap().len(), 1);
+
+        // Once migrated, the snapshot is forgotten.
+        migrate(&path, project_db::SUPPORTED_SCHEMA_VERSION).await;
+        let (done, failures) = preflight_of(&path, &pool).await;
+        assert_eq!(failures, 0);
+        assert!(!done.pending && done.backup_path.is_none());
+        assert!(load_pending(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn empty_databases_are_not_snapshotted() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool().await;
+        let path = dir.path().join(project_db::PROJECT_DB_FILE);
+        SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .unwrap()
+            .close()
+            .await
+            .unwrap();
+
+        let (preflight, _) = preflight_of(&path, &pool).await;
+        assert!(preflight.pending);
+        assert!(preflight.backup_path.is_none());
+        assert!(!dir.path().join(backup::BACKUP_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn settling_keeps_only_databases_still_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool().await;
+        let behind_dir = dir.path().join("behind");
+        let done_dir = dir.path().join("done");
+        std::fs::create_dir_all(&behind_dir).unwrap();
+        std::fs::create_dir_all(&done_dir).unwrap();
+        let behind = project_at(&behind_dir, 5).await;
+        let done = project_at(&done_dir, project_db::SUPPORTED_SCHEMA_VERSION).await;
+        let snapshot = PendingMigration {
+            backup_path: "snapshot.db".into(),
+            from_version: 5,
+            created_at: 0,
+        };
+        let pending = HashMap::from([
+            (pending_key(&behind), snapshot.clone()),
+            (pending_key(&done), snapshot.clone()),
+            (
+                dir.path().join("gone.db").to_string_lossy().into_owned(),
+                snapshot,
+            ),
+        ]);
+        save_pending(&pool, &pending).await.unwrap();
+
+        let mut failed = Vec::new();
+        settle(&pool, |key, _, version| {
+            failed.push((key.to_string(), version))
+        })
+        .await;
+        assert_eq!(failed, [(pending_key(&behind), 5)]);
+        let left: Vec<String> = load_pending(&pool).await.unwrap().into_keys().collect();
+        assert_eq!(left, [pending_key(&behind)]);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqliteSynchronous};
+use sqlx::ConnectOptions;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// How long a connection waits on a lock held by the frontend's
+/// tauri-plugin-sql pool before giving up with SQLITE_BUSY.
+const BUSY_TIMEOUT_SECS: u64 = 5;
+
+/// Latest project schema version (`PRAGMA user_version`) this build knows
+/// how to read. Keep in sync with the last entry in `src/db/migrations.ts`.
+pub const SUPPORTED_SCHEMA_VERSION: i64 = 13;
+
+/// File name of the database inside a project directory.
+pub const PROJECT_DB_FILE: &str = "backlog.db";
+
+/// URL prefix tauri-plugin-sql uses as `DbInstances` key (`sqlite:<path>`).
+const SQLITE_URL_PREFIX: &str = "sqlite:";
+
+/// First 16 bytes of every plaintext SQLite database. SQLCipher encrypts
+/// the whole file, header included.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// SQLCipher passphrases unlocked this session, by canonical database path.
+/// Filled by `encryption.rs`; every connection opened here applies the key
+/// of its file.
+static PROJECT_KEYS: Mutex<Option<HashMap<PathBuf, String>>> = Mutex::new(None);
+
+/// Project databases `open_project_probe` found not writable, by canonical
+/// path. `open_connection` refuses them with `ReadOnly`.
+static READ_ONLY_PROJECTS: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// `PRAGMA wal_autocheckpoint` set with `db_set_wal_autocheckpoint`, by
+/// canonical path. Applied to every connection opened here; the pragma is
+/// per connection and not stored in the file.
+static WAL_AUTOCHECKPOINT: Mutex<Option<HashMap<PathBuf, u32>>> = Mutex::new(None);
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Open a dedicated connection to an existing project database
+/// (`<projectPath>/backlog.db`). The frontend keeps its own pool open through
+/// tauri-plugin-sql; this second connection coexists with it under WAL.
+/// Fails with `ReadOnly` for a project the last probe found not writable.
+pub async fn open_connection(db_path: &Path) -> Result<SqliteConnection, AppError> {
+    if !db_path.is_file() {
+        return Err(AppError::Validation(format!(
+            "project database not found: {}",
+            db_path.display()
+        )));
+    }
+    if is_read_only(db_path) {
+        return Err(AppError::ReadOnly(db_path.display().to_string()));
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(false)
+        .busy_timeout(Duration::from_secs(BUSY_TIMEOUT_SECS));
+    let options = with_autocheckpoint(configure_options(options, false), db_path);
+    connect_keyed(options, db_path).await
+}
+
+/// Open a project database (or a backup of one) without write access.
+pub async fn open_read_only(db_path: &Path) -> Result<SqliteConnection, AppError> {
+    if !db_path.is_file() {
+        return Err(AppError::Validation(format!(
+            "database not found: {}",
+            db_path.display()
+        )));
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .read_only(true)
+        .busy_timeout(Duration::from_secs(BUSY_TIMEOUT_SECS));
+    let options = with_autocheckpoint(configure_options(options, true), db_path);
+    connect_keyed(options, db_path).await
+}
+
+/// Pragmas every connection the backend opens runs with: foreign keys
+/// enforced, and for writable connections WAL with `synchronous = NORMAL`.
+/// A read-only connection cannot change the journal mode, so it only gets
+/// `foreign_keys`.
+pub fn configure_options(options: SqliteConnectOptions, read_only: bool) -> SqliteConnectOptions {
+    let options = options.foreign_keys(true);
+    if read_only {
+        return options;
+    }
+    options
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+}
+
+/// Whether `db_path` exists but does not start with the plaintext SQLite
+/// header, i.e. it is (most likely) encrypted with SQLCipher.
+pub fn is_encrypted(db_path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(db_path) {
+        Ok(mut file) => {
+            std::io::Read::read_exact(&mut file, &mut header).is_ok() && &header != SQLITE_HEADER
+        }
+        Err(_) => false,
+    }
+}
+
+/// Remember (or, with `None`, forget) the SQLCipher passphrase of `db_path`
+/// for the rest of the session.
+pub fn set_project_key(db_path: &Path, key: Option<&str>) {
+    let mut keys = PROJECT_KEYS.lock().unwrap();
+    let keys = keys.get_or_insert_with(HashMap::new);
+    match key {
+        Some(key) => {
+            keys.insert(canonical(db_path), key.to_string());
+        }
+        None => {
+            keys.remove(&canonical(db_path));
+        }
+    }
+}
+
+/// Passphrase unlocked this session for `db_path`, if any.
+pub fn project_key(db_path: &Path) -> Option<String> {
+    PROJECT_KEYS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|keys| keys.get(&canonical(db_path)).cloned())
+}
+
+/// Record whether `db_path` is writable, as found by `open_project_probe`.
+pub fn set_read_only(db_path: &Path, read_only: bool) {
+    let mut projects = READ_ONLY_PROJECTS.lock().unwrap();
+    let projects = projects.get_or_insert_with(HashSet::new);
+    if read_only {
+        projects.insert(canonical(db_path));
+    } else {
+        projects.remove(&canonical(db_path));
+    }
+}
+
+/// Whether `db_path` was found not writable by the last probe.
+pub fn is_read_only(db_path: &Path) -> bool {
+    READ_ONLY_PROJECTS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|projects| projects.contains(&canonical(db_path)))
+}
+
+/// Use `pages` as the WAL autocheckpoint threshold of `db_path` for the
+/// rest of the session.
+pub fn set_wal_autocheckpoint(db_path: &Path, pages: u32) {
+    WAL_AUTOCHECKPOINT
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(canonical(db_path), pages);
+}
+
+/// WAL autocheckpoint threshold set this session for `db_path`, if any.
+pub fn wal_autocheckpoint(db_path: &Path) -> Option<u32> {
+    WAL_AUTOCHECKPOINT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|thresholds| thresholds.get(&canonical(db_path)).copied())
+}
+
+/// `PRAGMA key` value for `passphrase`: a quoted string literal, so SQLCipher
+/// derives the key from it.
+pub fn key_pragma(passphrase: &str) -> String {
+    format!("'{}'", passphrase.replace('\'', "''"))
+}
+
+/// Run `PRAGMA integrity_check` and return the reported problems (empty when
+/// the database is healthy).
+pub async fn integrity_problems(conn: &mut SqliteConnection) -> Result<Vec<String>, AppError> {
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check;")
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.into_iter().filter(|row| row != "ok").collect())
+}
+
+/// Schema version recorded by the frontend migration runner.
+pub async fn schema_version(conn: &mut SqliteConnection) -> Result<i64, AppError> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(version)
+}
+
+/// Paths of the project databases the frontend currently holds open through
+/// tauri-plugin-sql. The plugin's `close` shuts a pool down but leaves it in
+/// `DbInstances`, so closed pools are skipped.
+pub async fn open_database_paths(app: &AppHandle) -> Vec<PathBuf> {
+    let Some(instances) = app.try_state::<tauri_plugin_sql::DbInstances>() else {
+        return Vec::new();
+    };
+    let pools = instances.0.read().await;
+    pools
+        .iter()
+        .filter(|(_, pool)| match pool {
+            tauri_plugin_sql::DbPool::Sqlite(pool) => !pool.is_closed(),
+        })
+        .filter_map(|(url, _)| url.strip_prefix(SQLITE_URL_PREFIX))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Whether the frontend currently holds `db_path` open through
+/// tauri-plugin-sql.
+pub async fn is_open_in_frontend(app: &AppHandle, db_path: &Path) -> bool {
+    let target = canonical(db_path);
+    open_database_paths(app)
+        .await
+        .iter()
+        .any(|path| canonical(path) == target)
+}
+
+/// Fold the WAL back into the main database file. SQLite reports a busy
+/// checkpoint (readers still pinned on the WAL) as a result row rather than
+/// an error, so that case is only logged.
+pub async fn checkpoint_truncate(conn: &mut SqliteConnection) -> Result<(), AppError> {
+    let (busy, _, _): (i64, i64, i64) = sqlx::query_as("PRAGMA wal_checkpoint(TRUNCATE);")
+        .fetch_one(&mut *conn)
+        .await?;
+    if busy != 0 {
+        log::warn!("checkpoint_truncate: checkpoint incomplete, database busy");
+    }
+    Ok(())
+}
+
+/// Connect with the SQLCipher key of `db_path` when it is encrypted. A file
+/// without a registered key (a backup, or a project renamed since) is tried
+/// with every passphrase unlocked this session.
+async fn connect_keyed(
+    options: SqliteConnectOptions,
+    db_path: &Path,
+) -> Result<SqliteConnection, AppError> {
+    if !is_encrypted(db_path) {
+        return Ok(options.connect().await?);
+    }
+    let candidates: Vec<String> = match project_key(db_path) {
+        Some(key) => vec![key],
+        None => PROJECT_KEYS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|keys| keys.values().cloned().collect())
+            .unwrap_or_default(),
+    };
+    if candidates.is_empty() {
+        return Err(AppError::Unauthorized(format!(
+            "database is encrypted and locked: {}",
+            db_path.display()
+        )));
+    }
+
+    let mut last_error = None;
+    for key in candidates {
+        // SQLCipher only checks the key when the first page is read.
+        let attempt = async {
+            let mut conn = options
+                .clone()
+                .pragma("key", key_pragma(&key))
+                .connect()
+                .await?;
+            sqlx::query("SELECT count(*) FROM sqlite_master")
+                .execute(&mut conn)
+                .await?;
+            Ok::<_, sqlx::Error>(conn)
+        };
+        match attempt.await {
+            Ok(conn) => return Ok(conn),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error
+        .map(AppError::from)
+        .unwrap_or_else(|| AppError::Unauthorized("no passphrase matches".into())))
+}
+
+fn with_autocheckpoint(options: SqliteConnectOptions, db_path: &Path) -> SqliteConnectOptions {
+    match wal_autocheckpoint(db_path) {
+        Some(pages) => options.pragma("wal_autocheckpoint", pages.to_string()),
+        None => options,
+    }
+}
+
+/// Canonical form of a path for comparisons, falling back to the path as
+/// given when it cannot be resolved (e.g. it no longer exists).
+pub fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    /// An empty plaintext project database in `dir`.
+    async fn create_db(dir: &Path) -> PathBuf {
+        test_support::create_db(dir, SUPPORTED_SCHEMA_VERSION, "").await
+    }
+
+    async fn pragma_int(conn: &mut SqliteConnection, name: &str) -> i64 {
+        sqlx::query_scalar(&format!("PRAGMA {};", name))
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn orphan_rows_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path()).await;
+        let mut conn = open_connection(&path).await.unwrap();
+
+        let insert_section = "INSERT INTO sections (id, project_id, title, raw_header)
+                              VALUES (1, 42, 'Todo', '## Todo')";
+        let err = sqlx::query(insert_section)
+            .execute(&mut conn)
+            .await
+            .map_err(AppError::from)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("FOREIGN KEY constraint failed"),
+            "{}",
+            err
+        );
+
+        sqlx::query("INSERT INTO projects (id, name, path) VALUES (42, 'Demo', '/demo')")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query(insert_section)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn connections_run_with_the_configured_pragmas() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path()).await;
+
+        let mut conn = open_connection(&path).await.unwrap();
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode;")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(pragma_int(&mut conn, "foreign_keys").await, 1);
+        // NORMAL
+        assert_eq!(pragma_int(&mut conn, "synchronous").await, 1);
+
+        let mut read_only = open_read_only(&path).await.unwrap();
+        assert_eq!(pragma_int(&mut read_only, "foreign_keys").await, 1);
+    }
+}
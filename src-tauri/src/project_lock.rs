@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit;
+use crate::backup;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Suffix of the lock sidecar next to the project database.
+const LOCK_SUFFIX: &str = ".lock";
+
+/// How often the holder rewrites the heartbeat.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A lock whose heartbeat is older than this is left over from a crash (or
+/// a machine that went to sleep) and may be taken over. Leaves room for a
+/// file sync service to deliver a couple of heartbeats late.
+const STALE_AFTER_MS: i64 = 3 * 60 * 1000;
+
+/// Event emitted when another machine took over a lock we were holding.
+const LOCK_LOST_EVENT: &str = "project-lock:lost";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Content of a `<db>.lock` sidecar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub hostname: String,
+    pub pid: u32,
+    /// Unix milliseconds.
+    pub acquired_at: i64,
+    /// Unix milliseconds, refreshed every `HEARTBEAT_INTERVAL`.
+    pub heartbeat_at: i64,
+}
+
+/// Tauri managed state: heartbeat task of every lock this process holds,
+/// by database path.
+#[derive(Default)]
+pub struct ProjectLockState {
+    held: Mutex<HashMap<PathBuf, JoinHandle<()>>>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Take the advisory lock of a project before opening it, so two machines
+/// sharing the project through a synced folder don't write to it at once.
+/// Fails with `InUse` while another machine holds a fresh lock. A lock left
+/// by a crash is taken over once stale, or right away when it was written
+/// by this machine (the single-instance plugin rules out a second live
+/// Ticketflow here).
+#[tauri::command]
+pub async fn acquire_project_lock(
+    db_path: String,
+    app: AppHandle,
+    state: tauri::State<'_, ProjectLockState>,
+) -> Result<LockInfo, AppError> {
+    let db = PathBuf::from(&db_path);
+    files::validate_path(&app, &db)?;
+    let lock = lock_path(&db);
+    let info = lock_io(move || take_lock(&lock, now_ms())).await?;
+
+    let heartbeat = tauri::async_runtime::spawn(heartbeat(app.clone(), db.clone()));
+    if let Some(previous) = state.held.lock().unwrap().insert(db, heartbeat) {
+        previous.abort();
+    }
+    Ok(info)
+}
+
+/// Release the lock of a project when it is closed. Does nothing when the
+/// lock is not ours (anymore).
+#[tauri::command]
+pub async fn release_project_lock(
+    db_path: String,
+    app: AppHandle,
+    state: tauri::State<'_, ProjectLockState>,
+) -> Result<(), AppError> {
+    let db = PathBuf::from(&db_path);
+    files::validate_path(&app, &db)?;
+    stop_heartbeat(&state, &db);
+    let lock = lock_path(&db);
+    lock_io(move || {
+        remove_own_lock(&lock);
+        Ok(())
+    })
+    .await
+}
+
+/// Delete the lock of a project whatever its holder, for a lock the user
+/// knows to be stale (e.g. the other machine crashed and is off). The
+/// holder, if still running, stops its heartbeat and is notified.
+#[tauri::command]
+pub async fn force_unlock_project(
+    db_path: String,
+    app: AppHandle,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let db = PathBuf::from(&db_path);
+    let result = async {
+        files::validate_path(&app, &db)?;
+        let lock = lock_path(&db);
+        lock_io(move || {
+            let holder = read_lock(&lock);
+            match std::fs::remove_file(&lock) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            Ok(holder)
+        })
+        .await
+    }
+    .await;
+
+    let summary = match &result {
+        Ok(Some(holder)) => format!("{} (held by {})", db_path, holder.hostname),
+        _ => db_path.clone(),
+    };
+    audit::audit_log_command(
+        &state.pool(),
+        "force_unlock_project",
+        &summary,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result.map(|_| ())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Release every lock this process holds. Called from `orderly_cleanup`.
+pub fn release_all(app: &AppHandle) {
+    let Some(state) = app.try_state::<ProjectLockState>() else {
+        return;
+    };
+    let held: Vec<PathBuf> = state.held.lock().unwrap().keys().cloned().collect();
+    for db in held {
+        release(&state, &db);
+    }
+}
+
+fn release(state: &ProjectLockState, db: &Path) {
+    stop_heartbeat(state, db);
+    remove_own_lock(&lock_path(db));
+}
+
+fn stop_heartbeat(state: &ProjectLockState, db: &Path) {
+    if let Some(heartbeat) = state.held.lock().unwrap().remove(db) {
+        heartbeat.abort();
+    }
+}
+
+/// Delete `lock` if this process wrote it.
+fn remove_own_lock(lock: &Path) {
+    if read_lock(lock).is_some_and(|info| is_ours(&info)) {
+        if let Err(e) = std::fs::remove_file(lock) {
+            log::warn!("release_project_lock: {}: {}", lock.display(), e);
+        }
+    }
+}
+
+/// Write our lock to the sidecar `lock` unless another machine holds a
+/// lock there with a heartbeat fresh at `now`.
+fn take_lock(lock: &Path, now: i64) -> Result<LockInfo, AppError> {
+    let hostname = hostname();
+
+    if let Some(existing) = read_lock(lock) {
+        let fresh = now - existing.heartbeat_at < STALE_AFTER_MS;
+        if fresh && existing.hostname != hostname {
+            return Err(AppError::InUse(format!(
+                "in use by {} (last seen {}s ago)",
+                existing.hostname,
+                ((now - existing.heartbeat_at) / 1000).max(0)
+            )));
+        }
+        if existing.hostname != hostname || existing.pid != std::process::id() {
+            log::warn!(
+                "acquire_project_lock: taking over lock of {} (pid {}) on {}",
+                existing.hostname,
+                existing.pid,
+                lock.display()
+            );
+        }
+    }
+
+    let info = LockInfo {
+        hostname,
+        pid: std::process::id(),
+        acquired_at: now,
+        heartbeat_at: now,
+    };
+    write_lock(lock, &info)?;
+    Ok(info)
+}
+
+/// Refresh the heartbeat of `db`'s lock until aborted. Stops, and tells the
+/// frontend, when the lock was removed or taken over by someone else.
+async fn heartbeat(app: AppHandle, db: PathBuf) {
+    let lock = lock_path(&db);
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        match read_lock(&lock) {
+            Some(mut info) if is_ours(&info) => {
+                info.heartbeat_at = now_ms();
+                if let Err(e) = write_lock(&lock, &info) {
+                    log::warn!("heartbeat: {}: {}", lock.display(), e);
+                }
+            }
+            _ => {
+                log::warn!("heartbeat: lost the lock of {}", db.display());
+                if let Some(state) = app.try_state::<ProjectLockState>() {
+                    state.held.lock().unwrap().remove(&db);
+                }
+                app.emit(LOCK_LOST_EVENT, db.to_string_lossy().into_owned())
+                    .ok();
+                return;
+            }
+        }
+    }
+}
+
+/// Run lock-file I/O off the async workers, within `DB_TIMEOUT_MS`: the
+/// sidecar lives in a synced folder, which can hang on a network drive.
+async fn lock_io<T, F>(task: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    with_timeout(
+        async {
+            tauri::async_runtime::spawn_blocking(task)
+                .await
+                .map_err(|e| AppError::Io(format!("lock file task failed: {}", e)))?
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+fn is_ours(info: &LockInfo) -> bool {
+    info.pid == std::process::id() && info.hostname == hostname()
+}
+
+fn lock_path(db: &Path) -> PathBuf {
+    backup::append_suffix(db, LOCK_SUFFIX)
+}
+
+/// Parse a lock sidecar. A missing or unreadable file counts as no lock: a
+/// half-synced or truncated sidecar must not lock the user out.
+fn read_lock(lock: &Path) -> Option<LockInfo> {
+    let content = std::fs::read_to_string(lock).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Write the sidecar through a temporary file, so a reader (or a sync
+/// client) never sees a partial one.
+fn write_lock(lock: &Path, info: &LockInfo) -> Result<(), AppError> {
+    let json = serde_json::to_string(info)
+        .map_err(|e| AppError::Io(format!("cannot serialize lock: {}", e)))?;
+    let tmp = backup::append_suffix(lock, ".tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, lock)?;
+    Ok(())
+}
+
+fn hostname() -> String {
+    gethostname::gethostname().to_string_lossy().into_owned()
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn lock_of(hostname: &str, pid: u32, heartbeat_at: i64) -> LockInfo {
+        LockInfo {
+            hostname: hostname.to_string(),
+            pid,
+            acquired_at: heartbeat_at,
+            heartbeat_at,
+        }
+    }
+
+    #[test]
+    fn free_project_is_locked_by_this_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = lock_path(&dir.path().join("backlog.db"));
+        let info = take_lock(&lock, NOW).unwrap();
+        assert!(is_ours(&info));
+        assert_eq!(info.heartbeat_at, NOW);
+        assert!(read_lock(&lock).is_some_and(|info| is_ours(&info)));
+    }
+
+    #[test]
+    fn fresh_lock_of_another_host_is_in_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = lock_path(&dir.path().join("backlog.db"));
+        write_lock(&lock, &lock_of("laptop", 42, NOW - 30_000)).unwrap();
+
+        match take_lock(&lock, NOW) {
+            Err(AppError::InUse(message)) => {
+                assert_eq!(message, "in use by laptop (last seen 30s ago)")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(read_lock(&lock).unwrap().hostname, "laptop");
+    }
+
+    #[test]
+    fn stale_and_leftover_locks_are_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = lock_path(&dir.path().join("backlog.db"));
+
+        write_lock(&lock, &lock_of("laptop", 42, NOW - STALE_AFTER_MS)).unwrap();
+        assert!(is_ours(&take_lock(&lock, NOW).unwrap()));
+
+        // Left by a crashed run on this machine: taken over while fresh.
+        write_lock(&lock, &lock_of(&hostname(), std::process::id() + 1, NOW)).unwrap();
+        assert!(is_ours(&take_lock(&lock, NOW).unwrap()));
+
+        // A truncated sidecar, e.g. half-synced, does not count.
+        std::fs::write(&lock, b"{\"hostname\":\"lap").unwrap();
+        assert!(is_ours(&take_lock(&lock, NOW).unwrap()));
+    }
+
+    #[test]
+    fn release_only_removes_our_own_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("backlog.db");
+        let lock = lock_path(&db);
+        let state = ProjectLockState::default();
+
+        take_lock(&lock, NOW).unwrap();
+        release(&state, &db);
+        assert!(!lock.exists());
+
+        write_lock(&lock, &lock_of("laptop", 42, NOW)).unwrap();
+        release(&state, &db);
+        assert!(lock.exists());
+    }
+}
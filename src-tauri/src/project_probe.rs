@@ -0,0 +1,228 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::Connection;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::vacuum;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Emitted with a `ProjectReadOnly` when a probed project cannot be written.
+const PROJECT_READ_ONLY_EVENT: &str = "project:read-only";
+
+/// Primary result code of SQLITE_READONLY and its extended codes.
+const SQLITE_READONLY: i64 = 8;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `open_project_probe`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectProbe {
+    pub writable: bool,
+    /// Why the project cannot be written (`None` when it can).
+    pub reason: Option<String>,
+}
+
+/// Payload of `project:read-only`.
+#[derive(Debug, Clone, Serialize)]
+struct ProjectReadOnly {
+    db_path: String,
+    reason: String,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Check that a project can be written before it is loaded: the file's
+/// permissions, a file created next to it (SQLite needs its `-wal` and
+/// `-shm` there), and a write inside a transaction that is rolled back.
+/// A project that cannot be written is remembered: backend commands that
+/// write to it fail with `ReadOnly` until a later probe succeeds, and
+/// `project:read-only` is emitted so the UI can show a banner.
+#[tauri::command]
+pub async fn open_project_probe(db_path: String, app: AppHandle) -> Result<ProjectProbe, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let reason = probe(&db).await?;
+
+    let probe = ProjectProbe {
+        writable: reason.is_none(),
+        reason,
+    };
+    if let Some(reason) = &probe.reason {
+        log::warn!("open_project_probe: {}: {}", db.display(), reason);
+        project_db::set_read_only(&db, true);
+        app.emit(
+            PROJECT_READ_ONLY_EVENT,
+            ProjectReadOnly {
+                db_path,
+                reason: reason.clone(),
+            },
+        )
+        .ok();
+    }
+    Ok(probe)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Why `db_path` cannot be written, or `None` when it can.
+async fn probe(db_path: &Path) -> Result<Option<String>, AppError> {
+    if !db_path.is_file() {
+        return Err(AppError::Validation(format!(
+            "project database not found: {}",
+            db_path.display()
+        )));
+    }
+
+    // Probe with a connection open_connection would otherwise refuse.
+    project_db::set_read_only(db_path, false);
+    Ok(if std::fs::metadata(db_path)?.permissions().readonly() {
+        Some("the project file is read-only".to_string())
+    } else if let Some(reason) = directory_write_error(db_path) {
+        Some(reason)
+    } else {
+        with_timeout(database_write_error(db_path), DB_TIMEOUT_MS).await?
+    })
+}
+
+/// Create and delete a file next to the database; the error when the
+/// folder does not allow it.
+fn directory_write_error(db_path: &Path) -> Option<String> {
+    let dir = db_path.parent()?;
+    let mut name = db_path.file_name()?.to_os_string();
+    name.push(".probe");
+    let probe = dir.join(name);
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(file) => {
+            drop(file);
+            std::fs::remove_file(&probe).ok();
+            None
+        }
+        // Left over by an interrupted probe: the folder is writable.
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            std::fs::remove_file(&probe).ok();
+            None
+        }
+        Err(e) => Some(format!("the project folder is not writable ({})", e)),
+    }
+}
+
+/// Rewrite `user_version` with its own value in a transaction that is
+/// rolled back; the error when SQLite refuses the write. A database locked
+/// by another writer is writable.
+async fn database_write_error(db_path: &Path) -> Result<Option<String>, AppError> {
+    let mut conn = project_db::open_connection(db_path).await?;
+    let attempt = async {
+        let mut tx = conn.begin().await?;
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query(&format!("PRAGMA user_version = {};", version))
+            .execute(&mut *tx)
+            .await?;
+        tx.rollback().await
+    };
+    match attempt.await {
+        Ok(()) => Ok(None),
+        Err(e) if vacuum::is_busy(&e) => Ok(None),
+        Err(e) if is_read_only_error(&e) => Ok(Some(format!("SQLite cannot write to it ({})", e))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// SQLITE_READONLY, extended codes included.
+fn is_read_only_error(error: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(e) = error else {
+        return false;
+    };
+    e.code()
+        .and_then(|code| code.parse::<i64>().ok())
+        .is_some_and(|code| code & 0xff == SQLITE_READONLY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::path::PathBuf;
+
+    async fn create_db(dir: &Path) -> PathBuf {
+        test_support::create_db(dir, 7, "").await
+    }
+
+    #[tokio::test]
+    async fn writable_project_passes_and_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(dir.path()).await;
+        project_db::set_read_only(&db, true);
+        // Left over by an interrupted probe.
+        let leftover = dir
+            .path()
+            .join(format!("{}.probe", project_db::PROJECT_DB_FILE));
+        std::fs::write(&leftover, b"").unwrap();
+
+        assert_eq!(probe(&db).await.unwrap(), None);
+        assert!(!project_db::is_read_only(&db));
+        assert!(!leftover.exists());
+
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(version, 7);
+    }
+
+    #[tokio::test]
+    async fn read_only_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(dir.path()).await;
+        let mut permissions = std::fs::metadata(&db).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&db, permissions).unwrap();
+
+        assert_eq!(
+            probe(&db).await.unwrap().as_deref(),
+            Some("the project file is read-only")
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_project_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = probe(&dir.path().join("project.db")).await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(message) if message.contains("not found")));
+    }
+
+    #[tokio::test]
+    async fn read_only_errors_are_told_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(dir.path()).await;
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+
+        let err = sqlx::query("INSERT INTO user_preferences VALUES ('theme', 'dark')")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        assert!(is_read_only_error(&err));
+        let err = sqlx::query("INSERT INTO missing VALUES (1)")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        assert!(!is_read_only_error(&err));
+        assert!(!is_read_only_error(&sqlx::Error::RowNotFound));
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::import::open_project;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// First schema version with `project_settings`.
+const MIN_SCHEMA_VERSION: i64 = 13;
+
+/// Emitted with a `ProjectSettingChanged` whenever a setting is set or
+/// removed, so every window showing the project can refresh.
+const PROJECT_SETTINGS_CHANGED_EVENT: &str = "project-settings:changed";
+
+const MAX_KEY_CHARS: usize = 64;
+
+/// Upper bound on the serialized size of a free-form value.
+const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+const MAX_ASSIGNEE_CHARS: usize = 100;
+const MAX_PREFIX_CHARS: usize = 10;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Payload of `project-settings:changed`.
+#[derive(Debug, Clone, Serialize)]
+struct ProjectSettingChanged {
+    db_path: String,
+    key: String,
+    /// New value, or `null` when the setting was removed.
+    value: Value,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Value of a project setting, or `None` when it is not set.
+#[tauri::command]
+pub async fn project_get_setting(
+    db_path: String,
+    key: String,
+    app: AppHandle,
+) -> Result<Option<Value>, AppError> {
+    validate_key(&key)?;
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    read_setting(&db, &key).await
+}
+
+/// Set a project setting, or remove it when `value` is `null`. Known keys
+/// are checked and normalized (`default_assignee`, `ticket_prefix`,
+/// `column_order`); any other key takes free-form JSON. Returns the stored
+/// value.
+#[tauri::command]
+pub async fn project_set_setting(
+    db_path: String,
+    key: String,
+    value: Value,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<Value, AppError> {
+    validate_key(&key)?;
+    let value = validate_value(&key, value)?;
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+
+    let _guard = maintenance_guard(&maintenance)?;
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    store_setting(&db, &key, &value).await?;
+
+    app.emit(
+        PROJECT_SETTINGS_CHANGED_EVENT,
+        ProjectSettingChanged {
+            db_path,
+            key,
+            value: value.clone(),
+        },
+    )
+    .ok();
+    Ok(value)
+}
+
+/// Every setting of the project, by key.
+#[tauri::command]
+pub async fn project_settings_all(
+    db_path: String,
+    app: AppHandle,
+) -> Result<BTreeMap<String, Value>, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    read_settings(&db).await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Value of `key` in the project at `db_path`, or `None` when it is not set.
+async fn read_setting(db_path: &Path, key: &str) -> Result<Option<Value>, AppError> {
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, true, MIN_SCHEMA_VERSION).await?;
+            let value: Option<String> = sqlx::query_scalar(
+                "SELECT value FROM project_settings WHERE project_id = ? AND key = ?",
+            )
+            .bind(context.project_id)
+            .bind(key)
+            .fetch_optional(&mut conn)
+            .await?;
+            value.map(|json| decode(key, &json)).transpose()
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Every setting of the project at `db_path`, by key.
+async fn read_settings(db_path: &Path) -> Result<BTreeMap<String, Value>, AppError> {
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, true, MIN_SCHEMA_VERSION).await?;
+            let rows: Vec<(String, String)> = sqlx::query_as(
+                "SELECT key, value FROM project_settings WHERE project_id = ? ORDER BY key",
+            )
+            .bind(context.project_id)
+            .fetch_all(&mut conn)
+            .await?;
+            rows.into_iter()
+                .map(|(key, json)| {
+                    let value = decode(&key, &json)?;
+                    Ok((key, value))
+                })
+                .collect()
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Save `value` under `key`, or remove the key when `value` is `null`.
+/// Callers hold the maintenance lock and pause the project watch.
+async fn store_setting(db_path: &Path, key: &str, value: &Value) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, false, MIN_SCHEMA_VERSION).await?;
+            if value.is_null() {
+                sqlx::query("DELETE FROM project_settings WHERE project_id = ? AND key = ?")
+                    .bind(context.project_id)
+                    .bind(key)
+                    .execute(&mut conn)
+                    .await?;
+            } else {
+                sqlx::query(
+                    "INSERT INTO project_settings (project_id, key, value, updated_at)
+                     VALUES (?, ?, ?, ?)
+                     ON CONFLICT(project_id, key) DO UPDATE
+                     SET value = excluded.value, updated_at = excluded.updated_at",
+                )
+                .bind(context.project_id)
+                .bind(key)
+                .bind(value.to_string())
+                .bind(now_ms())
+                .execute(&mut conn)
+                .await?;
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Keys are lowercase ASCII letters, digits, `_`, `-` and `.`.
+fn validate_key(key: &str) -> Result<(), AppError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_CHARS
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "invalid setting key: {}",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Check and normalize the value of a known key; other keys only have a
+/// size limit. `null` passes through as a removal.
+fn validate_value(key: &str, value: Value) -> Result<Value, AppError> {
+    if value.is_null() {
+        return Ok(value);
+    }
+    let invalid = |reason: &str| AppError::Validation(format!("invalid {}: {}", key, reason));
+    let value = match key {
+        "default_assignee" => {
+            let assignee = value
+                .as_str()
+                .ok_or_else(|| invalid("expected a string"))?
+                .trim();
+            if assignee.is_empty() {
+                return Ok(Value::Null);
+            }
+            if assignee.chars().count() > MAX_ASSIGNEE_CHARS {
+                return Err(invalid("too long"));
+            }
+            Value::from(assignee)
+        }
+        "ticket_prefix" => {
+            let prefix = value
+                .as_str()
+                .ok_or_else(|| invalid("expected a string"))?
+                .trim()
+                .to_ascii_uppercase();
+            let valid = !prefix.is_empty()
+                && prefix.len() <= MAX_PREFIX_CHARS
+                && prefix.chars().all(|c| c.is_ascii_alphanumeric());
+            if !valid {
+                return Err(invalid("expected 1 to 10 letters or digits"));
+            }
+            Value::from(prefix)
+        }
+        "column_order" => {
+            let columns = value
+                .as_array()
+                .ok_or_else(|| invalid("expected an array of column names"))?;
+            let mut order: Vec<String> = Vec::with_capacity(columns.len());
+            for column in columns {
+                let column = column
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .ok_or_else(|| invalid("column names must be non-empty strings"))?;
+                if order.iter().any(|existing| existing == column) {
+                    return Err(invalid(&format!("duplicate column {}", column)));
+                }
+                order.push(column.to_string());
+            }
+            Value::from(order)
+        }
+        _ => value,
+    };
+    if value.to_string().len() > MAX_VALUE_BYTES {
+        return Err(invalid(&format!("larger than {} bytes", MAX_VALUE_BYTES)));
+    }
+    Ok(value)
+}
+
+fn decode(key: &str, json: &str) -> Result<Value, AppError> {
+    serde_json::from_str(json)
+        .map_err(|e| AppError::Database(format!("corrupt project setting {}: {}", key, e)))
+}
+
+fn maintenance_guard(
+    maintenance: &MaintenanceState,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation("a migration is in progress".into()));
+    }
+    maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+    use serde_json::json;
+
+    async fn project(dir: &Path) -> String {
+        let path = create_db(dir, crate::project_db::SUPPORTED_SCHEMA_VERSION, BOARD).await;
+        path.display().to_string()
+    }
+
+    #[tokio::test]
+    async fn settings_are_set_replaced_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        let path = Path::new(&db);
+
+        store_setting(path, "ticket_prefix", &json!("TF"))
+            .await
+            .unwrap();
+        store_setting(path, "ui.zoom", &json!({"level": 2}))
+            .await
+            .unwrap();
+        store_setting(path, "ticket_prefix", &json!("OPS"))
+            .await
+            .unwrap();
+        assert_eq!(
+            read_setting(path, "ticket_prefix").await.unwrap(),
+            Some(json!("OPS"))
+        );
+        let all = read_settings(path).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["ui.zoom"], json!({"level": 2}));
+
+        store_setting(path, "ticket_prefix", &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(read_setting(path, "ticket_prefix").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn corrupt_values_and_old_schemas_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        let mut conn = crate::project_db::open_connection(Path::new(&db))
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO project_settings (project_id, key, value, updated_at)
+             VALUES (1, 'broken', '{', 0)",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        let err = read_settings(Path::new(&db)).await.unwrap_err();
+        assert!(matches!(err, AppError::Database(_)), "{:?}", err);
+
+        sqlx::query("PRAGMA user_version = 12")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let err = read_setting(Path::new(&db), "broken").await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn keys_are_checked() {
+        for key in ["default_assignee", "ui.zoom", "plugin-x_2"] {
+            assert!(validate_key(key).is_ok(), "{}", key);
+        }
+        for key in ["", "Ticket_Prefix", "a b", &"k".repeat(MAX_KEY_CHARS + 1)] {
+            assert!(validate_key(key).is_err(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn known_values_are_normalized() {
+        let check = |key, value| validate_value(key, value).unwrap();
+        assert_eq!(check("default_assignee", json!("  ana ")), json!("ana"));
+        assert_eq!(check("default_assignee", json!("   ")), Value::Null);
+        assert_eq!(check("ticket_prefix", json!(" ops2 ")), json!("OPS2"));
+        assert_eq!(
+            check("column_order", json!([" Todo", "Done "])),
+            json!(["Todo", "Done"])
+        );
+        assert_eq!(
+            check("anything", json!([1, {"a": null}])),
+            json!([1, {"a": null}])
+        );
+        assert_eq!(check("ticket_prefix", Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn invalid_values_are_refused() {
+        for (key, value) in [
+            ("default_assignee", json!(42)),
+            (
+                "default_assignee",
+                json!("a".repeat(MAX_ASSIGNEE_CHARS + 1)),
+            ),
+            ("ticket_prefix", json!("")),
+            ("ticket_prefix", json!("OPS-1")),
+            ("ticket_prefix", json!("ABCDEFGHIJK")),
+            ("column_order", json!("Todo")),
+            ("column_order", json!(["Todo", ""])),
+            ("column_order", json!(["Todo", " Todo"])),
+            ("notes", json!("x".repeat(MAX_VALUE_BYTES))),
+        ] {
+            let result = validate_value(key, value.clone());
+            assert!(
+                matches!(result, Err(AppError::Validation(_))),
+                "{} = {} was accepted",
+                key,
+                value
+            );
+        }
+    }
+}
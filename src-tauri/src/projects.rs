@@ -0,0 +1,911 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::ConnectOptions;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::backup_schedule;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::encryption;
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::storage::StorageState;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Time allowed to open and read one database, so a file locked by another
+/// process does not hold up the whole listing.
+const FILE_TIMEOUT_MS: u64 = 3_000;
+
+/// Longest project name accepted by `create_project`.
+const MAX_NAME_CHARS: usize = 100;
+
+/// Characters Windows refuses in file names, path separators included.
+const FORBIDDEN_NAME_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves in every directory, with any extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Directory next to `backlog.db` holding the project's screenshots.
+const ASSETS_DIR: &str = ".backlog-assets";
+
+/// `projects` table as created by `initializeSchema` in `src/db/database.ts`;
+/// the rest of the schema is created when the frontend first loads the
+/// database.
+const PROJECTS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS projects (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      name TEXT NOT NULL,
+      path TEXT NOT NULL UNIQUE,
+      created_at TEXT DEFAULT (datetime('now')),
+      updated_at TEXT DEFAULT (datetime('now'))
+    )
+";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A project database found by `list_projects`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectSummary {
+    /// Name stored in the `projects` table, or the directory (file) name when
+    /// the database has none.
+    pub name: String,
+    /// Path of the database file.
+    pub path: String,
+    /// Size of the database file plus its WAL.
+    pub size_bytes: u64,
+    /// Unix milliseconds; the later of the database file and its WAL, since
+    /// writes land in the WAL until a checkpoint.
+    pub modified_at: i64,
+    pub ticket_count: i64,
+    pub schema_version: i64,
+    /// Why the file could not be read; the other fields then only describe
+    /// the file itself.
+    pub error: Option<String>,
+}
+
+/// Part of a summary read from inside the database, valid as long as the
+/// file keeps the same modification time and size.
+#[derive(Debug, Clone)]
+struct CachedContents {
+    modified_at: i64,
+    size_bytes: u64,
+    name: Option<String>,
+    ticket_count: i64,
+    schema_version: i64,
+    error: Option<String>,
+}
+
+/// Tauri managed state caching what `list_projects` read from each database.
+#[derive(Default)]
+pub struct ProjectListState {
+    cache: Mutex<HashMap<PathBuf, CachedContents>>,
+}
+
+impl ProjectListState {
+    /// The listing of `list_projects`, reading through and refreshing the
+    /// cache.
+    async fn list(&self, dir: &Path) -> Result<Vec<ProjectSummary>, AppError> {
+        if !dir.is_dir() {
+            return Err(AppError::Validation(format!(
+                "not a directory: {}",
+                dir.display()
+            )));
+        }
+
+        let paths = database_paths(dir)?;
+        let mut projects = Vec::with_capacity(paths.len());
+        let mut fresh = HashMap::with_capacity(paths.len());
+        for path in paths {
+            let Some((modified_at, size_bytes)) = file_stamp(&path) else {
+                continue;
+            };
+
+            let cached = self
+                .cache
+                .lock()
+                .unwrap()
+                .get(&path)
+                .filter(|c| c.modified_at == modified_at && c.size_bytes == size_bytes)
+                .cloned();
+            let contents = match cached {
+                Some(contents) => contents,
+                None => read_contents(&path, modified_at, size_bytes).await,
+            };
+
+            projects.push(ProjectSummary {
+                name: contents
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| fallback_name(&path)),
+                path: path.to_string_lossy().into_owned(),
+                size_bytes,
+                modified_at,
+                ticket_count: contents.ticket_count,
+                schema_version: contents.schema_version,
+                error: contents.error.clone(),
+            });
+            fresh.insert(path, contents);
+        }
+
+        // Entries of other directories are kept; the ones of files gone from
+        // this directory are dropped.
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|path, _| path.parent() != Some(dir) && !is_project_db_of(path, dir));
+        cache.extend(fresh);
+
+        projects.sort_by_key(|p| p.name.to_lowercase());
+        Ok(projects)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// List the project databases in `dir`: every `*.db` file directly inside it
+/// and the `backlog.db` of each project subdirectory. Each file is opened
+/// read-only for its project name, ticket count and schema version; these
+/// are cached per file and only read again when its modification time or
+/// size changes. Files that are not valid project databases are listed with
+/// an `error` instead of failing the command.
+#[tauri::command]
+pub async fn list_projects(
+    dir: String,
+    state: tauri::State<'_, ProjectListState>,
+) -> Result<Vec<ProjectSummary>, AppError> {
+    with_timeout(state.list(Path::new(&dir)), DB_TIMEOUT_MS).await
+}
+
+/// Create the project `name` as a new directory of `parent_dir` holding a
+/// `backlog.db` with its `projects` row, and return the project directory.
+/// The name must be a valid file name on every platform and not clash
+/// (case-insensitively) with an existing entry of `parent_dir`. Tables and
+/// migrations are applied by the frontend when it first opens the database,
+/// as for any other project.
+#[tauri::command]
+pub async fn create_project(
+    parent_dir: String,
+    name: String,
+    app: AppHandle,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<String, AppError> {
+    let name = name.trim().to_string();
+    let result = with_timeout(
+        async {
+            validate_name(&name)?;
+            let parent = Path::new(&parent_dir);
+            files::validate_path(&app, parent)?;
+            if !parent.is_dir() {
+                return Err(AppError::Validation(format!(
+                    "not a directory: {}",
+                    parent_dir
+                )));
+            }
+            ensure_unique(parent, &name)?;
+
+            let project_dir = parent.join(&name);
+            tokio::fs::create_dir(&project_dir).await?;
+            if let Err(e) = create_database(&project_dir).await {
+                // Do not leave a half-created project behind.
+                tokio::fs::remove_dir_all(&project_dir).await.ok();
+                return Err(e);
+            }
+            Ok(project_dir.to_string_lossy().into_owned())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "create_project",
+        &name,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Delete the project database at `db_path` together with its `-wal` and
+/// `-shm` files and the project's `.backlog-assets` directory. Everything is
+/// moved to the OS trash unless `to_trash` is `false`. The rest of the
+/// project directory (the user's own files, `.backlog-backups`) is left
+/// alone. Refuses while the frontend has the database open.
+#[tauri::command]
+pub async fn delete_project(
+    db_path: String,
+    to_trash: Option<bool>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let to_trash = to_trash.unwrap_or(true);
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let db = files::validate_path(&app, Path::new(&db_path))?;
+            if !db.is_file() {
+                return Err(AppError::Validation(format!(
+                    "project database not found: {}",
+                    db_path
+                )));
+            }
+            if project_db::is_open_in_frontend(&app, &db).await {
+                return Err(AppError::Validation(
+                    "project is open; close it before deleting".into(),
+                ));
+            }
+
+            let targets = project_files(&db);
+            if to_trash {
+                let targets = targets.clone();
+                tokio::task::spawn_blocking(move || trash::delete_all(targets))
+                    .await
+                    .map_err(|e| AppError::Io(e.to_string()))?
+                    .map_err(|e| AppError::Io(format!("cannot move to trash: {}", e)))?;
+            } else {
+                let encrypted = project_db::is_encrypted(&db);
+                for target in &targets {
+                    if target.is_dir() {
+                        tokio::fs::remove_dir_all(target).await?;
+                    } else {
+                        tokio::fs::remove_file(target).await?;
+                    }
+                }
+                // A trashed project keeps its keyring entry so it can still
+                // be unlocked once restored.
+                if encrypted {
+                    encryption::forget_stored_passphrase(&db);
+                    project_db::set_project_key(&db, None);
+                }
+            }
+            log::info!(
+                "delete_project: removed {} ({} entries, trash: {})",
+                db.display(),
+                targets.len(),
+                to_trash
+            );
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "delete_project",
+        &format!("{} (trash: {})", db_path, to_trash),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Rename the project directory `old_path` to `new_name` (same parent) and
+/// return the new path. The database must be closed in the frontend
+/// (`closeDatabase()`); its WAL is checkpointed first so the move is a
+/// single directory rename that takes `backlog.db`, its `-wal`/`-shm` files
+/// and `.backlog-assets` along atomically. The `projects` row, the recent
+/// projects and the scheduled backups directory follow the new name. If the
+/// database cannot be updated, the rename is undone.
+#[tauri::command]
+pub async fn rename_project(
+    old_path: String,
+    new_name: String,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<String, AppError> {
+    let new_name = new_name.trim().to_string();
+    let result = with_timeout(
+        async {
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            validate_name(&new_name)?;
+            let old_dir = Path::new(&old_path);
+            files::validate_path(&app, old_dir)?;
+            let old_db = old_dir.join(project_db::PROJECT_DB_FILE);
+            if !old_db.is_file() {
+                return Err(AppError::Validation(format!(
+                    "not a project directory: {}",
+                    old_path
+                )));
+            }
+            if project_db::is_open_in_frontend(&app, &old_db).await {
+                return Err(AppError::Validation(
+                    "project is open; close it before renaming".into(),
+                ));
+            }
+            let parent = old_dir
+                .parent()
+                .ok_or_else(|| AppError::Validation("project path has no parent".into()))?;
+            let old_name = old_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if new_name == old_name {
+                return Ok(old_path.clone());
+            }
+            // A case-only rename matches the project's own entry.
+            if !new_name.eq_ignore_ascii_case(&old_name) {
+                ensure_unique(parent, &new_name)?;
+            }
+
+            let new_dir = parent.join(&new_name);
+            move_project(old_dir, &new_dir).await?;
+            let new_path = new_dir.to_string_lossy().into_owned();
+
+            sqlx::query("UPDATE recent_files SET path = ?, name = ? WHERE path = ?")
+                .bind(&new_path)
+                .bind(&new_name)
+                .bind(&old_path)
+                .execute(&state.pool())
+                .await?;
+            rename_scheduled_backups(&app, &old_name, &new_name);
+            let new_db = new_dir.join(project_db::PROJECT_DB_FILE);
+            if project_db::is_encrypted(&new_db) {
+                if let Some(key) = project_db::project_key(&old_db) {
+                    project_db::set_project_key(&new_db, Some(&key));
+                }
+                encryption::move_stored_passphrase(&old_db, &new_db);
+            }
+            Ok(new_path)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "rename_project",
+        &format!("{} -> {}", old_path, new_name),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// A name that is a single valid file name on Windows, macOS and Linux.
+fn validate_name(name: &str) -> Result<(), AppError> {
+    let invalid = |reason: &str| {
+        Err(AppError::Validation(format!(
+            "invalid project name: {}",
+            reason
+        )))
+    };
+    if name.is_empty() {
+        return invalid("empty");
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return invalid("too long");
+    }
+    if name
+        .chars()
+        .any(|c| c.is_control() || FORBIDDEN_NAME_CHARS.contains(&c))
+    {
+        return invalid("contains a character not allowed in file names");
+    }
+    if name.starts_with('.') || name.ends_with('.') {
+        return invalid("starts or ends with a dot");
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        return invalid("reserved by the system");
+    }
+    Ok(())
+}
+
+/// Fail when `parent` already has an entry named `name`, ignoring case since
+/// the default file systems on Windows and macOS do.
+fn ensure_unique(parent: &Path, name: &str) -> Result<(), AppError> {
+    let wanted = name.to_lowercase();
+    for entry in std::fs::read_dir(parent)?.flatten() {
+        if entry.file_name().to_string_lossy().to_lowercase() == wanted {
+            return Err(AppError::Validation(format!(
+                "a project or folder named {} already exists",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Create `backlog.db` in WAL mode with the `projects` row.
+async fn create_database(project_dir: &Path) -> Result<(), AppError> {
+    let options = SqliteConnectOptions::new()
+        .filename(project_dir.join(project_db::PROJECT_DB_FILE))
+        .create_if_missing(true);
+    let mut conn = project_db::configure_options(options, false)
+        .connect()
+        .await?;
+    sqlx::query(PROJECTS_SCHEMA).execute(&mut conn).await?;
+    let name = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    sqlx::query(
+        "INSERT INTO projects (name, path, created_at, updated_at)
+         VALUES (?1, ?2, datetime('now'), datetime('now'))",
+    )
+    .bind(name)
+    .bind(project_dir.to_string_lossy().into_owned())
+    .execute(&mut conn)
+    .await?;
+    project_db::checkpoint_truncate(&mut conn).await?;
+    Ok(())
+}
+
+/// Checkpoint the database of the project directory `old_dir`, rename the
+/// directory to `new_dir` and point its `projects` row there. The rename is
+/// undone when the row cannot be updated.
+async fn move_project(old_dir: &Path, new_dir: &Path) -> Result<(), AppError> {
+    let mut conn = project_db::open_connection(&old_dir.join(project_db::PROJECT_DB_FILE)).await?;
+    project_db::checkpoint_truncate(&mut conn).await?;
+    drop(conn);
+
+    std::fs::rename(old_dir, new_dir).map_err(|e| rename_error(e, old_dir))?;
+    let old_path = old_dir.to_string_lossy();
+    let new_path = new_dir.to_string_lossy();
+    if let Err(e) = update_project_row(new_dir, &old_path, &new_path).await {
+        log::warn!("rename_project: undoing rename: {}", e);
+        if let Err(undo) = std::fs::rename(new_dir, old_dir) {
+            log::error!("rename_project: cannot undo rename: {}", undo);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Point the `projects` row of the moved database at its new directory.
+async fn update_project_row(
+    project_dir: &Path,
+    old_path: &str,
+    new_path: &str,
+) -> Result<(), AppError> {
+    let name = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut conn =
+        project_db::open_connection(&project_dir.join(project_db::PROJECT_DB_FILE)).await?;
+    sqlx::query(
+        "UPDATE projects SET name = ?, path = ?, updated_at = datetime('now') WHERE path = ?",
+    )
+    .bind(name)
+    .bind(new_path)
+    .bind(old_path)
+    .execute(&mut conn)
+    .await?;
+    project_db::checkpoint_truncate(&mut conn).await?;
+    Ok(())
+}
+
+/// Move `<data_dir>/backups/<old>` to the new project name. Best-effort: a
+/// failure only means older scheduled backups stay under the old name.
+fn rename_scheduled_backups(app: &AppHandle, old_name: &str, new_name: &str) {
+    let Some(storage) = app.try_state::<StorageState>() else {
+        return;
+    };
+    let from = backup_schedule::backups_dir(&storage.data_dir, old_name);
+    let to = backup_schedule::backups_dir(&storage.data_dir, new_name);
+    if from.is_dir() && !to.exists() {
+        if let Err(e) = std::fs::rename(&from, &to) {
+            log::warn!("rename_project: cannot move scheduled backups: {}", e);
+        }
+    }
+}
+
+/// Turn a failed directory rename into an error saying why nothing moved.
+fn rename_error(error: std::io::Error, from: &Path) -> AppError {
+    if is_cross_device(&error) {
+        AppError::Validation(format!(
+            "cannot rename {}: the new location is on another device",
+            from.display()
+        ))
+    } else if error.kind() == std::io::ErrorKind::AlreadyExists {
+        AppError::Validation("a project or folder with that name already exists".into())
+    } else {
+        AppError::Io(format!("cannot rename {}: {}", from.display(), error))
+    }
+}
+
+#[cfg(unix)]
+fn is_cross_device(error: &std::io::Error) -> bool {
+    error.raw_os_error() == Some(libc::EXDEV)
+}
+
+#[cfg(windows)]
+fn is_cross_device(error: &std::io::Error) -> bool {
+    // ERROR_NOT_SAME_DEVICE
+    error.raw_os_error() == Some(17)
+}
+
+/// The database, its `-wal`/`-shm` files and the assets directory, as far
+/// as they exist.
+fn project_files(db_path: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![db_path.to_path_buf()];
+    for suffix in ["-wal", "-shm"] {
+        let mut sibling = db_path.as_os_str().to_owned();
+        sibling.push(suffix);
+        paths.push(PathBuf::from(sibling));
+    }
+    if db_path
+        .file_name()
+        .is_some_and(|n| n == project_db::PROJECT_DB_FILE)
+    {
+        if let Some(dir) = db_path.parent() {
+            paths.push(dir.join(ASSETS_DIR));
+        }
+    }
+    paths.retain(|path| path.exists());
+    paths
+}
+
+/// `*.db` files in `dir` and `<subdir>/backlog.db` of its subdirectories.
+fn database_paths(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            let db = path.join(project_db::PROJECT_DB_FILE);
+            if db.is_file() {
+                paths.push(db);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "db") {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+async fn read_contents(path: &Path, modified_at: i64, size_bytes: u64) -> CachedContents {
+    let mut contents = CachedContents {
+        modified_at,
+        size_bytes,
+        name: None,
+        ticket_count: 0,
+        schema_version: 0,
+        error: None,
+    };
+    let result = with_timeout(
+        async {
+            let mut conn = project_db::open_read_only(path).await?;
+            let schema_version = project_db::schema_version(&mut conn).await?;
+            let name: Option<String> =
+                sqlx::query_scalar("SELECT name FROM projects ORDER BY id LIMIT 1")
+                    .fetch_optional(&mut conn)
+                    .await?;
+            let ticket_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM backlog_items")
+                .fetch_one(&mut conn)
+                .await?;
+            Ok((schema_version, name, ticket_count))
+        },
+        FILE_TIMEOUT_MS,
+    )
+    .await;
+
+    match result {
+        Ok((schema_version, name, ticket_count)) => {
+            contents.schema_version = schema_version;
+            contents.name = name.filter(|name| !name.trim().is_empty());
+            contents.ticket_count = ticket_count;
+        }
+        Err(e) => {
+            log::warn!("list_projects: {}: {}", path.display(), e);
+            contents.error = Some(e.to_string());
+        }
+    }
+    contents
+}
+
+/// Directory name for `<project>/backlog.db`, file stem otherwise.
+fn fallback_name(path: &Path) -> String {
+    let named = if path
+        .file_name()
+        .is_some_and(|n| n == project_db::PROJECT_DB_FILE)
+    {
+        path.parent().and_then(Path::file_name)
+    } else {
+        path.file_stem()
+    };
+    named
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_project_db_of(path: &Path, dir: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n == project_db::PROJECT_DB_FILE)
+        && path.parent().and_then(Path::parent) == Some(dir)
+}
+
+/// Modification time and size of a database together with its `-wal` file,
+/// or `None` when the database file is gone.
+fn file_stamp(db_path: &Path) -> Option<(i64, u64)> {
+    let meta = std::fs::metadata(db_path).ok()?;
+    let mut modified_at = meta.modified().map(unix_ms).unwrap_or_default();
+    let mut size_bytes = meta.len();
+
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    if let Ok(meta) = std::fs::metadata(&wal) {
+        modified_at = modified_at.max(meta.modified().map(unix_ms).unwrap_or_default());
+        size_bytes += meta.len();
+    }
+    Some((modified_at, size_bytes))
+}
+
+fn unix_ms(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    /// A project directory `name` in `parent` as `create_project` leaves
+    /// it, migrated as the frontend does on first load, with a section
+    /// holding `tickets` rows.
+    async fn create_test_project(parent: &Path, name: &str, tickets: i64) -> PathBuf {
+        let project_dir = parent.join(name);
+        std::fs::create_dir(&project_dir).unwrap();
+        create_database(&project_dir).await.unwrap();
+        let db = project_dir.join(project_db::PROJECT_DB_FILE);
+        test_support::migrate(&db, project_db::SUPPORTED_SCHEMA_VERSION).await;
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        sqlx::query(
+            "INSERT INTO sections (id, project_id, title, raw_header) VALUES (1, 1, 'Todo', '## Todo')",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        add_tickets(&mut conn, tickets).await;
+        db
+    }
+
+    async fn add_tickets(conn: &mut sqlx::SqliteConnection, count: i64) {
+        for _ in 0..count {
+            sqlx::query(
+                "INSERT INTO backlog_items (id, project_id, section_id, type, title, raw_markdown)
+                 SELECT 'BUG-' || (COUNT(*) + 1), 1, 1, 'BUG', 'Ticket', '' FROM backlog_items",
+            )
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        }
+        project_db::checkpoint_truncate(conn).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn projects_are_listed_with_their_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_project(dir.path(), "Zeta", 3).await;
+        std::fs::write(dir.path().join("notes.db"), b"not a database").unwrap();
+        std::fs::write(dir.path().join("readme.txt"), b"ignored").unwrap();
+
+        let state = ProjectListState::default();
+        let projects = state.list(dir.path()).await.unwrap();
+        assert_eq!(projects.len(), 2);
+
+        let broken = &projects[0];
+        assert_eq!(broken.name, "notes");
+        assert!(broken.error.is_some());
+        assert_eq!(broken.size_bytes, 14);
+
+        let zeta = &projects[1];
+        assert_eq!(zeta.name, "Zeta");
+        assert_eq!(zeta.path, db.to_string_lossy());
+        assert_eq!(zeta.ticket_count, 3);
+        assert_eq!(zeta.schema_version, project_db::SUPPORTED_SCHEMA_VERSION);
+        assert_eq!(zeta.error, None);
+        assert!(zeta.size_bytes > 0 && zeta.modified_at > 0);
+    }
+
+    #[tokio::test]
+    async fn cached_contents_are_refreshed_when_a_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_project(dir.path(), "Alpha", 1).await;
+        let notes = dir.path().join("notes.db");
+        std::fs::write(&notes, b"not a database").unwrap();
+
+        let state = ProjectListState::default();
+        assert_eq!(state.list(dir.path()).await.unwrap()[0].ticket_count, 1);
+        assert!(state.cache.lock().unwrap().contains_key(&notes));
+
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        add_tickets(&mut conn, 2).await;
+        drop(conn);
+        // The same page count and a coarse clock could leave the stamp as
+        // it was.
+        std::fs::File::options()
+            .write(true)
+            .open(&db)
+            .unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        std::fs::remove_file(&notes).unwrap();
+
+        let projects = state.list(dir.path()).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].ticket_count, 3);
+        assert!(!state.cache.lock().unwrap().contains_key(&notes));
+    }
+
+    #[tokio::test]
+    async fn listing_a_missing_directory_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ProjectListState::default();
+        let err = state.list(&dir.path().join("missing")).await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[test]
+    fn project_names_must_be_portable_file_names() {
+        for name in ["Client A", "Ticketflow 2.0", "Échéancier"] {
+            validate_name(name).unwrap();
+        }
+        let long = "x".repeat(MAX_NAME_CHARS + 1);
+        for name in [
+            "",
+            long.as_str(),
+            "a/b",
+            "a\\b",
+            "what?",
+            "tab\there",
+            ".hidden",
+            "trailing.",
+            "CON",
+            "com1.txt",
+            "Lpt9 .db",
+        ] {
+            assert!(
+                matches!(validate_name(name), Err(AppError::Validation(_))),
+                "{:?} was accepted",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn existing_names_clash_ignoring_case() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("Client A")).unwrap();
+        assert!(ensure_unique(dir.path(), "client a").is_err());
+        ensure_unique(dir.path(), "Client B").unwrap();
+    }
+
+    #[tokio::test]
+    async fn created_database_holds_its_project_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_dir = dir.path().join("Client A");
+        std::fs::create_dir(&project_dir).unwrap();
+        create_database(&project_dir).await.unwrap();
+
+        let db = project_dir.join(project_db::PROJECT_DB_FILE);
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+        let (name, path): (String, String) = sqlx::query_as("SELECT name, path FROM projects")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(name, "Client A");
+        assert_eq!(path, project_dir.to_string_lossy());
+    }
+
+    #[test]
+    fn deleted_files_are_the_database_its_siblings_and_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join(project_db::PROJECT_DB_FILE);
+        for file in ["backlog.db", "backlog.db-wal", "notes.md"] {
+            std::fs::write(dir.path().join(file), b"").unwrap();
+        }
+        std::fs::create_dir(dir.path().join(ASSETS_DIR)).unwrap();
+        std::fs::create_dir(dir.path().join(".backlog-backups")).unwrap();
+
+        let files = project_files(&db);
+        assert_eq!(
+            files,
+            vec![
+                db.clone(),
+                dir.path().join("backlog.db-wal"),
+                dir.path().join(ASSETS_DIR),
+            ]
+        );
+
+        // A loose `*.db` file has no assets directory of its own.
+        let loose = dir.path().join("other.db");
+        std::fs::write(&loose, b"").unwrap();
+        assert_eq!(project_files(&loose), vec![loose]);
+    }
+
+    #[tokio::test]
+    async fn renamed_project_moves_with_its_files_and_row() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_project(dir.path(), "Old", 2).await;
+        let old_dir = dir.path().join("Old");
+        std::fs::create_dir(old_dir.join(ASSETS_DIR)).unwrap();
+        let new_dir = dir.path().join("New");
+
+        move_project(&old_dir, &new_dir).await.unwrap();
+        assert!(!old_dir.exists());
+        assert!(new_dir.join(ASSETS_DIR).is_dir());
+        let db = new_dir.join(project_db::PROJECT_DB_FILE);
+        let mut conn = project_db::open_read_only(&db).await.unwrap();
+        let (name, path): (String, String) = sqlx::query_as("SELECT name, path FROM projects")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(name, "New");
+        assert_eq!(path, new_dir.to_string_lossy());
+    }
+
+    #[tokio::test]
+    async fn rename_is_undone_when_the_row_cannot_be_updated() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_project(dir.path(), "Old", 0).await;
+        let mut conn = project_db::open_connection(&db).await.unwrap();
+        sqlx::query("DROP TABLE projects")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let old_dir = dir.path().join("Old");
+        let new_dir = dir.path().join("New");
+        assert!(move_project(&old_dir, &new_dir).await.is_err());
+        assert!(db.is_file());
+        assert!(!new_dir.exists());
+    }
+
+    #[test]
+    fn failed_renames_say_why_nothing_moved() {
+        let from = Path::new("/projects/Old");
+        #[cfg(unix)]
+        let cross_device = std::io::Error::from_raw_os_error(libc::EXDEV);
+        #[cfg(windows)]
+        let cross_device = std::io::Error::from_raw_os_error(17);
+        match rename_error(cross_device, from) {
+            AppError::Validation(message) => assert!(message.contains("another device")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let exists = std::io::Error::from(std::io::ErrorKind::AlreadyExists);
+        assert!(matches!(
+            rename_error(exists, from),
+            AppError::Validation(_)
+        ));
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(rename_error(denied, from), AppError::Io(_)));
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::audit;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS, NETWORK_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::kv;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key holding the allowlist as a JSON array of host patterns.
+pub(crate) const ALLOWLIST_KV: &str = "proxy_allowlist";
+
+/// Responses larger than this are rejected rather than buffered.
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Response relayed back to the frontend by `proxy_http_request`.
+#[derive(Debug, Serialize)]
+pub struct ProxyResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Perform an HTTP request on behalf of the webview (no CORS), restricted to
+/// hosts matching the allowlist. Redirects are returned as-is, not followed.
+#[tauri::command]
+pub async fn proxy_http_request(
+    method: String,
+    url: String,
+    headers: HashMap<String, String>,
+    body: Option<String>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<ProxyResponse, AppError> {
+    let client = state.client.read().await.clone();
+    with_timeout(
+        send_proxied(&method, &url, &headers, body, &state.pool(), &client),
+        NETWORK_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Allow requests to hosts matching `pattern` (`api.github.com`, or
+/// `*.atlassian.net` for any subdomain).
+#[tauri::command]
+pub async fn add_proxy_allowlist_entry(
+    pattern: String,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<String>, AppError> {
+    let result = with_timeout(
+        async {
+            let pattern = normalize_pattern(&pattern)?;
+            let mut allowlist = load_allowlist(&state.pool()).await?;
+            if !allowlist.contains(&pattern) {
+                allowlist.push(pattern);
+                save_allowlist(&state.pool(), &allowlist).await?;
+            }
+            Ok(allowlist)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+    audit::audit_log_command(
+        &state.pool(),
+        "add_proxy_allowlist_entry",
+        &pattern,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Remove a pattern previously added with `add_proxy_allowlist_entry`.
+#[tauri::command]
+pub async fn remove_proxy_allowlist_entry(
+    pattern: String,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<String>, AppError> {
+    let result = with_timeout(
+        async {
+            let pattern = normalize_pattern(&pattern)?;
+            let mut allowlist = load_allowlist(&state.pool()).await?;
+            allowlist.retain(|entry| *entry != pattern);
+            save_allowlist(&state.pool(), &allowlist).await?;
+            Ok(allowlist)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+    audit::audit_log_command(
+        &state.pool(),
+        "remove_proxy_allowlist_entry",
+        &pattern,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+async fn send_proxied(
+    method: &str,
+    url: &str,
+    headers: &HashMap<String, String>,
+    body: Option<String>,
+    pool: &SqlitePool,
+    client: &reqwest::Client,
+) -> Result<ProxyResponse, AppError> {
+    let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .map_err(|_| AppError::Validation(format!("invalid HTTP method: {}", method)))?;
+
+    let url = reqwest::Url::parse(url)
+        .map_err(|e| AppError::Validation(format!("invalid URL: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::Validation(format!(
+            "unsupported URL scheme: {}",
+            url.scheme()
+        )));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::Validation("URL has no host".into()))?
+        .to_ascii_lowercase();
+
+    let allowlist = load_allowlist(pool).await?;
+    if !allowlist.iter().any(|pattern| host_matches(pattern, &host)) {
+        return Err(AppError::Unauthorized(format!(
+            "host not in proxy allowlist: {}",
+            host
+        )));
+    }
+
+    let mut request = client.request(method, url);
+    for (name, value) in headers {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| AppError::Validation(format!("invalid header name: {}", name)))?;
+        let value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| AppError::Validation(format!("invalid value for header {}", name)))?;
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+
+    let mut response = request.send().await?;
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_RESPONSE_BYTES as u64)
+    {
+        return Err(response_too_large());
+    }
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect();
+
+    // Read chunk by chunk so a missing or lying Content-Length can't make us
+    // buffer an unbounded body.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_RESPONSE_BYTES {
+            return Err(response_too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(ProxyResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+fn response_too_large() -> AppError {
+    AppError::Network(format!(
+        "response body exceeds {} bytes",
+        MAX_RESPONSE_BYTES
+    ))
+}
+
+async fn load_allowlist(pool: &SqlitePool) -> Result<Vec<String>, AppError> {
+    match kv::get(pool, ALLOWLIST_KV).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::Database(format!("corrupt proxy allowlist: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+async fn save_allowlist(pool: &SqlitePool, allowlist: &[String]) -> Result<(), AppError> {
+    let json = serde_json::to_string(allowlist)
+        .map_err(|e| AppError::Database(format!("cannot encode proxy allowlist: {}", e)))?;
+    kv::set(pool, ALLOWLIST_KV, &json).await?;
+    Ok(())
+}
+
+/// Lowercase and validate a host pattern: a bare host name, optionally
+/// prefixed with `*.` to match subdomains.
+pub(crate) fn normalize_pattern(pattern: &str) -> Result<String, AppError> {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let host = pattern.strip_prefix("*.").unwrap_or(&pattern);
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "invalid allowlist pattern: {}",
+            pattern
+        )));
+    }
+    Ok(pattern)
+}
+
+/// `*.example.com` matches `api.example.com` but not `example.com`.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.') && prefix.len() > 1),
+        None => pattern == host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    async fn pool(allowlist: &[&str]) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(&pool).await.unwrap();
+        let allowlist: Vec<String> = allowlist.iter().map(|p| p.to_string()).collect();
+        save_allowlist(&pool, &allowlist).await.unwrap();
+        pool
+    }
+
+    /// Answer one request on a local port with `response`, returning the
+    /// base URL and the request received.
+    fn http_server(response: Vec<u8>) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://localhost:{}", listener.local_addr().unwrap().port());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(&response).ok();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (url, handle)
+    }
+
+    async fn send(
+        pool: &SqlitePool,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ProxyResponse, AppError> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        send_proxied(method, url, &headers, None, pool, &client).await
+    }
+
+    #[tokio::test]
+    async fn allowed_hosts_are_relayed() {
+        let pool = pool(&["localhost"]).await;
+        let (url, server) =
+            http_server(b"HTTP/1.1 201 Created\r\nX-Id: 7\r\nContent-Length: 2\r\n\r\nok".to_vec());
+
+        let response = send(
+            &pool,
+            "post",
+            &format!("{}/items", url),
+            &[("X-Token", "t")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.headers["x-id"], "7");
+        assert_eq!(response.body, "ok");
+        let request = server.join().unwrap().to_ascii_lowercase();
+        assert!(request.starts_with("post /items http/1.1"), "{}", request);
+        assert!(request.contains("x-token: t"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn other_hosts_and_bad_requests_are_refused() {
+        let pool = pool(&["*.example.com"]).await;
+        let err = send(&pool, "GET", "http://example.com/", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)), "{:?}", err);
+
+        for (method, url, headers) in [
+            ("GE T", "https://api.example.com/", vec![]),
+            ("GET", "ftp://api.example.com/", vec![]),
+            ("GET", "not a url", vec![]),
+            ("GET", "https://api.example.com/", vec![("bad header", "x")]),
+        ] {
+            let err = send(&pool, method, url, &headers).await.unwrap_err();
+            assert!(
+                matches!(err, AppError::Validation(_)),
+                "{} {}: {:?}",
+                method,
+                url,
+                err
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_responses_are_rejected() {
+        let pool = pool(&["localhost"]).await;
+        let (url, _server) = http_server(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                MAX_RESPONSE_BYTES + 1
+            )
+            .into_bytes(),
+        );
+        let err = send(&pool, "GET", &url, &[]).await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Network(m) if m.contains("exceeds")),
+            "{:?}",
+            err
+        );
+
+        // Without a Content-Length, the body is counted as it arrives.
+        let mut response = b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_vec();
+        response.resize(response.len() + MAX_RESPONSE_BYTES + 1, b'x');
+        let (url, _server) = http_server(response);
+        let err = send(&pool, "GET", &url, &[]).await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Network(m) if m.contains("exceeds")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn corrupt_allowlist_is_a_database_error() {
+        let pool = pool(&[]).await;
+        assert!(load_allowlist(&pool).await.unwrap().is_empty());
+        kv::set(&pool, ALLOWLIST_KV, "{").await.unwrap();
+        let err = load_allowlist(&pool).await.unwrap_err();
+        assert!(matches!(err, AppError::Database(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn patterns_are_normalized_and_matched() {
+        assert_eq!(
+            normalize_pattern(" API.GitHub.com ").unwrap(),
+            "api.github.com"
+        );
+        assert_eq!(
+            normalize_pattern("*.Atlassian.net").unwrap(),
+            "*.atlassian.net"
+        );
+        for pattern in ["", "*.", "http://x.com", "a b", "*.*.com", "x.com/path"] {
+            assert!(normalize_pattern(pattern).is_err(), "{}", pattern);
+        }
+
+        assert!(host_matches("api.github.com", "api.github.com"));
+        assert!(!host_matches("api.github.com", "evil.api.github.com"));
+        assert!(host_matches("*.atlassian.net", "acme.atlassian.net"));
+        assert!(host_matches("*.atlassian.net", "a.b.atlassian.net"));
+        assert!(!host_matches("*.atlassian.net", "atlassian.net"));
+        assert!(!host_matches("*.atlassian.net", "evilatlassian.net"));
+        assert!(!host_matches("*.atlassian.net", ".atlassian.net"));
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Number of entries kept in `recent_files`.
+const MAX_RECENT_FILES: i64 = 10;
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// list of recently opened projects.
+pub const RECENT_FILES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS recent_files (
+        path TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        opened_at INTEGER NOT NULL
+    );
+";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A row of `recent_files`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecentFile {
+    pub path: String,
+    pub name: String,
+    /// Unix milliseconds.
+    pub opened_at: i64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Record that a project was opened. Re-opening a known path only bumps its
+/// `opened_at`; the oldest entries beyond the 10 most recent are dropped.
+#[tauri::command]
+pub async fn add_recent_file(
+    path: String,
+    name: String,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    if path.trim().is_empty() {
+        return Err(AppError::Validation("path must not be empty".into()));
+    }
+    let opened_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    with_timeout(
+        record(&state.pool(), &path, &name, opened_at),
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Return the recently opened projects, newest first. Entries whose path no
+/// longer exists are removed.
+#[tauri::command]
+pub async fn get_recent_files(
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<RecentFile>, AppError> {
+    with_timeout(existing(&state.pool()), DB_TIMEOUT_MS).await
+}
+
+/// Forget all recently opened projects.
+#[tauri::command]
+pub async fn clear_recent_files(state: tauri::State<'_, TelemetryState>) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            sqlx::query("DELETE FROM recent_files")
+                .execute(&state.pool())
+                .await?;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Add or bump `path`, keeping the `MAX_RECENT_FILES` most recent entries.
+async fn record(pool: &SqlitePool, path: &str, name: &str, opened_at: i64) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO recent_files (path, name, opened_at) VALUES (?, ?, ?)
+         ON CONFLICT(path) DO UPDATE SET name = excluded.name, opened_at = excluded.opened_at",
+    )
+    .bind(path)
+    .bind(name)
+    .bind(opened_at)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM recent_files WHERE path NOT IN (
+           SELECT path FROM recent_files ORDER BY opened_at DESC LIMIT ?
+         )",
+    )
+    .bind(MAX_RECENT_FILES)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Entries whose path still exists, newest first. The others are removed.
+async fn existing(pool: &SqlitePool) -> Result<Vec<RecentFile>, AppError> {
+    let entries = sqlx::query_as::<_, RecentFile>(
+        "SELECT path, name, opened_at FROM recent_files ORDER BY opened_at DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let (present, missing): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|entry| Path::new(&entry.path).exists());
+    for entry in &missing {
+        sqlx::query("DELETE FROM recent_files WHERE path = ?")
+            .bind(&entry.path)
+            .execute(pool)
+            .await?;
+    }
+    Ok(present)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(RECENT_FILES_SCHEMA)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn reopening_bumps_and_old_entries_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool().await;
+        let paths: Vec<String> = (0..12)
+            .map(|i| {
+                let path = dir.path().join(format!("project-{}", i));
+                std::fs::create_dir(&path).unwrap();
+                path.display().to_string()
+            })
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            record(&pool, path, "Project", i as i64).await.unwrap();
+        }
+        record(&pool, &paths[2], "Renamed", 100).await.unwrap();
+
+        let recent = existing(&pool).await.unwrap();
+        assert_eq!(recent.len(), MAX_RECENT_FILES as usize);
+        assert_eq!(recent[0].path, paths[2]);
+        assert_eq!(recent[0].name, "Renamed");
+        assert_eq!(recent[1].path, paths[11]);
+        assert!(recent
+            .iter()
+            .all(|entry| entry.path != paths[0] && entry.path != paths[1]));
+    }
+
+    #[tokio::test]
+    async fn missing_paths_are_forgotten() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool().await;
+        let kept = dir.path().display().to_string();
+        let gone = dir.path().join("gone").display().to_string();
+        record(&pool, &kept, "Kept", 1).await.unwrap();
+        record(&pool, &gone, "Gone", 2).await.unwrap();
+
+        let recent = existing(&pool).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].name, "Kept");
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recent_files")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+}
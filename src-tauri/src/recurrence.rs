@@ -0,0 +1,1010 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, SqliteConnection};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::import::{
+    lookup_code, open_project, resolve_type, ImportRow, ItemWriter, EFFORTS, PRIORITIES, SEVERITIES,
+};
+use crate::project_db;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// First schema version with `recurrences`.
+const MIN_SCHEMA_VERSION: i64 = 9;
+
+/// How often the scheduler wakes up to look for due recurrences.
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// With `catch_up = skip`, an occurrence this old is considered missed
+/// rather than late.
+const SKIP_GRACE_MS: i64 = 2 * CHECK_INTERVAL_SECS as i64 * 1000;
+
+/// With `catch_up = all`, at most this many missed occurrences (the most
+/// recent ones) are created at once.
+const MAX_CATCH_UP: usize = 50;
+
+const MAX_INTERVAL: u32 = 99;
+const MAX_TITLE_CHARS: usize = 200;
+
+/// Number of rule periods searched for the next occurrence. Monthly rules
+/// on the 29th-31st can skip months, and only repeat every 4 years for
+/// February 29th.
+const SEARCH_PERIODS: i64 = 48;
+
+const RECURRENCE_CREATED_EVENT: &str = "recurrence:created";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What the scheduler does with occurrences that fell due while the app
+/// was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatchUp {
+    /// Create a ticket for every missed occurrence (up to `MAX_CATCH_UP`).
+    All,
+    /// Create a single ticket for the most recent missed occurrence.
+    #[default]
+    Latest,
+    /// Create nothing for missed occurrences; only late ones still count.
+    Skip,
+}
+
+impl CatchUp {
+    fn as_str(self) -> &'static str {
+        match self {
+            CatchUp::All => "all",
+            CatchUp::Latest => "latest",
+            CatchUp::Skip => "skip",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "all" => CatchUp::All,
+            "skip" => CatchUp::Skip,
+            _ => CatchUp::Latest,
+        }
+    }
+}
+
+/// Arguments of `create_recurrence`: the template ticket and its schedule.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceInput {
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub title: String,
+    /// Title of the target section; the first section when absent.
+    pub section: Option<String>,
+    pub emoji: Option<String>,
+    pub component: Option<String>,
+    pub module: Option<String>,
+    pub severity: Option<String>,
+    pub priority: Option<String>,
+    pub effort: Option<String>,
+    pub description: Option<String>,
+    pub user_story: Option<String>,
+    /// RRULE subset, e.g. `FREQ=WEEKLY;BYDAY=MO,TH;BYHOUR=9`.
+    pub rule: String,
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    /// First possible occurrence in Unix milliseconds; now when absent.
+    pub starts_at: Option<i64>,
+}
+
+/// A row of `recurrences`, as returned by `list_recurrences`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Recurrence {
+    pub id: i64,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    pub item_type: String,
+    pub title: String,
+    pub section: Option<String>,
+    pub emoji: Option<String>,
+    pub component: Option<String>,
+    pub module: Option<String>,
+    pub severity: Option<String>,
+    pub priority: Option<String>,
+    pub effort: Option<String>,
+    pub description: Option<String>,
+    pub user_story: Option<String>,
+    pub rule: String,
+    pub catch_up: String,
+    /// Unix milliseconds.
+    pub starts_at: i64,
+    /// Unix milliseconds.
+    pub next_run: i64,
+}
+
+/// Payload of `recurrence:created`.
+#[derive(Debug, Clone, Serialize)]
+struct RecurrenceCreated {
+    db_path: String,
+    recurrence_id: i64,
+    ticket_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A parsed schedule: `FREQ` (DAILY, WEEKLY or MONTHLY) with optional
+/// `INTERVAL`, `BYDAY`, `BYMONTHDAY`, `BYHOUR` and `BYMINUTE`, in local
+/// time. Unset parts default to those of the start date.
+#[derive(Debug)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    by_day: Vec<Weekday>,
+    by_month_day: Option<u32>,
+    by_hour: Option<u32>,
+    by_minute: Option<u32>,
+}
+
+/// Tauri managed state for the recurrence scheduler.
+pub struct RecurrenceState {
+    /// Wakes the scheduler early when a recurrence is created.
+    wake: tokio::sync::Notify,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Register `RecurrenceState` and spawn the scheduler task. Called once
+/// from `lib.rs` during app setup, after `MaintenanceState` is managed.
+pub fn init_recurrence_scheduler(app: &AppHandle) {
+    app.manage(RecurrenceState {
+        wake: tokio::sync::Notify::new(),
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            run_due_recurrences(&app).await;
+            let state = app.state::<RecurrenceState>();
+            let _ = tokio::time::timeout(
+                Duration::from_secs(CHECK_INTERVAL_SECS),
+                state.wake.notified(),
+            )
+            .await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Add a recurring ticket to the project at `db_path`. The template is
+/// validated like an imported row; tickets are created by the scheduler
+/// while the project is open.
+#[tauri::command]
+pub async fn create_recurrence(
+    db_path: String,
+    recurrence: RecurrenceInput,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, RecurrenceState>,
+) -> Result<Recurrence, AppError> {
+    let rule = Rule::parse(&recurrence.rule).map_err(AppError::Validation)?;
+    let title = recurrence.title.trim();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+        return Err(AppError::Validation(format!(
+            "title must be 1 to {} characters",
+            MAX_TITLE_CHARS
+        )));
+    }
+    let severity = optional_code(recurrence.severity.as_deref(), &SEVERITIES, "severity")?;
+    let effort = optional_code(recurrence.effort.as_deref(), &EFFORTS, "effort")?;
+    let priority = match recurrence.priority.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(value) => Some(
+            priority_code(value)
+                .ok_or_else(|| AppError::Validation(format!("unknown priority: {}", value)))?,
+        ),
+    };
+
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+
+    let starts_at = recurrence.starts_at.unwrap_or_else(now_ms);
+    let next_run = rule
+        .next_after(starts_at, starts_at - 1)
+        .ok_or_else(|| AppError::Validation(format!("rule never occurs: {}", recurrence.rule)))?;
+
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation("a migration is in progress".into()));
+    }
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    let created = with_timeout(
+        async {
+            let (mut conn, context) = open_project(&db, false, MIN_SCHEMA_VERSION).await?;
+            let item_type =
+                resolve_type(&context, &recurrence.item_type).map_err(AppError::Validation)?;
+            let id = sqlx::query(
+                "INSERT INTO recurrences (
+                   project_id, type, title, section, emoji, component, module,
+                   severity, priority, effort, description, user_story,
+                   rule, catch_up, starts_at, next_run
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(context.project_id)
+            .bind(&item_type)
+            .bind(title)
+            .bind(non_empty(recurrence.section.as_deref()))
+            .bind(non_empty(recurrence.emoji.as_deref()))
+            .bind(non_empty(recurrence.component.as_deref()))
+            .bind(non_empty(recurrence.module.as_deref()))
+            .bind(severity)
+            .bind(priority)
+            .bind(effort)
+            .bind(non_empty(recurrence.description.as_deref()))
+            .bind(non_empty(recurrence.user_story.as_deref()))
+            .bind(recurrence.rule.trim().to_ascii_uppercase())
+            .bind(recurrence.catch_up.as_str())
+            .bind(starts_at)
+            .bind(next_run)
+            .execute(&mut conn)
+            .await?
+            .last_insert_rowid();
+            let created = sqlx::query_as(&select_sql("WHERE id = ?"))
+                .bind(id)
+                .fetch_one(&mut conn)
+                .await?;
+            Ok(created)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    state.wake.notify_one();
+    Ok(created)
+}
+
+/// Recurrences of the project at `db_path`, soonest first.
+#[tauri::command]
+pub async fn list_recurrences(
+    db_path: String,
+    app: AppHandle,
+) -> Result<Vec<Recurrence>, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(&db, true, MIN_SCHEMA_VERSION).await?;
+            let recurrences =
+                sqlx::query_as(&select_sql("WHERE project_id = ? ORDER BY next_run, id"))
+                    .bind(context.project_id)
+                    .fetch_all(&mut conn)
+                    .await?;
+            Ok(recurrences)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Delete a recurrence. Tickets it already created are kept.
+#[tauri::command]
+pub async fn delete_recurrence(
+    db_path: String,
+    recurrence_id: i64,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+) -> Result<(), AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let _guard = maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))?;
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation("a migration is in progress".into()));
+    }
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(&db, false, MIN_SCHEMA_VERSION).await?;
+            let deleted = sqlx::query("DELETE FROM recurrences WHERE id = ? AND project_id = ?")
+                .bind(recurrence_id)
+                .bind(context.project_id)
+                .execute(&mut conn)
+                .await?
+                .rows_affected();
+            if deleted == 0 {
+                return Err(AppError::Validation(format!(
+                    "no recurrence with id {}",
+                    recurrence_id
+                )));
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Create the tickets of every due recurrence in the open project
+/// databases. Skipped entirely while a restore or migration is running.
+/// Databases outside the data directory and fs scope are never opened.
+async fn run_due_recurrences(app: &AppHandle) {
+    let maintenance = app.state::<MaintenanceState>();
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        log::info!("run_due_recurrences: migration in progress, skipping");
+        return;
+    }
+    let Ok(_guard) = maintenance.lock.try_lock() else {
+        log::info!("run_due_recurrences: restore in progress, skipping");
+        return;
+    };
+    let _paused = fs_watch::pause_project_watch(app);
+
+    for db_path in project_db::open_database_paths(app).await {
+        let db_path = match files::validate_path(app, &db_path) {
+            Ok(db_path) => db_path,
+            Err(e) => {
+                log::warn!("run_due_recurrences: skipping {}: {}", db_path.display(), e);
+                continue;
+            }
+        };
+        let (mut conn, context) = match open_project(&db_path, false, MIN_SCHEMA_VERSION).await {
+            Ok(opened) => opened,
+            Err(AppError::Validation(e)) => {
+                log::debug!("run_due_recurrences: skipping {}: {}", db_path.display(), e);
+                continue;
+            }
+            Err(e) => {
+                log::warn!(
+                    "run_due_recurrences: cannot open {}: {}",
+                    db_path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        let now = now_ms();
+        let due: Vec<Recurrence> = match sqlx::query_as(&select_sql(
+            "WHERE project_id = ? AND next_run <= ? ORDER BY next_run, id",
+        ))
+        .bind(context.project_id)
+        .bind(now)
+        .fetch_all(&mut conn)
+        .await
+        {
+            Ok(due) => due,
+            Err(e) => {
+                log::warn!(
+                    "run_due_recurrences: query failed for {}: {}",
+                    db_path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        let mut writer = ItemWriter::new(&context);
+        for recurrence in due {
+            match run_recurrence(&mut conn, &mut writer, &recurrence, now).await {
+                Ok(ticket_ids) if !ticket_ids.is_empty() => {
+                    app.emit(
+                        RECURRENCE_CREATED_EVENT,
+                        RecurrenceCreated {
+                            db_path: db_path.to_string_lossy().into_owned(),
+                            recurrence_id: recurrence.id,
+                            ticket_ids,
+                        },
+                    )
+                    .ok();
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    // The writer's section and position caches may include
+                    // rows of the rolled-back transaction.
+                    log::error!(
+                        "run_due_recurrences: recurrence {} of {} failed: {}",
+                        recurrence.id,
+                        db_path.display(),
+                        e
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Create the tickets due for `recurrence` at `now` according to its
+/// catch-up policy and advance `next_run`, in one transaction. A rule with
+/// no further occurrence is deleted. Returns the new ticket ids.
+async fn run_recurrence(
+    conn: &mut SqliteConnection,
+    writer: &mut ItemWriter,
+    recurrence: &Recurrence,
+    now: i64,
+) -> Result<Vec<String>, AppError> {
+    let rule = Rule::parse(&recurrence.rule).map_err(AppError::Validation)?;
+
+    let mut due = VecDeque::new();
+    let mut next = Some(recurrence.next_run);
+    while let Some(at) = next.filter(|&at| at <= now) {
+        if due.len() == MAX_CATCH_UP {
+            due.pop_front();
+        }
+        due.push_back(at);
+        next = rule.next_after(recurrence.starts_at, at);
+    }
+
+    let occurrences = match CatchUp::parse(&recurrence.catch_up) {
+        CatchUp::All => due.len(),
+        CatchUp::Latest => due.len().min(1),
+        CatchUp::Skip => due
+            .back()
+            .filter(|&&at| now - at <= SKIP_GRACE_MS)
+            .map_or(0, |_| 1),
+    };
+
+    let row = ImportRow {
+        item_type: recurrence.item_type.clone(),
+        title: recurrence.title.clone(),
+        status: recurrence.section.clone(),
+        emoji: recurrence.emoji.clone(),
+        component: recurrence.component.clone(),
+        module: recurrence.module.clone(),
+        severity: recurrence
+            .severity
+            .as_deref()
+            .and_then(|value| lookup_code(&SEVERITIES, value)),
+        priority: recurrence.priority.as_deref().and_then(priority_code),
+        effort: recurrence
+            .effort
+            .as_deref()
+            .and_then(|value| lookup_code(&EFFORTS, value)),
+        description: recurrence.description.clone(),
+        user_story: recurrence.user_story.clone(),
+        ..Default::default()
+    };
+
+    let mut tx = conn.begin().await?;
+    let mut ticket_ids = Vec::with_capacity(occurrences);
+    for _ in 0..occurrences {
+        ticket_ids.push(writer.insert_item(&mut tx, &row).await?);
+    }
+    match next {
+        Some(next_run) => {
+            sqlx::query("UPDATE recurrences SET next_run = ? WHERE id = ?")
+                .bind(next_run)
+                .bind(recurrence.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        None => {
+            log::info!(
+                "run_recurrence: rule of recurrence {} has no further occurrence",
+                recurrence.id
+            );
+            sqlx::query("DELETE FROM recurrences WHERE id = ?")
+                .bind(recurrence.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(ticket_ids)
+}
+
+impl Rule {
+    /// Parse an RRULE subset such as `FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=15`.
+    /// A leading `RRULE:` is accepted.
+    fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().to_ascii_uppercase();
+        let text = text.strip_prefix("RRULE:").unwrap_or(&text);
+
+        let mut frequency = None;
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: None,
+            by_hour: None,
+            by_minute: None,
+        };
+        for part in text.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid rule part: {}", part))?;
+            match key {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("unsupported FREQ: {}", value)),
+                    })
+                }
+                "INTERVAL" => rule.interval = parse_number(key, value, 1, MAX_INTERVAL)?,
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|day| {
+                            parse_weekday(day).ok_or_else(|| format!("invalid BYDAY: {}", day))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => rule.by_month_day = Some(parse_number(key, value, 1, 31)?),
+                "BYHOUR" => rule.by_hour = Some(parse_number(key, value, 0, 23)?),
+                "BYMINUTE" => rule.by_minute = Some(parse_number(key, value, 0, 59)?),
+                _ => return Err(format!("unsupported rule part: {}", key)),
+            }
+        }
+
+        rule.frequency = frequency.ok_or("rule has no FREQ")?;
+        if rule.by_month_day.is_some() && rule.frequency != Frequency::Monthly {
+            return Err("BYMONTHDAY requires FREQ=MONTHLY".into());
+        }
+        if !rule.by_day.is_empty() && rule.frequency == Frequency::Monthly {
+            return Err("BYDAY is not supported with FREQ=MONTHLY".into());
+        }
+        Ok(rule)
+    }
+
+    /// First occurrence strictly after `after` (Unix ms) of the rule
+    /// anchored at `starts_at`, or `None` when there is none within
+    /// `SEARCH_PERIODS` periods.
+    fn next_after(&self, starts_at: i64, after: i64) -> Option<i64> {
+        let start = Local.timestamp_millis_opt(starts_at).single()?;
+        let start_date = start.date_naive();
+        let hour = self.by_hour.unwrap_or(start.hour());
+        let minute = self.by_minute.unwrap_or(start.minute());
+
+        let after = after.max(starts_at - 1);
+        let from = Local
+            .timestamp_millis_opt(after)
+            .single()?
+            .date_naive()
+            .max(start_date);
+        let period_days = match self.frequency {
+            Frequency::Daily => 1,
+            Frequency::Weekly => 7,
+            Frequency::Monthly => 31,
+        };
+        let horizon = i64::from(self.interval) * period_days * SEARCH_PERIODS + 7;
+
+        from.iter_days()
+            .take(horizon as usize)
+            .filter(|&date| self.matches(start_date, date))
+            .filter_map(|date| local_ms(date.and_hms_opt(hour, minute, 0)?))
+            .find(|&at| at > after)
+    }
+
+    /// Whether the rule anchored at `start` occurs on `date`.
+    fn matches(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        let interval = i64::from(self.interval);
+        match self.frequency {
+            Frequency::Daily => {
+                (date - start).num_days() % interval == 0
+                    && (self.by_day.is_empty() || self.by_day.contains(&date.weekday()))
+            }
+            Frequency::Weekly => {
+                let monday = |day: NaiveDate| {
+                    day - chrono::Duration::days(i64::from(day.weekday().num_days_from_monday()))
+                };
+                let weeks = (monday(date) - monday(start)).num_days() / 7;
+                let on_day = if self.by_day.is_empty() {
+                    date.weekday() == start.weekday()
+                } else {
+                    self.by_day.contains(&date.weekday())
+                };
+                weeks % interval == 0 && on_day
+            }
+            Frequency::Monthly => {
+                let months = (i64::from(date.year()) * 12 + i64::from(date.month0()))
+                    - (i64::from(start.year()) * 12 + i64::from(start.month0()));
+                months % interval == 0 && date.day() == self.by_month_day.unwrap_or(start.day())
+            }
+        }
+    }
+}
+
+/// `SELECT` of every `Recurrence` column followed by `clause`.
+fn select_sql(clause: &str) -> String {
+    format!(
+        "SELECT id, type, title, section, emoji, component, module, severity,
+                priority, effort, description, user_story, rule, catch_up,
+                starts_at, next_run
+         FROM recurrences {}",
+        clause
+    )
+}
+
+fn parse_number(key: &str, value: &str, min: u32, max: u32) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|number| (min..=max).contains(number))
+        .ok_or_else(|| format!("{} must be between {} and {}", key, min, max))
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    Some(match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Unix ms of a local wall-clock time. A time skipped by a DST change
+/// moves forward by an hour.
+fn local_ms(naive: NaiveDateTime) -> Option<i64> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(naive + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map(|at| at.timestamp_millis())
+}
+
+/// Validate an optional severity or effort against its codes.
+fn optional_code(
+    value: Option<&str>,
+    codes: &[(&'static str, &str)],
+    field: &str,
+) -> Result<Option<&'static str>, AppError> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => lookup_code(codes, value)
+            .map(Some)
+            .ok_or_else(|| AppError::Validation(format!("unknown {}: {}", field, value))),
+    }
+}
+
+fn priority_code(value: &str) -> Option<&'static str> {
+    PRIORITIES
+        .iter()
+        .find(|priority| priority.eq_ignore_ascii_case(value))
+        .copied()
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{items, project_at};
+    use std::path::PathBuf;
+
+    /// Unix ms of a local wall-clock time.
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        local_ms(date.and_hms_opt(hour, minute, 0).unwrap()).unwrap()
+    }
+
+    /// Successive occurrences of `rule` from its start.
+    fn occurrences(rule: &str, starts_at: i64, count: usize) -> Vec<i64> {
+        let rule = Rule::parse(rule).unwrap();
+        let mut found = Vec::new();
+        let mut after = starts_at - 1;
+        while found.len() < count {
+            after = rule.next_after(starts_at, after).unwrap();
+            found.push(after);
+        }
+        found
+    }
+
+    /// The Demo project at v9, with one recurrence of a BUG in `Done` on
+    /// `rule`, due at `next_run`.
+    async fn recurring_project(
+        dir: &Path,
+        rule: &str,
+        catch_up: CatchUp,
+        starts_at: i64,
+        next_run: i64,
+    ) -> (PathBuf, Recurrence) {
+        let db_path = project_at(dir, 9).await;
+        let mut conn = project_db::open_connection(&db_path).await.unwrap();
+        sqlx::query(
+            "INSERT INTO recurrences (project_id, type, title, section, severity, priority,
+               rule, catch_up, starts_at, next_run)
+             VALUES (1, 'BUG', 'Weekly report', 'Done', 'P1', 'haute', ?, ?, ?, ?)",
+        )
+        .bind(rule)
+        .bind(catch_up.as_str())
+        .bind(starts_at)
+        .bind(next_run)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        let recurrence = sqlx::query_as(&select_sql("WHERE id = 1"))
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+        (db_path, recurrence)
+    }
+
+    /// Run the fixture recurrence at `now`; returns the new tickets and the
+    /// stored `next_run`, `None` once the recurrence is deleted.
+    async fn run(db_path: &Path, recurrence: &Recurrence, now: i64) -> (Vec<String>, Option<i64>) {
+        let (mut conn, context) = open_project(db_path, false, MIN_SCHEMA_VERSION)
+            .await
+            .unwrap();
+        let mut writer = ItemWriter::new(&context);
+        let ids = run_recurrence(&mut conn, &mut writer, recurrence, now)
+            .await
+            .unwrap();
+        let next_run = sqlx::query_scalar("SELECT next_run FROM recurrences WHERE id = ?")
+            .bind(recurrence.id)
+            .fetch_optional(&mut conn)
+            .await
+            .unwrap();
+        (ids, next_run)
+    }
+
+    #[test]
+    fn rules_are_parsed_case_insensitively() {
+        let rule = Rule::parse(" rrule:freq=weekly;interval=2;byday=mo,th;byhour=9; ").unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, [Weekday::Mon, Weekday::Thu]);
+        assert_eq!(rule.by_hour, Some(9));
+        assert_eq!(rule.by_minute, None);
+
+        let rule = Rule::parse("FREQ=MONTHLY;BYMONTHDAY=31;BYMINUTE=30").unwrap();
+        assert_eq!(rule.frequency, Frequency::Monthly);
+        assert_eq!(rule.by_month_day, Some(31));
+        assert_eq!(rule.by_minute, Some(30));
+    }
+
+    #[test]
+    fn invalid_rules_are_refused() {
+        for (rule, error) in [
+            ("", "rule has no FREQ"),
+            ("INTERVAL=2", "rule has no FREQ"),
+            ("FREQ", "invalid rule part: FREQ"),
+            ("FREQ=YEARLY", "unsupported FREQ: YEARLY"),
+            ("FREQ=DAILY;COUNT=3", "unsupported rule part: COUNT"),
+            ("FREQ=DAILY;INTERVAL=0", "INTERVAL must be between 1 and 99"),
+            ("FREQ=DAILY;BYHOUR=24", "BYHOUR must be between 0 and 23"),
+            ("FREQ=WEEKLY;BYDAY=MO,XX", "invalid BYDAY: XX"),
+            (
+                "FREQ=WEEKLY;BYMONTHDAY=3",
+                "BYMONTHDAY requires FREQ=MONTHLY",
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=MO",
+                "BYDAY is not supported with FREQ=MONTHLY",
+            ),
+        ] {
+            assert_eq!(Rule::parse(rule).unwrap_err(), error, "{}", rule);
+        }
+    }
+
+    #[test]
+    fn weekly_rules_occur_on_their_days_every_interval() {
+        // 2024-01-01 is a Monday.
+        let start = at(2024, 1, 1, 8, 0);
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;BYDAY=MO,TH;BYHOUR=9", start, 3),
+            [
+                at(2024, 1, 1, 9, 0),
+                at(2024, 1, 4, 9, 0),
+                at(2024, 1, 8, 9, 0)
+            ]
+        );
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;INTERVAL=2", start, 3),
+            [start, at(2024, 1, 15, 8, 0), at(2024, 1, 29, 8, 0)]
+        );
+    }
+
+    #[test]
+    fn daily_rules_follow_interval_and_days() {
+        let start = at(2024, 1, 1, 7, 15);
+        assert_eq!(
+            occurrences("FREQ=DAILY;INTERVAL=3", start, 3),
+            [start, at(2024, 1, 4, 7, 15), at(2024, 1, 7, 7, 15)]
+        );
+        assert_eq!(
+            occurrences("FREQ=DAILY;BYDAY=SA,SU", start, 3),
+            [
+                at(2024, 1, 6, 7, 15),
+                at(2024, 1, 7, 7, 15),
+                at(2024, 1, 13, 7, 15)
+            ]
+        );
+    }
+
+    #[test]
+    fn first_occurrence_is_not_before_the_start() {
+        let start = at(2024, 1, 1, 10, 0);
+        let rule = Rule::parse("FREQ=DAILY;BYHOUR=9").unwrap();
+        assert_eq!(
+            rule.next_after(start, at(2023, 6, 1, 0, 0)),
+            Some(at(2024, 1, 2, 9, 0))
+        );
+    }
+
+    #[test]
+    fn monthly_rules_skip_months_without_the_day() {
+        let start = at(2024, 1, 1, 0, 0);
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;BYMONTHDAY=31;BYHOUR=9", start, 3),
+            [
+                at(2024, 1, 31, 9, 0),
+                at(2024, 3, 31, 9, 0),
+                at(2024, 5, 31, 9, 0)
+            ]
+        );
+        let leap_day = at(2024, 2, 29, 12, 0);
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;INTERVAL=12", leap_day, 2),
+            [leap_day, at(2028, 2, 29, 12, 0)]
+        );
+        let rule = Rule::parse("FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=30").unwrap();
+        let february = at(2024, 2, 1, 0, 0);
+        assert_eq!(rule.next_after(february, february - 1), None);
+    }
+
+    #[tokio::test]
+    async fn catch_up_all_creates_every_missed_occurrence() {
+        let dir = tempfile::tempdir().unwrap();
+        let rule = "FREQ=DAILY;BYHOUR=9;BYMINUTE=0";
+        let (db_path, recurrence) = recurring_project(
+            dir.path(),
+            rule,
+            CatchUp::All,
+            at(2024, 1, 1, 0, 0),
+            at(2024, 1, 1, 9, 0),
+        )
+        .await;
+
+        let (ids, next_run) = run(&db_path, &recurrence, at(2024, 1, 3, 12, 0)).await;
+        assert_eq!(ids, ["BUG-002", "BUG-003", "BUG-004"]);
+        assert_eq!(next_run, Some(at(2024, 1, 4, 9, 0)));
+
+        let items = items(&db_path).await;
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0], ("BUG-002".to_string(), "Done".to_string(), 0));
+        let mut conn = project_db::open_read_only(&db_path).await.unwrap();
+        let fields: (String, String, String) = sqlx::query_as(
+            "SELECT title, severity, priority FROM backlog_items WHERE id = 'BUG-002'",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            fields,
+            ("Weekly report".into(), "P1".into(), "Haute".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn catch_up_all_is_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, recurrence) = recurring_project(
+            dir.path(),
+            "FREQ=DAILY",
+            CatchUp::All,
+            at(2024, 1, 1, 9, 0),
+            at(2024, 1, 1, 9, 0),
+        )
+        .await;
+
+        let (ids, next_run) = run(&db_path, &recurrence, at(2024, 3, 1, 12, 0)).await;
+        assert_eq!(ids.len(), MAX_CATCH_UP);
+        assert_eq!(next_run, Some(at(2024, 3, 2, 9, 0)));
+    }
+
+    #[tokio::test]
+    async fn catch_up_latest_creates_one_ticket() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, recurrence) = recurring_project(
+            dir.path(),
+            "FREQ=DAILY",
+            CatchUp::Latest,
+            at(2024, 1, 1, 9, 0),
+            at(2024, 1, 1, 9, 0),
+        )
+        .await;
+
+        let (ids, next_run) = run(&db_path, &recurrence, at(2024, 1, 5, 12, 0)).await;
+        assert_eq!(ids, ["BUG-002"]);
+        assert_eq!(next_run, Some(at(2024, 1, 6, 9, 0)));
+    }
+
+    #[tokio::test]
+    async fn catch_up_skip_only_creates_late_occurrences() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, recurrence) = recurring_project(
+            dir.path(),
+            "FREQ=DAILY",
+            CatchUp::Skip,
+            at(2024, 1, 1, 9, 0),
+            at(2024, 1, 1, 9, 0),
+        )
+        .await;
+
+        let (ids, next_run) = run(&db_path, &recurrence, at(2024, 1, 3, 12, 0)).await;
+        assert!(ids.is_empty());
+        assert_eq!(next_run, Some(at(2024, 1, 4, 9, 0)));
+
+        let (ids, _) = run(&db_path, &recurrence, at(2024, 1, 1, 9, 1)).await;
+        assert_eq!(ids, ["BUG-002"]);
+    }
+
+    #[tokio::test]
+    async fn recurrence_without_further_occurrence_is_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, recurrence) = recurring_project(
+            dir.path(),
+            "FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=30",
+            CatchUp::Latest,
+            at(2024, 2, 1, 9, 0),
+            at(2024, 2, 1, 9, 0),
+        )
+        .await;
+
+        let (ids, next_run) = run(&db_path, &recurrence, at(2024, 2, 1, 10, 0)).await;
+        assert_eq!(ids, ["BUG-002"]);
+        assert_eq!(next_run, None);
+    }
+
+    #[test]
+    fn template_codes_are_validated() {
+        assert_eq!(priority_code("haute"), Some("Haute"));
+        assert_eq!(priority_code("Urgent"), None);
+        assert_eq!(
+            optional_code(Some(" p2 - moyenne "), &SEVERITIES, "severity").unwrap(),
+            Some("P2")
+        );
+        assert_eq!(optional_code(Some(" "), &EFFORTS, "effort").unwrap(), None);
+        let err = optional_code(Some("XXL"), &EFFORTS, "effort").unwrap_err();
+        assert!(matches!(err, AppError::Validation(message) if message == "unknown effort: XXL"));
+        assert_eq!(CatchUp::parse(CatchUp::Skip.as_str()), CatchUp::Skip);
+        assert_eq!(CatchUp::parse("sometimes"), CatchUp::Latest);
+    }
+}
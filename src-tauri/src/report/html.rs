@@ -0,0 +1,387 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::files;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const MAX_HTML_BYTES: usize = 5 * 1024 * 1024;
+
+/// Subdirectory of `app_cache_dir` holding the page and browser profile of
+/// a running export.
+const WORK_DIR: &str = "pdf-export";
+
+/// How long the headless browser may take to print.
+const PRINT_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Injected at the top of the page: no network access, so the HTML can
+/// only use inline styles and `data:` images and fonts.
+const CONTENT_POLICY: &str = "<meta http-equiv=\"Content-Security-Policy\" \
+    content=\"default-src 'none'; style-src 'unsafe-inline'; img-src data:; font-src data:\">";
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Print `html` (at most 5 MB) to a PDF at `output_path`, which must be a
+/// `.pdf` inside the user's documents folder, and return its size. The page
+/// is printed by a headless Chrome, Edge, Chromium or Brave, or by
+/// `wkhtmltopdf`, whichever is installed first; scripts and network access
+/// are disabled, so styles and images must be inline.
+#[tauri::command]
+pub async fn pdf_export(
+    html: String,
+    output_path: String,
+    app: AppHandle,
+) -> Result<u64, AppError> {
+    if html.len() > MAX_HTML_BYTES {
+        return Err(AppError::Validation(format!(
+            "HTML is too large ({} bytes, max {})",
+            html.len(),
+            MAX_HTML_BYTES
+        )));
+    }
+    let output = validate_output(&app, Path::new(&output_path))?;
+    let printer = platform::find_printer().ok_or_else(|| {
+        AppError::Io("no Chromium-based browser or wkhtmltopdf found to print the PDF".into())
+    })?;
+
+    let work_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Io(format!("cannot resolve cache directory: {}", e)))?
+        .join(WORK_DIR)
+        .join(now_ms().to_string());
+    tokio::fs::create_dir_all(&work_dir).await?;
+    let page = work_dir.join("page.html");
+    tokio::fs::write(&page, with_content_policy(&html)).await?;
+
+    // Printed inside the work directory first, so a failed print leaves an
+    // existing file at `output` untouched.
+    let printed = work_dir.join("page.pdf");
+    let result = async {
+        print(&printer, &page, &work_dir, &printed).await?;
+        if !printed.is_file() {
+            return Err(AppError::Io("the printer did not write the PDF".into()));
+        }
+        Ok(tokio::fs::copy(&printed, &output).await?)
+    }
+    .await;
+    if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+        log::warn!("pdf_export: cannot remove {}: {}", work_dir.display(), e);
+    }
+    let size = result?;
+    log::info!("pdf_export: {} ({} bytes)", output.display(), size);
+    Ok(size)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// `path` checked against the fs scope, with a `.pdf` extension and inside
+/// the documents folder.
+fn validate_output(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    {
+        return Err(AppError::Validation(
+            "output path must end with .pdf".into(),
+        ));
+    }
+    let path = files::validate_path(app, path)?;
+    let documents = app
+        .path()
+        .document_dir()
+        .map_err(|e| AppError::Io(format!("cannot resolve documents folder: {}", e)))?;
+    let documents = documents.canonicalize().unwrap_or(documents);
+    let parent = path
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .ok_or_else(|| {
+            AppError::Validation(format!("folder of {} does not exist", path.display()))
+        })?;
+    if !parent.starts_with(&documents) {
+        return Err(AppError::Validation(format!(
+            "output path must be inside {}",
+            documents.display()
+        )));
+    }
+    Ok(parent.join(path.file_name().unwrap_or_default()))
+}
+
+/// Insert `CONTENT_POLICY` after the doctype, so the page stays in
+/// standards mode.
+fn with_content_policy(html: &str) -> String {
+    let trimmed = html.trim_start();
+    let doctype_end = trimmed
+        .get(..9)
+        .filter(|start| start.eq_ignore_ascii_case("<!doctype"))
+        .and_then(|_| trimmed.find('>'))
+        .map_or(0, |end| end + 1);
+    format!(
+        "{}{}{}",
+        &trimmed[..doctype_end],
+        CONTENT_POLICY,
+        &trimmed[doctype_end..]
+    )
+}
+
+/// Run the headless printer on `page`, writing `output`, and wait for it,
+/// killing it after `PRINT_TIMEOUT`.
+async fn print(
+    printer: &Path,
+    page: &Path,
+    work_dir: &Path,
+    output: &Path,
+) -> Result<(), AppError> {
+    let mut command = Command::new(printer);
+    if is_wkhtmltopdf(printer) {
+        command
+            .args(["--quiet", "--disable-javascript", "--print-media-type"])
+            .arg(page)
+            .arg(output);
+    } else {
+        let mut print_to = std::ffi::OsString::from("--print-to-pdf=");
+        print_to.push(output);
+        let mut profile = std::ffi::OsString::from("--user-data-dir=");
+        profile.push(work_dir.join("profile"));
+        command
+            .args([
+                "--headless",
+                "--disable-gpu",
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--disable-extensions",
+                "--blink-settings=scriptEnabled=false",
+                "--no-pdf-header-footer",
+            ])
+            .arg(profile)
+            .arg(print_to)
+            .arg(page);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot start {}: {}", printer.display(), e)))?;
+
+    let deadline = Instant::now() + PRINT_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(AppError::Io(format!(
+                    "{} failed with {}",
+                    printer.display(),
+                    status
+                )));
+            }
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(AppError::Timeout(format!(
+                "PDF printing took more than {} seconds",
+                PRINT_TIMEOUT.as_secs()
+            )));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn is_wkhtmltopdf(printer: &Path) -> bool {
+    printer
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("wkhtmltopdf"))
+}
+
+/// First of `names` found in `PATH`.
+fn find_in_path(names: &[&str]) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    names.iter().find_map(|name| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::PathBuf;
+
+    pub fn find_printer() -> Option<PathBuf> {
+        super::find_in_path(&[
+            "google-chrome",
+            "google-chrome-stable",
+            "chromium",
+            "chromium-browser",
+            "microsoft-edge",
+            "microsoft-edge-stable",
+            "brave-browser",
+            "wkhtmltopdf",
+        ])
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::PathBuf;
+
+    const APP_BINARIES: [&str; 4] = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Brave Browser.app/Contents/MacOS/Brave Browser",
+    ];
+
+    pub fn find_printer() -> Option<PathBuf> {
+        APP_BINARIES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+            .or_else(|| super::find_in_path(&["wkhtmltopdf"]))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::path::PathBuf;
+
+    /// Install locations relative to the program files and local app data
+    /// folders. Edge ships with Windows 10 and later.
+    const APP_BINARIES: [&str; 3] = [
+        r"Microsoft\Edge\Application\msedge.exe",
+        r"Google\Chrome\Application\chrome.exe",
+        r"BraveSoftware\Brave-Browser\Application\brave.exe",
+    ];
+
+    pub fn find_printer() -> Option<PathBuf> {
+        let roots: Vec<PathBuf> = ["ProgramFiles(x86)", "ProgramFiles", "LOCALAPPDATA"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .collect();
+        APP_BINARIES
+            .iter()
+            .flat_map(|binary| roots.iter().map(move |root| root.join(binary)))
+            .find(|path| path.is_file())
+            .or_else(|| super::find_in_path(&["wkhtmltopdf.exe"]))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use std::path::PathBuf;
+
+    pub fn find_printer() -> Option<PathBuf> {
+        super::find_in_path(&["chromium", "wkhtmltopdf"])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE_HTML: &str =
+        "<!DOCTYPE html><html><body><h1>BUG-1</h1><p>Crash on save</p></body></html>";
+
+    #[test]
+    fn content_policy_follows_the_doctype() {
+        let page = with_content_policy(&format!("\n  {}", SIMPLE_HTML));
+        assert!(page.starts_with(&format!("<!DOCTYPE html>{}<html>", CONTENT_POLICY)));
+        assert_eq!(
+            with_content_policy("<p>no doctype</p>"),
+            format!("{}<p>no doctype</p>", CONTENT_POLICY)
+        );
+    }
+
+    /// A `wkhtmltopdf` stand-in copying the page to the output after a PDF
+    /// header, or failing with `exit_code`.
+    #[cfg(unix)]
+    fn fake_printer(dir: &Path, exit_code: i32) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let printer = dir.join("wkhtmltopdf");
+        let script = format!(
+            "#!/bin/sh\n\
+             [ {0} -ne 0 ] && exit {0}\n\
+             while [ $# -gt 2 ]; do shift; done\n\
+             {{ printf '%%PDF-1.4\\n'; cat \"$1\"; }} > \"$2\"\n",
+            exit_code
+        );
+        std::fs::write(&printer, script).unwrap();
+        std::fs::set_permissions(&printer, std::fs::Permissions::from_mode(0o755)).unwrap();
+        printer
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn simple_html_is_printed() {
+        let dir = tempfile::tempdir().unwrap();
+        let printer = fake_printer(dir.path(), 0);
+        let page = dir.path().join("page.html");
+        std::fs::write(&page, with_content_policy(SIMPLE_HTML)).unwrap();
+        let output = dir.path().join("page.pdf");
+
+        print(&printer, &page, dir.path(), &output).await.unwrap();
+        let pdf = std::fs::read_to_string(&output).unwrap();
+        assert!(pdf.starts_with("%PDF-"));
+        assert!(pdf.contains(CONTENT_POLICY));
+        assert!(pdf.contains("<h1>BUG-1</h1>"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn printer_failure_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let printer = fake_printer(dir.path(), 3);
+        let page = dir.path().join("page.html");
+        std::fs::write(&page, SIMPLE_HTML).unwrap();
+        let output = dir.path().join("page.pdf");
+
+        assert!(matches!(
+            print(&printer, &page, dir.path(), &output).await,
+            Err(AppError::Io(_))
+        ));
+        assert!(!output.exists());
+    }
+
+    /// Runs only where a headless browser or wkhtmltopdf is installed.
+    #[tokio::test]
+    async fn installed_printer_prints_simple_html() {
+        let Some(printer) = platform::find_printer() else {
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let page = dir.path().join("page.html");
+        std::fs::write(&page, with_content_policy(SIMPLE_HTML)).unwrap();
+        let output = dir.path().join("page.pdf");
+
+        print(&printer, &page, dir.path(), &output).await.unwrap();
+        assert!(std::fs::read(&output).unwrap().starts_with(b"%PDF-"));
+    }
+
+    #[test]
+    fn wkhtmltopdf_is_told_apart_from_browsers() {
+        assert!(is_wkhtmltopdf(Path::new("/usr/bin/wkhtmltopdf")));
+        assert!(is_wkhtmltopdf(Path::new("WKHTMLTOPDF.exe")));
+        assert!(!is_wkhtmltopdf(Path::new("/usr/bin/chromium")));
+    }
+}
@@ -0,0 +1,731 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+
+pub mod html;
+pub mod pdf;
+
+use pdf::{Font, Page};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Page sizes in points, portrait.
+const A4: (f64, f64) = (595.28, 841.89);
+const LETTER: (f64, f64) = (612.0, 792.0);
+
+const MARGIN: f64 = 40.0;
+const ROW_HEIGHT: f64 = 14.0;
+const TABLE_FONT_SIZE: f64 = 8.0;
+const FOOTER_FONT_SIZE: f64 = 8.0;
+
+/// Largest box the logo is scaled into on the title page.
+const LOGO_MAX: (f64, f64) = (180.0, 90.0);
+
+/// Logos larger than this on either side are refused.
+const LOGO_MAX_PIXELS: u32 = 4096;
+
+/// Ticket table columns: header and share of the content width.
+const COLUMNS: [(&str, f64); 7] = [
+    ("ID", 0.10),
+    ("Type", 0.08),
+    ("Titre", 0.28),
+    ("Sévérité", 0.08),
+    ("Priorité", 0.09),
+    ("Effort", 0.07),
+    ("Description", 0.30),
+];
+
+/// Group label of tickets without a value for the grouping field.
+const NO_GROUP: &str = "(aucun)";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Which tickets a report covers and how they are grouped. Empty lists
+/// don't filter.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReportFilter {
+    /// Board sections (statuses) to include.
+    pub sections: Vec<String>,
+    pub types: Vec<String>,
+    pub severities: Vec<String>,
+    pub priorities: Vec<String>,
+    pub group_by: ReportGroup,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGroup {
+    #[default]
+    Section,
+    Type,
+    Severity,
+    Priority,
+    Component,
+    Module,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+/// Page setup and title page of a report.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReportLayout {
+    pub page_size: PageSize,
+    pub landscape: bool,
+    /// Title page heading; the project folder name by default.
+    pub title: Option<String>,
+    /// PNG shown on the title page.
+    pub logo_path: Option<String>,
+}
+
+/// Return value of `export_report_pdf`.
+#[derive(Debug, Serialize)]
+pub struct ReportResult {
+    pub path: String,
+    pub pages: usize,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReportItem {
+    id: String,
+    #[sqlx(rename = "type")]
+    item_type: String,
+    title: String,
+    section: Option<String>,
+    severity: Option<String>,
+    priority: Option<String>,
+    effort: Option<String>,
+    component: Option<String>,
+    module: Option<String>,
+    description: Option<String>,
+}
+
+/// Pages being laid out, top to bottom.
+struct Renderer {
+    pages: Vec<Page>,
+    size: (f64, f64),
+    /// Baseline of the next line on the last page.
+    y: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Render the tickets matching `filter` as a printable PDF: a title page
+/// (logo, title, date, count per group) followed by one table per group,
+/// continued across pages with the header repeated. Every cell holds one
+/// line; longer text, descriptions included, is cut with an ellipsis.
+/// Written by an embedded PDF writer, so no browser is involved.
+#[tauri::command]
+pub async fn export_report_pdf(
+    db_path: String,
+    filter: ReportFilter,
+    dest_path: String,
+    layout: ReportLayout,
+    app: tauri::AppHandle,
+) -> Result<ReportResult, AppError> {
+    let dest = files::validate_path(&app, Path::new(&dest_path))?;
+    let logo = match &layout.logo_path {
+        Some(path) => Some(load_logo(&files::validate_path(&app, Path::new(path))?)?),
+        None => None,
+    };
+
+    write_report(Path::new(&db_path), &filter, &layout, logo.as_ref(), &dest).await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Render the report of `db_path` and write it to `dest` through
+/// `<dest>.tmp`.
+async fn write_report(
+    db_path: &Path,
+    filter: &ReportFilter,
+    layout: &ReportLayout,
+    logo: Option<&pdf::Image>,
+    dest: &Path,
+) -> Result<ReportResult, AppError> {
+    let items = with_timeout(fetch_items(db_path, filter), DB_TIMEOUT_MS).await?;
+    let groups = group_items(items, filter.group_by);
+
+    let title = layout.title.clone().unwrap_or_else(|| {
+        db_path
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Ticketflow".into())
+    });
+    let (width, height) = match layout.page_size {
+        PageSize::A4 => A4,
+        PageSize::Letter => LETTER,
+    };
+    let size = if layout.landscape {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    let mut renderer = Renderer::new(size);
+    renderer.title_page(&title, logo, &groups);
+    for (group, items) in &groups {
+        renderer.group_table(group, items);
+    }
+    renderer.footers();
+
+    let bytes = pdf::write_document(&renderer.pages, logo, &title)?;
+    let mut tmp = dest.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, &bytes)?;
+    if let Err(e) = std::fs::rename(&tmp, dest) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    Ok(ReportResult {
+        path: dest.to_string_lossy().into_owned(),
+        pages: renderer.pages.len(),
+        size_bytes: bytes.len() as u64,
+    })
+}
+
+async fn fetch_items(db_path: &Path, filter: &ReportFilter) -> Result<Vec<ReportItem>, AppError> {
+    let mut filters = Vec::new();
+    let mut params: Vec<&String> = Vec::new();
+    for (column, values) in [
+        ("s.title", &filter.sections),
+        ("i.type", &filter.types),
+        ("i.severity", &filter.severities),
+        ("i.priority", &filter.priorities),
+    ] {
+        if !values.is_empty() {
+            filters.push(format!(
+                "{} IN ({})",
+                column,
+                vec!["?"; values.len()].join(", ")
+            ));
+            params.extend(values);
+        }
+    }
+    let where_clause = if filters.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", filters.join(" AND "))
+    };
+    let sql = format!(
+        "SELECT i.id, i.type, i.title, s.title AS section, i.severity, i.priority,
+           i.effort, i.component, i.module, i.description
+         FROM backlog_items i
+         LEFT JOIN sections s ON s.id = i.section_id
+         {}
+         ORDER BY s.position, i.position, i.id",
+        where_clause
+    );
+
+    let mut conn = project_db::open_read_only(db_path).await?;
+    let mut query = sqlx::query_as(&sql);
+    for param in params {
+        query = query.bind(param);
+    }
+    Ok(query.fetch_all(&mut conn).await?)
+}
+
+/// Split items into groups. Sections keep the board order; other fields
+/// are sorted by value, with the items lacking one last.
+fn group_items(items: Vec<ReportItem>, group_by: ReportGroup) -> Vec<(String, Vec<ReportItem>)> {
+    let mut groups: Vec<(Option<String>, Vec<ReportItem>)> = Vec::new();
+    for item in items {
+        let key = match group_by {
+            ReportGroup::Section => item.section.clone(),
+            ReportGroup::Type => Some(item.item_type.clone()),
+            ReportGroup::Severity => item.severity.clone(),
+            ReportGroup::Priority => item.priority.clone(),
+            ReportGroup::Component => item.component.clone(),
+            ReportGroup::Module => item.module.clone(),
+        }
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty());
+        match groups.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((key, vec![item])),
+        }
+    }
+    if !matches!(group_by, ReportGroup::Section) {
+        groups.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => a.cmp(b),
+            _ => b.is_some().cmp(&a.is_some()),
+        });
+    }
+    groups
+        .into_iter()
+        .map(|(key, items)| (key.unwrap_or_else(|| NO_GROUP.into()), items))
+        .collect()
+}
+
+/// Decode a PNG logo into RGB samples and an alpha mask.
+fn load_logo(path: &Path) -> Result<pdf::Image, AppError> {
+    let invalid = |e: png::DecodingError| AppError::Validation(format!("invalid PNG logo: {}", e));
+    let mut decoder = png::Decoder::new(std::fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if width > LOGO_MAX_PIXELS || height > LOGO_MAX_PIXELS {
+        return Err(AppError::Validation(format!(
+            "logo must be at most {0}x{0} pixels",
+            LOGO_MAX_PIXELS
+        )));
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(invalid)?;
+    buffer.truncate(frame.buffer_size());
+
+    let channels = frame.color_type.samples();
+    let has_alpha = matches!(
+        frame.color_type,
+        png::ColorType::Rgba | png::ColorType::GrayscaleAlpha
+    );
+    let mut rgb = Vec::with_capacity(buffer.len() / channels * 3);
+    let mut alpha = Vec::new();
+    for pixel in buffer.chunks_exact(channels) {
+        match channels {
+            1 | 2 => rgb.extend_from_slice(&[pixel[0]; 3]),
+            _ => rgb.extend_from_slice(&pixel[..3]),
+        }
+        if has_alpha {
+            alpha.push(pixel[channels - 1]);
+        }
+    }
+    Ok(pdf::Image {
+        width,
+        height,
+        rgb,
+        alpha: has_alpha.then_some(alpha),
+    })
+}
+
+impl Renderer {
+    fn new(size: (f64, f64)) -> Self {
+        let mut renderer = Renderer {
+            pages: Vec::new(),
+            size,
+            y: 0.0,
+        };
+        renderer.new_page();
+        renderer
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::new(self.size.0, self.size.1));
+        self.y = self.size.1 - MARGIN;
+    }
+
+    fn page(&mut self) -> &mut Page {
+        self.pages.last_mut().expect("renderer has a page")
+    }
+
+    fn content_width(&self) -> f64 {
+        self.size.0 - 2.0 * MARGIN
+    }
+
+    /// Whether `height` more points fit above the footer.
+    fn fits(&self, height: f64) -> bool {
+        self.y - height >= MARGIN + ROW_HEIGHT
+    }
+
+    fn title_page(
+        &mut self,
+        title: &str,
+        logo: Option<&pdf::Image>,
+        groups: &[(String, Vec<ReportItem>)],
+    ) {
+        let width = self.content_width();
+        if let Some(logo) = logo {
+            let scale = (LOGO_MAX.0 / f64::from(logo.width))
+                .min(LOGO_MAX.1 / f64::from(logo.height))
+                .min(1.0);
+            let (w, h) = (
+                f64::from(logo.width) * scale,
+                f64::from(logo.height) * scale,
+            );
+            let y = self.y - h;
+            self.page().image(MARGIN, y, w, h);
+            self.y = y - 30.0;
+        } else {
+            self.y -= 40.0;
+        }
+
+        let heading = pdf::fit(title, Font::Bold, 24.0, width);
+        let y = self.y;
+        self.page().text(MARGIN, y, Font::Bold, 24.0, &heading);
+        self.y -= 22.0;
+        let total: usize = groups.iter().map(|(_, items)| items.len()).sum();
+        let subtitle = format!(
+            "Rapport du {} — {} ticket{}",
+            chrono::Local::now().format("%d/%m/%Y %H:%M"),
+            total,
+            if total == 1 { "" } else { "s" }
+        );
+        let y = self.y;
+        self.page().text(MARGIN, y, Font::Regular, 11.0, &subtitle);
+        self.y -= 36.0;
+
+        for (group, items) in groups {
+            if !self.fits(ROW_HEIGHT) {
+                self.new_page();
+            }
+            let count = items.len().to_string();
+            let count_width = pdf::text_width(&count, Font::Bold, 10.0);
+            let label = pdf::fit(group, Font::Regular, 10.0, width - count_width - 12.0);
+            let y = self.y;
+            let page = self.page();
+            page.text(MARGIN, y, Font::Regular, 10.0, &label);
+            page.text(MARGIN + width - count_width, y, Font::Bold, 10.0, &count);
+            page.line(MARGIN, y - 4.0, MARGIN + width, y - 4.0, 0.85);
+            self.y -= ROW_HEIGHT + 2.0;
+        }
+    }
+
+    /// A group heading with its ticket count, then one row per ticket. Each
+    /// group starts on a new page.
+    fn group_table(&mut self, group: &str, items: &[ReportItem]) {
+        self.new_page();
+        self.group_heading(&format!("{} ({})", group, items.len()));
+        for item in items {
+            if !self.fits(ROW_HEIGHT) {
+                self.new_page();
+                self.group_heading(&format!("{} (suite)", group));
+            }
+            let description = item
+                .description
+                .as_deref()
+                .and_then(|d| d.lines().find(|line| !line.trim().is_empty()))
+                .unwrap_or_default();
+            let cells = [
+                item.id.as_str(),
+                item.item_type.as_str(),
+                item.title.as_str(),
+                item.severity.as_deref().unwrap_or_default(),
+                item.priority.as_deref().unwrap_or_default(),
+                item.effort.as_deref().unwrap_or_default(),
+                description,
+            ];
+            self.row(&cells, Font::Regular);
+            let (y, right) = (self.y + ROW_HEIGHT - 4.0, self.size.0 - MARGIN);
+            self.page().line(MARGIN, y, right, y, 0.85);
+        }
+    }
+
+    fn group_heading(&mut self, text: &str) {
+        let heading = pdf::fit(text, Font::Bold, 14.0, self.content_width());
+        let y = self.y;
+        self.page().text(MARGIN, y, Font::Bold, 14.0, &heading);
+        self.y -= 24.0;
+
+        let width = self.content_width();
+        let y = self.y - 4.0;
+        self.page().fill_rect(MARGIN, y, width, ROW_HEIGHT, 0.9);
+        let headers = COLUMNS.map(|(header, _)| header);
+        self.row(&headers, Font::Bold);
+    }
+
+    /// One table row of single-line cells, then move down a row.
+    fn row(&mut self, cells: &[&str], font: Font) {
+        let width = self.content_width();
+        let mut x = MARGIN;
+        let y = self.y;
+        for (cell, (_, share)) in cells.iter().zip(COLUMNS) {
+            let column = width * share;
+            let text = pdf::fit(cell, font, TABLE_FONT_SIZE, column - 6.0);
+            self.page().text(x + 2.0, y, font, TABLE_FONT_SIZE, &text);
+            x += column;
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    /// Number every page at the bottom right.
+    fn footers(&mut self) {
+        let count = self.pages.len();
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            let text = format!("Page {} / {}", index + 1, count);
+            let x = page.width - MARGIN - pdf::text_width(&text, Font::Regular, FOOTER_FONT_SIZE);
+            page.text(x, MARGIN / 2.0, Font::Regular, FOOTER_FONT_SIZE, &text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+
+    /// Done first on the board, and a ticket whose section is gone.
+    const SEED: &str = "
+        PRAGMA foreign_keys = OFF;
+        UPDATE sections SET position = 1 - position;
+        INSERT INTO backlog_items (id, project_id, section_id, position, type, title, severity,
+            priority, component, raw_markdown) VALUES
+            ('BUG-1', 1, 1, 1, 'BUG', 'Crash', 'P0', 'Haute', 'core', ''),
+            ('BUG-2', 1, 1, 0, 'BUG', 'Slow', 'P2', NULL, ' ', ''),
+            ('FEAT-1', 1, 2, 0, 'FEAT', 'Export', NULL, 'Faible', 'ui', ''),
+            ('CT-1', 1, 99, 0, 'CT', 'Orphan', NULL, NULL, 'core', '');
+    ";
+
+    async fn project(dir: &Path) -> std::path::PathBuf {
+        create_db(
+            dir,
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            &[BOARD, SEED].concat(),
+        )
+        .await
+    }
+
+    fn item(id: &str, section: Option<&str>) -> ReportItem {
+        ReportItem {
+            id: id.into(),
+            item_type: "BUG".into(),
+            title: format!("Ticket {}", id),
+            section: section.map(Into::into),
+            severity: None,
+            priority: None,
+            effort: None,
+            component: None,
+            module: None,
+            description: Some("\n  \nFirst line\nSecond line".into()),
+        }
+    }
+
+    fn ids(items: &[ReportItem]) -> Vec<&str> {
+        items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    fn text_of(page: &Page) -> String {
+        String::from_utf8_lossy(&page.ops).into_owned()
+    }
+
+    #[tokio::test]
+    async fn items_follow_the_board_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+
+        let items = fetch_items(&db, &ReportFilter::default()).await.unwrap();
+        assert_eq!(ids(&items), ["CT-1", "FEAT-1", "BUG-2", "BUG-1"]);
+        assert_eq!(items[0].section, None);
+        assert_eq!(items[2].section.as_deref(), Some("Todo"));
+    }
+
+    #[tokio::test]
+    async fn filters_combine_and_values_within_a_filter_alternate() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+
+        let filter = ReportFilter {
+            sections: vec!["Todo".into(), "Done".into()],
+            types: vec!["BUG".into()],
+            ..Default::default()
+        };
+        let items = fetch_items(&db, &filter).await.unwrap();
+        assert_eq!(ids(&items), ["BUG-2", "BUG-1"]);
+
+        let filter = ReportFilter {
+            priorities: vec!["Faible".into()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&fetch_items(&db, &filter).await.unwrap()), ["FEAT-1"]);
+    }
+
+    #[tokio::test]
+    async fn missing_database_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = fetch_items(&dir.path().join("absent.db"), &ReportFilter::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn sections_keep_the_board_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        let items = fetch_items(&db, &ReportFilter::default()).await.unwrap();
+
+        let groups = group_items(items, ReportGroup::Section);
+        let labels: Vec<&str> = groups.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, [NO_GROUP, "Done", "Todo"]);
+        assert_eq!(ids(&groups[2].1), ["BUG-2", "BUG-1"]);
+    }
+
+    #[tokio::test]
+    async fn other_fields_are_sorted_with_blank_values_last() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path()).await;
+        let items = fetch_items(&db, &ReportFilter::default()).await.unwrap();
+
+        let groups = group_items(items, ReportGroup::Component);
+        let summary: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|(label, items)| (label.as_str(), ids(items)))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("core", vec!["CT-1", "BUG-1"]),
+                ("ui", vec!["FEAT-1"]),
+                (NO_GROUP, vec!["BUG-2"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_groups_continue_on_new_pages_with_the_header_repeated() {
+        let items: Vec<ReportItem> = (1..=120)
+            .map(|n| item(&format!("BUG-{}", n), Some("Todo")))
+            .collect();
+        let groups = vec![("Todo".to_string(), items)];
+
+        let mut renderer = Renderer::new(A4);
+        renderer.title_page("Board", None, &groups);
+        renderer.group_table("Todo", &groups[0].1);
+        renderer.footers();
+
+        let pages: Vec<String> = renderer.pages.iter().map(text_of).collect();
+        assert!(pages.len() >= 4, "{} pages", pages.len());
+        assert!(pages[0].contains("120 tickets"));
+        assert!(pages[1].contains("Todo \\(120\\)"));
+        for page in &pages[2..] {
+            assert!(page.contains("Todo \\(suite\\)"));
+            assert!(page.contains("(Description)"));
+        }
+        let rows: usize = pages
+            .iter()
+            .map(|page| page.matches("(Ticket BUG-").count())
+            .sum();
+        assert_eq!(rows, 120);
+        // Descriptions show their first non-blank line only.
+        assert!(pages[1].contains("(First line)"));
+        assert!(!pages[1].contains("Second line"));
+        let last = format!("(Page {0} / {0})", pages.len());
+        assert!(pages.last().unwrap().contains(&last));
+    }
+
+    #[test]
+    fn title_page_counts_each_group() {
+        let groups = vec![
+            ("Todo".to_string(), vec![item("BUG-1", Some("Todo"))]),
+            (
+                "Done".to_string(),
+                vec![item("BUG-2", Some("Done")), item("BUG-3", Some("Done"))],
+            ),
+        ];
+        let mut renderer = Renderer::new(LETTER);
+        renderer.title_page("Board", None, &groups);
+
+        let page = text_of(&renderer.pages[0]);
+        assert!(page.contains("3 tickets"));
+        assert!(page.contains("(Todo)") && page.contains("(Done)"));
+        assert!(page.contains("(1)") && page.contains("(2)"));
+    }
+
+    #[tokio::test]
+    async fn report_is_written_with_the_folder_name_as_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Roadmap");
+        std::fs::create_dir(&folder).unwrap();
+        let db = project(&folder).await;
+        let dest = dir.path().join("report.pdf");
+
+        let layout = ReportLayout {
+            landscape: true,
+            ..Default::default()
+        };
+        let result = write_report(&db, &ReportFilter::default(), &layout, None, &dest)
+            .await
+            .unwrap();
+
+        let bytes = std::fs::read(&dest).unwrap();
+        assert_eq!(result.size_bytes, bytes.len() as u64);
+        // The title page, then one page per section.
+        assert_eq!(result.pages, 4);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("/MediaBox [0 0 841.89 595.28]"));
+        assert!(text.contains(&pdf::utf16_string("Roadmap")));
+        assert!(!dir.path().join("report.pdf.tmp").exists());
+    }
+
+    fn write_png(path: &Path, width: u32, height: u32, color: png::ColorType, pixels: &[u8]) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut encoder = png::Encoder::new(file, width, height);
+        encoder.set_color(color);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn rgba_logo_is_split_into_color_and_mask() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo.png");
+        write_png(
+            &path,
+            2,
+            1,
+            png::ColorType::Rgba,
+            &[255, 0, 0, 128, 0, 0, 255, 255],
+        );
+
+        let logo = load_logo(&path).unwrap();
+        assert_eq!((logo.width, logo.height), (2, 1));
+        assert_eq!(logo.rgb, [255, 0, 0, 0, 0, 255]);
+        assert_eq!(logo.alpha, Some(vec![128, 255]));
+    }
+
+    #[test]
+    fn gray_logo_is_expanded_to_rgb_without_mask() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo.png");
+        write_png(&path, 2, 1, png::ColorType::Grayscale, &[10, 200]);
+
+        let logo = load_logo(&path).unwrap();
+        assert_eq!(logo.rgb, [10, 10, 10, 200, 200, 200]);
+        assert_eq!(logo.alpha, None);
+    }
+
+    #[test]
+    fn oversized_or_invalid_logos_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        let width = LOGO_MAX_PIXELS + 1;
+        write_png(
+            &path,
+            width,
+            1,
+            png::ColorType::Grayscale,
+            &vec![0; width as usize],
+        );
+        let err = load_logo(&path).err().unwrap();
+        assert!(matches!(err, AppError::Validation(message) if message.contains("4096x4096")));
+
+        let path = dir.path().join("fake.png");
+        std::fs::write(&path, b"not a png").unwrap();
+        let err = load_logo(&path).err().unwrap();
+        assert!(matches!(err, AppError::Validation(message) if message.starts_with("invalid PNG")));
+    }
+}
@@ -0,0 +1,449 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use unicode_normalization::UnicodeNormalization;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Advance widths of ASCII 32..=126 in Helvetica, per 1000 units of size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Advance widths of ASCII 32..=126 in Helvetica-Bold.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+const ELLIPSIS: char = '…';
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+/// An RGB image with an optional alpha channel, 8 bits per sample.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+    pub alpha: Option<Vec<u8>>,
+}
+
+/// Drawing operations of one page, in points from the bottom-left corner.
+pub struct Page {
+    pub width: f64,
+    pub height: f64,
+    pub(super) ops: Vec<u8>,
+}
+
+impl Page {
+    pub fn new(width: f64, height: f64) -> Self {
+        Page {
+            width,
+            height,
+            ops: Vec::new(),
+        }
+    }
+
+    /// Draw `text` with its baseline starting at `x`, `y`. Characters
+    /// outside WinAnsi are drawn as `?`.
+    pub fn text(&mut self, x: f64, y: f64, font: Font, size: f64, text: &str) {
+        let name = match font {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        };
+        let _ = write!(
+            self.ops,
+            "BT /{} {:.1} Tf {:.2} {:.2} Td (",
+            name, size, x, y
+        );
+        for byte in encode(text) {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                self.ops.push(b'\\');
+            }
+            self.ops.push(byte);
+        }
+        self.ops.extend_from_slice(b") Tj ET\n");
+    }
+
+    /// Fill a rectangle with a gray level (0 black, 1 white).
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, gray: f64) {
+        let _ = writeln!(
+            self.ops,
+            "{:.2} g {:.2} {:.2} {:.2} {:.2} re f 0 g",
+            gray, x, y, width, height
+        );
+    }
+
+    /// Stroke a 0.5 pt line in a gray level.
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, gray: f64) {
+        let _ = writeln!(
+            self.ops,
+            "{:.2} G 0.5 w {:.2} {:.2} m {:.2} {:.2} l S 0 G",
+            gray, x1, y1, x2, y2
+        );
+    }
+
+    /// Draw the document's image into the given box.
+    pub fn image(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let _ = writeln!(
+            self.ops,
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im1 Do Q",
+            width, height, x, y
+        );
+    }
+}
+
+/// Object writer keeping the byte offset of every object for the xref table.
+struct Writer {
+    out: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Writer {
+    fn object(&mut self, id: usize, body: &str) {
+        self.offsets[id] = self.out.len();
+        let _ = write!(self.out, "{} 0 obj\n{}\nendobj\n", id, body);
+    }
+
+    fn stream(&mut self, id: usize, dict: &str, data: &[u8]) -> std::io::Result<()> {
+        let data = deflate(data)?;
+        self.offsets[id] = self.out.len();
+        let _ = write!(
+            self.out,
+            "{} 0 obj\n<< {} /Filter /FlateDecode /Length {} >>\nstream\n",
+            id,
+            dict,
+            data.len()
+        );
+        self.out.extend_from_slice(&data);
+        self.out.extend_from_slice(b"\nendstream\nendobj\n");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Serialize `pages` into a PDF 1.4 document titled `title`. Text uses the
+/// standard Helvetica fonts (not embedded) in WinAnsi encoding; streams are
+/// Flate-compressed.
+pub fn write_document(
+    pages: &[Page],
+    image: Option<&Image>,
+    title: &str,
+) -> std::io::Result<Vec<u8>> {
+    // 1 catalog, 2 page tree, 3-4 fonts, 5 info, 6-7 image and its mask,
+    // then a content stream and a page object per page.
+    const FIRST_PAGE_ID: usize = 8;
+    let count = FIRST_PAGE_ID + 2 * pages.len();
+    let mut writer = Writer {
+        out: b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+        offsets: vec![0; count],
+    };
+
+    writer.object(1, "<< /Type /Catalog /Pages 2 0 R >>");
+    let kids = (0..pages.len())
+        .map(|index| format!("{} 0 R", FIRST_PAGE_ID + 2 * index + 1))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writer.object(
+        2,
+        &format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()),
+    );
+    writer.object(
+        3,
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+    );
+    writer.object(
+        4,
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+    );
+    writer.object(
+        5,
+        &format!(
+            "<< /Title {} /Producer (Ticketflow) /CreationDate (D:{}) >>",
+            utf16_string(title),
+            chrono::Local::now().format("%Y%m%d%H%M%S")
+        ),
+    );
+
+    match image {
+        Some(image) => {
+            let mask = match &image.alpha {
+                Some(alpha) => {
+                    writer.stream(
+                        7,
+                        &format!(
+                            "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                             /ColorSpace /DeviceGray /BitsPerComponent 8",
+                            image.width, image.height
+                        ),
+                        alpha,
+                    )?;
+                    " /SMask 7 0 R"
+                }
+                None => {
+                    writer.object(7, "null");
+                    ""
+                }
+            };
+            writer.stream(
+                6,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                     /ColorSpace /DeviceRGB /BitsPerComponent 8{}",
+                    image.width, image.height, mask
+                ),
+                &image.rgb,
+            )?;
+        }
+        None => {
+            writer.object(6, "null");
+            writer.object(7, "null");
+        }
+    }
+
+    let xobjects = if image.is_some() {
+        " /XObject << /Im1 6 0 R >>"
+    } else {
+        ""
+    };
+    for (index, page) in pages.iter().enumerate() {
+        let contents = FIRST_PAGE_ID + 2 * index;
+        writer.stream(contents, "", &page.ops)?;
+        writer.object(
+            contents + 1,
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >>{} >> /Contents {} 0 R >>",
+                page.width, page.height, xobjects, contents
+            ),
+        );
+    }
+
+    let xref = writer.out.len();
+    let _ = write!(writer.out, "xref\n0 {}\n0000000000 65535 f \n", count);
+    for offset in &writer.offsets[1..] {
+        let _ = writeln!(writer.out, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        writer.out,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+        count, xref
+    );
+    Ok(writer.out)
+}
+
+/// Width of `text` in points.
+pub fn text_width(text: &str, font: Font, size: f64) -> f64 {
+    text.chars().map(|c| char_width(c, font)).sum::<f64>() * size / 1000.0
+}
+
+/// `text` on one line, cut with an ellipsis to fit `max_width`.
+pub fn fit(text: &str, font: Font, size: f64, max_width: f64) -> String {
+    let text: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if text_width(&text, font, size) <= max_width {
+        return text;
+    }
+    let budget = max_width * 1000.0 / size - char_width(ELLIPSIS, font);
+    let mut used = 0.0;
+    let mut fitted = String::new();
+    for c in text.chars() {
+        used += char_width(c, font);
+        if used > budget {
+            break;
+        }
+        fitted.push(c);
+    }
+    fitted.truncate(fitted.trim_end().len());
+    fitted.push(ELLIPSIS);
+    fitted
+}
+
+/// Width of a character per 1000 units. Accented letters take the width
+/// of their base letter.
+fn char_width(c: char, font: Font) -> f64 {
+    let table = match font {
+        Font::Regular => &HELVETICA_WIDTHS,
+        Font::Bold => &HELVETICA_BOLD_WIDTHS,
+    };
+    let base = if c.is_ascii() {
+        c
+    } else {
+        c.nfd().next().unwrap_or(c)
+    };
+    let width = match base {
+        ' '..='~' => table[base as usize - 32],
+        '…' | '—' | '‰' | 'Œ' | 'Æ' => 1000,
+        'œ' | 'æ' => 944,
+        '‘' | '’' | '‚' => 222,
+        '•' => 350,
+        _ => 556,
+    };
+    f64::from(width)
+}
+
+/// WinAnsi bytes of `text`; unmappable characters become `?`.
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut buffer = [0u8; 4];
+    for c in text.chars() {
+        let c = if c.is_control() { ' ' } else { c };
+        let (encoded, _, unmappable) = encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buffer));
+        if unmappable || encoded.len() != 1 {
+            bytes.push(b'?');
+        } else {
+            bytes.push(encoded[0]);
+        }
+    }
+    bytes
+}
+
+/// PDF text string in UTF-16BE with a byte order mark, as hex.
+pub(super) fn utf16_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(hex, "{:04X}", unit);
+    }
+    hex.push('>');
+    hex
+}
+
+fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inflate(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        std::io::Read::read_to_end(&mut flate2::read::ZlibDecoder::new(data), &mut out).unwrap();
+        out
+    }
+
+    fn find(haystack: &[u8], needle: &[u8], from: usize) -> usize {
+        from + haystack[from..]
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .unwrap()
+    }
+
+    #[test]
+    fn text_is_encoded_in_winansi_and_escaped() {
+        let mut page = Page::new(100.0, 100.0);
+        page.text(1.0, 2.0, Font::Bold, 8.0, "É (a\\b) 日\t€");
+
+        let expected = b"BT /F2 8.0 Tf 1.00 2.00 Td (\xC9 \\(a\\\\b\\) ? \x80) Tj ET\n";
+        assert_eq!(page.ops, expected);
+    }
+
+    #[test]
+    fn fit_keeps_short_text_and_cuts_long_text_with_an_ellipsis() {
+        assert_eq!(
+            fit("Short\ttitle", Font::Regular, 10.0, 100.0),
+            "Short title"
+        );
+
+        let long = "A fairly long ticket title that cannot fit";
+        let fitted = fit(long, Font::Regular, 10.0, 80.0);
+        assert!(fitted.ends_with(ELLIPSIS));
+        assert!(!fitted.trim_end_matches(ELLIPSIS).ends_with(' '));
+        assert!(text_width(&fitted, Font::Regular, 10.0) <= 80.0);
+        assert!(long.starts_with(fitted.trim_end_matches(ELLIPSIS)));
+    }
+
+    #[test]
+    fn widths_follow_the_helvetica_metrics() {
+        assert_eq!(text_width("i", Font::Regular, 10.0), 2.22);
+        assert_eq!(text_width("i", Font::Bold, 10.0), 2.78);
+        // Accented letters take the width of their base letter.
+        assert_eq!(
+            text_width("é", Font::Regular, 1000.0),
+            text_width("e", Font::Regular, 1000.0)
+        );
+        assert_eq!(text_width("…", Font::Regular, 1000.0), 1000.0);
+    }
+
+    #[test]
+    fn titles_are_utf16_hex_strings() {
+        assert_eq!(utf16_string("É"), "<FEFF00C9>");
+        assert_eq!(utf16_string("😀"), "<FEFFD83DDE00>");
+    }
+
+    #[test]
+    fn xref_offsets_point_at_their_objects() {
+        let mut first = Page::new(200.0, 100.0);
+        first.text(10.0, 10.0, Font::Regular, 8.0, "Hello");
+        let second = Page::new(200.0, 100.0);
+        let image = Image {
+            width: 1,
+            height: 1,
+            rgb: vec![1, 2, 3],
+            alpha: Some(vec![4]),
+        };
+
+        let pdf = write_document(&[first, second], Some(&image), "Board").unwrap();
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/SMask 7 0 R"));
+
+        // The xref table and trailer are plain ASCII after the streams.
+        let startxref = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = text[startxref..].lines().next().unwrap().parse().unwrap();
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        assert!(table.starts_with("xref\n0 12\n"));
+        for (id, entry) in (1..).zip(table.lines().skip(3).take(11)) {
+            let offset: usize = entry[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", id);
+            assert!(
+                pdf[offset..].starts_with(header.as_bytes()),
+                "object {}",
+                id
+            );
+        }
+
+        let contents: &[u8] = b"8 0 obj\n<<  /Filter /FlateDecode /Length ";
+        let start = find(&pdf, contents, 0);
+        let stream = find(&pdf, b"stream\n", start) + b"stream\n".len();
+        let end = find(&pdf, b"\nendstream", stream);
+        assert_eq!(
+            inflate(&pdf[stream..end]),
+            b"BT /F1 8.0 Tf 10.00 10.00 Td (Hello) Tj ET\n"
+        );
+    }
+
+    #[test]
+    fn documents_without_a_logo_have_null_image_objects() {
+        let pdf = write_document(&[Page::new(10.0, 10.0)], None, "Board").unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("6 0 obj\nnull\nendobj"));
+        assert!(text.contains("7 0 obj\nnull\nendobj"));
+        assert!(!text.contains("/XObject"));
+    }
+}
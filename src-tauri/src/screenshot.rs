@@ -0,0 +1,579 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{Manager, WebviewWindow};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Subdirectory of `app_cache_dir` holding the captures.
+const SCREENSHOT_DIR: &str = "screenshots";
+
+/// How long a capture stays on disk before it is deleted.
+const SCREENSHOT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Same for region captures, named `region-*.png`.
+const REGION_TTL: Duration = Duration::from_secs(10 * 60);
+const REGION_PREFIX: &str = "region-";
+
+/// Largest width and height of a region capture, in pixels.
+const MAX_REGION_PIXELS: u32 = 4096;
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Capture the content of the calling window as a PNG in
+/// `<app_cache_dir>/screenshots/` and return its absolute path. The file is
+/// deleted after five minutes, so the frontend should read or copy it right
+/// away (e.g. to attach it to a bug report).
+#[tauri::command]
+pub async fn screenshot_window(window: WebviewWindow) -> Result<String, AppError> {
+    let dir = screenshot_dir(&window).await?;
+    let png = capture_png(&window).await?;
+    save_capture(&dir, "screenshot-", &png, SCREENSHOT_TTL).await
+}
+
+/// Capture only the `width`x`height` rectangle at `x`, `y` of the calling
+/// window, e.g. one ticket card, so the rest of the board is not shared.
+/// Coordinates are pixels of the capture: CSS pixels times
+/// `devicePixelRatio`. The region must lie inside the window and be at most
+/// 4096x4096. Saved like `screenshot_window`, and deleted after ten minutes.
+#[tauri::command]
+pub async fn screenshot_region(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    window: WebviewWindow,
+) -> Result<String, AppError> {
+    let (x, y) = validate_region(x, y, width, height)?;
+    let dir = screenshot_dir(&window).await?;
+    let png = capture_png(&window).await?;
+    let cropped = crop_png(&png, x, y, width, height)?;
+    save_capture(&dir, REGION_PREFIX, &cropped, REGION_TTL).await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// `<app_cache_dir>/screenshots/`, created if needed and cleared of expired
+/// captures.
+async fn screenshot_dir(window: &WebviewWindow) -> Result<PathBuf, AppError> {
+    let dir = window
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Io(format!("cannot resolve cache directory: {}", e)))?
+        .join(SCREENSHOT_DIR);
+    tokio::fs::create_dir_all(&dir).await?;
+    remove_expired(&dir);
+    Ok(dir)
+}
+
+/// PNG of the window content, captured on the main thread.
+async fn capture_png(window: &WebviewWindow) -> Result<Vec<u8>, AppError> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let capture_window = window.clone();
+    window
+        .run_on_main_thread(move || {
+            tx.send(platform::capture(&capture_window)).ok();
+        })
+        .map_err(|e| AppError::Io(format!("cannot capture window: {}", e)))?;
+    rx.await
+        .map_err(|_| AppError::Io("window capture was dropped".into()))?
+        .map_err(|e| AppError::Io(format!("cannot capture window: {}", e)))
+}
+
+/// Write `png` to `<dir>/<prefix><ms>.png`, schedule its deletion after
+/// `ttl` and return its path.
+async fn save_capture(
+    dir: &Path,
+    prefix: &str,
+    png: &[u8],
+    ttl: Duration,
+) -> Result<String, AppError> {
+    let path = dir.join(format!("{}{}.png", prefix, now_ms()));
+    tokio::fs::write(&path, png).await?;
+
+    let expiring = path.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(ttl).await;
+        tokio::fs::remove_file(&expiring).await.ok();
+    });
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Check the bounds of a region that do not depend on the window size, and
+/// return its origin.
+fn validate_region(x: i32, y: i32, width: u32, height: u32) -> Result<(u32, u32), AppError> {
+    let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+        return Err(AppError::Validation(format!(
+            "region origin {},{} is outside the window",
+            x, y
+        )));
+    };
+    if width == 0 || height == 0 || width > MAX_REGION_PIXELS || height > MAX_REGION_PIXELS {
+        return Err(AppError::Validation(format!(
+            "region must be 1x1 to {0}x{0} pixels, got {1}x{2}",
+            MAX_REGION_PIXELS, width, height
+        )));
+    }
+    Ok((x, y))
+}
+
+/// Cut the `width`x`height` rectangle at `x`, `y` out of a PNG, which must
+/// contain it entirely.
+fn crop_png(png_data: &[u8], x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, AppError> {
+    let invalid =
+        |e: png::DecodingError| AppError::Io(format!("cannot read window capture: {}", e));
+    let mut decoder = png::Decoder::new(png_data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(invalid)?;
+
+    let fits_x = x
+        .checked_add(width)
+        .is_some_and(|right| right <= frame.width);
+    let fits_y = y
+        .checked_add(height)
+        .is_some_and(|bottom| bottom <= frame.height);
+    if !fits_x || !fits_y {
+        return Err(AppError::Validation(format!(
+            "region {}x{} at {},{} is outside the {}x{} window",
+            width, height, x, y, frame.width, frame.height
+        )));
+    }
+
+    let channels = frame.color_type.samples();
+    let row_start = x as usize * channels;
+    let row_len = width as usize * channels;
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    for row in buffer
+        .chunks_exact(frame.line_size)
+        .skip(y as usize)
+        .take(height as usize)
+    {
+        pixels.extend_from_slice(&row[row_start..row_start + row_len]);
+    }
+
+    let encode_error = |e: png::EncodingError| AppError::Io(format!("cannot encode PNG: {}", e));
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(frame.color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    writer.write_image_data(&pixels).map_err(encode_error)?;
+    writer.finish().map_err(encode_error)?;
+    Ok(out)
+}
+
+/// Delete captures older than their TTL whose cleanup task did not run
+/// because the app was closed in the meantime.
+fn remove_expired(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let expired: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            let ttl = if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(REGION_PREFIX)
+            {
+                REGION_TTL
+            } else {
+                SCREENSHOT_TTL
+            };
+            entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > ttl)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    for path in expired {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("screenshot_window: {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use gtk::prelude::*;
+    use gtk::{cairo, gdk};
+    use tauri::WebviewWindow;
+
+    /// Render the window's widget tree into an image surface. Unlike reading
+    /// back the X11 window (`XGetImage`), this also works on Wayland and
+    /// does not pick up windows overlapping ours.
+    pub fn capture(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+        let widget = window.gtk_window().map_err(|e| e.to_string())?;
+        let scale = widget.scale_factor();
+        let (width, height) = (
+            widget.allocated_width() * scale,
+            widget.allocated_height() * scale,
+        );
+        if width <= 0 || height <= 0 {
+            return Err("window is not mapped".into());
+        }
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)
+            .map_err(|e| e.to_string())?;
+        {
+            let cr = cairo::Context::new(&surface).map_err(|e| e.to_string())?;
+            cr.scale(f64::from(scale), f64::from(scale));
+            widget.draw(&cr);
+        }
+        let pixbuf = gdk::pixbuf_get_from_surface(&surface, 0, 0, width, height)
+            .ok_or("cannot read the rendered window")?;
+        pixbuf
+            .save_to_bufferv("png", &[])
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use tauri::WebviewWindow;
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+        ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS, PW_CLIENTONLY};
+    use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    /// Also capture DirectComposition content, which is where WebView2
+    /// draws; not in the crate's flag constants.
+    const PW_RENDERFULLCONTENT: u32 = 0x2;
+
+    /// `PrintWindow` the client area into a bitmap and encode it as PNG.
+    pub fn capture(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+        let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0);
+        let mut rect = RECT::default();
+        // SAFETY: `hwnd` is the live window handle and `rect` a valid out pointer.
+        unsafe { GetClientRect(hwnd, &mut rect) }.map_err(|e| e.to_string())?;
+        let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+        if width <= 0 || height <= 0 {
+            return Err("window has no client area".into());
+        }
+
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        // SAFETY: every GDI object created here is released before the block
+        // ends, and `pixels` is large enough for a top-down 32 bpp image.
+        let (printed, lines) = unsafe {
+            let screen = GetDC(None);
+            let dc = CreateCompatibleDC(Some(screen));
+            let bitmap = CreateCompatibleBitmap(screen, width, height);
+            let previous = SelectObject(dc, bitmap.into());
+            let printed = PrintWindow(
+                hwnd,
+                dc,
+                PRINT_WINDOW_FLAGS(PW_CLIENTONLY.0 | PW_RENDERFULLCONTENT),
+            )
+            .as_bool();
+            SelectObject(dc, previous);
+
+            let mut info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let lines = GetDIBits(
+                dc,
+                bitmap,
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr().cast()),
+                &mut info,
+                DIB_RGB_COLORS,
+            );
+
+            let _ = DeleteObject(bitmap.into());
+            let _ = DeleteDC(dc);
+            ReleaseDC(None, screen);
+            (printed, lines)
+        };
+        if !printed || lines == 0 {
+            return Err("PrintWindow failed".into());
+        }
+
+        // GDI hands out BGRX; PNG wants RGBA.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 0xff;
+        }
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(png)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    use objc2::rc::Retained;
+    use objc2_app_kit::NSWindow;
+    use objc2_foundation::{NSMutableData, NSString};
+    use tauri::WebviewWindow;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct CGPoint {
+        x: f64,
+        y: f64,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct CGSize {
+        width: f64,
+        height: f64,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct CGRect {
+        origin: CGPoint,
+        size: CGSize,
+    }
+
+    const WINDOW_LIST_OPTION_INCLUDING_WINDOW: u32 = 1 << 3;
+    const WINDOW_IMAGE_BOUNDS_IGNORE_FRAMING: u32 = 1 << 0;
+    const WINDOW_IMAGE_BEST_RESOLUTION: u32 = 1 << 3;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        static CGRectNull: CGRect;
+        fn CGWindowListCreateImage(
+            bounds: CGRect,
+            list_option: u32,
+            window_id: u32,
+            image_option: u32,
+        ) -> *mut c_void;
+        fn CGImageRelease(image: *mut c_void);
+    }
+
+    #[link(name = "ImageIO", kind = "framework")]
+    extern "C" {
+        fn CGImageDestinationCreateWithData(
+            data: *mut c_void,
+            kind: *const c_void,
+            count: isize,
+            options: *const c_void,
+        ) -> *mut c_void;
+        fn CGImageDestinationAddImage(
+            destination: *mut c_void,
+            image: *mut c_void,
+            properties: *const c_void,
+        );
+        fn CGImageDestinationFinalize(destination: *mut c_void) -> bool;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(object: *const c_void);
+    }
+
+    /// `CGWindowListCreateImage` of our own window (no screen recording
+    /// permission needed for it), encoded to PNG with ImageIO.
+    #[allow(unused_unsafe)]
+    pub fn capture(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+        let window_ptr = window.ns_window().map_err(|e| e.to_string())?;
+        // SAFETY: Tauri hands out the live NSWindow, and this runs on the
+        // main thread.
+        let ns_window: Retained<NSWindow> =
+            unsafe { Retained::retain(window_ptr as *mut NSWindow) }
+                .ok_or("window has no NSWindow")?;
+        let window_id = unsafe { ns_window.windowNumber() } as u32;
+
+        // SAFETY: every CoreFoundation object created here is released below;
+        // NSMutableData and NSString are toll-free bridged to CFMutableData
+        // and CFString.
+        unsafe {
+            let image = CGWindowListCreateImage(
+                CGRectNull,
+                WINDOW_LIST_OPTION_INCLUDING_WINDOW,
+                window_id,
+                WINDOW_IMAGE_BOUNDS_IGNORE_FRAMING | WINDOW_IMAGE_BEST_RESOLUTION,
+            );
+            if image.is_null() {
+                return Err("CGWindowListCreateImage failed".into());
+            }
+
+            let data = NSMutableData::new();
+            let kind = NSString::from_str("public.png");
+            let destination = CGImageDestinationCreateWithData(
+                Retained::as_ptr(&data) as *mut c_void,
+                Retained::as_ptr(&kind) as *const c_void,
+                1,
+                std::ptr::null(),
+            );
+            if destination.is_null() {
+                CGImageRelease(image);
+                return Err("cannot create PNG encoder".into());
+            }
+            CGImageDestinationAddImage(destination, image, std::ptr::null());
+            let finalized = CGImageDestinationFinalize(destination);
+            CFRelease(destination);
+            CGImageRelease(image);
+            if !finalized {
+                return Err("cannot encode PNG".into());
+            }
+            Ok(data.to_vec())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use tauri::WebviewWindow;
+
+    pub fn capture(_window: &WebviewWindow) -> Result<Vec<u8>, String> {
+        Err("window capture is not supported on this platform".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `width`x`height` RGBA PNG whose pixel at `x`, `y` is `[x, y, 0, 255]`.
+    fn gradient_png(width: u32, height: u32) -> Vec<u8> {
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&[x as u8, y as u8, 0, 255]);
+            }
+        }
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    fn decode(data: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(data).read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buffer).unwrap();
+        buffer.truncate(frame.buffer_size());
+        (frame.width, frame.height, buffer)
+    }
+
+    #[tokio::test]
+    async fn capture_is_saved_as_a_png_in_the_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = gradient_png(4, 3);
+
+        let path = save_capture(dir.path(), "screenshot-", &png, SCREENSHOT_TTL)
+            .await
+            .unwrap();
+        let path = PathBuf::from(path);
+        assert!(path.is_absolute());
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert_eq!(path.extension().and_then(|ext| ext.to_str()), Some("png"));
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("screenshot-"));
+        assert_eq!(std::fs::read(&path).unwrap(), png);
+    }
+
+    #[test]
+    fn expired_captures_are_swept() {
+        let dir = tempfile::tempdir().unwrap();
+        let age = |name: &str, age: Duration| {
+            let path = dir.path().join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            file.set_modified(SystemTime::now() - age).unwrap();
+            path
+        };
+        let old_screenshot = age("screenshot-1.png", SCREENSHOT_TTL + Duration::from_secs(60));
+        // Regions live longer than screenshots.
+        let recent_region = age("region-1.png", SCREENSHOT_TTL + Duration::from_secs(60));
+        let old_region = age("region-2.png", REGION_TTL + Duration::from_secs(60));
+        let fresh = age("screenshot-2.png", Duration::ZERO);
+
+        remove_expired(dir.path());
+        assert!(!old_screenshot.exists());
+        assert!(recent_region.exists());
+        assert!(!old_region.exists());
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn region_inside_the_window_is_cropped() {
+        let cropped = crop_png(&gradient_png(40, 30), 10, 5, 20, 25).unwrap();
+        let (width, height, pixels) = decode(&cropped);
+        assert_eq!((width, height), (20, 25));
+        assert_eq!(&pixels[..4], [10, 5, 0, 255]);
+        assert_eq!(&pixels[pixels.len() - 4..], [29, 29, 0, 255]);
+    }
+
+    #[test]
+    fn region_past_the_window_edge_is_refused() {
+        let png = gradient_png(40, 30);
+        for (x, y, width, height) in [(30, 0, 11, 1), (0, 25, 1, 6), (u32::MAX, 0, 2, 1)] {
+            assert!(
+                matches!(
+                    crop_png(&png, x, y, width, height),
+                    Err(AppError::Validation(_))
+                ),
+                "{}x{} at {},{}",
+                width,
+                height,
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn region_bounds_are_checked_before_capturing() {
+        assert_eq!(validate_region(0, 7, 1, MAX_REGION_PIXELS).unwrap(), (0, 7));
+        for (x, y, width, height) in [
+            (-1, 0, 10, 10),
+            (0, -1, 10, 10),
+            (0, 0, 0, 10),
+            (0, 0, MAX_REGION_PIXELS + 1, 10),
+        ] {
+            assert!(validate_region(x, y, width, height).is_err());
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::{Row, SqliteConnection};
+use tauri::AppHandle;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::stats;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Upper bound on the page size of `search_tickets`.
+const MAX_RESULTS: i64 = 200;
+
+/// Private-use characters FTS5 puts around matches; they become `<mark>`
+/// tags once the text around them is HTML-escaped.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+/// bm25 weights of the `backlog_items_fts` columns: id, title, description,
+/// user_story, specs, criteria, dependencies, component, module.
+const BM25_WEIGHTS: &str = "10.0, 8.0, 3.0, 2.0, 1.0, 1.0, 1.0, 2.0, 2.0";
+
+/// First schema version with the `attachments_fts` index.
+const ATTACHMENT_TEXT_SCHEMA_VERSION: i64 = 12;
+
+/// Extensions of attachments whose text is indexed.
+const TEXT_EXTENSIONS: [&str; 5] = ["txt", "md", "markdown", "log", "csv"];
+
+/// Larger text attachments are stored but not indexed.
+const MAX_INDEXED_TEXT_BYTES: u64 = 1024 * 1024;
+
+/// Upper bound on the number of matches of `find_similar`.
+const MAX_SIMILAR: usize = 50;
+
+/// Matches scoring below this are not returned by `find_similar`.
+const MIN_SIMILARITY: f64 = 0.3;
+
+/// Weight of a description's first line against the title in `find_similar`.
+const DESCRIPTION_WEIGHT: f64 = 0.7;
+
+/// Characters of the description read for its first line.
+const DESCRIPTION_PREFIX: i64 = 300;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One ranked match.
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub title: String,
+    pub section: Option<String>,
+    /// HTML-escaped title with matches wrapped in `<mark>`.
+    pub title_highlight: String,
+    /// HTML-escaped excerpt of the best-matching column, matches wrapped in
+    /// `<mark>`.
+    pub snippet: String,
+    /// File name of the attachment the snippet comes from, when the best
+    /// match is in an attached file rather than the ticket itself.
+    pub attachment_filename: Option<String>,
+    /// bm25 score; lower is more relevant.
+    pub score: f64,
+}
+
+/// Return value of `search_tickets`.
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    /// Total number of matches, for paging.
+    pub total: i64,
+    pub hits: Vec<SearchHit>,
+}
+
+/// One match of `find_similar`.
+#[derive(Debug, Serialize)]
+pub struct SimilarTicket {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub title: String,
+    /// Trigram similarity from 0 to 1.
+    pub score: f64,
+    /// Whether the ticket is in the in-app archive.
+    pub archived: bool,
+}
+
+/// Trigrams of a folded text, each packed into a `u64`, sorted and unique.
+struct Trigrams(Vec<u64>);
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Full-text search over a project's items using the `backlog_items_fts`
+/// index (accent-insensitive through its `remove_diacritics` tokenizer),
+/// ranked by bm25 with title and id weighted highest. The text of attached
+/// plain-text files is searched too (`attachments_fts`); a ticket matching
+/// both ways is listed once, with its best match.
+///
+/// `"quoted phrases"` match exactly and other words match as prefixes. FTS5
+/// operators are not interpreted, so arbitrary user input, unbalanced
+/// quotes included, never makes the query fail. A database without the
+/// index fails with `Validation`.
+///
+/// `title_highlight` and `snippet` are HTML: the ticket text is escaped, so
+/// only the `<mark>` tags are markup.
+#[tauri::command]
+pub async fn search_tickets(
+    db_path: String,
+    query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    app: AppHandle,
+) -> Result<SearchResults, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    search_project(&db, &query, limit, offset).await
+}
+
+/// Tickets whose title resembles `title`, best first, for duplicate
+/// detection while a ticket is being written. Scores are the Dice
+/// coefficient of the character trigrams of both titles, ignoring case,
+/// accents and punctuation; with `with_description` the first line of the
+/// description is compared too, at a lower weight. Archived tickets are
+/// left out unless `include_archived` is set.
+///
+/// Everything is scored in memory, which takes a few milliseconds on ten
+/// thousand tickets, so it can run on every typing pause.
+#[tauri::command]
+pub async fn find_similar(
+    db_path: String,
+    title: String,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+    with_description: Option<bool>,
+    app: AppHandle,
+) -> Result<Vec<SimilarTicket>, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let query = Trigrams::of(&title);
+    if query.0.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(5).clamp(1, MAX_SIMILAR);
+    let with_description = with_description.unwrap_or(false);
+
+    with_timeout(
+        async {
+            let mut conn = project_db::open_read_only(&db).await?;
+            let mut sql = format!(
+                "SELECT id, type, title, substr(description, 1, {0}), 0 FROM backlog_items",
+                DESCRIPTION_PREFIX
+            );
+            if include_archived.unwrap_or(false)
+                && stats::table_exists(&mut conn, "archived_items").await?
+            {
+                sql.push_str(&format!(
+                    " UNION ALL SELECT id, type, title, substr(description, 1, {0}), 1
+                     FROM archived_items",
+                    DESCRIPTION_PREFIX
+                ));
+            }
+            let rows: Vec<(String, String, String, Option<String>, bool)> =
+                sqlx::query_as(&sql).fetch_all(&mut conn).await?;
+
+            let mut matches: Vec<SimilarTicket> = rows
+                .into_iter()
+                .filter_map(|(id, item_type, title, description, archived)| {
+                    let mut score = query.dice(&Trigrams::of(&title));
+                    if with_description {
+                        if let Some(line) = description
+                            .as_deref()
+                            .and_then(|d| d.lines().find(|line| !line.trim().is_empty()))
+                        {
+                            score = score.max(DESCRIPTION_WEIGHT * query.dice(&Trigrams::of(line)));
+                        }
+                    }
+                    (score >= MIN_SIMILARITY).then_some(SimilarTicket {
+                        id,
+                        item_type,
+                        title,
+                        score,
+                        archived,
+                    })
+                })
+                .collect();
+            matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+            matches.truncate(limit);
+            Ok(matches)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// `search_tickets` on an already validated path.
+async fn search_project(
+    db_path: &Path,
+    query: &str,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<SearchResults, AppError> {
+    let Some(fts_query) = fts_query(query) else {
+        return Ok(SearchResults {
+            total: 0,
+            hits: Vec::new(),
+        });
+    };
+    let limit = limit.unwrap_or(20).clamp(1, MAX_RESULTS);
+    let offset = offset.unwrap_or(0).max(0);
+
+    with_timeout(
+        async {
+            let mut conn = project_db::open_read_only(db_path).await?;
+            if !stats::table_exists(&mut conn, "backlog_items_fts").await? {
+                return Err(AppError::Validation(
+                    "this project has no search index; open it in the app to create it".into(),
+                ));
+            }
+            let with_attachments =
+                project_db::schema_version(&mut conn).await? >= ATTACHMENT_TEXT_SCHEMA_VERSION;
+            // Every match as (item rowid, title highlight, snippet,
+            // attachment file name, score).
+            let matches = format!(
+                "SELECT rowid AS item_rowid,
+                   highlight(backlog_items_fts, 1, '\u{E000}', '\u{E001}') AS title_highlight,
+                   snippet(backlog_items_fts, -1, '\u{E000}', '\u{E001}', '…', 24) AS snippet,
+                   NULL AS attachment_filename,
+                   bm25(backlog_items_fts, {}) AS score
+                 FROM backlog_items_fts
+                 WHERE backlog_items_fts MATCH ?1
+                 {}",
+                BM25_WEIGHTS,
+                if with_attachments {
+                    "UNION ALL
+                     SELECT bi.rowid, NULL,
+                       snippet(attachments_fts, 0, '\u{E000}', '\u{E001}', '…', 24),
+                       a.filename, bm25(attachments_fts)
+                     FROM attachments_fts
+                     JOIN attachments a ON a.id = attachments_fts.rowid
+                     JOIN backlog_items bi ON bi.id = a.item_id AND bi.project_id = a.project_id
+                     WHERE attachments_fts MATCH ?1"
+                } else {
+                    ""
+                }
+            );
+
+            // Materialized so SQLite does not flatten the matches into the
+            // aggregates below, where bm25() cannot be called.
+            let matches = format!("WITH matches AS MATERIALIZED ({})", matches);
+
+            let total: i64 = sqlx::query_scalar(&format!(
+                "{} SELECT COUNT(DISTINCT item_rowid) FROM matches",
+                matches
+            ))
+            .bind(&fts_query)
+            .fetch_one(&mut conn)
+            .await?;
+
+            // SQLite takes the bare columns of a MIN() aggregate from the
+            // row holding the minimum, i.e. each ticket's best match.
+            let sql = format!(
+                "{}, best AS (
+                   SELECT item_rowid, title_highlight, snippet, attachment_filename,
+                     MIN(score) AS score
+                   FROM matches GROUP BY item_rowid
+                 )
+                 SELECT bi.id, bi.type, bi.title, s.title,
+                   COALESCE(best.title_highlight, bi.title), best.snippet,
+                   best.attachment_filename, best.score
+                 FROM best
+                 JOIN backlog_items bi ON bi.rowid = best.item_rowid
+                 LEFT JOIN sections s ON s.id = bi.section_id
+                 ORDER BY best.score
+                 LIMIT ?2 OFFSET ?3",
+                matches
+            );
+            let hits = sqlx::query(&sql)
+                .bind(&fts_query)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&mut conn)
+                .await?
+                .iter()
+                .map(|row| {
+                    Ok(SearchHit {
+                        id: row.try_get(0)?,
+                        item_type: row.try_get(1)?,
+                        title: row.try_get(2)?,
+                        section: row.try_get(3)?,
+                        title_highlight: marked_html(
+                            &row.try_get::<Option<String>, _>(4)?.unwrap_or_default(),
+                        ),
+                        snippet: marked_html(
+                            &row.try_get::<Option<String>, _>(5)?.unwrap_or_default(),
+                        ),
+                        attachment_filename: row.try_get(6)?,
+                        score: row.try_get(7)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+            Ok(SearchResults { total, hits })
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// HTML-escape FTS5 output, turning the `MATCH_START`/`MATCH_END` markers
+/// into `<mark>` tags.
+fn marked_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            MATCH_START => html.push_str("<mark>"),
+            MATCH_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+/// Turn free-form input into a safe FTS5 query: each `"phrase"` (an
+/// unterminated one runs to the end of the input) becomes an exact phrase,
+/// each other word a quoted prefix term. Returns `None` when nothing is left
+/// to search for.
+fn fts_query(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    for (index, part) in input.split('"').enumerate() {
+        let part = part.trim();
+        if !part.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+        if index % 2 == 1 {
+            terms.push(format!("\"{}\"", part));
+        } else {
+            terms.extend(
+                part.split_whitespace()
+                    .filter(|word| word.chars().any(char::is_alphanumeric))
+                    .map(|word| format!("\"{}\"*", word)),
+            );
+        }
+    }
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Searchable text of an attachment: the content of a plain-text file up
+/// to `MAX_INDEXED_TEXT_BYTES`, decoded as UTF-8 (invalid sequences
+/// replaced) with whitespace runs collapsed. `None` for other files.
+pub(crate) fn attachment_text(filename: &str, path: &Path, size: u64) -> Option<String> {
+    let extension = Path::new(filename)
+        .extension()?
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    if !TEXT_EXTENSIONS.contains(&extension.as_str()) || size > MAX_INDEXED_TEXT_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path)
+        .inspect_err(|e| log::warn!("cannot read {} for indexing: {}", path.display(), e))
+        .ok()?;
+    let text = String::from_utf8_lossy(&bytes)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Add the text of attachment `id` to `attachments_fts`. A no-op on
+/// schemas older than v12.
+pub(crate) async fn index_attachment_text(
+    conn: &mut SqliteConnection,
+    id: i64,
+    text: &str,
+) -> Result<(), AppError> {
+    if project_db::schema_version(conn).await? >= ATTACHMENT_TEXT_SCHEMA_VERSION {
+        sqlx::query("INSERT INTO attachments_fts (rowid, attachment_text) VALUES (?, ?)")
+            .bind(id)
+            .bind(text)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Drop attachment `id` from `attachments_fts`, if it was indexed.
+pub(crate) async fn unindex_attachment(
+    conn: &mut SqliteConnection,
+    id: i64,
+) -> Result<(), AppError> {
+    if project_db::schema_version(conn).await? >= ATTACHMENT_TEXT_SCHEMA_VERSION {
+        sqlx::query("DELETE FROM attachments_fts WHERE rowid = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Lowercase `text`, strip accents and turn everything but letters and
+/// digits into single spaces: `"Écran  d'accueil"` gives `"ecran d accueil"`.
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfd().filter(|c| !is_combining_mark(*c)) {
+        if c.is_alphanumeric() {
+            folded.extend(c.to_lowercase());
+        } else if !folded.is_empty() && !folded.ends_with(' ') {
+            folded.push(' ');
+        }
+    }
+    folded.truncate(folded.trim_end().len());
+    folded
+}
+
+impl Trigrams {
+    /// Trigrams of the folded text padded with a space on each side, so
+    /// word starts and ends count too.
+    fn of(text: &str) -> Self {
+        let folded = fold(text);
+        if folded.is_empty() {
+            return Trigrams(Vec::new());
+        }
+        let chars: Vec<char> = std::iter::once(' ')
+            .chain(folded.chars())
+            .chain(std::iter::once(' '))
+            .collect();
+        let mut grams: Vec<u64> = chars
+            .windows(3)
+            .map(|w| (w[0] as u64) << 42 | (w[1] as u64) << 21 | w[2] as u64)
+            .collect();
+        grams.sort_unstable();
+        grams.dedup();
+        Trigrams(grams)
+    }
+
+    /// Dice coefficient: twice the shared trigrams over the total.
+    fn dice(&self, other: &Trigrams) -> f64 {
+        let (a, b) = (&self.0, &other.0);
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+        let (mut i, mut j, mut shared) = (0, 0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    shared += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        2.0 * shared as f64 / (a.len() + b.len()) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+
+    /// Two tickets, one log file attached to each.
+    const SEED: &str = "
+        INSERT INTO backlog_items (id, project_id, section_id, type, title, raw_markdown) VALUES
+            ('BUG-1', 1, 1, 'BUG', 'Crash on startup', ''),
+            ('BUG-2', 1, 1, 'BUG', 'Slow export', '');
+        INSERT INTO attachments (id, project_id, item_id, filename, mime_type, size_bytes, sha256)
+            VALUES (1, 1, 'BUG-1', 'stack.log', 'text/plain', 0, ''),
+                   (2, 1, 'BUG-2', 'trace.log', 'text/plain', 0, '');
+    ";
+
+    async fn project(dir: &Path) -> (String, SqliteConnection) {
+        let db_path = create_db(
+            dir,
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            &[BOARD, SEED].concat(),
+        )
+        .await;
+        let conn = project_db::open_connection(&db_path).await.unwrap();
+        (db_path.display().to_string(), conn)
+    }
+
+    async fn search(db_path: &str, query: &str) -> SearchResults {
+        search_project(Path::new(db_path), query, None, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn search_without_fts_index_is_a_validation_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = create_db(
+            dir.path(),
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            "DROP TABLE backlog_items_fts;",
+        )
+        .await;
+
+        let err = search_project(&db_path, "login", None, None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(message) if message.contains("search index")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn attachment_text_finds_its_ticket_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, mut conn) = project(dir.path()).await;
+        index_attachment_text(&mut conn, 1, "panic in the startup thread")
+            .await
+            .unwrap();
+        index_attachment_text(&mut conn, 2, "épuisé timeout")
+            .await
+            .unwrap();
+
+        let results = search(&db_path, "timeout").await;
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].id, "BUG-2");
+        assert_eq!(
+            results.hits[0].attachment_filename.as_deref(),
+            Some("trace.log")
+        );
+        assert!(results.hits[0].snippet.contains("<mark>timeout</mark>"));
+        assert_eq!(results.hits[0].title_highlight, "Slow export");
+        assert_eq!(search(&db_path, "epuise").await.total, 1);
+
+        // The title and the attachment both match: one hit.
+        let results = search(&db_path, "startup").await;
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].id, "BUG-1");
+
+        unindex_attachment(&mut conn, 2).await.unwrap();
+        assert_eq!(search(&db_path, "timeout").await.total, 0);
+    }
+
+    #[tokio::test]
+    async fn older_schemas_skip_attachment_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, mut conn) = project(dir.path()).await;
+        sqlx::raw_sql("DROP TABLE attachments_fts; PRAGMA user_version = 11;")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        index_attachment_text(&mut conn, 1, "timeout")
+            .await
+            .unwrap();
+        unindex_attachment(&mut conn, 1).await.unwrap();
+        let results = search(&db_path, "crash").await;
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].attachment_filename, None);
+    }
+
+    #[tokio::test]
+    async fn highlights_escape_ticket_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db_path, mut conn) = project(dir.path()).await;
+        sqlx::raw_sql(
+            "INSERT INTO backlog_items (id, project_id, section_id, type, title, raw_markdown)
+                VALUES ('BUG-3', 1, 1, 'BUG', '<img src=x onerror=\"alert(1)\"> & crash', '');",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        index_attachment_text(&mut conn, 1, "<script>boot()</script> failed")
+            .await
+            .unwrap();
+
+        let results = search(&db_path, "onerror").await;
+        assert_eq!(
+            results.hits[0].title_highlight,
+            "&lt;img src=x <mark>onerror</mark>=&quot;alert(1)&quot;&gt; &amp; crash"
+        );
+        assert_eq!(
+            results.hits[0].title,
+            "<img src=x onerror=\"alert(1)\"> & crash"
+        );
+
+        // An attachment match: the title falls back to the plain title,
+        // escaped as well.
+        let results = search(&db_path, "failed").await;
+        assert_eq!(results.hits[0].id, "BUG-1");
+        assert_eq!(
+            results.hits[0].snippet,
+            "&lt;script&gt;boot()&lt;/script&gt; <mark>failed</mark>"
+        );
+        assert_eq!(results.hits[0].title_highlight, "Crash on startup");
+    }
+
+    #[test]
+    fn only_small_text_files_are_indexed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob");
+        std::fs::write(&path, b"first  line\n\tsecond \xff line\n").unwrap();
+
+        assert_eq!(
+            attachment_text("Notes.MD", &path, 10).as_deref(),
+            Some("first line second \u{fffd} line")
+        );
+        assert_eq!(attachment_text("shot.png", &path, 10), None);
+        assert_eq!(attachment_text("README", &path, 10), None);
+        assert_eq!(
+            attachment_text("big.log", &path, MAX_INDEXED_TEXT_BYTES + 1),
+            None
+        );
+        std::fs::write(&path, b" \n ").unwrap();
+        assert_eq!(attachment_text("empty.txt", &path, 3), None);
+        assert_eq!(
+            attachment_text("gone.txt", &dir.path().join("gone"), 3),
+            None
+        );
+    }
+}
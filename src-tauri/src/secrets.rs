@@ -0,0 +1,54 @@
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Service name of the OS keyring entries holding named secrets (API tokens).
+/// Project passphrases use their own entries (see `encryption.rs`).
+const KEYRING_SERVICE: &str = "ticketflow.secrets";
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// The secret stored under `name`, or `None` when there is none.
+pub(crate) fn get_secret(name: &str) -> Result<Option<String>, AppError> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Io(format!("keyring: {}", e))),
+    }
+}
+
+/// Save `secret` under `name`, or delete the entry with `None`.
+pub(crate) fn set_secret(name: &str, secret: Option<&str>) -> Result<(), AppError> {
+    let entry = entry(name)?;
+    let result = match secret {
+        Some(secret) => entry.set_password(secret),
+        None => match entry.delete_credential() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            other => other,
+        },
+    };
+    result.map_err(|e| AppError::Io(format!("keyring: {}", e)))
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, AppError> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| AppError::Io(format!("keyring unavailable: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_secrets_are_none() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        assert_eq!(get_secret("github_token").unwrap(), None);
+        set_secret("github_token", Some("ghp_token")).unwrap();
+        // Deleting a secret that was never stored is not an error.
+        set_secret("smtp_password", None).unwrap();
+    }
+}
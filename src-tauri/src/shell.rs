@@ -0,0 +1,1016 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::window;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Programs `run_shell_command_safe` may start, by bare name resolved
+/// through `PATH`. Checked here as well as in the shell plugin scope, since
+/// the plugin scope only covers the frontend plugin API.
+const ALLOWED_COMMANDS: [&str; 3] = ["git", "grep", "find"];
+
+/// Characters a shell would interpret. Arguments are passed to the program
+/// directly, never through a shell, but are still refused in case one ends
+/// up in a script or hook.
+const FORBIDDEN_ARG_CHARS: [char; 7] = [';', '|', '&', '`', '$', '\n', '\r'];
+
+/// `find` options that run another program or write files.
+const FIND_FORBIDDEN_OPTIONS: [&str; 9] = [
+    "-exec", "-execdir", "-ok", "-okdir", "-delete", "-fprint", "-fprint0", "-fprintf", "-fls",
+];
+
+/// Config and options put before every `git` call, whatever the
+/// repository's own config says: no pager, fsmonitor or hooks, no signature
+/// checks (`--format=%G?` would run the gpg program) and no look into
+/// submodules, whose filters are not disabled. Filter drivers are disabled
+/// by `git_filter_overrides`.
+const GIT_FORCED_GLOBALS: [&str; 17] = [
+    "-c",
+    "core.pager=cat",
+    "-c",
+    "core.fsmonitor=false",
+    "-c",
+    "core.hooksPath=/dev/null",
+    "-c",
+    "log.showSignature=false",
+    "-c",
+    "gpg.program=false",
+    "-c",
+    "gpg.ssh.program=false",
+    "-c",
+    "gpg.x509.program=false",
+    "-c",
+    "diff.ignoreSubmodules=all",
+    "--no-pager",
+];
+
+/// Commands of a filter driver (`filter.<driver>.<command>`).
+const GIT_FILTER_COMMANDS: [&str; 3] = ["clean", "smudge", "process"];
+
+/// Options `git` accepts before the subcommand, all naming the repository.
+/// Each takes a value, as `--opt=value` or as the next argument.
+const GIT_GLOBAL_OPTIONS: [&str; 3] = ["-C", "--git-dir", "--work-tree"];
+
+/// Options shared by the history and diff subcommands.
+const GIT_DIFF_FLAGS: &[&str] = &[
+    "-p",
+    "--patch",
+    "-s",
+    "--no-patch",
+    "--stat",
+    "--shortstat",
+    "--numstat",
+    "--name-only",
+    "--name-status",
+    "--no-color",
+    "--word-diff",
+    "-w",
+    "--ignore-all-space",
+    "-b",
+    "--ignore-space-change",
+    "-z",
+    "-R",
+];
+const GIT_DIFF_VALUES: &[&str] = &[
+    "-U",
+    "--unified",
+    "--color",
+    "--diff-filter",
+    "--stat-width",
+    "--abbrev",
+];
+const GIT_LOG_FLAGS: &[&str] = &[
+    "--oneline",
+    "--graph",
+    "--decorate",
+    "--no-decorate",
+    "--all",
+    "--reverse",
+    "--follow",
+    "--first-parent",
+    "--merges",
+    "--no-merges",
+    "-i",
+    "--regexp-ignore-case",
+    "--abbrev-commit",
+];
+const GIT_LOG_VALUES: &[&str] = &[
+    "-n",
+    "--max-count",
+    "--skip",
+    "--since",
+    "--until",
+    "--after",
+    "--before",
+    "--author",
+    "--committer",
+    "--grep",
+    "--format",
+    "--pretty",
+    "--date",
+    "-L",
+];
+
+/// A `git` subcommand `run_shell_command_safe` may run. Every option given
+/// must be listed; anything else, such as `git grep -O<cmd>` or
+/// `git log --output=<file>`, is refused.
+struct GitSubcommand {
+    name: &'static str,
+    /// Options without a value (`--stat`, `-i`). Single-letter ones may be
+    /// combined (`-in`).
+    flags: &'static [&'static [&'static str]],
+    /// Options with a value: `--opt=value`, `--opt value`, `-xvalue` or
+    /// `-x value`.
+    with_value: &'static [&'static [&'static str]],
+    /// Whether `-<n>` limits the number of commits.
+    count: bool,
+    /// Options put right after the subcommand so that no textconv or
+    /// external diff program runs.
+    forced: &'static [&'static str],
+}
+
+const GIT_SUBCOMMANDS: [GitSubcommand; 8] = [
+    GitSubcommand {
+        name: "status",
+        flags: &[&[
+            "-s",
+            "--short",
+            "-b",
+            "--branch",
+            "--porcelain",
+            "--long",
+            "-z",
+            "--ignored",
+            "--no-renames",
+        ]],
+        with_value: &[&["-u", "--untracked-files"]],
+        count: false,
+        forced: &[],
+    },
+    GitSubcommand {
+        name: "log",
+        flags: &[GIT_LOG_FLAGS, GIT_DIFF_FLAGS],
+        with_value: &[GIT_LOG_VALUES, GIT_DIFF_VALUES],
+        count: true,
+        forced: &["--no-textconv", "--no-ext-diff"],
+    },
+    GitSubcommand {
+        name: "show",
+        flags: &[GIT_LOG_FLAGS, GIT_DIFF_FLAGS],
+        with_value: &[GIT_LOG_VALUES, GIT_DIFF_VALUES],
+        count: true,
+        forced: &["--no-textconv", "--no-ext-diff"],
+    },
+    GitSubcommand {
+        name: "diff",
+        flags: &[GIT_DIFF_FLAGS, &["--cached", "--staged", "--no-index"]],
+        with_value: &[GIT_DIFF_VALUES],
+        count: false,
+        forced: &["--no-textconv", "--no-ext-diff"],
+    },
+    GitSubcommand {
+        name: "grep",
+        flags: &[&[
+            "-n",
+            "--line-number",
+            "-i",
+            "--ignore-case",
+            "-w",
+            "--word-regexp",
+            "-v",
+            "--invert-match",
+            "-l",
+            "--files-with-matches",
+            "-L",
+            "--files-without-match",
+            "-c",
+            "--count",
+            "-E",
+            "--extended-regexp",
+            "-F",
+            "--fixed-strings",
+            "-I",
+            "-h",
+            "-H",
+            "--heading",
+            "--break",
+            "--full-name",
+            "--cached",
+            "--untracked",
+            "-z",
+        ]],
+        with_value: &[&["-e", "-A", "-B", "-C", "--context", "--max-depth"]],
+        count: false,
+        forced: &["--no-textconv"],
+    },
+    GitSubcommand {
+        name: "blame",
+        flags: &[&[
+            "-w",
+            "-s",
+            "-e",
+            "-l",
+            "--porcelain",
+            "--line-porcelain",
+            "--show-email",
+        ]],
+        with_value: &[&["-L", "--date"]],
+        count: false,
+        forced: &["--no-textconv"],
+    },
+    GitSubcommand {
+        name: "ls-files",
+        flags: &[&[
+            "-c",
+            "--cached",
+            "-m",
+            "--modified",
+            "-d",
+            "--deleted",
+            "-o",
+            "--others",
+            "-s",
+            "--stage",
+            "--exclude-standard",
+            "--full-name",
+            "-z",
+        ]],
+        with_value: &[],
+        count: false,
+        forced: &[],
+    },
+    GitSubcommand {
+        name: "rev-parse",
+        flags: &[&[
+            "--show-toplevel",
+            "--abbrev-ref",
+            "--short",
+            "--verify",
+            "--is-inside-work-tree",
+            "--git-dir",
+            "-q",
+            "--quiet",
+        ]],
+        with_value: &[],
+        count: false,
+        forced: &[],
+    },
+];
+
+const MAX_TIMEOUT_MS: u64 = 30_000;
+const MAX_ARGS: usize = 64;
+const MAX_ARG_CHARS: usize = 4096;
+
+/// Output kept per stream; the rest is read and dropped so the program
+/// never blocks on a full pipe.
+const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Streaming processes allowed to run at once.
+const MAX_STREAMS: usize = 3;
+
+/// Longest line emitted by `stream_shell_output`; the rest is cut.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// Emitted with a `StreamEnded` once a streamed process has exited.
+const STREAM_ENDED_EVENT: &str = "shell:stream-ended";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct ShellResult {
+    pub stdout: String,
+    pub stderr: String,
+    /// `-1` when the program was ended by a signal.
+    pub exit_code: i32,
+}
+
+/// Processes started by `stream_shell_output`, by process id.
+#[derive(Default)]
+pub struct ShellState {
+    streams: Mutex<HashMap<String, Arc<StreamedProcess>>>,
+    next_id: AtomicU64,
+}
+
+struct StreamedProcess {
+    /// Also locked while checking for exit, so a kill never signals a pid
+    /// that was already reaped and reused.
+    child: Mutex<Child>,
+    killed: AtomicBool,
+}
+
+impl ShellState {
+    /// Add a started process under a new id, or `None` when `MAX_STREAMS`
+    /// are already running.
+    fn register(&self, process: Arc<StreamedProcess>) -> Option<String> {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.len() >= MAX_STREAMS {
+            return None;
+        }
+        let process_id = format!("stream-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        streams.insert(process_id.clone(), process);
+        Some(process_id)
+    }
+
+    /// Terminate the process `process_id` unless it already exited.
+    fn kill(&self, process_id: &str) -> Result<(), AppError> {
+        let process = self
+            .streams
+            .lock()
+            .unwrap()
+            .get(process_id)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::Validation(format!("no streaming process with id {}", process_id))
+            })?;
+        let mut child = process.child.lock().unwrap();
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        process.killed.store(true, Ordering::SeqCst);
+        terminate(&mut child)
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct StreamEnded {
+    process_id: String,
+    /// `None` when the process was ended by a signal.
+    exit_code: Option<i32>,
+    killed: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Run `git`, `grep` or `find` with `args` and capture its output. Other
+/// programs fail with `Unauthorized`; arguments containing shell
+/// metacharacters, `find` options that run other programs, and `git`
+/// subcommands or options outside `GIT_SUBCOMMANDS` fail with
+/// `Validation`. `git` never starts a pager, hook, filter or other program
+/// named in the repository's config. The program is killed after
+/// `timeout_ms` (at most 30 seconds). A non-zero exit code is returned, not
+/// an error.
+#[tauri::command]
+pub async fn run_shell_command_safe(
+    command: String,
+    args: Vec<String>,
+    timeout_ms: u64,
+) -> Result<ShellResult, AppError> {
+    if !ALLOWED_COMMANDS.contains(&command.as_str()) {
+        log::warn!("run_shell_command_safe: refused {:?}", command);
+        return Err(AppError::Unauthorized(format!(
+            "command {:?} is not allowed",
+            command
+        )));
+    }
+    if timeout_ms == 0 || timeout_ms > MAX_TIMEOUT_MS {
+        return Err(AppError::Validation(format!(
+            "timeout must be between 1 and {} ms",
+            MAX_TIMEOUT_MS
+        )));
+    }
+    let args = validate_args(&command, &args)?;
+
+    let mut child = Command::new(&command)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot start {}: {}", command, e)))?;
+    let stdout = child.stdout.take().map(read_capped);
+    let stderr = child.stderr.take().map(read_capped);
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(AppError::Timeout(format!(
+                "{} took more than {} ms",
+                command, timeout_ms
+            )));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+
+    Ok(ShellResult {
+        stdout: collect(stdout).await,
+        stderr: collect(stderr).await,
+        exit_code: status.code().unwrap_or(-1),
+    })
+}
+
+/// Start `command` in the background with the same checks as
+/// `run_shell_command_safe` and emit `event_name` with each line it writes
+/// to stdout, then `shell:stream-ended`. Returns the process id for
+/// `kill_streamed_process`. At most 3 streams run at once.
+#[tauri::command]
+pub async fn stream_shell_output(
+    command: String,
+    args: Vec<String>,
+    event_name: String,
+    app: AppHandle,
+    state: tauri::State<'_, ShellState>,
+) -> Result<String, AppError> {
+    if !ALLOWED_COMMANDS.contains(&command.as_str()) {
+        log::warn!("stream_shell_output: refused {:?}", command);
+        return Err(AppError::Unauthorized(format!(
+            "command {:?} is not allowed",
+            command
+        )));
+    }
+    let args = validate_args(&command, &args)?;
+    window::validate_event_name(&event_name)?;
+
+    // Checked again by `register`: the lock is not held while spawning.
+    if state.streams.lock().unwrap().len() >= MAX_STREAMS {
+        return Err(too_many_streams());
+    }
+    let mut child = Command::new(&command)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::Io(format!("cannot start {}: {}", command, e)))?;
+    let stdout = child.stdout.take();
+    let process = Arc::new(StreamedProcess {
+        child: Mutex::new(child),
+        killed: AtomicBool::new(false),
+    });
+    let Some(process_id) = state.register(process.clone()) else {
+        let mut child = process.child.lock().unwrap();
+        child.kill().ok();
+        child.wait().ok();
+        return Err(too_many_streams());
+    };
+    log::info!("stream_shell_output: {} started {}", process_id, command);
+
+    let id = process_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(stdout) = stdout {
+            read_lines(stdout, |line| {
+                app.emit(&event_name, line).ok();
+            });
+        }
+        let exit_code = wait_for_exit(&process);
+        app.state::<ShellState>()
+            .streams
+            .lock()
+            .unwrap()
+            .remove(&id);
+        let ended = StreamEnded {
+            process_id: id,
+            exit_code,
+            killed: process.killed.load(Ordering::SeqCst),
+        };
+        app.emit(STREAM_ENDED_EVENT, ended).ok();
+    });
+    Ok(process_id)
+}
+
+/// Stop a process started by `stream_shell_output`: `SIGTERM` on Unix,
+/// `TerminateProcess` on Windows.
+#[tauri::command]
+pub fn kill_streamed_process(
+    process_id: String,
+    state: tauri::State<'_, ShellState>,
+) -> Result<(), AppError> {
+    state.kill(&process_id)?;
+    log::info!("kill_streamed_process: {}", process_id);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Check `args` and return the arguments to start `command` with: those
+/// given, with `GIT_FORCED_GLOBALS` and the subcommand's forced options
+/// added for `git`.
+fn validate_args(command: &str, args: &[String]) -> Result<Vec<String>, AppError> {
+    if args.len() > MAX_ARGS {
+        return Err(AppError::Validation(format!(
+            "too many arguments (max {})",
+            MAX_ARGS
+        )));
+    }
+    for arg in args {
+        if arg.chars().count() > MAX_ARG_CHARS {
+            return Err(AppError::Validation(format!(
+                "argument is longer than {} characters",
+                MAX_ARG_CHARS
+            )));
+        }
+        if arg.contains(FORBIDDEN_ARG_CHARS) || arg.contains('\0') {
+            return Err(AppError::Validation(format!(
+                "argument {:?} contains a forbidden character",
+                arg
+            )));
+        }
+    }
+    match command {
+        "find" => {
+            // `-fprint=x` is not valid `find` syntax, but costs nothing to refuse.
+            let forbidden = args.iter().find(|arg| {
+                FIND_FORBIDDEN_OPTIONS.contains(&arg.split('=').next().unwrap_or_default())
+            });
+            match forbidden {
+                Some(option) => Err(AppError::Validation(format!(
+                    "option {} is not allowed for find",
+                    option
+                ))),
+                None => Ok(args.to_vec()),
+            }
+        }
+        "git" => git_args(args),
+        _ => Ok(args.to_vec()),
+    }
+}
+
+/// Arguments of a `git` call: `GIT_FORCED_GLOBALS`, the filter overrides,
+/// the repository options given, the subcommand and its forced options,
+/// then the rest. Fails unless the subcommand is in `GIT_SUBCOMMANDS` and
+/// each option is one it lists.
+fn git_args(args: &[String]) -> Result<Vec<String>, AppError> {
+    let refuse =
+        |option: &str| AppError::Validation(format!("option {} is not allowed for git", option));
+    let mut out: Vec<String> = Vec::new();
+
+    let mut rest = args.iter();
+    let subcommand = loop {
+        let Some(arg) = rest.next() else {
+            return Err(AppError::Validation("git needs a subcommand".into()));
+        };
+        if !arg.starts_with('-') {
+            break arg;
+        }
+        let (name, value) = split_option(arg);
+        if !GIT_GLOBAL_OPTIONS.contains(&name) {
+            return Err(refuse(name));
+        }
+        out.push(arg.clone());
+        if value.is_none() {
+            let value = rest
+                .next()
+                .ok_or_else(|| AppError::Validation(format!("{} needs a value", name)))?;
+            out.push(value.clone());
+        }
+    };
+    let spec = GIT_SUBCOMMANDS
+        .iter()
+        .find(|spec| spec.name == subcommand)
+        .ok_or_else(|| AppError::Validation(format!("git {} is not allowed", subcommand)))?;
+    let repository = out;
+    let mut out: Vec<String> = GIT_FORCED_GLOBALS
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    out.extend(git_filter_overrides(&repository)?);
+    out.extend(repository);
+    out.push(subcommand.clone());
+    out.extend(spec.forced.iter().map(|option| option.to_string()));
+
+    // Every argument starting with `-` is checked as an option, including
+    // one that follows an option taking a value: git may read it either
+    // way. Values are only skipped when attached (`--author=x`, `-n5`).
+    let is_flag = |option: &str| spec.flags.iter().any(|group| group.contains(&option));
+    let takes_value = |option: &str| spec.with_value.iter().any(|group| group.contains(&option));
+    let mut rest = rest.cloned();
+    while let Some(arg) = rest.next() {
+        if arg == "--" {
+            // Paths only from here on.
+            out.push(arg);
+            out.extend(rest);
+            break;
+        }
+        if arg.starts_with("--") {
+            let (name, value) = split_option(&arg);
+            let allowed = match value {
+                Some(_) => takes_value(name),
+                None => is_flag(name) || takes_value(name),
+            };
+            if !allowed {
+                return Err(refuse(name));
+            }
+        } else if arg.len() > 1
+            && arg.starts_with('-')
+            // `git log -3`
+            && !(spec.count && arg[1..].chars().all(|c| c.is_ascii_digit()))
+        {
+            // `-in` is `-i -n`; a letter taking a value ends the group, the
+            // rest of the argument being that value (`-n5`, `-U3`).
+            for letter in arg[1..].chars() {
+                let option = format!("-{}", letter);
+                if takes_value(&option) {
+                    break;
+                }
+                if !is_flag(&option) {
+                    return Err(refuse(&option));
+                }
+            }
+        }
+        out.push(arg);
+    }
+    Ok(out)
+}
+
+/// `-c` options emptying the commands of every filter driver the config of
+/// the repository named by `repository` (its `-C`, `--git-dir` and
+/// `--work-tree` options) defines, so that `.gitattributes` cannot have
+/// `status` or `diff` run one. Reading the config runs nothing. When it
+/// cannot be read, git cannot read it either and the call fails on its own.
+fn git_filter_overrides(repository: &[String]) -> Result<Vec<String>, AppError> {
+    let output = Command::new("git")
+        .args(GIT_FORCED_GLOBALS)
+        .args(repository)
+        .args(["config", "--null", "--get-regexp", r"^filter\."])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    let Ok(output) = output else {
+        return Ok(Vec::new());
+    };
+
+    // `--null` prints `key\nvalue\0`; the driver is the middle of the key.
+    let drivers: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter_map(|entry| {
+            let key = entry.split('\n').next()?;
+            let (driver, _) = key.strip_prefix("filter.")?.rsplit_once('.')?;
+            Some(driver.to_string())
+        })
+        .collect();
+    let mut overrides = Vec::new();
+    for driver in drivers {
+        // `-c` splits at the first `=`, so such a driver cannot be emptied.
+        if driver.contains('=') {
+            return Err(AppError::Validation(format!(
+                "the repository defines a filter driver git cannot disable: {:?}",
+                driver
+            )));
+        }
+        for command in GIT_FILTER_COMMANDS {
+            overrides.push("-c".to_string());
+            overrides.push(format!("filter.{}.{}=", driver, command));
+        }
+    }
+    Ok(overrides)
+}
+
+/// `--name=value` as (`--name`, `Some("value")`); anything else as is.
+fn split_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) if arg.starts_with("--") => (name, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+/// Read `stream` to the end on a blocking thread, keeping the first
+/// `MAX_OUTPUT_BYTES`.
+fn read_capped<R: Read + Send + 'static>(
+    mut stream: R,
+) -> tauri::async_runtime::JoinHandle<Vec<u8>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut kept = Vec::new();
+        let mut buffer = [0u8; 8192];
+        while let Ok(read) = stream.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let room = MAX_OUTPUT_BYTES.saturating_sub(kept.len());
+            kept.extend_from_slice(&buffer[..read.min(room)]);
+        }
+        kept
+    })
+}
+
+async fn collect(reader: Option<tauri::async_runtime::JoinHandle<Vec<u8>>>) -> String {
+    match reader {
+        Some(reader) => String::from_utf8_lossy(&reader.await.unwrap_or_default()).into_owned(),
+        None => String::new(),
+    }
+}
+
+/// Pass each line of `stdout`, without its line ending and cut to
+/// `MAX_LINE_BYTES`, to `on_line` until the process closes it.
+fn read_lines(stdout: impl Read, mut on_line: impl FnMut(String)) {
+    let mut reader = BufReader::new(stdout);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        while line.last().is_some_and(|b| *b == b'\n' || *b == b'\r') {
+            line.pop();
+        }
+        line.truncate(MAX_LINE_BYTES);
+        on_line(String::from_utf8_lossy(&line).into_owned());
+    }
+}
+
+fn too_many_streams() -> AppError {
+    AppError::InUse(format!("{} commands are already streaming", MAX_STREAMS))
+}
+
+/// Wait for a streamed process without holding its lock, so it can still
+/// be killed. Returns its exit code.
+fn wait_for_exit(process: &StreamedProcess) -> Option<i32> {
+    loop {
+        match process.child.lock().unwrap().try_wait() {
+            Ok(Some(status)) => return status.code(),
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!("stream_shell_output: {}", e);
+                return None;
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(unix)]
+fn terminate(child: &mut Child) -> Result<(), AppError> {
+    let pid = libc::pid_t::try_from(child.id())
+        .map_err(|_| AppError::Io(format!("invalid pid {}", child.id())))?;
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) -> Result<(), AppError> {
+    // TerminateProcess
+    Ok(child.kill()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn refused(command: &str, list: &[&str]) -> bool {
+        matches!(
+            validate_args(command, &args(list)),
+            Err(AppError::Validation(_))
+        )
+    }
+
+    #[test]
+    fn shell_metacharacters_are_refused() {
+        for arg in ["a;b", "a|b", "a&b", "`id`", "$(id)", "a\nb", "a\0b"] {
+            assert!(refused("grep", &["-r", arg, "."]), "{:?}", arg);
+        }
+    }
+
+    #[test]
+    fn find_options_running_programs_are_refused() {
+        for option in FIND_FORBIDDEN_OPTIONS {
+            assert!(refused("find", &[".", option, "x"]), "{}", option);
+        }
+        assert_eq!(
+            validate_args("find", &args(&[".", "-name", "*.md"])).unwrap(),
+            args(&[".", "-name", "*.md"])
+        );
+    }
+
+    #[test]
+    fn git_grep_pager_is_refused() {
+        assert!(refused("git", &["grep", "--open-files-in-pager=sh", "x"]));
+        assert!(refused("git", &["grep", "--open-files-in-pager", "x"]));
+        assert!(refused("git", &["grep", "-Osh", "x"]));
+        assert!(refused("git", &["grep", "-O", "x"]));
+        assert!(refused("git", &["grep", "-nOsh", "x"]));
+    }
+
+    #[test]
+    fn git_output_files_are_refused() {
+        assert!(refused("git", &["log", "--output=/tmp/x"]));
+        assert!(refused("git", &["diff", "--output=/tmp/x"]));
+        assert!(refused("git", &["show", "--output", "/tmp/x"]));
+    }
+
+    #[test]
+    fn options_behind_value_options_are_still_checked() {
+        assert!(refused("git", &["log", "--color", "--output=/tmp/x"]));
+        assert!(refused("git", &["log", "-n", "--output=/tmp/x"]));
+        assert!(refused("git", &["grep", "-e", "--open-files-in-pager=sh"]));
+    }
+
+    #[test]
+    fn git_config_and_program_options_are_refused() {
+        assert!(refused("git", &["-c", "core.pager=sh", "log"]));
+        assert!(refused("git", &["-ccore.pager=sh", "log"]));
+        assert!(refused("git", &["--config-env=core.pager=X", "log"]));
+        assert!(refused("git", &["--exec-path=/tmp", "log"]));
+        assert!(refused("git", &["log", "-c", "core.pager=sh"]));
+        assert!(refused("git", &["diff", "--ext-diff"]));
+        assert!(refused("git", &["log", "--textconv"]));
+    }
+
+    #[test]
+    fn only_listed_git_subcommands_run() {
+        for list in [
+            &["fetch", "origin"][..],
+            &["ls-remote", "--upload-pack=sh", "x"],
+            &["clone", "--upload-pack=sh", "x"],
+            &["config", "core.pager", "sh"],
+            &["difftool"],
+            &["-C", "/repo"],
+        ] {
+            assert!(refused("git", list), "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn repository_config_cannot_choose_a_pager_or_fsmonitor() {
+        let out = validate_args("git", &args(&["-C", "/repo", "log", "-3"])).unwrap();
+        let forced = GIT_FORCED_GLOBALS.len();
+        assert_eq!(out[..forced], GIT_FORCED_GLOBALS.map(String::from));
+        assert_eq!(
+            out[forced..],
+            args(&["-C", "/repo", "log", "--no-textconv", "--no-ext-diff", "-3"])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn repository_filters_do_not_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().to_string_lossy().into_owned();
+        let git = |list: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(list)
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return; // No git on this machine.
+        }
+        // A tracked file changed since it was staged: `status` re-hashes it
+        // through the clean filter.
+        std::fs::write(dir.path().join(".gitattributes"), "* filter=evil\n").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        assert!(git(&["add", "."]));
+        std::fs::write(dir.path().join("a.txt"), "b\n").unwrap();
+        let pwned = dir.path().join("pwned");
+        let touch = format!("touch {}", pwned.display());
+        for (key, value) in [
+            ("filter.evil.clean", touch.as_str()),
+            ("filter.evil.smudge", touch.as_str()),
+            ("filter.my.evil.process", touch.as_str()),
+            ("core.fsmonitor", touch.as_str()),
+        ] {
+            assert!(git(&["config", key, value]), "{}", key);
+        }
+
+        let out = validate_args("git", &args(&["-C", &repo, "status", "--porcelain"])).unwrap();
+        for option in [
+            "filter.evil.clean=",
+            "filter.evil.process=",
+            "filter.my.evil.smudge=",
+        ] {
+            assert!(
+                out.iter().any(|arg| arg == option),
+                "{} in {:?}",
+                option,
+                out
+            );
+        }
+        let status = Command::new("git")
+            .args(&out)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(!pwned.exists(), "a repository command ran");
+    }
+
+    #[test]
+    fn listed_git_options_pass() {
+        for list in [
+            &["log", "--oneline", "-n5", "--author=Ana", "--", "src"][..],
+            &["show", "--stat", "HEAD~1"],
+            &["diff", "--cached", "-U3", "--name-only"],
+            &["grep", "-in", "-e", "todo", "--", "-O"],
+            &["status", "--porcelain", "-uno"],
+            &["blame", "-L", "1,20", "README.md"],
+            &["--git-dir=/repo/.git", "rev-parse", "--abbrev-ref", "HEAD"],
+        ] {
+            assert!(validate_args("git", &args(list)).is_ok(), "{:?}", list);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn register_refuses_past_max_streams() {
+        let state = ShellState::default();
+        let start = || {
+            Arc::new(StreamedProcess {
+                child: Mutex::new(Command::new("true").spawn().unwrap()),
+                killed: AtomicBool::new(false),
+            })
+        };
+        let ids: Vec<String> = (0..MAX_STREAMS)
+            .map(|_| state.register(start()).unwrap())
+            .collect();
+        assert_eq!(ids, ["stream-1", "stream-2", "stream-3"]);
+
+        let extra = start();
+        assert_eq!(state.register(extra.clone()), None);
+        extra.child.lock().unwrap().wait().unwrap();
+
+        state.streams.lock().unwrap().remove("stream-2");
+        assert_eq!(state.register(start()).as_deref(), Some("stream-4"));
+        for process in state.streams.lock().unwrap().values() {
+            process.child.lock().unwrap().wait().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    fn stream(command: &mut Command) -> (Arc<StreamedProcess>, Option<std::process::ChildStdout>) {
+        let mut child = command.stdout(Stdio::piped()).spawn().unwrap();
+        let stdout = child.stdout.take();
+        let process = Arc::new(StreamedProcess {
+            child: Mutex::new(child),
+            killed: AtomicBool::new(false),
+        });
+        (process, stdout)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn completed_stream_reports_every_line_and_the_exit_code() {
+        let (process, stdout) = stream(Command::new("printf").arg("one\\ntwo\\r\\n\\nlast"));
+        let mut lines = Vec::new();
+        read_lines(stdout.unwrap(), |line| lines.push(line));
+        assert_eq!(lines, ["one", "two", "", "last"]);
+        assert_eq!(wait_for_exit(&process), Some(0));
+        assert!(!process.killed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn long_lines_are_cut() {
+        let input = format!("{}\nshort\n", "x".repeat(MAX_LINE_BYTES + 10));
+        let mut lines = Vec::new();
+        read_lines(input.as_bytes(), |line| lines.push(line));
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), MAX_LINE_BYTES);
+        assert_eq!(lines[1], "short");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn killed_stream_ends_early() {
+        let state = ShellState::default();
+        let (process, stdout) = stream(Command::new("sleep").arg("30"));
+        let id = state.register(process.clone()).unwrap();
+
+        let started = std::time::Instant::now();
+        state.kill(&id).unwrap();
+        let mut lines = Vec::new();
+        read_lines(stdout.unwrap(), |line| lines.push(line));
+        // Ended by SIGTERM: no exit code.
+        assert_eq!(wait_for_exit(&process), None);
+        assert!(process.killed.load(Ordering::SeqCst));
+        assert!(lines.is_empty());
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+        // Killing an exited process is a no-op; unknown ids are refused.
+        state.kill(&id).unwrap();
+        assert!(matches!(
+            state.kill("stream-99"),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
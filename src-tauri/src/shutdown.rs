@@ -0,0 +1,141 @@
+use std::ffi::OsString;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::project_lock;
+use crate::telemetry::{self, TelemetryState};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Upper bound on the cleanup sequence so quit/restart can never hang.
+const CLEANUP_TIMEOUT_SECS: u64 = 5;
+
+// ---------------------------------------------------------------------------
+// Orderly shutdown
+// ---------------------------------------------------------------------------
+
+/// Flush telemetry, checkpoint and close every SQLite pool the backend knows
+/// about, then release the project locks. Bounded by `CLEANUP_TIMEOUT_SECS`; on timeout we log and carry on
+/// so the caller can still exit.
+pub async fn orderly_cleanup(app: &tauri::AppHandle) {
+    let cleanup = async {
+        if let Some(state) = app.try_state::<TelemetryState>() {
+            telemetry::shutdown(&state).await;
+        }
+        close_project_pools(app).await;
+        project_lock::release_all(app);
+    };
+
+    if tokio::time::timeout(Duration::from_secs(CLEANUP_TIMEOUT_SECS), cleanup)
+        .await
+        .is_err()
+    {
+        log::warn!(
+            "orderly_cleanup: timed out after {}s; exiting anyway",
+            CLEANUP_TIMEOUT_SECS
+        );
+    }
+}
+
+/// Checkpoint and close the project databases opened by tauri-plugin-sql
+/// from the frontend (`Database.load()`).
+async fn close_project_pools(app: &tauri::AppHandle) {
+    let Some(instances) = app.try_state::<tauri_plugin_sql::DbInstances>() else {
+        return;
+    };
+
+    let mut pools = instances.0.write().await;
+    for (url, pool) in pools.drain() {
+        let tauri_plugin_sql::DbPool::Sqlite(pool) = pool;
+        if let Err(e) = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+            .execute(&pool)
+            .await
+        {
+            log::warn!("close_project_pools: checkpoint failed for {}: {}", url, e);
+        }
+        pool.close().await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Restart
+// ---------------------------------------------------------------------------
+
+/// Relaunch the current binary with its original CLI flags plus `extra_args`,
+/// then exit this process. Call `orderly_cleanup` first.
+pub fn restart_with_args(app: &tauri::AppHandle, extra_args: &[String]) -> ! {
+    let mut env = app.env();
+    env.args_os = merge_restart_args(&env.args_os, extra_args);
+
+    app.cleanup_before_exit();
+    tauri::process::restart(&env)
+}
+
+/// Append `extra_args` to the current argv. A `--flag value` pair in
+/// `extra_args` replaces any existing occurrence of the same flag so repeated
+/// restarts don't accumulate duplicates.
+fn merge_restart_args(current: &[OsString], extra_args: &[String]) -> Vec<OsString> {
+    let flag = extra_args.first().filter(|arg| arg.starts_with("--"));
+    let takes_value = extra_args.len() > 1;
+
+    let mut merged = Vec::with_capacity(current.len() + extra_args.len());
+    let mut iter = current.iter();
+    while let Some(arg) = iter.next() {
+        if flag.is_some_and(|flag| arg.to_str() == Some(flag.as_str())) {
+            if takes_value {
+                iter.next();
+            }
+            continue;
+        }
+        merged.push(arg.clone());
+    }
+
+    merged.extend(extra_args.iter().map(OsString::from));
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    fn extra(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn flag_with_value_replaces_the_previous_one() {
+        let current = args(&["ticketflow", "--portable", "--open", "a.db", "--verbose"]);
+        let merged = merge_restart_args(&current, &extra(&["--open", "b.db"]));
+        assert_eq!(
+            merged,
+            args(&["ticketflow", "--portable", "--verbose", "--open", "b.db"])
+        );
+        // Restarting again does not pile up copies.
+        let again = merge_restart_args(&merged, &extra(&["--open", "c.db"]));
+        assert_eq!(
+            again,
+            args(&["ticketflow", "--portable", "--verbose", "--open", "c.db"])
+        );
+    }
+
+    #[test]
+    fn bare_flags_and_plain_args_are_appended() {
+        let current = args(&["ticketflow", "--safe-mode"]);
+        assert_eq!(
+            merge_restart_args(&current, &extra(&["--safe-mode"])),
+            args(&["ticketflow", "--safe-mode"])
+        );
+        assert_eq!(
+            merge_restart_args(&current, &extra(&["notes.db"])),
+            args(&["ticketflow", "--safe-mode", "notes.db"])
+        );
+        assert_eq!(merge_restart_args(&current, &[]), current);
+    }
+}
@@ -0,0 +1,670 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS, NETWORK_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::kv;
+use crate::secrets;
+use crate::telemetry::TelemetryState;
+use crate::webhooks;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// queue of Slack messages waiting for a retry.
+pub const SLACK_QUEUE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS slack_queue (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        message_json TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        retry_count INTEGER NOT NULL DEFAULT 0
+    );
+";
+
+/// `kv_store` key holding the rules as JSON.
+pub(crate) const RULES_KV: &str = "slack_rules";
+
+/// Secret holding the incoming-webhook URL, which embeds its token.
+pub(crate) const WEBHOOK_URL_SECRET: &str = "slack_webhook_url";
+
+const MAX_QUEUE_SIZE: i64 = 100;
+const MAX_RETRY_COUNT: i64 = 5;
+const FLUSH_BATCH_SIZE: i64 = 20;
+/// How often queued messages are retried.
+const FLUSH_INTERVAL_SECS: u64 = 5 * 60;
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+/// Slack truncates section text beyond 3000 characters.
+const MAX_TEMPLATE_CHARS: usize = 2_000;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Whether and how an event type is posted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlackRule {
+    /// Event type reported to `slack_notify`, e.g. `ticket.blocked`.
+    pub event_type: String,
+    pub enabled: bool,
+    /// Message text (Slack mrkdwn) with `{id}`, `{title}`, `{status}`,
+    /// `{assignee}` and `{project}` placeholders.
+    pub template: String,
+}
+
+/// Return value of `slack_get_config`.
+#[derive(Debug, Serialize)]
+pub struct SlackConfig {
+    /// Whether a webhook URL is stored. The URL itself is not returned.
+    pub configured: bool,
+    pub rules: Vec<SlackRule>,
+}
+
+/// A ticket change reported by the frontend to `slack_notify`.
+#[derive(Debug, Deserialize)]
+pub struct SlackTicketEvent {
+    pub event_type: String,
+    /// Display name of the project.
+    pub project_name: String,
+    /// Project path, for the `ticketflow://` link.
+    pub project_path: String,
+    pub ticket_id: String,
+    pub title: String,
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+}
+
+/// Failed POST to the webhook.
+enum SendError {
+    /// Network error, 429 or 5xx: worth retrying.
+    Retry(String),
+    /// Slack refused the message or the webhook (e.g. revoked); retrying
+    /// would fail the same way.
+    Rejected(String),
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Spawn the task that retries queued messages every 5 minutes. Called once
+/// from `lib.rs` during app setup, after `TelemetryState` is managed.
+pub fn init_slack(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let state = app.state::<TelemetryState>();
+            let queued: i64 = match sqlx::query_scalar("SELECT COUNT(*) FROM slack_queue")
+                .fetch_one(&state.pool())
+                .await
+            {
+                Ok(queued) => queued,
+                Err(e) => {
+                    log::error!("slack: cannot read the queue: {}", e);
+                    continue;
+                }
+            };
+            if queued == 0 {
+                continue;
+            }
+            match secrets::get_secret(WEBHOOK_URL_SECRET) {
+                Ok(Some(url)) => {
+                    let client = state.client.read().await.clone();
+                    flush_queue(&state.pool(), &client, &url).await;
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("slack: cannot read the webhook URL: {}", e),
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Save the Slack incoming-webhook URL and the rules. `webhook_url` is kept
+/// as is when `None`, and removed when empty.
+#[tauri::command]
+pub async fn slack_configure(
+    webhook_url: Option<String>,
+    rules: Vec<SlackRule>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<SlackConfig, AppError> {
+    let webhook_url = webhook_url.map(|url| url.trim().to_string());
+    let result = async {
+        if let Some(url) = webhook_url.as_deref().filter(|url| !url.is_empty()) {
+            validate_webhook_url(url)?;
+        }
+        validate_rules(&rules)?;
+
+        let json = serde_json::to_string(&rules)
+            .map_err(|e| AppError::Validation(format!("invalid rules: {}", e)))?;
+        with_timeout(
+            async {
+                kv::set(&state.pool(), RULES_KV, &json).await?;
+                Ok(())
+            },
+            DB_TIMEOUT_MS,
+        )
+        .await?;
+        if let Some(url) = webhook_url.as_deref() {
+            secrets::set_secret(WEBHOOK_URL_SECRET, Some(url).filter(|url| !url.is_empty()))?;
+        }
+        Ok(SlackConfig {
+            configured: secrets::get_secret(WEBHOOK_URL_SECRET)?.is_some(),
+            rules: rules.clone(),
+        })
+    }
+    .await;
+
+    let url_change = match webhook_url.as_deref() {
+        None => "url kept",
+        Some("") => "url removed",
+        Some(_) => "url set",
+    };
+    audit::audit_log_command(
+        &state.pool(),
+        "slack_configure",
+        &format!("{}, {} rules", url_change, rules.len()),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Whether a webhook URL is stored, and the rules.
+#[tauri::command]
+pub async fn slack_get_config(
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<SlackConfig, AppError> {
+    let rules = with_timeout(load_rules(&state.pool()), DB_TIMEOUT_MS).await?;
+    Ok(SlackConfig {
+        configured: secrets::get_secret(WEBHOOK_URL_SECRET)?.is_some(),
+        rules,
+    })
+}
+
+/// Post `event` when an enabled rule matches its type. A message that
+/// cannot be sent now is queued and retried. Returns whether a message was
+/// sent or queued.
+#[tauri::command]
+pub async fn slack_notify(
+    event: SlackTicketEvent,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<bool, AppError> {
+    webhooks::validate_event_type(&event.event_type)?;
+    let rules = with_timeout(load_rules(&state.pool()), DB_TIMEOUT_MS).await?;
+    let Some(rule) = rules
+        .iter()
+        .find(|rule| rule.enabled && rule.event_type == event.event_type)
+    else {
+        return Ok(false);
+    };
+    let Some(url) = secrets::get_secret(WEBHOOK_URL_SECRET)? else {
+        return Ok(false);
+    };
+
+    let message = build_message(&rule.template, &event);
+    let client = state.client.read().await.clone();
+    match post_message(&client, &url, &message).await {
+        Ok(()) => {
+            // Slack is reachable again: send what earlier failures queued.
+            flush_queue(&state.pool(), &client, &url).await;
+            Ok(true)
+        }
+        Err(SendError::Retry(e)) => {
+            log::warn!("slack_notify: {}; queuing the message", e);
+            with_timeout(queue_message(&state.pool(), &message), DB_TIMEOUT_MS).await?;
+            Ok(true)
+        }
+        Err(SendError::Rejected(e)) => Err(AppError::Network(e)),
+    }
+}
+
+/// Send a sample message right away so the user can check the setup.
+/// Failures are returned, not queued.
+#[tauri::command]
+pub async fn slack_test(state: tauri::State<'_, TelemetryState>) -> Result<(), AppError> {
+    let url = secrets::get_secret(WEBHOOK_URL_SECRET)?
+        .ok_or_else(|| AppError::Validation("no Slack webhook URL is configured".into()))?;
+    let sample = SlackTicketEvent {
+        event_type: "ticket.test".to_string(),
+        project_name: "Ticketflow".to_string(),
+        project_path: String::new(),
+        ticket_id: "TEST-001".to_string(),
+        title: "Slack notifications are set up".to_string(),
+        status: Some("Done".to_string()),
+        assignee: None,
+    };
+    let message = build_message("Test message from Ticketflow: *{id}* {title}", &sample);
+    let client = state.client.read().await.clone();
+    with_timeout(
+        async {
+            post_message(&client, &url, &message)
+                .await
+                .map_err(|e| match e {
+                    SendError::Retry(e) | SendError::Rejected(e) => AppError::Network(e),
+                })
+        },
+        NETWORK_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Rules stored in `kv_store`, none when unset or unreadable.
+pub(crate) async fn load_rules(pool: &SqlitePool) -> Result<Vec<SlackRule>, AppError> {
+    Ok(kv::get(pool, RULES_KV)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// One rule per valid event type, each with a template.
+pub(crate) fn validate_rules(rules: &[SlackRule]) -> Result<(), AppError> {
+    for (position, rule) in rules.iter().enumerate() {
+        webhooks::validate_event_type(&rule.event_type)?;
+        if rules[..position]
+            .iter()
+            .any(|other| other.event_type == rule.event_type)
+        {
+            return Err(AppError::Validation(format!(
+                "more than one rule for {}",
+                rule.event_type
+            )));
+        }
+        if rule.template.trim().is_empty() || rule.template.chars().count() > MAX_TEMPLATE_CHARS {
+            return Err(AppError::Validation(format!(
+                "the template for {} must have 1 to {} characters",
+                rule.event_type, MAX_TEMPLATE_CHARS
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_webhook_url(url: &str) -> Result<(), AppError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| AppError::Validation(format!("invalid webhook URL: {}", e)))?;
+    if parsed.scheme() != "https" || parsed.host_str().map_or(true, str::is_empty) {
+        return Err(AppError::Validation(
+            "the Slack webhook URL must be an https:// URL".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Block Kit message for `event`: the rendered template, then the ticket
+/// (linked to `ticketflow://open`), its status and its assignee.
+fn build_message(template: &str, event: &SlackTicketEvent) -> serde_json::Value {
+    let status = event.status.as_deref().unwrap_or("none");
+    let assignee = event.assignee.as_deref().unwrap_or("unassigned");
+    let text = template
+        .replace("{id}", &escape(&event.ticket_id))
+        .replace("{title}", &escape(&event.title))
+        .replace("{status}", &escape(status))
+        .replace("{assignee}", &escape(assignee))
+        .replace("{project}", &escape(&event.project_name));
+
+    let mut link = reqwest::Url::parse("ticketflow://open").expect("valid URL");
+    link.query_pairs_mut()
+        .append_pair("project", &event.project_path)
+        .append_pair("item", &event.ticket_id);
+    let ticket = format!(
+        "<{}|{}> {}",
+        link,
+        escape(&event.ticket_id),
+        escape(&event.title)
+    );
+
+    json!({
+        "text": text,
+        "blocks": [
+            { "type": "section", "text": { "type": "mrkdwn", "text": text } },
+            {
+                "type": "section",
+                "fields": [
+                    { "type": "mrkdwn", "text": format!("*Ticket*\n{}", ticket) },
+                    { "type": "mrkdwn", "text": format!("*Status*\n{}", escape(status)) },
+                    { "type": "mrkdwn", "text": format!("*Assignee*\n{}", escape(assignee)) },
+                ],
+            },
+            {
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": escape(&event.project_name) }],
+            },
+        ],
+    })
+}
+
+/// Escape the characters Slack mrkdwn treats as markup in user text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+async fn post_message(
+    client: &reqwest::Client,
+    url: &str,
+    message: &serde_json::Value,
+) -> Result<(), SendError> {
+    let response = client
+        .post(url)
+        .json(message)
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| SendError::Retry(format!("Slack request failed: {}", e)))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    // Slack explains refusals in the body, e.g. `invalid_payload` or
+    // `no_service` for a revoked webhook.
+    let reason = response.text().await.unwrap_or_default();
+    let message = format!("Slack returned HTTP {}: {}", status, reason.trim());
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        Err(SendError::Retry(message))
+    } else {
+        Err(SendError::Rejected(message))
+    }
+}
+
+/// Add `message` to `slack_queue`, dropping the oldest entries beyond
+/// MAX_QUEUE_SIZE.
+async fn queue_message(pool: &SqlitePool, message: &serde_json::Value) -> Result<(), AppError> {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    sqlx::query("INSERT INTO slack_queue (message_json, created_at) VALUES (?, ?)")
+        .bind(message.to_string())
+        .bind(created_at)
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "DELETE FROM slack_queue WHERE id NOT IN (
+           SELECT id FROM slack_queue ORDER BY id DESC LIMIT ?
+         )",
+    )
+    .bind(MAX_QUEUE_SIZE)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Send queued messages oldest first, stopping at the first one Slack does
+/// not take. Like the telemetry queue, a message is dropped after
+/// MAX_RETRY_COUNT failed attempts. Returns the number of messages sent.
+async fn flush_queue(pool: &SqlitePool, client: &reqwest::Client, url: &str) -> usize {
+    let rows: Vec<(i64, String)> = match sqlx::query_as(
+        "SELECT id, message_json FROM slack_queue WHERE retry_count < ? ORDER BY id LIMIT ?",
+    )
+    .bind(MAX_RETRY_COUNT)
+    .bind(FLUSH_BATCH_SIZE)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("slack flush_queue: fetch failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut sent = 0;
+    for (id, message_json) in rows {
+        let message = serde_json::from_str(&message_json).unwrap_or(serde_json::Value::Null);
+        let outcome = post_message(client, url, &message).await;
+        let done = match &outcome {
+            Ok(()) => {
+                sent += 1;
+                true
+            }
+            Err(SendError::Rejected(e)) => {
+                log::warn!("slack flush_queue: dropping message {}: {}", id, e);
+                true
+            }
+            Err(SendError::Retry(_)) => false,
+        };
+        let updated = if done {
+            sqlx::query("DELETE FROM slack_queue WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await
+        } else {
+            sqlx::query("UPDATE slack_queue SET retry_count = retry_count + 1 WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await
+        };
+        if let Err(e) = updated {
+            log::error!("slack flush_queue: updating message {} failed: {}", id, e);
+            break;
+        }
+        if !done {
+            break;
+        }
+    }
+
+    if let Err(e) = sqlx::query("DELETE FROM slack_queue WHERE retry_count >= ?")
+        .bind(MAX_RETRY_COUNT)
+        .execute(pool)
+        .await
+    {
+        log::error!("slack flush_queue: purging expired messages failed: {}", e);
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::tests::serve;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(SLACK_QUEUE_SCHEMA)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn rule(event_type: &str, template: &str) -> SlackRule {
+        SlackRule {
+            event_type: event_type.into(),
+            enabled: true,
+            template: template.into(),
+        }
+    }
+
+    fn event() -> SlackTicketEvent {
+        SlackTicketEvent {
+            event_type: "ticket.blocked".into(),
+            project_name: "R&D".into(),
+            project_path: "/home/ana/my project".into(),
+            ticket_id: "BUG-001".into(),
+            title: "Crash <on> save".into(),
+            status: Some("Blocked".into()),
+            assignee: None,
+        }
+    }
+
+    fn text_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    async fn queued(pool: &SqlitePool) -> Vec<(String, i64)> {
+        sqlx::query_as("SELECT message_json, retry_count FROM slack_queue ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn rules_are_validated() {
+        assert!(
+            validate_rules(&[rule("ticket.blocked", "{id}"), rule("ticket.done", "x")]).is_ok()
+        );
+        let long = "x".repeat(MAX_TEMPLATE_CHARS + 1);
+        for rules in [
+            vec![rule("Ticket", "{id}")],
+            vec![rule("ticket.done", "a"), rule("ticket.done", "b")],
+            vec![rule("ticket.done", "  ")],
+            vec![rule("ticket.done", &long)],
+        ] {
+            assert!(validate_rules(&rules).is_err(), "{:?}", rules);
+        }
+    }
+
+    #[test]
+    fn webhook_urls_must_be_https() {
+        assert!(validate_webhook_url("https://hooks.slack.com/services/T0/B0/x").is_ok());
+        assert!(validate_webhook_url("http://hooks.slack.com/services/T0/B0/x").is_err());
+        assert!(validate_webhook_url("hooks.slack.com").is_err());
+    }
+
+    #[test]
+    fn messages_render_the_template_escaped() {
+        let message = build_message(
+            "*{id}* {title} is {status} ({assignee}, {project})",
+            &event(),
+        );
+        let text = "*BUG-001* Crash &lt;on&gt; save is Blocked (unassigned, R&amp;D)";
+        assert_eq!(message["text"], text);
+        assert_eq!(message["blocks"][0]["text"]["text"], text);
+        assert_eq!(
+            message["blocks"][1]["fields"][0]["text"],
+            "*Ticket*\n<ticketflow://open?project=%2Fhome%2Fana%2Fmy+project&item=BUG-001|BUG-001> \
+             Crash &lt;on&gt; save"
+        );
+        assert_eq!(
+            message["blocks"][1]["fields"][2]["text"],
+            "*Assignee*\nunassigned"
+        );
+        assert_eq!(message["blocks"][2]["elements"][0]["text"], "R&amp;D");
+    }
+
+    #[tokio::test]
+    async fn rules_load_from_kv_store() {
+        let pool = pool().await;
+        assert!(load_rules(&pool).await.unwrap().is_empty());
+
+        let rules = vec![rule("ticket.blocked", "{id}")];
+        kv::set(&pool, RULES_KV, &serde_json::to_string(&rules).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(load_rules(&pool).await.unwrap(), rules);
+
+        kv::set(&pool, RULES_KV, "not json").await.unwrap();
+        assert!(load_rules(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failures_are_retried_or_rejected_by_status() {
+        let (server, requests) = serve(vec![
+            text_response("200 OK", "ok"),
+            text_response("429 Too Many Requests", ""),
+            text_response("503 Service Unavailable", ""),
+            text_response("404 Not Found", "no_service"),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        let message = json!({ "text": "hi" });
+
+        assert!(post_message(&client, &server, &message).await.is_ok());
+        assert!(requests.lock().unwrap()[0].ends_with(r#"{"text":"hi"}"#));
+        for _ in 0..2 {
+            let outcome = post_message(&client, &server, &message).await;
+            assert!(matches!(outcome, Err(SendError::Retry(_))));
+        }
+        let outcome = post_message(&client, &server, &message).await;
+        assert!(matches!(outcome, Err(SendError::Rejected(m))
+            if m == "Slack returned HTTP 404 Not Found: no_service"));
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let outcome = post_message(&client, &url, &message).await;
+        assert!(matches!(outcome, Err(SendError::Retry(_))));
+    }
+
+    #[tokio::test]
+    async fn queue_keeps_the_newest_messages() {
+        let pool = pool().await;
+        for n in 0..MAX_QUEUE_SIZE + 5 {
+            queue_message(&pool, &json!({ "n": n })).await.unwrap();
+        }
+        let queued = queued(&pool).await;
+        assert_eq!(queued.len() as i64, MAX_QUEUE_SIZE);
+        assert_eq!(queued[0].0, r#"{"n":5}"#);
+    }
+
+    #[tokio::test]
+    async fn flush_sends_in_order_until_a_retryable_failure() {
+        let pool = pool().await;
+        for n in 1..=4 {
+            queue_message(&pool, &json!({ "n": n })).await.unwrap();
+        }
+        let (server, requests) = serve(vec![
+            text_response("200 OK", "ok"),
+            text_response("400 Bad Request", "invalid_payload"),
+            text_response("500 Internal Server Error", ""),
+        ])
+        .await;
+
+        let sent = flush_queue(&pool, &reqwest::Client::new(), &server).await;
+        assert_eq!(sent, 1);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+        // The rejected message is dropped; the failed one waits with the rest.
+        assert_eq!(
+            queued(&pool).await,
+            [(r#"{"n":3}"#.to_string(), 1), (r#"{"n":4}"#.to_string(), 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn messages_are_dropped_after_their_last_retry() {
+        let pool = pool().await;
+        queue_message(&pool, &json!({ "n": 1 })).await.unwrap();
+        queue_message(&pool, &json!({ "n": 2 })).await.unwrap();
+        sqlx::query("UPDATE slack_queue SET retry_count = ? WHERE id = 1")
+            .bind(MAX_RETRY_COUNT - 1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (server, _) = serve(vec![text_response("502 Bad Gateway", "")]).await;
+
+        assert_eq!(
+            flush_queue(&pool, &reqwest::Client::new(), &server).await,
+            0
+        );
+        assert_eq!(queued(&pool).await, [(r#"{"n":2}"#.to_string(), 0)]);
+    }
+}
@@ -0,0 +1,707 @@
+use serde::Serialize;
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Upper bound on the errors returned by one `spell_check_text` call.
+const MAX_ERRORS: usize = 100;
+
+/// Suggestions kept per misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Upper bound on the checked text.
+const MAX_TEXT_BYTES: usize = 256 * 1024;
+
+/// Used when no language is given and the OS default has no dictionary.
+const FALLBACK_LANGUAGE: &str = "en";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A misspelled word found by `spell_check_text`.
+#[derive(Debug, Serialize)]
+pub struct SpellError {
+    pub word: String,
+    /// Position of the word in UTF-16 code units, i.e. a JavaScript string
+    /// index.
+    pub offset: usize,
+    pub suggestions: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Check `text` with the OS spell-checker (NSSpellChecker on macOS,
+/// ISpellChecker on Windows, Enchant on Linux) and return at most 100
+/// misspelled words with suggestions. `lang` is a BCP 47 tag such as
+/// `fr-FR` (or just `fr`) and must be one of `get_spell_check_languages`;
+/// the OS language is used when omitted.
+///
+/// Not async on purpose: synchronous commands run on the main thread, which
+/// the AppKit and COM spell-checkers expect.
+#[tauri::command]
+pub fn spell_check_text(text: String, lang: Option<String>) -> Result<Vec<SpellError>, AppError> {
+    if text.len() > MAX_TEXT_BYTES {
+        return Err(AppError::Validation(format!(
+            "text too long to spell-check (max {} bytes)",
+            MAX_TEXT_BYTES
+        )));
+    }
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let installed = platform::languages()?;
+    let language = match lang {
+        Some(lang) => resolve_language(&lang, &installed).ok_or_else(|| {
+            AppError::Validation(format!("no spell-check dictionary for {}", lang))
+        })?,
+        None => platform::default_language()
+            .and_then(|lang| resolve_language(&lang, &installed))
+            .or_else(|| resolve_language(FALLBACK_LANGUAGE, &installed))
+            .or_else(|| installed.first().cloned())
+            .ok_or_else(|| AppError::Validation("no spell-check dictionary installed".into()))?,
+    };
+    platform::check(&text, &language)
+}
+
+/// List the languages the OS spell-checker has dictionaries for, as BCP 47
+/// tags (`fr-FR`, `en`).
+#[tauri::command]
+pub fn get_spell_check_languages() -> Result<Vec<String>, AppError> {
+    let mut languages: Vec<String> = platform::languages()?
+        .iter()
+        .map(|lang| lang.replace('_', "-"))
+        .collect();
+    languages.sort();
+    languages.dedup();
+    Ok(languages)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Match a requested tag against the installed (platform-spelled) languages:
+/// exact match first (`fr-FR` = `fr_FR`), then same primary language
+/// (`fr-FR` → `fr`, `fr` → `fr_FR`).
+fn resolve_language(requested: &str, installed: &[String]) -> Option<String> {
+    let normalize = |tag: &str| tag.trim().replace('_', "-").to_ascii_lowercase();
+    let requested = normalize(requested);
+    let primary = |tag: &str| tag.split('-').next().unwrap_or_default().to_string();
+
+    installed
+        .iter()
+        .find(|lang| normalize(lang) == requested)
+        .or_else(|| {
+            installed
+                .iter()
+                .find(|lang| primary(&normalize(lang)) == primary(&requested))
+        })
+        .cloned()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::ffi::{c_char, c_int, c_void, CStr, CString};
+    use std::sync::OnceLock;
+
+    use libc::{size_t, ssize_t};
+
+    use super::{SpellError, MAX_ERRORS, MAX_SUGGESTIONS};
+    use crate::error::AppError;
+
+    /// Sonames tried in order. Enchant is loaded at runtime, so neither the
+    /// build nor the package needs it; spell checking reports an error on
+    /// systems without it.
+    const LIBRARIES: [&CStr; 2] = [c"libenchant-2.so.2", c"libenchant-2.so"];
+
+    type DescribeFn =
+        extern "C" fn(*const c_char, *const c_char, *const c_char, *const c_char, *mut c_void);
+
+    /// The Enchant 2 functions used here, with the signatures of enchant.h.
+    /// Brokers and dictionaries are opaque pointers.
+    struct Enchant {
+        broker_init: unsafe extern "C" fn() -> *mut c_void,
+        broker_free: unsafe extern "C" fn(*mut c_void),
+        broker_list_dicts: unsafe extern "C" fn(*mut c_void, DescribeFn, *mut c_void),
+        broker_request_dict: unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_void,
+        broker_free_dict: unsafe extern "C" fn(*mut c_void, *mut c_void),
+        broker_get_error: unsafe extern "C" fn(*mut c_void) -> *const c_char,
+        dict_check: unsafe extern "C" fn(*mut c_void, *const c_char, ssize_t) -> c_int,
+        dict_suggest: unsafe extern "C" fn(
+            *mut c_void,
+            *const c_char,
+            ssize_t,
+            *mut size_t,
+        ) -> *mut *mut c_char,
+        dict_free_string_list: unsafe extern "C" fn(*mut c_void, *mut *mut c_char),
+        dict_get_error: unsafe extern "C" fn(*mut c_void) -> *const c_char,
+    }
+
+    fn enchant() -> Result<&'static Enchant, AppError> {
+        static ENCHANT: OnceLock<Option<Enchant>> = OnceLock::new();
+        ENCHANT
+            .get_or_init(load)
+            .as_ref()
+            .ok_or_else(|| AppError::Io("spell checking needs Enchant 2 (libenchant-2)".into()))
+    }
+
+    fn load() -> Option<Enchant> {
+        // SAFETY: the names are NUL-terminated, and the library is never
+        // closed, so its symbols stay valid for the life of the process.
+        unsafe {
+            let handle = LIBRARIES
+                .iter()
+                .map(|name| libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL))
+                .find(|handle| !handle.is_null());
+            let Some(handle) = handle else {
+                log::warn!("Enchant 2 not found; spell checking is unavailable");
+                return None;
+            };
+            Some(Enchant {
+                broker_init: symbol(handle, c"enchant_broker_init")?,
+                broker_free: symbol(handle, c"enchant_broker_free")?,
+                broker_list_dicts: symbol(handle, c"enchant_broker_list_dicts")?,
+                broker_request_dict: symbol(handle, c"enchant_broker_request_dict")?,
+                broker_free_dict: symbol(handle, c"enchant_broker_free_dict")?,
+                broker_get_error: symbol(handle, c"enchant_broker_get_error")?,
+                dict_check: symbol(handle, c"enchant_dict_check")?,
+                dict_suggest: symbol(handle, c"enchant_dict_suggest")?,
+                dict_free_string_list: symbol(handle, c"enchant_dict_free_string_list")?,
+                dict_get_error: symbol(handle, c"enchant_dict_get_error")?,
+            })
+        }
+    }
+
+    /// Look up `name` in `handle` as a function pointer of type `F`.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be a function pointer type matching the symbol's signature.
+    unsafe fn symbol<F: Copy>(handle: *mut c_void, name: &CStr) -> Option<F> {
+        let symbol = libc::dlsym(handle, name.as_ptr());
+        if symbol.is_null() {
+            log::warn!("libenchant has no {:?}", name);
+            return None;
+        }
+        Some(std::mem::transmute_copy::<*mut c_void, F>(&symbol))
+    }
+
+    /// An Enchant broker, freed on drop.
+    struct Broker {
+        enchant: &'static Enchant,
+        ptr: *mut c_void,
+    }
+
+    impl Broker {
+        fn new() -> Result<Self, AppError> {
+            let enchant = enchant()?;
+            // SAFETY: no arguments; a null result is checked.
+            let ptr = unsafe { (enchant.broker_init)() };
+            if ptr.is_null() {
+                return Err(AppError::Io("cannot start the Enchant broker".into()));
+            }
+            Ok(Broker { enchant, ptr })
+        }
+    }
+
+    impl Drop for Broker {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` came from broker_init, and its dictionaries
+            // were freed first (`Dict` borrows the broker).
+            unsafe { (self.enchant.broker_free)(self.ptr) }
+        }
+    }
+
+    /// A dictionary requested from `broker`, freed on drop.
+    struct Dict<'a> {
+        broker: &'a Broker,
+        ptr: *mut c_void,
+    }
+
+    impl Drop for Dict<'_> {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` came from broker_request_dict on this broker.
+            unsafe { (self.broker.enchant.broker_free_dict)(self.broker.ptr, self.ptr) }
+        }
+    }
+
+    /// Copy an error string owned by Enchant.
+    fn error_message(message: *const c_char) -> String {
+        if message.is_null() {
+            return "unknown error".to_string();
+        }
+        // SAFETY: a non-null, NUL-terminated string owned by Enchant.
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Installed Enchant dictionaries (`fr_FR`, `en_US`, ...).
+    pub fn languages() -> Result<Vec<String>, AppError> {
+        extern "C" fn describe(
+            lang: *const c_char,
+            _provider_name: *const c_char,
+            _provider_desc: *const c_char,
+            _provider_file: *const c_char,
+            languages: *mut c_void,
+        ) {
+            if lang.is_null() {
+                return;
+            }
+            // SAFETY: `languages` is the Vec passed to list_dicts below, and
+            // `lang` a NUL-terminated tag.
+            unsafe {
+                (*languages.cast::<Vec<String>>())
+                    .push(CStr::from_ptr(lang).to_string_lossy().into_owned());
+            }
+        }
+
+        let broker = Broker::new()?;
+        let mut languages: Vec<String> = Vec::new();
+        // SAFETY: `describe` only runs during the call, while `languages`
+        // is borrowed.
+        unsafe {
+            (broker.enchant.broker_list_dicts)(
+                broker.ptr,
+                describe,
+                (&mut languages as *mut Vec<String>).cast(),
+            )
+        };
+        Ok(languages)
+    }
+
+    /// Language of the session locale (`LANG=fr_FR.UTF-8` → `fr_FR`).
+    pub fn default_language() -> Option<String> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .map(|value| {
+                value
+                    .split(['.', '@'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+    }
+
+    pub fn check(text: &str, language: &str) -> Result<Vec<SpellError>, AppError> {
+        let tag = CString::new(language)
+            .map_err(|_| AppError::Validation(format!("invalid language: {:?}", language)))?;
+        let broker = Broker::new()?;
+        let enchant = broker.enchant;
+        // SAFETY: a live broker and a NUL-terminated tag.
+        let ptr = unsafe { (enchant.broker_request_dict)(broker.ptr, tag.as_ptr()) };
+        if ptr.is_null() {
+            // SAFETY: a live broker.
+            let message = error_message(unsafe { (enchant.broker_get_error)(broker.ptr) });
+            return Err(AppError::Io(format!(
+                "cannot load dictionary {}: {}",
+                language, message
+            )));
+        }
+        let dict = Dict {
+            broker: &broker,
+            ptr,
+        };
+
+        let mut errors = Vec::new();
+        for (offset, word) in words(text) {
+            if errors.len() >= MAX_ERRORS {
+                break;
+            }
+            // Enchant takes UTF-8 with an explicit length, so `word` needs
+            // no terminator.
+            let (bytes, len) = (word.as_ptr().cast::<c_char>(), word.len() as ssize_t);
+            // SAFETY: a live dictionary and `len` bytes of UTF-8.
+            let status = unsafe { (enchant.dict_check)(dict.ptr, bytes, len) };
+            if status < 0 {
+                // SAFETY: a live dictionary.
+                let message = error_message(unsafe { (enchant.dict_get_error)(dict.ptr) });
+                return Err(AppError::Io(format!("spell check failed: {}", message)));
+            }
+            if status == 0 {
+                continue;
+            }
+
+            let mut count: size_t = 0;
+            // SAFETY: as above; the returned list holds `count` strings and
+            // is freed with dict_free_string_list.
+            let suggestions = unsafe {
+                let list = (enchant.dict_suggest)(dict.ptr, bytes, len, &mut count);
+                if list.is_null() {
+                    Vec::new()
+                } else {
+                    let suggestions = std::slice::from_raw_parts(list, count)
+                        .iter()
+                        .take(MAX_SUGGESTIONS)
+                        .map(|suggestion| {
+                            CStr::from_ptr(*suggestion).to_string_lossy().into_owned()
+                        })
+                        .collect();
+                    (enchant.dict_free_string_list)(dict.ptr, list);
+                    suggestions
+                }
+            };
+            errors.push(SpellError {
+                word: word.to_string(),
+                offset,
+                suggestions,
+            });
+        }
+        Ok(errors)
+    }
+
+    /// Split `text` into words with their UTF-16 offsets (Enchant only
+    /// checks single words). URLs, e-mail addresses, tokens with digits,
+    /// all-caps acronyms and `code spans` are skipped.
+    pub(super) fn words(text: &str) -> Vec<(usize, &str)> {
+        let is_word_char = |c: char| c.is_alphabetic() || matches!(c, '\'' | '’' | '-');
+
+        let mut words = Vec::new();
+        let mut offset = 0;
+        let mut in_code = false;
+        for token in text.split_inclusive(char::is_whitespace) {
+            let token_offset = offset;
+            offset += token.encode_utf16().count();
+
+            let ticks = token.matches('`').count();
+            let skip = in_code
+                || ticks > 0
+                || token.contains("://")
+                || token.contains('@')
+                || token.chars().any(|c| c.is_ascii_digit());
+            in_code ^= ticks % 2 == 1;
+            if skip {
+                continue;
+            }
+
+            for part in token.split(|c: char| !is_word_char(c)) {
+                let word = part.trim_matches(|c: char| !c.is_alphabetic());
+                if word.chars().count() < 2 || !word.chars().any(char::is_lowercase) {
+                    continue;
+                }
+                let start = word.as_ptr() as usize - token.as_ptr() as usize;
+                words.push((token_offset + token[..start].encode_utf16().count(), word));
+            }
+        }
+        words
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::Foundation::S_OK;
+    use windows::Win32::Globalization::{
+        GetUserDefaultLocaleName, ISpellCheckerFactory, SpellCheckerFactory,
+        CORRECTIVE_ACTION_GET_SUGGESTIONS, CORRECTIVE_ACTION_REPLACE, LOCALE_NAME_MAX_LENGTH,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoTaskMemFree, IEnumString, CLSCTX_ALL};
+
+    use super::{SpellError, MAX_ERRORS, MAX_SUGGESTIONS};
+    use crate::error::AppError;
+
+    fn factory() -> Result<ISpellCheckerFactory, AppError> {
+        // SAFETY: COM is initialized on the main thread by the event loop.
+        unsafe { CoCreateInstance(&SpellCheckerFactory, None, CLSCTX_ALL) }
+            .map_err(|e| AppError::Io(format!("spell checker unavailable: {}", e)))
+    }
+
+    /// Languages with an installed Windows spelling dictionary (`fr-FR`).
+    pub fn languages() -> Result<Vec<String>, AppError> {
+        let factory = factory()?;
+        // SAFETY: plain COM calls on a live interface.
+        let languages = unsafe { factory.SupportedLanguages() }
+            .map_err(|e| AppError::Io(format!("cannot list spelling languages: {}", e)))?;
+        Ok(collect_strings(&languages, usize::MAX))
+    }
+
+    /// The user's locale name (`fr-FR`).
+    pub fn default_language() -> Option<String> {
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+        // SAFETY: the buffer has the documented maximum length.
+        let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+        (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+    }
+
+    pub fn check(text: &str, language: &str) -> Result<Vec<SpellError>, AppError> {
+        let spell_error =
+            |e: windows::core::Error| AppError::Io(format!("spell check failed: {}", e));
+        let units: Vec<u16> = text.encode_utf16().collect();
+        let mut errors = Vec::new();
+
+        // SAFETY: plain COM calls on live interfaces; every string returned
+        // by the spell checker is freed with CoTaskMemFree.
+        unsafe {
+            let checker = factory()?
+                .CreateSpellChecker(&HSTRING::from(language))
+                .map_err(spell_error)?;
+            let found = checker.Check(&HSTRING::from(text)).map_err(spell_error)?;
+
+            while errors.len() < MAX_ERRORS {
+                let mut error = None;
+                if found.Next(&mut error) != S_OK {
+                    break;
+                }
+                let Some(error) = error else {
+                    break;
+                };
+                let start = error.StartIndex().map_err(spell_error)? as usize;
+                let length = error.Length().map_err(spell_error)? as usize;
+                let Some(word_units) = units.get(start..start + length) else {
+                    continue;
+                };
+                let word = String::from_utf16_lossy(word_units);
+
+                let action = error.CorrectiveAction().map_err(spell_error)?;
+                let suggestions = if action == CORRECTIVE_ACTION_REPLACE {
+                    let replacement = error.Replacement().map_err(spell_error)?;
+                    take_string(replacement).into_iter().collect()
+                } else if action == CORRECTIVE_ACTION_GET_SUGGESTIONS {
+                    let suggestions = checker
+                        .Suggest(&HSTRING::from(word.as_str()))
+                        .map_err(spell_error)?;
+                    collect_strings(&suggestions, MAX_SUGGESTIONS)
+                } else {
+                    // CORRECTIVE_ACTION_DELETE: a repeated word.
+                    Vec::new()
+                };
+
+                errors.push(SpellError {
+                    word,
+                    offset: start,
+                    suggestions,
+                });
+            }
+        }
+        Ok(errors)
+    }
+
+    fn collect_strings(strings: &IEnumString, max: usize) -> Vec<String> {
+        let mut out = Vec::new();
+        while out.len() < max {
+            let mut item = [PWSTR::null()];
+            // SAFETY: `item` has room for the one requested element.
+            let result = unsafe { strings.Next(&mut item, None) };
+            if result != S_OK || item[0].is_null() {
+                break;
+            }
+            out.extend(take_string(item[0]));
+        }
+        out
+    }
+
+    /// Copy and free a string allocated by the spell checker.
+    fn take_string(string: PWSTR) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        // SAFETY: a non-null, NUL-terminated string owned by us.
+        unsafe {
+            let value = string.to_string().ok();
+            CoTaskMemFree(Some(string.as_ptr() as *const _));
+            value
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::NSSpellChecker;
+    use objc2_foundation::{NSNotFound, NSRange, NSString};
+
+    use super::{SpellError, MAX_ERRORS, MAX_SUGGESTIONS};
+    use crate::error::AppError;
+
+    fn spell_checker() -> Result<objc2::rc::Retained<NSSpellChecker>, AppError> {
+        let mtm = MainThreadMarker::new()
+            .ok_or_else(|| AppError::Io("spell checking must run on the main thread".into()))?;
+        Ok(NSSpellChecker::sharedSpellChecker(mtm))
+    }
+
+    /// Languages NSSpellChecker has dictionaries for (`fr`, `en_GB`).
+    pub fn languages() -> Result<Vec<String>, AppError> {
+        let checker = spell_checker()?;
+        // `unused_unsafe`: these bindings are safe in recent objc2-app-kit
+        // releases and unsafe in older ones.
+        #[allow(unused_unsafe)]
+        let languages = unsafe { checker.availableLanguages() };
+        Ok(languages.iter().map(|lang| lang.to_string()).collect())
+    }
+
+    /// The spell checker's current (system) language.
+    pub fn default_language() -> Option<String> {
+        let checker = spell_checker().ok()?;
+        #[allow(unused_unsafe)]
+        let language = unsafe { checker.language() };
+        Some(language.to_string())
+    }
+
+    pub fn check(text: &str, language: &str) -> Result<Vec<SpellError>, AppError> {
+        let checker = spell_checker()?;
+        let string = NSString::from_str(text);
+        let language = NSString::from_str(language);
+        let units: Vec<u16> = text.encode_utf16().collect();
+
+        let mut errors = Vec::new();
+        let mut start = 0;
+        while errors.len() < MAX_ERRORS && start < units.len() {
+            #[allow(unused_unsafe)]
+            let range: NSRange = unsafe {
+                checker.checkSpellingOfString_startingAt_language_wrap_inSpellDocumentWithTag_wordCount(
+                    &string,
+                    start as isize,
+                    Some(&language),
+                    false,
+                    0,
+                    std::ptr::null_mut(),
+                )
+            };
+            if range.location == NSNotFound as usize || range.length == 0 {
+                break;
+            }
+            let end = (range.location + range.length).min(units.len());
+
+            #[allow(unused_unsafe)]
+            let guesses = unsafe {
+                checker.guessesForWordRange_inString_language_inSpellDocumentWithTag(
+                    range,
+                    &string,
+                    Some(&language),
+                    0,
+                )
+            };
+            errors.push(SpellError {
+                word: String::from_utf16_lossy(&units[range.location..end]),
+                offset: range.location,
+                suggestions: guesses
+                    .map(|guesses| {
+                        guesses
+                            .iter()
+                            .take(MAX_SUGGESTIONS)
+                            .map(|guess| guess.to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            });
+            start = end;
+        }
+        Ok(errors)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::SpellError;
+    use crate::error::AppError;
+
+    pub fn languages() -> Result<Vec<String>, AppError> {
+        Ok(Vec::new())
+    }
+
+    pub fn default_language() -> Option<String> {
+        None
+    }
+
+    pub fn check(_text: &str, _language: &str) -> Result<Vec<SpellError>, AppError> {
+        Err(AppError::Io(
+            "spell checking is not supported on this platform".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn exact_language_is_preferred() {
+        let installed = installed(&["en_GB", "en_US", "fr"]);
+        assert_eq!(
+            resolve_language("en-US", &installed).as_deref(),
+            Some("en_US")
+        );
+        assert_eq!(
+            resolve_language(" EN_gb ", &installed).as_deref(),
+            Some("en_GB")
+        );
+    }
+
+    #[test]
+    fn primary_language_is_the_fallback() {
+        let installed = installed(&["en_US", "fr"]);
+        assert_eq!(resolve_language("fr-CA", &installed).as_deref(), Some("fr"));
+        assert_eq!(resolve_language("en", &installed).as_deref(), Some("en_US"));
+        assert_eq!(resolve_language("de-DE", &installed), None);
+    }
+
+    #[test]
+    fn oversized_and_blank_texts_are_not_checked() {
+        let text = "a".repeat(MAX_TEXT_BYTES + 1);
+        assert!(matches!(
+            spell_check_text(text, None),
+            Err(AppError::Validation(_))
+        ));
+        assert!(spell_check_text(" \n\t".into(), None).unwrap().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn words_carry_utf16_offsets_and_skip_non_words() {
+        let text = "Le café 🎉 est prêt: see https://x.io, bob@x.io, v2, NASA and `let fooo` l'été";
+        let words: Vec<(usize, &str)> = platform::words(text);
+        let utf16_offset = |word: &str| text[..text.find(word).unwrap()].encode_utf16().count();
+        assert_eq!(
+            words,
+            [
+                (0, "Le"),
+                (utf16_offset("café"), "café"),
+                (utf16_offset("est"), "est"),
+                (utf16_offset("prêt"), "prêt"),
+                (utf16_offset("see"), "see"),
+                (utf16_offset("and"), "and"),
+                (utf16_offset("l'été"), "l'été"),
+            ]
+        );
+    }
+
+    /// Runs only where the OS spell-checker has an English dictionary
+    /// (e.g. Enchant with hunspell-en-us).
+    #[test]
+    fn known_misspellings_are_reported() {
+        let Ok(languages) = get_spell_check_languages() else {
+            return;
+        };
+        if !languages.iter().any(|lang| lang.starts_with("en")) {
+            return;
+        }
+
+        let errors =
+            spell_check_text("The tikcet is redy for review".into(), Some("en".into())).unwrap();
+        let words: Vec<(&str, usize)> = errors
+            .iter()
+            .map(|error| (error.word.as_str(), error.offset))
+            .collect();
+        assert_eq!(words, [("tikcet", 4), ("redy", 14)]);
+        assert!(errors[0]
+            .suggestions
+            .iter()
+            .any(|suggestion| suggestion == "ticket"));
+        assert!(errors
+            .iter()
+            .all(|error| error.suggestions.len() <= MAX_SUGGESTIONS));
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Wall-clock time of each startup phase, measured from the previous mark
+/// (the first one from `StartupTimer::new`, at the top of `run()`).
+pub struct StartupTimer {
+    last: Instant,
+    phases: Vec<(String, Duration)>,
+}
+
+impl StartupTimer {
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// End the current phase under `name` and start the next one.
+    pub fn mark(&mut self, name: &str) {
+        let now = Instant::now();
+        self.phases.push((name.to_string(), now - self.last));
+        self.last = now;
+    }
+
+    /// Phase names and durations in milliseconds, in the order they ran.
+    pub fn phases_ms(&self) -> Vec<(String, u64)> {
+        self.phases
+            .iter()
+            .map(|(name, duration)| (name.clone(), duration.as_millis() as u64))
+            .collect()
+    }
+}
+
+impl Default for StartupTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tauri managed state holding the timer filled in by `setup`.
+pub struct StartupState {
+    pub timer: Mutex<StartupTimer>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Phase-by-phase startup timing as `[name, ms]` pairs. The frontend sums
+/// them into the `app_started` telemetry event, which goes through its
+/// consent gate like every other event.
+#[tauri::command]
+pub fn get_startup_timing(
+    state: tauri::State<'_, StartupState>,
+) -> Result<Vec<(String, u64)>, AppError> {
+    Ok(state.timer.lock().unwrap().phases_ms())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_listed_in_the_order_they_were_marked() {
+        let mut timer = StartupTimer::new();
+        timer.mark("plugins");
+        std::thread::sleep(Duration::from_millis(5));
+        timer.mark("database");
+        timer.mark("window");
+
+        let phases = timer.phases_ms();
+        let names: Vec<&str> = phases.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["plugins", "database", "window"]);
+        assert!(phases[1].1 >= 5);
+    }
+
+    #[test]
+    fn a_new_timer_has_no_phases() {
+        assert!(StartupTimer::default().phases_ms().is_empty());
+    }
+}
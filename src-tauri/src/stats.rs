@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnection;
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::project_db;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Longest range `ticket_stats` buckets per day.
+const MAX_RANGE_DAYS: i64 = 731;
+
+/// UTC offsets in use span -12:00 to +14:00.
+const MAX_TZ_OFFSET_MINUTES: i32 = 14 * 60;
+
+/// Upper bound on the number of components returned.
+const MAX_COMPONENTS: i64 = 50;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Day range of `ticket_stats`, in the user's local time.
+#[derive(Debug, Deserialize)]
+pub struct StatsRange {
+    /// First day, `YYYY-MM-DD`.
+    pub from: String,
+    /// Last day (inclusive), `YYYY-MM-DD`.
+    pub to: String,
+    /// Minutes to add to UTC to get local time (e.g. 120 for UTC+2; the
+    /// opposite sign of JS `getTimezoneOffset()`).
+    #[serde(default)]
+    pub tz_offset_minutes: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabelCount {
+    pub label: String,
+    pub count: i64,
+}
+
+/// Tickets created and closed (archived) on one local day. Days with
+/// neither are omitted.
+#[derive(Debug, Default, Serialize)]
+pub struct DayCount {
+    pub day: String,
+    pub created: i64,
+    pub closed: i64,
+}
+
+/// Return value of `ticket_stats`. Counts by section, type and component
+/// describe the current board; `per_day`, `closed_in_range` and
+/// `avg_days_to_close` are limited to the range.
+#[derive(Debug, Serialize)]
+pub struct TicketStats {
+    pub total: i64,
+    /// Sections are the board's status columns.
+    pub by_section: Vec<LabelCount>,
+    pub by_type: Vec<LabelCount>,
+    /// Most used components first.
+    pub by_component: Vec<LabelCount>,
+    pub per_day: Vec<DayCount>,
+    pub closed_in_range: i64,
+    /// Mean days from creation to archiving of the tickets closed in the
+    /// range; `None` when there are none.
+    pub avg_days_to_close: Option<f64>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Dashboard aggregates of a project, computed with grouped SQL instead of
+/// loading every ticket into the webview. A ticket counts as closed when it
+/// is archived. A project without tickets (or not yet migrated to the
+/// archive table) yields zeros and empty lists.
+#[tauri::command]
+pub async fn ticket_stats(db_path: String, range: StatsRange) -> Result<TicketStats, AppError> {
+    let (from, to) = validate_range(&range)?;
+    let shift = format!("{:+} minutes", range.tz_offset_minutes);
+
+    with_timeout(
+        async {
+            let mut conn = project_db::open_read_only(Path::new(&db_path)).await?;
+            let has_archive = table_exists(&mut conn, "archived_items").await?;
+
+            let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM backlog_items")
+                .fetch_one(&mut conn)
+                .await?;
+            let by_section = label_counts(
+                &mut conn,
+                "SELECT s.title, COUNT(bi.id) FROM sections s
+                 JOIN backlog_items bi ON bi.section_id = s.id
+                 GROUP BY s.id
+                 ORDER BY s.position",
+            )
+            .await?;
+            let by_type = label_counts(
+                &mut conn,
+                "SELECT type, COUNT(*) FROM backlog_items
+                 GROUP BY type
+                 ORDER BY COUNT(*) DESC, type",
+            )
+            .await?;
+            let by_component = label_counts(
+                &mut conn,
+                &format!(
+                    "SELECT TRIM(component), COUNT(*) FROM backlog_items
+                     WHERE TRIM(COALESCE(component, '')) != ''
+                     GROUP BY TRIM(component)
+                     ORDER BY COUNT(*) DESC, TRIM(component)
+                     LIMIT {}",
+                    MAX_COMPONENTS
+                ),
+            )
+            .await?;
+
+            let created_sql = if has_archive {
+                "SELECT date(created_at, ?1) AS day, COUNT(*) FROM (
+                     SELECT created_at FROM backlog_items
+                     UNION ALL
+                     SELECT original_created_at FROM archived_items
+                 )
+                 WHERE day BETWEEN ?2 AND ?3
+                 GROUP BY day"
+            } else {
+                "SELECT date(created_at, ?1) AS day, COUNT(*) FROM backlog_items
+                 WHERE day BETWEEN ?2 AND ?3
+                 GROUP BY day"
+            };
+            let created: Vec<(String, i64)> = sqlx::query_as(created_sql)
+                .bind(&shift)
+                .bind(&from)
+                .bind(&to)
+                .fetch_all(&mut conn)
+                .await?;
+
+            let mut closed: Vec<(String, i64)> = Vec::new();
+            let mut closed_in_range = 0;
+            let mut avg_days_to_close = None;
+            if has_archive {
+                closed = sqlx::query_as(
+                    "SELECT date(archived_at, ?1) AS day, COUNT(*) FROM archived_items
+                     WHERE day BETWEEN ?2 AND ?3
+                     GROUP BY day",
+                )
+                .bind(&shift)
+                .bind(&from)
+                .bind(&to)
+                .fetch_all(&mut conn)
+                .await?;
+                closed_in_range = closed.iter().map(|(_, count)| count).sum();
+                avg_days_to_close = sqlx::query_scalar(
+                    "SELECT AVG(julianday(archived_at) - julianday(original_created_at))
+                     FROM archived_items
+                     WHERE original_created_at IS NOT NULL
+                       AND date(archived_at, ?1) BETWEEN ?2 AND ?3",
+                )
+                .bind(&shift)
+                .bind(&from)
+                .bind(&to)
+                .fetch_one(&mut conn)
+                .await?;
+            }
+
+            Ok(TicketStats {
+                total,
+                by_section,
+                by_type,
+                by_component,
+                per_day: merge_days(created, closed),
+                closed_in_range,
+                avg_days_to_close,
+            })
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Check the range and return its bounds normalized to `YYYY-MM-DD`.
+fn validate_range(range: &StatsRange) -> Result<(String, String), AppError> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("invalid date: {}", value)))
+    };
+    let (from, to) = (parse(&range.from)?, parse(&range.to)?);
+    if from > to {
+        return Err(AppError::Validation("range starts after it ends".into()));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(AppError::Validation(format!(
+            "range is longer than {} days",
+            MAX_RANGE_DAYS
+        )));
+    }
+    if range.tz_offset_minutes.abs() > MAX_TZ_OFFSET_MINUTES {
+        return Err(AppError::Validation(format!(
+            "invalid timezone offset: {} minutes",
+            range.tz_offset_minutes
+        )));
+    }
+    Ok((
+        from.format("%Y-%m-%d").to_string(),
+        to.format("%Y-%m-%d").to_string(),
+    ))
+}
+
+/// Whether the database has a table `name`.
+pub async fn table_exists(conn: &mut SqliteConnection, name: &str) -> Result<bool, AppError> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+    )
+    .bind(name)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(exists)
+}
+
+/// Run a `SELECT label, count` query.
+async fn label_counts(conn: &mut SqliteConnection, sql: &str) -> Result<Vec<LabelCount>, AppError> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(sql).fetch_all(&mut *conn).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(label, count)| LabelCount { label, count })
+        .collect())
+}
+
+/// Join the created and closed counts per day, in date order.
+fn merge_days(created: Vec<(String, i64)>, closed: Vec<(String, i64)>) -> Vec<DayCount> {
+    let mut days: BTreeMap<String, DayCount> = BTreeMap::new();
+    for (day, count) in created {
+        days.entry(day).or_default().created = count;
+    }
+    for (day, count) in closed {
+        days.entry(day).or_default().closed = count;
+    }
+    days.into_iter()
+        .map(|(day, mut count)| {
+            count.day = day;
+            count
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_db, BOARD};
+
+    /// The `BOARD` project in `dir`, then `seed`.
+    async fn project(dir: &Path, seed: &str) -> String {
+        let path = create_db(
+            dir,
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            &[BOARD, seed].concat(),
+        )
+        .await;
+        path.to_string_lossy().into_owned()
+    }
+
+    fn range(from: &str, to: &str, tz_offset_minutes: i32) -> StatsRange {
+        StatsRange {
+            from: from.to_string(),
+            to: to.to_string(),
+            tz_offset_minutes,
+        }
+    }
+
+    #[tokio::test]
+    async fn new_project_has_empty_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = project(dir.path(), "").await;
+        let stats = ticket_stats(db, range("2024-03-01", "2024-03-31", 0))
+            .await
+            .unwrap();
+        assert_eq!(stats.total, 0);
+        assert!(stats.by_section.is_empty());
+        assert!(stats.by_type.is_empty());
+        assert!(stats.per_day.is_empty());
+        assert_eq!(stats.closed_in_range, 0);
+        assert_eq!(stats.avg_days_to_close, None);
+    }
+
+    #[tokio::test]
+    async fn tickets_are_grouped_and_bucketed_by_local_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = "
+            INSERT INTO backlog_items (id, project_id, section_id, type, title, component,
+                raw_markdown, created_at) VALUES
+                ('BUG-1', 1, 1, 'BUG', 'Crash', ' api ', '', '2024-03-01 10:00:00'),
+                ('BUG-2', 1, 1, 'BUG', 'Hang', 'api', '', '2024-03-01 23:30:00'),
+                ('FEAT-1', 1, 2, 'FEAT', 'Export', '', '', '2024-02-10 08:00:00');
+            INSERT INTO archived_items (id, project_id, type, title, raw_markdown,
+                original_created_at, archived_at) VALUES
+                ('BUG-3', 1, 'BUG', 'Typo', '', '2024-02-28 09:00:00', '2024-03-02 09:00:00'),
+                ('BUG-4', 1, 'BUG', 'Leak', '', '2024-02-29 12:00:00', '2024-03-02 12:00:00');
+        ";
+        let db = project(dir.path(), seed).await;
+        // UTC+2: the ticket created at 23:30 UTC falls on March 2nd.
+        let stats = ticket_stats(db, range("2024-03-01", "2024-03-31", 120))
+            .await
+            .unwrap();
+
+        assert_eq!(stats.total, 3);
+        let labels = |counts: &[LabelCount]| {
+            counts
+                .iter()
+                .map(|c| (c.label.clone(), c.count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            labels(&stats.by_section),
+            [("Todo".to_string(), 2), ("Done".to_string(), 1)]
+        );
+        assert_eq!(
+            labels(&stats.by_type),
+            [("BUG".to_string(), 2), ("FEAT".to_string(), 1)]
+        );
+        assert_eq!(labels(&stats.by_component), [("api".to_string(), 2)]);
+
+        let days: Vec<(&str, i64, i64)> = stats
+            .per_day
+            .iter()
+            .map(|d| (d.day.as_str(), d.created, d.closed))
+            .collect();
+        assert_eq!(days, [("2024-03-01", 1, 0), ("2024-03-02", 1, 2)]);
+        assert_eq!(stats.closed_in_range, 2);
+        assert_eq!(stats.avg_days_to_close, Some(2.5));
+    }
+
+    #[test]
+    fn invalid_ranges_are_refused() {
+        for bad in [
+            range("2024-03-31", "2024-03-01", 0),
+            range("2024-02-30", "2024-03-01", 0),
+            range("2022-01-01", "2024-01-02", 0),
+            range("2024-03-01", "2024-03-31", 15 * 60),
+        ] {
+            assert!(matches!(validate_range(&bad), Err(AppError::Validation(_))));
+        }
+        assert_eq!(
+            validate_range(&range("2024-03-01", "2024-03-01", -720)).unwrap(),
+            ("2024-03-01".to_string(), "2024-03-01".to_string())
+        );
+    }
+}
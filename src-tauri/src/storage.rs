@@ -0,0 +1,635 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::commands::with_timeout;
+use crate::disk;
+use crate::error::AppError;
+use crate::files;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// CLI flag enabling portable mode.
+pub const PORTABLE_FLAG: &str = "--portable";
+
+/// Marker file next to the executable that enables portable mode.
+const PORTABLE_MARKER: &str = "portable.marker";
+
+/// Data directory (relative to the executable) used in portable mode.
+const PORTABLE_DATA_DIR: &str = "data";
+
+/// File stamped into the data directory recording which mode created it.
+const MODE_STAMP_FILE: &str = ".storage-mode";
+
+/// Bundle identifier; must match `identifier` in `tauri.conf.json`. Used to
+/// locate the app data directory when no Tauri app is running (headless CLI).
+const APP_IDENTIFIER: &str = "com.ticketflow.app";
+
+/// File in `<config_dir>/<identifier>/` holding the data directory chosen
+/// with `migrate_app_data`. Read at startup, before the data directory is.
+const CONFIG_FILE: &str = "config.json";
+
+/// The telemetry database, copied with `VACUUM INTO` because it is open.
+const TELEMETRY_DB_FILE: &str = "telemetry.db";
+
+const DATA_DIR_CHANGED_EVENT: &str = "app:data-dir-changed";
+
+/// Budget of `migrate_app_data`. Copying years of backups takes far longer
+/// than `DB_TIMEOUT_MS`.
+const MIGRATE_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+/// Directories owned by the OS; the app data is never moved inside them.
+#[cfg(unix)]
+const SYSTEM_DIRS: [&str; 10] = [
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/proc", "/sbin", "/sys", "/usr", "/System",
+];
+
+/// Environment variables naming the directories owned by the OS.
+#[cfg(windows)]
+const SYSTEM_DIR_VARS: [&str; 4] = [
+    "SystemRoot",
+    "ProgramFiles",
+    "ProgramFiles(x86)",
+    "ProgramData",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Where Ticketflow keeps its own databases (telemetry, settings).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMode {
+    /// OS app data directory (roaming profile).
+    Installed,
+    /// `<exe_dir>/data`, for USB sticks and locked-down machines.
+    Portable,
+}
+
+impl StorageMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            StorageMode::Installed => "installed",
+            StorageMode::Portable => "portable",
+        }
+    }
+}
+
+/// Tauri managed state describing the resolved storage location.
+pub struct StorageState {
+    pub mode: StorageMode,
+    pub data_dir: PathBuf,
+}
+
+/// Return value of `get_storage_mode`.
+#[derive(Debug, Serialize)]
+pub struct StorageInfo {
+    pub mode: StorageMode,
+    pub data_dir: String,
+}
+
+/// Contents of `config.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AppConfig {
+    /// Data directory used instead of the OS app data directory.
+    #[serde(default)]
+    data_dir: Option<PathBuf>,
+}
+
+/// Payload of `app:data-dir-changed`.
+#[derive(Debug, Clone, Serialize)]
+struct DataDirChanged {
+    old_dir: String,
+    new_dir: String,
+}
+
+// ---------------------------------------------------------------------------
+// Resolution (called from lib.rs before and during setup)
+// ---------------------------------------------------------------------------
+
+/// Portable mode is active when `--portable` is passed or `portable.marker`
+/// sits next to the executable. Must be known before the builder is
+/// assembled because portable builds don't register the updater.
+pub fn detect_storage_mode() -> StorageMode {
+    let flag = std::env::args().any(|arg| arg == PORTABLE_FLAG);
+    let marker = exe_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER).is_file());
+
+    if flag || marker {
+        StorageMode::Portable
+    } else {
+        StorageMode::Installed
+    }
+}
+
+/// Resolve the data directory for `mode`, create it, and check its mode
+/// stamp. A directory created by the other mode is rejected rather than
+/// silently reused.
+pub fn resolve_data_dir(app: &tauri::AppHandle, mode: StorageMode) -> Result<PathBuf, String> {
+    let data_dir = match mode {
+        StorageMode::Installed => match data_dir_override()? {
+            Some(dir) => dir,
+            None => app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("app data dir unavailable: {}", e))?,
+        },
+        StorageMode::Portable => exe_dir()
+            .ok_or_else(|| "cannot locate the executable directory".to_string())?
+            .join(PORTABLE_DATA_DIR),
+    };
+
+    prepare_data_dir(&data_dir, mode)?;
+
+    log::info!(
+        "storage: {} mode, data dir {}",
+        mode.as_str(),
+        data_dir.display()
+    );
+    Ok(data_dir)
+}
+
+/// Same as `resolve_data_dir`, for headless CLI flags that run before any
+/// Tauri app exists. Mirrors Tauri's `app_data_dir` (`<data_dir>/<identifier>`).
+pub fn resolve_headless_data_dir(mode: StorageMode) -> Result<PathBuf, String> {
+    let data_dir = match mode {
+        StorageMode::Installed => match data_dir_override()? {
+            Some(dir) => dir,
+            None => dirs::data_dir()
+                .ok_or_else(|| "app data dir unavailable".to_string())?
+                .join(APP_IDENTIFIER),
+        },
+        StorageMode::Portable => exe_dir()
+            .ok_or_else(|| "cannot locate the executable directory".to_string())?
+            .join(PORTABLE_DATA_DIR),
+    };
+
+    prepare_data_dir(&data_dir, mode)?;
+    Ok(data_dir)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Report whether the app runs in installed or portable mode.
+#[tauri::command]
+pub fn get_storage_mode(state: tauri::State<'_, StorageState>) -> Result<StorageInfo, AppError> {
+    Ok(StorageInfo {
+        mode: state.mode,
+        data_dir: state.data_dir.to_string_lossy().into_owned(),
+    })
+}
+
+/// Copy the app data (telemetry and settings database, backups, crash
+/// reports) to `new_dir` and use it from the next start on. `new_dir` must
+/// be in the fs scope, empty or not exist yet, outside the current data
+/// directory and not a system location. The current directory is left in
+/// place. Refused in portable mode, while a project is open and during a
+/// backup or restore.
+#[tauri::command]
+pub async fn migrate_app_data(
+    new_dir: String,
+    app: AppHandle,
+    storage: tauri::State<'_, StorageState>,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    telemetry: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let result = with_timeout(
+        async {
+            if storage.mode == StorageMode::Portable {
+                return Err(AppError::Validation(
+                    "portable installs keep their data next to the executable".into(),
+                ));
+            }
+            if !project_db::open_database_paths(&app).await.is_empty() {
+                return Err(AppError::Validation(
+                    "close the open project before moving the app data".into(),
+                ));
+            }
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let new_dir = files::validate_path(&app, Path::new(new_dir.trim()))?;
+            let new_dir = prepare_target(&storage.data_dir, &new_dir)?;
+            disk::ensure_free_space(&new_dir, dir_size(&storage.data_dir)?)?;
+
+            if let Err(e) = copy_data_dir(&storage.data_dir, &new_dir, &telemetry.pool()).await {
+                // The target was empty: leave it that way.
+                clear_dir(&new_dir);
+                return Err(e);
+            }
+            write_config(&AppConfig {
+                data_dir: Some(new_dir.clone()),
+            })?;
+
+            app.emit(
+                DATA_DIR_CHANGED_EVENT,
+                DataDirChanged {
+                    old_dir: storage.data_dir.to_string_lossy().into_owned(),
+                    new_dir: new_dir.to_string_lossy().into_owned(),
+                },
+            )
+            .ok();
+            Ok(())
+        },
+        MIGRATE_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &telemetry.pool(),
+        "migrate_app_data",
+        &new_dir,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// The data directory set with `migrate_app_data`, if any.
+#[tauri::command]
+pub fn get_app_data_override() -> Result<Option<String>, AppError> {
+    Ok(read_config()
+        .map_err(AppError::Io)?
+        .data_dir
+        .map(|dir| dir.to_string_lossy().into_owned()))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+fn prepare_data_dir(data_dir: &Path, mode: StorageMode) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("cannot create {}: {}", data_dir.display(), e))?;
+    check_mode_stamp(data_dir, mode)
+}
+
+/// Stamp a fresh data directory with `mode`, or verify an existing stamp.
+fn check_mode_stamp(data_dir: &Path, mode: StorageMode) -> Result<(), String> {
+    let stamp_path = data_dir.join(MODE_STAMP_FILE);
+
+    match std::fs::read_to_string(&stamp_path) {
+        Ok(stamp) if stamp.trim() == mode.as_str() => Ok(()),
+        Ok(stamp) => Err(format!(
+            "{} was created in {} mode and cannot be used in {} mode",
+            data_dir.display(),
+            stamp.trim(),
+            mode.as_str()
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            std::fs::write(&stamp_path, mode.as_str())
+                .map_err(|e| format!("cannot write {}: {}", stamp_path.display(), e))
+        }
+        Err(e) => Err(format!("cannot read {}: {}", stamp_path.display(), e)),
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_IDENTIFIER).join(CONFIG_FILE))
+}
+
+fn read_config() -> Result<AppConfig, String> {
+    let Some(path) = config_path() else {
+        return Ok(AppConfig::default());
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|e| format!("invalid {}: {}", path.display(), e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppConfig::default()),
+        Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+    }
+}
+
+fn write_config(config: &AppConfig) -> Result<(), AppError> {
+    let path =
+        config_path().ok_or_else(|| AppError::Io("OS config directory unavailable".into()))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_vec_pretty(config)
+        .map_err(|e| AppError::Io(format!("cannot serialize {}: {}", CONFIG_FILE, e)))?;
+    std::fs::write(&path, json)?;
+    Ok(())
+}
+
+/// Data directory set in `config.json`. It is not created when missing:
+/// an unplugged drive must not silently start an empty data directory.
+fn data_dir_override() -> Result<Option<PathBuf>, String> {
+    let Some(dir) = read_config()?.data_dir else {
+        return Ok(None);
+    };
+    if !dir.is_dir() {
+        return Err(format!(
+            "data directory {} (set in {}) is unavailable",
+            dir.display(),
+            CONFIG_FILE
+        ));
+    }
+    Ok(Some(dir))
+}
+
+/// Create `new_dir` if needed and check it can receive the app data:
+/// absolute, empty, writable, not a system location, and neither inside
+/// nor around `data_dir`. Returns its canonical path.
+fn prepare_target(data_dir: &Path, new_dir: &Path) -> Result<PathBuf, AppError> {
+    if !new_dir.is_absolute() {
+        return Err(AppError::Validation(format!(
+            "not an absolute path: {}",
+            new_dir.display()
+        )));
+    }
+    std::fs::create_dir_all(new_dir)?;
+    let new_dir = new_dir.canonicalize()?;
+    if is_system_location(&new_dir) {
+        return Err(AppError::Validation(format!(
+            "{} is a system location",
+            new_dir.display()
+        )));
+    }
+    let data_dir = project_db::canonical(data_dir);
+    if new_dir.starts_with(&data_dir) || data_dir.starts_with(&new_dir) {
+        return Err(AppError::Validation(format!(
+            "{} overlaps the current data directory",
+            new_dir.display()
+        )));
+    }
+    if std::fs::read_dir(&new_dir)?.next().is_some() {
+        return Err(AppError::Validation(format!(
+            "{} is not empty",
+            new_dir.display()
+        )));
+    }
+
+    let probe = new_dir.join(".write-test");
+    std::fs::write(&probe, b"").map_err(|e| {
+        AppError::Validation(format!("{} is not writable: {}", new_dir.display(), e))
+    })?;
+    std::fs::remove_file(&probe)?;
+    Ok(new_dir)
+}
+
+/// Whether the canonical `dir` is a filesystem root, one of the user's
+/// standard folders itself, or inside a directory owned by the OS or the
+/// installed app.
+fn is_system_location(dir: &Path) -> bool {
+    if dir.parent().is_none() {
+        return true;
+    }
+    let user_dirs = [
+        dirs::home_dir(),
+        dirs::desktop_dir(),
+        dirs::document_dir(),
+        dirs::download_dir(),
+        dirs::config_dir(),
+        dirs::data_dir(),
+        dirs::data_local_dir(),
+        dirs::cache_dir(),
+    ];
+    if user_dirs
+        .into_iter()
+        .flatten()
+        .any(|user_dir| project_db::canonical(&user_dir) == dir)
+    {
+        return true;
+    }
+
+    #[cfg(unix)]
+    let mut system: Vec<PathBuf> = SYSTEM_DIRS.iter().map(PathBuf::from).collect();
+    #[cfg(windows)]
+    let mut system: Vec<PathBuf> = SYSTEM_DIR_VARS
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
+    #[cfg(not(any(unix, windows)))]
+    let mut system: Vec<PathBuf> = Vec::new();
+    system.extend(exe_dir());
+    system
+        .iter()
+        .any(|system_dir| dir.starts_with(project_db::canonical(system_dir)))
+}
+
+/// Copy every file of `from` to `to`, checking each copy's size. The open
+/// telemetry database is snapshotted with `VACUUM INTO` instead, and its
+/// `-wal` / `-shm` files are skipped.
+async fn copy_data_dir(from: &Path, to: &Path, pool: &SqlitePool) -> Result<(), AppError> {
+    // Blocking work; off the async workers so the timeout can fire.
+    let (source, target) = (from.to_path_buf(), to.to_path_buf());
+    tauri::async_runtime::spawn_blocking(move || copy_tree(&source, &target, true))
+        .await
+        .map_err(|e| AppError::Io(format!("migrate_app_data task failed: {}", e)))??;
+    sqlx::query("VACUUM INTO ?")
+        .bind(to.join(TELEMETRY_DB_FILE).to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn copy_tree(from: &Path, to: &Path, root: bool) -> Result<(), AppError> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if root && name.to_string_lossy().starts_with(TELEMETRY_DB_FILE) {
+            continue;
+        }
+        let (source, target) = (entry.path(), to.join(&name));
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_tree(&source, &target, false)?;
+        } else if file_type.is_file() {
+            let copied = std::fs::copy(&source, &target)?;
+            if copied != entry.metadata()?.len() || std::fs::metadata(&target)?.len() != copied {
+                return Err(AppError::Io(format!(
+                    "copy of {} is incomplete",
+                    source.display()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Total size of the files under `dir`.
+fn dir_size(dir: &Path) -> Result<u64, AppError> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Remove everything inside `dir`, best effort.
+fn clear_dir(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(e) = removed {
+            log::warn!("migrate_app_data: cannot remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::ConnectOptions;
+
+    #[test]
+    fn target_must_be_absolute_empty_and_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        std::fs::create_dir_all(data_dir.join("backups")).unwrap();
+
+        let target = prepare_target(&data_dir, &dir.path().join("moved/data")).unwrap();
+        assert!(target.is_dir());
+        assert!(target.is_absolute());
+        assert_eq!(std::fs::read_dir(&target).unwrap().count(), 0, "probe left");
+
+        for new_dir in [
+            data_dir.join("backups"),
+            data_dir.join("inner"),
+            dir.path().to_path_buf(),
+            data_dir.clone(),
+        ] {
+            let err = prepare_target(&data_dir, &new_dir).unwrap_err();
+            assert!(
+                matches!(&err, AppError::Validation(m) if m.contains("overlaps")),
+                "{}: {:?}",
+                new_dir.display(),
+                err
+            );
+        }
+
+        std::fs::write(target.join("notes.txt"), b"x").unwrap();
+        let err = prepare_target(&data_dir, &target).unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(m) if m.contains("not empty")),
+            "{:?}",
+            err
+        );
+        let err = prepare_target(&data_dir, Path::new("relative/data")).unwrap_err();
+        assert!(
+            matches!(&err, AppError::Validation(m) if m.contains("absolute")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn system_locations_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let mut refused: Vec<PathBuf> = dirs::home_dir().into_iter().collect();
+        #[cfg(unix)]
+        refused.extend([PathBuf::from("/"), PathBuf::from("/usr")]);
+        refused.extend(exe_dir());
+        for new_dir in refused {
+            let err = prepare_target(&data_dir, &new_dir).unwrap_err();
+            assert!(
+                matches!(&err, AppError::Validation(m) if m.contains("system location")),
+                "{}: {:?}",
+                new_dir.display(),
+                err
+            );
+        }
+        assert!(!is_system_location(&project_db::canonical(dir.path())));
+    }
+
+    #[tokio::test]
+    async fn data_dir_is_copied_with_a_database_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("from"), dir.path().join("to"));
+        std::fs::create_dir_all(from.join("backups/client-a")).unwrap();
+        std::fs::write(from.join("backups/client-a/backlog.db.1"), b"backup").unwrap();
+        std::fs::write(from.join(MODE_STAMP_FILE), b"installed").unwrap();
+        let options = SqliteConnectOptions::new()
+            .filename(from.join(TELEMETRY_DB_FILE))
+            .create_if_missing(true)
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE kv_store (key TEXT, value TEXT); INSERT INTO kv_store VALUES ('a', 'b');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(from.join("telemetry.db-wal").is_file());
+
+        copy_data_dir(&from, &to, &pool).await.unwrap();
+
+        assert_eq!(
+            std::fs::read(to.join("backups/client-a/backlog.db.1")).unwrap(),
+            b"backup"
+        );
+        assert_eq!(
+            std::fs::read(to.join(MODE_STAMP_FILE)).unwrap(),
+            b"installed"
+        );
+        assert!(!to.join("telemetry.db-wal").exists());
+        let mut conn = SqliteConnectOptions::new()
+            .filename(to.join(TELEMETRY_DB_FILE))
+            .connect()
+            .await
+            .unwrap();
+        let value: String = sqlx::query_scalar("SELECT value FROM kv_store WHERE key = 'a'")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(value, "b");
+        assert_eq!(dir_size(&to.join("backups")).unwrap(), 6);
+
+        clear_dir(&to);
+        assert_eq!(std::fs::read_dir(&to).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn mode_stamp_is_written_then_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        check_mode_stamp(dir.path(), StorageMode::Portable).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(MODE_STAMP_FILE)).unwrap(),
+            "portable"
+        );
+        check_mode_stamp(dir.path(), StorageMode::Portable).unwrap();
+        let err = check_mode_stamp(dir.path(), StorageMode::Installed).unwrap_err();
+        assert!(err.contains("portable mode"), "{}", err);
+    }
+}
@@ -0,0 +1,180 @@
+// Project databases for the unit tests, created from the frontend's own
+// schema (`initializeSchema` in `src/db/database.ts`) and migrations
+// (`src/db/migrations.ts`), so the tests run against the tables, types and
+// constraints the app creates rather than a hand-written subset.
+
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqliteConnection};
+
+use crate::project_db;
+
+const BASE_SCHEMA_SOURCE: &str = include_str!("../../src/db/database.ts");
+const MIGRATIONS_SOURCE: &str = include_str!("../../src/db/migrations.ts");
+
+/// The Demo project with the BUG and FEAT types and a `Todo` (id 1) and a
+/// `Done` (id 2) section. `{path}` stands for the project folder.
+pub(crate) const BOARD: &str = "
+    INSERT INTO projects (id, name, path) VALUES (1, 'Demo', '{path}');
+    INSERT INTO type_configs (id, project_id, label, color, position, visible) VALUES
+        ('BUG', 1, 'Bug', '#f00', 0, 1), ('FEAT', 1, 'Feature', '#0f0', 1, 1);
+    INSERT INTO sections (id, project_id, title, position, raw_header) VALUES
+        (1, 1, 'Todo', 0, '## 1. Todo'), (2, 1, 'Done', 1, '## 2. Done');
+";
+
+/// BUG-1 in `Todo`.
+const DEMO: &str = "
+    INSERT INTO backlog_items (id, project_id, section_id, type, title, position, raw_markdown)
+        VALUES ('BUG-1', 1, 1, 'BUG', 'Crash', 0, '### BUG-1 | Crash');
+";
+
+/// A single ticket in `BOARD`'s `Todo`, for tests that read it back.
+pub(crate) const INVOICE: &str = "
+    INSERT INTO backlog_items (id, project_id, section_id, type, title, raw_markdown)
+        VALUES ('FEAT-1', 1, 1, 'FEAT', 'Invoice client', '### FEAT-1 | Invoice client');
+";
+
+/// The BUG counter for `DEMO`, from schema v7 where `type_counters` appears.
+const DEMO_COUNTER: &str = "
+    INSERT INTO type_counters (project_id, type_prefix, last_number) VALUES (1, 'BUG', 1);
+";
+
+/// Create `backlog.db` in `dir` at schema `version` (0 for the base schema
+/// alone), run `seed` on it with `{path}` replaced by `dir`, and return its
+/// path. Foreign keys are enforced unless `seed` turns them off.
+pub(crate) async fn create_db(dir: &Path, version: i64, seed: &str) -> PathBuf {
+    let path = dir.join(project_db::PROJECT_DB_FILE);
+    let mut conn = connect(&path).await;
+    sqlx::raw_sql(&schema(0, version))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    sqlx::raw_sql(&seed.replace("{path}", &dir.to_string_lossy()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    conn.close().await.unwrap();
+    path
+}
+
+/// Create the Demo project (`BOARD` and BUG-1) in `dir` at the supported
+/// schema version and return its database path.
+pub(crate) async fn project(dir: &Path) -> PathBuf {
+    project_at(dir, project_db::SUPPORTED_SCHEMA_VERSION).await
+}
+
+/// `project` at schema `version`.
+pub(crate) async fn project_at(dir: &Path, version: i64) -> PathBuf {
+    let counter = if version >= 7 { DEMO_COUNTER } else { "" };
+    create_db(dir, version, &[BOARD, DEMO, counter].concat()).await
+}
+
+/// Run the migrations of `db_path` up to `version`, as the frontend does
+/// when it opens the project.
+pub(crate) async fn migrate(db_path: &Path, version: i64) {
+    let mut conn = connect(db_path).await;
+    let current: i64 = sqlx::query_scalar("PRAGMA user_version;")
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    sqlx::raw_sql(&schema(current, version))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    conn.close().await.unwrap();
+}
+
+/// (id, section title, position) of every live item, by id.
+pub(crate) async fn items(db_path: &Path) -> Vec<(String, String, i64)> {
+    let mut conn = project_db::open_read_only(db_path).await.unwrap();
+    sqlx::query_as(
+        "SELECT i.id, s.title, i.position FROM backlog_items i
+         JOIN sections s ON s.id = i.section_id ORDER BY i.id",
+    )
+    .fetch_all(&mut conn)
+    .await
+    .unwrap()
+}
+
+async fn connect(path: &Path) -> SqliteConnection {
+    SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .connect()
+        .await
+        .unwrap()
+}
+
+/// SQL taking a database from schema `from` to `to`: the base schema when
+/// `from` is 0, then the migrations in between.
+fn schema(from: i64, to: i64) -> String {
+    let mut statements = if from == 0 {
+        ddl(BASE_SCHEMA_SOURCE)
+    } else {
+        Vec::new()
+    };
+    let version = Regex::new(r"version: (\d+),").unwrap();
+    let starts: Vec<(i64, usize)> = version
+        .captures_iter(MIGRATIONS_SOURCE)
+        .map(|c| (c[1].parse().unwrap(), c.get(0).unwrap().start()))
+        .collect();
+    for (i, &(migration, start)) in starts.iter().enumerate() {
+        if migration > from && migration <= to {
+            let end = starts.get(i + 1).map_or(MIGRATIONS_SOURCE.len(), |s| s.1);
+            statements.extend(ddl(&MIGRATIONS_SOURCE[start..end]));
+        }
+    }
+    statements.push(format!("PRAGMA user_version = {}", to));
+    statements.join(";\n") + ";"
+}
+
+/// The statements of `source` that create or alter tables, indexes and
+/// triggers: its template literals and quoted `CREATE INDEX` strings.
+fn ddl(source: &str) -> Vec<String> {
+    let literal = Regex::new(r"`([^`]*)`|'(CREATE INDEX [^']*)'").unwrap();
+    literal
+        .captures_iter(source)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|m| m.as_str().trim())
+        .filter(|sql| sql.starts_with("CREATE ") || sql.starts_with("ALTER "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_end_at_the_supported_version() {
+        let last = Regex::new(r"version: (\d+),")
+            .unwrap()
+            .captures_iter(MIGRATIONS_SOURCE)
+            .map(|c| c[1].parse::<i64>().unwrap())
+            .last();
+        assert_eq!(last, Some(project_db::SUPPORTED_SCHEMA_VERSION));
+    }
+
+    #[tokio::test]
+    async fn migrating_an_old_project_gives_the_current_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = create_db(dir.path(), 5, BOARD).await;
+        migrate(&old, project_db::SUPPORTED_SCHEMA_VERSION).await;
+        let current_dir = tempfile::tempdir().unwrap();
+        let current = create_db(current_dir.path(), project_db::SUPPORTED_SCHEMA_VERSION, "").await;
+
+        let tables = |db: PathBuf| async move {
+            let mut conn = project_db::open_read_only(&db).await.unwrap();
+            let tables: Vec<(String, String)> = sqlx::query_as(
+                "SELECT name, sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY name",
+            )
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+            tables
+        };
+        assert_eq!(tables(old).await, tables(current).await);
+    }
+}
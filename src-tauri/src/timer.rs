@@ -0,0 +1,525 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backup::MaintenanceState;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::import::open_project;
+use crate::kv;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// `kv_store` key holding the running timer as JSON, so it survives a
+/// restart.
+const RUNNING_TIMER_KV: &str = "running_timer";
+
+/// First schema version with `time_entries`.
+const MIN_SCHEMA_VERSION: i64 = 10;
+
+/// Emitted with the new `TimerStatus` (or `null`) whenever the timer
+/// starts, stops, pauses or resumes.
+const TIMER_CHANGED_EVENT: &str = "timer:changed";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// The running timer. Times are Unix milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunningTimer {
+    db_path: String,
+    ticket_id: String,
+    started_at: i64,
+    /// Paused time of completed pauses.
+    paused_ms: i64,
+    /// Start of the current pause, if paused.
+    paused_at: Option<i64>,
+}
+
+impl RunningTimer {
+    /// Paused time up to `now`, the current pause included.
+    fn paused_until(&self, now: i64) -> i64 {
+        self.paused_ms
+            + self
+                .paused_at
+                .map_or(0, |paused_at| (now - paused_at).max(0))
+    }
+
+    fn status(&self, now: i64) -> TimerStatus {
+        TimerStatus {
+            db_path: self.db_path.clone(),
+            ticket_id: self.ticket_id.clone(),
+            started_at: self.started_at,
+            elapsed_ms: (now - self.started_at - self.paused_until(now)).max(0),
+            paused: self.paused_at.is_some(),
+        }
+    }
+}
+
+/// Return value of `timer_status` and `timer_start`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerStatus {
+    pub db_path: String,
+    pub ticket_id: String,
+    /// Unix milliseconds.
+    pub started_at: i64,
+    /// Running time so far, pauses excluded.
+    pub elapsed_ms: i64,
+    pub paused: bool,
+}
+
+/// A row of `time_entries`. Times are Unix milliseconds.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub item_id: String,
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// Time tracked, pauses excluded.
+    pub duration_ms: i64,
+    pub paused_ms: i64,
+}
+
+/// Tracked time of one local day.
+#[derive(Debug, Serialize)]
+pub struct DailyTime {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub duration_ms: i64,
+}
+
+/// Return value of `list_time_entries`.
+#[derive(Debug, Serialize)]
+pub struct TimeEntries {
+    /// Newest first.
+    pub entries: Vec<TimeEntry>,
+    /// Oldest first, by the local day each entry started.
+    pub daily: Vec<DailyTime>,
+    pub total_ms: i64,
+}
+
+/// Tauri managed state of the ticket timer.
+pub struct TimerState {
+    /// Held across the database write of a stop, so two commands cannot
+    /// record the same interval.
+    running: tokio::sync::Mutex<Option<RunningTimer>>,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Register `TimerState` with the timer that was running when the app last
+/// exited; the time the app was closed counts as tracked. Called once from
+/// `lib.rs` during app setup, after `init_telemetry_db`.
+pub async fn init_timer(app: &AppHandle) {
+    let running = load_running(&app.state::<TelemetryState>().pool()).await;
+    app.manage(TimerState {
+        running: tokio::sync::Mutex::new(running),
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Start timing `ticket_id` of the project at `db_path`. A timer already
+/// running, for this ticket or another, is stopped and recorded first.
+#[tauri::command]
+pub async fn timer_start(
+    db_path: String,
+    ticket_id: String,
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    telemetry: tauri::State<'_, TelemetryState>,
+) -> Result<TimerStatus, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    let _guard = maintenance_guard(&maintenance)?;
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    with_timeout(
+        async {
+            let (mut conn, context) = open_project(&db, true, MIN_SCHEMA_VERSION).await?;
+            let exists: Option<i64> =
+                sqlx::query_scalar("SELECT 1 FROM backlog_items WHERE id = ? AND project_id = ?")
+                    .bind(&ticket_id)
+                    .bind(context.project_id)
+                    .fetch_optional(&mut conn)
+                    .await?;
+            if exists.is_none() {
+                return Err(AppError::Validation(format!(
+                    "no ticket with id {}",
+                    ticket_id
+                )));
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    let mut running = state.running.lock().await;
+    let now = now_ms();
+    if let Some(previous) = running.as_ref() {
+        record_entry(previous, now).await?;
+    }
+    let timer = RunningTimer {
+        db_path: db.to_string_lossy().into_owned(),
+        ticket_id,
+        started_at: now,
+        paused_ms: 0,
+        paused_at: None,
+    };
+    let status = timer.status(now);
+    *running = Some(timer);
+    persist(&telemetry.pool(), running.as_ref()).await;
+    app.emit(TIMER_CHANGED_EVENT, Some(&status)).ok();
+    Ok(status)
+}
+
+/// Stop the running timer and record its interval in `time_entries`.
+/// Returns `None` when no timer was running.
+#[tauri::command]
+pub async fn timer_stop(
+    app: AppHandle,
+    state: tauri::State<'_, TimerState>,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    telemetry: tauri::State<'_, TelemetryState>,
+) -> Result<Option<TimeEntry>, AppError> {
+    let _guard = maintenance_guard(&maintenance)?;
+    let _paused = fs_watch::pause_project_watch(&app);
+
+    with_timeout(
+        async {
+            let mut running = state.running.lock().await;
+            let Some(timer) = running.as_ref() else {
+                return Ok(None);
+            };
+            let entry = record_entry(timer, now_ms()).await?;
+            *running = None;
+            persist(&telemetry.pool(), None).await;
+            app.emit(TIMER_CHANGED_EVENT, None::<TimerStatus>).ok();
+            Ok(Some(entry))
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// The running timer, if any. The state lives in the backend, so it
+/// survives webview reloads and hidden windows.
+#[tauri::command]
+pub async fn timer_status(
+    state: tauri::State<'_, TimerState>,
+) -> Result<Option<TimerStatus>, AppError> {
+    with_timeout(
+        async {
+            let running = state.running.lock().await;
+            Ok(running.as_ref().map(|timer| timer.status(now_ms())))
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Recorded time of `ticket_id`, with totals per local day.
+#[tauri::command]
+pub async fn list_time_entries(
+    db_path: String,
+    ticket_id: String,
+    app: AppHandle,
+) -> Result<TimeEntries, AppError> {
+    let db = files::validate_path(&app, Path::new(&db_path))?;
+    time_entries(&db, &ticket_id).await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// `list_time_entries` for the project at `db_path`.
+async fn time_entries(db_path: &Path, ticket_id: &str) -> Result<TimeEntries, AppError> {
+    let entries: Vec<TimeEntry> = with_timeout(
+        async {
+            let (mut conn, context) = open_project(db_path, true, MIN_SCHEMA_VERSION).await?;
+            let entries = sqlx::query_as(
+                "SELECT id, item_id, started_at, ended_at, duration_ms, paused_ms
+                 FROM time_entries
+                 WHERE project_id = ? AND item_id = ?
+                 ORDER BY started_at DESC, id DESC",
+            )
+            .bind(context.project_id)
+            .bind(ticket_id)
+            .fetch_all(&mut conn)
+            .await?;
+            Ok(entries)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    let mut days: BTreeMap<String, i64> = BTreeMap::new();
+    for entry in &entries {
+        let date = Local
+            .timestamp_millis_opt(entry.started_at)
+            .single()
+            .map(|at| at.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        *days.entry(date).or_default() += entry.duration_ms;
+    }
+    Ok(TimeEntries {
+        total_ms: entries.iter().map(|entry| entry.duration_ms).sum(),
+        daily: days
+            .into_iter()
+            .map(|(date, duration_ms)| DailyTime { date, duration_ms })
+            .collect(),
+        entries,
+    })
+}
+
+/// Pause the running timer while the user is away (the app is locked);
+/// the paused time is left out of the recorded duration.
+pub async fn pause_running(app: &AppHandle) {
+    update_running(app, |timer, now| {
+        if timer.paused_at.is_none() {
+            timer.paused_at = Some(now);
+        }
+    })
+    .await;
+}
+
+/// Resume a timer paused by `pause_running`.
+pub async fn resume_running(app: &AppHandle) {
+    update_running(app, |timer, now| {
+        if let Some(paused_at) = timer.paused_at.take() {
+            timer.paused_ms += (now - paused_at).max(0);
+        }
+    })
+    .await;
+}
+
+async fn update_running(app: &AppHandle, update: impl FnOnce(&mut RunningTimer, i64)) {
+    let Some(state) = app.try_state::<TimerState>() else {
+        return;
+    };
+    let mut running = state.running.lock().await;
+    let Some(timer) = running.as_mut() else {
+        return;
+    };
+    let now = now_ms();
+    update(timer, now);
+    let status = timer.status(now);
+    persist(&app.state::<TelemetryState>().pool(), running.as_ref()).await;
+    app.emit(TIMER_CHANGED_EVENT, Some(&status)).ok();
+}
+
+/// Insert the interval of `timer` ending at `now` into its project's
+/// `time_entries`.
+async fn record_entry(timer: &RunningTimer, now: i64) -> Result<TimeEntry, AppError> {
+    let paused_ms = timer.paused_until(now);
+    let duration_ms = (now - timer.started_at - paused_ms).max(0);
+    with_timeout(
+        async {
+            let (mut conn, context) =
+                open_project(Path::new(&timer.db_path), false, MIN_SCHEMA_VERSION).await?;
+            let id = sqlx::query(
+                "INSERT INTO time_entries
+                   (project_id, item_id, started_at, ended_at, duration_ms, paused_ms)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(context.project_id)
+            .bind(&timer.ticket_id)
+            .bind(timer.started_at)
+            .bind(now)
+            .bind(duration_ms)
+            .bind(paused_ms)
+            .execute(&mut conn)
+            .await?
+            .last_insert_rowid();
+            Ok(TimeEntry {
+                id,
+                item_id: timer.ticket_id.clone(),
+                started_at: timer.started_at,
+                ended_at: now,
+                duration_ms,
+                paused_ms,
+            })
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// The timer saved by `persist`, if any.
+async fn load_running(pool: &SqlitePool) -> Option<RunningTimer> {
+    match kv::get(pool, RUNNING_TIMER_KV).await {
+        Ok(json) => json.and_then(|json| serde_json::from_str(&json).ok()),
+        Err(e) => {
+            log::warn!("init_timer: cannot read running timer: {}", e);
+            None
+        }
+    }
+}
+
+/// Save the running timer to `kv_store`; failures are logged only, as the
+/// in-memory state stays authoritative until the app exits.
+async fn persist(pool: &SqlitePool, timer: Option<&RunningTimer>) {
+    let result = match timer.and_then(|timer| serde_json::to_string(timer).ok()) {
+        Some(json) => kv::set(pool, RUNNING_TIMER_KV, &json).await,
+        None => kv::delete(pool, RUNNING_TIMER_KV).await,
+    };
+    if let Err(e) = result {
+        log::warn!("timer: cannot persist running timer: {}", e);
+    }
+}
+
+fn maintenance_guard(
+    maintenance: &MaintenanceState,
+) -> Result<tokio::sync::MutexGuard<'_, ()>, AppError> {
+    if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+        return Err(AppError::Validation("a migration is in progress".into()));
+    }
+    maintenance
+        .lock
+        .try_lock()
+        .map_err(|_| AppError::Validation("a backup or restore is in progress".into()))
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::project_at;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::path::PathBuf;
+
+    /// The Demo project at v10, the first with `time_entries`.
+    async fn timed_project(dir: &Path) -> PathBuf {
+        project_at(dir, 10).await
+    }
+
+    fn timer(db_path: &Path, ticket_id: &str, started_at: i64) -> RunningTimer {
+        RunningTimer {
+            db_path: db_path.to_string_lossy().into_owned(),
+            ticket_id: ticket_id.into(),
+            started_at,
+            paused_ms: 0,
+            paused_at: None,
+        }
+    }
+
+    /// Unix ms of a local wall-clock time.
+    fn at(day: u32, hour: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+            .unwrap()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn elapsed_time_leaves_out_pauses() {
+        let mut timer = timer(Path::new("project.db"), "BUG-1", 1_000);
+        timer.paused_ms = 200;
+        timer.paused_at = Some(5_000);
+
+        let status = timer.status(6_000);
+        assert_eq!(timer.paused_until(6_000), 1_200);
+        assert_eq!(status.elapsed_ms, 3_800);
+        assert!(status.paused);
+
+        // A pause starting after `now` (clock change) counts as none.
+        assert_eq!(timer.paused_until(4_000), 200);
+        timer.paused_at = None;
+        assert_eq!(timer.status(500).elapsed_ms, 0);
+    }
+
+    #[tokio::test]
+    async fn entries_are_recorded_and_totalled_per_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = timed_project(dir.path()).await;
+
+        let mut paused = timer(&db_path, "BUG-1", at(4, 9));
+        paused.paused_ms = 600_000;
+        paused.paused_at = Some(at(4, 10));
+        let entry = record_entry(&paused, at(4, 11)).await.unwrap();
+        assert_eq!(entry.paused_ms, 600_000 + 3_600_000);
+        assert_eq!(entry.duration_ms, 3_600_000 - 600_000);
+        record_entry(&timer(&db_path, "BUG-1", at(4, 14)), at(4, 15))
+            .await
+            .unwrap();
+        record_entry(&timer(&db_path, "BUG-1", at(5, 9)), at(5, 10))
+            .await
+            .unwrap();
+        record_entry(&timer(&db_path, "BUG-9", at(5, 9)), at(5, 12))
+            .await
+            .unwrap();
+
+        let listed = time_entries(&db_path, "BUG-1").await.unwrap();
+        let starts: Vec<i64> = listed.entries.iter().map(|e| e.started_at).collect();
+        assert_eq!(starts, [at(5, 9), at(4, 14), at(4, 9)]);
+        let daily: Vec<(&str, i64)> = listed
+            .daily
+            .iter()
+            .map(|day| (day.date.as_str(), day.duration_ms))
+            .collect();
+        assert_eq!(
+            daily,
+            [("2024-03-04", 6_600_000), ("2024-03-05", 3_600_000)]
+        );
+        assert_eq!(listed.total_ms, 10_200_000);
+    }
+
+    #[tokio::test]
+    async fn projects_before_time_entries_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = project_at(dir.path(), 9).await;
+
+        let err = record_entry(&timer(&db_path, "BUG-1", 0), 1_000)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(message) if message.contains("need v10")));
+    }
+
+    #[tokio::test]
+    async fn running_timer_survives_a_restart() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(&pool).await.unwrap();
+        assert!(load_running(&pool).await.is_none());
+
+        let mut running = timer(Path::new("/p/project.db"), "BUG-1", 1_000);
+        running.paused_at = Some(2_000);
+        persist(&pool, Some(&running)).await;
+        let loaded = load_running(&pool).await.unwrap();
+        assert_eq!(loaded.ticket_id, "BUG-1");
+        assert_eq!(loaded.paused_at, Some(2_000));
+
+        persist(&pool, None).await;
+        assert!(load_running(&pool).await.is_none());
+
+        kv::set(&pool, RUNNING_TIMER_KV, "not json").await.unwrap();
+        assert!(load_running(&pool).await.is_none());
+    }
+}
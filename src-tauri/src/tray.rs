@@ -0,0 +1,669 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{
+    image::Image,
+    menu::{Menu, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager,
+};
+use tauri_plugin_updater::UpdaterExt;
+
+use crate::commands::{with_timeout, DB_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::files;
+use crate::kv;
+use crate::shutdown;
+use crate::telemetry::TelemetryState;
+use crate::window;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+const MENU_OPEN: &str = "open";
+const MENU_QUIT: &str = "quit";
+const MENU_INSTALL_UPDATE: &str = "install_update";
+
+/// Ids the frontend may relabel or disable.
+const MENU_IDS: [&str; 3] = [MENU_OPEN, MENU_QUIT, MENU_INSTALL_UPDATE];
+
+const MAX_LABEL_CHARS: usize = 100;
+
+/// Tray icons, loaded from `resources/tray-<name>.png` at startup.
+const ICON_NAMES: [&str; 4] = ["default", "syncing", "error", "notification"];
+
+/// Bounds of a tray animation.
+const MIN_ANIMATION_FRAMES: usize = 2;
+const MAX_ANIMATION_FRAMES: usize = 24;
+const MIN_FRAME_INTERVAL_MS: u64 = 50;
+
+/// `kv_store` key of the close-button behavior (`true` or `false`).
+pub(crate) const MINIMIZE_TO_TRAY_KV: &str = "minimize_to_tray";
+
+/// Default tray menu labels, until the frontend sets translated ones.
+/// Every menu rebuild reads from this table so the optional items compose
+/// with the fixed ones in a stable order.
+struct TrayLabels {
+    open: &'static str,
+    quit: &'static str,
+    install_update: &'static str,
+}
+
+const LABELS: TrayLabels = TrayLabels {
+    open: "Ouvrir Ticketflow",
+    quit: "Quitter",
+    install_update: "Installer la mise à jour",
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Tauri managed state holding the tray handle and the inputs the menu is
+/// built from. Mutate the inputs, then call `rebuild_menu`.
+pub struct TrayState {
+    pub tray: TrayIcon,
+    /// Version of an update found by the last updater check, if any.
+    pub update_version: Mutex<Option<String>>,
+    /// Whether closing the main window hides it to the tray (default) or
+    /// quits the app.
+    pub minimize_to_tray: AtomicBool,
+    /// Labels and enabled flags set by the frontend.
+    overrides: Mutex<MenuOverrides>,
+    /// Items of the current menu by id, replaced on every rebuild.
+    items: Mutex<MenuItems>,
+    /// Icons of `ICON_NAMES` for `set_tray_icon`.
+    icons: HashMap<String, Image<'static>>,
+    /// Animation started by `start_tray_animation`, if running.
+    animation: Mutex<Option<TrayAnimation>>,
+    next_animation_id: AtomicU64,
+}
+
+/// What the close button of a window does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    /// Let the window close (secondary windows).
+    Close,
+    /// Hide the main window to the tray.
+    HideToTray,
+    /// Quit the app after the orderly cleanup.
+    Quit,
+}
+
+struct TrayAnimation {
+    id: String,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+type MenuItems = HashMap<String, MenuItem<tauri::Wry>>;
+
+/// Menu item changes made at runtime, by item id, kept across rebuilds.
+#[derive(Default)]
+struct MenuOverrides {
+    labels: HashMap<String, String>,
+    disabled: HashSet<String>,
+}
+
+impl MenuOverrides {
+    fn set_label(&mut self, id: &str, label: &str) -> Result<(), AppError> {
+        validate_item_id(id)?;
+        let label = label.trim();
+        if label.is_empty() || label.chars().count() > MAX_LABEL_CHARS {
+            return Err(AppError::Validation(format!(
+                "label must be 1 to {} characters",
+                MAX_LABEL_CHARS
+            )));
+        }
+        self.labels.insert(id.to_string(), label.to_string());
+        Ok(())
+    }
+
+    fn set_enabled(&mut self, id: &str, enabled: bool) -> Result<(), AppError> {
+        validate_item_id(id)?;
+        if enabled {
+            self.disabled.remove(id);
+        } else {
+            self.disabled.insert(id.to_string());
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Build the tray icon and its menu, and register `TrayState`.
+/// Called once from `lib.rs` during app setup, after `init_telemetry_db`.
+/// Fails, and with it the app start, when a tray icon resource is missing.
+pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+    let icons = load_icons(app)?;
+    let (menu, items) = build_menu(app, None, &MenuOverrides::default())?;
+
+    let tray = TrayIconBuilder::new()
+        .icon(icons["default"].clone())
+        .tooltip("Ticketflow")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            MENU_INSTALL_UPDATE => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    install_staged_update(app).await;
+                });
+            }
+            MENU_OPEN => {
+                window::show_main_window(app);
+            }
+            MENU_QUIT => {
+                // Show window first so user can see the confirmation modal
+                // (rebuilt if it was destroyed, so the modal has a host)
+                if let Some(window) = window::show_main_window(app) {
+                    // Then emit event for frontend to show confirmation
+                    window.emit("tray:quit-requested", ()).ok();
+                }
+            }
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            // Left click on tray icon = restore window
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                window::show_main_window(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    let pool = &app.state::<TelemetryState>().pool();
+    let minimize_to_tray = tauri::async_runtime::block_on(load_minimize_to_tray(pool));
+
+    app.manage(TrayState {
+        tray,
+        update_version: Mutex::new(None),
+        minimize_to_tray: AtomicBool::new(minimize_to_tray),
+        overrides: Mutex::new(MenuOverrides::default()),
+        items: Mutex::new(items),
+        icons,
+        animation: Mutex::new(None),
+        next_animation_id: AtomicU64::new(0),
+    });
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Called by the frontend after every updater check. `Some(version)` adds the
+/// "install update" item to the tray menu; `None` removes it.
+#[tauri::command]
+pub fn set_tray_update_available(version: Option<String>, app: AppHandle) -> Result<(), AppError> {
+    let state = app.state::<TrayState>();
+    *state.update_version.lock().unwrap() = version;
+    rebuild_menu(&app).map_err(|e| AppError::Io(format!("cannot rebuild tray menu: {}", e)))
+}
+
+/// Choose what the main window's close button does: hide to the tray
+/// (`true`, the default) or quit the app. Persisted across restarts.
+#[tauri::command]
+pub async fn set_minimize_to_tray(
+    enabled: bool,
+    tray: tauri::State<'_, TrayState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    with_timeout(
+        async {
+            kv::set(&state.pool(), MINIMIZE_TO_TRAY_KV, &enabled.to_string()).await?;
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+    tray.minimize_to_tray.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Whether the main window's close button hides to the tray.
+#[tauri::command]
+pub fn get_minimize_to_tray(tray: tauri::State<'_, TrayState>) -> Result<bool, AppError> {
+    Ok(tray.minimize_to_tray.load(Ordering::Relaxed))
+}
+
+/// Change the label of a tray menu item (`open`, `quit` or
+/// `install_update`), e.g. when the UI language changes. Kept until the
+/// app restarts; the update item gets its version appended.
+#[tauri::command]
+pub fn set_tray_menu_item_label(
+    item_id: String,
+    label: String,
+    tray: tauri::State<'_, TrayState>,
+) -> Result<(), AppError> {
+    let mut overrides = tray.overrides.lock().unwrap();
+    overrides.set_label(&item_id, &label)?;
+    if let Some(item) = tray.items.lock().unwrap().get(&item_id) {
+        let version = tray.update_version.lock().unwrap().clone();
+        item.set_text(item_label(&item_id, version.as_deref(), &overrides))
+            .map_err(|e| AppError::Io(format!("cannot update tray menu: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Enable or disable (grey out) a tray menu item.
+#[tauri::command]
+pub fn set_tray_menu_item_enabled(
+    item_id: String,
+    enabled: bool,
+    tray: tauri::State<'_, TrayState>,
+) -> Result<(), AppError> {
+    tray.overrides
+        .lock()
+        .unwrap()
+        .set_enabled(&item_id, enabled)?;
+    if let Some(item) = tray.items.lock().unwrap().get(&item_id) {
+        item.set_enabled(enabled)
+            .map_err(|e| AppError::Io(format!("cannot update tray menu: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Switch the tray icon to reflect the app state: `default`, `syncing`,
+/// `error` or `notification`.
+#[tauri::command]
+pub fn set_tray_icon(icon_name: String, app: AppHandle) -> Result<(), AppError> {
+    let tray = app.state::<TrayState>();
+    let icon = tray
+        .icons
+        .get(&icon_name)
+        .ok_or_else(|| AppError::Validation(format!("unknown tray icon: {}", icon_name)))?;
+    tray.tray
+        .set_icon(Some(icon.clone()))
+        .map_err(|e| AppError::Io(format!("cannot set tray icon: {}", e)))
+}
+
+/// Cycle the tray icon through the PNG images at `frame_paths` (2 to 24)
+/// every `interval_ms` (at least 50), e.g. while a sync runs. The frames
+/// are loaded up front. A running animation is replaced. Returns the id to
+/// pass to `stop_tray_animation`.
+#[tauri::command]
+pub fn start_tray_animation(
+    frame_paths: Vec<String>,
+    interval_ms: u64,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    if !(MIN_ANIMATION_FRAMES..=MAX_ANIMATION_FRAMES).contains(&frame_paths.len()) {
+        return Err(AppError::Validation(format!(
+            "an animation needs {} to {} frames",
+            MIN_ANIMATION_FRAMES, MAX_ANIMATION_FRAMES
+        )));
+    }
+    if interval_ms < MIN_FRAME_INTERVAL_MS {
+        return Err(AppError::Validation(format!(
+            "frame interval must be at least {} ms",
+            MIN_FRAME_INTERVAL_MS
+        )));
+    }
+    let frames = frame_paths
+        .iter()
+        .map(|path| {
+            let path = files::validate_path(&app, Path::new(path))?;
+            Image::from_path(&path)
+                .map_err(|e| AppError::Io(format!("cannot load frame {}: {}", path.display(), e)))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    let state = app.state::<TrayState>();
+    let id = format!(
+        "tray-animation-{}",
+        state.next_animation_id.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    let tray = state.tray.clone();
+    let task = tauri::async_runtime::spawn(animate(
+        frames,
+        Duration::from_millis(interval_ms),
+        move |frame| {
+            if let Err(e) = tray.set_icon(Some(frame.clone())) {
+                log::warn!("start_tray_animation: {}", e);
+            }
+        },
+    ));
+
+    let previous = state.animation.lock().unwrap().replace(TrayAnimation {
+        id: id.clone(),
+        task,
+    });
+    if let Some(previous) = previous {
+        previous.task.abort();
+    }
+    Ok(id)
+}
+
+/// Stop the animation started by `start_tray_animation` and restore the
+/// default icon.
+#[tauri::command]
+pub fn stop_tray_animation(
+    animation_id: String,
+    tray: tauri::State<'_, TrayState>,
+) -> Result<(), AppError> {
+    let mut animation = tray.animation.lock().unwrap();
+    match animation.as_ref() {
+        Some(running) if running.id == animation_id => {
+            running.task.abort();
+            *animation = None;
+        }
+        _ => {
+            return Err(AppError::Validation(format!(
+                "no tray animation with id {}",
+                animation_id
+            )))
+        }
+    }
+    tray.tray
+        .set_icon(Some(tray.icons["default"].clone()))
+        .map_err(|e| AppError::Io(format!("cannot set tray icon: {}", e)))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Whether closing the main window should hide it rather than quit. True
+/// until `TrayState` is registered.
+pub fn minimize_to_tray(app: &AppHandle) -> bool {
+    app.try_state::<TrayState>()
+        .map_or(true, |tray| tray.minimize_to_tray.load(Ordering::Relaxed))
+}
+
+/// What closing the window labelled `label` does, given the
+/// `minimize_to_tray` setting.
+pub fn close_action(label: &str, minimize_to_tray: bool) -> CloseAction {
+    if label != window::MAIN_WINDOW {
+        CloseAction::Close
+    } else if minimize_to_tray {
+        CloseAction::HideToTray
+    } else {
+        CloseAction::Quit
+    }
+}
+
+/// The persisted close-button behavior; hiding to the tray unless it was
+/// turned off.
+async fn load_minimize_to_tray(pool: &sqlx::SqlitePool) -> bool {
+    match kv::get(pool, MINIMIZE_TO_TRAY_KV).await {
+        Ok(value) => value.as_deref() != Some("false"),
+        Err(e) => {
+            log::warn!("init_tray: cannot read close behavior: {}", e);
+            true
+        }
+    }
+}
+
+/// Rebuild the tray menu from the current `TrayState` inputs.
+pub fn rebuild_menu(app: &AppHandle) -> tauri::Result<()> {
+    let state = app.state::<TrayState>();
+    let update_version = state.update_version.lock().unwrap().clone();
+    let (menu, items) = {
+        let overrides = state.overrides.lock().unwrap();
+        build_menu(app, update_version.as_deref(), &overrides)?
+    };
+    state.tray.set_menu(Some(menu))?;
+    *state.items.lock().unwrap() = items;
+    Ok(())
+}
+
+fn build_menu(
+    app: &AppHandle,
+    update_version: Option<&str>,
+    overrides: &MenuOverrides,
+) -> tauri::Result<(Menu<tauri::Wry>, MenuItems)> {
+    let menu = Menu::new(app)?;
+    let mut items = HashMap::new();
+    let mut append = |id: &str| -> tauri::Result<()> {
+        let item = MenuItem::with_id(
+            app,
+            id,
+            item_label(id, update_version, overrides),
+            !overrides.disabled.contains(id),
+            None::<&str>,
+        )?;
+        menu.append(&item)?;
+        items.insert(id.to_string(), item);
+        Ok(())
+    };
+
+    if update_version.is_some() {
+        append(MENU_INSTALL_UPDATE)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    append(MENU_OPEN)?;
+    append(MENU_QUIT)?;
+
+    Ok((menu, items))
+}
+
+/// Pass `frames` to `show` in a loop, one every `interval`, until the task
+/// is aborted.
+async fn animate(
+    frames: Vec<Image<'static>>,
+    interval: Duration,
+    mut show: impl FnMut(&Image<'static>),
+) {
+    let mut interval = tokio::time::interval(interval);
+    for frame in frames.iter().cycle() {
+        interval.tick().await;
+        show(frame);
+    }
+}
+
+/// Load every icon of `ICON_NAMES` from the bundled resources.
+fn load_icons(app: &AppHandle) -> tauri::Result<HashMap<String, Image<'static>>> {
+    load_icons_from(&app.path().resource_dir()?.join("resources"))
+}
+
+fn load_icons_from(dir: &Path) -> tauri::Result<HashMap<String, Image<'static>>> {
+    ICON_NAMES
+        .iter()
+        .map(|name| {
+            let path = dir.join(format!("tray-{}.png", name));
+            let icon = Image::from_path(&path).map_err(|e| {
+                log::error!("init_tray: cannot load {}: {}", path.display(), e);
+                e
+            })?;
+            Ok((name.to_string(), icon))
+        })
+        .collect()
+}
+
+/// Label of a menu item: the frontend's if set, else the default.
+fn item_label(id: &str, update_version: Option<&str>, overrides: &MenuOverrides) -> String {
+    let default = match id {
+        MENU_OPEN => LABELS.open,
+        MENU_QUIT => LABELS.quit,
+        _ => LABELS.install_update,
+    };
+    let label = overrides.labels.get(id).map_or(default, String::as_str);
+    match (id, update_version) {
+        (MENU_INSTALL_UPDATE, Some(version)) => format!("{} (v{})", label, version),
+        _ => label.to_string(),
+    }
+}
+
+fn validate_item_id(id: &str) -> Result<(), AppError> {
+    if !MENU_IDS.contains(&id) {
+        return Err(AppError::Validation(format!(
+            "unknown tray menu item: {}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Re-check, download and install the staged update, then restart through
+/// the orderly shutdown path. Drops the tray item if no update is found.
+async fn install_staged_update(app: AppHandle) {
+    let update = match app.updater() {
+        Ok(updater) => updater.check().await,
+        Err(e) => Err(e),
+    };
+
+    let update = match update {
+        Ok(Some(update)) => update,
+        Ok(None) => {
+            set_tray_update_available(None, app).ok();
+            return;
+        }
+        Err(e) => {
+            log::error!("install_staged_update: update check failed: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = update.download_and_install(|_, _| {}, || {}).await {
+        log::error!("install_staged_update: install failed: {}", e);
+        return;
+    }
+
+    set_tray_update_available(None, app.clone()).ok();
+    shutdown::orderly_cleanup(&app).await;
+    app.restart();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secondary_windows_always_close() {
+        for minimize in [true, false] {
+            assert_eq!(close_action("window-1", minimize), CloseAction::Close);
+        }
+    }
+
+    #[test]
+    fn main_window_hides_to_tray_when_enabled() {
+        assert_eq!(
+            close_action(window::MAIN_WINDOW, true),
+            CloseAction::HideToTray
+        );
+    }
+
+    #[test]
+    fn main_window_quits_when_minimize_to_tray_is_off() {
+        assert_eq!(close_action(window::MAIN_WINDOW, false), CloseAction::Quit);
+    }
+
+    fn resources() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")
+    }
+
+    #[test]
+    fn known_menu_items_are_relabeled_and_disabled() {
+        let mut overrides = MenuOverrides::default();
+        overrides
+            .set_label(MENU_OPEN, "  Open Ticketflow ")
+            .unwrap();
+        overrides
+            .set_label(MENU_INSTALL_UPDATE, "Install update")
+            .unwrap();
+        overrides.set_enabled(MENU_QUIT, false).unwrap();
+
+        assert_eq!(item_label(MENU_OPEN, None, &overrides), "Open Ticketflow");
+        assert_eq!(item_label(MENU_QUIT, None, &overrides), LABELS.quit);
+        assert_eq!(
+            item_label(MENU_INSTALL_UPDATE, Some("2.1.0"), &overrides),
+            "Install update (v2.1.0)"
+        );
+        assert!(overrides.disabled.contains(MENU_QUIT));
+        overrides.set_enabled(MENU_QUIT, true).unwrap();
+        assert!(overrides.disabled.is_empty());
+    }
+
+    #[test]
+    fn unknown_menu_items_and_bad_labels_are_refused() {
+        let mut overrides = MenuOverrides::default();
+        for id in ["settings", "", "OPEN", "open "] {
+            assert!(matches!(
+                overrides.set_label(id, "Label"),
+                Err(AppError::Validation(_))
+            ));
+            assert!(matches!(
+                overrides.set_enabled(id, false),
+                Err(AppError::Validation(_))
+            ));
+        }
+        for label in [" ", &"x".repeat(MAX_LABEL_CHARS + 1)] {
+            assert!(overrides.set_label(MENU_OPEN, label).is_err());
+        }
+        overrides
+            .set_label(MENU_OPEN, &"é".repeat(MAX_LABEL_CHARS))
+            .unwrap();
+        assert_eq!(overrides.labels.len(), 1);
+        assert!(overrides.disabled.is_empty());
+    }
+
+    #[test]
+    fn icon_map_holds_every_tray_icon() {
+        let icons = load_icons_from(&resources()).unwrap();
+        let mut names: Vec<&str> = icons.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["default", "error", "notification", "syncing"]);
+    }
+
+    #[test]
+    fn a_missing_icon_fails_the_load() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &ICON_NAMES[..3] {
+            std::fs::copy(
+                resources().join(format!("tray-{}.png", name)),
+                dir.path().join(format!("tray-{}.png", name)),
+            )
+            .unwrap();
+        }
+        assert!(load_icons_from(dir.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn animation_cycles_frames_until_aborted() {
+        let frames = (1..=3)
+            .map(|n| Image::new_owned(vec![n; 4], 1, 1))
+            .collect();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(animate(frames, Duration::from_millis(5), move |frame| {
+            tx.send(frame.rgba()[0]).ok();
+        }));
+
+        let mut shown = Vec::new();
+        for _ in 0..4 {
+            shown.push(rx.recv().await.unwrap());
+        }
+        assert_eq!(shown, [1, 2, 3, 1]);
+
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+        // Aborting dropped the task, and with it the sender.
+        let drained = tokio::time::timeout(Duration::from_secs(1), async {
+            while rx.recv().await.is_some() {}
+        })
+        .await;
+        assert!(drained.is_ok(), "the aborted animation is still running");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn close_behavior_defaults_to_the_tray(pool: sqlx::SqlitePool) {
+        sqlx::raw_sql(kv::KV_SCHEMA).execute(&pool).await.unwrap();
+        assert!(load_minimize_to_tray(&pool).await);
+
+        kv::set(&pool, MINIMIZE_TO_TRAY_KV, "false").await.unwrap();
+        assert!(!load_minimize_to_tray(&pool).await);
+        kv::set(&pool, MINIMIZE_TO_TRAY_KV, "true").await.unwrap();
+        assert!(load_minimize_to_tray(&pool).await);
+    }
+}
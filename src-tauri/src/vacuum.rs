@@ -0,0 +1,324 @@
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::audit;
+use crate::backup::MaintenanceState;
+use crate::commands::with_timeout;
+use crate::disk;
+use crate::error::AppError;
+use crate::files;
+use crate::fs_watch;
+use crate::project_db;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Event emitted when each phase starts and then every `HEARTBEAT_MS` while
+/// `VACUUM` runs.
+const VACUUM_PROGRESS_EVENT: &str = "vacuum:progress";
+
+const HEARTBEAT_MS: u64 = 1000;
+
+/// Attempts at `VACUUM` while another connection holds a lock, on top of the
+/// connection's own busy timeout.
+const BUSY_RETRIES: u32 = 4;
+
+/// First retry delay; doubled after every attempt.
+const BUSY_BACKOFF_MS: u64 = 500;
+
+/// Budget of `vacuum_project_db`. Rebuilding a large database takes far
+/// longer than `DB_TIMEOUT_MS`.
+const VACUUM_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Return value of `vacuum_project_db`. Sizes include the `-wal` file.
+#[derive(Debug, Serialize)]
+pub struct VacuumResult {
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub duration_ms: u64,
+}
+
+/// The task emitting `vacuum:progress` heartbeats, stopped when dropped,
+/// including when a timeout cancels `vacuum_database` mid-`VACUUM`.
+struct Heartbeat(tauri::async_runtime::JoinHandle<()>);
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Payload of `vacuum:progress`.
+#[derive(Debug, Clone, Serialize)]
+struct VacuumProgress {
+    job_id: Option<String>,
+    /// `checkpoint`, `vacuum` or `retry`.
+    phase: &'static str,
+    elapsed_ms: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Rebuild a project database with `VACUUM` to give the space of deleted
+/// rows back to the file system. The WAL is checkpointed first, then
+/// `VACUUM` runs on a dedicated connection; a lock held by the frontend's
+/// pool is retried with backoff. Emits `vacuum:progress` heartbeats tagged
+/// with `job_id` since large files take a while, which is also why the
+/// command gets `VACUUM_TIMEOUT_MS` rather than `DB_TIMEOUT_MS`. Refuses to
+/// run when the disk has less free space than the database occupies.
+#[tauri::command]
+pub async fn vacuum_project_db(
+    db_path: String,
+    job_id: Option<String>,
+    app: AppHandle,
+    maintenance: tauri::State<'_, MaintenanceState>,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<VacuumResult, AppError> {
+    let result = with_timeout(
+        async {
+            let db = files::validate_path(&app, Path::new(&db_path))?;
+            if maintenance.migration_in_progress.load(Ordering::SeqCst) {
+                return Err(AppError::Validation(
+                    "a schema migration is in progress".into(),
+                ));
+            }
+            let _guard = maintenance.lock.try_lock().map_err(|_| {
+                AppError::Validation("a backup or restore is already in progress".into())
+            })?;
+            let _paused = fs_watch::pause_project_watch(&app);
+            vacuum_database(&db, progress_emitter(&app, job_id)).await
+        },
+        VACUUM_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "vacuum_project_db",
+        &db_path,
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Checkpoint and `VACUUM` a project database, calling `report` with the
+/// phase and elapsed milliseconds. Callers hold the maintenance lock and
+/// pause the project watch.
+pub async fn vacuum_database(
+    db_path: &Path,
+    report: impl Fn(&'static str, u64) + Clone + Send + Sync + 'static,
+) -> Result<VacuumResult, AppError> {
+    let started = Instant::now();
+    let emit = |phase: &'static str| report(phase, started.elapsed().as_millis() as u64);
+
+    let before_bytes = database_size(db_path);
+    let dir = db_path
+        .parent()
+        .ok_or_else(|| AppError::Validation("database path has no parent".into()))?;
+    let available = disk::disk_space(dir)?.free_bytes;
+    if available < before_bytes {
+        return Err(AppError::Io(format!(
+            "not enough free disk space: {} bytes available, {} needed",
+            available, before_bytes
+        )));
+    }
+
+    let mut conn = project_db::open_connection(db_path).await?;
+    emit("checkpoint");
+    project_db::checkpoint_truncate(&mut conn).await?;
+
+    emit("vacuum");
+    let heartbeat = {
+        let report = report.clone();
+        Heartbeat(tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(HEARTBEAT_MS)).await;
+                report("vacuum", started.elapsed().as_millis() as u64);
+            }
+        }))
+    };
+
+    let mut backoff = Duration::from_millis(BUSY_BACKOFF_MS);
+    let mut attempt = 0;
+    let result = loop {
+        match sqlx::query("VACUUM").execute(&mut conn).await {
+            Err(e) if is_busy(&e) && attempt < BUSY_RETRIES => {
+                attempt += 1;
+                log::warn!(
+                    "vacuum_project_db: database busy, retry {}/{}",
+                    attempt,
+                    BUSY_RETRIES
+                );
+                emit("retry");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            other => break other,
+        }
+    };
+    drop(heartbeat);
+    result?;
+
+    // VACUUM in WAL mode writes the rebuilt pages through the WAL.
+    project_db::checkpoint_truncate(&mut conn).await?;
+    drop(conn);
+
+    Ok(VacuumResult {
+        before_bytes,
+        after_bytes: database_size(db_path),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Emit `vacuum:progress` for `job_id` with a phase and the elapsed time.
+pub fn progress_emitter(
+    app: &AppHandle,
+    job_id: Option<String>,
+) -> impl Fn(&'static str, u64) + Clone + Send + Sync + 'static {
+    let app = app.clone();
+    move |phase, elapsed_ms| {
+        app.emit(
+            VACUUM_PROGRESS_EVENT,
+            VacuumProgress {
+                job_id: job_id.clone(),
+                phase,
+                elapsed_ms,
+            },
+        )
+        .ok();
+    }
+}
+
+/// Size of the database file plus its WAL.
+pub fn database_size(db_path: &Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path, Path::new(&wal)]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// SQLITE_BUSY or SQLITE_LOCKED, extended codes included.
+pub(crate) fn is_busy(error: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(e) = error else {
+        return false;
+    };
+    e.code()
+        .and_then(|code| code.parse::<i64>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 5 | 6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, BOARD};
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::ConnectOptions;
+    use std::sync::{Arc, Mutex};
+
+    /// A WAL-mode project in `dir` with about 2 MB of history snapshots.
+    async fn create_db(dir: &Path) -> std::path::PathBuf {
+        let seed = "
+            PRAGMA journal_mode = WAL;
+            WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 512)
+            INSERT INTO history (project_id, backlog_snapshot)
+                SELECT 1, hex(randomblob(2048)) FROM seq;
+        ";
+        test_support::create_db(
+            dir,
+            project_db::SUPPORTED_SCHEMA_VERSION,
+            &[BOARD, seed].concat(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn vacuum_gives_deleted_space_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path()).await;
+        let mut conn = project_db::open_connection(&path).await.unwrap();
+        sqlx::query("DELETE FROM history WHERE id > 16")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let report = {
+            let phases = phases.clone();
+            move |phase, _| phases.lock().unwrap().push(phase)
+        };
+        let result = vacuum_database(&path, report).await.unwrap();
+        assert!(result.before_bytes > 2_000_000, "{:?}", result);
+        // What is left is mostly the empty tables and indexes.
+        assert!(result.after_bytes * 4 < result.before_bytes, "{:?}", result);
+        assert_eq!(result.after_bytes, database_size(&path));
+        assert_eq!(*phases.lock().unwrap(), ["checkpoint", "vacuum"]);
+
+        let mut conn = project_db::open_read_only(&path).await.unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM history")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(rows, 16);
+    }
+
+    #[test]
+    fn size_includes_the_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backlog.db");
+        assert_eq!(database_size(&path), 0);
+        std::fs::write(&path, [0; 100]).unwrap();
+        std::fs::write(dir.path().join("backlog.db-wal"), [0; 20]).unwrap();
+        assert_eq!(database_size(&path), 120);
+    }
+
+    #[tokio::test]
+    async fn busy_errors_are_recognized() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_db(dir.path()).await;
+        let mut holder = project_db::open_connection(&path).await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut holder)
+            .await
+            .unwrap();
+
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&path)
+            .busy_timeout(Duration::ZERO)
+            .connect()
+            .await
+            .unwrap();
+        let err = sqlx::query("DELETE FROM history")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        assert!(is_busy(&err), "{:?}", err);
+        assert!(!is_busy(&sqlx::Error::RowNotFound));
+
+        let err = sqlx::query("SELECT * FROM missing")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        assert!(!is_busy(&err), "{:?}", err);
+    }
+}
@@ -0,0 +1,1064 @@
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::commands::{with_timeout, DB_TIMEOUT_MS, NETWORK_TIMEOUT_MS};
+use crate::error::AppError;
+use crate::secrets;
+use crate::telemetry::{self, ProxySettings, TelemetryState};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// DDL executed once at startup (from `init_telemetry_db`) to create the
+/// webhook endpoints and their delivery queue.
+pub const WEBHOOKS_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS webhooks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        url TEXT NOT NULL,
+        secret_ref TEXT NOT NULL,
+        events TEXT NOT NULL DEFAULT '[]',
+        enabled INTEGER NOT NULL DEFAULT 1,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS webhook_deliveries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        webhook_id INTEGER NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+        event_type TEXT NOT NULL,
+        body TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        retry_count INTEGER NOT NULL DEFAULT 0,
+        next_attempt_at INTEGER NOT NULL,
+        response_status INTEGER,
+        last_error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+        ON webhook_deliveries(status, next_attempt_at);
+    CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook
+        ON webhook_deliveries(webhook_id, id DESC);
+";
+
+/// Prefix of the keyring entry (see `secrets.rs`) holding a webhook's
+/// secret, followed by the webhook id. The entry name is kept in
+/// `webhooks.secret_ref`.
+const SECRET_NAME_PREFIX: &str = "webhook_secret_";
+
+/// Attempts before a delivery is given up and marked `failed`.
+const MAX_RETRY_COUNT: i64 = 5;
+/// Delay before the first retry, doubled after each further failure.
+const RETRY_BASE_SECS: i64 = 30;
+const MAX_RETRY_DELAY_SECS: i64 = 3_600;
+
+/// How often the flusher wakes up to retry due deliveries.
+const FLUSH_INTERVAL_SECS: u64 = 60;
+const FLUSH_BATCH_SIZE: i64 = 20;
+
+/// Finished deliveries kept per webhook for `list_webhook_deliveries`.
+const MAX_DELIVERIES_PER_WEBHOOK: i64 = 100;
+
+const MAX_PAYLOAD_BYTES: usize = 256 * 1024;
+const MAX_REDIRECTS: usize = 5;
+
+/// Dotted lowercase event names, e.g. `ticket.status_changed`.
+const EVENT_TYPE_PATTERN: &str = r"^[a-z][a-z0-9_]*(\.[a-z][a-z0-9_]*)*$";
+const MAX_EVENT_TYPE_CHARS: usize = 64;
+
+const EVENT_HEADER: &str = "X-Ticketflow-Event";
+const DELIVERY_HEADER: &str = "X-Ticketflow-Delivery";
+/// `sha256=<hex HMAC-SHA256 of the body keyed with the webhook secret>`.
+const SIGNATURE_HEADER: &str = "X-Ticketflow-Signature";
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_DELIVERED: &str = "delivered";
+const STATUS_FAILED: &str = "failed";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A webhook endpoint, as returned by `list_webhooks`. Its secret is never
+/// sent back to the frontend.
+#[derive(Debug, Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    /// Event types sent to the endpoint; empty for every event.
+    pub events: Vec<String>,
+    pub enabled: bool,
+    /// Unix milliseconds.
+    pub created_at: i64,
+}
+
+/// Settings of `create_webhook` and `update_webhook`.
+#[derive(Debug, Deserialize)]
+pub struct WebhookInput {
+    /// `http(s)://` endpoint the JSON body is POSTed to.
+    pub url: String,
+    /// Key of the HMAC-SHA256 body signature. Required on creation; `None`
+    /// keeps the current one on update.
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub enabled: bool,
+}
+
+/// A row of `webhook_deliveries`, as returned by `list_webhook_deliveries`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_type: String,
+    /// JSON body as signed and sent.
+    pub body: String,
+    /// `pending`, `delivered`, or `failed` once its retries are exhausted.
+    pub status: String,
+    pub retry_count: i64,
+    /// When a pending delivery is next attempted, in Unix milliseconds.
+    pub next_attempt_at: i64,
+    /// HTTP status of the last attempt, when the endpoint answered.
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Tauri managed state for webhook delivery.
+pub struct WebhookState {
+    /// Wakes the flusher when `fire_webhook` queues deliveries.
+    wake: tokio::sync::Notify,
+}
+
+/// A pending delivery with what is needed to send it.
+#[derive(sqlx::FromRow)]
+struct DueDelivery {
+    id: i64,
+    webhook_id: i64,
+    url: String,
+    secret_ref: String,
+    event_type: String,
+    body: String,
+    retry_count: i64,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Register `WebhookState` and spawn the flusher that sends queued
+/// deliveries, after moving secrets still stored in `webhooks` to the
+/// keyring. Called once from `lib.rs` during app setup, after
+/// `TelemetryState` is managed.
+pub fn init_webhooks(app: &AppHandle) {
+    app.manage(WebhookState {
+        wake: tokio::sync::Notify::new(),
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let pool = app.state::<TelemetryState>().pool();
+        if let Err(e) = migrate_plaintext_secrets(&pool).await {
+            log::error!("init_webhooks: cannot move secrets to the keyring: {}", e);
+        }
+        loop {
+            let pool = app.state::<TelemetryState>().pool();
+            deliver_due(&pool).await;
+            let state = app.state::<WebhookState>();
+            let _ = tokio::time::timeout(
+                Duration::from_secs(FLUSH_INTERVAL_SECS),
+                state.wake.notified(),
+            )
+            .await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Every webhook, oldest first.
+#[tauri::command]
+pub async fn list_webhooks(
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<Webhook>, AppError> {
+    with_timeout(
+        async {
+            let rows: Vec<(i64, String, String, bool, i64)> = sqlx::query_as(
+                "SELECT id, url, events, enabled, created_at FROM webhooks ORDER BY id",
+            )
+            .fetch_all(&state.pool())
+            .await?;
+            Ok(rows.into_iter().map(to_webhook).collect())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// Add a webhook. A secret is required: every body is signed with it. The
+/// secret goes to the OS keyring; the table only names its entry.
+#[tauri::command]
+pub async fn create_webhook(
+    webhook: WebhookInput,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Webhook, AppError> {
+    let url = webhook.url.trim().to_string();
+    let result = async {
+        validate_input(&webhook)?;
+        let secret = webhook
+            .secret
+            .as_deref()
+            .ok_or_else(|| AppError::Validation("a webhook needs a secret".into()))?;
+        let events = serde_json::to_string(&webhook.events).unwrap_or_default();
+        let created_at = now_ms();
+        with_timeout(
+            async {
+                let mut tx = state.pool().begin().await?;
+                let id = sqlx::query(
+                    "INSERT INTO webhooks (url, secret_ref, events, enabled, created_at)
+                     VALUES (?, '', ?, ?, ?)",
+                )
+                .bind(&url)
+                .bind(&events)
+                .bind(webhook.enabled)
+                .bind(created_at)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+                let secret_ref = secret_name(id);
+                sqlx::query("UPDATE webhooks SET secret_ref = ? WHERE id = ?")
+                    .bind(&secret_ref)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                secrets::set_secret(&secret_ref, Some(secret))?;
+                if let Err(e) = tx.commit().await {
+                    secrets::set_secret(&secret_ref, None).ok();
+                    return Err(e.into());
+                }
+                Ok(Webhook {
+                    id,
+                    url: url.clone(),
+                    events: webhook.events.clone(),
+                    enabled: webhook.enabled,
+                    created_at,
+                })
+            },
+            DB_TIMEOUT_MS,
+        )
+        .await
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "create_webhook",
+        &url_host(&url),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Replace the settings of webhook `id`, keeping its secret when
+/// `webhook.secret` is `None`.
+#[tauri::command]
+pub async fn update_webhook(
+    id: i64,
+    webhook: WebhookInput,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Webhook, AppError> {
+    let url = webhook.url.trim().to_string();
+    let result = async {
+        validate_input(&webhook)?;
+        let events = serde_json::to_string(&webhook.events).unwrap_or_default();
+        with_timeout(
+            async {
+                let row: Option<(i64, String, String, bool, i64, String)> = sqlx::query_as(
+                    "UPDATE webhooks
+                     SET url = ?, events = ?, enabled = ?
+                     WHERE id = ?
+                     RETURNING id, url, events, enabled, created_at, secret_ref",
+                )
+                .bind(&url)
+                .bind(&events)
+                .bind(webhook.enabled)
+                .bind(id)
+                .fetch_optional(&state.pool())
+                .await?;
+                let (id, url, events, enabled, created_at, secret_ref) =
+                    row.ok_or_else(|| AppError::Validation(format!("no webhook {}", id)))?;
+                if let Some(secret) = webhook.secret.as_deref() {
+                    secrets::set_secret(&secret_ref, Some(secret))?;
+                }
+                Ok(to_webhook((id, url, events, enabled, created_at)))
+            },
+            DB_TIMEOUT_MS,
+        )
+        .await
+    }
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "update_webhook",
+        &format!("{} {}", id, url_host(&url)),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Remove webhook `id`, its deliveries (pending ones included) and its
+/// keyring secret.
+#[tauri::command]
+pub async fn delete_webhook(
+    id: i64,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<(), AppError> {
+    let result = with_timeout(
+        async {
+            let secret_ref: Option<String> =
+                sqlx::query_scalar("DELETE FROM webhooks WHERE id = ? RETURNING secret_ref")
+                    .bind(id)
+                    .fetch_optional(&state.pool())
+                    .await?;
+            let Some(secret_ref) = secret_ref else {
+                return Err(AppError::Validation(format!("no webhook {}", id)));
+            };
+            if let Err(e) = secrets::set_secret(&secret_ref, None) {
+                log::warn!("delete_webhook: cannot remove secret of {}: {}", id, e);
+            }
+            Ok(())
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await;
+
+    audit::audit_log_command(
+        &state.pool(),
+        "delete_webhook",
+        &id.to_string(),
+        &audit::outcome_of(&result),
+    )
+    .await;
+    result
+}
+
+/// Queue `payload` for every enabled webhook listening to `event_type` and
+/// wake the flusher to send it. Returns the ids of the queued deliveries;
+/// their outcome shows in `list_webhook_deliveries`.
+#[tauri::command]
+pub async fn fire_webhook(
+    event_type: String,
+    payload: serde_json::Value,
+    state: tauri::State<'_, TelemetryState>,
+    webhooks: tauri::State<'_, WebhookState>,
+) -> Result<Vec<i64>, AppError> {
+    validate_event_type(&event_type)?;
+    let now = now_ms();
+    let body = serde_json::json!({
+        "event": event_type,
+        "timestamp": now,
+        "payload": payload,
+    })
+    .to_string();
+    if body.len() > MAX_PAYLOAD_BYTES {
+        return Err(AppError::Validation(format!(
+            "webhook payload exceeds {} bytes",
+            MAX_PAYLOAD_BYTES
+        )));
+    }
+
+    let ids = with_timeout(
+        async {
+            let listeners: Vec<(i64, String)> =
+                sqlx::query_as("SELECT id, events FROM webhooks WHERE enabled = 1")
+                    .fetch_all(&state.pool())
+                    .await?;
+
+            let mut tx = state.pool().begin().await?;
+            let mut ids = Vec::new();
+            for (webhook_id, events) in listeners {
+                let events: Vec<String> = serde_json::from_str(&events).unwrap_or_default();
+                if !events.is_empty() && !events.contains(&event_type) {
+                    continue;
+                }
+                let id = sqlx::query(
+                    "INSERT INTO webhook_deliveries
+                       (webhook_id, event_type, body, status, next_attempt_at, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(webhook_id)
+                .bind(&event_type)
+                .bind(&body)
+                .bind(STATUS_PENDING)
+                .bind(now)
+                .bind(now)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+                ids.push(id);
+            }
+            tx.commit().await?;
+            Ok(ids)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await?;
+
+    if !ids.is_empty() {
+        webhooks.wake.notify_one();
+    }
+    Ok(ids)
+}
+
+/// The deliveries of webhook `webhook_id`, newest first: the pending ones
+/// and the last 100 finished ones.
+#[tauri::command]
+pub async fn list_webhook_deliveries(
+    webhook_id: i64,
+    state: tauri::State<'_, TelemetryState>,
+) -> Result<Vec<WebhookDelivery>, AppError> {
+    with_timeout(
+        async {
+            let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+                "SELECT id, webhook_id, event_type, body, status, retry_count, next_attempt_at,
+                        response_status, last_error, created_at, updated_at
+                 FROM webhook_deliveries
+                 WHERE webhook_id = ?
+                 ORDER BY id DESC",
+            )
+            .bind(webhook_id)
+            .fetch_all(&state.pool())
+            .await?;
+            Ok(deliveries)
+        },
+        DB_TIMEOUT_MS,
+    )
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn validate_input(webhook: &WebhookInput) -> Result<(), AppError> {
+    let url = reqwest::Url::parse(webhook.url.trim())
+        .map_err(|e| AppError::Validation(format!("invalid webhook URL: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::Validation(
+            "webhook URL must be http:// or https://".into(),
+        ));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err(AppError::Validation("webhook URL has no host".into()));
+    }
+    if webhook.secret.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::Validation(
+            "webhook secret must not be empty".into(),
+        ));
+    }
+    for event_type in &webhook.events {
+        validate_event_type(event_type)?;
+    }
+    Ok(())
+}
+
+/// Check a dotted lowercase event type, e.g. `ticket.status_changed`.
+pub(crate) fn validate_event_type(event_type: &str) -> Result<(), AppError> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(EVENT_TYPE_PATTERN).unwrap());
+    if event_type.len() > MAX_EVENT_TYPE_CHARS || !pattern.is_match(event_type) {
+        return Err(AppError::Validation(format!(
+            "invalid event type: {:?}",
+            event_type
+        )));
+    }
+    Ok(())
+}
+
+/// Host of a webhook URL, for the audit trail: paths and queries of
+/// webhook URLs often carry a token.
+fn url_host(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "<invalid URL>".to_string())
+}
+
+fn to_webhook((id, url, events, enabled, created_at): (i64, String, String, bool, i64)) -> Webhook {
+    Webhook {
+        id,
+        url,
+        events: serde_json::from_str(&events).unwrap_or_default(),
+        enabled,
+        created_at,
+    }
+}
+
+/// Send every due delivery of an enabled webhook, a batch at a time.
+/// Failures are rescheduled, so each pass ends.
+async fn deliver_due(pool: &SqlitePool) {
+    let mut client = None;
+    loop {
+        let due = sqlx::query_as::<_, DueDelivery>(
+            "SELECT d.id, d.webhook_id, w.url, w.secret_ref, d.event_type, d.body, d.retry_count
+             FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+             WHERE d.status = ? AND d.next_attempt_at <= ? AND w.enabled = 1
+             ORDER BY d.next_attempt_at, d.id
+             LIMIT ?",
+        )
+        .bind(STATUS_PENDING)
+        .bind(now_ms())
+        .bind(FLUSH_BATCH_SIZE)
+        .fetch_all(pool)
+        .await;
+        let due = match due {
+            Ok(due) if due.is_empty() => return,
+            Ok(due) => due,
+            Err(e) => {
+                log::error!("deliver_due: fetch failed: {}", e);
+                return;
+            }
+        };
+
+        if client.is_none() {
+            let proxy = telemetry::load_proxy_settings(pool).await;
+            match webhook_client(&proxy) {
+                Ok(built) => client = Some(built),
+                Err(e) => {
+                    log::error!("deliver_due: cannot build the HTTP client: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(client) = client.as_ref() else {
+            return;
+        };
+
+        for delivery in &due {
+            let outcome = match secrets::get_secret(&delivery.secret_ref) {
+                Ok(Some(secret)) => post_delivery(client, delivery, &secret).await,
+                Ok(None) => Err(format!("no keyring secret {}", delivery.secret_ref)),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = record_attempt(pool, delivery, outcome).await {
+                log::error!(
+                    "deliver_due: recording delivery {} failed: {}",
+                    delivery.id,
+                    e
+                );
+            }
+        }
+        if (due.len() as i64) < FLUSH_BATCH_SIZE {
+            return;
+        }
+    }
+}
+
+/// Client for webhook endpoints: the shared proxy settings, with redirects
+/// followed only to https URLs so a signed body is never sent in the clear
+/// on a server's say-so.
+fn webhook_client(proxy: &ProxySettings) -> Result<reqwest::Client, AppError> {
+    let policy = reqwest::redirect::Policy::custom(|attempt| {
+        if attempt.url().scheme() != "https" {
+            let message = format!("refused redirect to non-https URL {}", attempt.url());
+            attempt.error(message)
+        } else if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else {
+            attempt.follow()
+        }
+    });
+    Ok(telemetry::http_client_builder(proxy)
+        .redirect(policy)
+        .timeout(Duration::from_millis(NETWORK_TIMEOUT_MS))
+        .build()?)
+}
+
+/// POST a delivery's body, signed with its webhook's `secret`. `Ok`
+/// carries the response status, whether or not it is a success.
+async fn post_delivery(
+    client: &reqwest::Client,
+    delivery: &DueDelivery,
+    secret: &str,
+) -> Result<reqwest::StatusCode, String> {
+    let response = client
+        .post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(SIGNATURE_HEADER, sign(secret, &delivery.body))
+        .body(delivery.body.clone())
+        .send()
+        .await
+        .map_err(|e| error_chain(&e))?;
+    Ok(response.status())
+}
+
+/// Mark a delivery delivered, or count the failed attempt: it is retried
+/// with exponential backoff until MAX_RETRY_COUNT, then marked failed.
+async fn record_attempt(
+    pool: &SqlitePool,
+    delivery: &DueDelivery,
+    outcome: Result<reqwest::StatusCode, String>,
+) -> Result<(), sqlx::Error> {
+    let now = now_ms();
+    let (response_status, error) = match &outcome {
+        Ok(status) if status.is_success() => (Some(status.as_u16()), None),
+        Ok(status) => (Some(status.as_u16()), Some(format!("HTTP {}", status))),
+        Err(e) => (None, Some(e.clone())),
+    };
+
+    let (status, retry_count, next_attempt_at) = match &error {
+        None => (STATUS_DELIVERED, delivery.retry_count, now),
+        Some(e) => {
+            let retry_count = delivery.retry_count + 1;
+            log::warn!(
+                "webhook delivery {} to webhook {} failed (attempt {}): {}",
+                delivery.id,
+                delivery.webhook_id,
+                retry_count,
+                e
+            );
+            if retry_count >= MAX_RETRY_COUNT {
+                (STATUS_FAILED, retry_count, now)
+            } else {
+                (
+                    STATUS_PENDING,
+                    retry_count,
+                    now + retry_delay_ms(retry_count),
+                )
+            }
+        }
+    };
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = ?, retry_count = ?, next_attempt_at = ?, response_status = ?,
+             last_error = ?, updated_at = ?
+         WHERE id = ?",
+    )
+    .bind(status)
+    .bind(retry_count)
+    .bind(next_attempt_at)
+    .bind(response_status)
+    .bind(&error)
+    .bind(now)
+    .bind(delivery.id)
+    .execute(&mut *tx)
+    .await?;
+    if status != STATUS_PENDING {
+        sqlx::query(
+            "DELETE FROM webhook_deliveries
+             WHERE webhook_id = ? AND status != ? AND id NOT IN (
+               SELECT id FROM webhook_deliveries
+               WHERE webhook_id = ? AND status != ?
+               ORDER BY id DESC LIMIT ?
+             )",
+        )
+        .bind(delivery.webhook_id)
+        .bind(STATUS_PENDING)
+        .bind(delivery.webhook_id)
+        .bind(STATUS_PENDING)
+        .bind(MAX_DELIVERIES_PER_WEBHOOK)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Move the secrets of a `webhooks` table created before they were kept in
+/// the keyring: each goes to its keyring entry, then the `secret` column is
+/// dropped.
+async fn migrate_plaintext_secrets(pool: &SqlitePool) -> Result<(), AppError> {
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('webhooks')")
+        .fetch_all(pool)
+        .await?;
+    if !columns.iter().any(|column| column == "secret") {
+        return Ok(());
+    }
+    if !columns.iter().any(|column| column == "secret_ref") {
+        sqlx::query("ALTER TABLE webhooks ADD COLUMN secret_ref TEXT NOT NULL DEFAULT ''")
+            .execute(pool)
+            .await?;
+    }
+
+    let rows: Vec<(i64, String)> = sqlx::query_as("SELECT id, secret FROM webhooks")
+        .fetch_all(pool)
+        .await?;
+    for (id, secret) in rows {
+        let secret_ref = secret_name(id);
+        secrets::set_secret(&secret_ref, Some(&secret))?;
+        sqlx::query("UPDATE webhooks SET secret_ref = ? WHERE id = ?")
+            .bind(&secret_ref)
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+    sqlx::query("ALTER TABLE webhooks DROP COLUMN secret")
+        .execute(pool)
+        .await?;
+    log::info!("migrate_plaintext_secrets: webhook secrets moved to the keyring");
+    Ok(())
+}
+
+/// Keyring entry name of webhook `id`'s secret.
+fn secret_name(id: i64) -> String {
+    format!("{}{}", SECRET_NAME_PREFIX, id)
+}
+
+/// Delay before the attempt following the `retry_count`th failure.
+fn retry_delay_ms(retry_count: i64) -> i64 {
+    let exponent = (retry_count - 1).clamp(0, 16) as u32;
+    RETRY_BASE_SECS
+        .saturating_mul(1 << exponent)
+        .min(MAX_RETRY_DELAY_SECS)
+        * 1000
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` keyed with `secret`.
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body.as_bytes());
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+/// `err` and its causes, e.g. the redirect refusal behind a reqwest error.
+fn error_chain(err: &reqwest::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::tests::{json_response, serve};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(WEBHOOKS_SCHEMA).execute(&pool).await.unwrap();
+        pool
+    }
+
+    /// Add webhook `url` with one pending delivery due now; returns it.
+    async fn queue(pool: &SqlitePool, url: &str, enabled: bool) -> DueDelivery {
+        let webhook_id = sqlx::query(
+            "INSERT INTO webhooks (url, secret_ref, enabled, created_at) VALUES (?, '', ?, 0)",
+        )
+        .bind(url)
+        .bind(enabled)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        let secret_ref = secret_name(webhook_id);
+        sqlx::query("UPDATE webhooks SET secret_ref = ? WHERE id = ?")
+            .bind(&secret_ref)
+            .bind(webhook_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        let id = sqlx::query(
+            "INSERT INTO webhook_deliveries
+               (webhook_id, event_type, body, next_attempt_at, created_at, updated_at)
+             VALUES (?, 'ticket.created', '{\"n\":1}', 0, 0, 0)",
+        )
+        .bind(webhook_id)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        DueDelivery {
+            id,
+            webhook_id,
+            url: url.into(),
+            secret_ref,
+            event_type: "ticket.created".into(),
+            body: "{\"n\":1}".into(),
+            retry_count: 0,
+        }
+    }
+
+    async fn delivery(
+        pool: &SqlitePool,
+        id: i64,
+    ) -> (String, i64, i64, Option<i64>, Option<String>) {
+        sqlx::query_as(
+            "SELECT status, retry_count, next_attempt_at, response_status, last_error
+             FROM webhook_deliveries WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    fn input(url: &str, secret: Option<&str>, events: &[&str]) -> WebhookInput {
+        WebhookInput {
+            url: url.into(),
+            secret: secret.map(Into::into),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn inputs_are_validated() {
+        assert!(validate_input(&input(" https://hooks.example.com/x ", Some("s"), &[])).is_ok());
+        assert!(validate_input(&input("http://localhost:8080", None, &["ticket.created"])).is_ok());
+        for webhook in [
+            input("ftp://hooks.example.com", Some("s"), &[]),
+            input("not a url", Some("s"), &[]),
+            input("https://hooks.example.com", Some(""), &[]),
+            input("https://hooks.example.com", Some("s"), &["Ticket.Created"]),
+        ] {
+            assert!(validate_input(&webhook).is_err(), "{:?}", webhook);
+        }
+    }
+
+    #[test]
+    fn event_types_are_dotted_lowercase_names() {
+        for ok in ["ticket", "ticket.status_changed", "a1.b_2.c"] {
+            assert!(validate_event_type(ok).is_ok(), "{}", ok);
+        }
+        let long = format!("a{}", ".b".repeat(40));
+        for bad in [
+            "",
+            "Ticket",
+            "ticket..created",
+            "ticket.",
+            "1ticket",
+            "a b",
+            long.as_str(),
+        ] {
+            assert!(validate_event_type(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn bodies_are_signed_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_an_hour() {
+        assert_eq!(retry_delay_ms(1), 30_000);
+        assert_eq!(retry_delay_ms(2), 60_000);
+        assert_eq!(retry_delay_ms(4), 240_000);
+        assert_eq!(retry_delay_ms(9), 3_600_000);
+        assert_eq!(retry_delay_ms(100), 3_600_000);
+    }
+
+    #[test]
+    fn audit_trail_keeps_the_host_only() {
+        assert_eq!(
+            url_host("https://hooks.example.com/T0/secret?token=x"),
+            "hooks.example.com"
+        );
+        assert_eq!(url_host("nope"), "<invalid URL>");
+    }
+
+    #[tokio::test]
+    async fn failed_attempts_back_off_then_give_up() {
+        let pool = pool().await;
+        let mut due = queue(&pool, "https://hooks.example.com", true).await;
+
+        let before = now_ms();
+        record_attempt(&pool, &due, Ok(reqwest::StatusCode::BAD_GATEWAY))
+            .await
+            .unwrap();
+        let (status, retry_count, next_attempt_at, response_status, error) =
+            delivery(&pool, due.id).await;
+        assert_eq!((status.as_str(), retry_count), (STATUS_PENDING, 1));
+        assert!(next_attempt_at >= before + 30_000);
+        assert_eq!(response_status, Some(502));
+        assert_eq!(error.as_deref(), Some("HTTP 502 Bad Gateway"));
+
+        due.retry_count = MAX_RETRY_COUNT - 1;
+        record_attempt(&pool, &due, Err("connection refused".into()))
+            .await
+            .unwrap();
+        let (status, retry_count, _, response_status, error) = delivery(&pool, due.id).await;
+        assert_eq!(
+            (status.as_str(), retry_count),
+            (STATUS_FAILED, MAX_RETRY_COUNT)
+        );
+        assert_eq!(response_status, None);
+        assert_eq!(error.as_deref(), Some("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn finished_deliveries_are_pruned_per_webhook() {
+        let pool = pool().await;
+        let due = queue(&pool, "https://hooks.example.com", true).await;
+        sqlx::raw_sql(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 120)
+             INSERT INTO webhook_deliveries
+               (webhook_id, event_type, body, status, next_attempt_at, created_at, updated_at)
+             SELECT 1, 'ticket.created', '{}', 'delivered', 0, 0, 0 FROM n;
+             INSERT INTO webhook_deliveries
+               (webhook_id, event_type, body, next_attempt_at, created_at, updated_at)
+             VALUES (1, 'ticket.created', '{}', 0, 0, 0);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        record_attempt(&pool, &due, Ok(reqwest::StatusCode::NO_CONTENT))
+            .await
+            .unwrap();
+        let counts: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT status, COUNT(*) FROM webhook_deliveries GROUP BY status ORDER BY status",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            counts,
+            [
+                (STATUS_DELIVERED.to_string(), MAX_DELIVERIES_PER_WEBHOOK),
+                (STATUS_PENDING.to_string(), 1),
+            ]
+        );
+        // The newest finished ones are kept: ids 2 to 121 were added after
+        // the recorded delivery (1), and 122 is pending.
+        let oldest: i64 =
+            sqlx::query_scalar("SELECT MIN(id) FROM webhook_deliveries WHERE status = ?")
+                .bind(STATUS_DELIVERED)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(oldest, 22);
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_posted_signed() {
+        let (server, requests) =
+            serve(vec![json_response("500 Internal Server Error", "", "{}")]).await;
+        let pool = pool().await;
+        let due = queue(&pool, &format!("{}/hook", server), true).await;
+        let client = webhook_client(&ProxySettings::default()).unwrap();
+
+        let status = post_delivery(&client, &due, "s3cret").await.unwrap();
+        assert_eq!(status, reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+
+        let request = requests.lock().unwrap()[0].to_ascii_lowercase();
+        assert!(request.starts_with("post /hook "));
+        assert!(request.contains("x-ticketflow-event: ticket.created"));
+        assert!(request.contains(&format!("x-ticketflow-delivery: {}", due.id)));
+        let signature = format!("x-ticketflow-signature: {}", sign("s3cret", &due.body));
+        assert!(request.contains(&signature));
+        assert!(request.ends_with("{\"n\":1}"));
+    }
+
+    #[tokio::test]
+    async fn redirects_to_plain_http_are_refused() {
+        let (server, _) = serve(vec![json_response(
+            "302 Found",
+            "location: http://elsewhere.example.com/\r\n",
+            "{}",
+        )])
+        .await;
+        let pool = pool().await;
+        let due = queue(&pool, &server, true).await;
+        let client = webhook_client(&ProxySettings::default()).unwrap();
+
+        let err = post_delivery(&client, &due, "s3cret").await.unwrap_err();
+        assert!(err.contains("refused redirect to non-https URL"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn due_deliveries_of_enabled_webhooks_are_attempted() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let pool = pool().await;
+        let enabled = queue(&pool, "https://hooks.example.com", true).await;
+        let disabled = queue(&pool, "https://other.example.com", false).await;
+
+        deliver_due(&pool).await;
+        let (status, retry_count, _, _, error) = delivery(&pool, enabled.id).await;
+        assert_eq!((status.as_str(), retry_count), (STATUS_PENDING, 1));
+        assert_eq!(
+            error,
+            Some(format!("no keyring secret {}", enabled.secret_ref))
+        );
+        assert_eq!(delivery(&pool, disabled.id).await.1, 0);
+    }
+
+    #[tokio::test]
+    async fn plaintext_secrets_move_to_the_keyring() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "CREATE TABLE webhooks (
+               id INTEGER PRIMARY KEY, url TEXT, secret TEXT, events TEXT, enabled INTEGER,
+               created_at INTEGER
+             );
+             INSERT INTO webhooks VALUES (3, 'https://hooks.example.com', 's', '[]', 1, 0);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        migrate_plaintext_secrets(&pool).await.unwrap();
+        let columns: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info('webhooks')")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert!(!columns.contains(&"secret".to_string()));
+        let secret_ref: String = sqlx::query_scalar("SELECT secret_ref FROM webhooks")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(secret_ref, "webhook_secret_3");
+
+        // Nothing left to move.
+        migrate_plaintext_secrets(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,1283 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::image::Image;
+use tauri::{
+    AppHandle, Emitter, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewUrl,
+    WebviewWindow, WebviewWindowBuilder,
+};
+
+use crate::error::AppError;
+use crate::kv;
+use crate::telemetry::TelemetryState;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+pub const MAIN_WINDOW: &str = "main";
+
+/// `kv_store` key holding the last known main window geometry.
+const WINDOW_STATE_KV: &str = "window_state";
+
+/// CLI flag asking the app to open a project (`--project <path>`).
+const PROJECT_FLAG: &str = "--project";
+
+const MAX_TITLE_CHARS: usize = 128;
+
+/// Windows open at once, the main window included.
+const MAX_WINDOWS: usize = 5;
+
+/// Labels `create_window` accepts. The `secondary-windows` capability
+/// grants its permissions to these labels only, so keep both lists in sync.
+const SECONDARY_WINDOWS: [&str; 4] = ["window-1", "window-2", "window-3", "window-4"];
+
+/// Bounds of a secondary window's width and height, in logical pixels.
+const MIN_WINDOW_SIZE: f64 = 200.0;
+const MAX_WINDOW_SIZE: f64 = 8192.0;
+
+/// Events emitted to the main window with the label of a secondary window.
+const WINDOW_CREATED_EVENT: &str = "window:created";
+const WINDOW_CLOSED_EVENT: &str = "window:closed";
+
+/// Event namespaces of the backend that windows may not emit themselves
+/// (the app lock in particular).
+const RESERVED_EVENT_PREFIXES: [&str; 3] = ["tauri:", "tray:", "app:"];
+
+const MAX_EVENT_CHARS: usize = 64;
+
+/// Largest PNG accepted by `set_window_icon`.
+const MAX_ICON_BYTES: usize = 1024 * 1024;
+
+/// Largest width or height of a window icon source, in pixels.
+const MAX_ICON_SOURCE_PIXELS: u32 = 1024;
+
+/// Window icon edge on standard and high-density displays.
+const ICON_SIZE: u32 = 32;
+const ICON_SIZE_HIDPI: u32 = 64;
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Persisted geometry (physical pixels) and title of the main window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    /// Absent in states saved before titles were persisted.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub vibrancy: VibrancyEffect,
+}
+
+/// Translucent background material of a window. Each platform maps it to
+/// its closest effect; Linux has none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VibrancyEffect {
+    #[default]
+    None,
+    Blur,
+    Acrylic,
+    Mica,
+    Sidebar,
+}
+
+/// A connected display, in physical pixels; mirrors `tauri::Monitor`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub position_x: i32,
+    pub position_y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+/// Tauri managed state for the main window lifecycle.
+pub struct MainWindowState {
+    /// Last captured geometry, used when the window has to be rebuilt.
+    pub last_state: Mutex<Option<WindowState>>,
+    /// Project requested via `--project`, until the frontend consumes it.
+    pub pending_project: Mutex<Option<String>>,
+    /// Effect applied to the main window, saved with its geometry.
+    pub vibrancy: Mutex<VibrancyEffect>,
+}
+
+// ---------------------------------------------------------------------------
+// Initialization
+// ---------------------------------------------------------------------------
+
+/// Load the persisted geometry, apply it to the main window and register
+/// `MainWindowState`. `args` are this process's CLI arguments.
+/// Called once from `lib.rs` during app setup, after `init_telemetry_db`.
+pub async fn init_main_window(app: &AppHandle, args: &[String]) {
+    let pool = &app.state::<TelemetryState>().pool();
+    let last_state = match kv::get(pool, WINDOW_STATE_KV).await {
+        Ok(Some(json)) => serde_json::from_str::<WindowState>(&json).ok(),
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("init_main_window: cannot read window state: {}", e);
+            None
+        }
+    };
+
+    if let (Some(window), Some(state)) = (app.get_webview_window(MAIN_WINDOW), &last_state) {
+        restore_window_state(&window, state);
+    }
+
+    app.manage(MainWindowState {
+        vibrancy: Mutex::new(
+            last_state
+                .as_ref()
+                .map(|state| state.vibrancy)
+                .unwrap_or_default(),
+        ),
+        last_state: Mutex::new(last_state),
+        pending_project: Mutex::new(project_arg(args)),
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri command
+// ---------------------------------------------------------------------------
+
+/// Consume the project path requested on the command line, if any. The
+/// frontend calls this on startup and whenever `cli:open-project` fires.
+#[tauri::command]
+pub fn take_cli_navigation(
+    state: tauri::State<'_, MainWindowState>,
+) -> Result<Option<String>, AppError> {
+    Ok(state.pending_project.lock().unwrap().take())
+}
+
+/// List the connected monitors with their geometry and scale factor.
+#[tauri::command]
+pub fn get_screen_info(app: AppHandle) -> Result<Vec<MonitorInfo>, AppError> {
+    let mut monitors: Vec<MonitorInfo> = app
+        .available_monitors()
+        .map_err(monitor_error)?
+        .iter()
+        .map(monitor_info)
+        .collect();
+    let primary = app.primary_monitor().map_err(monitor_error)?;
+    flag_primary(&mut monitors, primary.as_ref().map(monitor_info).as_ref());
+    Ok(monitors)
+}
+
+/// Return the monitor containing the center of `window` (the calling
+/// window). Falls back to the monitor the OS reports for the window, then to
+/// the primary monitor, when the center is off-screen.
+#[tauri::command]
+pub fn get_monitor_at_window_position(window: WebviewWindow) -> Result<MonitorInfo, AppError> {
+    let primary = window.primary_monitor().map_err(monitor_error)?;
+    let containing = monitor_at_center(&window).map_err(monitor_error)?;
+    let monitor = match containing {
+        Some(monitor) => Some(monitor),
+        None => window.current_monitor().map_err(monitor_error)?,
+    }
+    .or_else(|| primary.clone())
+    .ok_or_else(|| AppError::Io("no monitor found".into()))?;
+
+    let mut info = monitor_info(&monitor);
+    info.is_primary =
+        primary.is_some_and(|primary| is_same_monitor(&monitor_info(&primary), &info));
+    Ok(info)
+}
+
+/// Move `window` (the calling window) to the center of its current monitor.
+#[tauri::command]
+pub fn window_center(window: WebviewWindow) -> Result<(), AppError> {
+    window.center().map_err(monitor_error)
+}
+
+/// Center `window` (the calling window) when its center is on none of the
+/// connected monitors, e.g. after the monitor it was last on was removed.
+#[tauri::command]
+pub fn window_ensure_on_screen(window: WebviewWindow) -> Result<(), AppError> {
+    ensure_on_screen(&window).map_err(monitor_error)
+}
+
+/// Set the title of `window` (the calling window), e.g. to the name of the
+/// open project. The main window's title is restored at the next startup.
+#[tauri::command]
+pub fn set_window_title(title: String, window: WebviewWindow) -> Result<(), AppError> {
+    validate_title(&title)?;
+    window
+        .set_title(&title)
+        .map_err(|e| AppError::Io(format!("cannot set window title: {}", e)))?;
+
+    if window.label() == MAIN_WINDOW {
+        let app = window.app_handle();
+        let state = app.try_state::<MainWindowState>().and_then(|main_state| {
+            let mut last_state = main_state.last_state.lock().unwrap();
+            let state = last_state.as_mut()?;
+            state.title = Some(title);
+            Some(state.clone())
+        });
+        // Without a saved geometry yet, the title is persisted with it when
+        // the window is first hidden.
+        if let Some(state) = state {
+            persist_window_state(app, state);
+        }
+    }
+    Ok(())
+}
+
+/// Apply a translucent background `effect` to `window` (the calling
+/// window). The main window's effect is restored at the next startup. The
+/// effect shows only where the window and page background are transparent.
+/// On Linux this only logs a warning.
+#[tauri::command]
+pub fn set_window_vibrancy(effect: VibrancyEffect, window: WebviewWindow) -> Result<(), AppError> {
+    apply_vibrancy(&window, effect)?;
+
+    if window.label() == MAIN_WINDOW {
+        let app = window.app_handle();
+        let state = app.try_state::<MainWindowState>().and_then(|main_state| {
+            *main_state.vibrancy.lock().unwrap() = effect;
+            let mut last_state = main_state.last_state.lock().unwrap();
+            let state = last_state.as_mut()?;
+            state.vibrancy = effect;
+            Some(state.clone())
+        });
+        // Without a saved geometry yet, the effect is persisted with it when
+        // the window is first hidden.
+        if let Some(state) = state {
+            persist_window_state(app, state);
+        }
+    }
+    Ok(())
+}
+
+/// Return the effect of the main window: `none`, `blur`, `acrylic`, `mica`
+/// or `sidebar`.
+#[tauri::command]
+pub fn get_window_vibrancy(state: tauri::State<'_, MainWindowState>) -> Result<String, AppError> {
+    let effect = *state.vibrancy.lock().unwrap();
+    serde_json::to_value(effect)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .ok_or_else(|| AppError::Io("cannot encode the vibrancy effect".into()))
+}
+
+/// Return the title of `window` (the calling window).
+#[tauri::command]
+pub fn get_window_title(window: WebviewWindow) -> Result<String, AppError> {
+    window
+        .title()
+        .map_err(|e| AppError::Io(format!("cannot read window title: {}", e)))
+}
+
+/// Open a secondary window, e.g. a ticket detail panel. `label` is one of
+/// `window-1` to `window-4`; `url` is a path of the app (`/ticket/BUG-1`)
+/// or a full URL on the app's origin. At most 5 windows, the main window
+/// included, may be open. Emits `window:created` to the main window.
+#[tauri::command]
+pub async fn create_window(
+    label: String,
+    url: String,
+    width: f64,
+    height: f64,
+    title: String,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    validate_label(&label)?;
+    validate_title(&title)?;
+    validate_size(width, height)?;
+    let path = app_path(&app, &url)?;
+    let open: Vec<String> = app.webview_windows().into_keys().collect();
+    check_window_slot(&open, &label)?;
+
+    WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(path.into()))
+        .title(&title)
+        .inner_size(width, height)
+        .build()
+        .map_err(|e| AppError::Io(format!("cannot create window: {}", e)))?;
+    app.emit_to(MAIN_WINDOW, WINDOW_CREATED_EVENT, &label).ok();
+    Ok(())
+}
+
+/// Close a secondary window opened by `create_window`. `window:closed` is
+/// emitted once it is destroyed.
+#[tauri::command]
+pub fn close_window(label: String, app: AppHandle) -> Result<(), AppError> {
+    if label == MAIN_WINDOW {
+        return Err(AppError::Validation(
+            "the main window cannot be closed".into(),
+        ));
+    }
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| AppError::Validation(format!("no window labelled {}", label)))?;
+    window
+        .destroy()
+        .map_err(|e| AppError::Io(format!("cannot close window: {}", e)))
+}
+
+/// Labels of the open windows, the main window included, sorted.
+#[tauri::command]
+pub fn list_windows(app: AppHandle) -> Result<Vec<String>, AppError> {
+    let mut labels: Vec<String> = app.webview_windows().into_keys().collect();
+    labels.sort();
+    Ok(labels)
+}
+
+/// Emit `event` with `payload` to the window labelled `target_label` only,
+/// e.g. to tell another ticket panel that a ticket changed. Event names are
+/// letters, digits, `_`, `:` and `-`; backend namespaces (`tauri:`, `tray:`,
+/// `app:`) are refused.
+#[tauri::command]
+pub fn emit_to_window(
+    target_label: String,
+    event: String,
+    payload: serde_json::Value,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    validate_event_name(&event)?;
+    if app.get_webview_window(&target_label).is_none() {
+        return Err(AppError::Validation(format!(
+            "no window labelled {}",
+            target_label
+        )));
+    }
+    app.emit_to(target_label.as_str(), &event, payload)
+        .map_err(|e| AppError::Io(format!("cannot emit event: {}", e)))
+}
+
+/// Emit `event` with `payload` to every window. Same naming rules as
+/// `emit_to_window`.
+#[tauri::command]
+pub fn broadcast_event(
+    event: String,
+    payload: serde_json::Value,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    validate_event_name(&event)?;
+    app.emit(&event, payload)
+        .map_err(|e| AppError::Io(format!("cannot emit event: {}", e)))
+}
+
+/// Replace the icon of `window` (the calling window) with a PNG of at most
+/// 1 MB, e.g. to tell projects apart in the taskbar. The image is scaled to
+/// 32x32, or 64x64 on high-density displays, keeping its aspect ratio.
+/// Window icons are not supported on macOS, where this does nothing.
+#[tauri::command]
+pub fn set_window_icon(icon_data: Vec<u8>, window: WebviewWindow) -> Result<(), AppError> {
+    let size = if window.scale_factor().unwrap_or(1.0) > 1.0 {
+        ICON_SIZE_HIDPI
+    } else {
+        ICON_SIZE
+    };
+    let icon = Image::new_owned(icon_pixels(&icon_data, size)?, size, size);
+    window
+        .set_icon(icon)
+        .map_err(|e| AppError::Io(format!("cannot set window icon: {}", e)))
+}
+
+/// Restore the app icon on `window` (the calling window).
+#[tauri::command]
+pub fn reset_window_icon(window: WebviewWindow) -> Result<(), AppError> {
+    let icon = window
+        .app_handle()
+        .default_window_icon()
+        .cloned()
+        .ok_or_else(|| AppError::Io("the app has no default window icon".into()))?;
+    window
+        .set_icon(icon)
+        .map_err(|e| AppError::Io(format!("cannot set window icon: {}", e)))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// The monitor containing the center of `window`, if any.
+fn monitor_at_center(window: &WebviewWindow) -> tauri::Result<Option<Monitor>> {
+    let center = center_of(window.outer_position()?, window.outer_size()?);
+    Ok(window
+        .available_monitors()?
+        .into_iter()
+        .find(|monitor| contains(*monitor.position(), *monitor.size(), center)))
+}
+
+/// Center `window` on the primary monitor when its center is on no monitor.
+/// `center()` is not used here: it centers on the window's current monitor,
+/// which an off-screen window does not have.
+fn ensure_on_screen(window: &WebviewWindow) -> tauri::Result<()> {
+    if monitor_at_center(window)?.is_some() {
+        return Ok(());
+    }
+    let monitor = match window.primary_monitor()? {
+        Some(monitor) => monitor,
+        None => match window.available_monitors()?.into_iter().next() {
+            Some(monitor) => monitor,
+            None => return Ok(()),
+        },
+    };
+    let position = centered_position(*monitor.position(), *monitor.size(), window.outer_size()?);
+    log::info!(
+        "ensure_on_screen: window is off-screen, moving it to {},{}",
+        position.x,
+        position.y
+    );
+    window.set_position(position)
+}
+
+/// Center of a window at `position` with outer size `size`.
+fn center_of(position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> (i32, i32) {
+    (
+        position.x + (size.width / 2) as i32,
+        position.y + (size.height / 2) as i32,
+    )
+}
+
+/// Whether `point` is on the monitor at `origin` of size `area`.
+fn contains(origin: PhysicalPosition<i32>, area: PhysicalSize<u32>, point: (i32, i32)) -> bool {
+    (origin.x..origin.x + area.width as i32).contains(&point.0)
+        && (origin.y..origin.y + area.height as i32).contains(&point.1)
+}
+
+/// Position centering a window of outer size `size` on the monitor at
+/// `origin` of size `area`. A window larger than the monitor is aligned to
+/// its top-left corner.
+fn centered_position(
+    origin: PhysicalPosition<i32>,
+    area: PhysicalSize<u32>,
+    size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    PhysicalPosition::new(
+        origin.x + (area.width.saturating_sub(size.width) / 2) as i32,
+        origin.y + (area.height.saturating_sub(size.height) / 2) as i32,
+    )
+}
+
+/// `monitor` as a `MonitorInfo`, not yet flagged as primary.
+fn monitor_info(monitor: &Monitor) -> MonitorInfo {
+    let (position, size) = (monitor.position(), monitor.size());
+    MonitorInfo {
+        name: monitor.name().cloned().unwrap_or_default(),
+        position_x: position.x,
+        position_y: position.y,
+        width: size.width,
+        height: size.height,
+        scale_factor: monitor.scale_factor(),
+        is_primary: false,
+    }
+}
+
+/// Monitors carry no id; the primary one is recognized by name and origin.
+fn is_same_monitor(a: &MonitorInfo, b: &MonitorInfo) -> bool {
+    a.name == b.name && a.position_x == b.position_x && a.position_y == b.position_y
+}
+
+/// Flag the monitor matching `primary`. Mirrored displays share a name and
+/// an origin, so only the first match is flagged.
+fn flag_primary(monitors: &mut [MonitorInfo], primary: Option<&MonitorInfo>) {
+    let Some(primary) = primary else {
+        return;
+    };
+    if let Some(monitor) = monitors
+        .iter_mut()
+        .find(|monitor| is_same_monitor(monitor, primary))
+    {
+        monitor.is_primary = true;
+    }
+}
+
+/// A title of 1 to `MAX_TITLE_CHARS` characters on a single line. Any other
+/// Unicode (accents, emoji) is allowed.
+fn validate_title(title: &str) -> Result<(), AppError> {
+    if title.trim().is_empty() {
+        return Err(AppError::Validation("window title is empty".into()));
+    }
+    if title.chars().count() > MAX_TITLE_CHARS {
+        return Err(AppError::Validation(format!(
+            "window title is longer than {} characters",
+            MAX_TITLE_CHARS
+        )));
+    }
+    if title.chars().any(char::is_control) {
+        return Err(AppError::Validation(
+            "window title contains control characters".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// One of the `SECONDARY_WINDOWS` labels.
+fn validate_label(label: &str) -> Result<(), AppError> {
+    if !SECONDARY_WINDOWS.contains(&label) {
+        return Err(AppError::Validation(format!(
+            "window label must be one of {}",
+            SECONDARY_WINDOWS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn validate_size(width: f64, height: f64) -> Result<(), AppError> {
+    let size = MIN_WINDOW_SIZE..=MAX_WINDOW_SIZE;
+    if !size.contains(&width) || !size.contains(&height) {
+        return Err(AppError::Validation(format!(
+            "window size must be {} to {} pixels",
+            MIN_WINDOW_SIZE, MAX_WINDOW_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Whether a window labelled `label` may open next to the `open` ones.
+fn check_window_slot(open: &[String], label: &str) -> Result<(), AppError> {
+    if open.iter().any(|open| open == label) {
+        return Err(AppError::Validation(format!(
+            "a window labelled {} is already open",
+            label
+        )));
+    }
+    if open.len() >= MAX_WINDOWS {
+        return Err(AppError::Validation(format!(
+            "at most {} windows can be open",
+            MAX_WINDOWS
+        )));
+    }
+    Ok(())
+}
+
+/// An event name of 1 to `MAX_EVENT_CHARS` ASCII letters, digits, `_`, `:`
+/// or `-`, outside `RESERVED_EVENT_PREFIXES`.
+pub(crate) fn validate_event_name(event: &str) -> Result<(), AppError> {
+    let valid = !event.is_empty()
+        && event.len() <= MAX_EVENT_CHARS
+        && event
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '-'));
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "event name must be 1 to {} letters, digits, _, : or -",
+            MAX_EVENT_CHARS
+        )));
+    }
+    if let Some(prefix) = RESERVED_EVENT_PREFIXES
+        .iter()
+        .find(|prefix| event.starts_with(*prefix))
+    {
+        return Err(AppError::Validation(format!(
+            "events starting with {} are reserved",
+            prefix
+        )));
+    }
+    Ok(())
+}
+
+/// Path within the app of `url`, which is either a path (`/ticket/BUG-1`)
+/// or a URL on the origin the main window is served from (the dev server
+/// or the app's custom protocol).
+fn app_path(app: &AppHandle, url: &str) -> Result<String, AppError> {
+    if let Some(path) = url.strip_prefix('/') {
+        return Ok(path.to_string());
+    }
+    let main_url = app
+        .get_webview_window(MAIN_WINDOW)
+        .and_then(|window| window.url().ok())
+        .ok_or_else(|| AppError::Validation("the main window is not open".into()))?;
+    let mut origin = format!(
+        "{}://{}",
+        main_url.scheme(),
+        main_url.host_str().unwrap_or_default()
+    );
+    if let Some(port) = main_url.port() {
+        origin.push_str(&format!(":{}", port));
+    }
+    match url.strip_prefix(&origin) {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '?', '#']) => {
+            Ok(rest.trim_start_matches('/').to_string())
+        }
+        _ => Err(AppError::Validation(format!(
+            "window URL must be on the app origin {}",
+            origin
+        ))),
+    }
+}
+
+/// Tell the main window a secondary window was destroyed, whether through
+/// `close_window` or its own close button.
+pub fn notify_window_closed(app: &AppHandle, label: &str) {
+    if label != MAIN_WINDOW {
+        app.emit_to(MAIN_WINDOW, WINDOW_CLOSED_EVENT, label).ok();
+    }
+}
+
+fn monitor_error(e: tauri::Error) -> AppError {
+    AppError::Io(format!("cannot query monitors: {}", e))
+}
+
+/// Show, unminimize and focus the main window, rebuilding it from its
+/// `tauri.conf.json` configuration if it was destroyed.
+pub fn show_main_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let window = match app.get_webview_window(MAIN_WINDOW) {
+        Some(window) => window,
+        None => match recreate_main_window(app) {
+            Ok(window) => window,
+            Err(e) => {
+                log::error!("show_main_window: cannot recreate main window: {}", e);
+                return None;
+            }
+        },
+    };
+
+    window.show().ok();
+    window.unminimize().ok();
+    window.set_focus().ok();
+    Some(window)
+}
+
+/// Handle a second-instance launch: bring the main window back and forward
+/// any `--project <path>` argument to the frontend.
+pub fn apply_cli_navigation(app: &AppHandle, args: &[String]) {
+    let window = show_main_window(app);
+
+    let Some(project) = project_arg(args) else {
+        return;
+    };
+    if let Some(state) = app.try_state::<MainWindowState>() {
+        *state.pending_project.lock().unwrap() = Some(project.clone());
+    }
+    // A freshly rebuilt webview may not be listening yet; it picks the
+    // request up through `take_cli_navigation` once loaded.
+    if let Some(window) = window {
+        window.emit("cli:open-project", project).ok();
+    }
+}
+
+/// Capture the main window geometry and persist it. Called before the
+/// window is hidden to tray.
+pub fn save_window_state(window: &tauri::Window) {
+    let (Ok(position), Ok(size), Ok(maximized)) = (
+        window.outer_position(),
+        window.inner_size(),
+        window.is_maximized(),
+    ) else {
+        return;
+    };
+
+    let app = window.app_handle();
+    let main_state = app.try_state::<MainWindowState>();
+    let state = WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+        title: window.title().ok(),
+        vibrancy: main_state
+            .as_ref()
+            .map(|main_state| *main_state.vibrancy.lock().unwrap())
+            .unwrap_or_default(),
+    };
+
+    if let Some(main_state) = main_state {
+        *main_state.last_state.lock().unwrap() = Some(state.clone());
+    }
+    persist_window_state(app, state);
+}
+
+/// Write `state` to `kv_store` in the background.
+fn persist_window_state(app: &AppHandle, state: WindowState) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Ok(json) = serde_json::to_string(&state) else {
+            return;
+        };
+        let pool = &app.state::<TelemetryState>().pool();
+        if let Err(e) = kv::set(pool, WINDOW_STATE_KV, &json).await {
+            log::warn!("persist_window_state: cannot persist window state: {}", e);
+        }
+    });
+}
+
+fn restore_window_state(window: &WebviewWindow, state: &WindowState) {
+    window
+        .set_position(PhysicalPosition::new(state.x, state.y))
+        .ok();
+    window
+        .set_size(PhysicalSize::new(state.width, state.height))
+        .ok();
+    // The saved position may belong to a monitor that is gone.
+    if let Err(e) = ensure_on_screen(window) {
+        log::warn!("restore_window_state: {}", e);
+    }
+    if state.maximized {
+        window.maximize().ok();
+    }
+    if let Some(title) = &state.title {
+        window.set_title(title).ok();
+    }
+    if state.vibrancy != VibrancyEffect::None {
+        if let Err(e) = apply_vibrancy(window, state.vibrancy) {
+            log::warn!("restore_window_state: {}", e);
+        }
+    }
+}
+
+/// Apply `effect` with the platform's window-vibrancy call, clearing the
+/// previous effect for `VibrancyEffect::None`.
+#[cfg(target_os = "macos")]
+fn apply_vibrancy(window: &WebviewWindow, effect: VibrancyEffect) -> Result<(), AppError> {
+    match macos_material(effect) {
+        Some(material) => {
+            window_vibrancy::apply_vibrancy(window, material, None, None).map_err(vibrancy_error)
+        }
+        None => window_vibrancy::clear_vibrancy(window)
+            .map(|_| ())
+            .map_err(vibrancy_error),
+    }
+}
+
+/// Closest `NSVisualEffectMaterial` to `effect`; `None` clears it.
+#[cfg(target_os = "macos")]
+fn macos_material(effect: VibrancyEffect) -> Option<window_vibrancy::NSVisualEffectMaterial> {
+    use window_vibrancy::NSVisualEffectMaterial;
+
+    match effect {
+        VibrancyEffect::None => None,
+        VibrancyEffect::Blur => Some(NSVisualEffectMaterial::UnderWindowBackground),
+        VibrancyEffect::Acrylic => Some(NSVisualEffectMaterial::HudWindow),
+        VibrancyEffect::Mica => Some(NSVisualEffectMaterial::WindowBackground),
+        VibrancyEffect::Sidebar => Some(NSVisualEffectMaterial::Sidebar),
+    }
+}
+
+/// Apply `effect` with the platform's window-vibrancy call, clearing the
+/// previous effect for `VibrancyEffect::None`. Mica needs Windows 11,
+/// acrylic Windows 10 or later.
+#[cfg(windows)]
+fn apply_vibrancy(window: &WebviewWindow, effect: VibrancyEffect) -> Result<(), AppError> {
+    // Only one effect may be active: clear the others (those never
+    // applied report an error, which is expected).
+    let _ = window_vibrancy::clear_blur(window);
+    let _ = window_vibrancy::clear_acrylic(window);
+    let _ = window_vibrancy::clear_mica(window);
+    match windows_backdrop(effect) {
+        None => Ok(()),
+        Some(WindowsBackdrop::Blur) => window_vibrancy::apply_blur(window, None),
+        Some(WindowsBackdrop::Acrylic) => window_vibrancy::apply_acrylic(window, None),
+        Some(WindowsBackdrop::Mica) => window_vibrancy::apply_mica(window, None),
+    }
+    .map_err(vibrancy_error)
+}
+
+/// Windows backdrop applied for a `VibrancyEffect`. Plain data, so the
+/// mapping is tested on every platform.
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WindowsBackdrop {
+    Blur,
+    Acrylic,
+    Mica,
+}
+
+#[cfg(any(windows, test))]
+fn windows_backdrop(effect: VibrancyEffect) -> Option<WindowsBackdrop> {
+    match effect {
+        VibrancyEffect::None => None,
+        VibrancyEffect::Blur => Some(WindowsBackdrop::Blur),
+        VibrancyEffect::Acrylic => Some(WindowsBackdrop::Acrylic),
+        // Mica is the material Windows 11 uses behind sidebars too.
+        VibrancyEffect::Mica | VibrancyEffect::Sidebar => Some(WindowsBackdrop::Mica),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn apply_vibrancy(_window: &WebviewWindow, effect: VibrancyEffect) -> Result<(), AppError> {
+    if effect != VibrancyEffect::None {
+        log::warn!(
+            "window vibrancy ({:?}) is not supported on this platform",
+            effect
+        );
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn vibrancy_error(e: window_vibrancy::Error) -> AppError {
+    AppError::Validation(format!("cannot apply the vibrancy effect: {}", e))
+}
+
+fn recreate_main_window(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|config| config.label == MAIN_WINDOW)
+        .cloned()
+        .unwrap_or_default();
+
+    let window = tauri::WebviewWindowBuilder::from_config(app, &config)?.build()?;
+
+    if let Some(state) = app.try_state::<MainWindowState>() {
+        if let Some(last_state) = state.last_state.lock().unwrap().as_ref() {
+            restore_window_state(&window, last_state);
+        }
+    }
+
+    log::info!("recreate_main_window: main window rebuilt");
+    Ok(window)
+}
+
+/// Extract the value following `--project`, if present.
+fn project_arg(args: &[String]) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == PROJECT_FLAG)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+/// RGBA pixels of a `size`x`size` window icon made from PNG `data`.
+fn icon_pixels(data: &[u8], size: u32) -> Result<Vec<u8>, AppError> {
+    if data.len() > MAX_ICON_BYTES {
+        return Err(AppError::Validation(format!(
+            "icon is too large ({} bytes, max {})",
+            data.len(),
+            MAX_ICON_BYTES
+        )));
+    }
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err(AppError::Validation("icon is not a PNG image".into()));
+    }
+    let (rgba, width, height) = decode_icon(data)?;
+    Ok(resize_icon(&rgba, width, height, size))
+}
+
+/// Decode a PNG into RGBA samples with its width and height.
+fn decode_icon(data: &[u8]) -> Result<(Vec<u8>, u32, u32), AppError> {
+    let invalid = |e: png::DecodingError| AppError::Validation(format!("invalid PNG icon: {}", e));
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if width > MAX_ICON_SOURCE_PIXELS || height > MAX_ICON_SOURCE_PIXELS {
+        return Err(AppError::Validation(format!(
+            "icon must be at most {0}x{0} pixels",
+            MAX_ICON_SOURCE_PIXELS
+        )));
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(invalid)?;
+    buffer.truncate(frame.buffer_size());
+
+    let channels = frame.color_type.samples();
+    let mut rgba = Vec::with_capacity(buffer.len() / channels * 4);
+    for pixel in buffer.chunks_exact(channels) {
+        match channels {
+            1 => rgba.extend_from_slice(&[pixel[0], pixel[0], pixel[0], 255]),
+            2 => rgba.extend_from_slice(&[pixel[0], pixel[0], pixel[0], pixel[1]]),
+            3 => rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]),
+            _ => rgba.extend_from_slice(&pixel[..4]),
+        }
+    }
+    Ok((rgba, width, height))
+}
+
+/// Scale RGBA pixels into a `size`x`size` square, centered with transparent
+/// margins when the image is not square. Each target pixel averages the
+/// source pixels it covers, weighted by alpha so transparent pixels don't
+/// darken the edges; enlarging repeats the nearest pixel.
+fn resize_icon(rgba: &[u8], width: u32, height: u32, size: u32) -> Vec<u8> {
+    let side = width.max(height);
+    let (left, top) = ((side - width) / 2, (side - height) / 2);
+    let pixel = |x: u32, y: u32| -> [u8; 4] {
+        if x < left || y < top || x >= left + width || y >= top + height {
+            return [0; 4];
+        }
+        let i = (((y - top) * width + (x - left)) * 4) as usize;
+        [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+    };
+
+    let mut out = Vec::with_capacity((size * size * 4) as usize);
+    for ty in 0..size {
+        let y0 = ty * side / size;
+        let y1 = ((ty + 1) * side / size).max(y0 + 1);
+        for tx in 0..size {
+            let x0 = tx * side / size;
+            let x1 = ((tx + 1) * side / size).max(x0 + 1);
+            let mut sum = [0u64; 4];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let [r, g, b, a] = pixel(x, y);
+                    let a = u64::from(a);
+                    sum[0] += u64::from(r) * a;
+                    sum[1] += u64::from(g) * a;
+                    sum[2] += u64::from(b) * a;
+                    sum[3] += a;
+                }
+            }
+            if sum[3] == 0 {
+                out.extend_from_slice(&[0; 4]);
+                continue;
+            }
+            let count = u64::from((y1 - y0) * (x1 - x0));
+            out.extend_from_slice(&[
+                (sum[0] / sum[3]) as u8,
+                (sum[1] / sum[3]) as u8,
+                (sum[2] / sum[3]) as u8,
+                (sum[3] / count) as u8,
+            ]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_secondary_window_labels_are_accepted() {
+        for label in SECONDARY_WINDOWS {
+            validate_label(label).unwrap();
+        }
+        for label in [MAIN_WINDOW, "quick-capture", "window-5", "ticket-BUG-1", ""] {
+            assert!(matches!(
+                validate_label(label),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn empty_titles_are_refused() {
+        for title in ["", "   "] {
+            assert!(matches!(
+                validate_title(title),
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn titles_are_limited_in_characters_not_bytes() {
+        validate_title(&"é".repeat(MAX_TITLE_CHARS)).unwrap();
+        assert!(validate_title(&"a".repeat(MAX_TITLE_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn titles_may_hold_any_printable_unicode() {
+        validate_title("Sprint 12 — “Café” <BUG-1> & 🚀🐛").unwrap();
+        validate_title("日本語のプロジェクト").unwrap();
+        for title in ["two\nlines", "tab\there", "bell\u{7}"] {
+            assert!(validate_title(title).is_err(), "{:?}", title);
+        }
+    }
+
+    fn labels(list: &[&str]) -> Vec<String> {
+        list.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn at_most_five_windows_are_open() {
+        let four = labels(&[MAIN_WINDOW, "window-1", "window-2", "quick-capture"]);
+        check_window_slot(&four, "window-3").unwrap();
+
+        let five = labels(&[
+            MAIN_WINDOW,
+            "window-1",
+            "window-2",
+            "window-3",
+            "quick-capture",
+        ]);
+        let err = check_window_slot(&five, "window-4").unwrap_err();
+        assert!(err.to_string().contains("at most 5 windows"), "{}", err);
+    }
+
+    #[test]
+    fn an_open_label_is_not_reused() {
+        let open = labels(&[MAIN_WINDOW, "window-1"]);
+        let err = check_window_slot(&open, "window-1").unwrap_err();
+        assert!(err.to_string().contains("already open"), "{}", err);
+    }
+
+    #[test]
+    fn window_size_is_bounded() {
+        validate_size(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE).unwrap();
+        for (width, height) in [(199.0, 480.0), (640.0, 8193.0), (f64::NAN, 480.0)] {
+            assert!(
+                validate_size(width, height).is_err(),
+                "{}x{}",
+                width,
+                height
+            );
+        }
+    }
+
+    /// A `width`x`height` RGB PNG filled with `color`.
+    fn png(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        let pixels = color.repeat((width * height) as usize);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    fn is_invalid_icon(data: &[u8]) -> bool {
+        matches!(icon_pixels(data, ICON_SIZE), Err(AppError::Validation(_)))
+    }
+
+    #[test]
+    fn non_png_or_corrupt_icons_are_refused() {
+        assert!(is_invalid_icon(b"GIF89a"));
+        assert!(is_invalid_icon(PNG_SIGNATURE));
+        let valid = png(4, 4, [255, 0, 0]);
+        assert!(is_invalid_icon(&valid[..valid.len() / 2]));
+    }
+
+    #[test]
+    fn oversized_icons_are_refused() {
+        let mut huge = PNG_SIGNATURE.to_vec();
+        huge.resize(MAX_ICON_BYTES + 1, 0);
+        assert!(is_invalid_icon(&huge));
+        assert!(is_invalid_icon(&png(MAX_ICON_SOURCE_PIXELS + 1, 1, [0; 3])));
+    }
+
+    #[test]
+    fn valid_icon_is_scaled_into_a_square() {
+        // 64x32: scaled to 32x16, centered between transparent bands.
+        let rgba = icon_pixels(&png(64, 32, [10, 200, 30]), ICON_SIZE).unwrap();
+        assert_eq!(rgba.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * ICON_SIZE + x) * 4) as usize;
+            &rgba[i..i + 4]
+        };
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(16, 16), [10, 200, 30, 255]);
+        assert_eq!(pixel(31, 31), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn event_names_are_plain_identifiers() {
+        for event in ["ticket:updated", "board-refresh", "sync_done", "a"] {
+            validate_event_name(event).unwrap();
+        }
+        validate_event_name(&"e".repeat(MAX_EVENT_CHARS)).unwrap();
+        for event in [
+            "",
+            "ticket updated",
+            "ticket/updated",
+            "ticket.updated",
+            "évènement",
+            "ticket:updated\n",
+        ] {
+            assert!(
+                matches!(validate_event_name(event), Err(AppError::Validation(_))),
+                "{:?}",
+                event
+            );
+        }
+        assert!(validate_event_name(&"e".repeat(MAX_EVENT_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn reserved_event_namespaces_are_refused() {
+        for prefix in RESERVED_EVENT_PREFIXES {
+            let event = format!("{}forged", prefix);
+            assert!(validate_event_name(&event).is_err(), "{}", event);
+        }
+        for event in ["tauri://close-requested", "tray:open", "app:unlock"] {
+            assert!(validate_event_name(event).is_err(), "{}", event);
+        }
+    }
+
+    #[test]
+    fn capability_names_the_secondary_windows() {
+        let capability: serde_json::Value =
+            serde_json::from_str(include_str!("../capabilities/secondary-windows.json")).unwrap();
+        let windows: Vec<&str> = capability["windows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|label| label.as_str().unwrap())
+            .collect();
+        assert_eq!(windows, SECONDARY_WINDOWS);
+    }
+
+    fn monitor(name: &str, x: i32, y: i32) -> MonitorInfo {
+        MonitorInfo {
+            name: name.into(),
+            position_x: x,
+            position_y: y,
+            width: 1920,
+            height: 1080,
+            scale_factor: 1.0,
+            is_primary: false,
+        }
+    }
+
+    fn primary_count(monitors: &[MonitorInfo]) -> usize {
+        monitors.iter().filter(|monitor| monitor.is_primary).count()
+    }
+
+    #[test]
+    fn the_primary_monitor_is_flagged_once() {
+        let mut monitors = vec![monitor("DP-1", -1920, 0), monitor("HDMI-1", 0, 0)];
+        flag_primary(&mut monitors, Some(&monitor("HDMI-1", 0, 0)));
+        assert_eq!(primary_count(&monitors), 1);
+        assert!(monitors[1].is_primary);
+
+        // Mirrored displays report the same name and origin.
+        let mut mirrored = vec![monitor("HDMI-1", 0, 0), monitor("HDMI-1", 0, 0)];
+        flag_primary(&mut mirrored, Some(&monitor("HDMI-1", 0, 0)));
+        assert_eq!(primary_count(&mirrored), 1);
+    }
+
+    #[test]
+    fn a_monitor_is_recognized_by_name_and_origin() {
+        let mut monitors = vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)];
+        flag_primary(&mut monitors, Some(&monitor("DP-2", 0, 0)));
+        assert_eq!(primary_count(&monitors), 0);
+        flag_primary(&mut monitors, None);
+        assert_eq!(primary_count(&monitors), 0);
+    }
+
+    const ALL_EFFECTS: [VibrancyEffect; 5] = [
+        VibrancyEffect::None,
+        VibrancyEffect::Blur,
+        VibrancyEffect::Acrylic,
+        VibrancyEffect::Mica,
+        VibrancyEffect::Sidebar,
+    ];
+
+    #[test]
+    fn vibrancy_effects_use_the_bridge_names() {
+        let names: Vec<String> = ALL_EFFECTS
+            .iter()
+            .map(|effect| {
+                serde_json::to_value(effect)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names, ["none", "blur", "acrylic", "mica", "sidebar"]);
+        for (effect, name) in ALL_EFFECTS.iter().zip(&names) {
+            let parsed: VibrancyEffect = serde_json::from_value(serde_json::json!(name)).unwrap();
+            assert_eq!(parsed, *effect);
+        }
+        assert!(serde_json::from_value::<VibrancyEffect>(serde_json::json!("Blur")).is_err());
+    }
+
+    #[test]
+    fn states_saved_before_vibrancy_have_none() {
+        let state: WindowState = serde_json::from_value(serde_json::json!({
+            "x": 0, "y": 0, "width": 800, "height": 600, "maximized": false
+        }))
+        .unwrap();
+        assert_eq!(state.vibrancy, VibrancyEffect::None);
+    }
+
+    #[test]
+    fn windows_backdrop_per_effect() {
+        let backdrops: Vec<Option<WindowsBackdrop>> = ALL_EFFECTS
+            .iter()
+            .map(|effect| windows_backdrop(*effect))
+            .collect();
+        assert_eq!(
+            backdrops,
+            [
+                None,
+                Some(WindowsBackdrop::Blur),
+                Some(WindowsBackdrop::Acrylic),
+                Some(WindowsBackdrop::Mica),
+                Some(WindowsBackdrop::Mica),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn macos_material_per_effect() {
+        use window_vibrancy::NSVisualEffectMaterial;
+
+        let materials: Vec<Option<NSVisualEffectMaterial>> = ALL_EFFECTS
+            .iter()
+            .map(|effect| macos_material(*effect))
+            .collect();
+        assert_eq!(
+            materials,
+            [
+                None,
+                Some(NSVisualEffectMaterial::UnderWindowBackground),
+                Some(NSVisualEffectMaterial::HudWindow),
+                Some(NSVisualEffectMaterial::WindowBackground),
+                Some(NSVisualEffectMaterial::Sidebar),
+            ]
+        );
+    }
+
+    #[test]
+    fn off_screen_window_is_centered_on_the_monitor() {
+        let origin = PhysicalPosition::new(0, 0);
+        let area = PhysicalSize::new(1920, 1080);
+        let size = PhysicalSize::new(800, 600);
+
+        // Left where a removed second monitor used to be.
+        let center = center_of(PhysicalPosition::new(2400, 300), size);
+        assert!(!contains(origin, area, center));
+        assert_eq!(
+            centered_position(origin, area, size),
+            PhysicalPosition::new(560, 240)
+        );
+        let moved = centered_position(origin, area, size);
+        assert!(contains(origin, area, center_of(moved, size)));
+    }
+
+    #[test]
+    fn window_half_off_screen_is_left_alone() {
+        let origin = PhysicalPosition::new(0, 0);
+        let area = PhysicalSize::new(1920, 1080);
+        let size = PhysicalSize::new(800, 600);
+        assert!(contains(
+            origin,
+            area,
+            center_of(PhysicalPosition::new(-300, 700), size)
+        ));
+        assert!(!contains(
+            origin,
+            area,
+            center_of(PhysicalPosition::new(-500, 0), size)
+        ));
+    }
+
+    #[test]
+    fn monitors_left_of_the_primary_are_handled() {
+        let origin = PhysicalPosition::new(-2560, -200);
+        let area = PhysicalSize::new(2560, 1440);
+        let size = PhysicalSize::new(800, 600);
+        assert!(contains(
+            origin,
+            area,
+            center_of(PhysicalPosition::new(-1000, 100), size)
+        ));
+        assert_eq!(
+            centered_position(origin, area, size),
+            PhysicalPosition::new(-1680, 220)
+        );
+        // Bigger than the monitor: pinned to its corner.
+        assert_eq!(
+            centered_position(origin, area, PhysicalSize::new(3000, 2000)),
+            origin
+        );
+    }
+}
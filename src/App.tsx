@@ -28,7 +28,7 @@ import { ProjectWorkspace } from './components/workspace/ProjectWorkspace';
 import { initSecureStorage } from './lib/ai';
 import type { TypeDefinition } from './types/typeConfig';
 import { isFileSystemAccessSupported } from './lib/fileSystem';
-import { isTauri, getDirFromPath, getFolderName, forceQuit, listenTrayQuitRequested } from './lib/tauri-bridge';
+import { isTauri, getDirFromPath, getFolderName, forceQuit, listenTrayQuitRequested, getStartupTiming, setWindowTitle } from './lib/tauri-bridge';
 import { UpdateModal } from './components/ui/UpdateModal';
 import { WhatsNewModal } from './components/ui/WhatsNewModal';
 import { ErrorBoundary } from './components/ui/ErrorBoundary';
@@ -119,6 +119,19 @@ function App() {
       // Previously accepted — initialize telemetry and fire app_launched
       initTelemetry();
       track('app_launched');
+      if (isTauri()) {
+        getStartupTiming()
+          .then((phases) => {
+            const properties: Record<string, number> = {
+              total_ms: phases.reduce((sum, [, ms]) => sum + ms, 0),
+            };
+            for (const [name, ms] of phases) {
+              properties[`${name}_ms`] = ms;
+            }
+            track('app_started', properties);
+          })
+          .catch((error) => console.warn('[telemetry] Failed to read startup timing:', error));
+      }
     } else if (consent === null && shouldPromptConsent()) {
       // First launch or one previous dismiss — show consent dialog
       setShowConsent(true);
@@ -130,11 +143,12 @@ function App() {
   useEffect(() => {
     const projectPath = typeConfig.projectPath;
 
-    if (projectPath) {
-      const projectName = getFolderName(projectPath);
-      document.title = `Ticketflow - ${projectName}`;
-    } else {
-      document.title = 'Ticketflow';
+    const title = projectPath
+      ? `Ticketflow — ${getFolderName(projectPath)}`
+      : 'Ticketflow';
+    document.title = title;
+    if (isTauri()) {
+      setWindowTitle(title).catch(console.warn);
     }
   }, [typeConfig.projectPath]);
 
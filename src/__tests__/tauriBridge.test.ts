@@ -174,11 +174,35 @@ vi.mock('@tauri-apps/api/event', () => ({
   listen: vi.fn(),
 }));
 
+vi.mock('@tauri-apps/api/webviewWindow', () => ({
+  getCurrentWebviewWindow: vi.fn(),
+}));
+
 import { open, save } from '@tauri-apps/plugin-dialog';
 import { readTextFile, writeTextFile, exists, readFile, writeFile, readDir } from '@tauri-apps/plugin-fs';
 import { open as openUrl } from '@tauri-apps/plugin-shell';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { isAppError } from '../types/appError';
+import type { AppErrorKind } from '../types/appError';
+
+/**
+ * Await a bridge call expected to fail, and check that the caller receives
+ * a structured AppError of `kind` it can branch on (see types/appError.ts)
+ */
+async function expectAppError(call: Promise<unknown>, kind: AppErrorKind, message?: RegExp): Promise<void> {
+  const error = await call.then(
+    () => {
+      throw new Error('expected the call to reject');
+    },
+    (rejection: unknown) => rejection
+  );
+  expect(isAppError(error)).toBe(true);
+  expect(isAppError(error) && error.kind).toBe(kind);
+  if (message) {
+    expect(isAppError(error) && error.message).toMatch(message);
+  }
+}
 
 import {
   openMarkdownFileDialog,
@@ -409,3 +433,1390 @@ describe('writeImageFromBase64', () => {
     expect(calledBytes).toEqual(expectedBytes);
   });
 });
+
+// ============================================================
+// WINDOW TITLE TESTS (36-39)
+// ============================================================
+
+import { setWindowTitle, getWindowTitle } from '../lib/tauri-bridge';
+
+describe('setWindowTitle', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('36. surfaces the backend rejection of an empty title', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'window title is empty' });
+
+    await expectAppError(setWindowTitle(''), 'Validation', /title is empty/);
+    expect(invoke).toHaveBeenCalledWith('set_window_title', { title: '' });
+  });
+
+  test('37. passes a max-length title unchanged', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+    const title = 'T'.repeat(128);
+
+    await setWindowTitle(title);
+
+    expect(invoke).toHaveBeenCalledWith('set_window_title', { title });
+  });
+
+  test('38. passes special characters and emoji unchanged', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+    const title = 'Ticketflow — Café <&> 🚀';
+
+    await setWindowTitle(title);
+
+    expect(invoke).toHaveBeenCalledWith('set_window_title', { title });
+  });
+});
+
+describe('getWindowTitle', () => {
+  test('39. returns the title from get_window_title', async () => {
+    vi.mocked(invoke).mockResolvedValue('Ticketflow — My Project');
+
+    const title = await getWindowTitle();
+
+    expect(invoke).toHaveBeenCalledWith('get_window_title');
+    expect(title).toBe('Ticketflow — My Project');
+  });
+});
+
+// ============================================================
+// TRAY MENU TESTS (40-45)
+// ============================================================
+
+import {
+  setTrayMenuItemLabel,
+  setTrayMenuItemEnabled,
+  setTrayIcon,
+  startTrayAnimation,
+  stopTrayAnimation,
+} from '../lib/tauri-bridge';
+import type { TrayMenuItemId, TrayIconName } from '../lib/tauri-bridge';
+
+describe('setTrayMenuItemLabel', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('40. relabels a known item', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setTrayMenuItemLabel('open', 'Open Ticketflow');
+
+    expect(invoke).toHaveBeenCalledWith('set_tray_menu_item_label', {
+      itemId: 'open',
+      label: 'Open Ticketflow',
+    });
+  });
+
+  test('41. surfaces the backend rejection of an unknown item', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'unknown tray menu item: settings' });
+
+    await expectAppError(
+      setTrayMenuItemLabel('settings' as TrayMenuItemId, 'Settings'),
+      'Validation',
+      /unknown tray menu item/
+    );
+  });
+});
+
+describe('setTrayMenuItemEnabled', () => {
+  test('42. passes the enabled flag', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setTrayMenuItemEnabled('quit', false);
+
+    expect(invoke).toHaveBeenCalledWith('set_tray_menu_item_enabled', {
+      itemId: 'quit',
+      enabled: false,
+    });
+  });
+});
+
+describe('setTrayIcon', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('43. passes every known icon name', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+    const names: TrayIconName[] = ['default', 'syncing', 'error', 'notification'];
+
+    for (const name of names) {
+      await setTrayIcon(name);
+    }
+
+    expect(vi.mocked(invoke).mock.calls).toEqual(
+      names.map((iconName) => ['set_tray_icon', { iconName }])
+    );
+  });
+
+  test('44. surfaces the backend rejection of an unknown icon', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'unknown tray icon: busy' });
+
+    await expectAppError(setTrayIcon('busy' as TrayIconName), 'Validation', /unknown tray icon/);
+  });
+});
+
+describe('startTrayAnimation', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('45. stops the animation with the id it was started with', async () => {
+    vi.mocked(invoke).mockResolvedValueOnce('tray-animation-1').mockResolvedValueOnce(undefined);
+    const frames = ['/frames/sync-1.png', '/frames/sync-2.png', '/frames/sync-3.png'];
+
+    const id = await startTrayAnimation(frames, 120);
+    await stopTrayAnimation(id);
+
+    expect(invoke).toHaveBeenNthCalledWith(1, 'start_tray_animation', {
+      framePaths: frames,
+      intervalMs: 120,
+    });
+    expect(invoke).toHaveBeenNthCalledWith(2, 'stop_tray_animation', {
+      animationId: 'tray-animation-1',
+    });
+  });
+});
+
+// ============================================================
+// SECONDARY WINDOW TESTS (46-48)
+// ============================================================
+
+import { createWindow, closeWindow, listWindows } from '../lib/tauri-bridge';
+import type { SecondaryWindowLabel } from '../lib/tauri-bridge';
+
+describe('createWindow', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('46. passes label, url, size and title', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await createWindow('window-1', '/ticket/BUG-1', 640, 480, 'BUG-1');
+
+    expect(invoke).toHaveBeenCalledWith('create_window', {
+      label: 'window-1',
+      url: '/ticket/BUG-1',
+      width: 640,
+      height: 480,
+      title: 'BUG-1',
+    });
+  });
+
+  test('47. surfaces the backend rejection of an invalid label or a sixth window', async () => {
+    vi.mocked(invoke).mockRejectedValueOnce({
+      kind: 'Validation',
+      message: 'window label must be one of window-1, window-2, window-3, window-4',
+    });
+    vi.mocked(invoke).mockRejectedValueOnce({ kind: 'Validation', message: 'at most 5 windows can be open' });
+
+    await expectAppError(
+      createWindow('ticket-1' as SecondaryWindowLabel, '/ticket/1', 640, 480, 'T'),
+      'Validation',
+      /window label/
+    );
+    await expectAppError(createWindow('window-4', '/ticket/6', 640, 480, 'T'), 'Validation', /at most 5 windows/);
+  });
+});
+
+describe('closeWindow / listWindows', () => {
+  test('48. closes by label and lists the open windows', async () => {
+    vi.mocked(invoke).mockResolvedValueOnce(undefined).mockResolvedValueOnce(['main']);
+
+    await closeWindow('window-1');
+    const labels = await listWindows();
+
+    expect(invoke).toHaveBeenCalledWith('close_window', { label: 'window-1' });
+    expect(invoke).toHaveBeenCalledWith('list_windows');
+    expect(labels).toEqual(['main']);
+  });
+});
+
+// ============================================================
+// CROSS-WINDOW EVENT TESTS (49-50)
+// ============================================================
+
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { emitToWindow, listenWindowEvent } from '../lib/tauri-bridge';
+
+describe('emitToWindow / listenWindowEvent', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('49. sends the event to the target window label', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await emitToWindow('window-1', 'ticket:updated', { id: 'BUG-1' });
+
+    expect(invoke).toHaveBeenCalledWith('emit_to_window', {
+      targetLabel: 'window-1',
+      event: 'ticket:updated',
+      payload: { id: 'BUG-1' },
+    });
+  });
+
+  test('50. delivers payloads addressed to the current window', async () => {
+    const unlisten = vi.fn();
+    const windowListen = vi.fn((_event: string, handler: (e: { payload: unknown }) => void) => {
+      handler({ payload: { id: 'BUG-1' } });
+      return Promise.resolve(unlisten);
+    });
+    vi.mocked(getCurrentWebviewWindow).mockReturnValue({ listen: windowListen } as never);
+    const callback = vi.fn();
+
+    const result = await listenWindowEvent('ticket:updated', callback);
+
+    expect(windowListen).toHaveBeenCalledWith('ticket:updated', expect.any(Function));
+    expect(callback).toHaveBeenCalledWith({ id: 'BUG-1' });
+    expect(result).toBe(unlisten);
+  });
+});
+
+// ============================================================
+// WINDOW ICON TESTS (51-52)
+// ============================================================
+
+import { setWindowIcon, resetWindowIcon } from '../lib/tauri-bridge';
+
+describe('setWindowIcon / resetWindowIcon', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('51. sends the PNG bytes as a plain array', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setWindowIcon(new Uint8Array([0x89, 0x50, 0x4e, 0x47]));
+
+    expect(invoke).toHaveBeenCalledWith('set_window_icon', { iconData: [0x89, 0x50, 0x4e, 0x47] });
+  });
+
+  test('52. propagates rejected icons and resets without arguments', async () => {
+    vi.mocked(invoke).mockRejectedValueOnce({ kind: 'Validation', message: 'icon is not a PNG image' });
+
+    await expectAppError(setWindowIcon(new Uint8Array([1, 2, 3])), 'Validation', /not a PNG/);
+
+    vi.mocked(invoke).mockResolvedValue(undefined);
+    await resetWindowIcon();
+    expect(invoke).toHaveBeenLastCalledWith('reset_window_icon');
+  });
+});
+
+// ============================================================
+// REGION SCREENSHOT TESTS (53-54)
+// ============================================================
+
+import { screenshotRegion, screenshotElement } from '../lib/tauri-bridge';
+
+describe('screenshotRegion / screenshotElement', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('53. surfaces the backend rejection of an out-of-bounds region', async () => {
+    vi.mocked(invoke).mockRejectedValue({
+      kind: 'Validation',
+      message: 'region 200x200 at 900,700 is outside the 1000x800 window',
+    });
+
+    await expectAppError(screenshotRegion(900, 700, 200, 200), 'Validation', /outside the 1000x800 window/);
+    expect(invoke).toHaveBeenCalledWith('screenshot_region', { x: 900, y: 700, width: 200, height: 200 });
+  });
+
+  test('54. converts an element rectangle to capture pixels', async () => {
+    vi.mocked(invoke).mockResolvedValue('/cache/screenshots/region-1.png');
+    const originalRatio = window.devicePixelRatio;
+    Object.defineProperty(window, 'devicePixelRatio', { value: 2, configurable: true });
+    const element = document.createElement('div');
+    element.getBoundingClientRect = () =>
+      ({ left: 10.5, top: 20, right: 110.5, bottom: 70, width: 100, height: 50 }) as DOMRect;
+
+    const path = await screenshotElement(element);
+
+    expect(invoke).toHaveBeenCalledWith('screenshot_region', { x: 21, y: 40, width: 200, height: 100 });
+    expect(path).toBe('/cache/screenshots/region-1.png');
+    Object.defineProperty(window, 'devicePixelRatio', { value: originalRatio, configurable: true });
+  });
+});
+
+// ============================================================
+// HTML PDF EXPORT TESTS (55)
+// ============================================================
+
+import { pdfExport } from '../lib/tauri-bridge';
+
+describe('pdfExport', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('55. sends simple HTML and returns the PDF size', async () => {
+    vi.mocked(invoke).mockResolvedValue(18432);
+    const html = '<!DOCTYPE html><html><body><h1>Sprint 12</h1></body></html>';
+
+    const size = await pdfExport(html, '/home/user/Documents/sprint-12.pdf');
+
+    expect(invoke).toHaveBeenCalledWith('pdf_export', {
+      html,
+      outputPath: '/home/user/Documents/sprint-12.pdf',
+    });
+    expect(size).toBe(18432);
+  });
+});
+
+// ============================================================
+// ATTACHMENT TESTS (56)
+// ============================================================
+
+import { saveAttachment } from '../lib/tauri-bridge';
+
+describe('saveAttachment', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('56. sends either a path or bytes and surfaces the quota error', async () => {
+    vi.mocked(invoke).mockRejectedValueOnce({
+      kind: 'QuotaExceeded',
+      message: 'attachments would use 1073741900 of the 1073741824 bytes allowed per project',
+    });
+
+    await expectAppError(
+      saveAttachment('/p/backlog.db', 'BUG-001', { path: '/home/user/log.txt' }),
+      'QuotaExceeded'
+    );
+    expect(invoke).toHaveBeenCalledWith('save_attachment', {
+      dbPath: '/p/backlog.db',
+      ticketId: 'BUG-001',
+      sourcePath: '/home/user/log.txt',
+    });
+
+    vi.mocked(invoke).mockResolvedValue({ id: 3 });
+    await saveAttachment('/p/backlog.db', 'BUG-001', { bytes: new Uint8Array([1, 2]), filename: 'a.bin' });
+    expect(invoke).toHaveBeenLastCalledWith('save_attachment', {
+      dbPath: '/p/backlog.db',
+      ticketId: 'BUG-001',
+      bytes: [1, 2],
+      filename: 'a.bin',
+    });
+  });
+});
+
+// ============================================================
+// SAFE SHELL COMMAND TESTS (57-58)
+// ============================================================
+
+import { runShellCommandSafe } from '../lib/tauri-bridge';
+
+describe('runShellCommandSafe', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('57. passes arguments unchanged and returns the captured output', async () => {
+    vi.mocked(invoke).mockResolvedValue({ stdout: 'main\n', stderr: '', exit_code: 0 });
+
+    const result = await runShellCommandSafe('git', ['branch', '--show-current']);
+
+    expect(invoke).toHaveBeenCalledWith('run_shell_command_safe', {
+      command: 'git',
+      args: ['branch', '--show-current'],
+      timeoutMs: 10000,
+    });
+    expect(result.stdout).toBe('main\n');
+  });
+
+  test('58. surfaces rejected injection attempts', async () => {
+    vi.mocked(invoke)
+      .mockRejectedValueOnce({ kind: 'Validation', message: 'argument "log; rm -rf ~" contains a forbidden character' })
+      .mockRejectedValueOnce({ kind: 'Validation', message: 'option -exec is not allowed for find' })
+      .mockRejectedValueOnce({ kind: 'Unauthorized', message: 'command "sh" is not allowed' });
+
+    await expectAppError(runShellCommandSafe('git', ['log; rm -rf ~']), 'Validation', /forbidden character/);
+    await expectAppError(runShellCommandSafe('find', ['.', '-exec', 'rm', '{}', '+']), 'Validation', /-exec/);
+    await expectAppError(runShellCommandSafe('sh' as 'git', ['-c', 'echo pwned']), 'Unauthorized');
+    expect(invoke).toHaveBeenLastCalledWith('run_shell_command_safe', {
+      command: 'sh',
+      args: ['-c', 'echo pwned'],
+      timeoutMs: 10000,
+    });
+  });
+});
+
+// ============================================================
+// STREAMED SHELL OUTPUT TESTS (59-60)
+// ============================================================
+
+import { streamShellOutput } from '../lib/tauri-bridge';
+
+describe('streamShellOutput', () => {
+  let handlers: Map<string, (event: { payload: unknown }) => void>;
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    handlers = new Map();
+    vi.mocked(listen).mockImplementation(async (event, handler) => {
+      handlers.set(event, handler as (event: { payload: unknown }) => void);
+      return () => handlers.delete(event);
+    });
+  });
+
+  test('59. delivers lines until the process completes', async () => {
+    vi.mocked(invoke).mockResolvedValue('stream-1');
+    const lines: string[] = [];
+
+    const stream = await streamShellOutput('git', ['log', '--oneline'], (line) => lines.push(line));
+    const eventName = vi.mocked(invoke).mock.calls[0][1]!.eventName as string;
+    handlers.get(eventName)!({ payload: 'abc123 First' });
+    handlers.get(eventName)!({ payload: 'def456 Second' });
+    handlers.get('shell:stream-ended')!({
+      payload: { process_id: 'stream-1', exit_code: 0, killed: false },
+    });
+
+    await expect(stream.done).resolves.toEqual({ process_id: 'stream-1', exit_code: 0, killed: false });
+    expect(lines).toEqual(['abc123 First', 'def456 Second']);
+    expect(invoke).toHaveBeenCalledWith('stream_shell_output', {
+      command: 'git',
+      args: ['log', '--oneline'],
+      eventName,
+    });
+    expect(handlers.size).toBe(0);
+  });
+
+  test('60. kills the process early', async () => {
+    vi.mocked(invoke).mockResolvedValueOnce('stream-2').mockResolvedValueOnce(undefined);
+
+    const stream = await streamShellOutput('find', ['/', '-name', '*.md'], () => {});
+    await stream.kill();
+    handlers.get('shell:stream-ended')!({
+      payload: { process_id: 'stream-2', exit_code: null, killed: true },
+    });
+
+    expect(invoke).toHaveBeenLastCalledWith('kill_streamed_process', { processId: 'stream-2' });
+    await expect(stream.done).resolves.toMatchObject({ killed: true });
+  });
+});
+
+// ============================================================
+// ATTACHMENT CLEANUP TESTS (61)
+// ============================================================
+
+import { cleanupOrphanedAttachments } from '../lib/tauri-bridge';
+
+describe('cleanupOrphanedAttachments', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('61. deletes by default and can do a dry run', async () => {
+    vi.mocked(invoke).mockResolvedValue(4096);
+
+    await expect(cleanupOrphanedAttachments('/p/backlog.db', true)).resolves.toBe(4096);
+    expect(invoke).toHaveBeenCalledWith('cleanup_orphaned_attachments', { dbPath: '/p/backlog.db', dryRun: true });
+
+    await cleanupOrphanedAttachments('/p/backlog.db');
+    expect(invoke).toHaveBeenLastCalledWith('cleanup_orphaned_attachments', { dbPath: '/p/backlog.db', dryRun: false });
+  });
+});
+
+// ============================================================
+// BACKGROUND JOB TESTS (62-64)
+// ============================================================
+
+import { spawnBackgroundJob, cancelBackgroundJob, listenJobProgress } from '../lib/tauri-bridge';
+
+describe('background jobs', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('62. enqueues a job and returns its id', async () => {
+    vi.mocked(invoke).mockResolvedValue('job-1');
+
+    const id = await spawnBackgroundJob('fts_rebuild', { db_path: '/p/backlog.db' });
+
+    expect(id).toBe('job-1');
+    expect(invoke).toHaveBeenCalledWith('spawn_background_job', {
+      kind: 'fts_rebuild',
+      params: { db_path: '/p/backlog.db' },
+    });
+  });
+
+  test('63. forwards job:progress payloads', async () => {
+    let handler: ((event: { payload: unknown }) => void) | undefined;
+    vi.mocked(listen).mockImplementation(async (_event, h) => {
+      handler = h as typeof handler;
+      return () => {};
+    });
+    const received: unknown[] = [];
+
+    await listenJobProgress((progress) => received.push(progress));
+    handler!({ payload: { job_id: 'job-1', progress: 0.1, step: 'rebuilding' } });
+
+    expect(listen).toHaveBeenCalledWith('job:progress', expect.any(Function));
+    expect(received).toEqual([{ job_id: 'job-1', progress: 0.1, step: 'rebuilding' }]);
+  });
+
+  test('64. cancels a job and surfaces refusals', async () => {
+    vi.mocked(invoke)
+      .mockResolvedValueOnce(undefined)
+      .mockRejectedValueOnce({ kind: 'Validation', message: 'a running vacuum job cannot be cancelled' });
+
+    await cancelBackgroundJob('job-2');
+    expect(invoke).toHaveBeenCalledWith('cancel_background_job', { jobId: 'job-2' });
+    await expectAppError(cancelBackgroundJob('job-3'), 'Validation', /cannot be cancelled/);
+  });
+});
+
+// ============================================================
+// CLIPBOARD ATTACHMENT TESTS (65)
+// ============================================================
+
+import { saveClipboardImage } from '../lib/tauri-bridge';
+
+describe('saveClipboardImage', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('65. returns the typed result, no_image included', async () => {
+    vi.mocked(invoke).mockResolvedValue({ kind: 'no_image' });
+
+    const result = await saveClipboardImage('/p/backlog.db', 'BUG-001');
+
+    expect(invoke).toHaveBeenCalledWith('save_clipboard_image', { dbPath: '/p/backlog.db', ticketId: 'BUG-001' });
+    expect(result.kind).toBe('no_image');
+  });
+});
+
+// ============================================================
+// DISK SPACE TESTS (66-67)
+// ============================================================
+
+import { getFreeDiskSpace, compressFile } from '../lib/tauri-bridge';
+
+describe('getFreeDiskSpace', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('66. returns the space reported by the backend', async () => {
+    const info = { free_bytes: 5e9, total_bytes: 256e9, used_bytes: 251e9 };
+    vi.mocked(invoke).mockResolvedValue(info);
+
+    await expect(getFreeDiskSpace('/home/user/Documents')).resolves.toEqual(info);
+    expect(invoke).toHaveBeenCalledWith('get_free_disk_space', { path: '/home/user/Documents' });
+  });
+
+  test('67. surfaces the insufficient space error of compressFile', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Io', message: 'Insufficient disk space' });
+
+    await expectAppError(compressFile('/p/big.db', '/p/big.db.zst'), 'Io', /disk space/);
+  });
+});
+
+// ============================================================
+// FILE DROP TESTS (68-69)
+// ============================================================
+
+import { setDropTarget, listenAttachmentsAdded } from '../lib/tauri-bridge';
+
+describe('file drop attachments', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('68. sets the drop target', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setDropTarget('/p/backlog.db', 'BUG-001');
+
+    expect(invoke).toHaveBeenCalledWith('set_drop_target', { dbPath: '/p/backlog.db', ticketId: 'BUG-001' });
+  });
+
+  test('69. delivers attached and rejected files', async () => {
+    let handler: ((event: { payload: unknown }) => void) | undefined;
+    vi.mocked(listen).mockImplementation(async (_event, h) => {
+      handler = h as typeof handler;
+      return () => {};
+    });
+    const received: unknown[] = [];
+    const payload = {
+      db_path: '/p/backlog.db',
+      ticket_id: 'BUG-001',
+      attachments: [],
+      rejected: [{ path: '/tmp/setup.exe', error: 'validation: .exe files cannot be attached' }],
+    };
+
+    await listenAttachmentsAdded((added) => received.push(added));
+    handler!({ payload });
+
+    expect(listen).toHaveBeenCalledWith('attachments:added', expect.any(Function));
+    expect(received).toEqual([payload]);
+  });
+});
+
+// ============================================================
+// HTTP PROXY SETTINGS TESTS (70-71)
+// ============================================================
+
+import { setHttpProxy } from '../lib/tauri-bridge';
+
+describe('setHttpProxy', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('70. passes the proxy and bypass list', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setHttpProxy('http://proxy.corp:8080', 'localhost,.corp');
+
+    expect(invoke).toHaveBeenCalledWith('set_http_proxy', {
+      proxyUrl: 'http://proxy.corp:8080',
+      noProxy: 'localhost,.corp',
+    });
+  });
+
+  test('71. surfaces an invalid proxy URL', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'unsupported proxy scheme: ftp' });
+
+    await expectAppError(setHttpProxy('ftp://proxy.corp'), 'Validation', /unsupported proxy scheme/);
+    expect(invoke).toHaveBeenCalledWith('set_http_proxy', { proxyUrl: 'ftp://proxy.corp', noProxy: null });
+  });
+});
+
+// ============================================================
+// ATTACHMENT TEXT SEARCH TESTS (72)
+// ============================================================
+
+import { searchTickets } from '../lib/tauri-bridge';
+
+describe('searchTickets attachment matches', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('72. returns the attachment a match came from', async () => {
+    const results = {
+      total: 1,
+      hits: [{
+        id: 'BUG-007',
+        type: 'BUG',
+        title: 'Crash on export',
+        section: null,
+        title_highlight: 'Crash on export',
+        snippet: '…at <mark>ExportWorker</mark>.run…',
+        attachment_filename: 'crash.log',
+        score: -3.2,
+      }],
+    };
+    vi.mocked(invoke).mockResolvedValue(results);
+
+    const found = await searchTickets('/p/backlog.db', 'ExportWorker');
+
+    expect(found.hits[0].attachment_filename).toBe('crash.log');
+  });
+});
+
+// ============================================================
+// TLS PINNING TESTS (73)
+// ============================================================
+
+import { pinTlsCert } from '../lib/tauri-bridge';
+
+describe('pinTlsCert', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('73. sends the certificate as a byte array', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await pinTlsCert(new Uint8Array([0x30, 0x82, 0x01]));
+
+    expect(invoke).toHaveBeenCalledWith('pin_tls_cert', { derBytes: [0x30, 0x82, 0x01] });
+  });
+});
+
+// ============================================================
+// SETTINGS EXPORT / IMPORT TESTS (74-75)
+// ============================================================
+
+import { importSettings } from '../lib/tauri-bridge';
+
+describe('importSettings', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('74. previews changes without applying them', async () => {
+    const preview = {
+      changes: [{ key: 'minimize_to_tray', current: true, imported: false }],
+      warnings: ['unknown setting skipped: quiet_hours'],
+      secrets: ['app_lock_pin'],
+      applied: false,
+    };
+    vi.mocked(invoke).mockResolvedValue(preview);
+
+    const result = await importSettings('/home/u/settings.json', true);
+
+    expect(invoke).toHaveBeenCalledWith('import_settings', { path: '/home/u/settings.json', dryRun: true });
+    expect(result.applied).toBe(false);
+    expect(result.warnings).toHaveLength(1);
+  });
+
+  test('75. surfaces an invalid value', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'interval_hours must be at least 1' });
+
+    await expectAppError(importSettings('/home/u/settings.json', false), 'Validation', /interval_hours/);
+  });
+});
+
+// ============================================================
+// TELEMETRY DIAGNOSTICS TESTS (76)
+// ============================================================
+
+import { telemetryDiagnostics } from '../lib/tauri-bridge';
+
+describe('telemetryDiagnostics', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('76. returns the report from a single command', async () => {
+    const report = {
+      queue_depth: 12,
+      retrying_depth: 3,
+      dead_letter_depth: 1,
+      circuit_breaker: 'closed',
+      opted_out: false,
+      last_flush_at_ms: null,
+      send_in_flight: false,
+      has_api_key: true,
+      proxy_configured: false,
+      tls_pinned: false,
+      api_host: 'https://eu.i.posthog.com',
+    };
+    vi.mocked(invoke).mockResolvedValue(report);
+
+    const result = await telemetryDiagnostics();
+
+    expect(invoke).toHaveBeenCalledTimes(1);
+    expect(invoke).toHaveBeenCalledWith('telemetry_diagnostics');
+    expect(result).toEqual(report);
+  });
+});
+
+// ============================================================
+// PROJECT SETTINGS TESTS (77-78)
+// ============================================================
+
+import { projectSetSetting } from '../lib/tauri-bridge';
+
+describe('projectSetSetting', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('77. returns the normalized value', async () => {
+    vi.mocked(invoke).mockResolvedValue('TF');
+
+    const stored = await projectSetSetting('/p/backlog.db', 'ticket_prefix', 'tf');
+
+    expect(invoke).toHaveBeenCalledWith('project_set_setting', {
+      dbPath: '/p/backlog.db',
+      key: 'ticket_prefix',
+      value: 'tf',
+    });
+    expect(stored).toBe('TF');
+  });
+
+  test('78. surfaces an invalid column order', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'invalid column_order: duplicate column Done' });
+
+    await expectAppError(
+      projectSetSetting('/p/backlog.db', 'column_order', ['Done', 'Done']),
+      'Validation',
+      /duplicate column/
+    );
+  });
+});
+
+// ============================================================
+// AUTOMATIC COMPACTION TESTS (79-80)
+// ============================================================
+
+import { checkProjectCompaction, setCompactionSettings } from '../lib/tauri-bridge';
+
+describe('automatic compaction', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('79. checkProjectCompaction reports the free pages and schedule', async () => {
+    const check = {
+      size_bytes: 8 * 1024 * 1024,
+      page_count: 2048,
+      freelist_count: 1024,
+      free_percent: 50,
+      scheduled: true,
+    };
+    vi.mocked(invoke).mockResolvedValue(check);
+
+    const result = await checkProjectCompaction('/p/backlog.db');
+
+    expect(invoke).toHaveBeenCalledWith('check_project_compaction', { dbPath: '/p/backlog.db' });
+    expect(result).toEqual(check);
+  });
+
+  test('80. setCompactionSettings surfaces an out-of-range threshold', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'free_percent must be between 5 and 95' });
+
+    await expectAppError(
+      setCompactionSettings({ enabled: true, free_percent: 100, min_size_mb: 4 }),
+      'Validation',
+      /free_percent/
+    );
+  });
+});
+
+// ============================================================
+// WAL CHECKPOINT TESTS (81-82)
+// ============================================================
+
+import { checkpointProjectDb } from '../lib/tauri-bridge';
+
+describe('checkpointProjectDb', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('81. reports the checkpointed frames', async () => {
+    const result = { wal_frames: 120, checkpointed_frames: 120, busy: false, attempts: 1 };
+    vi.mocked(invoke).mockResolvedValue(result);
+
+    const report = await checkpointProjectDb('/p/backlog.db');
+
+    expect(invoke).toHaveBeenCalledWith('checkpoint_project_db', { dbPath: '/p/backlog.db' });
+    expect(report).toEqual(result);
+  });
+
+  test('82. reports a database that stayed busy', async () => {
+    vi.mocked(invoke).mockResolvedValue({
+      wal_frames: 80,
+      checkpointed_frames: 40,
+      busy: true,
+      attempts: 5,
+    });
+
+    const report = await checkpointProjectDb('/p/backlog.db');
+
+    expect(report.busy).toBe(true);
+    expect(report.checkpointed_frames).toBeLessThan(report.wal_frames);
+  });
+});
+
+// ============================================================
+// READ-ONLY PROJECT TESTS (83-84)
+// ============================================================
+
+import { openProjectProbe } from '../lib/tauri-bridge';
+
+describe('openProjectProbe', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('83. reports a writable project', async () => {
+    vi.mocked(invoke).mockResolvedValue({ writable: true, reason: null });
+
+    const probe = await openProjectProbe('/p/backlog.db');
+
+    expect(invoke).toHaveBeenCalledWith('open_project_probe', { dbPath: '/p/backlog.db' });
+    expect(probe).toEqual({ writable: true, reason: null });
+  });
+
+  test('84. reports why a project is read-only', async () => {
+    vi.mocked(invoke).mockResolvedValue({
+      writable: false,
+      reason: 'the project folder is not writable (Read-only file system (os error 30))',
+    });
+
+    const probe = await openProjectProbe('/mnt/share/backlog.db');
+
+    expect(probe.writable).toBe(false);
+    expect(probe.reason).toMatch(/not writable/);
+  });
+});
+
+// ============================================================
+// APP DATA MIGRATION TESTS (85-86)
+// ============================================================
+
+import { getAppDataOverride, migrateAppData } from '../lib/tauri-bridge';
+
+describe('app data migration', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('85. migrateAppData surfaces a non-empty target', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: '/mnt/ssd/ticketflow is not empty' });
+
+    await expectAppError(migrateAppData('/mnt/ssd/ticketflow'), 'Validation', /not empty/);
+    expect(invoke).toHaveBeenCalledWith('migrate_app_data', { newDir: '/mnt/ssd/ticketflow' });
+  });
+
+  test('86. getAppDataOverride returns null without an override', async () => {
+    vi.mocked(invoke).mockResolvedValue(null);
+
+    await expect(getAppDataOverride()).resolves.toBeNull();
+    expect(invoke).toHaveBeenCalledWith('get_app_data_override');
+  });
+});
+
+// ============================================================
+// GITHUB IMPORT TESTS (87-88)
+// ============================================================
+
+import { githubImport, setGithubToken } from '../lib/tauri-bridge';
+
+describe('GitHub import', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('87. githubImport passes the repository and options', async () => {
+    const report = {
+      created: 2,
+      updated: 1,
+      skipped: 3,
+      created_ids: ['BUG-004', 'BUG-005'],
+      updated_ids: ['BUG-001'],
+      problems: [],
+      new_sections: ['Closed'],
+      rate_limit_waits: 0,
+    };
+    vi.mocked(invoke).mockResolvedValue(report);
+
+    await expect(githubImport('/p/backlog.db', 'acme/widgets', { labels: { bug: 'BUG' } })).resolves.toEqual(report);
+    expect(invoke).toHaveBeenCalledWith('github_import', {
+      dbPath: '/p/backlog.db',
+      repo: 'acme/widgets',
+      options: { labels: { bug: 'BUG' } },
+    });
+  });
+
+  test('88. setGithubToken(null) forgets the token', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await setGithubToken(null);
+    expect(invoke).toHaveBeenCalledWith('set_github_token', { token: null });
+  });
+});
+
+// ============================================================
+// WAL AUTOCHECKPOINT TESTS (89-90)
+// ============================================================
+
+import { dbGetWalAutocheckpoint, dbSetWalAutocheckpoint } from '../lib/tauri-bridge';
+
+describe('WAL autocheckpoint', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('89. sets the threshold and reads it back', async () => {
+    vi.mocked(invoke).mockResolvedValueOnce(undefined).mockResolvedValueOnce(200);
+
+    await dbSetWalAutocheckpoint('/p/backlog.db', 200);
+    const pages = await dbGetWalAutocheckpoint('/p/backlog.db');
+
+    expect(invoke).toHaveBeenNthCalledWith(1, 'db_set_wal_autocheckpoint', { dbPath: '/p/backlog.db', pages: 200 });
+    expect(invoke).toHaveBeenNthCalledWith(2, 'db_get_wal_autocheckpoint', { dbPath: '/p/backlog.db' });
+    expect(pages).toBe(200);
+  });
+
+  test('90. surfaces an out-of-range threshold', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Validation', message: 'pages must be between 1 and 100000' });
+
+    await expectAppError(dbSetWalAutocheckpoint('/p/backlog.db', 0), 'Validation', /between 1 and 100000/);
+  });
+});
+
+// ============================================================
+// GITLAB IMPORT TESTS (91-92)
+// ============================================================
+
+import { gitlabImport } from '../lib/tauri-bridge';
+
+describe('GitLab import', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('91. gitlabImport passes the instance and project', async () => {
+    vi.mocked(invoke).mockResolvedValue({
+      created: 1,
+      updated: 0,
+      skipped: 0,
+      created_ids: ['CT-010'],
+      updated_ids: [],
+      problems: [],
+      new_sections: [],
+      rate_limit_waits: 0,
+    });
+
+    const report = await gitlabImport('/p/backlog.db', 'https://git.corp.example', 'team/app');
+
+    expect(invoke).toHaveBeenCalledWith('gitlab_import', {
+      dbPath: '/p/backlog.db',
+      baseUrl: 'https://git.corp.example',
+      projectId: 'team/app',
+      options: {},
+    });
+    expect(report.created_ids).toEqual(['CT-010']);
+  });
+
+  test('92. surfaces an untrusted certificate as a typed error', async () => {
+    const error = { kind: 'Certificate', message: 'git.corp.example presented a certificate that is not trusted' };
+    vi.mocked(invoke).mockRejectedValue(error);
+
+    await expect(gitlabImport('/p/backlog.db', 'https://git.corp.example', '42')).rejects.toEqual(error);
+  });
+});
+
+// ============================================================
+// INDEX MANAGEMENT TESTS (93-95)
+// ============================================================
+
+import { dbCreateIndex, dbDropIndex, dbListIndexes } from '../lib/tauri-bridge';
+
+describe('index management', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('93. dbCreateIndex returns the generated name', async () => {
+    vi.mocked(invoke).mockResolvedValue('idx_backlog_items_priority_effort');
+
+    await expect(dbCreateIndex('/p/backlog.db', 'backlog_items', ['priority', 'effort'])).resolves.toBe(
+      'idx_backlog_items_priority_effort'
+    );
+    expect(invoke).toHaveBeenCalledWith('db_create_index', {
+      dbPath: '/p/backlog.db',
+      table: 'backlog_items',
+      columns: ['priority', 'effort'],
+      unique: false,
+    });
+  });
+
+  test('94. surfaces rejected injection attempts', async () => {
+    const attempts: [string, string[]][] = [
+      ['backlog_items; DROP TABLE projects', ['title']],
+      ['backlog_items', ['title") ; DROP TABLE projects; --']],
+      ['sqlite_master', ['name']],
+    ];
+    for (const [table, columns] of attempts) {
+      vi.mocked(invoke).mockRejectedValueOnce({ kind: 'Validation', message: 'invalid identifier' });
+      await expect(dbCreateIndex('/p/backlog.db', table, columns)).rejects.toMatchObject({ kind: 'Validation' });
+    }
+    vi.mocked(invoke).mockRejectedValueOnce({ kind: 'Validation', message: 'invalid identifier' });
+    await expect(dbDropIndex('/p/backlog.db', 'x"; DROP TABLE projects; --')).rejects.toMatchObject({
+      kind: 'Validation',
+    });
+    expect(invoke).toHaveBeenCalledTimes(4);
+  });
+
+  test('95. dbListIndexes tells user indexes from schema ones', async () => {
+    vi.mocked(invoke).mockResolvedValue([
+      { name: 'idx_items_section', table: 'backlog_items', columns: ['section_id'], unique: false, user_managed: false },
+      { name: 'idx_backlog_items_effort', table: 'backlog_items', columns: ['effort'], unique: false, user_managed: true },
+    ]);
+
+    const indexes = await dbListIndexes('/p/backlog.db');
+
+    expect(invoke).toHaveBeenCalledWith('db_list_indexes', { dbPath: '/p/backlog.db' });
+    expect(indexes.filter((index) => index.user_managed).map((index) => index.name)).toEqual([
+      'idx_backlog_items_effort',
+    ]);
+  });
+});
+
+// ============================================================
+// JIRA CLOUD IMPORT TESTS (96-97)
+// ============================================================
+
+import { jiraImport, listenJiraFetchProgress } from '../lib/tauri-bridge';
+
+describe('Jira Cloud import', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('96. jiraImport passes the JQL and dry-run flag', async () => {
+    vi.mocked(invoke).mockResolvedValue({ dry_run: true, total: 340, preview: [] });
+
+    const report = await jiraImport('/p/backlog.db', 'https://acme.atlassian.net', 'project = APP', { dry_run: true });
+
+    expect(invoke).toHaveBeenCalledWith('jira_import', {
+      dbPath: '/p/backlog.db',
+      siteUrl: 'https://acme.atlassian.net',
+      jql: 'project = APP',
+      options: { dry_run: true },
+    });
+    expect(report.total).toBe(340);
+  });
+
+  test('97. listenJiraFetchProgress forwards the payload', async () => {
+    let handler: ((event: { payload: unknown }) => void) | undefined;
+    vi.mocked(listen).mockImplementation(async (_event, cb) => {
+      handler = cb as typeof handler;
+      return () => {};
+    });
+    const callback = vi.fn();
+
+    await listenJiraFetchProgress(callback);
+    handler?.({ payload: { fetched: 200, total: 340 } });
+
+    expect(listen).toHaveBeenCalledWith('jira:fetch-progress', expect.any(Function));
+    expect(callback).toHaveBeenCalledWith({ fetched: 200, total: 340 });
+  });
+});
+
+// ============================================================
+// WEBHOOK TESTS (98-99)
+// ============================================================
+
+import { createWebhook, fireWebhook, listWebhookDeliveries } from '../lib/tauri-bridge';
+
+describe('Webhooks', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('98. createWebhook and fireWebhook pass their arguments', async () => {
+    vi.mocked(invoke).mockResolvedValueOnce({
+      id: 1,
+      url: 'https://hooks.example.com/t',
+      events: ['ticket.status_changed'],
+      enabled: true,
+      created_at: 1,
+    });
+    vi.mocked(invoke).mockResolvedValueOnce([7]);
+
+    await createWebhook({
+      url: 'https://hooks.example.com/t',
+      secret: 's3cret',
+      events: ['ticket.status_changed'],
+      enabled: true,
+    });
+    const ids = await fireWebhook('ticket.status_changed', { id: 'BUG-001', status: 'done' });
+
+    expect(invoke).toHaveBeenNthCalledWith(1, 'create_webhook', {
+      webhook: {
+        url: 'https://hooks.example.com/t',
+        secret: 's3cret',
+        events: ['ticket.status_changed'],
+        enabled: true,
+      },
+    });
+    expect(invoke).toHaveBeenNthCalledWith(2, 'fire_webhook', {
+      eventType: 'ticket.status_changed',
+      payload: { id: 'BUG-001', status: 'done' },
+    });
+    expect(ids).toEqual([7]);
+  });
+
+  test('99. listWebhookDeliveries returns failed attempts', async () => {
+    vi.mocked(invoke).mockResolvedValue([
+      {
+        id: 7,
+        webhook_id: 1,
+        event_type: 'ticket.status_changed',
+        body: '{}',
+        status: 'pending',
+        retry_count: 1,
+        next_attempt_at: 30_000,
+        response_status: null,
+        last_error: 'refused redirect to non-https URL http://hooks.example.com/t',
+        created_at: 0,
+        updated_at: 0,
+      },
+    ]);
+
+    const deliveries = await listWebhookDeliveries(1);
+
+    expect(invoke).toHaveBeenCalledWith('list_webhook_deliveries', { webhookId: 1 });
+    expect(deliveries[0].last_error).toContain('non-https');
+  });
+});
+
+// ============================================================
+// TELEMETRY POOL HEALTH TESTS (100-101)
+// ============================================================
+
+import { getPoolHealth } from '../lib/tauri-bridge';
+
+describe('getPoolHealth', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('100. reports a healthy pool', async () => {
+    vi.mocked(invoke).mockResolvedValue({
+      healthy: true,
+      last_health_check_at: 1_700_000_000_000,
+      last_reconnect_at: null,
+      connections: 1,
+    });
+
+    const health = await getPoolHealth();
+
+    expect(invoke).toHaveBeenCalledWith('get_pool_health');
+    expect(health.healthy).toBe(true);
+    expect(health.last_reconnect_at).toBeNull();
+  });
+
+  test('101. reports a pool that could not be reconnected', async () => {
+    vi.mocked(invoke).mockResolvedValue({
+      healthy: false,
+      last_health_check_at: 1_700_000_000_000,
+      last_reconnect_at: null,
+      connections: 0,
+    });
+
+    const health = await getPoolHealth();
+
+    expect(health.healthy).toBe(false);
+  });
+});
+
+// ============================================================
+// SLACK NOTIFICATION TESTS (102-103)
+// ============================================================
+
+import { slackConfigure, slackNotify, slackTest } from '../lib/tauri-bridge';
+
+describe('Slack notifications', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('102. slackConfigure and slackNotify pass their arguments', async () => {
+    const rules = [{ event_type: 'ticket.blocked', enabled: true, template: ':no_entry: *{id}* {title} is blocked' }];
+    vi.mocked(invoke).mockResolvedValueOnce({ configured: true, rules });
+    vi.mocked(invoke).mockResolvedValueOnce(true);
+
+    await slackConfigure('https://hooks.slack.com/services/T000/B000/XXXX', rules);
+    const posted = await slackNotify({
+      event_type: 'ticket.blocked',
+      project_name: 'App',
+      project_path: '/p',
+      ticket_id: 'BUG-001',
+      title: 'Login fails',
+      status: 'Blocked',
+    });
+
+    expect(invoke).toHaveBeenNthCalledWith(1, 'slack_configure', {
+      webhookUrl: 'https://hooks.slack.com/services/T000/B000/XXXX',
+      rules,
+    });
+    expect(invoke).toHaveBeenNthCalledWith(2, 'slack_notify', {
+      event: expect.objectContaining({ event_type: 'ticket.blocked', ticket_id: 'BUG-001' }),
+    });
+    expect(posted).toBe(true);
+  });
+
+  test('103. slackTest surfaces the rejection reason', async () => {
+    vi.mocked(invoke).mockRejectedValue({ kind: 'Network', message: 'Slack returned HTTP 404 Not Found: no_service' });
+
+    await expect(slackTest()).rejects.toMatchObject({ kind: 'Network', message: expect.stringContaining('no_service') });
+    expect(invoke).toHaveBeenCalledWith('slack_test');
+  });
+});
+
+// ============================================================
+// WINDOW VIBRANCY TESTS (104-105)
+// ============================================================
+
+import { getWindowVibrancy, setWindowVibrancy } from '../lib/tauri-bridge';
+
+describe('Window vibrancy', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('104. setWindowVibrancy passes each effect', async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    for (const effect of ['none', 'blur', 'acrylic', 'mica', 'sidebar'] as const) {
+      await setWindowVibrancy(effect);
+      expect(invoke).toHaveBeenLastCalledWith('set_window_vibrancy', { effect });
+    }
+    expect(invoke).toHaveBeenCalledTimes(5);
+  });
+
+  test('105. getWindowVibrancy returns the saved effect', async () => {
+    vi.mocked(invoke).mockResolvedValue('mica');
+
+    await expect(getWindowVibrancy()).resolves.toBe('mica');
+    expect(invoke).toHaveBeenCalledWith('get_window_vibrancy');
+  });
+});
+
+// ============================================================
+// EMAIL TESTS (106-107)
+// ============================================================
+
+import { emailConfigure, emailSendTicket, emailTest } from '../lib/tauri-bridge';
+
+describe('Email', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  test('106. emailConfigure then emailSendTicket pass their arguments', async () => {
+    const smtp = {
+      host: 'smtp.example.com',
+      port: 587,
+      tls: 'starttls' as const,
+      from: 'Consulting <me@example.com>',
+      username: 'me@example.com',
+      password: 'secret',
+    };
+    vi.mocked(invoke).mockResolvedValueOnce({ settings: { ...smtp, password: undefined }, password_set: true });
+    vi.mocked(invoke).mockResolvedValueOnce({ message_id: '<1.abc@example.com>', attachments: 1, omitted: [] });
+
+    await emailConfigure(smtp);
+    const sent = await emailSendTicket('/p/backlog.db', 'BUG-001', ['client@example.org'], {
+      include_attachments: true,
+    });
+
+    expect(invoke).toHaveBeenNthCalledWith(1, 'email_configure', { smtp });
+    expect(invoke).toHaveBeenNthCalledWith(2, 'email_send_ticket', {
+      dbPath: '/p/backlog.db',
+      ticketId: 'BUG-001',
+      to: ['client@example.org'],
+      options: { include_attachments: true },
+    });
+    expect(sent.message_id).toBe('<1.abc@example.com>');
+  });
+
+  test('107. emailTest surfaces refused credentials', async () => {
+    vi.mocked(invoke).mockRejectedValue({
+      kind: 'Unauthorized',
+      message: 'the SMTP server refused the credentials: 535 5.7.8 authentication failed',
+    });
+
+    await expect(emailTest()).rejects.toMatchObject({ kind: 'Unauthorized' });
+    expect(invoke).toHaveBeenCalledWith('email_test');
+  });
+});
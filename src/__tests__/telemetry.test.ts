@@ -13,6 +13,8 @@
  * 7. shouldPromptConsent() returns false when consent already granted
  * 8. getDeviceId() generates and persists a UUID
  * 9. track() batches multiple events in a single flush
+ * 10. shouldPromptConsent() returns false when consent is declined
+ * 11. setConsentState() mirrors the decision to the backend opt-out
  */
 
 import { describe, test, expect, vi, beforeEach, afterEach } from 'vitest';
@@ -193,6 +195,7 @@ describe('Telemetry — Consent Gate', () => {
     const mockInvoke = vi.mocked(invoke);
 
     telemetry.setConsentState('granted');
+    mockInvoke.mockClear(); // the consent is mirrored to the backend
 
     // Fire 3 events before the 100ms timer fires
     telemetry.track('event_one', { n: 1 });
@@ -238,4 +241,15 @@ describe('Telemetry — shouldPromptConsent with declined state', () => {
     telemetry.setConsentState('declined');
     expect(telemetry.shouldPromptConsent()).toBe(false);
   });
+
+  test('11. setConsentState() mirrors the decision to the backend opt-out', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    const mockInvoke = vi.mocked(invoke);
+
+    telemetry.setConsentState('declined');
+    expect(mockInvoke).toHaveBeenCalledWith('set_telemetry_opt_out', { optOut: true });
+
+    telemetry.setConsentState('granted');
+    expect(mockInvoke).toHaveBeenCalledWith('set_telemetry_opt_out', { optOut: false });
+  });
 });
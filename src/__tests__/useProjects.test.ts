@@ -21,6 +21,7 @@ vi.mock('../lib/tauri-bridge', () => ({
   fileExists: vi.fn(),
   writeTextFileContents: vi.fn(),
   readTextFileContents: vi.fn(),
+  renameProject: vi.fn(),
 }));
 
 import { useProjects } from '../hooks/useProjects';
@@ -249,6 +250,7 @@ import {
   writeTextFileContents,
   readTextFileContents,
   joinPath,
+  renameProject,
 } from '../lib/tauri-bridge';
 
 describe('useProjects - scanForBacklog Tauri Mode', () => {
@@ -636,3 +638,57 @@ describe('useProjects - validateProject', () => {
     expect(isValid).toBe(false);
   });
 });
+
+// ============================================================
+// RENAMEPROJECT TESTS (29-30)
+// ============================================================
+
+describe('useProjects - renameProject', () => {
+  beforeEach(() => {
+    localStorage.clear();
+    vi.clearAllMocks();
+    vi.mocked(isTauri).mockReturnValue(true);
+  });
+
+  test('29. renameProject updates the stored project and last file', async () => {
+    vi.mocked(renameProject).mockResolvedValue('/projects/Renamed');
+    localStorage.setItem(PROJECTS_STORAGE_KEY, JSON.stringify([{
+      id: 'test-id',
+      name: 'Old',
+      path: '/projects/Old',
+      backlogFile: BACKLOG_FILE_NAME,
+      lastOpened: Date.now(),
+    }]));
+    localStorage.setItem('ticketflow-last-file', `/projects/Old/${BACKLOG_FILE_NAME}`);
+
+    const { result } = renderHook(() => useProjects());
+
+    await act(async () => {
+      await result.current.renameProject('test-id', 'Renamed');
+    });
+
+    expect(renameProject).toHaveBeenCalledWith('/projects/Old', 'Renamed');
+    expect(result.current.projects[0].name).toBe('Renamed');
+    expect(result.current.projects[0].path).toBe('/projects/Renamed');
+    expect(localStorage.getItem('ticketflow-last-file')).toBe(`/projects/Renamed/${BACKLOG_FILE_NAME}`);
+  });
+
+  test('30. renameProject leaves the project unchanged when the backend refuses', async () => {
+    vi.mocked(renameProject).mockRejectedValue(new Error('project is open'));
+    localStorage.setItem(PROJECTS_STORAGE_KEY, JSON.stringify([{
+      id: 'test-id',
+      name: 'Old',
+      path: '/projects/Old',
+      backlogFile: BACKLOG_FILE_NAME,
+      lastOpened: Date.now(),
+    }]));
+
+    const { result } = renderHook(() => useProjects());
+
+    await act(async () => {
+      await expect(result.current.renameProject('test-id', 'Renamed')).rejects.toThrow('project is open');
+    });
+
+    expect(result.current.projects[0].path).toBe('/projects/Old');
+  });
+});
@@ -18,6 +18,8 @@ import { renderHookWithProviders as renderHook, act } from '../test-utils/test-w
 // Mock tauri-bridge
 vi.mock('../lib/tauri-bridge', () => ({
   isTauri: vi.fn(() => false),
+  getStorageMode: vi.fn(() => Promise.resolve({ mode: 'installed', dataDir: '' })),
+  setTrayUpdateAvailable: vi.fn(() => Promise.resolve()),
 }));
 
 // Mock Tauri updater plugin
@@ -9,6 +9,18 @@
 
 import Database from '@tauri-apps/plugin-sql';
 import { runMigrations } from './migrations';
+import {
+  acquireProjectLock,
+  checkpointProjectDb,
+  checkProjectCompaction,
+  isProjectEncrypted,
+  openEncryptedProject,
+  openProjectProbe,
+  preflightProjectMigration,
+  releaseProjectLock,
+  unwatchProject,
+  watchProject,
+} from '../lib/tauri-bridge';
 
 /** The current database instance (singleton) */
 let db: Database | null = null;
@@ -270,7 +282,10 @@ export async function getDatabase(projectPath: string): Promise<Database> {
   // Close existing connection if switching projects
   if (db && currentPath !== projectPath) {
     connectionLock = (async () => {
+      await checkpointProjectDb(`${currentPath}/backlog.db`).catch(console.warn);
       await db!.close();
+      await unwatchProject().catch(console.warn);
+      await releaseProjectLock(`${currentPath}/backlog.db`).catch(console.warn);
       db = null;
       currentPath = null;
     })();
@@ -279,8 +294,36 @@ export async function getDatabase(projectPath: string): Promise<Database> {
   }
 
   if (!db) {
+    // A project on a read-only share or with wrong permissions cannot take
+    // the lock; the backend emits project:read-only for the banner. The
+    // probe of an encrypted project fails until it is unlocked.
+    const probe = await openProjectProbe(`${projectPath}/backlog.db`).catch((error) => {
+      console.warn(error);
+      return null;
+    });
+
+    // Refuse to open a project another machine is writing to (e.g. through
+    // a synced folder). Rejects with an AppError of kind 'InUse'.
+    if (probe?.writable !== false) {
+      await acquireProjectLock(`${projectPath}/backlog.db`);
+    }
+
+    // Encrypted projects need a keyed pool, which the backend registers
+    // with tauri-plugin-sql; Database.load() cannot pass a key.
+    // Rejects when no passphrase has been entered or stored.
+    const encrypted = await isProjectEncrypted(`${projectPath}/backlog.db`);
+    if (encrypted) {
+      await openEncryptedProject(`${projectPath}/backlog.db`);
+    }
+
+    // Snapshot the file before tauri-plugin-sql opens it if this build
+    // will migrate it (see runMigrations)
+    if (!initializedPaths.has(projectPath)) {
+      await preflightProjectMigration(`${projectPath}/backlog.db`);
+    }
+
     const dbPath = `sqlite:${projectPath}/backlog.db`;
-    db = await Database.load(dbPath);
+    db = encrypted ? Database.get(dbPath) : await Database.load(dbPath);
     currentPath = projectPath;
 
     // Enforce PRAGMAs on every new connection
@@ -302,6 +345,11 @@ export async function getDatabase(projectPath: string): Promise<Database> {
       await runMigrations(db);
       initializedPaths.add(projectPath);
     }
+
+    // Our own writes go through the WAL, so they are not reported
+    await watchProject(`${projectPath}/backlog.db`).catch(console.warn);
+    // Schedules an automatic compaction when the file is mostly free pages
+    await checkProjectCompaction(`${projectPath}/backlog.db`).catch(console.warn);
   }
 
   return db;
@@ -327,6 +375,8 @@ export async function closeDatabase(): Promise<void> {
   if (db) {
     connectionLock = (async () => {
       await db!.close();
+      await unwatchProject().catch(console.warn);
+      await releaseProjectLock(`${currentPath}/backlog.db`).catch(console.warn);
       db = null;
       currentPath = null;
     })();
@@ -13,6 +13,7 @@
  */
 
 import type Database from '@tauri-apps/plugin-sql';
+import { setMigrationInProgress } from '../lib/tauri-bridge';
 
 interface Migration {
   version: number;
@@ -180,6 +181,141 @@ const MIGRATIONS: Migration[] = [
       `);
     },
   },
+  {
+    version: 8,
+    description: 'Add item_external_refs table for imported items',
+    up: async (db) => {
+      // Links an item to its key in the tool it was imported from (e.g. Jira
+      // PROJ-123), so re-running an import skips what is already there
+      await db.execute(`
+        CREATE TABLE IF NOT EXISTS item_external_refs (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          project_id INTEGER NOT NULL,
+          item_id TEXT NOT NULL,
+          source TEXT NOT NULL,
+          external_key TEXT NOT NULL,
+          created_at TEXT DEFAULT (datetime('now')),
+          FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+          UNIQUE(project_id, source, external_key)
+        )
+      `);
+      await db.execute('CREATE INDEX IF NOT EXISTS idx_external_refs_item ON item_external_refs(item_id)');
+    },
+  },
+  {
+    version: 9,
+    description: 'Add recurrences table for recurring tickets',
+    up: async (db) => {
+      // Template of a ticket the backend scheduler creates on an RRULE-like
+      // schedule (see src-tauri/src/recurrence.rs). next_run and starts_at
+      // are Unix milliseconds.
+      await db.execute(`
+        CREATE TABLE IF NOT EXISTS recurrences (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          project_id INTEGER NOT NULL,
+          type TEXT NOT NULL,
+          title TEXT NOT NULL,
+          section TEXT,
+          emoji TEXT,
+          component TEXT,
+          module TEXT,
+          severity TEXT,
+          priority TEXT,
+          effort TEXT,
+          description TEXT,
+          user_story TEXT,
+          rule TEXT NOT NULL,
+          catch_up TEXT NOT NULL DEFAULT 'latest' CHECK (catch_up IN ('all', 'latest', 'skip')),
+          starts_at INTEGER NOT NULL,
+          next_run INTEGER NOT NULL,
+          created_at TEXT DEFAULT (datetime('now')),
+          FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )
+      `);
+      await db.execute('CREATE INDEX IF NOT EXISTS idx_recurrences_next_run ON recurrences(next_run)');
+    },
+  },
+  {
+    version: 10,
+    description: 'Add time_entries table for ticket timers',
+    up: async (db) => {
+      // Written by the backend timer (see src-tauri/src/timer.rs) when a
+      // timer stops. Times are Unix milliseconds; duration_ms excludes
+      // paused_ms.
+      await db.execute(`
+        CREATE TABLE IF NOT EXISTS time_entries (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          project_id INTEGER NOT NULL,
+          item_id TEXT NOT NULL,
+          started_at INTEGER NOT NULL,
+          ended_at INTEGER NOT NULL,
+          duration_ms INTEGER NOT NULL,
+          paused_ms INTEGER NOT NULL DEFAULT 0,
+          created_at TEXT DEFAULT (datetime('now')),
+          FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )
+      `);
+      await db.execute('CREATE INDEX IF NOT EXISTS idx_time_entries_item ON time_entries(item_id, started_at)');
+    },
+  },
+  {
+    version: 11,
+    description: 'Add attachments table for deduplicated ticket files',
+    up: async (db) => {
+      // Metadata of files attached to tickets. The content lives once per
+      // project under <data dir>/attachments/, addressed by sha256 (see
+      // src-tauri/src/attachments.rs); rows sharing a hash share the file.
+      await db.execute(`
+        CREATE TABLE IF NOT EXISTS attachments (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          project_id INTEGER NOT NULL,
+          item_id TEXT NOT NULL,
+          filename TEXT NOT NULL,
+          mime_type TEXT NOT NULL,
+          size_bytes INTEGER NOT NULL,
+          sha256 TEXT NOT NULL,
+          created_at TEXT DEFAULT (datetime('now')),
+          FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )
+      `);
+      await db.execute('CREATE INDEX IF NOT EXISTS idx_attachments_item ON attachments(item_id)');
+      await db.execute('CREATE INDEX IF NOT EXISTS idx_attachments_hash ON attachments(project_id, sha256)');
+    },
+  },
+  {
+    version: 12,
+    description: 'Add attachments_fts index for attachment text',
+    up: async (db) => {
+      // Text of plain-text attachments, keyed by attachments.id and written
+      // by the backend when a file is attached (see src-tauri/src/search.rs).
+      // Files attached before this version are not indexed.
+      await db.execute(`
+        CREATE VIRTUAL TABLE IF NOT EXISTS attachments_fts USING fts5(
+          attachment_text,
+          tokenize='unicode61 remove_diacritics 2'
+        )
+      `);
+    },
+  },
+  {
+    version: 13,
+    description: 'Add project_settings table',
+    up: async (db) => {
+      // Per-project settings (default assignee, ticket prefix, column
+      // order...), values stored as JSON text. Written by the backend (see
+      // src-tauri/src/project_settings.rs); updated_at is Unix milliseconds.
+      await db.execute(`
+        CREATE TABLE IF NOT EXISTS project_settings (
+          project_id INTEGER NOT NULL,
+          key TEXT NOT NULL,
+          value TEXT NOT NULL,
+          updated_at INTEGER NOT NULL,
+          PRIMARY KEY (project_id, key),
+          FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        )
+      `);
+    },
+  },
 ];
 
 /**
@@ -200,12 +336,18 @@ export async function runMigrations(db: Database): Promise<void> {
     return; // Already up to date
   }
 
-  for (const migration of MIGRATIONS) {
-    if (migration.version > currentVersion) {
-      console.log(`[migrations] Running v${migration.version}: ${migration.description}`);
-      await migration.up(db);
-      await db.execute(`PRAGMA user_version = ${migration.version}`);
+  // Keep scheduled backups from snapshotting a half-migrated database
+  await setMigrationInProgress(true);
+  try {
+    for (const migration of MIGRATIONS) {
+      if (migration.version > currentVersion) {
+        console.log(`[migrations] Running v${migration.version}: ${migration.description}`);
+        await migration.up(db);
+        await db.execute(`PRAGMA user_version = ${migration.version}`);
+      }
     }
+  } finally {
+    await setMigrationInProgress(false);
   }
 
   console.log(`[migrations] Schema at version ${targetVersion}`);
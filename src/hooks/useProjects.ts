@@ -20,7 +20,9 @@ import {
   fileExists,
   writeTextFileContents,
   readTextFileContents,
+  renameProject as renameProjectDirectory,
 } from '../lib/tauri-bridge';
+import { STORAGE_KEYS } from '../constants/storage';
 import { useTranslation } from '../i18n';
 
 // Generate unique ID
@@ -83,6 +85,8 @@ export interface UseProjectsReturn {
   toggleFavorite: (id: string) => void;
   /** Check if a project's path still exists */
   validateProject: (project: Project) => Promise<boolean>;
+  /** Rename a project's directory (must be closed) and update its entry */
+  renameProject: (id: string, newName: string) => Promise<Project | null>;
 }
 
 export function useProjects(): UseProjectsReturn {
@@ -373,6 +377,35 @@ ${sortedTypes.map(t => `- **${t.id}-XXX** : ${t.label}`).join('\n')}
     }
   }, [isTauriMode]);
 
+  /**
+   * Rename a project's directory on disk, then update the stored entry and
+   * the last opened file if it lived in that directory.
+   * Throws when the backend refuses (name taken, project still open...).
+   */
+  const renameProject = useCallback(async (id: string, newName: string): Promise<Project | null> => {
+    if (!isTauriMode) {
+      setError(t.error.tauriRequired);
+      return null;
+    }
+
+    const currentProjects = loadProjectsFromStorage();
+    const project = currentProjects.find(p => p.id === id);
+    if (!project) return null;
+
+    const newPath = await renameProjectDirectory(project.path, newName);
+    const renamed: Project = { ...project, name: newName, path: newPath };
+    const next = currentProjects.map(p => (p.id === id ? renamed : p));
+    saveProjectsToStorage(next);
+    setProjects(next);
+
+    const lastFile = localStorage.getItem(STORAGE_KEYS.TAURI_LAST_FILE);
+    if (lastFile?.startsWith(project.path)) {
+      localStorage.setItem(STORAGE_KEYS.TAURI_LAST_FILE, newPath + lastFile.slice(project.path.length));
+    }
+
+    return renamed;
+  }, [isTauriMode, t]);
+
   return {
     projects,
     isLoading,
@@ -387,5 +420,6 @@ ${sortedTypes.map(t => `- **${t.id}-XXX** : ${t.label}`).join('\n')}
     toggleFavorite,
     loadProjectContent,
     validateProject,
+    renameProject,
   };
 }
@@ -9,7 +9,7 @@
  */
 
 import { useState, useEffect, useCallback, useRef } from 'react';
-import { isTauri } from '../lib/tauri-bridge';
+import { isTauri, getStorageMode, setTrayUpdateAvailable } from '../lib/tauri-bridge';
 import { useTranslation } from '../i18n';
 
 // Types for the updater plugin
@@ -54,6 +54,16 @@ const CHECK_INTERVAL_MS = 4 * 60 * 60 * 1000;
 // localStorage key for dismiss persistence
 const STORAGE_KEY = 'ticketflow-update-dismissed';
 
+// Portable builds update by file replacement (updater plugin not registered)
+let portableModePromise: Promise<boolean> | null = null;
+
+function isPortableMode(): Promise<boolean> {
+  portableModePromise ??= getStorageMode()
+    .then(info => info.mode === 'portable')
+    .catch(() => false);
+  return portableModePromise;
+}
+
 export function useUpdater() {
   const { t } = useTranslation();
   const [state, setState] = useState<UpdaterState>(() => {
@@ -82,6 +92,7 @@ export function useUpdater() {
   // Check for updates
   const checkForUpdates = useCallback(async (silent = false): Promise<UpdateInfo | null> => {
     if (!isTauri()) return null;
+    if (await isPortableMode()) return null;
 
     if (!silent) {
       setState(prev => ({ ...prev, checking: true, error: null }));
@@ -113,10 +124,13 @@ export function useUpdater() {
             dismissedVersion: isDismissedVersion ? prev.dismissedVersion : null,
           };
         });
+        // Surface the update in the tray for users who keep the window hidden
+        void setTrayUpdateAvailable(info.version);
 
         return info;
       } else {
         setState(prev => ({ ...prev, checking: false, available: null }));
+        void setTrayUpdateAvailable(null);
         return null;
       }
     } catch (err) {
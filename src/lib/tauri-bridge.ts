@@ -10,6 +10,7 @@ import { readTextFile, writeTextFile, exists, readFile, writeFile, readDir } fro
 import { open as openUrl } from '@tauri-apps/plugin-shell';
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 
 // ============================================================
 // ENVIRONMENT DETECTION
@@ -128,6 +129,141 @@ export function getDirFromPath(path: string): string {
   return parts.join('/');
 }
 
+export type TextEncoding = 'utf-8' | 'utf-16le' | 'utf-16be' | 'latin-1';
+
+/**
+ * Read a text file with an explicit encoding (decoded on the Rust side)
+ * The path must be in the app data directory or a user-granted folder
+ * @param path File path
+ * @param encoding Text encoding (default utf-8)
+ * @returns Decoded file content
+ */
+export async function readFileText(path: string, encoding?: TextEncoding): Promise<string> {
+  return invoke<string>('read_file_text', { path, encoding: encoding ?? null });
+}
+
+/**
+ * Write a text file with an explicit encoding (no byte order mark)
+ * @param path File path
+ * @param content Text to write
+ * @param encoding Text encoding (default utf-8)
+ * @param atomic Write to a temporary file and rename it into place
+ */
+export async function writeFileText(
+  path: string,
+  content: string,
+  encoding?: TextEncoding,
+  atomic = false
+): Promise<void> {
+  await invoke('write_file_text', { path, content, encoding: encoding ?? null, atomic });
+}
+
+export interface ReadLinesResult {
+  lines: string[];
+  total_matched: number;
+  has_more: boolean;
+}
+
+/**
+ * Page through the lines of a large text file without loading it in JS
+ * @param path File path (app data dir or a user-granted folder, max 500 MB)
+ * @param options Substring `filter` or `filterRegex` (not both), paging
+ * @returns Matching lines for the page, total matches and whether more remain
+ */
+export async function readFileLines(
+  path: string,
+  options: { filter?: string; filterRegex?: string; maxLines?: number; offset?: number } = {}
+): Promise<ReadLinesResult> {
+  return invoke<ReadLinesResult>('read_file_lines', {
+    path,
+    filter: options.filter ?? null,
+    filterRegex: options.filterRegex ?? null,
+    maxLines: options.maxLines ?? 1000,
+    offset: options.offset ?? 0,
+  });
+}
+
+export type HashAlgorithm = 'sha256' | 'md5' | 'sha1';
+
+/**
+ * Compute the hex digest of a file (max 2 GB) on the Rust side
+ * @param path File path (app data dir or a user-granted folder)
+ * @param algorithm Digest algorithm
+ * @returns Lowercase hex digest
+ */
+export async function fileHash(path: string, algorithm: HashAlgorithm = 'sha256'): Promise<string> {
+  return invoke<string>('file_hash', { path, algorithm });
+}
+
+/**
+ * Check a file against a known digest (hex, case-insensitive)
+ * @returns true when the digests match
+ */
+export async function verifyFileHash(
+  path: string,
+  algorithm: HashAlgorithm,
+  expected: string
+): Promise<boolean> {
+  return invoke<boolean>('verify_file_hash', { path, algorithm, expected });
+}
+
+export interface CompressResult {
+  original_bytes: number;
+  compressed_bytes: number;
+  /** original_bytes / compressed_bytes */
+  ratio: number;
+}
+
+/**
+ * Compress a file with zstd (level 3)
+ * Progress is emitted as `compress:progress` with { src, processed_bytes }
+ * Fails with "Insufficient disk space" below twice the source size free
+ * @param src Source file
+ * @param dest Compressed output path
+ */
+export async function compressFile(src: string, dest: string): Promise<CompressResult> {
+  return invoke<CompressResult>('compress_file', { src, dest });
+}
+
+/**
+ * Decompress a zstd file
+ * @returns Size of the decompressed file in bytes
+ */
+export async function decompressFile(src: string, dest: string): Promise<number> {
+  return invoke<number>('decompress_file', { src, dest });
+}
+
+export interface DiskSpaceInfo {
+  /** Bytes available to the current user */
+  free_bytes: number;
+  total_bytes: number;
+  used_bytes: number;
+}
+
+/**
+ * Space of the disk holding a path, e.g. before a large export
+ * @param path Any file or folder; the app data folder is used if it does not exist
+ */
+export async function getFreeDiskSpace(path: string): Promise<DiskSpaceInfo> {
+  return invoke<DiskSpaceInfo>('get_free_disk_space', { path });
+}
+
+/**
+ * Reveal a file in Finder/Explorer (opens the parent folder on Linux)
+ * @param path File in the app data dir or a user-granted folder
+ */
+export async function showItemInFolder(path: string): Promise<void> {
+  await invoke('show_item_in_folder', { path });
+}
+
+/**
+ * Open a file or folder with its default application
+ * @param path File in the app data dir or a user-granted folder
+ */
+export async function openPath(path: string): Promise<void> {
+  await invoke('open_path', { path });
+}
+
 // ============================================================
 // IMAGE OPERATIONS (for screenshots)
 // ============================================================
@@ -177,6 +313,45 @@ export async function openFolderDialog(): Promise<string | null> {
   return folder ?? null;
 }
 
+/**
+ * Capture the current window as a PNG in the app cache directory.
+ * The file is deleted after five minutes; read it with readImageAsBase64
+ * or copy it before then.
+ * @returns Absolute path of the PNG file
+ */
+export async function screenshotWindow(): Promise<string> {
+  return invoke<string>('screenshot_window');
+}
+
+/**
+ * Capture one rectangle of the current window, in capture pixels
+ * (CSS pixels times devicePixelRatio). The region must lie inside the
+ * window and be at most 4096x4096; the file is deleted after ten minutes.
+ * @returns Absolute path of the PNG file
+ */
+export async function screenshotRegion(
+  x: number,
+  y: number,
+  width: number,
+  height: number
+): Promise<string> {
+  return invoke<string>('screenshot_region', { x, y, width, height });
+}
+
+/**
+ * Capture only the area of a DOM element (e.g. one ticket card)
+ * @returns Absolute path of the PNG file
+ */
+export async function screenshotElement(element: Element): Promise<string> {
+  const rect = element.getBoundingClientRect();
+  const ratio = window.devicePixelRatio || 1;
+  const x = Math.max(0, Math.floor(rect.left * ratio));
+  const y = Math.max(0, Math.floor(rect.top * ratio));
+  const width = Math.ceil(rect.right * ratio) - x;
+  const height = Math.ceil(rect.bottom * ratio) - y;
+  return screenshotRegion(x, y, width, height);
+}
+
 // ============================================================
 // EXTERNAL URLS
 // ============================================================
@@ -189,6 +364,23 @@ export async function openExternalUrl(url: string): Promise<void> {
   await openUrl(url);
 }
 
+/**
+ * Get the id of the user's default web browser
+ * Desktop file id on Linux, bundle id on macOS, ProgId on Windows
+ */
+export async function getDefaultBrowser(): Promise<string> {
+  return invoke<string>('get_default_browser');
+}
+
+/**
+ * Open an http(s) URL in a given browser, launched directly
+ * @param url http:// or https:// URL (localhost only in dev builds)
+ * @param browser Browser id from getDefaultBrowser (defaults to the default browser)
+ */
+export async function openUrlInBrowser(url: string, browser?: string): Promise<void> {
+  await invoke('open_url_in_browser', { url, browser: browser ?? null });
+}
+
 /**
  * Setup global click handler for external links in Tauri
  * Call this once in your app initialization
@@ -316,6 +508,52 @@ export function joinPath(...parts: string[]): string {
 // APPLICATION LIFECYCLE
 // ============================================================
 
+export interface StorageInfo {
+  mode: 'installed' | 'portable';
+  dataDir: string;
+}
+
+/**
+ * Get the storage mode the backend was started in
+ * Portable mode keeps data next to the executable and disables the updater
+ */
+export async function getStorageMode(): Promise<StorageInfo> {
+  const info = await invoke<{ mode: StorageInfo['mode']; data_dir: string }>('get_storage_mode');
+  return { mode: info.mode, dataDir: info.data_dir };
+}
+
+export interface DataDirChangedEvent {
+  old_dir: string;
+  new_dir: string;
+}
+
+/**
+ * Copy the app data (settings, telemetry, backups) to another directory,
+ * used from the next start on. The target must be empty or new; the old
+ * directory is kept. Rejected in portable mode and while a project is open.
+ * @param newDir Absolute path of the new data directory
+ */
+export async function migrateAppData(newDir: string): Promise<void> {
+  await invoke('migrate_app_data', { newDir });
+}
+
+/**
+ * Get the data directory chosen with migrateAppData, or null for the default
+ */
+export async function getAppDataOverride(): Promise<string | null> {
+  return invoke<string | null>('get_app_data_override');
+}
+
+/**
+ * Listen for a completed app data migration (restart to apply)
+ * @returns Unlisten function
+ */
+export async function listenDataDirChanged(
+  callback: (event: DataDirChangedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<DataDirChangedEvent>('app:data-dir-changed', (event) => callback(event.payload));
+}
+
 /**
  * Force quit the application
  * Bypasses the tray minimize behavior
@@ -324,6 +562,127 @@ export async function forceQuit(): Promise<void> {
   await invoke('force_quit');
 }
 
+/**
+ * Choose what the main window's close button does
+ * @param enabled true hides to the tray (default), false quits the app
+ */
+export async function setMinimizeToTray(enabled: boolean): Promise<void> {
+  await invoke('set_minimize_to_tray', { enabled });
+}
+
+/**
+ * Whether the main window's close button hides to the tray
+ */
+export async function getMinimizeToTray(): Promise<boolean> {
+  return invoke<boolean>('get_minimize_to_tray');
+}
+
+export type TrayMenuItemId = 'open' | 'quit' | 'install_update';
+
+/**
+ * Change a tray menu item's label, e.g. after switching the UI language
+ * The install_update item gets the update version appended
+ */
+export async function setTrayMenuItemLabel(itemId: TrayMenuItemId, label: string): Promise<void> {
+  await invoke('set_tray_menu_item_label', { itemId, label });
+}
+
+/**
+ * Enable or grey out a tray menu item
+ */
+export async function setTrayMenuItemEnabled(itemId: TrayMenuItemId, enabled: boolean): Promise<void> {
+  await invoke('set_tray_menu_item_enabled', { itemId, enabled });
+}
+
+export type TrayIconName = 'default' | 'syncing' | 'error' | 'notification';
+
+/**
+ * Switch the tray icon to reflect the app state (e.g. 'syncing' during a save)
+ */
+export async function setTrayIcon(iconName: TrayIconName): Promise<void> {
+  await invoke('set_tray_icon', { iconName });
+}
+
+/**
+ * Cycle the tray icon through PNG frames, e.g. while a sync runs
+ * Replaces a running animation
+ * @param framePaths 2 to 24 PNG paths (app data dir or a user-granted folder)
+ * @param intervalMs Time per frame (at least 50)
+ * @returns Animation id for stopTrayAnimation
+ */
+export async function startTrayAnimation(framePaths: string[], intervalMs: number): Promise<string> {
+  return invoke<string>('start_tray_animation', { framePaths, intervalMs });
+}
+
+/**
+ * Stop a tray animation and restore the default icon
+ */
+export async function stopTrayAnimation(animationId: string): Promise<void> {
+  await invoke('stop_tray_animation', { animationId });
+}
+
+/**
+ * Restart the application after an orderly shutdown
+ * Current CLI flags are preserved; extraArgs are appended
+ * @param extraArgs Additional arguments, e.g. ['--project', path]
+ */
+export async function restartApp(extraArgs?: string[]): Promise<void> {
+  await invoke('restart_app', { extraArgs });
+}
+
+/**
+ * Get the duration of each startup phase of the Rust backend
+ * @returns [phase name, milliseconds] pairs in the order they ran
+ */
+export async function getStartupTiming(): Promise<[string, number][]> {
+  return invoke<[string, number][]>('get_startup_timing');
+}
+
+export interface CrashReport {
+  id: number;
+  message: string;
+  /** file:line:column of the panic */
+  location: string;
+  backtrace: string;
+  /** Unix milliseconds */
+  occurred_at: number;
+}
+
+/**
+ * Get the most recent backend panics, newest first
+ * @param limit Maximum number of reports (1-100)
+ */
+export async function getCrashReports(limit = 20): Promise<CrashReport[]> {
+  return invoke<CrashReport[]>('get_crash_reports', { limit });
+}
+
+/**
+ * Delete all recorded crash reports
+ */
+export async function clearCrashReports(): Promise<void> {
+  await invoke('clear_crash_reports');
+}
+
+/**
+ * Listen for the notice that the previous session panicked
+ * @param callback Receives the number of crash reports from that session
+ * @returns Unlisten function
+ */
+export async function listenPreviousSessionCrash(
+  callback: (count: number) => void
+): Promise<UnlistenFn> {
+  return listen<number>('crash:previous-session', (event) => callback(event.payload));
+}
+
+/**
+ * Show or hide the "install update" item in the tray menu
+ * Best-effort: failures are logged, never thrown
+ * @param version Version found by the updater, or null when up to date
+ */
+export async function setTrayUpdateAvailable(version: string | null): Promise<void> {
+  await invoke('set_tray_update_available', { version }).catch(console.warn);
+}
+
 /**
  * Listen for tray quit request
  * Called when user clicks "Quitter" in tray menu
@@ -333,3 +692,2668 @@ export async function forceQuit(): Promise<void> {
 export async function listenTrayQuitRequested(callback: () => void): Promise<UnlistenFn> {
   return listen('tray:quit-requested', callback);
 }
+
+/**
+ * Consume the project path passed with `--project <path>`, if any
+ * Call on startup; a second launch also emits `cli:open-project`
+ * @returns Project path, or null when none is pending
+ */
+export async function takeCliNavigation(): Promise<string | null> {
+  return invoke<string | null>('take_cli_navigation');
+}
+
+/**
+ * Listen for a project requested by a second app launch
+ * @param callback Function called with the project path
+ * @returns Unlisten function
+ */
+export async function listenCliOpenProject(callback: (projectPath: string) => void): Promise<UnlistenFn> {
+  return listen<string>('cli:open-project', (event) => callback(event.payload));
+}
+
+export interface MonitorInfo {
+  name: string;
+  position_x: number;
+  position_y: number;
+  width: number;
+  height: number;
+  scale_factor: number;
+  is_primary: boolean;
+}
+
+/**
+ * List the connected monitors (physical pixels)
+ */
+export async function getScreenInfo(): Promise<MonitorInfo[]> {
+  return invoke<MonitorInfo[]>('get_screen_info');
+}
+
+/**
+ * Get the monitor containing the center of the current window
+ */
+export async function getMonitorAtWindowPosition(): Promise<MonitorInfo> {
+  return invoke<MonitorInfo>('get_monitor_at_window_position');
+}
+
+/**
+ * Move the current window to the center of its monitor
+ */
+export async function windowCenter(): Promise<void> {
+  await invoke('window_center');
+}
+
+/**
+ * Move the current window back on screen (centered on the primary monitor)
+ * when it is on none of the connected monitors
+ */
+export async function windowEnsureOnScreen(): Promise<void> {
+  await invoke('window_ensure_on_screen');
+}
+
+/**
+ * Set the title of the current window (1-128 characters, single line)
+ * The main window's title is restored at the next startup
+ */
+export async function setWindowTitle(title: string): Promise<void> {
+  await invoke('set_window_title', { title });
+}
+
+/**
+ * Get the title of the current window
+ */
+export async function getWindowTitle(): Promise<string> {
+  return invoke<string>('get_window_title');
+}
+
+export type VibrancyEffect = 'none' | 'blur' | 'acrylic' | 'mica' | 'sidebar';
+
+/**
+ * Apply a translucent background to the current window (macOS and Windows;
+ * a no-op on Linux). Shows only where the page background is transparent.
+ * The main window's effect is restored at the next startup
+ */
+export async function setWindowVibrancy(effect: VibrancyEffect): Promise<void> {
+  await invoke('set_window_vibrancy', { effect });
+}
+
+/**
+ * Effect of the main window
+ */
+export async function getWindowVibrancy(): Promise<VibrancyEffect> {
+  return invoke<VibrancyEffect>('get_window_vibrancy');
+}
+
+/** Labels of the windows createWindow can open (the only ones the secondary-windows capability covers) */
+export type SecondaryWindowLabel = 'window-1' | 'window-2' | 'window-3' | 'window-4';
+
+/**
+ * Open a secondary window, e.g. a ticket detail panel (at most 5 windows, main included)
+ * @param label Window slot to open
+ * @param url App path (e.g. '/ticket/BUG-1') or a URL on the app's origin
+ * @param width Width in logical pixels (200-8192)
+ * @param height Height in logical pixels (200-8192)
+ * @param title Window title
+ */
+export async function createWindow(
+  label: SecondaryWindowLabel,
+  url: string,
+  width: number,
+  height: number,
+  title: string
+): Promise<void> {
+  await invoke('create_window', { label, url, width, height, title });
+}
+
+/**
+ * Close a secondary window opened with createWindow
+ */
+export async function closeWindow(label: string): Promise<void> {
+  await invoke('close_window', { label });
+}
+
+/**
+ * Labels of the open windows, 'main' included
+ */
+export async function listWindows(): Promise<string[]> {
+  return invoke<string[]>('list_windows');
+}
+
+/**
+ * Listen for secondary windows being opened (main window only)
+ * @returns Unlisten function
+ */
+export async function listenWindowCreated(callback: (label: string) => void): Promise<UnlistenFn> {
+  return listen<string>('window:created', (event) => callback(event.payload));
+}
+
+/**
+ * Listen for secondary windows being closed, by closeWindow or by the user (main window only)
+ * @returns Unlisten function
+ */
+export async function listenWindowClosed(callback: (label: string) => void): Promise<UnlistenFn> {
+  return listen<string>('window:closed', (event) => callback(event.payload));
+}
+
+/**
+ * Send an event to one other window, e.g. 'ticket:updated' to a detail panel
+ * Names: letters, digits, _, : and -; tauri:, tray: and app: are reserved
+ * @param targetLabel Label of the receiving window
+ */
+export async function emitToWindow(targetLabel: string, event: string, payload: unknown = null): Promise<void> {
+  await invoke('emit_to_window', { targetLabel, event, payload });
+}
+
+/**
+ * Send an event to every window (same naming rules as emitToWindow)
+ */
+export async function broadcastEvent(event: string, payload: unknown = null): Promise<void> {
+  await invoke('broadcast_event', { event, payload });
+}
+
+/**
+ * Listen for an event addressed to this window by emitToWindow (broadcasts included)
+ * @returns Unlisten function
+ */
+export async function listenWindowEvent<T>(event: string, callback: (payload: T) => void): Promise<UnlistenFn> {
+  return getCurrentWebviewWindow().listen<T>(event, (e) => callback(e.payload));
+}
+
+/**
+ * Replace this window's icon, e.g. to tell projects apart in the taskbar (no-op on macOS)
+ * @param iconData PNG bytes, at most 1 MB and 1024x1024 pixels
+ */
+export async function setWindowIcon(iconData: Uint8Array): Promise<void> {
+  await invoke('set_window_icon', { iconData: Array.from(iconData) });
+}
+
+/**
+ * Restore the app icon on this window
+ */
+export async function resetWindowIcon(): Promise<void> {
+  await invoke('reset_window_icon');
+}
+
+// ============================================================
+// RECENT PROJECTS
+// ============================================================
+
+export interface RecentFile {
+  path: string;
+  name: string;
+  /** Unix milliseconds */
+  opened_at: number;
+}
+
+/**
+ * Record that a project was opened (keeps the 10 most recent)
+ */
+export async function addRecentFile(path: string, name: string): Promise<void> {
+  await invoke('add_recent_file', { path, name });
+}
+
+/**
+ * Get recently opened projects, newest first
+ * Entries whose path no longer exists are dropped
+ */
+export async function getRecentFiles(): Promise<RecentFile[]> {
+  return invoke<RecentFile[]>('get_recent_files');
+}
+
+/**
+ * Forget all recently opened projects
+ */
+export async function clearRecentFiles(): Promise<void> {
+  await invoke('clear_recent_files');
+}
+
+export interface ProjectSummary {
+  name: string;
+  path: string;
+  size_bytes: number;
+  /** Unix milliseconds */
+  modified_at: number;
+  ticket_count: number;
+  schema_version: number;
+  /** Set when the file is not a readable project database */
+  error: string | null;
+}
+
+/**
+ * List the project databases in a directory (`*.db` files and
+ * `<subdir>/backlog.db`), with metadata read from each file
+ * @param dir Directory to scan
+ */
+export async function listProjects(dir: string): Promise<ProjectSummary[]> {
+  return invoke<ProjectSummary[]>('list_projects', { dir });
+}
+
+/**
+ * Create a project directory with an initialized backlog.db
+ * @param parentDir Directory to create the project in
+ * @param name Project name, also used as the directory name
+ * @returns Path of the new project directory
+ */
+export async function createProject(parentDir: string, name: string): Promise<string> {
+  return invoke<string>('create_project', { parentDir, name });
+}
+
+/**
+ * Delete a project's database, its -wal/-shm files and .backlog-assets.
+ * Fails while the project is open; call closeDatabase() first.
+ * @param dbPath Path to the project's backlog.db
+ * @param toTrash Move to the OS trash (default) instead of deleting
+ */
+export async function deleteProject(dbPath: string, toTrash = true): Promise<void> {
+  await invoke('delete_project', { dbPath, toTrash });
+}
+
+/**
+ * Rename a project directory, moving its database and assets with it.
+ * Fails while the project is open; call closeDatabase() first.
+ * @param oldPath Project directory
+ * @param newName New directory name
+ * @returns Path of the renamed project directory
+ */
+export async function renameProject(oldPath: string, newName: string): Promise<string> {
+  return invoke<string>('rename_project', { oldPath, newName });
+}
+
+/** Return value of openProjectProbe */
+export interface ProjectProbe {
+  writable: boolean;
+  /** Why the project cannot be written, null when it can */
+  reason: string | null;
+}
+
+export interface ProjectReadOnlyEvent {
+  db_path: string;
+  reason: string;
+}
+
+/**
+ * Check that a project can be written before loading it (file permissions,
+ * its folder, and a rolled-back write). When it cannot, backend commands
+ * that write to it reject with an AppError of kind 'ReadOnly' and
+ * `project:read-only` is emitted.
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function openProjectProbe(dbPath: string): Promise<ProjectProbe> {
+  return invoke<ProjectProbe>('open_project_probe', { dbPath });
+}
+
+/**
+ * Listen for projects found read-only, to show a banner instead of
+ * failing action by action
+ * @returns Unlisten function
+ */
+export async function listenProjectReadOnly(
+  callback: (event: ProjectReadOnlyEvent) => void
+): Promise<UnlistenFn> {
+  return listen<ProjectReadOnlyEvent>('project:read-only', (event) => callback(event.payload));
+}
+
+/** Holder of a project lock (`<db>.lock`), as returned by acquireProjectLock */
+export interface ProjectLockInfo {
+  hostname: string;
+  pid: number;
+  /** Unix milliseconds */
+  acquired_at: number;
+  /** Unix milliseconds, refreshed every minute */
+  heartbeat_at: number;
+}
+
+/**
+ * Take the advisory lock of a project before opening it.
+ * Rejects with an AppError of kind 'InUse' while another machine holds it.
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function acquireProjectLock(dbPath: string): Promise<ProjectLockInfo> {
+  return invoke<ProjectLockInfo>('acquire_project_lock', { dbPath });
+}
+
+/**
+ * Release the lock of a project after closing it
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function releaseProjectLock(dbPath: string): Promise<void> {
+  await invoke('release_project_lock', { dbPath });
+}
+
+/**
+ * Remove a project lock whatever its holder, for a lock known to be stale
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function forceUnlockProject(dbPath: string): Promise<void> {
+  await invoke('force_unlock_project', { dbPath });
+}
+
+/**
+ * Listen for a project lock taken over by another machine
+ * @param callback Receives the path of the project's backlog.db
+ */
+export async function listenProjectLockLost(
+  callback: (dbPath: string) => void
+): Promise<UnlistenFn> {
+  return listen<string>('project-lock:lost', (event) => callback(event.payload));
+}
+
+/**
+ * Check whether a project database is encrypted with SQLCipher
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function isProjectEncrypted(dbPath: string): Promise<boolean> {
+  return invoke<boolean>('is_project_encrypted', { dbPath });
+}
+
+/**
+ * Encrypt a project database in place. Existing backups are not touched.
+ * Fails while the project is open; call closeDatabase() first.
+ * @param dbPath Path to the project's backlog.db
+ * @param passphrase At least 8 characters
+ * @param remember Store the passphrase in the OS keyring
+ */
+export async function encryptProject(
+  dbPath: string,
+  passphrase: string,
+  remember = false
+): Promise<void> {
+  await invoke('encrypt_project', { dbPath, passphrase, remember });
+}
+
+/**
+ * Unlock an encrypted project so getDatabase() can use it.
+ * Without a passphrase, the one unlocked this session or stored in the
+ * OS keyring is used. Rejects with an "Unauthorized" error for a wrong or
+ * missing passphrase and a "Database" error for a damaged file.
+ * @param dbPath Path to the project's backlog.db
+ * @param passphrase Passphrase entered by the user
+ * @param remember Store (true) or forget (false) the passphrase in the OS keyring
+ */
+export async function openEncryptedProject(
+  dbPath: string,
+  passphrase?: string,
+  remember?: boolean
+): Promise<void> {
+  await invoke('open_encrypted_project', { dbPath, passphrase, remember });
+}
+
+/**
+ * Change the passphrase of an encrypted project.
+ * Fails while the project is open; call closeDatabase() first.
+ * @param dbPath Path to the project's backlog.db
+ * @param currentPassphrase Current passphrase
+ * @param newPassphrase New passphrase, at least 8 characters
+ * @param remember Store (true) or forget (false) the new passphrase in the OS keyring;
+ *   by default a stored passphrase is updated
+ */
+export async function changeProjectPassphrase(
+  dbPath: string,
+  currentPassphrase: string,
+  newPassphrase: string,
+  remember?: boolean
+): Promise<void> {
+  await invoke('change_project_passphrase', {
+    dbPath,
+    currentPassphrase,
+    newPassphrase,
+    remember,
+  });
+}
+
+// ============================================================
+// PROJECT DATABASE MAINTENANCE
+// ============================================================
+
+export interface ProjectBackupResult {
+  path: string;
+  sizeBytes: number;
+}
+
+/**
+ * Write a consistent, timestamped copy of a project database
+ * Safe while the database is open; rejects with an AppError on failure
+ * @param dbPath Path to the project's backlog.db
+ * @param destDir Output directory (defaults to the project's .backlog-backups)
+ * @returns Output path and size in bytes
+ */
+export async function backupProjectDb(dbPath: string, destDir?: string): Promise<ProjectBackupResult> {
+  const result = await invoke<{ path: string; size_bytes: number }>('backup_project_db', {
+    dbPath,
+    destDir: destDir ?? null,
+  });
+  return { path: result.path, sizeBytes: result.size_bytes };
+}
+
+export interface ProjectRestoreReport {
+  restoredPath: string;
+  safetyCopyPath: string | null;
+  schemaVersion: number;
+  sizeBytes: number;
+}
+
+/**
+ * Replace a project database with a validated backup
+ * The current database is kept as `backlog.db.pre-restore`
+ * @param backupPath Backup file to restore
+ * @param targetPath Project database to replace
+ * @param connectionClosed Pass true once closeDatabase() has been called
+ * @returns What was restored and where the previous database went
+ */
+export async function restoreProjectDb(
+  backupPath: string,
+  targetPath: string,
+  connectionClosed = false
+): Promise<ProjectRestoreReport> {
+  const report = await invoke<{
+    restored_path: string;
+    safety_copy_path: string | null;
+    schema_version: number;
+    size_bytes: number;
+  }>('restore_project_db', { backupPath, targetPath, connectionClosed });
+  return {
+    restoredPath: report.restored_path,
+    safetyCopyPath: report.safety_copy_path,
+    schemaVersion: report.schema_version,
+    sizeBytes: report.size_bytes,
+  };
+}
+
+/**
+ * Tell the backend a schema migration is running so scheduled backups wait
+ * Errors are swallowed (outside Tauri there is no scheduler to pause)
+ */
+export async function setMigrationInProgress(active: boolean): Promise<void> {
+  await invoke('set_migration_in_progress', { active }).catch(console.warn);
+}
+
+export interface MigrationPreflight {
+  schema_version: number;
+  target_version: number;
+  /** Migrations will run when the database is loaded */
+  pending: boolean;
+  /** Snapshot taken before migrating (in .backlog-backups/pre-migration) */
+  backup_path: string | null;
+  /** An earlier migration of this database did not complete */
+  failed: boolean;
+}
+
+export interface MigrationFailedEvent {
+  db_path: string;
+  backup_path: string;
+  from_version: number;
+  schema_version: number;
+}
+
+/**
+ * Snapshot a project database before pending schema migrations run
+ * Call before Database.load(); errors are logged and yield null so a failed
+ * snapshot never blocks opening the project
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function preflightProjectMigration(dbPath: string): Promise<MigrationPreflight | null> {
+  return invoke<MigrationPreflight>('preflight_project_migration', { dbPath }).catch((error) => {
+    console.warn(error);
+    return null;
+  });
+}
+
+/**
+ * Restore the snapshot taken before a failed migration
+ * The current database is kept as `backlog.db.pre-restore`
+ * @param dbPath Path to the project's backlog.db
+ * @param connectionClosed Pass true once closeDatabase() has been called
+ */
+export async function restorePreMigrationBackup(
+  dbPath: string,
+  connectionClosed = false
+): Promise<ProjectRestoreReport> {
+  const report = await invoke<{
+    restored_path: string;
+    safety_copy_path: string | null;
+    schema_version: number;
+    size_bytes: number;
+  }>('restore_pre_migration_backup', { dbPath, connectionClosed });
+  return {
+    restoredPath: report.restored_path,
+    safetyCopyPath: report.safety_copy_path,
+    schemaVersion: report.schema_version,
+    sizeBytes: report.size_bytes,
+  };
+}
+
+/**
+ * Listen for migrations that left a database behind the app's schema
+ * @returns Unlisten function
+ */
+export async function listenMigrationFailed(
+  callback: (event: MigrationFailedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<MigrationFailedEvent>('migration:failed', (event) => callback(event.payload));
+}
+
+export interface VacuumResult {
+  before_bytes: number;
+  after_bytes: number;
+  duration_ms: number;
+}
+
+export interface VacuumProgressEvent {
+  job_id: string | null;
+  phase: 'checkpoint' | 'vacuum' | 'retry';
+  elapsed_ms: number;
+}
+
+/**
+ * Rebuild a project database to reclaim the space of deleted rows
+ * Emits `vacuum:progress` heartbeats; fails when free disk space is below the DB size
+ * @param dbPath Path to the project's backlog.db
+ * @param jobId Optional id echoed in progress events
+ * @returns File size (database + WAL) before and after
+ */
+export async function vacuumProjectDb(dbPath: string, jobId?: string): Promise<VacuumResult> {
+  return invoke<VacuumResult>('vacuum_project_db', { dbPath, jobId: jobId ?? null });
+}
+
+/**
+ * Listen for vacuum progress heartbeats
+ * @returns Unlisten function
+ */
+export async function listenVacuumProgress(
+  callback: (progress: VacuumProgressEvent) => void
+): Promise<UnlistenFn> {
+  return listen<VacuumProgressEvent>('vacuum:progress', (event) => callback(event.payload));
+}
+
+export interface CheckpointResult {
+  /** Frames in the WAL when the last attempt started */
+  wal_frames: number;
+  /** Frames copied into the database file */
+  checkpointed_frames: number;
+  /** True when the database stayed busy and the WAL was not fully truncated */
+  busy: boolean;
+  attempts: number;
+}
+
+/**
+ * Fold a project's WAL into the database file and truncate it, so sync
+ * clients (Dropbox, Syncthing) upload one up-to-date file ("sync now").
+ * A busy database is retried with backoff.
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function checkpointProjectDb(dbPath: string): Promise<CheckpointResult> {
+  return invoke<CheckpointResult>('checkpoint_project_db', { dbPath });
+}
+
+/**
+ * Set how many WAL pages trigger an automatic checkpoint on the backend's
+ * connections to a project (SQLite default: 1000), for this session
+ * @param pages 1 to 100000
+ */
+export async function dbSetWalAutocheckpoint(dbPath: string, pages: number): Promise<void> {
+  return invoke<void>('db_set_wal_autocheckpoint', { dbPath, pages });
+}
+
+/**
+ * WAL autocheckpoint threshold, in pages, of the backend's connections to a project
+ */
+export async function dbGetWalAutocheckpoint(dbPath: string): Promise<number> {
+  return invoke<number>('db_get_wal_autocheckpoint', { dbPath });
+}
+
+export interface IndexInfo {
+  name: string;
+  table: string;
+  /** Indexed columns in order; '<expr>' for an expression */
+  columns: string[];
+  unique: boolean;
+  /** Made by dbCreateIndex, so dbDropIndex may drop it */
+  user_managed: boolean;
+}
+
+/**
+ * Create idx_<table>_<columns> on a project table if it does not exist
+ * Table and column names must be plain identifiers ([A-Za-z_][A-Za-z0-9_]*)
+ * of an existing table; SQLite and virtual tables are refused
+ * @returns The index name
+ */
+export async function dbCreateIndex(
+  dbPath: string,
+  table: string,
+  columns: string[],
+  unique = false
+): Promise<string> {
+  return invoke<string>('db_create_index', { dbPath, table, columns, unique });
+}
+
+/**
+ * Drop an index made by dbCreateIndex (schema indexes are refused)
+ */
+export async function dbDropIndex(dbPath: string, indexName: string): Promise<void> {
+  return invoke<void>('db_drop_index', { dbPath, indexName });
+}
+
+/**
+ * Every index of a project database's tables
+ */
+export async function dbListIndexes(dbPath: string): Promise<IndexInfo[]> {
+  return invoke<IndexInfo[]>('db_list_indexes', { dbPath });
+}
+
+export interface CompactionSettings {
+  enabled: boolean;
+  /** Share of free pages (percent, 5-95) above which a database is compacted */
+  free_percent: number;
+  /** Databases smaller than this are left alone */
+  min_size_mb: number;
+}
+
+export interface CompactionCheck {
+  size_bytes: number;
+  page_count: number;
+  freelist_count: number;
+  free_percent: number;
+  scheduled: boolean;
+}
+
+export interface CompactionStartedEvent {
+  db_path: string;
+  free_percent: number;
+  size_bytes: number;
+  method: 'incremental_vacuum' | 'vacuum';
+}
+
+export interface CompactionFinishedEvent {
+  db_path: string;
+  before_bytes: number;
+  after_bytes: number;
+  error: string | null;
+}
+
+/**
+ * Get the automatic compaction settings
+ */
+export async function getCompactionSettings(): Promise<CompactionSettings> {
+  return invoke<CompactionSettings>('get_compaction_settings');
+}
+
+/**
+ * Update the automatic compaction settings (applied immediately)
+ * @returns The saved settings
+ */
+export async function setCompactionSettings(
+  settings: CompactionSettings
+): Promise<CompactionSettings> {
+  return invoke<CompactionSettings>('set_compaction_settings', { settings });
+}
+
+/**
+ * Measure the free pages of a project database and schedule a compaction
+ * when they exceed the threshold. Call after opening a project and after
+ * large deletes; the compaction runs a couple of minutes later, never
+ * during an import, backup, restore or migration.
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function checkProjectCompaction(dbPath: string): Promise<CompactionCheck> {
+  return invoke<CompactionCheck>('check_project_compaction', { dbPath });
+}
+
+/**
+ * Listen for automatic compactions starting
+ * @returns Unlisten function
+ */
+export async function listenCompactionStarted(
+  callback: (event: CompactionStartedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<CompactionStartedEvent>('compaction:started', (event) => callback(event.payload));
+}
+
+/**
+ * Listen for automatic compactions finishing (successfully or not)
+ * @returns Unlisten function
+ */
+export async function listenCompactionFinished(
+  callback: (event: CompactionFinishedEvent) => void
+): Promise<UnlistenFn> {
+  return listen<CompactionFinishedEvent>('compaction:finished', (event) => callback(event.payload));
+}
+
+export interface BackupSchedule {
+  enabled: boolean;
+  interval_hours: number;
+  keep_daily: number;
+  keep_weekly: number;
+}
+
+export interface ScheduledBackup {
+  path: string;
+  file_name: string;
+  size_bytes: number;
+  created_at: number;
+}
+
+/**
+ * Get the automatic backup schedule
+ */
+export async function getBackupSchedule(): Promise<BackupSchedule> {
+  return invoke<BackupSchedule>('get_backup_schedule');
+}
+
+/**
+ * Update the automatic backup schedule (applied immediately)
+ * @returns The saved schedule
+ */
+export async function setBackupSchedule(schedule: BackupSchedule): Promise<BackupSchedule> {
+  return invoke<BackupSchedule>('set_backup_schedule', { schedule });
+}
+
+/**
+ * List scheduled backups of a project, newest first
+ * @param project Project directory name
+ */
+export async function listBackups(project: string): Promise<ScheduledBackup[]> {
+  return invoke<ScheduledBackup[]>('list_backups', { project });
+}
+
+export interface DbViolation {
+  kind: 'open' | 'integrity' | 'foreign_key' | 'missing_table' | 'schema';
+  message: string;
+}
+
+export interface DbCheckReport {
+  path: string;
+  ok: boolean;
+  violations: DbViolation[];
+  size_bytes: number;
+  page_count: number;
+  freelist_pages: number;
+  journal_mode: string;
+  schema_version: number;
+}
+
+/**
+ * Check a project database (integrity, foreign keys, tables, schema version)
+ * Opens the file read-only; problems are listed in the report, not thrown
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function checkProjectDb(dbPath: string): Promise<DbCheckReport> {
+  return invoke<DbCheckReport>('check_project_db', { dbPath });
+}
+
+/**
+ * Check every open and recently opened project database
+ */
+export async function checkAllProjects(): Promise<DbCheckReport[]> {
+  return invoke<DbCheckReport[]>('check_all_projects');
+}
+
+export interface ArchiveReport {
+  tickets_moved: number;
+  /** Tickets of the in-app archive (archived_items) moved */
+  archived_moved: number;
+  relations_moved: number;
+  /** Matching tickets left in place because they changed meanwhile */
+  skipped: number;
+  vacuumed: boolean;
+  /** About zero unless vacuumed */
+  reclaimed_bytes: number;
+}
+
+export interface ArchivedTicket {
+  id: string;
+  type: string;
+  title: string;
+  section: string | null;
+  in_app_archive: boolean;
+  date: string | null;
+}
+
+export interface ArchiveSearchResults {
+  total: number;
+  hits: ArchivedTicket[];
+}
+
+export interface ArchiveRestoreReport {
+  restored: string[];
+  not_found: string[];
+}
+
+/**
+ * Move old tickets into the project's archive database (backlog.archive.db)
+ * Moves board tickets in `statuses` last updated before `beforeDate`, in-app
+ * archive tickets archived before it, and their relations. Reload the project afterwards.
+ * @param dbPath Path to the project's backlog.db
+ * @param beforeDate YYYY-MM-DD (UTC)
+ * @param statuses Section titles, case-insensitive
+ * @param vacuum Vacuum the project afterwards to shrink the file
+ */
+export async function archiveTickets(
+  dbPath: string,
+  beforeDate: string,
+  statuses: string[],
+  vacuum = false
+): Promise<ArchiveReport> {
+  return invoke<ArchiveReport>('archive_tickets', { dbPath, beforeDate, statuses, vacuum });
+}
+
+/**
+ * Search the project's archive database (id, title, description, component, module)
+ * @param query Substring to look for; empty lists everything, most recent first
+ */
+export async function searchArchive(
+  dbPath: string,
+  query: string,
+  limit?: number,
+  offset?: number
+): Promise<ArchiveSearchResults> {
+  return invoke<ArchiveSearchResults>('search_archive', {
+    dbPath,
+    query,
+    limit: limit ?? null,
+    offset: offset ?? null,
+  });
+}
+
+/**
+ * Move tickets back from the archive database into the project. Reload the project afterwards.
+ * @param ticketIds Ids from searchArchive
+ */
+export async function restoreFromArchive(
+  dbPath: string,
+  ticketIds: string[]
+): Promise<ArchiveRestoreReport> {
+  return invoke<ArchiveRestoreReport>('restore_from_archive', { dbPath, ticketIds });
+}
+
+// ============================================================
+// HTTP PROXY (third-party APIs without webview CORS limits)
+// ============================================================
+
+export interface ProxyResponse {
+  status: number;
+  headers: Record<string, string>;
+  body: string;
+}
+
+/**
+ * Perform an HTTP request from the Rust side
+ * The host must match an entry of the proxy allowlist; redirects are not followed
+ * @param method HTTP method (GET, POST, ...)
+ * @param url Absolute http(s) URL
+ * @param headers Request headers
+ * @param body Optional request body
+ * @returns Status, headers and body (capped at 10 MB)
+ */
+export async function proxyHttpRequest(
+  method: string,
+  url: string,
+  headers: Record<string, string> = {},
+  body?: string
+): Promise<ProxyResponse> {
+  return invoke<ProxyResponse>('proxy_http_request', { method, url, headers, body: body ?? null });
+}
+
+/**
+ * Allow proxied requests to a host (`api.github.com`, `*.atlassian.net`)
+ * @returns Updated allowlist
+ */
+export async function addProxyAllowlistEntry(pattern: string): Promise<string[]> {
+  return invoke<string[]>('add_proxy_allowlist_entry', { pattern });
+}
+
+/**
+ * Remove a host pattern from the proxy allowlist
+ * @returns Updated allowlist
+ */
+export async function removeProxyAllowlistEntry(pattern: string): Promise<string[]> {
+  return invoke<string[]>('remove_proxy_allowlist_entry', { pattern });
+}
+
+/**
+ * Route outgoing HTTP (telemetry, proxied requests) through a corporate proxy
+ * @param proxyUrl e.g. http://proxy.corp:8080; null to fall back to the
+ *   HTTP_PROXY / HTTPS_PROXY environment variables
+ * @param noProxy Hosts reached directly, comma-separated (e.g. "localhost,.corp")
+ */
+export async function setHttpProxy(proxyUrl: string | null, noProxy: string | null = null): Promise<void> {
+  return invoke<void>('set_http_proxy', { proxyUrl, noProxy });
+}
+
+/**
+ * Proxy currently in use, whether set in the app or from the environment
+ */
+export async function getHttpProxy(): Promise<string | null> {
+  return invoke<string | null>('get_http_proxy');
+}
+
+/**
+ * Debug builds only: trust this DER root certificate instead of the system
+ * roots for telemetry, to test certificate pinning. Empty restores the
+ * built-in pin.
+ */
+export async function pinTlsCert(derBytes: Uint8Array): Promise<void> {
+  return invoke<void>('pin_tls_cert', { derBytes: Array.from(derBytes) });
+}
+
+/**
+ * Stop (or resume) telemetry in the backend. Opting out drops the offline
+ * queue and discards events sent afterwards
+ */
+export async function setTelemetryOptOut(optOut: boolean): Promise<void> {
+  return invoke<void>('set_telemetry_opt_out', { optOut });
+}
+
+export interface TelemetryDiagnostics {
+  /** Events waiting in the offline queue */
+  queue_depth: number;
+  /** Queued events that already failed at least once */
+  retrying_depth: number;
+  /** Events kept after exhausting their retries; no longer sent */
+  dead_letter_depth: number;
+  /** Open: too many failed deliveries, events are queued without a request */
+  circuit_breaker: 'closed' | 'open' | 'half_open';
+  /** Whether the backend drops events (see setTelemetryOptOut) */
+  opted_out: boolean;
+  /** Last successful delivery (Unix ms) */
+  last_flush_at_ms: number | null;
+  send_in_flight: boolean;
+  has_api_key: boolean;
+  proxy_configured: boolean;
+  tls_pinned: boolean;
+  api_host: string;
+}
+
+/**
+ * Health of the telemetry pipeline in one call (queue, dead letters, circuit
+ * breaker, opt-out, last delivery, key, proxy, pinning)
+ */
+export async function telemetryDiagnostics(): Promise<TelemetryDiagnostics> {
+  return invoke<TelemetryDiagnostics>('telemetry_diagnostics');
+}
+
+export interface PoolHealthStatus {
+  /** Whether telemetry.db answered just now (after reconnecting if needed) */
+  healthy: boolean;
+  /** Previous check (Unix ms), null before the first */
+  last_health_check_at: number | null;
+  /** Last reconnection (Unix ms), null if none */
+  last_reconnect_at: number | null;
+  connections: number;
+}
+
+/**
+ * Check the telemetry.db connection, reconnecting when it stopped answering
+ */
+export async function getPoolHealth(): Promise<PoolHealthStatus> {
+  return invoke<PoolHealthStatus>('get_pool_health');
+}
+
+// ============================================================
+// CLIPBOARD
+// ============================================================
+
+/**
+ * Start watching the clipboard; changes are emitted as `clipboard:changed`
+ * Only one watcher may run at a time
+ * @returns Watcher id for unwatchClipboard
+ */
+export async function watchClipboard(): Promise<string> {
+  return invoke<string>('watch_clipboard');
+}
+
+/**
+ * Stop a clipboard watcher started with watchClipboard
+ */
+export async function unwatchClipboard(watcherId: string): Promise<void> {
+  await invoke('unwatch_clipboard', { watcherId });
+}
+
+// ============================================================
+// DIRECTORY WATCHING
+// ============================================================
+
+export interface FsChangeEvent {
+  watcher_id: string;
+  kind: 'create' | 'modify' | 'remove';
+  path: string;
+}
+
+/**
+ * Watch a directory (non-recursive); changes are emitted as `fs:change`
+ * At most 10 directories can be watched at once
+ * @returns Watcher id for unwatchDirectory
+ */
+export async function watchDirectory(path: string): Promise<string> {
+  return invoke<string>('watch_directory', { path });
+}
+
+/**
+ * Stop a directory watcher started with watchDirectory
+ */
+export async function unwatchDirectory(watcherId: string): Promise<void> {
+  await invoke('unwatch_directory', { watcherId });
+}
+
+/**
+ * Watch the active project database for changes made outside the app
+ * (e.g. a sync client replacing it); replaces the previous project's watcher
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function watchProject(dbPath: string): Promise<void> {
+  await invoke('watch_project', { dbPath });
+}
+
+/**
+ * Stop watching the active project database
+ */
+export async function unwatchProject(): Promise<void> {
+  await invoke('unwatch_project');
+}
+
+/**
+ * Listen for external changes to the active project database
+ * @param callback Receives the path of the changed backlog.db; offer a reload
+ * @returns Unlisten function
+ */
+export async function listenProjectChangedExternally(
+  callback: (dbPath: string) => void
+): Promise<UnlistenFn> {
+  return listen<{ db_path: string }>('project:changed-externally', (event) =>
+    callback(event.payload.db_path)
+  );
+}
+
+/**
+ * Listen for clipboard text changes (requires an active watcher)
+ * @param callback Function called with the new clipboard text
+ * @returns Unlisten function
+ */
+export async function listenClipboardChanged(callback: (text: string) => void): Promise<UnlistenFn> {
+  return listen<string>('clipboard:changed', (event) => callback(event.payload));
+}
+
+// ============================================================
+// NATIVE DRAG
+// ============================================================
+
+/**
+ * Start a native OS drag (e.g. a ticket id to a terminal)
+ * Call from a mousedown/dragstart handler while the button is held
+ * @param data Dragged content, always offered as plain text too
+ * @param mimeType Additional MIME type for the content
+ */
+export async function startNativeDrag(data: string, mimeType = 'text/plain'): Promise<void> {
+  await invoke('start_native_drag', { data, mimeType });
+}
+
+/**
+ * Listen for the end of a native drag
+ * @param callback Called with true when dropped, false when cancelled
+ * @returns Unlisten function
+ */
+export async function listenNativeDragEnd(callback: (completed: boolean) => void): Promise<UnlistenFn> {
+  const unlistenComplete = await listen('drag:complete', () => callback(true));
+  const unlistenCancelled = await listen('drag:cancelled', () => callback(false));
+  return () => {
+    unlistenComplete();
+    unlistenCancelled();
+  };
+}
+
+// ============================================================
+// EXPORT
+// ============================================================
+
+export interface CsvExportOptions {
+  /** Columns to export, in order (default: id, type, title, section, ...) */
+  columns?: string[];
+  /** Only export items in these sections */
+  sections?: string[];
+  /** Only export items updated at or after this date (YYYY-MM-DD) */
+  updated_from?: string;
+  /** Only export items updated before this date (YYYY-MM-DD) */
+  updated_to?: string;
+  /** Prefix the file with a UTF-8 BOM for Excel */
+  bom?: boolean;
+  /** Id used to cancel the export with cancelExport() */
+  job_id?: string;
+}
+
+export interface CsvExportResult {
+  path: string;
+  rows: number;
+}
+
+/**
+ * Export a project's items to CSV from the Rust side (no webview serialization)
+ * Progress is emitted as `export:progress` with { job_id, rows }
+ * @param dbPath Path to the project's backlog.db
+ * @param destPath Output CSV path (app data dir or a user-granted folder)
+ * @param options Column selection, filters, BOM and job id
+ */
+export async function exportTicketsCsv(
+  dbPath: string,
+  destPath: string,
+  options: CsvExportOptions = {}
+): Promise<CsvExportResult> {
+  return invoke<CsvExportResult>('export_tickets_csv', { dbPath, destPath, options });
+}
+
+/**
+ * Cancel a running export started with a job_id
+ */
+export async function cancelExport(jobId: string): Promise<void> {
+  await invoke('cancel_export', { jobId });
+}
+
+export interface JsonExportResult {
+  path: string;
+  size_bytes: number;
+  compressed: boolean;
+  /** [table, rowCount] pairs */
+  tables: [string, number][];
+}
+
+/**
+ * Export a whole project database as a single JSON document
+ * Outputs over 20 MB are gzip-compressed and get a `.gz` suffix
+ * @param dbPath Path to the project's backlog.db
+ * @param destPath Output path (app data dir or a user-granted folder)
+ */
+export async function exportProjectJson(dbPath: string, destPath: string): Promise<JsonExportResult> {
+  return invoke<JsonExportResult>('export_project_json', { dbPath, destPath });
+}
+
+export interface MarkdownExportOptions {
+  /** Replace files already in the destination folder (default: keep them) */
+  overwrite?: boolean;
+  /** Id used to cancel the export with cancelExport() */
+  job_id?: string;
+}
+
+export interface MarkdownExportResult {
+  path: string;
+  /** Markdown files written, index.md included */
+  files: number;
+  /** Screenshots copied into assets/ */
+  assets: number;
+  /** Existing files left untouched, relative to path */
+  skipped: string[];
+}
+
+/**
+ * Export a project as a folder of Markdown files (one per ticket + index.md), e.g. for Obsidian
+ * Screenshots are copied into assets/; progress is emitted as `export:progress`
+ * @param dbPath Path to the project's backlog.db
+ * @param destDir Output folder (app data dir or a user-granted folder)
+ * @param options Overwrite flag and job id
+ */
+export async function exportMarkdown(
+  dbPath: string,
+  destDir: string,
+  options: MarkdownExportOptions = {}
+): Promise<MarkdownExportResult> {
+  return invoke<MarkdownExportResult>('export_markdown', { dbPath, destDir, options });
+}
+
+export interface ReportFilter {
+  /** Board sections (statuses) to include; empty or omitted = all */
+  sections?: string[];
+  types?: string[];
+  severities?: string[];
+  priorities?: string[];
+  /** Field the ticket tables are grouped by (default: section) */
+  group_by?: 'section' | 'type' | 'severity' | 'priority' | 'component' | 'module';
+}
+
+export interface ReportLayout {
+  /** Default: a4 */
+  page_size?: 'a4' | 'letter';
+  landscape?: boolean;
+  /** Title page heading (default: project folder name) */
+  title?: string;
+  /** PNG logo shown on the title page */
+  logo_path?: string;
+}
+
+export interface ReportResult {
+  path: string;
+  pages: number;
+  size_bytes: number;
+}
+
+/**
+ * Render a printable PDF report: title page with counts per group, then one ticket table per group
+ * Cells are single-line; long text is cut with an ellipsis
+ * @param dbPath Path to the project's backlog.db
+ * @param filter Tickets to include and grouping
+ * @param destPath Output PDF path (app data dir or a user-granted folder)
+ * @param layout Page size, orientation, title and logo
+ */
+export async function exportReportPdf(
+  dbPath: string,
+  filter: ReportFilter,
+  destPath: string,
+  layout: ReportLayout = {}
+): Promise<ReportResult> {
+  return invoke<ReportResult>('export_report_pdf', { dbPath, filter, destPath, layout });
+}
+
+/**
+ * Print an HTML page to PDF with a headless Chrome/Edge/Chromium/Brave or wkhtmltopdf.
+ * Scripts and network access are disabled: inline styles, data: images and fonts only.
+ * @param html Complete HTML document, at most 5 MB
+ * @param outputPath .pdf path inside the user's documents folder
+ * @returns Size of the PDF in bytes
+ */
+export async function pdfExport(html: string, outputPath: string): Promise<number> {
+  return invoke<number>('pdf_export', { html, outputPath });
+}
+
+// ============================================================
+// IMPORT
+// ============================================================
+
+export interface CsvImportMapping {
+  /** CSV header -> item field (title, description, status, type, emoji, component, module, severity, priority, effort, user_story) */
+  columns: Record<string, string>;
+  /** Type for rows without a type cell */
+  default_type?: string;
+  /** Section for rows without a status cell */
+  default_status?: string;
+}
+
+export interface CsvImportReport {
+  dry_run: boolean;
+  delimiter: string;
+  rows_ok: number;
+  /** Invalid rows, by line number in the file */
+  errors: { line: number; message: string }[];
+  /** Statuses without a matching section (one is created for each) */
+  new_statuses: string[];
+  /** Ids of created items; empty for a dry run or when any row is invalid */
+  created_ids: string[];
+}
+
+/**
+ * Create items from a CSV file in a single transaction
+ * Run with dryRun first to get the validation report without writing
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param dbPath Path to the project's backlog.db
+ * @param csvPath CSV file (`,` or `;` delimited, optional BOM)
+ * @param mapping Column to field mapping
+ * @param dryRun Validate only
+ */
+export async function importTicketsCsv(
+  dbPath: string,
+  csvPath: string,
+  mapping: CsvImportMapping,
+  dryRun: boolean
+): Promise<CsvImportReport> {
+  return invoke<CsvImportReport>('import_tickets_csv', { dbPath, csvPath, mapping, dryRun });
+}
+
+export interface TrelloImportOptions {
+  /** Leave archived cards out instead of importing them as archived items */
+  skip_archived?: boolean;
+  /** Type given to every card (default: the project's first type) */
+  item_type?: string;
+}
+
+export interface TrelloImportSummary {
+  lists: number;
+  new_sections: string[];
+  cards: number;
+  archived: number;
+  created_ids: string[];
+  skipped: { name: string; reason: string }[];
+  /** Card data with no equivalent in a project (due dates, labels) */
+  warnings: string[];
+}
+
+/**
+ * Import a Trello board JSON export in a single transaction
+ * Lists become sections, checklists become acceptance criteria,
+ * attachments are kept as links in the description
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param dbPath Path to the project's backlog.db
+ * @param jsonPath Trello export file
+ */
+export async function importTrello(
+  dbPath: string,
+  jsonPath: string,
+  options: TrelloImportOptions = {}
+): Promise<TrelloImportSummary> {
+  return invoke<TrelloImportSummary>('import_trello', { dbPath, jsonPath, options });
+}
+
+export interface JiraImportMapping {
+  /** Jira status name or category key (new, indeterminate, done) -> section title */
+  statuses?: Record<string, string>;
+  /** Jira issue type name -> item type */
+  types?: Record<string, string>;
+  /** Type for unmapped issue types (default: the project's first type) */
+  default_type?: string;
+}
+
+export interface JiraImportReport {
+  imported: { key: string; id: string }[];
+  /** Keys skipped because an earlier import already created them */
+  already_imported: string[];
+  problems: { key: string; message: string }[];
+  new_sections: string[];
+  /** Epic/parent links created as related-to relations */
+  links: number;
+}
+
+/**
+ * Import issues from a Jira JSON export in a single transaction
+ * Re-running the import skips issues whose key was already imported
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param dbPath Path to the project's backlog.db
+ * @param filePath Jira export (REST search response or array of issues)
+ * @param mapping Status and type mapping
+ */
+export async function importJira(
+  dbPath: string,
+  filePath: string,
+  mapping: JiraImportMapping = {}
+): Promise<JiraImportReport> {
+  return invoke<JiraImportReport>('import_jira', { dbPath, filePath, mapping });
+}
+
+export interface JiraCloudImportOptions {
+  /** Status and type mapping, as for file imports */
+  mapping?: JiraImportMapping;
+  /** Leave already imported issues untouched instead of updating them */
+  skip_existing?: boolean;
+  /** Count the matching issues and preview the first page without writing */
+  dry_run?: boolean;
+}
+
+export interface JiraPreview {
+  key: string;
+  title: string;
+  item_type: string;
+  /** Target section */
+  status: string | null;
+  priority: string | null;
+  component: string | null;
+  /** Ticket an earlier import created, which would be updated */
+  existing_id: string | null;
+}
+
+export interface JiraCloudImportReport {
+  dry_run: boolean;
+  /** Issues matching the JQL (an estimate for dry runs) */
+  total: number;
+  created: number;
+  updated: number;
+  /** Unchanged issues and issues listed in problems */
+  skipped: number;
+  created_ids: string[];
+  updated_ids: string[];
+  problems: { key: string; message: string }[];
+  new_sections: string[];
+  links: number;
+  rate_limit_waits: number;
+  /** Mapping of the first page of issues; dry runs only */
+  preview: JiraPreview[];
+}
+
+export interface JiraFetchProgress {
+  fetched: number;
+  /** Approximate number of matching issues, when Jira reported it */
+  total: number | null;
+}
+
+/**
+ * Store the email and API token used with a Jira Cloud site in the OS keyring
+ * @param siteUrl e.g. https://acme.atlassian.net
+ * @param token API token, or null to forget the credentials
+ */
+export async function setJiraCredentials(siteUrl: string, email: string, token: string | null): Promise<void> {
+  return invoke<void>('set_jira_credentials', { siteUrl, email, token });
+}
+
+/**
+ * Whether credentials are stored for a Jira Cloud site
+ */
+export async function hasJiraCredentials(siteUrl: string): Promise<boolean> {
+  return invoke<boolean>('has_jira_credentials', { siteUrl });
+}
+
+/**
+ * Import the issues a JQL query matches on a Jira Cloud site
+ * Re-running the import updates the tickets it created (matched by issue key)
+ * Emits `jira:fetch-progress` while downloading, then `import:progress`
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function jiraImport(
+  dbPath: string,
+  siteUrl: string,
+  jql: string,
+  options: JiraCloudImportOptions = {}
+): Promise<JiraCloudImportReport> {
+  return invoke<JiraCloudImportReport>('jira_import', { dbPath, siteUrl, jql, options });
+}
+
+/**
+ * Listen for download progress of a Jira Cloud import
+ * @returns Unlisten function
+ */
+export async function listenJiraFetchProgress(
+  callback: (progress: JiraFetchProgress) => void
+): Promise<UnlistenFn> {
+  return listen<JiraFetchProgress>('jira:fetch-progress', (event) => callback(event.payload));
+}
+
+export interface GithubImportOptions {
+  /** open, closed or all (default) */
+  state?: 'open' | 'closed' | 'all';
+  /** Only issues updated since this RFC 3339 timestamp */
+  since?: string;
+  /** Issue state (open, closed) or close reason (completed, not_planned) -> section title */
+  statuses?: Record<string, string>;
+  /** Label name -> item type; the first mapped label wins */
+  labels?: Record<string, string>;
+  /** Type for issues without a mapped label (default: the project's first type) */
+  default_type?: string;
+  /** Leave already imported issues untouched instead of updating them */
+  skip_existing?: boolean;
+}
+
+export interface GithubImportReport {
+  created: number;
+  updated: number;
+  /** Pull requests, unchanged issues and issues listed in problems */
+  skipped: number;
+  created_ids: string[];
+  updated_ids: string[];
+  problems: { number: number; message: string }[];
+  new_sections: string[];
+  /** Times the import waited for the GitHub rate limit to reset */
+  rate_limit_waits: number;
+}
+
+/**
+ * Store the GitHub personal access token in the OS keyring
+ * @param token Token, or null to forget it
+ */
+export async function setGithubToken(token: string | null): Promise<void> {
+  return invoke<void>('set_github_token', { token });
+}
+
+/**
+ * Whether a GitHub token is stored
+ */
+export async function hasGithubToken(): Promise<boolean> {
+  return invoke<boolean>('has_github_token');
+}
+
+/**
+ * Import the issues of a GitHub repository with the stored token
+ * Re-running the import updates the tickets it created (matched by issue number)
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param dbPath Path to the project's backlog.db
+ * @param repo Repository as owner/name
+ */
+export async function githubImport(
+  dbPath: string,
+  repo: string,
+  options: GithubImportOptions = {}
+): Promise<GithubImportReport> {
+  return invoke<GithubImportReport>('github_import', { dbPath, repo, options });
+}
+
+export interface GitlabImportOptions {
+  /** opened, closed or all (default) */
+  state?: 'opened' | 'closed' | 'all';
+  /** Only issues updated since this RFC 3339 timestamp */
+  since?: string;
+  /** Issue state (opened, closed) -> section title */
+  statuses?: Record<string, string>;
+  /** Label name -> item type; the first mapped label wins */
+  labels?: Record<string, string>;
+  /** Type for issues without a mapped label (default: the project's first type) */
+  default_type?: string;
+  /** Leave confidential issues out instead of importing them marked 🔒 */
+  skip_confidential?: boolean;
+  /** Leave already imported issues untouched instead of updating them */
+  skip_existing?: boolean;
+  /** Skip TLS verification (self-signed certificates of self-hosted instances) */
+  accept_invalid_certs?: boolean;
+}
+
+export interface GitlabImportReport {
+  created: number;
+  updated: number;
+  /** Unchanged issues, confidential issues left out and issues listed in problems */
+  skipped: number;
+  created_ids: string[];
+  updated_ids: string[];
+  problems: { iid: number; message: string }[];
+  new_sections: string[];
+  /** Times the import waited for the GitLab rate limit to reset */
+  rate_limit_waits: number;
+}
+
+/**
+ * Store the personal access token of a GitLab instance in the OS keyring
+ * @param baseUrl Instance URL, or null for gitlab.com
+ * @param token Token, or null to forget it
+ */
+export async function setGitlabToken(baseUrl: string | null, token: string | null): Promise<void> {
+  return invoke<void>('set_gitlab_token', { baseUrl, token });
+}
+
+/**
+ * Whether a token is stored for a GitLab instance
+ * @param baseUrl Instance URL, or null for gitlab.com
+ */
+export async function hasGitlabToken(baseUrl: string | null): Promise<boolean> {
+  return invoke<boolean>('has_gitlab_token', { baseUrl });
+}
+
+/**
+ * Import the issues of a GitLab project with the token stored for its instance
+ * Re-running the import updates the tickets it created (matched by IID)
+ * An untrusted server certificate rejects with an AppError of kind 'Certificate'
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param dbPath Path to the project's backlog.db
+ * @param baseUrl Instance URL, or null for gitlab.com
+ * @param projectId Numeric project id or group/project path
+ */
+export async function gitlabImport(
+  dbPath: string,
+  baseUrl: string | null,
+  projectId: string,
+  options: GitlabImportOptions = {}
+): Promise<GitlabImportReport> {
+  return invoke<GitlabImportReport>('gitlab_import', { dbPath, baseUrl, projectId, options });
+}
+
+export interface MergeOptions {
+  /** Digits put before every source ticket number (BUG-012 -> BUG-7012 with '7'); default: next free numbers */
+  number_prefix?: string;
+  /** Leave out the source's archived tickets */
+  skip_archived?: boolean;
+  /** Build the report without writing anything */
+  dry_run?: boolean;
+}
+
+export interface MergeReport {
+  dry_run: boolean;
+  /** Source ticket id -> id in the target */
+  id_map: Record<string, string>;
+  new_sections: string[];
+  new_types: string[];
+  relations: number;
+  screenshots_copied: number;
+  missing_screenshots: string[];
+}
+
+/**
+ * Copy every ticket of one project into another in a single transaction
+ * The source is only read; both projects must be at the same schema version
+ * Progress is emitted as `import:progress` with { rows, total }
+ * @param sourceDb backlog.db of the project to copy from
+ * @param targetDb backlog.db of the project to copy into (reload it afterwards)
+ */
+export async function mergeProjects(
+  sourceDb: string,
+  targetDb: string,
+  options: MergeOptions = {}
+): Promise<MergeReport> {
+  return invoke<MergeReport>('merge_projects', { sourceDb, targetDb, options });
+}
+
+// ============================================================
+// SEARCH
+// ============================================================
+
+export interface SearchHit {
+  id: string;
+  type: string;
+  title: string;
+  section: string | null;
+  /** Title with matches wrapped in <mark> */
+  title_highlight: string;
+  /** Best-matching excerpt, matches wrapped in <mark> */
+  snippet: string;
+  /** Set when the snippet comes from the text of this attached file */
+  attachment_filename: string | null;
+  /** bm25 score, lower is more relevant */
+  score: number;
+}
+
+export interface SearchResults {
+  total: number;
+  hits: SearchHit[];
+}
+
+/**
+ * Ranked, accent-insensitive full-text search over a project's items
+ * "Quoted phrases" match exactly, other words as prefixes; any input is safe.
+ * The text of attached .txt/.md/.log/.csv files is searched too
+ * @param dbPath Path to the project's backlog.db
+ * @param query Raw user input
+ * @param limit Page size (default 20, max 200)
+ * @param offset Number of hits to skip
+ */
+export async function searchTickets(
+  dbPath: string,
+  query: string,
+  limit?: number,
+  offset?: number
+): Promise<SearchResults> {
+  return invoke<SearchResults>('search_tickets', { dbPath, query, limit, offset });
+}
+
+export interface SimilarTicket {
+  id: string;
+  type: string;
+  title: string;
+  /** Trigram similarity from 0 to 1 */
+  score: number;
+  archived: boolean;
+}
+
+/**
+ * Tickets whose title resembles a draft title, best first (duplicate detection)
+ * Ignores case, accents and punctuation; fast enough for every typing pause
+ * @param dbPath Path to the project's backlog.db
+ * @param title Title being written
+ * @param limit Maximum matches (default 5, max 50)
+ * @param includeArchived Also match archived tickets
+ * @param withDescription Also compare the first line of descriptions
+ */
+export async function findSimilar(
+  dbPath: string,
+  title: string,
+  limit?: number,
+  includeArchived?: boolean,
+  withDescription?: boolean
+): Promise<SimilarTicket[]> {
+  return invoke<SimilarTicket[]>('find_similar', {
+    dbPath,
+    title,
+    limit,
+    includeArchived,
+    withDescription,
+  });
+}
+
+// ============================================================
+// STATS
+// ============================================================
+
+export interface StatsRange {
+  /** First day, YYYY-MM-DD (local) */
+  from: string;
+  /** Last day, inclusive, YYYY-MM-DD (local) */
+  to: string;
+  /** Minutes to add to UTC for local time: -new Date().getTimezoneOffset() */
+  tz_offset_minutes: number;
+}
+
+export interface LabelCount {
+  label: string;
+  count: number;
+}
+
+export interface DayCount {
+  day: string;
+  created: number;
+  /** Tickets archived that day */
+  closed: number;
+}
+
+export interface TicketStats {
+  total: number;
+  by_section: LabelCount[];
+  by_type: LabelCount[];
+  by_component: LabelCount[];
+  /** Days with activity only, in date order */
+  per_day: DayCount[];
+  closed_in_range: number;
+  avg_days_to_close: number | null;
+}
+
+/**
+ * Dashboard aggregates computed in SQL (a ticket is closed once archived)
+ * @param dbPath Path to the project's backlog.db
+ * @param range Day range in local time, at most 731 days
+ */
+export async function ticketStats(dbPath: string, range: StatsRange): Promise<TicketStats> {
+  return invoke<TicketStats>('ticket_stats', { dbPath, range });
+}
+
+// ============================================================
+// SPELL CHECK
+// ============================================================
+
+export interface SpellError {
+  word: string;
+  /** Index of the word in the checked string (UTF-16, like JS indices) */
+  offset: number;
+  suggestions: string[];
+}
+
+/**
+ * Spell-check text with the OS spell-checker (at most 100 errors)
+ * @param text Text to check, e.g. a ticket description
+ * @param lang BCP 47 tag from getSpellCheckLanguages (defaults to the OS language)
+ */
+export async function spellCheckText(text: string, lang?: string): Promise<SpellError[]> {
+  return invoke<SpellError[]>('spell_check_text', { text, lang: lang ?? null });
+}
+
+/**
+ * List the languages the OS spell-checker has dictionaries for (`fr-FR`, `en`)
+ */
+export async function getSpellCheckLanguages(): Promise<string[]> {
+  return invoke<string[]>('get_spell_check_languages');
+}
+
+// ============================================================
+// APP LOCK
+// ============================================================
+
+/**
+ * Lock the app: every window hides its content behind the lock screen
+ * @param pin PIN required by appUnlock (4-64 characters); omit for a lock without PIN
+ * @throws Unauthorized if the app is already locked
+ */
+export async function appLock(pin?: string): Promise<void> {
+  const passwordHash = pin === undefined ? null : await invoke<string>('hash_lock_pin', { pin });
+  await invoke('app_lock', { passwordHash });
+}
+
+/**
+ * Unlock the app
+ * @returns false for a wrong PIN; rejects with Unauthorized after 5 wrong PINs in a row (for 30s)
+ */
+export async function appUnlock(pin?: string): Promise<boolean> {
+  return invoke<boolean>('app_unlock', { pin: pin ?? null });
+}
+
+/**
+ * Whether the app is locked (also across restarts)
+ */
+export async function isAppLocked(): Promise<boolean> {
+  return invoke<boolean>('is_app_locked');
+}
+
+/**
+ * Listen for the app being locked
+ * @returns Unlisten function
+ */
+export async function listenAppLocked(callback: () => void): Promise<UnlistenFn> {
+  return listen('app:locked', () => callback());
+}
+
+/**
+ * Listen for the app being unlocked
+ * @returns Unlisten function
+ */
+export async function listenAppUnlocked(callback: () => void): Promise<UnlistenFn> {
+  return listen('app:unlocked', () => callback());
+}
+
+// ============================================================
+// LOCALE
+// ============================================================
+
+/**
+ * OS locale as a language[-REGION] tag (`fr-FR`, `en`); `en` when unset
+ */
+export async function getSystemLocale(): Promise<string> {
+  return invoke<string>('get_system_locale');
+}
+
+/**
+ * Start emitting locale:changed when the OS locale changes (checked every 30s).
+ * Safe to call more than once.
+ */
+export async function watchLocaleChange(): Promise<void> {
+  await invoke('watch_locale_change');
+}
+
+/**
+ * Listen for OS locale changes (after watchLocaleChange)
+ * @returns Unlisten function
+ */
+export async function listenLocaleChanged(callback: (locale: string) => void): Promise<UnlistenFn> {
+  return listen<string>('locale:changed', (event) => callback(event.payload));
+}
+
+// ============================================================
+// RECURRING TICKETS
+// ============================================================
+
+/**
+ * Handling of occurrences missed while the app was closed:
+ * every one (capped at 50), only the latest, or none
+ */
+export type RecurrenceCatchUp = 'all' | 'latest' | 'skip';
+
+export interface RecurrenceInput {
+  type: string;
+  title: string;
+  /** Target section title; the first section when omitted */
+  section?: string;
+  emoji?: string;
+  component?: string;
+  module?: string;
+  /** Code (`P1`) or label (`P1 - Critique`) */
+  severity?: string;
+  priority?: string;
+  /** Code (`M`) or label (`M (Medium)`) */
+  effort?: string;
+  description?: string;
+  userStory?: string;
+  /**
+   * RRULE subset: FREQ=DAILY|WEEKLY|MONTHLY with optional INTERVAL, BYDAY,
+   * BYMONTHDAY, BYHOUR and BYMINUTE, in local time (e.g. `FREQ=WEEKLY;BYDAY=MO;BYHOUR=9`)
+   */
+  rule: string;
+  /** Defaults to 'latest' */
+  catchUp?: RecurrenceCatchUp;
+  /** First possible occurrence in Unix ms; now when omitted */
+  startsAt?: number;
+}
+
+export interface Recurrence {
+  id: number;
+  type: string;
+  title: string;
+  section: string | null;
+  emoji: string | null;
+  component: string | null;
+  module: string | null;
+  severity: string | null;
+  priority: string | null;
+  effort: string | null;
+  description: string | null;
+  user_story: string | null;
+  rule: string;
+  catch_up: RecurrenceCatchUp;
+  /** Unix ms */
+  starts_at: number;
+  /** Unix ms */
+  next_run: number;
+}
+
+export interface RecurrenceCreated {
+  db_path: string;
+  recurrence_id: number;
+  ticket_ids: string[];
+}
+
+/**
+ * Add a recurring ticket; the backend creates its tickets while the project is open
+ * @param dbPath Path to the project's backlog.db
+ * @param recurrence Template ticket and schedule
+ */
+export async function createRecurrence(
+  dbPath: string,
+  recurrence: RecurrenceInput
+): Promise<Recurrence> {
+  return invoke<Recurrence>('create_recurrence', { dbPath, recurrence });
+}
+
+/**
+ * Recurring tickets of a project, soonest first
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function listRecurrences(dbPath: string): Promise<Recurrence[]> {
+  return invoke<Recurrence[]>('list_recurrences', { dbPath });
+}
+
+/**
+ * Delete a recurring ticket (tickets already created are kept)
+ * @param dbPath Path to the project's backlog.db
+ * @param recurrenceId Recurrence id
+ */
+export async function deleteRecurrence(dbPath: string, recurrenceId: number): Promise<void> {
+  await invoke('delete_recurrence', { dbPath, recurrenceId });
+}
+
+/**
+ * Listen for tickets created by a recurrence; reload the project when received
+ * @returns Unlisten function
+ */
+export async function listenRecurrenceCreated(
+  callback: (created: RecurrenceCreated) => void
+): Promise<UnlistenFn> {
+  return listen<RecurrenceCreated>('recurrence:created', (event) => callback(event.payload));
+}
+
+// ============================================================
+// TIME TRACKING
+// ============================================================
+
+export interface TimerStatus {
+  db_path: string;
+  ticket_id: string;
+  /** Unix ms */
+  started_at: number;
+  /** Running time so far, pauses excluded */
+  elapsed_ms: number;
+  /** Paused while the app is locked */
+  paused: boolean;
+}
+
+export interface TimeEntry {
+  id: number;
+  item_id: string;
+  /** Unix ms */
+  started_at: number;
+  /** Unix ms */
+  ended_at: number;
+  /** Tracked time, pauses excluded */
+  duration_ms: number;
+  paused_ms: number;
+}
+
+export interface TimeEntries {
+  /** Newest first */
+  entries: TimeEntry[];
+  /** Oldest first; `date` is the local YYYY-MM-DD each entry started */
+  daily: { date: string; duration_ms: number }[];
+  total_ms: number;
+}
+
+/**
+ * Start timing a ticket; a running timer is stopped and recorded first
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id (e.g. BUG-001)
+ */
+export async function timerStart(dbPath: string, ticketId: string): Promise<TimerStatus> {
+  return invoke<TimerStatus>('timer_start', { dbPath, ticketId });
+}
+
+/**
+ * Stop the running timer and record the interval
+ * @returns The recorded entry, or null when no timer was running
+ */
+export async function timerStop(): Promise<TimeEntry | null> {
+  return invoke<TimeEntry | null>('timer_stop');
+}
+
+/**
+ * The running timer, if any (kept by the backend across reloads and restarts)
+ */
+export async function timerStatus(): Promise<TimerStatus | null> {
+  return invoke<TimerStatus | null>('timer_status');
+}
+
+/**
+ * Recorded time of a ticket with daily totals
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id
+ */
+export async function listTimeEntries(dbPath: string, ticketId: string): Promise<TimeEntries> {
+  return invoke<TimeEntries>('list_time_entries', { dbPath, ticketId });
+}
+
+/**
+ * Listen for the timer starting, stopping, pausing or resuming
+ * @param callback Receives the new status, or null once stopped
+ * @returns Unlisten function
+ */
+export async function listenTimerChanged(
+  callback: (status: TimerStatus | null) => void
+): Promise<UnlistenFn> {
+  return listen<TimerStatus | null>('timer:changed', (event) => callback(event.payload));
+}
+
+// ============================================================
+// ATTACHMENTS
+// ============================================================
+
+export interface Attachment {
+  id: number;
+  item_id: string;
+  filename: string;
+  mime_type: string;
+  size_bytes: number;
+  /** Hex sha256 of the content; identical files share storage */
+  sha256: string;
+  created_at: string | null;
+}
+
+/**
+ * Attach a file to a ticket, from a path or from bytes
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id (e.g. BUG-001)
+ * @param source Path of the file to copy, or its bytes with a file name
+ * @throws QuotaExceeded when the project's attachments would exceed 1 GB
+ */
+export async function saveAttachment(
+  dbPath: string,
+  ticketId: string,
+  source: { path: string } | { bytes: Uint8Array; filename: string }
+): Promise<Attachment> {
+  return invoke<Attachment>('save_attachment', {
+    dbPath,
+    ticketId,
+    ...('path' in source
+      ? { sourcePath: source.path }
+      : { bytes: Array.from(source.bytes), filename: source.filename }),
+  });
+}
+
+export type ClipboardAttachment =
+  | { kind: 'image'; attachment: Attachment }
+  | { kind: 'files'; attachments: Attachment[] }
+  | { kind: 'no_image' };
+
+/**
+ * Attach the clipboard to a ticket: copied files, else the image as PNG
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id (e.g. BUG-001)
+ * @returns kind 'no_image' when the clipboard holds nothing to attach
+ */
+export async function saveClipboardImage(
+  dbPath: string,
+  ticketId: string
+): Promise<ClipboardAttachment> {
+  return invoke<ClipboardAttachment>('save_clipboard_image', { dbPath, ticketId });
+}
+
+export interface AttachmentsAdded {
+  db_path: string;
+  ticket_id: string;
+  attachments: Attachment[];
+  /** Dropped files that were refused or failed, with the reason */
+  rejected: { path: string; error: string }[];
+}
+
+/**
+ * Attach files dropped on the window to this ticket from now on; the
+ * backend validates and stores them and emits `attachments:added`
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id (e.g. BUG-001)
+ */
+export async function setDropTarget(dbPath: string, ticketId: string): Promise<void> {
+  return invoke<void>('set_drop_target', { dbPath, ticketId });
+}
+
+/**
+ * Stop attaching dropped files (e.g. when the ticket is closed)
+ */
+export async function clearDropTarget(): Promise<void> {
+  return invoke<void>('clear_drop_target');
+}
+
+/**
+ * Extensions refused when files are dropped (default: executables and scripts)
+ */
+export async function getDropDenylist(): Promise<string[]> {
+  return invoke<string[]>('get_drop_denylist');
+}
+
+/**
+ * Replace the extensions refused when files are dropped
+ * @param extensions e.g. ["exe", ".bat"]
+ * @returns The saved list, normalized
+ */
+export async function setDropDenylist(extensions: string[]): Promise<string[]> {
+  return invoke<string[]>('set_drop_denylist', { extensions });
+}
+
+/**
+ * Listen for dropped files being attached
+ * @returns Unlisten function
+ */
+export async function listenAttachmentsAdded(
+  callback: (added: AttachmentsAdded) => void
+): Promise<UnlistenFn> {
+  return listen<AttachmentsAdded>('attachments:added', (event) => callback(event.payload));
+}
+
+/**
+ * Attachments of a ticket, oldest first
+ * @param dbPath Path to the project's backlog.db
+ * @param ticketId Ticket id
+ */
+export async function listAttachments(dbPath: string, ticketId: string): Promise<Attachment[]> {
+  return invoke<Attachment[]>('list_attachments', { dbPath, ticketId });
+}
+
+/**
+ * Content of an attachment
+ * @param dbPath Path to the project's backlog.db
+ * @param attachmentId Attachment id
+ */
+export async function readAttachment(dbPath: string, attachmentId: number): Promise<Uint8Array> {
+  const buffer = await invoke<ArrayBuffer>('read_attachment', { dbPath, attachmentId });
+  return new Uint8Array(buffer);
+}
+
+/**
+ * Delete an attachment; its file goes once no other attachment shares it
+ * @param dbPath Path to the project's backlog.db
+ * @param attachmentId Attachment id
+ */
+export async function deleteAttachment(dbPath: string, attachmentId: number): Promise<void> {
+  return invoke<void>('delete_attachment', { dbPath, attachmentId });
+}
+
+export interface AttachmentStorageStats {
+  /** Size of every file in the project's attachment folder */
+  total_bytes: number;
+  /** Largest first; ticket_exists is false once the ticket was deleted */
+  tickets: { item_id: string; attachments: number; bytes: number; ticket_exists: boolean }[];
+  /** Files no attachment of the project or its archive refers to */
+  orphaned_blobs: number;
+  orphaned_bytes: number;
+}
+
+/**
+ * Disk usage of a project's attachments
+ * @param dbPath Path to the project's backlog.db
+ */
+export async function attachmentStorageStats(dbPath: string): Promise<AttachmentStorageStats> {
+  return invoke<AttachmentStorageStats>('attachment_storage_stats', { dbPath });
+}
+
+/**
+ * Delete attachment files no longer referenced by the project or its archive
+ * @param dbPath Path to the project's backlog.db
+ * @param dryRun Only report what would be reclaimed
+ * @returns Bytes reclaimed (or that would be)
+ */
+export async function cleanupOrphanedAttachments(dbPath: string, dryRun = false): Promise<number> {
+  return invoke<number>('cleanup_orphaned_attachments', { dbPath, dryRun });
+}
+
+// ============================================================
+// BACKGROUND JOBS
+// ============================================================
+
+export type BackgroundJobKind = 'fts_rebuild' | 'vacuum' | 'export_json' | 'export_csv';
+
+export interface JobStatus {
+  id: string;
+  kind: BackgroundJobKind;
+  status: 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+  /** Return value of the underlying command once completed */
+  result: unknown;
+  error: string | null;
+}
+
+export interface JobProgress {
+  job_id: string;
+  /** From 0 to 1 */
+  progress: number;
+  step: string;
+}
+
+/**
+ * Queue heavy work on the backend's job worker thread
+ * @param kind Job to run
+ * @param params { db_path } for fts_rebuild and vacuum; { db_path, dest_path }
+ *   for export_json; { db_path, dest_path, options } for export_csv
+ * @returns Job id
+ */
+export async function spawnBackgroundJob(
+  kind: BackgroundJobKind,
+  params: Record<string, unknown>
+): Promise<string> {
+  return invoke<string>('spawn_background_job', { kind, params });
+}
+
+/**
+ * Cancel a queued job, or a running export_csv job
+ * @param jobId Id returned by spawnBackgroundJob
+ */
+export async function cancelBackgroundJob(jobId: string): Promise<void> {
+  return invoke<void>('cancel_background_job', { jobId });
+}
+
+/**
+ * Queued, running and recently finished jobs, oldest first
+ */
+export async function listBackgroundJobs(): Promise<JobStatus[]> {
+  return invoke<JobStatus[]>('list_background_jobs');
+}
+
+/**
+ * Current status of one job
+ * @param jobId Id returned by spawnBackgroundJob
+ */
+export async function getBackgroundJob(jobId: string): Promise<JobStatus> {
+  return invoke<JobStatus>('get_background_job', { jobId });
+}
+
+/**
+ * Listen for progress of background jobs
+ * @returns Unlisten function
+ */
+export async function listenJobProgress(
+  callback: (progress: JobProgress) => void
+): Promise<UnlistenFn> {
+  return listen<JobProgress>('job:progress', (event) => callback(event.payload));
+}
+
+/**
+ * Listen for background jobs finishing, cancelled ones included
+ * @returns Unlisten function
+ */
+export async function listenJobComplete(
+  callback: (status: JobStatus) => void
+): Promise<UnlistenFn> {
+  return listen<JobStatus>('job:complete', (event) => callback(event.payload));
+}
+
+// ============================================================
+// SHELL
+// ============================================================
+
+export interface ShellResult {
+  stdout: string;
+  stderr: string;
+  /** -1 when the program was killed by a signal */
+  exit_code: number;
+}
+
+/**
+ * Run git, grep or find and capture its output. No shell is involved;
+ * other programs are refused (Unauthorized), as are arguments with shell
+ * metacharacters, options such as `find -exec`, and git subcommands or
+ * options outside the backend allowlist (Validation)
+ * @param command "git", "grep" or "find"
+ * @param args Arguments passed as-is
+ * @param timeoutMs Kill the program after this long (max 30000)
+ */
+export async function runShellCommandSafe(
+  command: 'git' | 'grep' | 'find',
+  args: string[],
+  timeoutMs = 10000
+): Promise<ShellResult> {
+  return invoke<ShellResult>('run_shell_command_safe', { command, args, timeoutMs });
+}
+
+export interface StreamEnded {
+  process_id: string;
+  /** null when the process was ended by a signal */
+  exit_code: number | null;
+  killed: boolean;
+}
+
+export interface ShellStream {
+  processId: string;
+  /** Resolves once the process has exited and all its lines were delivered */
+  done: Promise<StreamEnded>;
+  /** Send SIGTERM (TerminateProcess on Windows) */
+  kill: () => Promise<void>;
+}
+
+let shellStreamCounter = 0;
+
+/**
+ * Run git, grep or find in the background and receive its stdout line by
+ * line, with the same checks as runShellCommandSafe. At most 3 streams run
+ * at once (InUse otherwise)
+ * @param command "git", "grep" or "find"
+ * @param args Arguments passed as-is
+ * @param onLine Called with each stdout line, without its line ending
+ */
+export async function streamShellOutput(
+  command: 'git' | 'grep' | 'find',
+  args: string[],
+  onLine: (line: string) => void
+): Promise<ShellStream> {
+  const eventName = `shell-stream-${Date.now()}-${++shellStreamCounter}`;
+  let processId: string | null = null;
+  const ended = new Map<string, StreamEnded>();
+  let resolveDone: (ended: StreamEnded) => void = () => {};
+  const done = new Promise<StreamEnded>((resolve) => {
+    resolveDone = resolve;
+  });
+
+  const unlistenLine = await listen<string>(eventName, (event) => onLine(event.payload));
+  // The end event can arrive before invoke returns the process id
+  const unlistenEnd = await listen<StreamEnded>('shell:stream-ended', (event) => {
+    ended.set(event.payload.process_id, event.payload);
+    if (processId !== null) finish();
+  });
+  const finish = () => {
+    const payload = processId !== null ? ended.get(processId) : undefined;
+    if (!payload) return;
+    unlistenLine();
+    unlistenEnd();
+    resolveDone(payload);
+  };
+
+  try {
+    processId = await invoke<string>('stream_shell_output', { command, args, eventName });
+  } catch (error) {
+    unlistenLine();
+    unlistenEnd();
+    throw error;
+  }
+  finish();
+  const id = processId;
+  return {
+    processId: id,
+    done,
+    kill: () => killStreamedProcess(id),
+  };
+}
+
+/**
+ * Stop a process started by streamShellOutput
+ * @param processId Id returned by the backend
+ */
+export async function killStreamedProcess(processId: string): Promise<void> {
+  return invoke<void>('kill_streamed_process', { processId });
+}
+
+// ============================================================
+// SETTINGS EXPORT / IMPORT
+// ============================================================
+
+export interface SettingsExport {
+  path: string;
+  /** Keys of the exported settings */
+  keys: string[];
+  /** Secrets left out of the file, by name (e.g. app_lock_pin) */
+  secrets: string[];
+}
+
+export interface SettingChange {
+  key: string;
+  current: unknown;
+  imported: unknown;
+}
+
+export interface SettingsImport {
+  /** Settings whose imported value differs from the current one */
+  changes: SettingChange[];
+  /** Skipped entries, e.g. settings unknown to this version */
+  warnings: string[];
+  /** Secrets set on the exporting machine, to enter again here */
+  secrets: string[];
+  /** False for a dry run */
+  applied: boolean;
+}
+
+/**
+ * Write the backend settings (backup schedule, close behavior, proxy,
+ * allowlists) to a JSON file for another machine. Secrets are not included
+ * @param destPath Target .json file
+ */
+export async function exportSettings(destPath: string): Promise<SettingsExport> {
+  return invoke<SettingsExport>('export_settings', { destPath });
+}
+
+/**
+ * Import a settings file. Run with dryRun first to show what would change
+ * @param path File written by exportSettings
+ * @param dryRun Only report the changes
+ */
+export async function importSettings(path: string, dryRun: boolean): Promise<SettingsImport> {
+  return invoke<SettingsImport>('import_settings', { path, dryRun });
+}
+
+// ============================================================
+// PROJECT SETTINGS
+// ============================================================
+
+export interface ProjectSettingChanged {
+  db_path: string;
+  key: string;
+  /** New value, null when removed */
+  value: unknown;
+}
+
+/**
+ * Value of a project setting, null when not set
+ * @param dbPath Path to the project's backlog.db
+ * @param key e.g. default_assignee, ticket_prefix, column_order
+ */
+export async function projectGetSetting<T = unknown>(dbPath: string, key: string): Promise<T | null> {
+  return invoke<T | null>('project_get_setting', { dbPath, key });
+}
+
+/**
+ * Set a project setting (null removes it). default_assignee, ticket_prefix
+ * and column_order are validated; other keys take any JSON
+ * @returns The stored, normalized value
+ */
+export async function projectSetSetting<T = unknown>(dbPath: string, key: string, value: T | null): Promise<T | null> {
+  return invoke<T | null>('project_set_setting', { dbPath, key, value });
+}
+
+/**
+ * Every setting of the project, by key
+ */
+export async function projectSettingsAll(dbPath: string): Promise<Record<string, unknown>> {
+  return invoke<Record<string, unknown>>('project_settings_all', { dbPath });
+}
+
+/**
+ * Listen for project settings changed from any window
+ * @returns Unlisten function
+ */
+export async function listenProjectSettingsChanged(
+  callback: (change: ProjectSettingChanged) => void
+): Promise<UnlistenFn> {
+  return listen<ProjectSettingChanged>('project-settings:changed', (event) => callback(event.payload));
+}
+
+// ============================================================
+// WEBHOOKS
+// ============================================================
+
+export interface Webhook {
+  id: number;
+  url: string;
+  /** Event types sent to the endpoint; empty for every event */
+  events: string[];
+  enabled: boolean;
+  /** Unix milliseconds */
+  created_at: number;
+}
+
+export interface WebhookInput {
+  url: string;
+  /** HMAC-SHA256 signing key. Required on creation; omit on update to keep it */
+  secret?: string | null;
+  events?: string[];
+  enabled: boolean;
+}
+
+export interface WebhookDelivery {
+  id: number;
+  webhook_id: number;
+  event_type: string;
+  /** JSON body as signed and sent */
+  body: string;
+  status: 'pending' | 'delivered' | 'failed';
+  retry_count: number;
+  /** Next attempt of a pending delivery, Unix milliseconds */
+  next_attempt_at: number;
+  /** HTTP status of the last attempt, null when the request failed */
+  response_status: number | null;
+  last_error: string | null;
+  created_at: number;
+  updated_at: number;
+}
+
+/**
+ * Every webhook, oldest first. Secrets are never returned
+ */
+export async function listWebhooks(): Promise<Webhook[]> {
+  return invoke<Webhook[]>('list_webhooks');
+}
+
+/**
+ * Add a webhook. Bodies are signed in the X-Ticketflow-Signature header
+ * as sha256=<hex HMAC-SHA256 of the body>
+ */
+export async function createWebhook(webhook: WebhookInput): Promise<Webhook> {
+  return invoke<Webhook>('create_webhook', { webhook });
+}
+
+export async function updateWebhook(id: number, webhook: WebhookInput): Promise<Webhook> {
+  return invoke<Webhook>('update_webhook', { id, webhook });
+}
+
+/**
+ * Remove a webhook and its deliveries
+ */
+export async function deleteWebhook(id: number): Promise<void> {
+  return invoke<void>('delete_webhook', { id });
+}
+
+/**
+ * Queue an event for every enabled webhook listening to it. Failed
+ * deliveries are retried with backoff in the background
+ * @param eventType Dotted lowercase name, e.g. ticket.status_changed
+ * @returns Ids of the queued deliveries
+ */
+export async function fireWebhook(eventType: string, payload: unknown): Promise<number[]> {
+  return invoke<number[]>('fire_webhook', { eventType, payload });
+}
+
+/**
+ * Pending and recent deliveries of a webhook, newest first
+ */
+export async function listWebhookDeliveries(webhookId: number): Promise<WebhookDelivery[]> {
+  return invoke<WebhookDelivery[]>('list_webhook_deliveries', { webhookId });
+}
+
+// ============================================================
+// SLACK NOTIFICATIONS
+// ============================================================
+
+export interface SlackRule {
+  /** e.g. ticket.blocked */
+  event_type: string;
+  enabled: boolean;
+  /** Slack mrkdwn with {id}, {title}, {status}, {assignee} and {project} placeholders */
+  template: string;
+}
+
+export interface SlackConfig {
+  /** Whether a webhook URL is stored (the URL is never returned) */
+  configured: boolean;
+  rules: SlackRule[];
+}
+
+export interface SlackTicketEvent {
+  event_type: string;
+  project_name: string;
+  /** Used for the ticketflow:// link */
+  project_path: string;
+  ticket_id: string;
+  title: string;
+  status?: string | null;
+  assignee?: string | null;
+}
+
+/**
+ * Save the Slack incoming-webhook URL (kept in the OS keyring) and the rules
+ * @param webhookUrl null keeps the stored URL, '' removes it
+ */
+export async function slackConfigure(webhookUrl: string | null, rules: SlackRule[]): Promise<SlackConfig> {
+  return invoke<SlackConfig>('slack_configure', { webhookUrl, rules });
+}
+
+export async function slackGetConfig(): Promise<SlackConfig> {
+  return invoke<SlackConfig>('slack_get_config');
+}
+
+/**
+ * Report a ticket change; posted when an enabled rule matches its type.
+ * Messages Slack cannot take now are queued and retried
+ * @returns Whether a message was sent or queued
+ */
+export async function slackNotify(event: SlackTicketEvent): Promise<boolean> {
+  return invoke<boolean>('slack_notify', { event });
+}
+
+/**
+ * Send a sample message to check the setup. Rejects with Slack's reason
+ */
+export async function slackTest(): Promise<void> {
+  return invoke<void>('slack_test');
+}
+
+// ============================================================
+// EMAIL (SMTP)
+// ============================================================
+
+export type SmtpTlsMode = 'starttls' | 'implicit' | 'none';
+
+export interface SmtpSettings {
+  host: string;
+  port: number;
+  /** starttls: usually port 587, implicit: usually 465, none: local relays only */
+  tls: SmtpTlsMode;
+  /** 'address' or 'Name <address>' */
+  from: string;
+  username?: string | null;
+}
+
+export interface SmtpConfigInput extends SmtpSettings {
+  /** null keeps the stored password, '' removes it */
+  password?: string | null;
+}
+
+export interface SmtpConfig {
+  /** null until configured */
+  settings: SmtpSettings | null;
+  /** Whether a password is stored (it is never returned) */
+  password_set: boolean;
+}
+
+export interface EmailTicketOptions {
+  /** Markdown written above the ticket */
+  note?: string | null;
+  /** Attach the ticket's attachments and screenshots, up to 15 MB in total */
+  include_attachments?: boolean;
+}
+
+export interface EmailSendResult {
+  message_id: string;
+  attachments: number;
+  /** Files left out: missing, or past the size cap */
+  omitted: string[];
+}
+
+/**
+ * Save the SMTP server settings; the password is kept in the OS keyring
+ */
+export async function emailConfigure(smtp: SmtpConfigInput): Promise<SmtpConfig> {
+  return invoke<SmtpConfig>('email_configure', { smtp });
+}
+
+export async function emailGetConfig(): Promise<SmtpConfig> {
+  return invoke<SmtpConfig>('email_get_config');
+}
+
+/**
+ * Email a ticket (HTML with a plain-text alternative).
+ * Rejects with Unauthorized (credentials), Validation (recipient rejected),
+ * Network, Timeout or Certificate (server unreachable)
+ */
+export async function emailSendTicket(
+  dbPath: string,
+  ticketId: string,
+  to: string[],
+  options: EmailTicketOptions = {}
+): Promise<EmailSendResult> {
+  return invoke<EmailSendResult>('email_send_ticket', { dbPath, ticketId, to, options });
+}
+
+/**
+ * Connect and log in with the saved settings; nothing is sent
+ */
+export async function emailTest(): Promise<void> {
+  return invoke<void>('email_test');
+}
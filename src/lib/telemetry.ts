@@ -13,7 +13,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { isTauri } from './tauri-bridge';
+import { isTauri, setTelemetryOptOut } from './tauri-bridge';
 import { APP_VERSION } from './version';
 
 // ============================================================
@@ -71,11 +71,15 @@ export function getConsentState(): ConsentState {
 }
 
 /**
- * Persist the user's consent decision to localStorage.
+ * Persist the user's consent decision to localStorage, and mirror it to the
+ * Rust backend so queued and backend-reported events follow it too.
  * Calling this with 'declined' immediately stops all future track() calls.
  */
 export function setConsentState(state: 'granted' | 'declined'): void {
   localStorage.setItem(CONSENT_KEY, state);
+  if (isTauri()) {
+    setTelemetryOptOut(state === 'declined').catch(console.warn);
+  }
 }
 
 // ============================================================
@@ -0,0 +1,58 @@
+/**
+ * Error shape returned by Ticketflow's Rust commands.
+ *
+ * Mirrors `AppError` in src-tauri/src/error.rs, which serializes as
+ * `{ kind, message }`. Branch on `kind` to tell failure classes apart.
+ *
+ * @module types/appError
+ */
+
+export type AppErrorKind =
+  | 'Database'
+  | 'Network'
+  | 'Validation'
+  | 'Io'
+  | 'Unauthorized'
+  | 'Timeout'
+  | 'InUse'
+  | 'QuotaExceeded'
+  | 'ReadOnly'
+  | 'Certificate';
+
+export type AppError =
+  | { kind: 'Database'; message: string }
+  | { kind: 'Network'; message: string }
+  | { kind: 'Validation'; message: string }
+  | { kind: 'Io'; message: string }
+  | { kind: 'Unauthorized'; message: string }
+  | { kind: 'Timeout'; message: string }
+  | { kind: 'InUse'; message: string }
+  | { kind: 'QuotaExceeded'; message: string }
+  | { kind: 'ReadOnly'; message: string }
+  | { kind: 'Certificate'; message: string };
+
+const APP_ERROR_KINDS: readonly AppErrorKind[] = [
+  'Database',
+  'Network',
+  'Validation',
+  'Io',
+  'Unauthorized',
+  'Timeout',
+  'InUse',
+  'QuotaExceeded',
+  'ReadOnly',
+  'Certificate',
+];
+
+/**
+ * Check if a value rejected by `invoke()` is a structured AppError
+ */
+export function isAppError(value: unknown): value is AppError {
+  if (typeof value !== 'object' || value === null) return false;
+  const { kind, message } = value as Record<string, unknown>;
+  return (
+    typeof kind === 'string' &&
+    (APP_ERROR_KINDS as readonly string[]).includes(kind) &&
+    typeof message === 'string'
+  );
+}
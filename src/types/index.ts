@@ -71,3 +71,7 @@ export {
 
 // AI Provider Registry Types
 export type { ProviderConfig, BuiltInProviderId, CustomProviderInput } from './aiProvider';
+
+// Rust command errors
+export type { AppError, AppErrorKind } from './appError';
+export { isAppError } from './appError';